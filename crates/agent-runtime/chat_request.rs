@@ -1,20 +1,27 @@
 use crate::context_builder;
 use crate::errors::CustomError;
 use crate::jwt::Jwt;
-use crate::moderation::{moderate_chat, strip_tool_data, ModerationVerdict};
+use crate::moderation::{
+    is_blocked, moderate_chat, strip_tool_data, GuardModelCheck, ModerationVerdict, OutputGuard,
+    TeamPolicy,
+};
 use crate::user_config::UserConfig;
-use db::{queries, ChatRole, ChatStatus, Pool};
+use db::{
+    queries, ChatRole, ChatStatus, ModelEndpoint, ModerationDirection, OutputModeration, Pool,
+    Transaction,
+};
 use rig::completion::{CompletionRequest, Message as RigMessage};
 use rig::OneOrMany;
 use tool_runtime::{get_chat_tool_definitions, ToolDefinition};
 
 pub(crate) struct RigChatRequest {
     pub(crate) model_name: String,
-    pub(crate) base_url: String,
-    pub(crate) api_key: Option<String>,
+    pub(crate) endpoints: Vec<ModelEndpoint>,
     pub(crate) completion: CompletionRequest,
     pub(crate) model_id: i32,
     pub(crate) user_id: i32,
+    /// Set when the team moderates assistant responses.
+    pub(crate) output_guard: Option<Box<dyn OutputGuard>>,
 }
 
 /// Builds the model request payload and marks the chat as in-progress.
@@ -33,6 +40,11 @@ pub(crate) async fn create_request(
         .one()
         .await?;
 
+    let endpoints = queries::model_endpoints::model_endpoints()
+        .bind(&transaction, &model.id)
+        .all()
+        .await?;
+
     let capabilities = queries::capabilities::get_model_capabilities()
         .bind(&transaction, &model.id)
         .all()
@@ -117,50 +129,57 @@ pub(crate) async fn create_request(
         None
     };
 
-    if capabilities
+    let is_guarded = capabilities
         .iter()
-        .any(|c| c.capability == db::ModelCapability::Guarded)
-    {
-        let guard_model = queries::models::models()
-            .bind(&transaction, &db::ModelType::Guard)
-            .one()
-            .await?;
+        .any(|c| c.capability == db::ModelCapability::Guarded);
+
+    let output_moderation = queries::moderation_policies::output_moderation()
+        .bind(&transaction, &conversation.team_id)
+        .one()
+        .await?;
+
+    let mut output_guard: Option<Box<dyn OutputGuard>> = None;
 
+    if is_guarded || output_moderation != OutputModeration::Off {
+        let guard_model = queries::models::guard_model()
+            .bind(&transaction)
+            .opt()
+            .await?;
+        let policy = TeamPolicy::load(&transaction, conversation.team_id).await?;
         let sanitized = strip_tool_data(&messages);
-        match moderate_chat(
-            &guard_model.base_url,
-            guard_model.api_key.as_deref(),
-            &guard_model.name,
-            sanitized,
-        )
-        .await
-        {
-            Ok(ModerationVerdict::Safe) => {}
-            Ok(ModerationVerdict::Unsafe(code)) => {
-                queries::chats::new_chat()
-                    .bind(
-                        &transaction,
-                        &conversation.id,
-                        &chat.prompt_id,
-                        &None::<String>,
-                        &None::<String>,
-                        &"Your question violated our guidelines",
-                        &ChatRole::Assistant,
-                        &ChatStatus::Error,
-                    )
-                    .one()
-                    .await?;
-                queries::prompt_flags::insert_prompt_flag()
-                    .bind(&transaction, &chat_id, &code)
-                    .await?;
-                transaction.commit().await?;
-                return Err(CustomError::FaultySetup("Moderation failed".into()));
+
+        match guard_model {
+            Some(guard_model) => {
+                if is_guarded {
+                    let verdict = moderate_chat(&guard_model, sanitized.clone()).await;
+                    if let Some(err) =
+                        check_input(&transaction, &verdict, &policy, &conversation, &chat).await?
+                    {
+                        transaction.commit().await?;
+                        return Err(err);
+                    }
+                }
+
+                if output_moderation != OutputModeration::Off {
+                    output_guard = Some(Box::new(GuardModelCheck::new(
+                        guard_model,
+                        policy,
+                        output_moderation == OutputModeration::Streaming,
+                        sanitized,
+                    )));
+                }
             }
-            Err(status) => {
+            None if is_guarded => {
                 transaction.commit().await?;
-                return Err(CustomError::FaultySetup(format!(
-                    "Moderation failed: {status}"
-                )));
+                return Err(CustomError::FaultySetup(
+                    "Moderation failed: no guard model configured".into(),
+                ));
+            }
+            None => {
+                tracing::warn!(
+                    "Output moderation is on for team {} but no guard model is configured",
+                    conversation.team_id
+                );
             }
         }
     }
@@ -183,10 +202,61 @@ pub(crate) async fn create_request(
 
     Ok(RigChatRequest {
         model_name: model.name,
-        base_url: model.base_url,
-        api_key: model.api_key,
+        endpoints,
         completion,
         model_id: model.id,
         user_id: conversation.user_id,
+        output_guard,
     })
 }
+
+/// Records any categories the guard raised on the user's message. Returns an error when the
+/// team's policy blocks the message, after leaving an explanation in the conversation.
+async fn check_input(
+    transaction: &Transaction<'_>,
+    verdict: &Result<ModerationVerdict, reqwest::StatusCode>,
+    policy: &TeamPolicy,
+    conversation: &db::Conversation,
+    chat: &db::Chat,
+) -> Result<Option<CustomError>, CustomError> {
+    let flags = match verdict {
+        Ok(verdict) => policy.apply(verdict),
+        Err(status) => {
+            return Ok(Some(CustomError::FaultySetup(format!(
+                "Moderation failed: {status}"
+            ))))
+        }
+    };
+
+    for flag in &flags {
+        queries::prompt_flags::insert_prompt_flag()
+            .bind(
+                transaction,
+                &chat.id,
+                &flag.flag_type,
+                &ModerationDirection::Input,
+                &flag.action,
+            )
+            .await?;
+    }
+
+    if !is_blocked(&flags) {
+        return Ok(None);
+    }
+
+    queries::chats::new_chat()
+        .bind(
+            transaction,
+            &conversation.id,
+            &chat.prompt_id,
+            &None::<String>,
+            &None::<String>,
+            &"Your question violated our guidelines",
+            &ChatRole::Assistant,
+            &ChatStatus::Error,
+        )
+        .one()
+        .await?;
+
+    Ok(Some(CustomError::FaultySetup("Moderation failed".into())))
+}
@@ -0,0 +1,317 @@
+//! Runs a conversation without anyone watching, for automations.
+//!
+//! The chat goes through the same request building, moderation and persistence as the
+//! console. Tool calls are run and the model is called again with their results, as the
+//! console does after each reload, until it answers or an integration call needs the
+//! owner's approval.
+
+use crate::chat_request::create_request;
+use crate::jwt::Jwt;
+use crate::limits;
+use crate::result_sink::{DbResultSink, ResultSink, SaveRequest};
+use crate::routing::DbHealthTracker;
+use crate::ui_chat_orchestrator::{stream_chat_with_rig, GenerationEvent, StreamOutcome};
+use crate::user_config::UserConfig;
+use db::{queries, ChatStatus, Pool};
+use tokio::sync::mpsc;
+
+/// How many times the model can be called with tool results before the run gives up.
+const MAX_STEPS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeadlessOutcome {
+    /// The assistant's final answer.
+    Completed(String),
+    /// An integration call is waiting for the owner in the conversation.
+    AwaitingApproval,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    /// Tool results were saved and the model should see them.
+    CallAgain,
+    Finished(HeadlessOutcome),
+}
+
+/// Runs the user chat `chat_id` of `conversation_id` as the user with `sub`. Errors are
+/// saved in the conversation as they would be in the console, and returned.
+pub async fn run_chat(
+    pool: &Pool,
+    sub: &str,
+    conversation_id: i64,
+    chat_id: i32,
+) -> Result<HeadlessOutcome, String> {
+    let result_sink = DbResultSink::new(pool.clone());
+    let health = DbHealthTracker::new(pool.clone());
+    let current_user = Jwt {
+        sub: sub.to_string(),
+        email: String::new(),
+        given_name: None,
+        family_name: None,
+    };
+    let user_config = UserConfig {
+        default_prompt: None,
+    };
+    let mut chat_id = chat_id;
+
+    for _ in 0..MAX_STEPS {
+        let request = match create_request(pool, &current_user, chat_id, &user_config).await {
+            Ok(request) => request,
+            Err(err) => return Err(save_error(&result_sink, chat_id, sub, err.to_string()).await),
+        };
+
+        let refusal = limits::refusal_from_pool(pool, chat_id, request.model_id, request.user_id)
+            .await
+            .map_err(|err| err.to_string())?;
+        if let Some(message) = refusal {
+            return Err(save_error(&result_sink, chat_id, sub, message.to_string()).await);
+        }
+
+        let (sender, mut receiver) = mpsc::channel::<Result<GenerationEvent, axum::Error>>(10);
+        let generation = stream_chat_with_rig(request, sender, &health);
+        let collect = async {
+            let mut last = None;
+            while let Some(event) = receiver.recv().await {
+                if let Ok(
+                    event @ (GenerationEvent::End { .. } | GenerationEvent::Retracted { .. }),
+                ) = event
+                {
+                    last = Some(event);
+                }
+            }
+            last
+        };
+        let (outcome, last) = tokio::join!(generation, collect);
+
+        let event = match outcome {
+            Ok(StreamOutcome::Completed) => last,
+            // The receiver is read until the sender is dropped, so this can't happen.
+            Ok(StreamOutcome::ClientDisconnected { .. }) => None,
+            Err(err) => return Err(save_error(&result_sink, chat_id, sub, err.to_string()).await),
+        };
+        let Some(event) = event else {
+            let message = "The model stopped without a response".to_string();
+            return Err(save_error(&result_sink, chat_id, sub, message).await);
+        };
+
+        match save_step(&result_sink, event, chat_id, sub).await? {
+            Step::Finished(outcome) => return Ok(outcome),
+            Step::CallAgain => {
+                chat_id = last_chat_id(pool, sub, conversation_id).await?;
+            }
+        }
+    }
+
+    Err(format!(
+        "The assistant was still calling tools after {MAX_STEPS} steps"
+    ))
+}
+
+/// Saves the end of one model call and decides whether the model needs calling again.
+async fn save_step(
+    result_sink: &dyn ResultSink,
+    event: GenerationEvent,
+    chat_id: i32,
+    sub: &str,
+) -> Result<Step, String> {
+    match event {
+        GenerationEvent::End {
+            snapshot,
+            tool_calls,
+            reasoning,
+            usage,
+            model_endpoint_id,
+            output_flags,
+        } => {
+            let called_tools = tool_calls.as_ref().is_some_and(|calls| !calls.is_empty());
+            let pending_approvals = result_sink
+                .save(SaveRequest {
+                    snapshot: &snapshot,
+                    tool_calls,
+                    reasoning,
+                    usage,
+                    model_endpoint_id,
+                    output_flags,
+                    chat_id,
+                    sub,
+                    status: ChatStatus::Success,
+                })
+                .await;
+            if !pending_approvals.is_empty() {
+                Ok(Step::Finished(HeadlessOutcome::AwaitingApproval))
+            } else if called_tools {
+                Ok(Step::CallAgain)
+            } else {
+                Ok(Step::Finished(HeadlessOutcome::Completed(snapshot)))
+            }
+        }
+        GenerationEvent::Retracted {
+            message,
+            output_flags,
+            model_endpoint_id,
+        } => {
+            result_sink
+                .save(SaveRequest {
+                    snapshot: &message,
+                    tool_calls: None,
+                    reasoning: None,
+                    usage: None,
+                    model_endpoint_id,
+                    output_flags,
+                    chat_id,
+                    sub,
+                    status: ChatStatus::Error,
+                })
+                .await;
+            Err(message)
+        }
+        GenerationEvent::Text { .. } => Err("The model stopped without a response".to_string()),
+    }
+}
+
+async fn save_error(
+    result_sink: &dyn ResultSink,
+    chat_id: i32,
+    sub: &str,
+    message: String,
+) -> String {
+    result_sink
+        .save(SaveRequest {
+            snapshot: &message,
+            tool_calls: None,
+            reasoning: None,
+            usage: None,
+            model_endpoint_id: None,
+            output_flags: vec![],
+            chat_id,
+            sub,
+            status: ChatStatus::Error,
+        })
+        .await;
+    message
+}
+
+/// The console calls the model again with the newest chat, the last tool result.
+async fn last_chat_id(pool: &Pool, sub: &str, conversation_id: i64) -> Result<i32, String> {
+    let mut client = pool.get().await.map_err(|e| e.to_string())?;
+    let transaction = client.transaction().await.map_err(|e| e.to_string())?;
+    db::authz::set_row_level_security_user_id(&transaction, sub.to_string())
+        .await
+        .map_err(|e| e.to_string())?;
+    let chats = queries::chats::chats()
+        .bind(&transaction, &conversation_id)
+        .all()
+        .await
+        .map_err(|e| e.to_string())?;
+    transaction.commit().await.map_err(|e| e.to_string())?;
+    chats
+        .last()
+        .map(|chat| chat.id)
+        .ok_or_else(|| "The conversation has no chats".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::sync::Mutex;
+    use tool_runtime::{PendingApproval, ToolCall, ToolCallFunction};
+
+    #[derive(Default)]
+    struct FakeResultSink {
+        saved: Mutex<Vec<(String, ChatStatus)>>,
+        pending_approvals: Vec<PendingApproval>,
+    }
+
+    #[async_trait]
+    impl ResultSink for FakeResultSink {
+        async fn save(&self, request: SaveRequest<'_>) -> Vec<PendingApproval> {
+            self.saved
+                .lock()
+                .unwrap()
+                .push((request.snapshot.to_string(), request.status));
+            self.pending_approvals.clone()
+        }
+    }
+
+    fn end(snapshot: &str, tool_calls: Option<Vec<ToolCall>>) -> GenerationEvent {
+        GenerationEvent::End {
+            snapshot: snapshot.to_string(),
+            tool_calls,
+            reasoning: None,
+            usage: None,
+            model_endpoint_id: Some(1),
+            output_flags: vec![],
+        }
+    }
+
+    fn tool_call() -> ToolCall {
+        ToolCall {
+            id: "call_1".to_string(),
+            call_id: None,
+            signature: None,
+            additional_params: None,
+            function: ToolCallFunction {
+                name: "search_context".to_string(),
+                arguments: json!({}),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_calls_send_the_results_back_to_the_model() {
+        let sink = FakeResultSink::default();
+
+        let step = save_step(&sink, end("", Some(vec![tool_call()])), 7, "sub")
+            .await
+            .unwrap();
+        assert_eq!(step, Step::CallAgain);
+
+        let step = save_step(&sink, end("The digest", None), 8, "sub")
+            .await
+            .unwrap();
+        assert_eq!(
+            step,
+            Step::Finished(HeadlessOutcome::Completed("The digest".to_string()))
+        );
+        assert_eq!(sink.saved.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn calls_waiting_for_approval_end_the_run() {
+        let sink = FakeResultSink {
+            pending_approvals: vec![PendingApproval {
+                approval_id: 1,
+                function_name: "create_ticket".to_string(),
+                method: "POST".to_string(),
+                url: "https://tickets.example.com".to_string(),
+            }],
+            ..FakeResultSink::default()
+        };
+
+        let step = save_step(&sink, end("", Some(vec![tool_call()])), 7, "sub")
+            .await
+            .unwrap();
+        assert_eq!(step, Step::Finished(HeadlessOutcome::AwaitingApproval));
+    }
+
+    #[tokio::test]
+    async fn retracted_responses_are_saved_as_errors() {
+        let sink = FakeResultSink::default();
+
+        let retracted = GenerationEvent::Retracted {
+            message: "Withheld".to_string(),
+            output_flags: vec![],
+            model_endpoint_id: Some(1),
+        };
+        assert_eq!(
+            save_step(&sink, retracted, 7, "sub").await,
+            Err("Withheld".to_string())
+        );
+        assert_eq!(
+            sink.saved.lock().unwrap().as_slice(),
+            &[("Withheld".to_string(), ChatStatus::Error)]
+        );
+    }
+}
@@ -1,13 +1,17 @@
 mod chat_request;
 mod context_builder;
 mod errors;
+pub mod headless;
 mod jwt;
 pub mod limits;
 pub mod moderation;
+mod providers;
 mod result_sink;
+mod routing;
 pub mod synthesize;
 #[cfg(test)]
 mod tests;
+pub mod transcribe;
 pub mod ui_chat_orchestrator;
 #[cfg(test)]
 mod ui_chat_orchestrator_tests;
@@ -29,6 +33,7 @@ pub fn routes() -> Router {
 
     Router::new()
         .typed_post(synthesize::synthesize)
+        .typed_post(transcribe::transcribe)
         .typed_post(ui_chat_orchestrator::chat_generate)
         .typed_get(ui_chat_orchestrator::chat_generate)
         .layer(cors) // Apply the CORS layer
@@ -43,3 +48,7 @@ pub struct UICompletions {
 #[derive(TypedPath, Deserialize)]
 #[typed_path("/app/synthesize")]
 pub struct UISynthesize {}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/app/transcribe")]
+pub struct UITranscribe {}
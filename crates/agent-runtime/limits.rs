@@ -1,10 +1,14 @@
 use db::{
-    queries::{inference_metrics, models},
+    queries::{budgets, inference_metrics, models},
     Pool, Transaction,
 };
 
 use crate::errors::CustomError;
 
+const TOKEN_LIMIT_MESSAGE: &str = "You have exceeded your token limit for this model";
+const TEAM_BUDGET_MESSAGE: &str = "Your team has used its budget for this month";
+const USER_BUDGET_MESSAGE: &str = "You have used your budget in this team for this month";
+
 // Fetch the usage stats so far and compare with the limits
 // if we have gone over the limits return true
 pub async fn is_limit_exceeded(
@@ -33,13 +37,39 @@ pub async fn is_limit_exceeded(
     }
 }
 
-pub async fn is_limit_exceeded_from_pool(
+// Budgets stop a team, or one member of it, once this month's spending reaches their
+// stop threshold. Returns the message to show instead of a response.
+pub async fn budget_stop(
+    transaction: &Transaction<'_>,
+    chat_id: i32,
+) -> Result<Option<&'static str>, CustomError> {
+    let stopped = budgets::stopped_budget()
+        .bind(transaction, &chat_id)
+        .opt()
+        .await?;
+
+    Ok(stopped.map(|budget| {
+        tracing::warn!("Budget {} stopped chat {}", budget.id, chat_id);
+        if budget.user_id.is_some() {
+            USER_BUDGET_MESSAGE
+        } else {
+            TEAM_BUDGET_MESSAGE
+        }
+    }))
+}
+
+/// The token limit is checked first, then budgets.
+pub async fn refusal_from_pool(
     pool: &Pool,
+    chat_id: i32,
     model_id: i32,
     user_id: i32,
-) -> Result<bool, CustomError> {
+) -> Result<Option<&'static str>, CustomError> {
     let mut db_client = pool.get().await?;
     let transaction = db_client.transaction().await?;
 
-    is_limit_exceeded(&transaction, model_id, user_id).await
+    if is_limit_exceeded(&transaction, model_id, user_id).await? {
+        return Ok(Some(TOKEN_LIMIT_MESSAGE));
+    }
+    budget_stop(&transaction, chat_id).await
 }
@@ -1,10 +1,11 @@
-use db::PromptFlagType;
-use reqwest::{
-    header::{AUTHORIZATION, CONTENT_TYPE},
-    StatusCode,
-};
+use crate::providers::{model_call_span, record_failure, ProviderModel};
+use async_trait::async_trait;
+use db::{queries, GuardFormat, GuardModel, ModerationAction, PromptFlagType, Transaction};
+use reqwest::StatusCode;
+use rig::completion::CompletionRequest;
 use rig::message::{AssistantContent, Message, UserContent};
-use serde::Deserialize;
+use rig::OneOrMany;
+use tracing::Instrument;
 
 pub fn strip_tool_data(messages: &[Message]) -> Vec<Message> {
     messages
@@ -21,8 +22,8 @@ pub fn strip_tool_data(messages: &[Message]) -> Vec<Message> {
                     None
                 } else {
                     Some(Message::User {
-                        content: rig::OneOrMany::many(kept)
-                            .unwrap_or_else(|_| rig::OneOrMany::one(UserContent::text(""))),
+                        content: OneOrMany::many(kept)
+                            .unwrap_or_else(|_| OneOrMany::one(UserContent::text(""))),
                     })
                 }
             }
@@ -38,8 +39,8 @@ pub fn strip_tool_data(messages: &[Message]) -> Vec<Message> {
                 } else {
                     Some(Message::Assistant {
                         id,
-                        content: rig::OneOrMany::many(kept)
-                            .unwrap_or_else(|_| rig::OneOrMany::one(AssistantContent::text(""))),
+                        content: OneOrMany::many(kept)
+                            .unwrap_or_else(|_| OneOrMany::one(AssistantContent::text(""))),
                     })
                 }
             }
@@ -47,87 +48,338 @@ pub fn strip_tool_data(messages: &[Message]) -> Vec<Message> {
         .collect()
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ModerationVerdict {
     Safe,
-    Unsafe(PromptFlagType),
+    Unsafe(Vec<PromptFlagType>),
+}
+
+/// Reads a guard model's reply. Guard models disagree on output format, so each
+/// `GuardFormat` has its own parser.
+pub trait GuardParser: Send + Sync {
+    /// Returns `None` when the reply isn't in the expected format.
+    fn parse(&self, content: &str) -> Option<ModerationVerdict>;
+}
+
+/// Llama Guard replies `safe`, or `unsafe` followed by a line of comma separated
+/// category codes such as `S1,S10`.
+pub struct LlamaGuardParser;
+
+impl GuardParser for LlamaGuardParser {
+    fn parse(&self, content: &str) -> Option<ModerationVerdict> {
+        let content = content.trim().to_lowercase();
+        if content.starts_with("safe") {
+            return Some(ModerationVerdict::Safe);
+        }
+        if !content.starts_with("unsafe") {
+            return None;
+        }
+
+        let flags: Vec<PromptFlagType> = content["unsafe".len()..]
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|code| !code.is_empty())
+            .map(flag_type_from_code)
+            .collect::<Option<_>>()?;
+
+        if flags.is_empty() {
+            None
+        } else {
+            Some(ModerationVerdict::Unsafe(flags))
+        }
+    }
+}
+
+/// For guard models prompted to answer with JSON, e.g.
+/// `{"safe": false, "categories": ["S1"]}`. Surrounding text such as code fences is ignored.
+pub struct JsonGuardParser;
+
+impl GuardParser for JsonGuardParser {
+    fn parse(&self, content: &str) -> Option<ModerationVerdict> {
+        let start = content.find('{')?;
+        let end = content.rfind('}')?;
+        let value: serde_json::Value = serde_json::from_str(content.get(start..=end)?).ok()?;
+
+        if value.get("safe")?.as_bool()? {
+            return Some(ModerationVerdict::Safe);
+        }
+
+        let flags: Vec<PromptFlagType> = value
+            .get("categories")
+            .or_else(|| value.get("violated_categories"))?
+            .as_array()?
+            .iter()
+            .map(|code| code.as_str().and_then(flag_type_from_code))
+            .collect::<Option<_>>()?;
+
+        if flags.is_empty() {
+            None
+        } else {
+            Some(ModerationVerdict::Unsafe(flags))
+        }
+    }
+}
+
+pub fn guard_parser(format: GuardFormat) -> &'static dyn GuardParser {
+    match format {
+        GuardFormat::LlamaGuard => &LlamaGuardParser,
+        GuardFormat::Json => &JsonGuardParser,
+    }
+}
+
+fn flag_type_from_code(code: &str) -> Option<PromptFlagType> {
+    let flag = match code.trim().to_uppercase().as_str() {
+        "S1" => PromptFlagType::S1,
+        "S2" => PromptFlagType::S2,
+        "S3" => PromptFlagType::S3,
+        "S4" => PromptFlagType::S4,
+        "S5" => PromptFlagType::S5,
+        "S6" => PromptFlagType::S6,
+        "S7" => PromptFlagType::S7,
+        "S8" => PromptFlagType::S8,
+        "S9" => PromptFlagType::S9,
+        "S10" => PromptFlagType::S10,
+        "S11" => PromptFlagType::S11,
+        "S12" => PromptFlagType::S12,
+        "S13" => PromptFlagType::S13,
+        "S14" => PromptFlagType::S14,
+        _ => return None,
+    };
+    Some(flag)
+}
+
+/// A category the guard model raised and what the team's policy does about it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlaggedCategory {
+    pub flag_type: PromptFlagType,
+    pub action: ModerationAction,
+}
+
+pub fn is_blocked(flags: &[FlaggedCategory]) -> bool {
+    flags
+        .iter()
+        .any(|flag| flag.action == ModerationAction::Block)
+}
+
+/// A team's choice of which categories block and which are only flagged.
+/// Categories the team hasn't configured block.
+#[derive(Debug, Clone, Default)]
+pub struct TeamPolicy {
+    flag_only: Vec<PromptFlagType>,
+}
+
+impl TeamPolicy {
+    pub async fn load(
+        transaction: &Transaction<'_>,
+        team_id: i32,
+    ) -> Result<Self, db::TokioPostgresError> {
+        let rows = queries::moderation_policies::moderation_policies()
+            .bind(transaction, &team_id)
+            .all()
+            .await?;
+        Ok(Self::from_rows(rows))
+    }
+
+    pub fn from_rows(rows: Vec<db::ModerationPolicy>) -> Self {
+        Self {
+            flag_only: rows
+                .into_iter()
+                .filter(|row| row.action == ModerationAction::Flag)
+                .map(|row| row.flag_type)
+                .collect(),
+        }
+    }
+
+    pub fn action(&self, flag_type: PromptFlagType) -> ModerationAction {
+        if self.flag_only.contains(&flag_type) {
+            ModerationAction::Flag
+        } else {
+            ModerationAction::Block
+        }
+    }
+
+    pub fn apply(&self, verdict: &ModerationVerdict) -> Vec<FlaggedCategory> {
+        match verdict {
+            ModerationVerdict::Safe => vec![],
+            ModerationVerdict::Unsafe(flags) => flags
+                .iter()
+                .map(|flag_type| FlaggedCategory {
+                    flag_type: *flag_type,
+                    action: self.action(*flag_type),
+                })
+                .collect(),
+        }
+    }
 }
 
 pub async fn moderate_chat(
-    base_url: &str,
-    api_key: Option<&str>,
-    model_name: &str,
+    guard: &GuardModel,
     messages: Vec<Message>,
 ) -> Result<ModerationVerdict, StatusCode> {
-    let completion = serde_json::json!({
-        "model": model_name,
-        "messages": messages,
-    });
-
-    let client = reqwest::Client::new();
-    let mut request = client
-        .post(format!("{}/chat/completions", base_url))
-        .header(CONTENT_TYPE, "application/json");
-    if let Some(key) = api_key {
-        request = request.header(AUTHORIZATION, format!("Bearer {}", key));
-    }
+    let model = ProviderModel::new(
+        guard.provider_kind,
+        &guard.base_url,
+        guard.api_key.as_deref(),
+        &guard.name,
+    )
+    .map_err(|e| {
+        tracing::error!("Failed to create guard model client: {}", e);
+        StatusCode::BAD_GATEWAY
+    })?;
 
-    let resp = request
-        .body(completion.to_string())
-        .send()
+    let request = CompletionRequest {
+        model: None,
+        preamble: None,
+        chat_history: OneOrMany::many(messages).map_err(|_| StatusCode::BAD_REQUEST)?,
+        documents: vec![],
+        tools: vec![],
+        temperature: None,
+        max_tokens: None,
+        tool_choice: None,
+        additional_params: None,
+        output_schema: None,
+    };
+
+    let span = model_call_span(guard.provider_kind, &guard.name);
+    let content = model
+        .completion_text(request)
+        .instrument(span.clone())
         .await
-        .map_err(|_| StatusCode::BAD_GATEWAY)?;
-    if !resp.status().is_success() {
-        return Err(resp.status());
+        .map_err(|e| {
+            record_failure(&span, &e);
+            tracing::error!("Guard model call failed: {}", e);
+            StatusCode::BAD_GATEWAY
+        })?;
+
+    guard_parser(guard.guard_format)
+        .parse(&content)
+        .ok_or_else(|| {
+            tracing::error!("Unrecognised guard model reply: {}", content);
+            StatusCode::BAD_GATEWAY
+        })
+}
+
+/// Checks assistant responses before they are kept.
+#[async_trait]
+pub(crate) trait OutputGuard: Send + Sync {
+    /// Check the response in windows while it streams, not only once it's complete.
+    fn streaming(&self) -> bool;
+    async fn check(&self, response: &str) -> Result<Vec<FlaggedCategory>, StatusCode>;
+}
+
+/// Runs the team's guard model over the conversation followed by the assistant response.
+pub(crate) struct GuardModelCheck {
+    guard: GuardModel,
+    policy: TeamPolicy,
+    streaming: bool,
+    conversation: Vec<Message>,
+}
+
+impl GuardModelCheck {
+    pub(crate) fn new(
+        guard: GuardModel,
+        policy: TeamPolicy,
+        streaming: bool,
+        conversation: Vec<Message>,
+    ) -> Self {
+        Self {
+            guard,
+            policy,
+            streaming,
+            conversation,
+        }
     }
+}
 
-    #[derive(Deserialize)]
-    struct ResponseChoice {
-        message: Message,
+#[async_trait]
+impl OutputGuard for GuardModelCheck {
+    fn streaming(&self) -> bool {
+        self.streaming
     }
 
-    #[derive(Deserialize)]
-    struct CompletionResponse {
-        choices: Vec<ResponseChoice>,
+    async fn check(&self, response: &str) -> Result<Vec<FlaggedCategory>, StatusCode> {
+        let mut messages = self.conversation.clone();
+        messages.push(Message::assistant(response));
+        let verdict = moderate_chat(&self.guard, messages).await?;
+        Ok(self.policy.apply(&verdict))
     }
+}
 
-    let CompletionResponse { choices } = resp.json().await.map_err(|_| StatusCode::BAD_GATEWAY)?;
-
-    let content = choices
-        .first()
-        .and_then(|c| match &c.message {
-            Message::Assistant { content, .. } => content.iter().find_map(|item| match item {
-                AssistantContent::Text(text) => Some(text.text.clone()),
-                AssistantContent::ToolCall(_)
-                | AssistantContent::Reasoning(_)
-                | AssistantContent::Image(_) => None,
-            }),
-            Message::System { .. } | Message::User { .. } => None,
-        })
-        .unwrap_or_default();
-
-    let content = content.trim();
-
-    if content.to_lowercase().starts_with("safe") {
-        Ok(ModerationVerdict::Safe)
-    } else {
-        let code = content.split_whitespace().last().unwrap_or("");
-        let flag = match code {
-            "S1" => PromptFlagType::S1,
-            "S2" => PromptFlagType::S2,
-            "S3" => PromptFlagType::S3,
-            "S4" => PromptFlagType::S4,
-            "S5" => PromptFlagType::S5,
-            "S6" => PromptFlagType::S6,
-            "S7" => PromptFlagType::S7,
-            "S8" => PromptFlagType::S8,
-            "S9" => PromptFlagType::S9,
-            "S10" => PromptFlagType::S10,
-            "S11" => PromptFlagType::S11,
-            "S12" => PromptFlagType::S12,
-            "S13" => PromptFlagType::S13,
-            "S14" => PromptFlagType::S14,
-            _ => return Err(StatusCode::BAD_GATEWAY),
-        };
-        Ok(ModerationVerdict::Unsafe(flag))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_llama_guard_parser() {
+        let parser = LlamaGuardParser;
+
+        assert_eq!(parser.parse("safe"), Some(ModerationVerdict::Safe));
+        assert_eq!(
+            parser.parse("\n\nunsafe\nS10"),
+            Some(ModerationVerdict::Unsafe(vec![PromptFlagType::S10]))
+        );
+        assert_eq!(
+            parser.parse("unsafe\nS1,S14"),
+            Some(ModerationVerdict::Unsafe(vec![
+                PromptFlagType::S1,
+                PromptFlagType::S14
+            ]))
+        );
+        assert_eq!(parser.parse("unsafe"), None);
+        assert_eq!(parser.parse("unsafe\nS99"), None);
+        assert_eq!(parser.parse("I can't help with that"), None);
+    }
+
+    #[test]
+    fn test_json_guard_parser() {
+        let parser = JsonGuardParser;
+
+        assert_eq!(
+            parser.parse(r#"{"safe": true}"#),
+            Some(ModerationVerdict::Safe)
+        );
+        assert_eq!(
+            parser.parse("```json\n{\"safe\": false, \"categories\": [\"s2\", \"S9\"]}\n```"),
+            Some(ModerationVerdict::Unsafe(vec![
+                PromptFlagType::S2,
+                PromptFlagType::S9
+            ]))
+        );
+        assert_eq!(
+            parser.parse(r#"{"safe": false, "violated_categories": ["S5"]}"#),
+            Some(ModerationVerdict::Unsafe(vec![PromptFlagType::S5]))
+        );
+        assert_eq!(parser.parse(r#"{"safe": false, "categories": []}"#), None);
+        assert_eq!(parser.parse("safe"), None);
+    }
+
+    #[test]
+    fn test_team_policy_defaults_to_block() {
+        let policy = TeamPolicy::from_rows(vec![
+            db::ModerationPolicy {
+                flag_type: PromptFlagType::S6,
+                action: ModerationAction::Flag,
+            },
+            db::ModerationPolicy {
+                flag_type: PromptFlagType::S1,
+                action: ModerationAction::Block,
+            },
+        ]);
+
+        let flags = policy.apply(&ModerationVerdict::Unsafe(vec![PromptFlagType::S6]));
+        assert_eq!(
+            flags,
+            vec![FlaggedCategory {
+                flag_type: PromptFlagType::S6,
+                action: ModerationAction::Flag
+            }]
+        );
+        assert!(!is_blocked(&flags));
+
+        let flags = policy.apply(&ModerationVerdict::Unsafe(vec![
+            PromptFlagType::S6,
+            PromptFlagType::S2,
+        ]));
+        assert!(is_blocked(&flags));
+        assert!(policy.apply(&ModerationVerdict::Safe).is_empty());
     }
 }
@@ -0,0 +1,196 @@
+//! Maps a model's `ProviderKind` onto the matching native rig provider.
+use db::ProviderKind;
+use rig::client::CompletionClient;
+use rig::completion::{AssistantContent, CompletionModel, CompletionRequest, Usage};
+use rig::providers::{anthropic, azure, gemini, ollama, openai};
+use tracing::{field::Empty, Span};
+
+pub(crate) type ProviderError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A completion model for one of the supported provider kinds.
+pub(crate) enum ProviderModel {
+    OpenAI(openai::CompletionModel),
+    Anthropic(anthropic::completion::CompletionModel),
+    Gemini(gemini::CompletionModel),
+    AzureOpenAI(azure::CompletionModel),
+    Ollama(ollama::CompletionModel),
+}
+
+impl ProviderModel {
+    pub(crate) fn new(
+        provider_kind: ProviderKind,
+        base_url: &str,
+        api_key: Option<&str>,
+        model_name: &str,
+    ) -> Result<Self, ProviderError> {
+        let api_key = api_key.unwrap_or("");
+        let base_url = base_url.trim_end_matches('/');
+
+        let model = match provider_kind {
+            ProviderKind::OpenAI => ProviderModel::OpenAI(
+                openai::Client::builder()
+                    .api_key(api_key)
+                    .base_url(base_url)
+                    .build()?
+                    .completion_model(model_name)
+                    .completions_api(),
+            ),
+            // `with_model` falls back to a default `max_tokens` for model names rig doesn't know.
+            ProviderKind::Anthropic => {
+                ProviderModel::Anthropic(anthropic::completion::CompletionModel::with_model(
+                    anthropic::Client::builder()
+                        .api_key(api_key)
+                        .base_url(base_url)
+                        .build()?,
+                    model_name,
+                ))
+            }
+            ProviderKind::Gemini => ProviderModel::Gemini(
+                gemini::Client::builder()
+                    .api_key(api_key)
+                    .base_url(strip_suffixes(base_url, &["/v1beta/openai", "/v1beta"]))
+                    .build()?
+                    .completion_model(model_name),
+            ),
+            ProviderKind::AzureOpenAI => {
+                let (endpoint, api_version) = azure_endpoint(base_url);
+                let mut builder = azure::Client::builder()
+                    .api_key(azure::AzureOpenAIAuth::ApiKey(api_key.to_string()))
+                    .azure_endpoint(endpoint);
+                if let Some(api_version) = api_version {
+                    builder = builder.api_version(&api_version);
+                }
+                ProviderModel::AzureOpenAI(builder.build()?.completion_model(model_name))
+            }
+            ProviderKind::Ollama => ProviderModel::Ollama(
+                ollama::Client::builder()
+                    .api_key(api_key)
+                    .base_url(strip_suffixes(base_url, &["/v1", "/api"]))
+                    .build()?
+                    .completion_model(model_name),
+            ),
+        };
+
+        Ok(model)
+    }
+
+    /// Runs a non-streaming completion and returns the first text part of the reply.
+    pub(crate) async fn completion_text(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<String, ProviderError> {
+        match self {
+            ProviderModel::OpenAI(model) => completion_text(model, request).await,
+            ProviderModel::Anthropic(model) => completion_text(model, request).await,
+            ProviderModel::Gemini(model) => completion_text(model, request).await,
+            ProviderModel::AzureOpenAI(model) => completion_text(model, request).await,
+            ProviderModel::Ollama(model) => completion_text(model, request).await,
+        }
+    }
+}
+
+async fn completion_text<M: CompletionModel>(
+    model: &M,
+    request: CompletionRequest,
+) -> Result<String, ProviderError> {
+    let response = model.completion(request).await?;
+    record_usage(&Span::current(), &response.usage);
+
+    Ok(response
+        .choice
+        .iter()
+        .find_map(|content| match content {
+            AssistantContent::Text(text) => Some(text.text.clone()),
+            _ => None,
+        })
+        .unwrap_or_default())
+}
+
+/// A span for one call to a model, with the attributes of OpenTelemetry's GenAI conventions.
+pub(crate) fn model_call_span(provider_kind: ProviderKind, model_name: &str) -> Span {
+    tracing::info_span!(
+        "gen_ai.chat",
+        otel.name = format!("chat {model_name}"),
+        otel.kind = "client",
+        otel.status_code = Empty,
+        otel.status_message = Empty,
+        gen_ai.operation.name = "chat",
+        gen_ai.provider.name = provider_name(provider_kind),
+        gen_ai.request.model = model_name,
+        gen_ai.usage.input_tokens = Empty,
+        gen_ai.usage.output_tokens = Empty,
+        bionic.model_endpoint_id = Empty,
+    )
+}
+
+pub(crate) fn record_usage(span: &Span, usage: &Usage) {
+    span.record("gen_ai.usage.input_tokens", usage.input_tokens);
+    span.record("gen_ai.usage.output_tokens", usage.output_tokens);
+}
+
+pub(crate) fn record_failure(span: &Span, error: &dyn std::fmt::Display) {
+    span.record("otel.status_code", "ERROR");
+    span.record("otel.status_message", error.to_string());
+}
+
+fn provider_name(provider_kind: ProviderKind) -> &'static str {
+    match provider_kind {
+        ProviderKind::OpenAI => "openai",
+        ProviderKind::Anthropic => "anthropic",
+        ProviderKind::Gemini => "gcp.gemini",
+        ProviderKind::AzureOpenAI => "azure.ai.openai",
+        ProviderKind::Ollama => "ollama",
+    }
+}
+
+fn strip_suffixes<'a>(base_url: &'a str, suffixes: &[&str]) -> &'a str {
+    suffixes
+        .iter()
+        .find_map(|suffix| base_url.strip_suffix(suffix))
+        .unwrap_or(base_url)
+}
+
+/// Azure endpoints are stored as the resource URL, optionally with an `api-version` query.
+fn azure_endpoint(base_url: &str) -> (String, Option<String>) {
+    let (endpoint, query) = base_url.split_once('?').unwrap_or((base_url, ""));
+    let api_version = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("api-version="))
+        .filter(|version| !version.is_empty())
+        .map(str::to_string);
+    let endpoint = strip_suffixes(endpoint.trim_end_matches('/'), &["/openai"]).to_string();
+
+    (endpoint, api_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_azure_endpoint() {
+        assert_eq!(
+            azure_endpoint("https://example.openai.azure.com"),
+            ("https://example.openai.azure.com".to_string(), None)
+        );
+        assert_eq!(
+            azure_endpoint("https://example.openai.azure.com/openai/?api-version=2025-01-01"),
+            (
+                "https://example.openai.azure.com".to_string(),
+                Some("2025-01-01".to_string())
+            )
+        );
+    }
+
+    #[test]
+    fn test_strip_suffixes() {
+        assert_eq!(
+            strip_suffixes("http://ollama:11434/v1", &["/v1", "/api"]),
+            "http://ollama:11434"
+        );
+        assert_eq!(
+            strip_suffixes("https://generativelanguage.googleapis.com", &["/v1beta"]),
+            "https://generativelanguage.googleapis.com"
+        );
+    }
+}
@@ -1,8 +1,11 @@
+use crate::moderation::FlaggedCategory;
 use async_trait::async_trait;
-use db::{queries, ChatRole, ChatStatus, Pool};
+use db::{queries, ChatRole, ChatStatus, ModerationDirection, Pool};
 use rig::completion::Usage;
+use tool_runtime::builtin_tools::approvals;
 use tool_runtime::{
-    execute_tool_calls, serialize_assistant_tool_state, Reasoning, ToolCall, ToolResultContent,
+    execute_tool_calls, serialize_assistant_tool_state, PendingApproval, Reasoning, ToolCall,
+    ToolResultContent,
 };
 
 pub(crate) struct SaveRequest<'a> {
@@ -10,6 +13,10 @@ pub(crate) struct SaveRequest<'a> {
     pub(crate) tool_calls: Option<Vec<ToolCall>>,
     pub(crate) reasoning: Option<Vec<Reasoning>>,
     pub(crate) usage: Option<Usage>,
+    /// The endpoint that served the response, recorded on the assistant chat.
+    pub(crate) model_endpoint_id: Option<i32>,
+    /// Categories output moderation raised on the response.
+    pub(crate) output_flags: Vec<FlaggedCategory>,
     pub(crate) chat_id: i32,
     pub(crate) sub: &'a str,
     pub(crate) status: ChatStatus,
@@ -17,7 +24,9 @@ pub(crate) struct SaveRequest<'a> {
 
 #[async_trait]
 pub(crate) trait ResultSink: Send + Sync {
-    async fn save(&self, request: SaveRequest<'_>);
+    /// Saves the response and runs its tool calls, returning any integration calls
+    /// that are waiting for the user's approval.
+    async fn save(&self, request: SaveRequest<'_>) -> Vec<PendingApproval>;
 }
 
 pub(crate) struct DbResultSink {
@@ -32,17 +41,25 @@ impl DbResultSink {
 
 #[async_trait]
 impl ResultSink for DbResultSink {
-    async fn save(&self, request: SaveRequest<'_>) {
-        save_results_db(&self.pool, request).await;
+    async fn save(&self, request: SaveRequest<'_>) -> Vec<PendingApproval> {
+        let mut pending_approvals = Vec::new();
+        save_results_db(&self.pool, request, &mut pending_approvals).await;
+        pending_approvals
     }
 }
 
-async fn save_results_db(pool: &Pool, request: SaveRequest<'_>) {
+async fn save_results_db(
+    pool: &Pool,
+    request: SaveRequest<'_>,
+    pending_approvals: &mut Vec<PendingApproval>,
+) {
     let SaveRequest {
         snapshot,
         tool_calls,
         reasoning,
         usage,
+        model_endpoint_id,
+        output_flags,
         chat_id,
         sub,
         status,
@@ -102,7 +119,7 @@ async fn save_results_db(pool: &Pool, request: SaveRequest<'_>) {
             }
         }
 
-        if let Err(e) = queries::chats::new_chat()
+        let assistant_chat_id = match queries::chats::new_chat()
             .bind(
                 &transaction,
                 &chat.conversation_id,
@@ -116,8 +133,36 @@ async fn save_results_db(pool: &Pool, request: SaveRequest<'_>) {
             .one()
             .await
         {
-            tracing::error!("Error creating chat: {:?}", e);
-            return;
+            Ok(id) => id,
+            Err(e) => {
+                tracing::error!("Error creating chat: {:?}", e);
+                return;
+            }
+        };
+
+        if model_endpoint_id.is_some() {
+            if let Err(e) = queries::chats::set_model_endpoint()
+                .bind(&transaction, &model_endpoint_id, &assistant_chat_id)
+                .await
+            {
+                tracing::error!("Error recording model endpoint: {:?}", e);
+            }
+        }
+
+        // Output flags sit with the user's chat, next to any flags on the question itself.
+        for flag in &output_flags {
+            if let Err(e) = queries::prompt_flags::insert_prompt_flag()
+                .bind(
+                    &transaction,
+                    &chat_id,
+                    &flag.flag_type,
+                    &ModerationDirection::Output,
+                    &flag.action,
+                )
+                .await
+            {
+                tracing::error!("Error recording output flag: {:?}", e);
+            }
         }
 
         if status == ChatStatus::Success {
@@ -191,7 +236,7 @@ async fn save_results_db(pool: &Pool, request: SaveRequest<'_>) {
                             }
                         }
                     };
-                    if let Err(e) = queries::chats::new_chat()
+                    let tool_chat_id = match queries::chats::new_chat()
                         .bind(
                             &transaction,
                             &chat.conversation_id,
@@ -205,9 +250,21 @@ async fn save_results_db(pool: &Pool, request: SaveRequest<'_>) {
                         .one()
                         .await
                     {
-                        tracing::error!("Error creating tool call results chat: {:?}", e);
-                        return;
+                        Ok(id) => id,
+                        Err(e) => {
+                            tracing::error!("Error creating tool call results chat: {:?}", e);
+                            return;
+                        }
+                    };
+
+                    // The decision is written back into this chat once the user makes it.
+                    let waiting = approvals::pending_approvals(&result_json);
+                    if let Err(e) =
+                        approvals::attach_to_chat(&transaction, tool_chat_id, &waiting).await
+                    {
+                        tracing::error!("Error linking tool call approvals: {:?}", e);
                     }
+                    pending_approvals.extend(waiting);
                 }
             }
         }
@@ -0,0 +1,214 @@
+//! Picks which of a model's endpoints serves a chat and tracks endpoint health.
+use async_trait::async_trait;
+use db::{queries, ModelEndpoint, Pool};
+use rand::RngExt;
+use rig::completion::CompletionError;
+use rig::http_client;
+
+/// Consecutive retryable failures before an endpoint's circuit opens.
+const FAILURE_THRESHOLD: i32 = 3;
+/// How long an open circuit keeps an endpoint out of rotation.
+const COOLDOWN_SECONDS: f64 = 30.0;
+
+#[async_trait]
+pub(crate) trait HealthTracker: Send + Sync {
+    async fn record_success(&self, endpoint_id: i32);
+    async fn record_failure(&self, endpoint_id: i32, error: &str);
+}
+
+pub(crate) struct DbHealthTracker {
+    pool: Pool,
+}
+
+impl DbHealthTracker {
+    pub(crate) fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl HealthTracker for DbHealthTracker {
+    async fn record_success(&self, endpoint_id: i32) {
+        let result = match self.pool.get().await {
+            Ok(client) => queries::model_endpoints::record_success()
+                .bind(&client, &endpoint_id)
+                .await
+                .map(|_| ()),
+            Err(e) => {
+                tracing::error!("Error getting database client: {:?}", e);
+                return;
+            }
+        };
+        if let Err(e) = result {
+            tracing::error!("Error recording endpoint success: {:?}", e);
+        }
+    }
+
+    async fn record_failure(&self, endpoint_id: i32, error: &str) {
+        let last_error: String = error.chars().take(500).collect();
+        let result = match self.pool.get().await {
+            Ok(client) => queries::model_endpoints::record_failure()
+                .bind(
+                    &client,
+                    &last_error,
+                    &FAILURE_THRESHOLD,
+                    &COOLDOWN_SECONDS,
+                    &endpoint_id,
+                )
+                .await
+                .map(|_| ()),
+            Err(e) => {
+                tracing::error!("Error getting database client: {:?}", e);
+                return;
+            }
+        };
+        if let Err(e) = result {
+            tracing::error!("Error recording endpoint failure: {:?}", e);
+        }
+    }
+}
+
+/// Orders endpoints for a single request: lower priorities first, weighted random within a
+/// priority. Endpoints with an open circuit go last so a chat can still be attempted when
+/// every endpoint is marked down.
+pub(crate) fn route(endpoints: Vec<ModelEndpoint>) -> Vec<ModelEndpoint> {
+    order_endpoints(endpoints, |total| rand::rng().random_range(0..total))
+}
+
+fn order_endpoints(
+    mut endpoints: Vec<ModelEndpoint>,
+    mut pick: impl FnMut(i64) -> i64,
+) -> Vec<ModelEndpoint> {
+    endpoints.sort_by_key(|endpoint| (endpoint.circuit_open, endpoint.priority));
+
+    let mut ordered = Vec::with_capacity(endpoints.len());
+    while let Some(first) = endpoints.first() {
+        let key = (first.circuit_open, first.priority);
+        let group_len = endpoints
+            .iter()
+            .take_while(|endpoint| (endpoint.circuit_open, endpoint.priority) == key)
+            .count();
+        let mut group: Vec<ModelEndpoint> = endpoints.drain(..group_len).collect();
+
+        while !group.is_empty() {
+            let total: i64 = group.iter().map(weight).sum();
+            let mut roll = pick(total);
+            let index = group
+                .iter()
+                .position(|endpoint| {
+                    if roll < weight(endpoint) {
+                        true
+                    } else {
+                        roll -= weight(endpoint);
+                        false
+                    }
+                })
+                .unwrap_or(0);
+            ordered.push(group.remove(index));
+        }
+    }
+    ordered
+}
+
+fn weight(endpoint: &ModelEndpoint) -> i64 {
+    i64::from(endpoint.weight.max(1))
+}
+
+/// Connection failures, rate limits and server errors are worth retrying on another endpoint.
+/// Anything else (bad requests, auth, unknown model) would fail the same way everywhere.
+pub(crate) fn is_retryable(error: &CompletionError) -> bool {
+    if let Some(status) = error.provider_response_status() {
+        return status.as_u16() == 429 || status.is_server_error();
+    }
+    match error {
+        CompletionError::HttpError(
+            http_client::Error::Instance(_) | http_client::Error::StreamEnded,
+        ) => true,
+        // Streaming providers flatten transport failures into a provider error message.
+        CompletionError::ProviderError(message) => message.starts_with("Http client error"),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use db::ProviderKind;
+
+    fn endpoint(id: i32, priority: i32, weight: i32, circuit_open: bool) -> ModelEndpoint {
+        ModelEndpoint {
+            id,
+            model_id: 1,
+            is_primary: id == 1,
+            provider_kind: ProviderKind::OpenAI,
+            base_url: format!("http://endpoint-{id}/v1"),
+            api_key: None,
+            priority,
+            weight,
+            consecutive_failures: 0,
+            circuit_open,
+            last_error: None,
+        }
+    }
+
+    fn ids(endpoints: &[ModelEndpoint]) -> Vec<i32> {
+        endpoints.iter().map(|endpoint| endpoint.id).collect()
+    }
+
+    #[test]
+    fn test_order_endpoints_by_priority_then_open_circuit() {
+        let endpoints = vec![
+            endpoint(1, 0, 1, true),
+            endpoint(2, 1, 1, false),
+            endpoint(3, 0, 1, false),
+            endpoint(4, 2, 1, false),
+        ];
+
+        let ordered = order_endpoints(endpoints, |_| 0);
+
+        assert_eq!(ids(&ordered), vec![3, 2, 4, 1]);
+    }
+
+    #[test]
+    fn test_order_endpoints_uses_weights_within_priority() {
+        let endpoints = || {
+            vec![
+                endpoint(1, 0, 1, false),
+                endpoint(2, 0, 3, false),
+                endpoint(3, 1, 1, false),
+            ]
+        };
+
+        // A roll of 0 lands on the first unit of weight, 1..=3 on the heavier endpoint.
+        assert_eq!(ids(&order_endpoints(endpoints(), |_| 0)), vec![1, 2, 3]);
+        assert_eq!(ids(&order_endpoints(endpoints(), |_| 1)), vec![2, 1, 3]);
+
+        let mut totals = vec![];
+        order_endpoints(endpoints(), |total| {
+            totals.push(total);
+            total - 1
+        });
+        assert_eq!(totals, vec![4, 1, 1]);
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let status = |code: u16| {
+            CompletionError::HttpError(http_client::Error::InvalidStatusCodeWithMessage(
+                http::StatusCode::from_u16(code).unwrap(),
+                "error".to_string(),
+            ))
+        };
+
+        assert!(is_retryable(&status(429)));
+        assert!(is_retryable(&status(503)));
+        assert!(!is_retryable(&status(400)));
+        assert!(!is_retryable(&status(401)));
+        assert!(is_retryable(&CompletionError::ProviderError(
+            "Http client error: error sending request".to_string()
+        )));
+        assert!(!is_retryable(&CompletionError::ResponseError(
+            "invalid json".to_string()
+        )));
+    }
+}
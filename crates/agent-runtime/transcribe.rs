@@ -0,0 +1,123 @@
+use crate::errors::CustomError;
+use crate::jwt::Jwt;
+use axum::body::Body;
+use axum::extract::Multipart;
+use axum::response::Response;
+use axum::Extension;
+use db::queries::models;
+use db::{Model, ModelType, Pool, Transaction};
+use reqwest::header::CONTENT_TYPE;
+use speech_to_text::TranscriptionError;
+
+use super::UITranscribe;
+
+const AUDIO_EXTENSIONS: [&str; 10] = [
+    "flac", "m4a", "mp3", "mp4", "mpeg", "mpga", "oga", "ogg", "wav", "webm",
+];
+
+// Called from the front end to turn recorded audio into text
+pub async fn transcribe(
+    UITranscribe {}: UITranscribe,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    mut multipart: Multipart,
+) -> Result<Response<Body>, CustomError> {
+    let mut audio: Option<(String, String, Vec<u8>)> = None;
+    let mut language: Option<String> = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| CustomError::FaultySetup(e.to_string()))?
+    {
+        match field.name().unwrap_or("") {
+            "file" => {
+                let file_name = field.file_name().unwrap_or("audio.webm").to_string();
+                let mime_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let bytes = field
+                    .bytes()
+                    .await
+                    .map_err(|e| CustomError::FaultySetup(e.to_string()))?;
+                audio = Some((file_name, mime_type, bytes.to_vec()));
+            }
+            "language" => {
+                language = field.text().await.ok().filter(|text| !text.is_empty());
+            }
+            _ => {}
+        }
+    }
+
+    let Some((file_name, mime_type, bytes)) = audio else {
+        return Err(CustomError::FaultySetup(
+            "No audio file supplied".to_string(),
+        ));
+    };
+
+    let mut db_client = pool.get().await?;
+    let transaction = db_client.transaction().await?;
+    db::authz::set_row_level_security_user_id(&transaction, current_user.sub.to_string()).await?;
+    let model = speech_to_text_model(&transaction)
+        .await?
+        .ok_or_else(|| CustomError::FaultySetup("No speech to text model".to_string()))?;
+    transaction.commit().await?;
+
+    let response = speech_to_text::request(&model, &file_name, &mime_type, bytes, language)
+        .map_err(|e| match e {
+            TranscriptionError::TooLarge => CustomError::Limits(e.to_string()),
+            TranscriptionError::Request(message) => CustomError::FaultySetup(message),
+        })?
+        .send()
+        .await
+        .map_err(|e| {
+            tracing::error!("Error calling model: {:?}", e);
+            CustomError::FaultySetup("Error calling model".to_string())
+        })?;
+
+    let response_builder = Response::builder()
+        .status(response.status().as_u16())
+        .header(CONTENT_TYPE, "application/json");
+    response_builder
+        .body(Body::from_stream(response.bytes_stream()))
+        .map_err(|e| CustomError::FaultySetup(e.to_string()))
+}
+
+/// Returns the speech to text model visible to the current transaction, if one is configured.
+pub async fn speech_to_text_model(
+    transaction: &Transaction<'_>,
+) -> Result<Option<Model>, db::TokioPostgresError> {
+    Ok(models::models()
+        .bind(transaction, &ModelType::SpeechToText)
+        .all()
+        .await?
+        .into_iter()
+        .next())
+}
+
+/// Audio attachments are detected by mime type first and fall back to the file extension,
+/// as browsers often upload recordings as `application/octet-stream`.
+pub fn is_audio(file_name: &str, mime_type: &str) -> bool {
+    if mime_type.starts_with("audio/") {
+        return true;
+    }
+    file_name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase())
+        .is_some_and(|extension| AUDIO_EXTENSIONS.contains(&extension.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_audio() {
+        assert!(is_audio("standup.bin", "audio/mpeg"));
+        assert!(is_audio("Meeting.M4A", "application/octet-stream"));
+        assert!(is_audio("recording.webm", ""));
+        assert!(!is_audio("report.pdf", "application/pdf"));
+        assert!(!is_audio("notes", "text/plain"));
+    }
+}
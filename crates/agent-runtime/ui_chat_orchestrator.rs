@@ -1,26 +1,35 @@
 use crate::chat_request::{create_request, RigChatRequest};
 use crate::errors::CustomError;
 use crate::jwt::Jwt;
+use crate::moderation::{is_blocked, FlaggedCategory, OutputGuard};
+use crate::providers::{self, ProviderError, ProviderModel};
 pub(crate) use crate::result_sink::ResultSink;
 use crate::result_sink::{DbResultSink, SaveRequest};
+use crate::routing::{self, DbHealthTracker, HealthTracker};
 use crate::user_config::UserConfig;
 use axum::response::{sse::Event, Sse};
 use axum::Extension;
 use db::{ChatStatus, Pool};
-use rig::client::CompletionClient;
-use rig::completion::{CompletionModel as _, GetTokenUsage, Usage};
+use rig::completion::{CompletionError, CompletionModel, CompletionRequest, GetTokenUsage, Usage};
 use rig::message::ReasoningContent;
-use rig::providers::openai;
 use rig::streaming::StreamedAssistantContent;
 use serde_json::json;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::StreamExt;
-use tool_runtime::{Reasoning, ToolCall};
+use tool_runtime::{PendingApproval, Reasoning, ToolCall};
+use tracing::{Instrument, Span};
 
 use super::{limits, UICompletions};
 
+/// With streaming moderation the guard model sees the response every time this much new
+/// text has arrived.
+const OUTPUT_WINDOW_CHARS: usize = 800;
+const RETRACTED_MESSAGE: &str = "This response was withheld because it violated our guidelines";
+const UNCHECKED_MESSAGE: &str =
+    "This response was withheld because it couldn't be checked against our guidelines";
+
 /// Formats an SSE message for a streaming text chunk.
 fn event_data_for_text(delta: String) -> String {
     json!({
@@ -41,6 +50,28 @@ fn event_data_for_done() -> String {
     .to_string()
 }
 
+/// Formats an SSE message for a response whose integration calls need the user's approval.
+fn event_data_for_approval_required(approvals: Vec<PendingApproval>) -> String {
+    json!({
+        "type": "approval_required",
+        "data": {
+            "approvals": approvals
+        }
+    })
+    .to_string()
+}
+
+/// Formats an SSE message telling the client to replace the streamed text.
+fn event_data_for_retracted(message: String) -> String {
+    json!({
+        "type": "retracted",
+        "data": {
+            "message": message
+        }
+    })
+    .to_string()
+}
+
 /// Formats an SSE error event payload.
 fn event_data_for_error(message: String) -> Event {
     Event::default().data(
@@ -64,6 +95,14 @@ pub enum GenerationEvent {
         tool_calls: Option<Vec<ToolCall>>,
         reasoning: Option<Vec<Reasoning>>,
         usage: Option<Usage>,
+        model_endpoint_id: Option<i32>,
+        output_flags: Vec<FlaggedCategory>,
+    },
+    /// Output moderation withdrew the response that was streamed so far.
+    Retracted {
+        message: String,
+        output_flags: Vec<FlaggedCategory>,
+        model_endpoint_id: Option<i32>,
     },
 }
 
@@ -75,6 +114,8 @@ pub(crate) enum StreamOutcome {
         tool_calls: Option<Vec<ToolCall>>,
         reasoning: Option<Vec<Reasoning>>,
         usage: Option<Usage>,
+        model_endpoint_id: Option<i32>,
+        output_flags: Vec<FlaggedCategory>,
     },
 }
 
@@ -102,19 +143,48 @@ where
                         tool_calls,
                         reasoning,
                         usage,
+                        model_endpoint_id,
+                        output_flags,
                     } => {
-                        result_sink
+                        let pending_approvals = result_sink
                             .save(SaveRequest {
                                 snapshot: &snapshot,
                                 tool_calls,
                                 reasoning,
                                 usage,
+                                model_endpoint_id,
+                                output_flags,
                                 chat_id,
                                 sub: &sub,
                                 status: ChatStatus::Success,
                             })
                             .await;
-                        Ok(Event::default().data(event_data_for_done()))
+                        if pending_approvals.is_empty() {
+                            Ok(Event::default().data(event_data_for_done()))
+                        } else {
+                            Ok(Event::default()
+                                .data(event_data_for_approval_required(pending_approvals)))
+                        }
+                    }
+                    GenerationEvent::Retracted {
+                        message,
+                        output_flags,
+                        model_endpoint_id,
+                    } => {
+                        result_sink
+                            .save(SaveRequest {
+                                snapshot: &message,
+                                tool_calls: None,
+                                reasoning: None,
+                                usage: None,
+                                model_endpoint_id,
+                                output_flags,
+                                chat_id,
+                                sub: &sub,
+                                status: ChatStatus::Error,
+                            })
+                            .await;
+                        Ok(Event::default().data(event_data_for_retracted(message)))
                     }
                 },
                 Err(e) => {
@@ -125,6 +195,8 @@ where
                             tool_calls: None,
                             reasoning: None,
                             usage: None,
+                            model_endpoint_id: None,
+                            output_flags: vec![],
                             chat_id,
                             sub: &sub,
                             status: ChatStatus::Error,
@@ -148,17 +220,18 @@ pub async fn chat_generate(
 
     match create_request(&pool, &current_user, chat_id, &user_config).await {
         Ok(request) => {
-            let is_limit_breached =
-                limits::is_limit_exceeded_from_pool(&pool, request.model_id, request.user_id)
+            let refusal =
+                limits::refusal_from_pool(&pool, chat_id, request.model_id, request.user_id)
                     .await?;
 
             let (sender, receiver) = mpsc::channel::<Result<GenerationEvent, axum::Error>>(10);
             let result_sink_clone = Arc::clone(&result_sink);
+            let health = DbHealthTracker::new(pool.clone());
             let sub_for_save = current_user.sub.clone();
 
-            tokio::spawn(async move {
-                if is_limit_breached {
-                    let limit_message = "You have exceeded your token limit for this model";
+            // The answer outlives the request, but its spans belong to the request's trace.
+            let generate = async move {
+                if let Some(limit_message) = refusal {
                     if sender
                         .send(Err(axum::Error::new(std::io::Error::other(limit_message))))
                         .await
@@ -170,6 +243,8 @@ pub async fn chat_generate(
                                 tool_calls: None,
                                 reasoning: None,
                                 usage: None,
+                                model_endpoint_id: None,
+                                output_flags: vec![],
                                 chat_id,
                                 sub: &sub_for_save,
                                 status: ChatStatus::Error,
@@ -179,13 +254,15 @@ pub async fn chat_generate(
                     return;
                 }
 
-                match stream_chat_with_rig(request, sender.clone()).await {
+                match stream_chat_with_rig(request, sender.clone(), &health).await {
                     Ok(StreamOutcome::Completed) => {}
                     Ok(StreamOutcome::ClientDisconnected {
                         snapshot,
                         tool_calls,
                         reasoning,
                         usage,
+                        model_endpoint_id,
+                        output_flags,
                     }) => {
                         result_sink_clone
                             .save(SaveRequest {
@@ -193,6 +270,8 @@ pub async fn chat_generate(
                                 tool_calls,
                                 reasoning,
                                 usage,
+                                model_endpoint_id,
+                                output_flags,
                                 chat_id,
                                 sub: &sub_for_save,
                                 status: ChatStatus::Error,
@@ -215,6 +294,8 @@ pub async fn chat_generate(
                                     tool_calls: None,
                                     reasoning: None,
                                     usage: None,
+                                    model_endpoint_id: None,
+                                    output_flags: vec![],
                                     chat_id,
                                     sub: &sub_for_save,
                                     status: ChatStatus::Error,
@@ -223,7 +304,8 @@ pub async fn chat_generate(
                         }
                     }
                 }
-            });
+            };
+            tokio::spawn(generate.in_current_span());
 
             let sub_arc = Arc::new(current_user.sub.clone());
             let receiver_stream = ReceiverStream::new(receiver);
@@ -238,6 +320,8 @@ pub async fn chat_generate(
                     tool_calls: None,
                     reasoning: None,
                     usage: None,
+                    model_endpoint_id: None,
+                    output_flags: vec![],
                     chat_id,
                     sub: &current_user.sub,
                     status: ChatStatus::Error,
@@ -248,25 +332,122 @@ pub async fn chat_generate(
     }
 }
 
-/// Executes a streaming rig completion and publishes intermediate events.
+/// Streams the completion from the first healthy endpoint of the model. Connection errors,
+/// rate limits and server errors fail over to the next endpoint until a token reaches the client.
 pub(crate) async fn stream_chat_with_rig(
     request: RigChatRequest,
     sender: mpsc::Sender<Result<GenerationEvent, axum::Error>>,
-) -> Result<StreamOutcome, Box<dyn std::error::Error + Send + Sync>> {
-    let api_key = request.api_key.as_deref().unwrap_or("");
-    let client = openai::Client::builder()
-        .api_key(api_key)
-        .base_url(&request.base_url)
-        .build()?;
-    let model = client
-        .completion_model(&request.model_name)
-        .completions_api();
-    let mut stream = model.stream(request.completion).await?;
+    health: &dyn HealthTracker,
+) -> Result<StreamOutcome, ProviderError> {
+    let mut last_error: Option<ProviderError> = None;
+    let output_guard = request.output_guard.as_deref();
+
+    for endpoint in routing::route(request.endpoints) {
+        let model = match ProviderModel::new(
+            endpoint.provider_kind,
+            &endpoint.base_url,
+            endpoint.api_key.as_deref(),
+            &request.model_name,
+        ) {
+            Ok(model) => model,
+            // A configuration error isn't the provider failing, so the circuit stays closed.
+            Err(err) => {
+                tracing::warn!("Skipping misconfigured endpoint {}: {}", endpoint.id, err);
+                last_error = Some(err);
+                continue;
+            }
+        };
+
+        let completion = request.completion.clone();
+        let sender = sender.clone();
+        let span = providers::model_call_span(endpoint.provider_kind, &request.model_name);
+        span.record("bionic.model_endpoint_id", endpoint.id);
+        let result = async {
+            match model {
+                ProviderModel::OpenAI(model) => {
+                    stream_completion(model, completion, sender, endpoint.id, output_guard).await
+                }
+                ProviderModel::Anthropic(model) => {
+                    stream_completion(model, completion, sender, endpoint.id, output_guard).await
+                }
+                ProviderModel::Gemini(model) => {
+                    stream_completion(model, completion, sender, endpoint.id, output_guard).await
+                }
+                ProviderModel::AzureOpenAI(model) => {
+                    stream_completion(model, completion, sender, endpoint.id, output_guard).await
+                }
+                ProviderModel::Ollama(model) => {
+                    stream_completion(model, completion, sender, endpoint.id, output_guard).await
+                }
+            }
+        }
+        .instrument(span.clone())
+        .await;
+
+        match result {
+            Ok(outcome) => {
+                health.record_success(endpoint.id).await;
+                return Ok(outcome);
+            }
+            Err(failure) => {
+                providers::record_failure(&span, &failure.error);
+                if failure.retryable {
+                    health
+                        .record_failure(endpoint.id, &failure.error.to_string())
+                        .await;
+                }
+                if !failure.retryable || failure.streamed {
+                    return Err(failure.error);
+                }
+                tracing::warn!(
+                    "Endpoint {} failed, trying the next one: {}",
+                    endpoint.id,
+                    failure.error
+                );
+                last_error = Some(failure.error);
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| "The model has no endpoints configured".into()))
+}
+
+/// A failed attempt against one endpoint.
+struct StreamFailure {
+    error: ProviderError,
+    retryable: bool,
+    /// Text already reached the client, so another endpoint can't take over.
+    streamed: bool,
+}
+
+impl StreamFailure {
+    fn from_completion(error: CompletionError, streamed: bool) -> Self {
+        Self {
+            retryable: routing::is_retryable(&error),
+            error: Box::new(error),
+            streamed,
+        }
+    }
+}
+
+async fn stream_completion<M: CompletionModel>(
+    model: M,
+    completion: CompletionRequest,
+    sender: mpsc::Sender<Result<GenerationEvent, axum::Error>>,
+    model_endpoint_id: i32,
+    output_guard: Option<&dyn OutputGuard>,
+) -> Result<StreamOutcome, StreamFailure> {
+    let mut stream = model
+        .stream(completion)
+        .await
+        .map_err(|err| StreamFailure::from_completion(err, false))?;
 
     let mut snapshot = String::new();
     let mut tool_calls: Vec<ToolCall> = Vec::new();
     let mut reasoning: Vec<Reasoning> = Vec::new();
     let mut usage: Option<Usage> = None;
+    let mut output_flags: Vec<FlaggedCategory> = Vec::new();
+    let mut checked_len = 0;
 
     while let Some(item) = stream.next().await {
         match item {
@@ -279,6 +460,20 @@ pub(crate) async fn stream_chat_with_rig(
                     .await
                     .is_err()
                 {
+                    // Moderate what is about to be kept even though nobody is watching.
+                    if let Some(guard) = output_guard {
+                        if let Some(outcome) = moderate_output(
+                            guard,
+                            &snapshot,
+                            &mut output_flags,
+                            &sender,
+                            model_endpoint_id,
+                        )
+                        .await
+                        {
+                            return Ok(outcome);
+                        }
+                    }
                     return Ok(StreamOutcome::ClientDisconnected {
                         snapshot,
                         tool_calls: if tool_calls.is_empty() {
@@ -292,8 +487,27 @@ pub(crate) async fn stream_chat_with_rig(
                             Some(reasoning)
                         },
                         usage,
+                        model_endpoint_id: Some(model_endpoint_id),
+                        output_flags,
                     });
                 }
+
+                if let Some(guard) = output_guard.filter(|guard| guard.streaming()) {
+                    if snapshot.len() - checked_len >= OUTPUT_WINDOW_CHARS {
+                        checked_len = snapshot.len();
+                        if let Some(outcome) = moderate_output(
+                            guard,
+                            &snapshot,
+                            &mut output_flags,
+                            &sender,
+                            model_endpoint_id,
+                        )
+                        .await
+                        {
+                            return Ok(outcome);
+                        }
+                    }
+                }
             }
             Ok(StreamedAssistantContent::ToolCall { tool_call, .. }) => {
                 tool_calls.push(tool_call);
@@ -310,9 +524,13 @@ pub(crate) async fn stream_chat_with_rig(
             }
             Ok(StreamedAssistantContent::Unknown(_)) => {}
             Ok(StreamedAssistantContent::Final(final_response)) => {
-                usage = Some(final_response.token_usage());
+                let final_usage = final_response.token_usage();
+                providers::record_usage(&Span::current(), &final_usage);
+                usage = Some(final_usage);
+            }
+            Err(err) => {
+                return Err(StreamFailure::from_completion(err, !snapshot.is_empty()));
             }
-            Err(err) => return Err(Box::new(err)),
         }
     }
 
@@ -328,9 +546,27 @@ pub(crate) async fn stream_chat_with_rig(
     };
 
     if snapshot.trim().is_empty() && tool_calls_for_end.is_none() {
-        return Err(Box::new(std::io::Error::other(
-            "Model returned an empty response",
-        )));
+        return Err(StreamFailure {
+            error: Box::new(std::io::Error::other("Model returned an empty response")),
+            retryable: false,
+            streamed: false,
+        });
+    }
+
+    if let Some(guard) = output_guard {
+        if !snapshot.trim().is_empty() && checked_len < snapshot.len() {
+            if let Some(outcome) = moderate_output(
+                guard,
+                &snapshot,
+                &mut output_flags,
+                &sender,
+                model_endpoint_id,
+            )
+            .await
+            {
+                return Ok(outcome);
+            }
+        }
     }
 
     if sender
@@ -339,6 +575,8 @@ pub(crate) async fn stream_chat_with_rig(
             tool_calls: tool_calls_for_end,
             reasoning: reasoning_for_end,
             usage,
+            model_endpoint_id: Some(model_endpoint_id),
+            output_flags: output_flags.clone(),
         }))
         .await
         .is_err()
@@ -356,15 +594,72 @@ pub(crate) async fn stream_chat_with_rig(
                 Some(reasoning)
             },
             usage,
+            model_endpoint_id: Some(model_endpoint_id),
+            output_flags,
         });
     }
 
     Ok(StreamOutcome::Completed)
 }
 
+/// Runs output moderation over the response so far. Categories that only flag are collected
+/// for saving with the response. When the policy blocks, or the guard can't be reached, the
+/// response is retracted and the outcome of the stream is returned.
+async fn moderate_output(
+    guard: &dyn OutputGuard,
+    snapshot: &str,
+    output_flags: &mut Vec<FlaggedCategory>,
+    sender: &mpsc::Sender<Result<GenerationEvent, axum::Error>>,
+    model_endpoint_id: i32,
+) -> Option<StreamOutcome> {
+    let message = match guard.check(snapshot).await {
+        Ok(flags) => {
+            for flag in flags {
+                if !output_flags.contains(&flag) {
+                    output_flags.push(flag);
+                }
+            }
+            if !is_blocked(output_flags) {
+                return None;
+            }
+            RETRACTED_MESSAGE
+        }
+        Err(status) => {
+            tracing::error!("Output moderation failed: {}", status);
+            UNCHECKED_MESSAGE
+        }
+    };
+
+    let output_flags = std::mem::take(output_flags);
+    if sender
+        .send(Ok(GenerationEvent::Retracted {
+            message: message.to_string(),
+            output_flags: output_flags.clone(),
+            model_endpoint_id: Some(model_endpoint_id),
+        }))
+        .await
+        .is_err()
+    {
+        return Some(StreamOutcome::ClientDisconnected {
+            snapshot: message.to_string(),
+            tool_calls: None,
+            reasoning: None,
+            usage: None,
+            model_endpoint_id: Some(model_endpoint_id),
+            output_flags,
+        });
+    }
+    Some(StreamOutcome::Completed)
+}
+
 fn push_reasoning(reasoning: &mut Vec<Reasoning>, reasoning_item: Reasoning) {
     if let Some(id) = reasoning_item.id.as_deref() {
         reasoning.retain(|existing| existing.id.as_deref() != Some(id));
+    } else if reasoning.last().is_some_and(|last| {
+        // Anthropic streams thinking deltas, then repeats the whole block with its signature.
+        last.id.is_none() && reasoning_text(last) == reasoning_text(&reasoning_item)
+    }) {
+        reasoning.pop();
     }
     reasoning.push(reasoning_item);
 }
@@ -389,3 +684,14 @@ fn reasoning_ends_with_text(reasoning: &Reasoning) -> bool {
         Some(ReasoningContent::Text { .. })
     )
 }
+
+fn reasoning_text(reasoning: &Reasoning) -> String {
+    reasoning
+        .content
+        .iter()
+        .filter_map(|content| match content {
+            ReasoningContent::Text { text, .. } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect()
+}
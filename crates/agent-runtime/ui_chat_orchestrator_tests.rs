@@ -1,6 +1,8 @@
 #![allow(non_snake_case)]
 use crate::chat_request::RigChatRequest;
+use crate::moderation::{FlaggedCategory, OutputGuard};
 use crate::result_sink::SaveRequest;
+use crate::routing::HealthTracker;
 use crate::ui_chat_orchestrator::{
     build_event_stream, stream_chat_with_rig, GenerationEvent, ResultSink,
 };
@@ -9,7 +11,7 @@ use axum::body::Body;
 use axum::http::{header, Response, StatusCode};
 use axum::routing::post;
 use axum::Router;
-use db::ChatStatus;
+use db::{ChatStatus, ModelEndpoint, ModerationAction, PromptFlagType, ProviderKind};
 use rig::completion::{CompletionRequest, Message};
 use rig::OneOrMany;
 use serde_json::json;
@@ -18,27 +20,89 @@ use tokio::net::TcpListener;
 use tokio::pin;
 use tokio::sync::mpsc;
 use tokio_stream::StreamExt;
-use tool_runtime::{ToolCall, ToolCallFunction, ToolDefinition};
+use tool_runtime::{PendingApproval, ToolCall, ToolCallFunction, ToolDefinition};
 
 #[derive(Debug, Clone)]
 struct SaveCall {
     snapshot: String,
     tool_calls_len: Option<usize>,
+    model_endpoint_id: Option<i32>,
+    output_flags: Vec<FlaggedCategory>,
     status: ChatStatus,
 }
 
 struct FakeResultSink {
     calls: Mutex<Vec<SaveCall>>,
+    pending_approvals: Vec<PendingApproval>,
 }
 
 #[async_trait]
 impl ResultSink for FakeResultSink {
-    async fn save(&self, request: SaveRequest<'_>) {
+    async fn save(&self, request: SaveRequest<'_>) -> Vec<PendingApproval> {
         self.calls.lock().unwrap().push(SaveCall {
             snapshot: request.snapshot.to_string(),
             tool_calls_len: request.tool_calls.as_ref().map(|calls| calls.len()),
+            model_endpoint_id: request.model_endpoint_id,
+            output_flags: request.output_flags,
             status: request.status,
         });
+        self.pending_approvals.clone()
+    }
+}
+
+#[derive(Default)]
+struct FakeHealthTracker {
+    successes: Mutex<Vec<i32>>,
+    failures: Mutex<Vec<i32>>,
+}
+
+#[async_trait]
+impl HealthTracker for FakeHealthTracker {
+    async fn record_success(&self, endpoint_id: i32) {
+        self.successes.lock().unwrap().push(endpoint_id);
+    }
+
+    async fn record_failure(&self, endpoint_id: i32, _error: &str) {
+        self.failures.lock().unwrap().push(endpoint_id);
+    }
+}
+
+/// Flags any response containing `trigger` with the given action and remembers how much
+/// of the response it was shown each time.
+struct FakeOutputGuard {
+    streaming: bool,
+    trigger: &'static str,
+    action: ModerationAction,
+    checked: Mutex<Vec<usize>>,
+}
+
+impl FakeOutputGuard {
+    fn new(streaming: bool, trigger: &'static str, action: ModerationAction) -> Self {
+        Self {
+            streaming,
+            trigger,
+            action,
+            checked: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl OutputGuard for FakeOutputGuard {
+    fn streaming(&self) -> bool {
+        self.streaming
+    }
+
+    async fn check(&self, response: &str) -> Result<Vec<FlaggedCategory>, reqwest::StatusCode> {
+        self.checked.lock().unwrap().push(response.len());
+        if response.contains(self.trigger) {
+            Ok(vec![FlaggedCategory {
+                flag_type: PromptFlagType::S10,
+                action: self.action,
+            }])
+        } else {
+            Ok(vec![])
+        }
     }
 }
 
@@ -46,6 +110,7 @@ impl ResultSink for FakeResultSink {
 async fn event_stream_saves_on_end_with_tool_calls() {
     let result_sink = Arc::new(FakeResultSink {
         calls: Mutex::new(Vec::new()),
+        pending_approvals: vec![],
     });
     let result_sink_dyn: Arc<dyn ResultSink> = result_sink.clone();
     let sub = Arc::new("user-1".to_string());
@@ -70,6 +135,8 @@ async fn event_stream_saves_on_end_with_tool_calls() {
             tool_calls: Some(tool_calls),
             reasoning: None,
             usage: None,
+            model_endpoint_id: Some(3),
+            output_flags: vec![],
         }),
     ]);
 
@@ -82,13 +149,51 @@ async fn event_stream_saves_on_end_with_tool_calls() {
     assert_eq!(calls.len(), 1);
     assert_eq!(calls[0].snapshot, "final");
     assert_eq!(calls[0].tool_calls_len, Some(1));
+    assert_eq!(calls[0].model_endpoint_id, Some(3));
     assert_eq!(calls[0].status, ChatStatus::Success);
 }
 
+#[tokio::test]
+async fn event_stream_reports_calls_waiting_for_approval() {
+    let result_sink = Arc::new(FakeResultSink {
+        calls: Mutex::new(Vec::new()),
+        pending_approvals: vec![PendingApproval {
+            approval_id: 5,
+            function_name: "mail_sendemail".to_string(),
+            method: "POST".to_string(),
+            url: "https://mail.example.com/send".to_string(),
+        }],
+    });
+    let result_sink_dyn: Arc<dyn ResultSink> = result_sink.clone();
+    let sub = Arc::new("user-1".to_string());
+
+    let input = tokio_stream::iter(vec![Ok(GenerationEvent::End {
+        snapshot: "".to_string(),
+        tool_calls: None,
+        reasoning: None,
+        usage: None,
+        model_endpoint_id: None,
+        output_flags: vec![],
+    })]);
+
+    let stream = build_event_stream(input, Arc::clone(&result_sink_dyn), 42, sub);
+    pin!(stream);
+    let mut events = vec![];
+    while let Some(event) = stream.next().await {
+        events.push(format!("{:?}", event.expect("expected Ok(event)")));
+    }
+
+    assert_eq!(events.len(), 1);
+    assert!(events[0].contains("approval_required"));
+    assert!(events[0].contains("https://mail.example.com/send"));
+    assert!(!events[0].contains("done"));
+}
+
 #[tokio::test]
 async fn event_stream_saves_on_error() {
     let result_sink = Arc::new(FakeResultSink {
         calls: Mutex::new(Vec::new()),
+        pending_approvals: vec![],
     });
     let result_sink_dyn: Arc<dyn ResultSink> = result_sink.clone();
     let sub = Arc::new("user-1".to_string());
@@ -107,10 +212,50 @@ async fn event_stream_saves_on_error() {
     assert!(calls[0].snapshot.contains("boom"));
 }
 
+#[tokio::test]
+async fn event_stream_saves_retraction_as_error_with_flags() {
+    let result_sink = Arc::new(FakeResultSink {
+        calls: Mutex::new(Vec::new()),
+        pending_approvals: vec![],
+    });
+    let result_sink_dyn: Arc<dyn ResultSink> = result_sink.clone();
+    let sub = Arc::new("user-1".to_string());
+    let flag = FlaggedCategory {
+        flag_type: PromptFlagType::S1,
+        action: ModerationAction::Block,
+    };
+
+    let input = tokio_stream::iter(vec![
+        Ok(GenerationEvent::Text {
+            delta: "partial".to_string(),
+        }),
+        Ok(GenerationEvent::Retracted {
+            message: "withheld".to_string(),
+            output_flags: vec![flag],
+            model_endpoint_id: Some(2),
+        }),
+    ]);
+
+    let stream = build_event_stream(input, Arc::clone(&result_sink_dyn), 9, sub);
+    pin!(stream);
+    let mut events = vec![];
+    while let Some(event) = stream.next().await {
+        events.push(format!("{:?}", event.expect("expected Ok(event)")));
+    }
+
+    assert!(events[1].contains("retracted"));
+    let calls = result_sink.calls.lock().unwrap().clone();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].snapshot, "withheld");
+    assert_eq!(calls[0].status, ChatStatus::Error);
+    assert_eq!(calls[0].output_flags, vec![flag]);
+}
+
 #[tokio::test]
 async fn event_stream_emits_error_event() {
     let result_sink = Arc::new(FakeResultSink {
         calls: Mutex::new(Vec::new()),
+        pending_approvals: vec![],
     });
     let result_sink_dyn: Arc<dyn ResultSink> = result_sink.clone();
     let sub = Arc::new("user-1".to_string());
@@ -130,10 +275,38 @@ async fn event_stream_emits_error_event() {
 }
 
 fn tool_enabled_request(base_url: String) -> RigChatRequest {
-    RigChatRequest {
-        model_name: "test-model".to_string(),
+    provider_request(ProviderKind::OpenAI, base_url)
+}
+
+fn provider_request(provider_kind: ProviderKind, base_url: String) -> RigChatRequest {
+    routed_request(vec![endpoint(1, provider_kind, base_url, 0)])
+}
+
+fn endpoint(
+    id: i32,
+    provider_kind: ProviderKind,
+    base_url: String,
+    priority: i32,
+) -> ModelEndpoint {
+    ModelEndpoint {
+        id,
+        model_id: 1,
+        is_primary: id == 1,
+        provider_kind,
         base_url,
         api_key: Some("test-key".to_string()),
+        priority,
+        weight: 1,
+        consecutive_failures: 0,
+        circuit_open: false,
+        last_error: None,
+    }
+}
+
+fn routed_request(endpoints: Vec<ModelEndpoint>) -> RigChatRequest {
+    RigChatRequest {
+        model_name: "test-model".to_string(),
+        endpoints,
         completion: CompletionRequest {
             model: None,
             preamble: None,
@@ -158,6 +331,7 @@ fn tool_enabled_request(base_url: String) -> RigChatRequest {
         },
         model_id: 1,
         user_id: 1,
+        output_guard: None,
     }
 }
 
@@ -196,9 +370,13 @@ async fn rig_stream_uses_chat_completions_for_tool_enabled_requests() {
     .await;
     let (sender, mut receiver) = mpsc::channel(8);
 
-    stream_chat_with_rig(tool_enabled_request(base_url), sender)
-        .await
-        .expect("chat completion stream should succeed");
+    stream_chat_with_rig(
+        tool_enabled_request(base_url),
+        sender,
+        &FakeHealthTracker::default(),
+    )
+    .await
+    .expect("chat completion stream should succeed");
 
     let mut text = String::new();
     let mut ended = false;
@@ -209,6 +387,7 @@ async fn rig_stream_uses_chat_completions_for_tool_enabled_requests() {
                 assert_eq!(snapshot, "Hello");
                 ended = true;
             }
+            GenerationEvent::Retracted { .. } => panic!("nothing should be retracted"),
         }
     }
 
@@ -233,12 +412,403 @@ async fn rig_stream_preserves_provider_error_body() {
             .await;
     let (sender, _receiver) = mpsc::channel(8);
 
-    let error = stream_chat_with_rig(tool_enabled_request(base_url), sender)
-        .await
-        .expect_err("provider error should fail the stream");
+    let error = stream_chat_with_rig(
+        tool_enabled_request(base_url),
+        sender,
+        &FakeHealthTracker::default(),
+    )
+    .await
+    .expect_err("provider error should fail the stream");
     let message = error.to_string();
 
     assert!(message.contains("404"));
     assert!(message.contains("model does not exist"));
     assert!(message.contains("model_not_found"));
 }
+
+fn event_stream_response(body: String) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+struct CollectedStream {
+    text: String,
+    snapshot: String,
+    tool_calls: Vec<String>,
+    reasoning: usize,
+    usage: Option<rig::completion::Usage>,
+    output_flags: Vec<FlaggedCategory>,
+    retracted: Option<String>,
+}
+
+async fn collect_stream(request: RigChatRequest) -> CollectedStream {
+    let (sender, mut receiver) = mpsc::channel(16);
+    stream_chat_with_rig(request, sender, &FakeHealthTracker::default())
+        .await
+        .expect("provider stream should succeed");
+
+    let mut collected = CollectedStream {
+        text: String::new(),
+        snapshot: String::new(),
+        tool_calls: vec![],
+        reasoning: 0,
+        usage: None,
+        output_flags: vec![],
+        retracted: None,
+    };
+    while let Some(event) = receiver.recv().await {
+        match event.expect("generation event should succeed") {
+            GenerationEvent::Text { delta } => collected.text.push_str(&delta),
+            GenerationEvent::End {
+                snapshot,
+                tool_calls,
+                reasoning,
+                usage,
+                output_flags,
+                ..
+            } => {
+                collected.snapshot = snapshot;
+                collected.tool_calls = tool_calls
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|call| call.function.name)
+                    .collect();
+                collected.reasoning = reasoning.map(|items| items.len()).unwrap_or_default();
+                collected.usage = usage;
+                collected.output_flags = output_flags;
+            }
+            GenerationEvent::Retracted { message, .. } => collected.retracted = Some(message),
+        }
+    }
+    collected
+}
+
+async fn anthropic_messages() -> Response<Body> {
+    let events = [
+        json!({"type": "message_start", "message": {
+            "id": "msg_1", "role": "assistant", "content": [], "model": "test-model",
+            "stop_reason": null, "stop_sequence": null,
+            "usage": {"input_tokens": 12, "output_tokens": 1}
+        }}),
+        json!({"type": "content_block_start", "index": 0,
+            "content_block": {"type": "thinking", "thinking": "", "signature": ""}}),
+        json!({"type": "content_block_delta", "index": 0,
+            "delta": {"type": "thinking_delta", "thinking": "The user wants a greeting."}}),
+        json!({"type": "content_block_stop", "index": 0}),
+        json!({"type": "content_block_start", "index": 1,
+            "content_block": {"type": "text", "text": ""}}),
+        json!({"type": "content_block_delta", "index": 1,
+            "delta": {"type": "text_delta", "text": "Hello"}}),
+        json!({"type": "content_block_stop", "index": 1}),
+        json!({"type": "content_block_start", "index": 2,
+            "content_block": {"type": "tool_use", "id": "toolu_1", "name": "run_bash", "input": {}}}),
+        json!({"type": "content_block_delta", "index": 2,
+            "delta": {"type": "input_json_delta", "partial_json": "{\"commands\":\"ls\"}"}}),
+        json!({"type": "content_block_stop", "index": 2}),
+        json!({"type": "message_delta", "delta": {"stop_reason": "tool_use", "stop_sequence": null},
+            "usage": {"output_tokens": 7}}),
+        json!({"type": "message_stop"}),
+    ];
+    let body = events
+        .iter()
+        .map(|event| {
+            format!(
+                "event: {}\ndata: {}\n\n",
+                event["type"].as_str().unwrap(),
+                event
+            )
+        })
+        .collect();
+    event_stream_response(body)
+}
+
+#[tokio::test]
+async fn rig_stream_supports_anthropic_messages() {
+    let base_url =
+        start_mock_provider(Router::new().route("/v1/messages", post(anthropic_messages))).await;
+
+    let collected = collect_stream(provider_request(ProviderKind::Anthropic, base_url)).await;
+
+    assert_eq!(collected.text, "Hello");
+    assert_eq!(collected.snapshot, "Hello");
+    assert_eq!(collected.tool_calls, vec!["run_bash".to_string()]);
+    assert_eq!(collected.reasoning, 1);
+    let usage = collected.usage.expect("usage should be reported");
+    assert_eq!(usage.input_tokens, 12);
+    assert_eq!(usage.output_tokens, 7);
+}
+
+async fn gemini_stream_generate_content() -> Response<Body> {
+    let chunks = [
+        json!({"candidates": [{"content": {"role": "model", "parts": [{"text": "Hello"}]}}]}),
+        json!({
+            "candidates": [{
+                "content": {"role": "model", "parts": [
+                    {"functionCall": {"name": "run_bash", "args": {"commands": "ls"}}}
+                ]},
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {"promptTokenCount": 9, "candidatesTokenCount": 4, "totalTokenCount": 13}
+        }),
+    ];
+    let body = chunks
+        .iter()
+        .map(|chunk| format!("data: {chunk}\n\n"))
+        .collect();
+    event_stream_response(body)
+}
+
+#[tokio::test]
+async fn rig_stream_supports_gemini() {
+    let base_url = start_mock_provider(Router::new().route(
+        "/v1beta/models/{model}",
+        post(gemini_stream_generate_content),
+    ))
+    .await;
+    let base_url = base_url.trim_end_matches("/v1").to_string();
+
+    let collected = collect_stream(provider_request(ProviderKind::Gemini, base_url)).await;
+
+    assert_eq!(collected.snapshot, "Hello");
+    assert_eq!(collected.tool_calls, vec!["run_bash".to_string()]);
+    let usage = collected.usage.expect("usage should be reported");
+    assert_eq!(usage.input_tokens, 9);
+    assert_eq!(usage.output_tokens, 4);
+}
+
+#[tokio::test]
+async fn rig_stream_supports_azure_openai_deployments() {
+    let base_url = start_mock_provider(Router::new().route(
+        "/openai/deployments/test-model/chat/completions",
+        post(successful_chat_completion),
+    ))
+    .await;
+    let base_url = base_url.trim_end_matches("/v1").to_string();
+
+    let collected = collect_stream(provider_request(ProviderKind::AzureOpenAI, base_url)).await;
+
+    assert_eq!(collected.text, "Hello");
+    assert_eq!(collected.snapshot, "Hello");
+}
+
+async fn ollama_chat() -> Response<Body> {
+    let lines = [
+        json!({"model": "test-model", "created_at": "2026-10-19T09:00:00Z",
+            "message": {"role": "assistant", "content": "Hello"}, "done": false}),
+        json!({"model": "test-model", "created_at": "2026-10-19T09:00:01Z",
+            "message": {"role": "assistant", "content": ""}, "done": true,
+            "done_reason": "stop", "prompt_eval_count": 5, "eval_count": 2}),
+    ];
+    let body: String = lines.iter().map(|line| format!("{line}\n")).collect();
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/x-ndjson")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn rig_stream_supports_ollama() {
+    // Ollama models are often registered with the OpenAI compatible `/v1` URL.
+    let base_url = start_mock_provider(Router::new().route("/api/chat", post(ollama_chat))).await;
+
+    let collected = collect_stream(provider_request(ProviderKind::Ollama, base_url)).await;
+
+    assert_eq!(collected.snapshot, "Hello");
+    let usage = collected.usage.expect("usage should be reported");
+    assert_eq!(usage.input_tokens, 5);
+    assert_eq!(usage.output_tokens, 2);
+}
+
+async fn service_unavailable() -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .body(Body::from("upstream overloaded"))
+        .unwrap()
+}
+
+async fn rate_limited() -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .body(Body::from("slow down"))
+        .unwrap()
+}
+
+async fn unused_port_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{address}/v1")
+}
+
+#[tokio::test]
+async fn rig_stream_fails_over_before_the_first_token() {
+    let down =
+        start_mock_provider(Router::new().route("/v1/chat/completions", post(service_unavailable)))
+            .await;
+    let limited =
+        start_mock_provider(Router::new().route("/v1/chat/completions", post(rate_limited))).await;
+    let healthy = start_mock_provider(
+        Router::new().route("/v1/chat/completions", post(successful_chat_completion)),
+    )
+    .await;
+    let request = routed_request(vec![
+        endpoint(1, ProviderKind::OpenAI, down, 0),
+        endpoint(2, ProviderKind::OpenAI, unused_port_url().await, 1),
+        endpoint(3, ProviderKind::OpenAI, limited, 2),
+        endpoint(4, ProviderKind::OpenAI, healthy, 3),
+    ]);
+    let health = FakeHealthTracker::default();
+    let (sender, mut receiver) = mpsc::channel(8);
+
+    stream_chat_with_rig(request, sender, &health)
+        .await
+        .expect("the healthy endpoint should serve the chat");
+
+    let mut served_by = None;
+    while let Some(event) = receiver.recv().await {
+        if let GenerationEvent::End {
+            model_endpoint_id, ..
+        } = event.expect("generation event should succeed")
+        {
+            served_by = model_endpoint_id;
+        }
+    }
+
+    assert_eq!(served_by, Some(4));
+    assert_eq!(*health.failures.lock().unwrap(), vec![1, 2, 3]);
+    assert_eq!(*health.successes.lock().unwrap(), vec![4]);
+}
+
+#[tokio::test]
+async fn rig_stream_does_not_fail_over_on_client_errors() {
+    let missing =
+        start_mock_provider(Router::new().route("/v1/chat/completions", post(model_not_found)))
+            .await;
+    let healthy = start_mock_provider(
+        Router::new().route("/v1/chat/completions", post(successful_chat_completion)),
+    )
+    .await;
+    let request = routed_request(vec![
+        endpoint(1, ProviderKind::OpenAI, missing, 0),
+        endpoint(2, ProviderKind::OpenAI, healthy, 1),
+    ]);
+    let health = FakeHealthTracker::default();
+    let (sender, _receiver) = mpsc::channel(8);
+
+    let error = stream_chat_with_rig(request, sender, &health)
+        .await
+        .expect_err("a missing model should not be retried elsewhere");
+
+    assert!(error.to_string().contains("model_not_found"));
+    assert!(health.failures.lock().unwrap().is_empty());
+    assert!(health.successes.lock().unwrap().is_empty());
+}
+
+async fn long_chat_completion() -> Response<Body> {
+    let chunk = |text: String| {
+        format!(
+            "data: {}\n\n",
+            json!({"id": "response-1", "model": "test-model", "choices": [
+                {"index": 0, "delta": {"content": text}, "finish_reason": null}
+            ], "usage": null})
+        )
+    };
+    let body = [
+        chunk("a".repeat(500)),
+        chunk("b".repeat(500)),
+        chunk("forbidden".to_string()),
+        chunk("c".repeat(800)),
+        chunk("d".repeat(500)),
+        "data: [DONE]\n\n".to_string(),
+    ]
+    .concat();
+    event_stream_response(body)
+}
+
+#[tokio::test]
+async fn output_moderation_retracts_blocked_responses_on_completion() {
+    let base_url = start_mock_provider(
+        Router::new().route("/v1/chat/completions", post(long_chat_completion)),
+    )
+    .await;
+    let mut request = tool_enabled_request(base_url);
+    request.output_guard = Some(Box::new(FakeOutputGuard::new(
+        false,
+        "forbidden",
+        ModerationAction::Block,
+    )));
+
+    let collected = collect_stream(request).await;
+
+    assert_eq!(collected.text.len(), 2309);
+    assert!(collected.snapshot.is_empty());
+    assert!(collected
+        .retracted
+        .unwrap()
+        .contains("violated our guidelines"));
+}
+
+#[tokio::test]
+async fn output_moderation_keeps_flagged_responses() {
+    let base_url = start_mock_provider(
+        Router::new().route("/v1/chat/completions", post(long_chat_completion)),
+    )
+    .await;
+    let mut request = tool_enabled_request(base_url);
+    request.output_guard = Some(Box::new(FakeOutputGuard::new(
+        false,
+        "forbidden",
+        ModerationAction::Flag,
+    )));
+
+    let collected = collect_stream(request).await;
+
+    assert!(collected.retracted.is_none());
+    assert_eq!(collected.snapshot.len(), 2309);
+    assert_eq!(
+        collected.output_flags,
+        vec![FlaggedCategory {
+            flag_type: PromptFlagType::S10,
+            action: ModerationAction::Flag,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn streaming_output_moderation_stops_at_the_first_blocked_window() {
+    let base_url = start_mock_provider(
+        Router::new().route("/v1/chat/completions", post(long_chat_completion)),
+    )
+    .await;
+    let guard = Arc::new(FakeOutputGuard::new(
+        true,
+        "forbidden",
+        ModerationAction::Block,
+    ));
+    let mut request = tool_enabled_request(base_url);
+    request.output_guard = Some(Box::new(SharedGuard(Arc::clone(&guard))));
+
+    let collected = collect_stream(request).await;
+
+    // Windows close every 800 characters, so the last chunk is never streamed.
+    assert_eq!(*guard.checked.lock().unwrap(), vec![1000, 1809]);
+    assert_eq!(collected.text.len(), 1809);
+    assert!(collected.retracted.is_some());
+}
+
+struct SharedGuard(Arc<FakeOutputGuard>);
+
+#[async_trait]
+impl OutputGuard for SharedGuard {
+    fn streaming(&self) -> bool {
+        self.0.streaming()
+    }
+
+    async fn check(&self, response: &str) -> Result<Vec<FlaggedCategory>, reqwest::StatusCode> {
+        self.0.check(response).await
+    }
+}
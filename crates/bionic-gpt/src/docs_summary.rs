@@ -228,6 +228,82 @@ pub fn summary() -> Summary {
                         author_image: None,
                         author: None,
                     },
+                    PageSummary {
+                        date: "",
+                        title: "User Provisioning (SCIM)",
+                        description: "User Provisioning (SCIM)",
+                        folder: "docs/configuration/scim/",
+                        markdown: include_str!("../content/docs/configuration/scim/index.md"),
+                        image: None,
+                        author_image: None,
+                        author: None,
+                    },
+                    PageSummary {
+                        date: "",
+                        title: "Audit Trail",
+                        description: "Audit Trail",
+                        folder: "docs/configuration/audit-trail/",
+                        markdown: include_str!(
+                            "../content/docs/configuration/audit-trail/index.md"
+                        ),
+                        image: None,
+                        author_image: None,
+                        author: None,
+                    },
+                    PageSummary {
+                        date: "",
+                        title: "Webhooks",
+                        description: "Webhooks",
+                        folder: "docs/configuration/webhooks/",
+                        markdown: include_str!("../content/docs/configuration/webhooks/index.md"),
+                        image: None,
+                        author_image: None,
+                        author: None,
+                    },
+                    PageSummary {
+                        date: "",
+                        title: "Automations",
+                        description: "Automations",
+                        folder: "docs/configuration/automations/",
+                        markdown: include_str!(
+                            "../content/docs/configuration/automations/index.md"
+                        ),
+                        image: None,
+                        author_image: None,
+                        author: None,
+                    },
+                    PageSummary {
+                        date: "",
+                        title: "Data Retention",
+                        description: "Data Retention",
+                        folder: "docs/configuration/data-retention/",
+                        markdown: include_str!(
+                            "../content/docs/configuration/data-retention/index.md"
+                        ),
+                        image: None,
+                        author_image: None,
+                        author: None,
+                    },
+                    PageSummary {
+                        date: "",
+                        title: "Costs & Budgets",
+                        description: "Costs & Budgets",
+                        folder: "docs/configuration/costs/",
+                        markdown: include_str!("../content/docs/configuration/costs/index.md"),
+                        image: None,
+                        author_image: None,
+                        author: None,
+                    },
+                    PageSummary {
+                        date: "",
+                        title: "Export, Import & Sharing",
+                        description: "Export, Import & Sharing",
+                        folder: "docs/configuration/sharing/",
+                        markdown: include_str!("../content/docs/configuration/sharing/index.md"),
+                        image: None,
+                        author_image: None,
+                        author: None,
+                    },
                     PageSummary {
                         date: "",
                         title: "Database Backups",
@@ -250,6 +326,16 @@ pub fn summary() -> Summary {
                         author_image: None,
                         author: None,
                     },
+                    PageSummary {
+                        date: "",
+                        title: "Tracing (OpenTelemetry)",
+                        description: "Tracing (OpenTelemetry)",
+                        folder: "docs/configuration/tracing/",
+                        markdown: include_str!("../content/docs/configuration/tracing/index.md"),
+                        image: None,
+                        author_image: None,
+                        author: None,
+                    },
                     PageSummary {
                         date: "",
                         title: "Automating Document Upload",
@@ -0,0 +1,19 @@
+//! Request details recorded with every audit event.
+//!
+//! The web server runs each request inside [`with_client_ip`]; the address is then set
+//! on the transaction together with the RLS user, and `ops.audit_trail` picks it up as
+//! the default for `ip_address`.
+
+use std::future::Future;
+
+tokio::task_local! {
+    static CLIENT_IP: Option<String>;
+}
+
+pub async fn with_client_ip<F: Future>(client_ip: Option<String>, future: F) -> F::Output {
+    CLIENT_IP.scope(client_ip, future).await
+}
+
+pub(crate) fn client_ip() -> Option<String> {
+    CLIENT_IP.try_with(|ip| ip.clone()).ok().flatten()
+}
@@ -5,6 +5,9 @@ use crate::queries::users::InsertParams;
 use crate::{queries, Dataset, Prompt};
 use crate::{types, Permission, Transaction};
 
+/// The error `iam.set_app_user` raises for users the identity provider deactivated.
+pub const DEACTIVATED: &str = "This account has been deactivated";
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Authentication {
     pub sub: String,
@@ -101,16 +104,19 @@ async fn set_rls_and_encryption_keys(
     transaction: &Transaction<'_>,
     user_id: i32,
 ) -> Result<(), crate::TokioPostgresError> {
+    // Refuses users the identity provider has deactivated.
     transaction
-        .query(
-            &format!("SET LOCAL row_level_security.user_id = {}", user_id),
-            &[],
-        )
+        .query("SELECT iam.set_app_user($1)", &[&user_id])
         .await?;
 
-    if let Some(key) = crate::customer_keys::get_customer_key() {
+    crate::customer_keys::set_local_keys(transaction).await?;
+
+    if let Some(client_ip) = crate::audit::client_ip() {
         transaction
-            .query(&format!("SET LOCAL encryption.root_key = '{}'", key), &[])
+            .query(
+                "SELECT set_config('audit.client_ip', $1, true)",
+                &[&client_ip],
+            )
             .await?;
     }
 
@@ -136,19 +142,35 @@ pub async fn setup_user_if_not_already_registered(
     transaction: &Transaction<'_>,
     authentication: &Authentication,
 ) -> Result<(i32, String, Option<String>, Option<String>, bool), crate::TokioPostgresError> {
-    let user_id = queries::users::insert()
-        .params(
+    let provisioned = queries::users::claim_provisioned_user()
+        .bind(
             transaction,
-            &InsertParams {
-                openid_sub: &authentication.sub,
-                email: &authentication.email,
-                first_name: authentication.given_name.clone(),
-                last_name: authentication.family_name.clone(),
-            },
+            &authentication.sub,
+            &authentication.given_name,
+            &authentication.family_name,
+            &authentication.email,
         )
-        .one()
+        .opt()
         .await?;
 
+    let user_id = match provisioned {
+        Some(user_id) => user_id,
+        None => {
+            queries::users::insert()
+                .params(
+                    transaction,
+                    &InsertParams {
+                        openid_sub: &authentication.sub,
+                        email: &authentication.email,
+                        first_name: authentication.given_name.clone(),
+                        last_name: authentication.family_name.clone(),
+                    },
+                )
+                .one()
+                .await?
+        }
+    };
+
     set_rls_and_encryption_keys(transaction, user_id).await?;
 
     let inserted_org_id = queries::teams::insert_team()
@@ -273,4 +295,24 @@ impl Rbac {
     pub fn can_manage_projects(&self) -> bool {
         self.permissions.contains(&Permission::ManageProjects)
     }
+
+    pub fn can_manage_webhooks(&self) -> bool {
+        self.permissions.contains(&Permission::ManageWebhooks)
+    }
+
+    pub fn can_manage_automations(&self) -> bool {
+        self.permissions.contains(&Permission::ManageAutomations)
+    }
+
+    pub fn can_manage_retention(&self) -> bool {
+        self.permissions.contains(&Permission::ManageRetention)
+    }
+
+    pub fn can_manage_roles(&self) -> bool {
+        self.permissions.contains(&Permission::ManageRoles)
+    }
+
+    pub fn can_manage_budgets(&self) -> bool {
+        self.permissions.contains(&Permission::ManageBudgets)
+    }
 }
@@ -0,0 +1,174 @@
+//! The permissions a team can combine into its own roles.
+//!
+//! Custom roles are granted on top of the built in Team Manager and Collaborator roles
+//! and are read by `get_permissions` with them, so the usual `Rbac` checks apply. Only
+//! team level permissions can be granted. Setting up models and reading the audit
+//! trail cover every team, and managing roles would let a role grant itself more.
+
+use crate::authz::Rbac;
+use crate::Permission;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GrantablePermission {
+    pub permission: Permission,
+    pub name: &'static str,
+    pub label: &'static str,
+    pub description: &'static str,
+}
+
+pub const GRANTABLE: [GrantablePermission; 19] = [
+    GrantablePermission {
+        permission: Permission::ViewCurrentTeam,
+        name: "ViewCurrentTeam",
+        label: "View team",
+        description: "See the team's members and switch between teams",
+    },
+    GrantablePermission {
+        permission: Permission::InvitePeopleToTeam,
+        name: "InvitePeopleToTeam",
+        label: "Invite people",
+        description: "Invite people to the team and remove members",
+    },
+    GrantablePermission {
+        permission: Permission::ViewPrompts,
+        name: "ViewPrompts",
+        label: "View assistants",
+        description: "Browse and chat with the team's assistants",
+    },
+    GrantablePermission {
+        permission: Permission::MakeAssistantPublic,
+        name: "MakeAssistantPublic",
+        label: "Share assistants",
+        description: "Share assistants and integrations with the whole team",
+    },
+    GrantablePermission {
+        permission: Permission::ViewSystemPrompt,
+        name: "ViewSystemPrompt",
+        label: "View system prompts",
+        description: "See the system prompt behind an assistant's answers",
+    },
+    GrantablePermission {
+        permission: Permission::ViewChats,
+        name: "ViewChats",
+        label: "Chat",
+        description: "Use the chat console",
+    },
+    GrantablePermission {
+        permission: Permission::ViewChatHistory,
+        name: "ViewChatHistory",
+        label: "Chat history",
+        description: "Browse and search their conversations",
+    },
+    GrantablePermission {
+        permission: Permission::DeleteChat,
+        name: "DeleteChat",
+        label: "Delete chats",
+        description: "Delete messages from their conversations",
+    },
+    GrantablePermission {
+        permission: Permission::ViewDatasets,
+        name: "ViewDatasets",
+        label: "View datasets",
+        description: "See the team's datasets and their documents",
+    },
+    GrantablePermission {
+        permission: Permission::ManageDatasets,
+        name: "ManageDatasets",
+        label: "Manage datasets",
+        description: "Create, edit and delete datasets and upload documents",
+    },
+    GrantablePermission {
+        permission: Permission::ManageDocumentPipelines,
+        name: "ManageDocumentPipelines",
+        label: "Document pipelines",
+        description: "Set up pipelines that load documents into datasets",
+    },
+    GrantablePermission {
+        permission: Permission::CreateApiKeys,
+        name: "CreateApiKeys",
+        label: "API keys",
+        description: "Create API keys for assistants",
+    },
+    GrantablePermission {
+        permission: Permission::ManageMcpKeys,
+        name: "ManageMcpKeys",
+        label: "MCP keys",
+        description: "Create keys for the MCP server",
+    },
+    GrantablePermission {
+        permission: Permission::ViewIntegrations,
+        name: "ViewIntegrations",
+        label: "View integrations",
+        description: "See and connect to the team's integrations",
+    },
+    GrantablePermission {
+        permission: Permission::ManageIntegrations,
+        name: "ManageIntegrations",
+        label: "Manage integrations",
+        description: "Add, edit and delete integrations",
+    },
+    GrantablePermission {
+        permission: Permission::ManageProjects,
+        name: "ManageProjects",
+        label: "Projects",
+        description: "Create and manage projects",
+    },
+    GrantablePermission {
+        permission: Permission::ManageWebhooks,
+        name: "ManageWebhooks",
+        label: "Webhooks",
+        description: "Add webhooks and see their deliveries",
+    },
+    GrantablePermission {
+        permission: Permission::ManageAutomations,
+        name: "ManageAutomations",
+        label: "Automations",
+        description: "Schedule assistants to run on their own",
+    },
+    GrantablePermission {
+        permission: Permission::ManageRetention,
+        name: "ManageRetention",
+        label: "Data retention",
+        description: "Choose how long the team keeps its data",
+    },
+];
+
+/// The permission a form value names, if a custom role can grant it.
+pub fn grantable(name: &str) -> Option<Permission> {
+    GRANTABLE
+        .iter()
+        .find(|grantable| grantable.name == name)
+        .map(|grantable| grantable.permission)
+}
+
+/// Whether `rbac` can give someone a role with these permissions. Without ManageRoles
+/// they can only pass on permissions they already have.
+pub fn can_grant(rbac: &Rbac, permissions: &[Permission]) -> bool {
+    rbac.can_manage_roles()
+        || permissions
+            .iter()
+            .all(|permission| rbac.permissions.contains(permission))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_match_the_permissions() {
+        for grantable in GRANTABLE {
+            assert_eq!(format!("{:?}", grantable.permission), grantable.name);
+        }
+    }
+
+    #[test]
+    fn system_permissions_cannot_be_granted() {
+        assert_eq!(
+            grantable("ManageDatasets"),
+            Some(Permission::ManageDatasets)
+        );
+        assert_eq!(grantable("SetupModels"), None);
+        assert_eq!(grantable("ViewAuditTrail"), None);
+        assert_eq!(grantable("ManageRoles"), None);
+    }
+}
@@ -1,6 +1,47 @@
+use cornucopia_async::GenericClient;
 use std::env;
 
 // export CUSTOMER_KEY='190a5bf4b3cbb6c0991967ab1c48ab30790af876720f1835cbbf3820f4f5d949'
 pub fn get_customer_key() -> Option<String> {
-    env::var("CUSTOMER_KEY").ok()
+    env::var("CUSTOMER_KEY").ok().filter(|key| !key.is_empty())
+}
+
+// The key CUSTOMER_KEY replaced. Only needed until `web-server rotate-keys` has re-wrapped
+// the data keys with the new one.
+pub fn get_previous_customer_key() -> Option<String> {
+    env::var("CUSTOMER_KEY_PREVIOUS")
+        .ok()
+        .filter(|key| !key.is_empty())
+}
+
+/// Makes the customer keys available to encrypt_text/decrypt_text (and the bytes
+/// variants) until the transaction ends.
+pub async fn set_local_keys<C: GenericClient>(client: &C) -> Result<(), crate::TokioPostgresError> {
+    set_keys(client, true).await
+}
+
+/// Makes the customer keys available for the whole session, for long running
+/// connections that don't use transactions.
+pub async fn set_session_keys<C: GenericClient>(
+    client: &C,
+) -> Result<(), crate::TokioPostgresError> {
+    set_keys(client, false).await
+}
+
+async fn set_keys<C: GenericClient>(
+    client: &C,
+    local: bool,
+) -> Result<(), crate::TokioPostgresError> {
+    let settings = [
+        ("encryption.root_key", get_customer_key()),
+        ("encryption.previous_root_key", get_previous_customer_key()),
+    ];
+    for (name, key) in settings {
+        if let Some(key) = key {
+            client
+                .query("SELECT set_config($1, $2, $3)", &[&name, &key, &local])
+                .await?;
+        }
+    }
+    Ok(())
 }
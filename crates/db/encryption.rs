@@ -0,0 +1,101 @@
+//! Rotation of the customer encryption keys.
+//!
+//! Values are encrypted with versioned data keys that are themselves wrapped with the
+//! customer root key (`CUSTOMER_KEY`), see the envelope-encryption migration. A rotation
+//!
+//! 1. re-wraps data keys still wrapped with `CUSTOMER_KEY_PREVIOUS` with `CUSTOMER_KEY`,
+//! 2. optionally retires the active data key and creates a new one,
+//! 3. re-encrypts chats, chunks, connection secrets, OAuth client secrets and stored
+//!    objects in batches until everything uses the active data key,
+//! 4. deletes the retired data keys once nothing needs them.
+//!
+//! It is safe to stop and run again; each batch commits on its own.
+
+use std::collections::BTreeMap;
+
+use cornucopia_async::GenericClient;
+
+use crate::queries::encryption;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotationOptions {
+    /// Values re-encrypted per column in each batch.
+    pub batch_size: i32,
+    /// Create a new data key rather than only re-wrapping the existing ones.
+    pub new_data_key: bool,
+}
+
+impl Default for RotationOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 500,
+            new_data_key: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RotationReport {
+    pub rewrapped_keys: i32,
+    pub data_key_version: Option<i32>,
+    /// Values re-encrypted, keyed by `table.column`.
+    pub reencrypted: BTreeMap<String, i64>,
+    /// Values still not on the active data key, usually because they were encrypted with
+    /// a root key that isn't configured any more.
+    pub remaining: BTreeMap<String, i64>,
+    pub deleted_keys: i32,
+}
+
+pub async fn rotate_keys<C: GenericClient>(
+    client: &C,
+    options: &RotationOptions,
+) -> Result<RotationReport, crate::TokioPostgresError> {
+    crate::customer_keys::set_session_keys(client).await?;
+
+    let mut report = RotationReport {
+        rewrapped_keys: encryption::rewrap_data_keys().bind(client).one().await?,
+        ..Default::default()
+    };
+    tracing::info!("Re-wrapped {} data keys", report.rewrapped_keys);
+
+    if options.new_data_key {
+        let version = encryption::rotate_data_key().bind(client).one().await?;
+        tracing::info!("Encrypting with data key version {}", version);
+        report.data_key_version = Some(version);
+    }
+
+    loop {
+        let batch = encryption::reencrypt_batch()
+            .bind(client, &options.batch_size)
+            .all()
+            .await?;
+        let total: i64 = batch.iter().map(|column| column.count).sum();
+        for column in batch.into_iter().filter(|column| column.count > 0) {
+            *report
+                .reencrypted
+                .entry(format!("{}.{}", column.table_name, column.column_name))
+                .or_default() += column.count;
+        }
+        tracing::info!("Re-encrypted {} values", total);
+        if total == 0 {
+            break;
+        }
+    }
+
+    report.remaining = encryption::remaining()
+        .bind(client)
+        .all()
+        .await?
+        .into_iter()
+        .filter(|column| column.count > 0)
+        .map(|column| {
+            (
+                format!("{}.{}", column.table_name, column.column_name),
+                column.count,
+            )
+        })
+        .collect();
+    report.deleted_keys = encryption::delete_retired_keys().bind(client).one().await?;
+
+    Ok(report)
+}
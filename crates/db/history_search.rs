@@ -0,0 +1,313 @@
+//! Searches a user's chat history by meaning and by keyword.
+//!
+//! Conversation turns are embedded by rag-engine (see `llm.chat_embeddings`). A search
+//! takes the turns nearest to the query's embedding together with the ones that contain
+//! the search words. Turns are encrypted, so keywords are only looked for in the nearest
+//! turns and in the user's most recent ones, which catches a recent turn however far it
+//! is by meaning and whether or not it is embedded yet. Without an embeddings model to
+//! use, only keywords match.
+
+use crate::{PromptType, TokioPostgresError, Transaction};
+use chrono::{DateTime, FixedOffset};
+
+/// How many turns the vector search considers before they are grouped.
+const VECTOR_CANDIDATES: i64 = 50;
+
+/// How many of the user's most recent turns are searched by keyword.
+pub const KEYWORD_WINDOW: i64 = 500;
+
+/// Turns further than this (cosine distance) from the query only match by keyword.
+const MAX_DISTANCE: f64 = 0.6;
+
+/// How many matching turns are shown for each conversation.
+const SNIPPETS_PER_CONVERSATION: usize = 3;
+
+/// The query embedded with the team's history model.
+pub struct QueryEmbedding {
+    pub model_id: i32,
+    pub embeddings: Vec<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryMatch {
+    pub conversation_id: i64,
+    pub prompt_id: i32,
+    pub prompt_type: PromptType,
+    pub created_at: DateTime<FixedOffset>,
+    pub content: String,
+    pub distance: Option<f64>,
+    pub keyword: bool,
+}
+
+impl HistoryMatch {
+    /// Closer turns score higher, and containing the search words counts as much as
+    /// an exact match by meaning.
+    pub fn score(&self) -> f64 {
+        let similarity = self.distance.map(|d| 1.0 - d).unwrap_or(0.0);
+        similarity + if self.keyword { 1.0 } else { 0.0 }
+    }
+}
+
+/// A conversation with the turns that matched, best first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversationMatch {
+    pub id: i64,
+    pub prompt_id: i32,
+    pub prompt_type: PromptType,
+    pub created_at: DateTime<FixedOffset>,
+    pub score: f64,
+    pub snippets: Vec<String>,
+}
+
+pub async fn search_history(
+    transaction: &Transaction<'_>,
+    user_id: i32,
+    team_id: i32,
+    search_term: &str,
+    query: Option<&QueryEmbedding>,
+    limit: usize,
+) -> Result<Vec<ConversationMatch>, TokioPostgresError> {
+    let words = search_words(search_term);
+    let embedding = query.map(|query| pgvector::Vector::from(query.embeddings.clone()));
+    let model_id = query.map(|query| query.model_id);
+
+    let rows = transaction
+        .query(
+            "
+            WITH candidates AS (
+                SELECT
+                    ce.chat_id,
+                    (ce.embeddings <=> $4)::FLOAT8 AS distance
+                FROM
+                    llm.chat_embeddings ce
+                JOIN llm.chats c ON c.id = ce.chat_id
+                JOIN llm.conversations conv ON conv.id = c.conversation_id
+                WHERE
+                    $4::vector IS NOT NULL
+                AND ce.model_id = $5
+                AND ce.embeddings IS NOT NULL
+                AND conv.user_id = $1
+                AND conv.team_id = $2
+                ORDER BY distance
+                LIMIT $6
+            ),
+            recent AS (
+                SELECT c.id
+                FROM
+                    llm.chats c
+                JOIN llm.conversations conv ON conv.id = c.conversation_id
+                WHERE
+                    conv.user_id = $1
+                AND conv.team_id = $2
+                AND c.role IN ('User', 'Assistant')
+                AND c.content IS NOT NULL
+                ORDER BY c.id DESC
+                LIMIT $8
+            ),
+            turns AS (
+                SELECT
+                    c.id,
+                    c.conversation_id,
+                    c.prompt_id,
+                    conv.created_at,
+                    decrypt_text(c.content) AS content
+                FROM
+                    llm.chats c
+                JOIN llm.conversations conv ON conv.id = c.conversation_id
+                WHERE
+                    conv.user_id = $1
+                AND conv.team_id = $2
+                AND c.role IN ('User', 'Assistant')
+                AND c.content IS NOT NULL
+                AND c.id IN (
+                    SELECT chat_id FROM candidates
+                    UNION
+                    SELECT id FROM recent
+                )
+            ),
+            keyword_matches AS (
+                SELECT t.id
+                FROM turns t
+                WHERE (
+                    SELECT COALESCE(bool_and(POSITION(w IN LOWER(t.content)) > 0), FALSE)
+                    FROM unnest($3::TEXT[]) w
+                )
+            ),
+            matches AS (
+                SELECT chat_id AS id FROM candidates
+                UNION
+                SELECT id FROM keyword_matches
+            ),
+            scored AS (
+                SELECT
+                    t.conversation_id,
+                    t.prompt_id,
+                    t.created_at,
+                    t.content,
+                    CASE WHEN cand.distance <= $7 THEN cand.distance END AS distance,
+                    k.id IS NOT NULL AS keyword
+                FROM matches m
+                JOIN turns t ON t.id = m.id
+                LEFT JOIN candidates cand ON cand.chat_id = m.id
+                LEFT JOIN keyword_matches k ON k.id = m.id
+            )
+            SELECT
+                s.conversation_id,
+                s.prompt_id,
+                p.prompt_type,
+                s.created_at,
+                s.content,
+                s.distance,
+                s.keyword
+            FROM
+                scored s
+            JOIN assistants.prompts p ON p.id = s.prompt_id
+            WHERE
+                s.distance IS NOT NULL OR s.keyword
+            ",
+            &[
+                &user_id,
+                &team_id,
+                &words,
+                &embedding,
+                &model_id,
+                &VECTOR_CANDIDATES,
+                &MAX_DISTANCE,
+                &KEYWORD_WINDOW,
+            ],
+        )
+        .await?;
+
+    let matches = rows
+        .into_iter()
+        .map(|row| HistoryMatch {
+            conversation_id: row.get(0),
+            prompt_id: row.get(1),
+            prompt_type: row.get(2),
+            created_at: row.get(3),
+            content: row.get(4),
+            distance: row.get(5),
+            keyword: row.get(6),
+        })
+        .collect();
+
+    Ok(group_by_conversation(matches, limit))
+}
+
+/// The lowercased words a keyword match needs to contain.
+pub fn search_words(search_term: &str) -> Vec<String> {
+    search_term
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+/// Groups turns by conversation. Conversations are ordered by their best turn, with
+/// newer conversations first when they score the same.
+pub fn group_by_conversation(
+    mut matches: Vec<HistoryMatch>,
+    limit: usize,
+) -> Vec<ConversationMatch> {
+    matches.sort_by(|a, b| b.score().total_cmp(&a.score()));
+
+    let mut conversations: Vec<ConversationMatch> = Vec::new();
+    for turn in matches {
+        let score = turn.score();
+        match conversations
+            .iter_mut()
+            .find(|conversation| conversation.id == turn.conversation_id)
+        {
+            Some(conversation) => {
+                if conversation.snippets.len() < SNIPPETS_PER_CONVERSATION {
+                    conversation.snippets.push(turn.content);
+                }
+            }
+            None => conversations.push(ConversationMatch {
+                id: turn.conversation_id,
+                prompt_id: turn.prompt_id,
+                prompt_type: turn.prompt_type,
+                created_at: turn.created_at,
+                score,
+                snippets: vec![turn.content],
+            }),
+        }
+    }
+
+    conversations.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| b.created_at.cmp(&a.created_at))
+    });
+    conversations.truncate(limit);
+    conversations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(
+        conversation_id: i64,
+        content: &str,
+        distance: Option<f64>,
+        keyword: bool,
+    ) -> HistoryMatch {
+        HistoryMatch {
+            conversation_id,
+            prompt_id: 1,
+            prompt_type: PromptType::Model,
+            created_at: DateTime::parse_from_rfc3339("2026-10-01T00:00:00Z").unwrap(),
+            content: content.to_string(),
+            distance,
+            keyword,
+        }
+    }
+
+    #[test]
+    fn keyword_matches_outrank_meaning_alone() {
+        let grouped = group_by_conversation(
+            vec![
+                turn(1, "close in meaning", Some(0.1), false),
+                turn(2, "has the words", Some(0.5), true),
+            ],
+            10,
+        );
+        let ids: Vec<i64> = grouped.iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![2, 1]);
+    }
+
+    #[test]
+    fn turns_are_grouped_by_conversation_best_first() {
+        let grouped = group_by_conversation(
+            vec![
+                turn(1, "a", Some(0.4), false),
+                turn(1, "b", Some(0.2), false),
+                turn(1, "c", Some(0.3), false),
+                turn(1, "d", Some(0.5), false),
+                turn(2, "e", None, true),
+            ],
+            10,
+        );
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(grouped[1].snippets, vec!["b", "c", "a"]);
+        assert!((grouped[1].score - 0.8).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn limit_applies_to_conversations() {
+        let grouped = group_by_conversation(
+            vec![
+                turn(1, "a", None, true),
+                turn(2, "b", None, true),
+                turn(3, "c", None, true),
+            ],
+            2,
+        );
+        assert_eq!(grouped.len(), 2);
+    }
+
+    #[test]
+    fn search_words_are_lowercased() {
+        assert_eq!(search_words("  Rust  Traits "), vec!["rust", "traits"]);
+    }
+}
@@ -1,15 +1,24 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
 
 use tokio::sync::RwLock;
 
 use crate::queries;
+use crate::queries::i18n::Translation;
 use crate::Pool;
 
+/// How long a user's locale is cached. Other replicas pick up a change after this.
+const USER_LOCALE_TTL: Duration = Duration::from_secs(300);
+
+/// A user's locale, if they picked one, and when it was loaded.
+type CachedLocale = (Option<String>, Instant);
+
 #[derive(Clone)]
 pub struct I18n {
     pool: Pool,
     cache: Arc<RwLock<HashMap<String, HashMap<String, String>>>>,
+    user_locales: Arc<RwLock<HashMap<String, CachedLocale>>>,
 }
 
 static GLOBAL_I18N: OnceLock<I18n> = OnceLock::new();
@@ -19,9 +28,49 @@ impl I18n {
         Self {
             pool,
             cache: Arc::new(RwLock::new(HashMap::new())),
+            user_locales: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// The locale the user picked on their profile, looked up by their OpenID subject.
+    pub async fn user_locale(&self, openid_sub: &str) -> Option<String> {
+        if let Some((locale, loaded_at)) = self.user_locales.read().await.get(openid_sub) {
+            if loaded_at.elapsed() < USER_LOCALE_TTL {
+                return locale.clone();
+            }
+        }
+
+        let client = match self.pool.get().await {
+            Ok(client) => client,
+            Err(err) => {
+                tracing::warn!(target: "db::i18n", ?err, "failed to get database client for user locale");
+                return None;
+            }
+        };
+        let locale = match queries::users::locale_by_openid_sub()
+            .bind(&client, &openid_sub)
+            .opt()
+            .await
+        {
+            Ok(row) => row.flatten(),
+            Err(err) => {
+                tracing::warn!(target: "db::i18n", ?err, "failed to load user locale");
+                return None;
+            }
+        };
+        drop(client);
+
+        let mut user_locales = self.user_locales.write().await;
+        user_locales.retain(|_, (_, loaded_at)| loaded_at.elapsed() < USER_LOCALE_TTL);
+        user_locales.insert(openid_sub.to_string(), (locale.clone(), Instant::now()));
+        locale
+    }
+
+    /// Drops the cached locale after the user changes it.
+    pub async fn forget_user_locale(&self, openid_sub: &str) {
+        self.user_locales.write().await.remove(openid_sub);
+    }
+
     async fn cached_value(&self, locale: &str, key: &str) -> Option<String> {
         let cache = self.cache.read().await;
         cache
@@ -65,19 +114,40 @@ impl I18n {
     }
 
     pub async fn warm_cache(&self) {
+        if let Some(grouped) = self.load_all().await {
+            let mut cache = self.cache.write().await;
+            for (locale, translations) in grouped {
+                cache.insert(locale, translations);
+            }
+        }
+    }
+
+    /// Replaces the cache with what is in the database, so edits show up without a
+    /// restart. Returns false when the translations couldn't be loaded.
+    pub async fn reload(&self) -> bool {
+        match self.load_all().await {
+            Some(grouped) => {
+                *self.cache.write().await = grouped;
+                true
+            }
+            None => false,
+        }
+    }
+
+    async fn load_all(&self) -> Option<HashMap<String, HashMap<String, String>>> {
         let client = match self.pool.get().await {
             Ok(client) => client,
             Err(err) => {
-                tracing::warn!(target: "db::i18n", ?err, "failed to get database client to warm translations cache");
-                return;
+                tracing::warn!(target: "db::i18n", ?err, "failed to get database client to load translations");
+                return None;
             }
         };
 
         let rows = match queries::i18n::all_translations().bind(&client).all().await {
             Ok(rows) => rows,
             Err(err) => {
-                tracing::warn!(target: "db::i18n", ?err, "failed to load translations while warming cache");
-                return;
+                tracing::warn!(target: "db::i18n", ?err, "failed to load translations");
+                return None;
             }
         };
 
@@ -90,11 +160,7 @@ impl I18n {
                 .or_default()
                 .insert(row.key, row.value);
         }
-
-        let mut cache = self.cache.write().await;
-        for (locale, translations) in grouped {
-            cache.insert(locale, translations);
-        }
+        Some(grouped)
     }
 
     pub async fn ensure_locale(&self, locale: &str) {
@@ -189,6 +255,19 @@ pub enum I18nKey {
 }
 
 impl I18nKey {
+    pub const ALL: [I18nKey; 10] = [
+        Self::AiAssistants,
+        Self::Integrations,
+        Self::Integration,
+        Self::Prompts,
+        Self::Datasets,
+        Self::Assistants,
+        Self::Assistant,
+        Self::Dataset,
+        Self::Histories,
+        Self::History,
+    ];
+
     pub const fn as_str(self) -> &'static str {
         match self {
             Self::AiAssistants => "i18n.ai_assistants",
@@ -204,3 +283,101 @@ impl I18nKey {
         }
     }
 }
+
+/// Locales are language tags such as `en`, `de` or `pt-BR`.
+pub fn is_valid_locale(locale: &str) -> bool {
+    !locale.is_empty()
+        && locale.len() <= 35
+        && locale
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// How much of the known keys a locale translates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocaleCoverage {
+    pub locale: String,
+    pub translated: usize,
+    pub total: usize,
+    pub missing: Vec<String>,
+}
+
+impl LocaleCoverage {
+    pub fn percent(&self) -> usize {
+        (self.translated * 100)
+            .checked_div(self.total)
+            .unwrap_or(100)
+    }
+}
+
+/// Coverage of every locale against all keys, those used in the code and those any
+/// locale has a translation for.
+pub fn coverage(translations: &[Translation]) -> Vec<LocaleCoverage> {
+    let mut keys: BTreeSet<&str> = I18nKey::ALL.iter().map(|key| key.as_str()).collect();
+    let mut by_locale: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::from([("en", BTreeSet::new())]);
+    for translation in translations {
+        keys.insert(&translation.key);
+        by_locale
+            .entry(&translation.locale)
+            .or_default()
+            .insert(&translation.key);
+    }
+
+    by_locale
+        .into_iter()
+        .map(|(locale, translated)| LocaleCoverage {
+            locale: locale.to_string(),
+            translated: translated.len(),
+            total: keys.len(),
+            missing: keys
+                .iter()
+                .filter(|key| !translated.contains(*key))
+                .map(|key| key.to_string())
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translation(locale: &str, key: &str) -> Translation {
+        Translation {
+            key: key.to_string(),
+            locale: locale.to_string(),
+            value: format!("{locale} {key}"),
+        }
+    }
+
+    #[test]
+    fn coverage_counts_keys_missing_from_each_locale() {
+        let mut translations: Vec<Translation> = I18nKey::ALL
+            .iter()
+            .map(|key| translation("en", key.as_str()))
+            .collect();
+        translations.push(translation("de", "i18n.history"));
+        translations.push(translation("de", "custom.greeting"));
+
+        let coverage = coverage(&translations);
+        let total = I18nKey::ALL.len() + 1;
+
+        assert_eq!(coverage.len(), 2);
+        let de = &coverage[0];
+        assert_eq!(de.locale, "de");
+        assert_eq!((de.translated, de.total), (2, total));
+        assert!(de.missing.contains(&"i18n.ai_assistants".to_string()));
+        assert!(!de.missing.contains(&"i18n.history".to_string()));
+
+        let en = &coverage[1];
+        assert_eq!(en.missing, vec!["custom.greeting".to_string()]);
+        assert_eq!(en.percent(), (total - 1) * 100 / total);
+    }
+
+    #[test]
+    fn locales_are_language_tags() {
+        assert!(is_valid_locale("pt-BR"));
+        assert!(!is_valid_locale(""));
+        assert!(!is_valid_locale("../en"));
+    }
+}
@@ -1,58 +1,81 @@
+pub mod audit;
 pub mod authz;
+pub mod custom_roles;
 pub mod customer_keys;
+pub mod encryption;
+pub mod history_search;
 pub mod i18n;
+pub mod pool;
 pub mod team_public_id;
 pub mod vector_search;
+pub mod webhooks;
 
 use std::str::FromStr;
 
 pub use cornucopia_async::Params;
-pub use deadpool_postgres::{Pool, PoolError, Transaction};
+pub use deadpool_postgres::PoolError;
 pub use i18n::{I18n, I18nKey};
+pub use pool::{Client, Pool, Transaction};
 pub use queries::api_keys::ApiKey;
 pub use queries::audit_trail::AuditTrail;
+pub use queries::automations::{Automation, AutomationRun, ClaimedRun, DueSchedule};
+pub use queries::budgets::{Budget, BudgetAlert, TeamCost};
 pub use queries::categories::Category;
 pub use queries::chats::Chat;
 pub use queries::connections::{
     oauth2_connections_needing_refresh, update_oauth2_connection, ApiKeyConnection,
     ConnectedIntegration, Oauth2Connection, Oauth2RefreshCandidate,
 };
+pub use queries::conversation_shares::{ConversationShare, SharedConversation};
+pub use queries::conversation_workspaces::{ConversationWorkspace, PreviousWorkspace};
 pub use queries::conversations::{Conversation, ConversationContextSize};
+pub use queries::custom_roles::{CustomRole, MemberCustomRoles};
 pub use queries::datasets::Dataset;
 pub use queries::document_pipelines::DocumentPipeline;
 pub use queries::generated_outputs::{GeneratedOutput, GeneratedOutputData};
-pub use queries::history::History;
+pub use queries::history::{History, HistoryEmbeddingsModel};
 pub use queries::integrations::Integration;
 pub use queries::invitations::{Invitation, InviteSummary};
-pub use queries::models::{Model, ModelWithPrompt};
+pub use queries::model_endpoints::{EndpointHealth, ModelEndpoint};
+pub use queries::models::{GuardModel, Model, ModelWithPrompt};
+pub use queries::moderation_policies::ModerationPolicy;
 pub use queries::oauth_clients::OauthClient;
 pub use queries::object_storage::ObjectStorage;
 pub use queries::openapi_specs::OpenapiSpec;
 pub use queries::projects::{Project, ProjectSummary};
-pub use queries::prompt_flags::insert_prompt_flag;
+pub use queries::prompt_flags::{insert_prompt_flag, PromptFlag};
 pub use queries::prompts::{Prompt, PromptDataset, SinglePrompt};
 pub use queries::providers::Provider;
 pub use queries::rate_limits::RateLimit;
+pub use queries::retention::{DuePolicy, RetentionPolicy};
 pub use queries::runtime_settings::RuntimeSetting;
+pub use queries::scim::{
+    MappableCustomRoles, MappableTeams, ScimGroup, ScimGroupMembers, ScimUser,
+};
 pub use queries::skills::{Skill, SkillFile};
 pub use queries::teams::GetUsers as Member;
 pub use queries::teams::{Team, TeamOwner};
+pub use queries::tool_call_approvals::{ToolCallApproval, ToolCallPolicyRow};
+pub use queries::user_data::ExportFile;
 pub use queries::users::User;
+pub use queries::webhooks::{ClaimedDelivery, Webhook, WebhookDelivery};
 pub use tokio_postgres::types::Json;
 pub use tokio_postgres::Error as TokioPostgresError;
 pub use vector_search::{get_related_context, RelatedContext};
 
-pub fn create_pool(database_url: &str) -> deadpool_postgres::Pool {
+pub fn create_pool(database_url: &str) -> Pool {
     let config = tokio_postgres::Config::from_str(database_url).unwrap();
     let manager = deadpool_postgres::Manager::new(config, tokio_postgres::NoTls);
 
-    deadpool_postgres::Pool::builder(manager).build().unwrap()
+    Pool::new(deadpool_postgres::Pool::builder(manager).build().unwrap())
 }
 
 include!(concat!(env!("OUT_DIR"), "/cornucopia/src/lib.rs"));
 
 pub use types::{
-    AuditAccessType, AuditAction, ChatRole, ChatStatus, IntegrationType, ModelCapability,
-    ModelType, OpenapiSpecCategory, Permission, PromptFlagType, PromptType, Role, TokenUsageType,
-    Visibility,
+    AuditAccessType, AuditAction, AutomationRunStatus, AutomationTrigger, ChatRole, ChatStatus,
+    GuardFormat, IntegrationType, ModelCapability, ModelType, ModerationAction,
+    ModerationDirection, OpenapiSpecCategory, OutputModeration, Permission, PromptFlagType,
+    PromptType, ProviderKind, Role, ShareVisibility, TokenUsageType, ToolCallApprovalStatus,
+    ToolCallPolicy, Visibility, WebhookDeliveryStatus,
 };
@@ -0,0 +1,4 @@
+-- migrate:up
+ALTER TYPE model_type ADD VALUE 'SpeechToText';
+
+-- migrate:down
@@ -0,0 +1,23 @@
+-- migrate:up
+CREATE TYPE provider_kind AS ENUM (
+    'OpenAI',
+    'Anthropic',
+    'Gemini',
+    'AzureOpenAI',
+    'Ollama'
+);
+
+ALTER TABLE model_registry.providers
+    ADD COLUMN provider_kind provider_kind NOT NULL DEFAULT 'OpenAI';
+
+ALTER TABLE model_registry.models
+    ADD COLUMN provider_kind provider_kind NOT NULL DEFAULT 'OpenAI';
+
+-- migrate:down
+ALTER TABLE model_registry.models
+    DROP COLUMN provider_kind;
+
+ALTER TABLE model_registry.providers
+    DROP COLUMN provider_kind;
+
+DROP TYPE provider_kind;
@@ -0,0 +1,69 @@
+-- migrate:up
+CREATE TABLE model_registry.model_endpoints (
+    id INT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    model_id INT NOT NULL REFERENCES model_registry.models(id) ON DELETE CASCADE,
+    -- The primary endpoint mirrors the model's own base_url and api_key.
+    is_primary BOOLEAN NOT NULL DEFAULT FALSE,
+    provider_kind provider_kind NOT NULL DEFAULT 'OpenAI',
+    base_url VARCHAR NOT NULL,
+    api_key VARCHAR,
+    -- Lower priorities are tried first, weights balance load within a priority.
+    priority INT NOT NULL DEFAULT 1 CHECK (priority >= 0),
+    weight INT NOT NULL DEFAULT 1 CHECK (weight > 0),
+    consecutive_failures INT NOT NULL DEFAULT 0,
+    circuit_open_until TIMESTAMPTZ,
+    last_error TEXT,
+    last_failure_at TIMESTAMPTZ,
+    last_success_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX model_endpoints_primary_idx
+    ON model_registry.model_endpoints (model_id) WHERE is_primary;
+
+SELECT updated_at('model_registry.model_endpoints');
+
+CREATE FUNCTION model_registry.sync_primary_endpoint()
+   RETURNS TRIGGER
+   LANGUAGE PLPGSQL
+AS $$
+BEGIN
+  INSERT INTO model_registry.model_endpoints
+    (model_id, is_primary, provider_kind, base_url, api_key, priority, weight)
+  VALUES
+    (NEW.id, TRUE, NEW.provider_kind, NEW.base_url, NEW.api_key, 0, 1)
+  ON CONFLICT (model_id) WHERE is_primary DO UPDATE SET
+    provider_kind = EXCLUDED.provider_kind,
+    base_url = EXCLUDED.base_url,
+    api_key = EXCLUDED.api_key;
+
+  RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER sync_primary_endpoint
+  AFTER INSERT OR UPDATE OF provider_kind, base_url, api_key
+  ON model_registry.models
+  FOR EACH ROW
+  EXECUTE PROCEDURE model_registry.sync_primary_endpoint();
+
+INSERT INTO model_registry.model_endpoints
+    (model_id, is_primary, provider_kind, base_url, api_key, priority, weight)
+SELECT id, TRUE, provider_kind, base_url, api_key, 0, 1
+FROM model_registry.models;
+
+ALTER TABLE llm.chats
+    ADD COLUMN model_endpoint_id INT
+    REFERENCES model_registry.model_endpoints(id) ON DELETE SET NULL;
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON model_registry.model_endpoints TO application_user;
+GRANT USAGE, SELECT ON model_registry.model_endpoints_id_seq TO application_user;
+GRANT SELECT ON model_registry.model_endpoints TO application_readonly;
+GRANT SELECT ON model_registry.model_endpoints_id_seq TO application_readonly;
+
+-- migrate:down
+ALTER TABLE llm.chats DROP COLUMN IF EXISTS model_endpoint_id;
+DROP TRIGGER IF EXISTS sync_primary_endpoint ON model_registry.models;
+DROP FUNCTION IF EXISTS model_registry.sync_primary_endpoint;
+DROP TABLE IF EXISTS model_registry.model_endpoints;
@@ -0,0 +1,48 @@
+-- migrate:up
+CREATE TYPE moderation_action AS ENUM ('Block', 'Flag');
+CREATE TYPE moderation_direction AS ENUM ('Input', 'Output');
+CREATE TYPE output_moderation AS ENUM ('Off', 'OnCompletion', 'Streaming');
+CREATE TYPE guard_format AS ENUM ('LlamaGuard', 'Json');
+
+-- How the guard model's verdict should be read.
+ALTER TABLE model_registry.models
+    ADD COLUMN guard_format guard_format NOT NULL DEFAULT 'LlamaGuard';
+
+-- Whether assistant responses are checked, and when.
+ALTER TABLE iam.teams
+    ADD COLUMN output_moderation output_moderation NOT NULL DEFAULT 'Off';
+
+-- Categories without a row block, which matches the behaviour before policies existed.
+CREATE TABLE llm.moderation_policies (
+    id INT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    team_id INT NOT NULL REFERENCES iam.teams(id) ON DELETE CASCADE,
+    flag_type prompt_flag_type NOT NULL,
+    action moderation_action NOT NULL DEFAULT 'Block',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (team_id, flag_type)
+);
+
+SELECT updated_at('llm.moderation_policies');
+
+-- Flags on assistant responses are recorded against the user chat that prompted them.
+ALTER TABLE llm.prompt_flags
+    ADD COLUMN direction moderation_direction NOT NULL DEFAULT 'Input',
+    ADD COLUMN action moderation_action NOT NULL DEFAULT 'Block';
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON llm.moderation_policies TO application_user;
+GRANT USAGE, SELECT ON llm.moderation_policies_id_seq TO application_user;
+GRANT SELECT ON llm.moderation_policies TO application_readonly;
+GRANT SELECT ON llm.moderation_policies_id_seq TO application_readonly;
+
+-- migrate:down
+ALTER TABLE llm.prompt_flags
+    DROP COLUMN direction,
+    DROP COLUMN action;
+DROP TABLE IF EXISTS llm.moderation_policies;
+ALTER TABLE iam.teams DROP COLUMN output_moderation;
+ALTER TABLE model_registry.models DROP COLUMN guard_format;
+DROP TYPE guard_format;
+DROP TYPE output_moderation;
+DROP TYPE moderation_direction;
+DROP TYPE moderation_action;
@@ -0,0 +1,25 @@
+-- migrate:up
+-- Assistants can keep /home/user/work between run_bash calls in the same conversation.
+ALTER TABLE assistants.prompts
+    ADD COLUMN persistent_workspace BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- The latest workspace snapshot for a conversation. Deleting the object removes the row.
+CREATE TABLE llm.conversation_workspaces (
+    conversation_id INT PRIMARY KEY REFERENCES llm.conversations(id) ON DELETE CASCADE,
+    object_id INT NOT NULL REFERENCES storage.objects(id) ON DELETE CASCADE,
+    size_bytes BIGINT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT updated_at('llm.conversation_workspaces');
+
+CREATE INDEX conversation_workspaces_expires_at_idx ON llm.conversation_workspaces(expires_at);
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON llm.conversation_workspaces TO application_user;
+GRANT SELECT ON llm.conversation_workspaces TO application_readonly;
+
+-- migrate:down
+DROP TABLE IF EXISTS llm.conversation_workspaces;
+ALTER TABLE assistants.prompts DROP COLUMN persistent_workspace;
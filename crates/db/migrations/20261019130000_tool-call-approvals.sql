@@ -0,0 +1,130 @@
+-- migrate:up
+-- Whether a side-effecting integration call runs straight away or waits for the user.
+CREATE TYPE tool_call_policy AS ENUM (
+    'Allow',
+    'Confirm',
+    'Deny'
+);
+
+CREATE TYPE tool_call_approval_status AS ENUM (
+    'Pending',
+    'Approved',
+    'Rejected'
+);
+
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'ApproveToolCall';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'RejectToolCall';
+
+-- Per integration ('*') or per operation overrides. Without a row GET calls are
+-- allowed and every other method needs confirmation.
+CREATE TABLE integrations.tool_call_policies (
+    id INT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    team_id INT NOT NULL REFERENCES iam.teams(id) ON DELETE CASCADE,
+    integration_id INT NOT NULL REFERENCES integrations.integrations(id) ON DELETE CASCADE,
+    operation_id TEXT NOT NULL DEFAULT '*',
+    policy tool_call_policy NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (integration_id, operation_id)
+);
+
+SELECT updated_at('integrations.tool_call_policies');
+
+COMMENT ON COLUMN integrations.tool_call_policies.operation_id IS 'The OpenAPI operation id, or * for the whole integration';
+
+-- An integration call the model made which is waiting for, or received, a decision.
+CREATE TABLE llm.tool_call_approvals (
+    id INT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    team_id INT NOT NULL REFERENCES iam.teams(id) ON DELETE CASCADE,
+    conversation_id BIGINT NOT NULL REFERENCES llm.conversations(id) ON DELETE CASCADE,
+    chat_id INT REFERENCES llm.chats(id) ON DELETE CASCADE,
+    integration_id INT REFERENCES integrations.integrations(id) ON DELETE SET NULL,
+    function_name TEXT NOT NULL,
+    operation_id TEXT NOT NULL,
+    method TEXT NOT NULL,
+    url TEXT NOT NULL,
+    request_body JSONB,
+    arguments JSONB NOT NULL,
+    status tool_call_approval_status NOT NULL DEFAULT 'Pending',
+    response TEXT,
+    requested_by INT NOT NULL REFERENCES iam.users(id) ON DELETE CASCADE,
+    decided_by INT REFERENCES iam.users(id) ON DELETE SET NULL,
+    decided_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT updated_at('llm.tool_call_approvals');
+
+CREATE INDEX tool_call_approvals_conversation_idx
+    ON llm.tool_call_approvals(conversation_id, status);
+
+COMMENT ON COLUMN llm.tool_call_approvals.url IS 'The resolved request URL shown to the user, without credentials';
+COMMENT ON COLUMN llm.tool_call_approvals.arguments IS 'The function arguments replayed when the call is approved';
+
+-- Links decisions to the audit trail.
+CREATE TABLE ops.audit_trail_tool_call_approvals (
+    id INT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    audit_id INT NOT NULL REFERENCES ops.audit_trail(id) ON DELETE CASCADE,
+    approval_id INT NOT NULL REFERENCES llm.tool_call_approvals(id) ON DELETE CASCADE
+);
+
+CREATE FUNCTION audit_tool_call_approvals()
+   RETURNS TRIGGER
+   LANGUAGE PLPGSQL
+AS $$
+DECLARE
+  audit_id ops.audit_trail.id%type;
+BEGIN
+  INSERT INTO ops.audit_trail
+  (
+    user_id,
+    team_id,
+    access_type,
+    action
+  )
+  VALUES(
+    current_app_user(),
+    NEW.team_id,
+    TG_ARGV[0]::audit_access_type,
+    (CASE WHEN NEW.status = 'Approved' THEN 'ApproveToolCall' ELSE 'RejectToolCall' END)::audit_action
+  )
+  RETURNING id INTO audit_id;
+
+  INSERT INTO ops.audit_trail_tool_call_approvals (audit_id, approval_id)
+  VALUES (audit_id, NEW.id);
+
+  RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER decide_tool_call_approval
+  AFTER UPDATE OF status
+  ON llm.tool_call_approvals
+  FOR EACH ROW
+  WHEN (OLD.status = 'Pending' AND NEW.status <> 'Pending')
+  EXECUTE PROCEDURE audit_tool_call_approvals('UserInterface');
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON integrations.tool_call_policies TO application_user;
+GRANT USAGE, SELECT ON integrations.tool_call_policies_id_seq TO application_user;
+GRANT SELECT ON integrations.tool_call_policies TO application_readonly;
+GRANT SELECT ON integrations.tool_call_policies_id_seq TO application_readonly;
+
+GRANT SELECT, INSERT, UPDATE ON llm.tool_call_approvals TO application_user;
+GRANT USAGE, SELECT ON llm.tool_call_approvals_id_seq TO application_user;
+GRANT SELECT ON llm.tool_call_approvals TO application_readonly;
+GRANT SELECT ON llm.tool_call_approvals_id_seq TO application_readonly;
+
+GRANT SELECT, INSERT ON ops.audit_trail_tool_call_approvals TO application_user;
+GRANT USAGE, SELECT ON ops.audit_trail_tool_call_approvals_id_seq TO application_user;
+GRANT SELECT ON ops.audit_trail_tool_call_approvals TO application_readonly;
+GRANT SELECT ON ops.audit_trail_tool_call_approvals_id_seq TO application_readonly;
+
+-- migrate:down
+DROP TRIGGER decide_tool_call_approval ON llm.tool_call_approvals;
+DROP FUNCTION audit_tool_call_approvals;
+DROP TABLE ops.audit_trail_tool_call_approvals;
+DROP TABLE llm.tool_call_approvals;
+DROP TABLE integrations.tool_call_policies;
+DROP TYPE tool_call_approval_status;
+DROP TYPE tool_call_policy;
@@ -0,0 +1,444 @@
+-- migrate:up
+
+-- Envelope encryption. Values are encrypted with a versioned data key and each data key
+-- is stored encrypted (wrapped) with the customer's root key (CUSTOMER_KEY). Changing the
+-- root key only re-wraps the data keys; rotating a data key re-encrypts the data in
+-- batches with encryption.reencrypt_batch.
+--
+-- Ciphertext formats:
+--   text  'enc:v<version>:<base64 pgp message>'
+--   bytea 'BENC' || int4 version || pgp message
+-- Text encrypted before versioning ('\x...') was encrypted with the root key directly
+-- and is still readable.
+
+CREATE SCHEMA IF NOT EXISTS encryption;
+
+CREATE TABLE encryption.data_keys (
+    version INT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    -- The data key encrypted with the root key
+    wrapped_key BYTEA NOT NULL,
+    -- sha256 of the root key that wraps this data key
+    root_key_hash TEXT NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    retired_at TIMESTAMPTZ
+);
+
+-- Only one data key encrypts new values
+CREATE UNIQUE INDEX data_keys_active ON encryption.data_keys (active) WHERE active;
+
+COMMENT ON TABLE encryption.data_keys IS 'Versioned data keys, wrapped with the customer root key.';
+
+CREATE FUNCTION encryption.setting(name text) RETURNS text AS $$
+    -- A custom setting reads as '' once a SET LOCAL from an earlier transaction ends.
+    SELECT NULLIF(current_setting(name, true), '');
+$$ LANGUAGE sql STABLE;
+
+CREATE FUNCTION encryption.root_key_hash(key text) RETURNS text AS $$
+    SELECT encode(digest(key, 'sha256'), 'hex');
+$$ LANGUAGE sql IMMUTABLE SET search_path = public, pg_temp;
+
+-- The configured root key with the given hash: the current one or, while rotating, the
+-- previous one.
+CREATE FUNCTION encryption.root_key_for(hash text) RETURNS text AS $$
+DECLARE
+    key text;
+BEGIN
+    FOREACH key IN ARRAY ARRAY[
+        encryption.setting('encryption.root_key'),
+        encryption.setting('encryption.previous_root_key')
+    ] LOOP
+        IF key IS NOT NULL AND encryption.root_key_hash(key) = hash THEN
+            RETURN key;
+        END IF;
+    END LOOP;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+-- The unwrapped data key for a version, or NULL when the configured root keys can't
+-- unwrap it. Unwrapped keys are cached for the rest of the transaction.
+CREATE FUNCTION encryption.data_key(key_version int) RETURNS text AS $$
+DECLARE
+    cached text;
+    wrapped bytea;
+    hash text;
+    root text;
+    key text;
+BEGIN
+    cached := encryption.setting('encryption.data_key_' || key_version);
+    IF cached IS NOT NULL THEN
+        RETURN cached;
+    END IF;
+
+    SELECT wrapped_key, root_key_hash INTO wrapped, hash
+    FROM encryption.data_keys WHERE version = key_version;
+    IF NOT FOUND THEN
+        RETURN NULL;
+    END IF;
+
+    root := encryption.root_key_for(hash);
+    IF root IS NULL THEN
+        RETURN NULL;
+    END IF;
+
+    key := pgp_sym_decrypt(wrapped, root);
+    PERFORM set_config('encryption.data_key_' || key_version, key, true);
+    RETURN key;
+EXCEPTION WHEN others THEN
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER SET search_path = public, pg_temp;
+
+-- The version of the data key new values are encrypted with, or NULL when no root key
+-- is configured. The first data key is created on first use.
+CREATE FUNCTION encryption.active_version() RETURNS int AS $$
+DECLARE
+    root text;
+    active_version int;
+BEGIN
+    root := encryption.setting('encryption.root_key');
+    IF root IS NULL THEN
+        RETURN NULL;
+    END IF;
+
+    SELECT version INTO active_version FROM encryption.data_keys WHERE active;
+    IF FOUND THEN
+        RETURN active_version;
+    END IF;
+
+    PERFORM pg_advisory_xact_lock(hashtext('encryption.data_keys'));
+    SELECT version INTO active_version FROM encryption.data_keys WHERE active;
+    IF FOUND THEN
+        RETURN active_version;
+    END IF;
+
+    INSERT INTO encryption.data_keys (wrapped_key, root_key_hash)
+    VALUES (
+        pgp_sym_encrypt(encode(gen_random_bytes(32), 'hex'), root, 'cipher-algo=aes256'),
+        encryption.root_key_hash(root)
+    )
+    RETURNING version INTO active_version;
+    RETURN active_version;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER SET search_path = public, pg_temp;
+
+CREATE FUNCTION encryption.require_data_key(key_version int) RETURNS text AS $$
+DECLARE
+    key text;
+BEGIN
+    key := encryption.data_key(key_version);
+    IF key IS NULL THEN
+        RAISE EXCEPTION 'Data key % can''t be unwrapped with the configured root key', key_version;
+    END IF;
+    RETURN key;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+-- The plain text of a value, the value itself when it was never encrypted, or NULL when
+-- it is encrypted with a key that isn't available.
+CREATE FUNCTION encryption.plaintext(data text) RETURNS text AS $$
+DECLARE
+    key_version int;
+    key text;
+BEGIN
+    IF data IS NULL THEN
+        RETURN NULL;
+    END IF;
+
+    IF data LIKE 'enc:v%:%' THEN
+        key_version := substring(data from '^enc:v([0-9]+):')::int;
+        key := encryption.data_key(key_version);
+        IF key IS NULL THEN
+            RETURN NULL;
+        END IF;
+        BEGIN
+            RETURN pgp_sym_decrypt(
+                decode(substr(data, length('enc:v' || key_version || ':') + 1), 'base64'),
+                key
+            );
+        EXCEPTION WHEN others THEN
+            RETURN NULL;
+        END;
+    END IF;
+
+    IF data ~ '^\\x[0-9a-f]+$' THEN
+        FOREACH key IN ARRAY ARRAY[
+            encryption.setting('encryption.root_key'),
+            encryption.setting('encryption.previous_root_key')
+        ] LOOP
+            IF key IS NOT NULL THEN
+                BEGIN
+                    RETURN pgp_sym_decrypt(data::bytea, key);
+                EXCEPTION WHEN others THEN
+                    NULL;
+                END;
+            END IF;
+        END LOOP;
+        RETURN NULL;
+    END IF;
+
+    RETURN data;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION encryption.plainbytes(data bytea) RETURNS bytea AS $$
+DECLARE
+    key text;
+BEGIN
+    IF data IS NULL OR length(data) < 8 OR substring(data from 1 for 4) <> 'BENC'::bytea THEN
+        RETURN data;
+    END IF;
+
+    key := encryption.data_key(
+        ('x' || encode(substring(data from 5 for 4), 'hex'))::bit(32)::int
+    );
+    IF key IS NULL THEN
+        RETURN NULL;
+    END IF;
+    RETURN pgp_sym_decrypt_bytea(substring(data from 9), key);
+EXCEPTION WHEN others THEN
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION encrypt_text(data text) RETURNS text AS $$
+DECLARE
+    key_version int;
+BEGIN
+    key_version := encryption.active_version();
+    IF data IS NULL OR key_version IS NULL THEN
+        RETURN data;
+    END IF;
+
+    RETURN 'enc:v' || key_version || ':' || encode(
+        pgp_sym_encrypt(
+            data,
+            encryption.require_data_key(key_version),
+            'compress-algo=1, cipher-algo=aes256'
+        ),
+        'base64'
+    );
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION decrypt_text(data text) RETURNS text AS $$
+    -- Values that can't be decrypted are returned as they are
+    SELECT COALESCE(encryption.plaintext(data), data);
+$$ LANGUAGE sql;
+
+CREATE FUNCTION encrypt_bytes(data bytea) RETURNS bytea AS $$
+DECLARE
+    key_version int;
+BEGIN
+    key_version := encryption.active_version();
+    IF data IS NULL OR key_version IS NULL THEN
+        RETURN data;
+    END IF;
+
+    RETURN 'BENC'::bytea || int4send(key_version) || pgp_sym_encrypt_bytea(
+        data,
+        encryption.require_data_key(key_version),
+        'cipher-algo=aes256'
+    );
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION decrypt_bytes(data bytea) RETURNS bytea AS $$
+    SELECT COALESCE(encryption.plainbytes(data), data);
+$$ LANGUAGE sql;
+
+-- The encrypted columns and how to find their rows
+CREATE TABLE encryption.encrypted_columns (
+    table_name TEXT NOT NULL,
+    key_column TEXT NOT NULL,
+    column_name TEXT NOT NULL,
+    is_bytes BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (table_name, column_name)
+);
+
+INSERT INTO encryption.encrypted_columns (table_name, key_column, column_name, is_bytes) VALUES
+    ('llm.chats', 'id', 'content', FALSE),
+    ('rag.chunks', 'id', 'text', FALSE),
+    ('integrations.oauth2_connections', 'id', 'access_token', FALSE),
+    ('integrations.oauth2_connections', 'id', 'refresh_token', FALSE),
+    ('integrations.api_key_connections', 'id', 'api_key', FALSE),
+    ('integrations.openapi_spec_api_keys', 'openapi_spec_id', 'api_key', FALSE),
+    ('iam.oauth_clients', 'id', 'client_secret', FALSE),
+    ('storage.objects', 'id', 'object_data', TRUE);
+
+-- The filter for values of a column that are not encrypted with the given key version
+-- but can be read.
+CREATE FUNCTION encryption.stale_filter(column_name text, is_bytes boolean) RETURNS text AS $$
+    SELECT CASE WHEN is_bytes THEN
+        format(
+            '%1$I IS NOT NULL AND substring(%1$I from 1 for 8) IS DISTINCT FROM ''BENC''::bytea || int4send($1) AND encryption.plainbytes(%1$I) IS NOT NULL',
+            column_name
+        )
+    ELSE
+        format(
+            '%1$I IS NOT NULL AND %1$I NOT LIKE ''enc:v'' || $1 || '':%%'' AND encryption.plaintext(%1$I) IS NOT NULL',
+            column_name
+        )
+    END;
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Re-encrypts up to batch_size values of every encrypted column with the active data
+-- key. Returns how many values each column had re-encrypted; call it until every count
+-- is zero.
+CREATE FUNCTION encryption.reencrypt_batch(batch_size int)
+RETURNS TABLE (table_name text, column_name text, reencrypted bigint) AS $$
+DECLARE
+    target encryption.encrypted_columns;
+    key_version int;
+    updated bigint;
+BEGIN
+    key_version := encryption.active_version();
+    IF key_version IS NULL THEN
+        RAISE EXCEPTION 'No root key is configured';
+    END IF;
+    PERFORM encryption.require_data_key(key_version);
+
+    FOR target IN SELECT * FROM encryption.encrypted_columns ec ORDER BY ec.table_name, ec.column_name LOOP
+        EXECUTE format(
+            'UPDATE %1$s SET %2$I = %3$s WHERE %4$I IN (SELECT %4$I FROM %1$s WHERE %5$s LIMIT $2 FOR UPDATE SKIP LOCKED)',
+            target.table_name,
+            target.column_name,
+            CASE WHEN target.is_bytes
+                THEN format('encrypt_bytes(encryption.plainbytes(%I))', target.column_name)
+                ELSE format('encrypt_text(encryption.plaintext(%I))', target.column_name)
+            END,
+            target.key_column,
+            encryption.stale_filter(target.column_name, target.is_bytes)
+        ) USING key_version, batch_size;
+        GET DIAGNOSTICS updated = ROW_COUNT;
+
+        table_name := target.table_name;
+        column_name := target.column_name;
+        reencrypted := updated;
+        RETURN NEXT;
+    END LOOP;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER SET search_path = public, pg_temp;
+
+-- Values not encrypted with the active data key, including ones that can't be read with
+-- the configured keys. Old data keys can only be deleted once this is empty.
+CREATE FUNCTION encryption.remaining()
+RETURNS TABLE (table_name text, column_name text, remaining bigint) AS $$
+DECLARE
+    target encryption.encrypted_columns;
+    key_version int;
+BEGIN
+    key_version := encryption.active_version();
+    FOR target IN SELECT * FROM encryption.encrypted_columns ec ORDER BY ec.table_name, ec.column_name LOOP
+        table_name := target.table_name;
+        column_name := target.column_name;
+        EXECUTE format(
+            CASE WHEN target.is_bytes THEN
+                'SELECT COUNT(*) FROM %1$s WHERE %2$I IS NOT NULL AND substring(%2$I from 1 for 8) IS DISTINCT FROM ''BENC''::bytea || int4send($1)'
+            ELSE
+                'SELECT COUNT(*) FROM %1$s WHERE %2$I IS NOT NULL AND %2$I NOT LIKE ''enc:v'' || $1 || '':%%'''
+            END,
+            target.table_name,
+            target.column_name
+        ) INTO remaining USING key_version;
+        RETURN NEXT;
+    END LOOP;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER SET search_path = public, pg_temp;
+
+-- Re-wraps every data key with the current root key. Keys wrapped with the previous root
+-- key need encryption.previous_root_key to be set.
+CREATE FUNCTION encryption.rewrap_data_keys() RETURNS int AS $$
+DECLARE
+    root text;
+    data_key encryption.data_keys;
+    key text;
+    rewrapped int := 0;
+BEGIN
+    root := encryption.setting('encryption.root_key');
+    IF root IS NULL THEN
+        RAISE EXCEPTION 'No root key is configured';
+    END IF;
+
+    FOR data_key IN SELECT * FROM encryption.data_keys FOR UPDATE LOOP
+        CONTINUE WHEN data_key.root_key_hash = encryption.root_key_hash(root);
+        key := encryption.data_key(data_key.version);
+        IF key IS NULL THEN
+            RAISE EXCEPTION 'Data key % is wrapped with an unknown root key; set the previous key', data_key.version;
+        END IF;
+        UPDATE encryption.data_keys
+        SET
+            wrapped_key = pgp_sym_encrypt(key, root, 'cipher-algo=aes256'),
+            root_key_hash = encryption.root_key_hash(root)
+        WHERE version = data_key.version;
+        rewrapped := rewrapped + 1;
+    END LOOP;
+    RETURN rewrapped;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER SET search_path = public, pg_temp;
+
+-- Retires the active data key and creates a new one. Returns the new version.
+CREATE FUNCTION encryption.rotate_data_key() RETURNS int AS $$
+BEGIN
+    IF encryption.setting('encryption.root_key') IS NULL THEN
+        RAISE EXCEPTION 'No root key is configured';
+    END IF;
+    PERFORM pg_advisory_xact_lock(hashtext('encryption.data_keys'));
+    UPDATE encryption.data_keys SET active = FALSE, retired_at = NOW() WHERE active;
+    RETURN encryption.active_version();
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER SET search_path = public, pg_temp;
+
+-- Deletes retired data keys once nothing is encrypted with them. Returns how many.
+CREATE FUNCTION encryption.delete_retired_keys() RETURNS int AS $$
+DECLARE
+    deleted int;
+BEGIN
+    IF EXISTS (SELECT 1 FROM encryption.remaining() r WHERE r.remaining > 0) THEN
+        RETURN 0;
+    END IF;
+    DELETE FROM encryption.data_keys WHERE NOT active;
+    GET DIAGNOSTICS deleted = ROW_COUNT;
+    RETURN deleted;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER SET search_path = public, pg_temp;
+
+-- The data keys are only reached through the functions above
+GRANT USAGE ON SCHEMA encryption TO application_user;
+GRANT USAGE ON SCHEMA encryption TO application_readonly;
+REVOKE ALL ON ALL TABLES IN SCHEMA encryption FROM application_user, application_readonly;
+
+-- migrate:down
+DROP FUNCTION decrypt_bytes(bytea);
+DROP FUNCTION encrypt_bytes(bytea);
+
+CREATE OR REPLACE FUNCTION encrypt_text(data text) RETURNS text AS $$
+DECLARE
+    key text;
+BEGIN
+    key := current_setting('encryption.root_key', true);
+    IF key IS NULL THEN
+        RETURN data;
+    END IF;
+    RETURN pgp_sym_encrypt(data, key, 'compress-algo=1, cipher-algo=aes256');
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION decrypt_text(data text) RETURNS text AS $$
+DECLARE
+    key text;
+BEGIN
+    key := current_setting('encryption.root_key', true);
+    IF key IS NULL THEN
+        RETURN data;
+    END IF;
+    BEGIN
+        RETURN pgp_sym_decrypt(data::bytea, key);
+    EXCEPTION WHEN others THEN
+        RETURN data;
+    END;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP SCHEMA encryption CASCADE;
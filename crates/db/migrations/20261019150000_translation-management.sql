@@ -0,0 +1,13 @@
+-- migrate:up
+
+-- Translations are edited and imported from the admin pages
+GRANT INSERT, UPDATE, DELETE ON ops.translations TO application_user;
+
+-- The locale a user picked, overrides the browser's Accept-Language
+ALTER TABLE iam.users ADD COLUMN locale TEXT;
+
+-- migrate:down
+
+ALTER TABLE iam.users DROP COLUMN locale;
+
+REVOKE INSERT, UPDATE, DELETE ON ops.translations FROM application_user;
@@ -0,0 +1,199 @@
+-- migrate:up
+
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'CreateConfiguration';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'UpdateConfiguration';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'DeleteConfiguration';
+
+-- Where the request came from. The web server sets audit.client_ip with the RLS user.
+ALTER TABLE ops.audit_trail
+    ADD COLUMN ip_address TEXT DEFAULT NULLIF(current_setting('audit.client_ip', true), '');
+
+CREATE INDEX idx_audit_trail_created_at ON ops.audit_trail (created_at);
+
+-- The before and after of a configuration change. Updates only keep the fields that
+-- changed.
+CREATE TABLE ops.audit_trail_config_changes (
+    id INT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    audit_id INT NOT NULL REFERENCES ops.audit_trail(id) ON DELETE CASCADE,
+    object_type TEXT NOT NULL,
+    object_id TEXT,
+    before JSONB,
+    after JSONB
+);
+
+CREATE INDEX idx_audit_trail_config_changes_audit_id ON ops.audit_trail_config_changes (audit_id);
+
+-- Secrets never reach the audit trail and long values (logos, specs, file contents)
+-- are shortened.
+CREATE FUNCTION ops.audit_redact(data jsonb) RETURNS jsonb AS $$
+    SELECT jsonb_object_agg(
+        key,
+        CASE
+            WHEN value = 'null'::jsonb THEN value
+            WHEN key ~ '(secret|api_key|token|password)' THEN to_jsonb('[redacted]'::text)
+            WHEN length(value::text) > 1000 THEN to_jsonb(left(value #>> '{}', 1000) || '…')
+            ELSE value
+        END
+    )
+    FROM jsonb_each(data);
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Records inserts, updates and deletes of configuration tables.
+-- TG_ARGV[0] is the column that identifies a row, any further arguments are columns
+-- that change at runtime and aren't configuration.
+CREATE FUNCTION ops.audit_config_change()
+   RETURNS TRIGGER
+   LANGUAGE PLPGSQL
+AS $$
+DECLARE
+  actor INT;
+  old_row jsonb;
+  new_row jsonb;
+  before_row jsonb;
+  after_row jsonb;
+  row_data jsonb;
+  ignored text[];
+  change_team_id INT;
+  audit_id ops.audit_trail.id%type;
+BEGIN
+  -- Migrations and background jobs run without a user and aren't admin changes.
+  actor := NULLIF(current_setting('row_level_security.user_id', true), '')::int;
+  IF actor IS NULL THEN
+    RETURN NULL;
+  END IF;
+
+  ignored := ARRAY['created_at', 'updated_at'] || TG_ARGV[1:];
+  IF TG_OP <> 'INSERT' THEN
+    old_row := to_jsonb(OLD);
+  END IF;
+  IF TG_OP <> 'DELETE' THEN
+    new_row := to_jsonb(NEW);
+  END IF;
+
+  IF TG_OP = 'UPDATE' THEN
+    SELECT jsonb_object_agg(o.key, o.value) INTO before_row
+    FROM jsonb_each(old_row) o
+    WHERE o.key <> ALL(ignored) AND o.value IS DISTINCT FROM new_row -> o.key;
+    SELECT jsonb_object_agg(n.key, n.value) INTO after_row
+    FROM jsonb_each(new_row) n
+    WHERE n.key <> ALL(ignored) AND n.value IS DISTINCT FROM old_row -> n.key;
+    IF after_row IS NULL THEN
+      RETURN NULL;
+    END IF;
+  ELSE
+    before_row := old_row;
+    after_row := new_row;
+  END IF;
+
+  row_data := COALESCE(new_row, old_row);
+  -- The team may be the one being deleted.
+  SELECT t.id INTO change_team_id FROM iam.teams t WHERE t.id = (row_data ->> 'team_id')::int;
+
+  INSERT INTO ops.audit_trail
+  (
+    user_id,
+    team_id,
+    access_type,
+    action
+  )
+  VALUES(
+    actor,
+    change_team_id,
+    'UserInterface',
+    (CASE TG_OP
+      WHEN 'INSERT' THEN 'CreateConfiguration'
+      WHEN 'UPDATE' THEN 'UpdateConfiguration'
+      ELSE 'DeleteConfiguration'
+    END)::audit_action
+  )
+  RETURNING id INTO audit_id;
+
+  INSERT INTO ops.audit_trail_config_changes (audit_id, object_type, object_id, before, after)
+  VALUES (
+    audit_id,
+    TG_TABLE_SCHEMA || '.' || TG_TABLE_NAME,
+    row_data ->> TG_ARGV[0],
+    ops.audit_redact(before_row),
+    ops.audit_redact(after_row)
+  );
+
+  RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON model_registry.models
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON model_registry.providers
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON model_registry.model_capabilities
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('model_id');
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON model_registry.model_endpoints
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change(
+    'id', 'consecutive_failures', 'circuit_open_until', 'last_error', 'last_failure_at', 'last_success_at'
+  );
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON integrations.integrations
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON integrations.openapi_specs
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON integrations.openapi_spec_selections
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('category');
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON integrations.openapi_spec_api_keys
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('openapi_spec_id');
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON integrations.tool_call_policies
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON iam.oauth_clients
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON context.skills
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON context.skill_files
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON llm.rate_limits
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON llm.moderation_policies
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON ops.runtime_settings
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('key');
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON ops.translations
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON assistants.categories
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+
+-- How far each external sink (syslog, webhook) has been sent.
+CREATE TABLE ops.audit_sink_cursors (
+    sink TEXT PRIMARY KEY,
+    last_audit_id INT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+GRANT SELECT, INSERT ON ops.audit_trail_config_changes TO application_user;
+GRANT USAGE, SELECT ON ops.audit_trail_config_changes_id_seq TO application_user;
+GRANT SELECT ON ops.audit_trail_config_changes TO application_readonly;
+GRANT SELECT ON ops.audit_trail_config_changes_id_seq TO application_readonly;
+GRANT SELECT, INSERT, UPDATE ON ops.audit_sink_cursors TO application_user;
+GRANT SELECT ON ops.audit_sink_cursors TO application_readonly;
+
+-- migrate:down
+
+DROP TRIGGER audit_config_change ON model_registry.models;
+DROP TRIGGER audit_config_change ON model_registry.providers;
+DROP TRIGGER audit_config_change ON model_registry.model_capabilities;
+DROP TRIGGER audit_config_change ON model_registry.model_endpoints;
+DROP TRIGGER audit_config_change ON integrations.integrations;
+DROP TRIGGER audit_config_change ON integrations.openapi_specs;
+DROP TRIGGER audit_config_change ON integrations.openapi_spec_selections;
+DROP TRIGGER audit_config_change ON integrations.openapi_spec_api_keys;
+DROP TRIGGER audit_config_change ON integrations.tool_call_policies;
+DROP TRIGGER audit_config_change ON iam.oauth_clients;
+DROP TRIGGER audit_config_change ON context.skills;
+DROP TRIGGER audit_config_change ON context.skill_files;
+DROP TRIGGER audit_config_change ON llm.rate_limits;
+DROP TRIGGER audit_config_change ON llm.moderation_policies;
+DROP TRIGGER audit_config_change ON ops.runtime_settings;
+DROP TRIGGER audit_config_change ON ops.translations;
+DROP TRIGGER audit_config_change ON assistants.categories;
+DROP FUNCTION ops.audit_config_change;
+DROP FUNCTION ops.audit_redact;
+DROP TABLE ops.audit_sink_cursors;
+DROP TABLE ops.audit_trail_config_changes;
+DROP INDEX ops.idx_audit_trail_created_at;
+ALTER TABLE ops.audit_trail DROP COLUMN ip_address;
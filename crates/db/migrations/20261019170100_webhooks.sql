@@ -0,0 +1,222 @@
+-- migrate:up
+
+INSERT INTO iam.roles_permissions VALUES('TeamManager', 'ManageWebhooks');
+INSERT INTO iam.roles_permissions VALUES('SystemAdministrator', 'ManageWebhooks');
+
+CREATE TYPE webhook_delivery_status AS ENUM (
+    'Pending',
+    'Delivered',
+    'Failed'
+);
+
+-- A team's subscription to platform events.
+CREATE TABLE ops.webhooks (
+    id INT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    team_id INT NOT NULL REFERENCES iam.teams(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    secret TEXT NOT NULL,
+    events TEXT[] NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by INT NOT NULL REFERENCES iam.users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT updated_at('ops.webhooks');
+
+COMMENT ON COLUMN ops.webhooks.secret IS 'Signs each payload, stored with encrypt_text';
+COMMENT ON COLUMN ops.webhooks.events IS 'Event types such as document.processed';
+
+-- One event for one webhook. The queue is the rows still Pending.
+CREATE TABLE ops.webhook_deliveries (
+    id INT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    webhook_id INT NOT NULL REFERENCES ops.webhooks(id) ON DELETE CASCADE,
+    event_id UUID NOT NULL DEFAULT gen_random_uuid(),
+    event_type TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    status webhook_delivery_status NOT NULL DEFAULT 'Pending',
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    response_status INT,
+    response_body TEXT,
+    last_error TEXT,
+    delivered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT updated_at('ops.webhook_deliveries');
+
+CREATE INDEX idx_webhook_deliveries_pending
+    ON ops.webhook_deliveries (next_attempt_at) WHERE status = 'Pending';
+CREATE INDEX idx_webhook_deliveries_webhook_id ON ops.webhook_deliveries (webhook_id, id);
+
+COMMENT ON COLUMN ops.webhook_deliveries.event_id IS 'Stays the same on retries and replays so receivers can deduplicate';
+
+-- Queues an event for every active webhook of the team subscribed to it.
+CREATE FUNCTION ops.enqueue_webhook_event(event_team_id INT, event_type TEXT, payload JSONB)
+RETURNS INT AS $$
+    WITH queued AS (
+        INSERT INTO ops.webhook_deliveries (webhook_id, event_type, payload)
+        SELECT w.id, event_type, payload
+        FROM ops.webhooks w
+        WHERE w.team_id = event_team_id
+        AND w.active
+        AND event_type = ANY(w.events)
+        RETURNING id
+    )
+    SELECT COUNT(*)::INT FROM queued;
+$$ LANGUAGE sql;
+
+-- A document is processed when its last chunk has been embedded.
+CREATE FUNCTION ops.webhook_document_processed()
+   RETURNS TRIGGER
+   LANGUAGE PLPGSQL
+AS $$
+BEGIN
+  IF NOT EXISTS (
+    SELECT 1 FROM rag.chunks c WHERE c.document_id = NEW.document_id AND c.processed IS NOT TRUE
+  ) THEN
+    PERFORM ops.enqueue_webhook_event(
+      ds.team_id,
+      'document.processed',
+      jsonb_build_object(
+        'document_id', d.id,
+        'file_name', d.file_name,
+        'dataset_id', ds.id,
+        'dataset', ds.name,
+        'chunks', (SELECT COUNT(*) FROM rag.chunks c WHERE c.document_id = d.id)
+      )
+    )
+    FROM rag.documents d
+    JOIN rag.datasets ds ON ds.id = d.dataset_id
+    WHERE d.id = NEW.document_id;
+  END IF;
+  RETURN NULL;
+END;
+$$;
+
+CREATE FUNCTION ops.webhook_document_failed()
+   RETURNS TRIGGER
+   LANGUAGE PLPGSQL
+AS $$
+BEGIN
+  PERFORM ops.enqueue_webhook_event(
+    ds.team_id,
+    'document.failed',
+    jsonb_build_object(
+      'document_id', NEW.id,
+      'file_name', NEW.file_name,
+      'dataset_id', ds.id,
+      'dataset', ds.name,
+      'failure_reason', NEW.failure_reason
+    )
+  )
+  FROM rag.datasets ds
+  WHERE ds.id = NEW.dataset_id;
+  RETURN NULL;
+END;
+$$;
+
+CREATE FUNCTION ops.webhook_chat_flagged()
+   RETURNS TRIGGER
+   LANGUAGE PLPGSQL
+AS $$
+BEGIN
+  PERFORM ops.enqueue_webhook_event(
+    conv.team_id,
+    'chat.flagged',
+    jsonb_build_object(
+      'flag_id', NEW.id,
+      'chat_id', NEW.chat_id,
+      'conversation_id', conv.id,
+      'user', u.email,
+      'flag_type', NEW.flag_type,
+      'direction', NEW.direction,
+      'action', NEW.action
+    )
+  )
+  FROM llm.chats c
+  JOIN llm.conversations conv ON conv.id = c.conversation_id
+  JOIN iam.users u ON u.id = conv.user_id
+  WHERE c.id = NEW.chat_id;
+  RETURN NULL;
+END;
+$$;
+
+CREATE FUNCTION ops.webhook_team_member_joined()
+   RETURNS TRIGGER
+   LANGUAGE PLPGSQL
+AS $$
+BEGIN
+  PERFORM ops.enqueue_webhook_event(
+    NEW.team_id,
+    'team.member_joined',
+    jsonb_build_object(
+      'user_id', u.id,
+      'email', u.email,
+      'roles', NEW.roles
+    )
+  )
+  FROM iam.users u
+  WHERE u.id = NEW.user_id;
+  RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER webhook_document_processed
+  AFTER UPDATE OF processed ON rag.chunks
+  FOR EACH ROW
+  WHEN (NEW.processed IS TRUE AND OLD.processed IS NOT TRUE)
+  EXECUTE PROCEDURE ops.webhook_document_processed();
+
+CREATE TRIGGER webhook_document_failed
+  AFTER UPDATE OF failure_reason ON rag.documents
+  FOR EACH ROW
+  WHEN (OLD.failure_reason IS NULL AND NEW.failure_reason IS NOT NULL)
+  EXECUTE PROCEDURE ops.webhook_document_failed();
+
+CREATE TRIGGER webhook_chat_flagged
+  AFTER INSERT ON llm.prompt_flags
+  FOR EACH ROW
+  EXECUTE PROCEDURE ops.webhook_chat_flagged();
+
+CREATE TRIGGER webhook_team_member_joined
+  AFTER INSERT ON iam.team_users
+  FOR EACH ROW
+  EXECUTE PROCEDURE ops.webhook_team_member_joined();
+
+-- The processed trigger looks up the other chunks of the document.
+CREATE INDEX IF NOT EXISTS idx_chunks_document_id ON rag.chunks (document_id);
+
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON ops.webhooks
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON ops.webhooks TO application_user;
+GRANT USAGE, SELECT ON ops.webhooks_id_seq TO application_user;
+GRANT SELECT ON ops.webhooks TO application_readonly;
+GRANT SELECT ON ops.webhooks_id_seq TO application_readonly;
+
+GRANT SELECT, INSERT, UPDATE ON ops.webhook_deliveries TO application_user;
+GRANT USAGE, SELECT ON ops.webhook_deliveries_id_seq TO application_user;
+GRANT SELECT ON ops.webhook_deliveries TO application_readonly;
+GRANT SELECT ON ops.webhook_deliveries_id_seq TO application_readonly;
+
+-- migrate:down
+
+DROP TRIGGER audit_config_change ON ops.webhooks;
+DROP TRIGGER webhook_team_member_joined ON iam.team_users;
+DROP TRIGGER webhook_chat_flagged ON llm.prompt_flags;
+DROP TRIGGER webhook_document_failed ON rag.documents;
+DROP TRIGGER webhook_document_processed ON rag.chunks;
+DROP INDEX rag.idx_chunks_document_id;
+DROP FUNCTION ops.webhook_team_member_joined;
+DROP FUNCTION ops.webhook_chat_flagged;
+DROP FUNCTION ops.webhook_document_failed;
+DROP FUNCTION ops.webhook_document_processed;
+DROP FUNCTION ops.enqueue_webhook_event;
+DROP TABLE ops.webhook_deliveries;
+DROP TABLE ops.webhooks;
+DROP TYPE webhook_delivery_status;
+DELETE FROM iam.roles_permissions WHERE permission = 'ManageWebhooks';
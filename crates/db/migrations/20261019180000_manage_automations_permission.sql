@@ -0,0 +1,6 @@
+-- migrate:up
+
+ALTER TYPE permission ADD VALUE IF NOT EXISTS 'ManageAutomations';
+
+-- migrate:down
+-- Enum values cannot be removed; no-op.
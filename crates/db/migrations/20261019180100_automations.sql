@@ -0,0 +1,142 @@
+-- migrate:up
+
+INSERT INTO iam.roles_permissions VALUES('Collaborator', 'ManageAutomations');
+INSERT INTO iam.roles_permissions VALUES('TeamManager', 'ManageAutomations');
+INSERT INTO iam.roles_permissions VALUES('SystemAdministrator', 'ManageAutomations');
+
+CREATE SCHEMA IF NOT EXISTS automations;
+GRANT USAGE ON SCHEMA automations TO application_user;
+GRANT USAGE ON SCHEMA automations TO application_readonly;
+
+CREATE TYPE automation_trigger AS ENUM (
+    'Schedule',
+    'DocumentProcessed',
+    'Webhook'
+);
+
+CREATE TYPE automation_run_status AS ENUM (
+    'Pending',
+    'Running',
+    'Success',
+    'AwaitingApproval',
+    'Failed'
+);
+
+-- A prompt template sent to an assistant on a schedule or when something happens.
+-- Runs are made as the owner, so they see the owner's datasets and integrations.
+CREATE TABLE automations.automations (
+    id INT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    team_id INT NOT NULL REFERENCES iam.teams(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES iam.users(id) ON DELETE CASCADE,
+    prompt_id INT NOT NULL REFERENCES assistants.prompts(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    prompt_template TEXT NOT NULL,
+    trigger_type automation_trigger NOT NULL,
+    cron TEXT,
+    dataset_id INT REFERENCES rag.datasets(id) ON DELETE CASCADE,
+    webhook_token TEXT UNIQUE,
+    notify_email TEXT,
+    notify_webhook_url TEXT,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    next_run_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (trigger_type <> 'Schedule' OR cron IS NOT NULL),
+    CHECK (trigger_type <> 'DocumentProcessed' OR dataset_id IS NOT NULL),
+    CHECK (trigger_type <> 'Webhook' OR webhook_token IS NOT NULL)
+);
+
+SELECT updated_at('automations.automations');
+
+CREATE INDEX idx_automations_team_id ON automations.automations (team_id);
+CREATE INDEX idx_automations_due ON automations.automations (next_run_at)
+    WHERE trigger_type = 'Schedule' AND enabled;
+
+COMMENT ON COLUMN automations.automations.cron IS 'Five field cron expression, evaluated in UTC';
+COMMENT ON COLUMN automations.automations.prompt_template IS 'Placeholders like {{document.file_name}} are filled from the event';
+COMMENT ON COLUMN automations.automations.webhook_token IS 'Bearer token for POST /v1/automations/trigger';
+COMMENT ON COLUMN automations.automations.next_run_at IS 'When the schedule next fires, set by the web server';
+
+-- One run of an automation. The queue is the rows still Pending.
+CREATE TABLE automations.automation_runs (
+    id INT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    automation_id INT NOT NULL REFERENCES automations.automations(id) ON DELETE CASCADE,
+    trigger_type automation_trigger NOT NULL,
+    event JSONB NOT NULL DEFAULT '{}',
+    status automation_run_status NOT NULL DEFAULT 'Pending',
+    attempts INT NOT NULL DEFAULT 0,
+    locked_until TIMESTAMPTZ,
+    conversation_id BIGINT REFERENCES llm.conversations(id) ON DELETE SET NULL,
+    error TEXT,
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT updated_at('automations.automation_runs');
+
+CREATE INDEX idx_automation_runs_queue
+    ON automations.automation_runs (created_at) WHERE status IN ('Pending', 'Running');
+CREATE INDEX idx_automation_runs_automation_id ON automations.automation_runs (automation_id, id);
+
+COMMENT ON COLUMN automations.automation_runs.event IS 'What started the run, used to fill the prompt template';
+
+-- Starts the automations watching a dataset once one of its documents is processed.
+CREATE FUNCTION automations.document_processed()
+   RETURNS TRIGGER
+   LANGUAGE PLPGSQL
+AS $$
+BEGIN
+  IF NOT EXISTS (
+    SELECT 1 FROM rag.chunks c WHERE c.document_id = NEW.document_id AND c.processed IS NOT TRUE
+  ) THEN
+    INSERT INTO automations.automation_runs (automation_id, trigger_type, event)
+    SELECT
+      a.id,
+      'DocumentProcessed',
+      jsonb_build_object(
+        'document', jsonb_build_object('id', d.id, 'file_name', d.file_name),
+        'dataset', jsonb_build_object('id', ds.id, 'name', ds.name)
+      )
+    FROM rag.documents d
+    JOIN rag.datasets ds ON ds.id = d.dataset_id
+    JOIN automations.automations a ON a.dataset_id = ds.id
+    WHERE d.id = NEW.document_id
+    AND a.trigger_type = 'DocumentProcessed'
+    AND a.enabled;
+  END IF;
+  RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER automations_document_processed
+  AFTER UPDATE OF processed ON rag.chunks
+  FOR EACH ROW
+  WHEN (NEW.processed IS TRUE AND OLD.processed IS NOT TRUE)
+  EXECUTE PROCEDURE automations.document_processed();
+
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON automations.automations
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id', 'next_run_at');
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON automations.automations TO application_user;
+GRANT USAGE, SELECT ON automations.automations_id_seq TO application_user;
+GRANT SELECT ON automations.automations TO application_readonly;
+GRANT SELECT ON automations.automations_id_seq TO application_readonly;
+
+GRANT SELECT, INSERT, UPDATE ON automations.automation_runs TO application_user;
+GRANT USAGE, SELECT ON automations.automation_runs_id_seq TO application_user;
+GRANT SELECT ON automations.automation_runs TO application_readonly;
+GRANT SELECT ON automations.automation_runs_id_seq TO application_readonly;
+
+-- migrate:down
+
+DROP TRIGGER audit_config_change ON automations.automations;
+DROP TRIGGER automations_document_processed ON rag.chunks;
+DROP FUNCTION automations.document_processed;
+DROP TABLE automations.automation_runs;
+DROP TABLE automations.automations;
+DROP TYPE automation_run_status;
+DROP TYPE automation_trigger;
+DROP SCHEMA automations;
+DELETE FROM iam.roles_permissions WHERE permission = 'ManageAutomations';
@@ -0,0 +1,10 @@
+-- migrate:up
+
+-- Chunkers of the native chunking engine, which runs inside rag-engine.
+ALTER TYPE chunking_strategy ADD VALUE IF NOT EXISTS 'RecursiveCharacter';
+ALTER TYPE chunking_strategy ADD VALUE IF NOT EXISTS 'SentenceWindow';
+ALTER TYPE chunking_strategy ADD VALUE IF NOT EXISTS 'MarkdownHeading';
+ALTER TYPE chunking_strategy ADD VALUE IF NOT EXISTS 'TablePreserving';
+
+-- migrate:down
+-- Enum values cannot be removed; no-op.
@@ -0,0 +1,15 @@
+-- migrate:up
+
+-- Where each chunk came from: its heading path, element type, offsets in the page,
+-- whether it is a table or figure and, for web pages, the source URL. Unlike the chunk
+-- text it isn't encrypted, so that searches can filter on it.
+ALTER TABLE rag.chunks ADD COLUMN metadata JSONB NOT NULL DEFAULT '{}';
+
+COMMENT ON COLUMN rag.chunks.metadata IS 'Heading path, element type, offsets and flags written by rag-engine';
+
+-- Filters are containment queries such as metadata @> '{"is_table": true}'.
+CREATE INDEX chunks_metadata_idx ON rag.chunks USING GIN (metadata jsonb_path_ops);
+
+-- migrate:down
+DROP INDEX rag.chunks_metadata_idx;
+ALTER TABLE rag.chunks DROP COLUMN metadata;
@@ -0,0 +1,62 @@
+-- migrate:up
+
+-- Documents in higher priority datasets are chunked and embedded first.
+ALTER TABLE rag.datasets ADD COLUMN priority INT NOT NULL DEFAULT 0;
+
+-- rag-engine replicas share the work by leasing rows. A worker holds a document until
+-- locked_until and extends the lease while it works. If the worker stops, the document
+-- is picked up again once the lease runs out.
+ALTER TABLE rag.documents
+    ADD COLUMN chunked_at TIMESTAMPTZ,
+    ADD COLUMN attempts INT NOT NULL DEFAULT 0,
+    ADD COLUMN locked_by TEXT,
+    ADD COLUMN locked_until TIMESTAMPTZ;
+
+UPDATE rag.documents d
+SET chunked_at = d.updated_at
+WHERE EXISTS (SELECT 1 FROM rag.chunks c WHERE c.document_id = d.id);
+
+ALTER TABLE rag.chunks ADD COLUMN locked_until TIMESTAMPTZ;
+
+COMMENT ON COLUMN rag.datasets.priority IS 'Higher priority datasets are processed first';
+COMMENT ON COLUMN rag.documents.chunked_at IS 'When the chunks were stored. Until then the document is queued';
+COMMENT ON COLUMN rag.documents.locked_by IS 'The rag-engine worker holding the lease';
+
+CREATE INDEX idx_documents_queue ON rag.documents (id)
+    WHERE chunked_at IS NULL AND failure_reason IS NULL;
+CREATE INDEX idx_chunks_queue ON rag.chunks (id) WHERE processed IS NOT TRUE;
+
+-- Wakes rag-engine when there is work, so it doesn't have to poll.
+CREATE FUNCTION rag.notify_ingestion()
+   RETURNS TRIGGER
+   LANGUAGE PLPGSQL
+AS $$
+BEGIN
+  PERFORM pg_notify('rag_ingestion', TG_TABLE_NAME);
+  RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER documents_notify_ingestion
+  AFTER INSERT ON rag.documents
+  FOR EACH STATEMENT
+  EXECUTE PROCEDURE rag.notify_ingestion();
+
+CREATE TRIGGER chunks_notify_ingestion
+  AFTER INSERT ON rag.chunks
+  FOR EACH STATEMENT
+  EXECUTE PROCEDURE rag.notify_ingestion();
+
+-- migrate:down
+DROP TRIGGER chunks_notify_ingestion ON rag.chunks;
+DROP TRIGGER documents_notify_ingestion ON rag.documents;
+DROP FUNCTION rag.notify_ingestion();
+DROP INDEX rag.idx_chunks_queue;
+DROP INDEX rag.idx_documents_queue;
+ALTER TABLE rag.chunks DROP COLUMN locked_until;
+ALTER TABLE rag.documents
+    DROP COLUMN chunked_at,
+    DROP COLUMN attempts,
+    DROP COLUMN locked_by,
+    DROP COLUMN locked_until;
+ALTER TABLE rag.datasets DROP COLUMN priority;
@@ -0,0 +1,72 @@
+-- migrate:up
+
+-- Conversation turns embedded for history search. A row is queued when a user or
+-- assistant turn succeeds, and rag-engine fills in the embeddings. The content stays
+-- encrypted in llm.chats, so only the vector is stored here.
+CREATE TABLE llm.chat_embeddings (
+    chat_id INT PRIMARY KEY REFERENCES llm.chats(id) ON DELETE CASCADE,
+    model_id INT REFERENCES model_registry.models(id) ON DELETE SET NULL,
+    embeddings vector,
+    processed BOOLEAN NOT NULL DEFAULT FALSE,
+    locked_until TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT updated_at('llm.chat_embeddings');
+
+CREATE INDEX idx_chat_embeddings_queue ON llm.chat_embeddings (chat_id) WHERE processed IS NOT TRUE;
+
+COMMENT ON TABLE llm.chat_embeddings IS 'Embeddings of conversation turns, used to search chat history';
+COMMENT ON COLUMN llm.chat_embeddings.model_id IS 'The model that made the embeddings. Searches only compare embeddings from the same model';
+COMMENT ON COLUMN llm.chat_embeddings.processed IS 'Set once rag-engine has tried to embed the turn, whether or not it succeeded';
+
+-- Teams don't choose a model for history, so we use the one their newest dataset
+-- embeds with, or else the first embeddings model that was set up.
+CREATE FUNCTION llm.history_embeddings_model(p_team_id INT)
+   RETURNS INT
+   LANGUAGE SQL
+   STABLE
+AS $$
+  SELECT COALESCE(
+    (SELECT embeddings_model_id FROM rag.datasets
+     WHERE team_id = p_team_id ORDER BY created_at DESC, id DESC LIMIT 1),
+    (SELECT id FROM model_registry.models
+     WHERE model_type = 'Embeddings' ORDER BY created_at, id LIMIT 1)
+  );
+$$;
+
+-- Queues a turn for embedding when it succeeds, or again when its content changes.
+CREATE FUNCTION llm.queue_chat_embedding()
+   RETURNS TRIGGER
+   LANGUAGE PLPGSQL
+AS $$
+BEGIN
+  INSERT INTO llm.chat_embeddings (chat_id)
+  VALUES (NEW.id)
+  ON CONFLICT (chat_id) DO UPDATE
+  SET processed = FALSE, embeddings = NULL, model_id = NULL, locked_until = NULL;
+  PERFORM pg_notify('rag_ingestion', 'chat_embeddings');
+  RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER chats_queue_embedding
+  AFTER INSERT OR UPDATE OF status, content ON llm.chats
+  FOR EACH ROW
+  WHEN (NEW.status = 'Success' AND NEW.role IN ('User', 'Assistant') AND NEW.content IS NOT NULL)
+  EXECUTE PROCEDURE llm.queue_chat_embedding();
+
+-- Existing history is embedded in the background like new turns.
+INSERT INTO llm.chat_embeddings (chat_id)
+SELECT id FROM llm.chats
+WHERE status = 'Success' AND role IN ('User', 'Assistant') AND content IS NOT NULL;
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON llm.chat_embeddings TO application_user;
+GRANT SELECT ON llm.chat_embeddings TO application_readonly;
+
+-- migrate:down
+DROP TRIGGER chats_queue_embedding ON llm.chats;
+DROP FUNCTION llm.queue_chat_embedding();
+DROP FUNCTION llm.history_embeddings_model(INT);
+DROP TABLE llm.chat_embeddings;
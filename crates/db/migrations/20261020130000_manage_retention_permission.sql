@@ -0,0 +1,9 @@
+-- migrate:up
+
+ALTER TYPE permission ADD VALUE IF NOT EXISTS 'ManageRetention';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'RetentionPurge';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'ExportUserData';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'EraseUserData';
+
+-- migrate:down
+-- Enum values cannot be removed; no-op.
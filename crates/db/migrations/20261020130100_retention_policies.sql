@@ -0,0 +1,39 @@
+-- migrate:up
+
+INSERT INTO iam.roles_permissions VALUES('TeamManager', 'ManageRetention');
+INSERT INTO iam.roles_permissions VALUES('SystemAdministrator', 'ManageRetention');
+
+-- How long a team keeps its content. Each limit is in days and NULL keeps that
+-- content for ever. The web server purges what is older once an hour.
+CREATE TABLE ops.retention_policies (
+    team_id INT PRIMARY KEY REFERENCES iam.teams(id) ON DELETE CASCADE,
+    conversation_days INT CHECK (conversation_days > 0),
+    attachment_days INT CHECK (attachment_days > 0),
+    generated_output_days INT CHECK (generated_output_days > 0),
+    token_usage_days INT CHECK (token_usage_days > 0),
+    updated_by INT REFERENCES iam.users(id) ON DELETE SET NULL,
+    last_run_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT updated_at('ops.retention_policies');
+
+COMMENT ON COLUMN ops.retention_policies.conversation_days IS 'Conversations with no messages for this many days are deleted, with their files';
+COMMENT ON COLUMN ops.retention_policies.attachment_days IS 'Files attached to chats are deleted after this many days';
+COMMENT ON COLUMN ops.retention_policies.generated_output_days IS 'Files generated by tools are deleted after this many days';
+COMMENT ON COLUMN ops.retention_policies.token_usage_days IS 'Token usage metrics are deleted after this many days';
+COMMENT ON COLUMN ops.retention_policies.updated_by IS 'Purges are recorded in the audit trail as this user';
+COMMENT ON COLUMN ops.retention_policies.last_run_at IS 'When the policy was last enforced, set by the web server';
+
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON ops.retention_policies
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('team_id', 'last_run_at');
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON ops.retention_policies TO application_user;
+GRANT SELECT ON ops.retention_policies TO application_readonly;
+
+-- migrate:down
+
+DROP TRIGGER audit_config_change ON ops.retention_policies;
+DROP TABLE ops.retention_policies;
+DELETE FROM iam.roles_permissions WHERE permission = 'ManageRetention';
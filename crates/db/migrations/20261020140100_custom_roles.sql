@@ -0,0 +1,56 @@
+-- migrate:up
+
+INSERT INTO iam.roles_permissions VALUES('TeamManager', 'ManageRoles');
+INSERT INTO iam.roles_permissions VALUES('SystemAdministrator', 'ManageRoles');
+
+-- Roles a team defines for itself from the team level permissions, alongside the
+-- built in Team Manager and Collaborator roles.
+CREATE TABLE iam.custom_roles (
+    id INT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    team_id INT NOT NULL REFERENCES iam.teams(id) ON DELETE CASCADE,
+    name VARCHAR NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    permissions permission[] NOT NULL DEFAULT '{}',
+    created_by INT REFERENCES iam.users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (team_id, name),
+    -- Lets members and invitations reference a role of their own team only.
+    UNIQUE (id, team_id)
+);
+
+SELECT updated_at('iam.custom_roles');
+
+COMMENT ON TABLE iam.custom_roles IS 'Team defined roles, granted to members on top of iam.team_users.roles';
+
+-- The custom roles each member of a team has.
+CREATE TABLE iam.team_users_custom_roles (
+    user_id INT NOT NULL,
+    team_id INT NOT NULL,
+    custom_role_id INT NOT NULL,
+    PRIMARY KEY (user_id, team_id, custom_role_id),
+    FOREIGN KEY (user_id, team_id) REFERENCES iam.team_users(user_id, team_id) ON DELETE CASCADE,
+    FOREIGN KEY (custom_role_id, team_id) REFERENCES iam.custom_roles(id, team_id) ON DELETE CASCADE
+);
+
+-- The custom role a member gets when they accept the invitation.
+ALTER TABLE iam.invitations
+    ADD COLUMN custom_role_id INT REFERENCES iam.custom_roles(id) ON DELETE SET NULL;
+
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON iam.custom_roles
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+CREATE TRIGGER audit_config_change AFTER INSERT OR DELETE ON iam.team_users_custom_roles
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('user_id');
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON iam.custom_roles TO application_user;
+GRANT USAGE, SELECT ON iam.custom_roles_id_seq TO application_user;
+GRANT SELECT ON iam.custom_roles TO application_readonly;
+GRANT SELECT ON iam.custom_roles_id_seq TO application_readonly;
+GRANT SELECT, INSERT, UPDATE, DELETE ON iam.team_users_custom_roles TO application_user;
+GRANT SELECT ON iam.team_users_custom_roles TO application_readonly;
+
+-- migrate:down
+ALTER TABLE iam.invitations DROP COLUMN custom_role_id;
+DROP TABLE iam.team_users_custom_roles;
+DROP TABLE iam.custom_roles;
+DELETE FROM iam.roles_permissions WHERE permission = 'ManageRoles';
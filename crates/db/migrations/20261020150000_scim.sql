@@ -0,0 +1,67 @@
+-- migrate:up
+
+-- Users the identity provider creates over SCIM have no subject until they first
+-- sign in, and are deactivated rather than deleted when they are deprovisioned.
+ALTER TABLE iam.users ALTER COLUMN openid_sub DROP NOT NULL;
+ALTER TABLE iam.users ADD COLUMN active BOOLEAN NOT NULL DEFAULT true;
+ALTER TABLE iam.users ADD COLUMN scim_external_id VARCHAR;
+
+COMMENT ON COLUMN iam.users.active IS 'False once the identity provider deprovisions the user, who can then no longer sign in';
+COMMENT ON COLUMN iam.users.scim_external_id IS 'The identity provider''s own id for the user, if it provisioned them';
+
+-- Sets the user for the request, refusing those who have been deactivated.
+CREATE FUNCTION iam.set_app_user(app_user_id INT) RETURNS VOID AS $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM iam.users WHERE id = app_user_id AND NOT active) THEN
+        RAISE EXCEPTION 'This account has been deactivated'
+            USING ERRCODE = 'insufficient_privilege';
+    END IF;
+    PERFORM set_config('row_level_security.user_id', app_user_id::text, true);
+END;
+$$ LANGUAGE plpgsql;
+
+-- Memberships granted by a SCIM group, which are removed when no group grants them.
+ALTER TABLE iam.team_users ADD COLUMN scim_managed BOOLEAN NOT NULL DEFAULT false;
+
+-- Groups pushed by the identity provider. A system administrator maps each one to a
+-- team and the roles its members get there.
+CREATE TABLE iam.scim_groups (
+    id INT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    display_name VARCHAR NOT NULL,
+    external_id VARCHAR,
+    team_id INT REFERENCES iam.teams(id) ON DELETE SET NULL,
+    roles role[] NOT NULL DEFAULT '{Collaborator}',
+    custom_role_id INT REFERENCES iam.custom_roles(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (display_name)
+);
+
+SELECT updated_at('iam.scim_groups');
+
+COMMENT ON TABLE iam.scim_groups IS 'Identity provider groups, and the team membership they grant';
+
+CREATE TABLE iam.scim_group_members (
+    group_id INT NOT NULL REFERENCES iam.scim_groups(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES iam.users(id) ON DELETE CASCADE,
+    PRIMARY KEY (group_id, user_id)
+);
+
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON iam.scim_groups
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON iam.scim_groups TO application_user;
+GRANT USAGE, SELECT ON iam.scim_groups_id_seq TO application_user;
+GRANT SELECT ON iam.scim_groups TO application_readonly;
+GRANT SELECT ON iam.scim_groups_id_seq TO application_readonly;
+GRANT SELECT, INSERT, UPDATE, DELETE ON iam.scim_group_members TO application_user;
+GRANT SELECT ON iam.scim_group_members TO application_readonly;
+
+-- migrate:down
+DROP TABLE iam.scim_group_members;
+DROP TABLE iam.scim_groups;
+DROP FUNCTION iam.set_app_user(INT);
+ALTER TABLE iam.team_users DROP COLUMN scim_managed;
+ALTER TABLE iam.users DROP COLUMN scim_external_id;
+ALTER TABLE iam.users DROP COLUMN active;
+ALTER TABLE iam.users ALTER COLUMN openid_sub SET NOT NULL;
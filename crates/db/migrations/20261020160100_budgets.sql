@@ -0,0 +1,126 @@
+-- migrate:up
+
+INSERT INTO iam.roles_permissions VALUES('TeamManager', 'ManageBudgets');
+INSERT INTO iam.roles_permissions VALUES('SystemAdministrator', 'ManageBudgets');
+
+-- Amounts are in micros, millionths of whatever currency the prices are set in, so
+-- costs add up exactly.
+ALTER TABLE model_registry.models
+    ADD COLUMN input_price_micros BIGINT NOT NULL DEFAULT 0 CHECK (input_price_micros >= 0),
+    ADD COLUMN output_price_micros BIGINT NOT NULL DEFAULT 0 CHECK (output_price_micros >= 0);
+
+COMMENT ON COLUMN model_registry.models.input_price_micros IS 'Price of a million prompt tokens, in micros';
+COMMENT ON COLUMN model_registry.models.output_price_micros IS 'Price of a million completion tokens, in micros';
+
+ALTER TABLE llm.token_usage_metrics
+    ADD COLUMN cost_micros BIGINT NOT NULL DEFAULT 0;
+
+COMMENT ON COLUMN llm.token_usage_metrics.cost_micros IS 'What the tokens cost at the model''s prices when they were used';
+
+-- Usage is priced as it is recorded, so changing a price doesn't rewrite past costs.
+-- The model is the one behind the chat's assistant, or the API key's.
+CREATE FUNCTION llm.price_token_usage()
+   RETURNS TRIGGER
+   LANGUAGE PLPGSQL
+AS $$
+DECLARE
+  price BIGINT;
+BEGIN
+  SELECT
+    CASE NEW.type WHEN 'Prompt' THEN m.input_price_micros ELSE m.output_price_micros END
+  INTO price
+  FROM assistants.prompts p
+  JOIN model_registry.models m ON m.id = p.model_id
+  WHERE p.id = COALESCE(
+    (SELECT c.prompt_id FROM llm.chats c WHERE c.id = NEW.chat_id),
+    (SELECT k.prompt_id FROM iam.api_keys k WHERE k.id = NEW.api_key_id)
+  );
+  NEW.cost_micros := COALESCE(ROUND(NEW.tokens::NUMERIC * price / 1000000), 0);
+  RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER price_token_usage BEFORE INSERT ON llm.token_usage_metrics
+  FOR EACH ROW EXECUTE PROCEDURE llm.price_token_usage();
+
+-- Each usage row with the team, user, assistant and model it is charged to.
+CREATE VIEW llm.token_usage_costs AS
+SELECT
+    tum.id,
+    tum.created_at,
+    tum.type,
+    tum.tokens,
+    tum.cost_micros,
+    conv.team_id,
+    conv.user_id,
+    c.prompt_id,
+    NULL::INT AS api_key_id
+FROM llm.token_usage_metrics tum
+JOIN llm.chats c ON c.id = tum.chat_id
+JOIN llm.conversations conv ON conv.id = c.conversation_id
+
+UNION ALL
+
+SELECT
+    tum.id,
+    tum.created_at,
+    tum.type,
+    tum.tokens,
+    tum.cost_micros,
+    k.team_id,
+    k.user_id,
+    k.prompt_id,
+    k.id AS api_key_id
+FROM llm.token_usage_metrics tum
+JOIN iam.api_keys k ON k.id = tum.api_key_id;
+
+GRANT SELECT ON llm.token_usage_costs TO application_user;
+GRANT SELECT ON llm.token_usage_costs TO application_readonly;
+
+-- A monthly spending limit for a team, or for one member's spending in it. Months are
+-- calendar months in UTC.
+CREATE TABLE llm.budgets (
+    id INT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    team_id INT NOT NULL REFERENCES iam.teams(id) ON DELETE CASCADE,
+    user_id INT REFERENCES iam.users(id) ON DELETE CASCADE,
+    monthly_limit_micros BIGINT NOT NULL CHECK (monthly_limit_micros > 0),
+    warn_percent INT NOT NULL DEFAULT 80 CHECK (warn_percent BETWEEN 1 AND 100),
+    stop_percent INT DEFAULT 100 CHECK (stop_percent >= warn_percent),
+    alert_email VARCHAR,
+    warned_month DATE,
+    stopped_month DATE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT updated_at('llm.budgets');
+
+CREATE UNIQUE INDEX idx_budgets_team_user ON llm.budgets (team_id, COALESCE(user_id, 0));
+
+COMMENT ON COLUMN llm.budgets.user_id IS 'NULL for the whole team, otherwise the member whose spending is limited';
+COMMENT ON COLUMN llm.budgets.warn_percent IS 'Alerts go out once spending reaches this share of the limit';
+COMMENT ON COLUMN llm.budgets.stop_percent IS 'Chats are refused from this share of the limit. NULL only alerts';
+COMMENT ON COLUMN llm.budgets.alert_email IS 'Also emailed alerts, besides the team''s budget webhooks';
+COMMENT ON COLUMN llm.budgets.warned_month IS 'The month the warning alert was last sent for, set by the web server';
+COMMENT ON COLUMN llm.budgets.stopped_month IS 'The month the stop alert was last sent for, set by the web server';
+
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON llm.budgets
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id', 'warned_month', 'stopped_month');
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON llm.budgets TO application_user;
+GRANT USAGE, SELECT ON llm.budgets_id_seq TO application_user;
+GRANT SELECT ON llm.budgets TO application_readonly;
+GRANT SELECT ON llm.budgets_id_seq TO application_readonly;
+
+-- migrate:down
+
+DROP TRIGGER audit_config_change ON llm.budgets;
+DROP TABLE llm.budgets;
+DROP VIEW llm.token_usage_costs;
+DROP TRIGGER price_token_usage ON llm.token_usage_metrics;
+DROP FUNCTION llm.price_token_usage();
+ALTER TABLE llm.token_usage_metrics DROP COLUMN cost_micros;
+ALTER TABLE model_registry.models
+    DROP COLUMN input_price_micros,
+    DROP COLUMN output_price_micros;
+DELETE FROM iam.roles_permissions WHERE permission = 'ManageBudgets';
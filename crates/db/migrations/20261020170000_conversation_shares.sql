@@ -0,0 +1,41 @@
+-- migrate:up
+
+CREATE TYPE share_visibility AS ENUM (
+    'Team',
+    'Public'
+);
+
+-- A read-only link to a conversation. Team links need a member of the conversation's
+-- team who can view chats, public links need nobody to sign in. Revoking deletes the row.
+CREATE TABLE llm.conversation_shares (
+    id INT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    team_id INT NOT NULL REFERENCES iam.teams(id) ON DELETE CASCADE,
+    conversation_id BIGINT NOT NULL REFERENCES llm.conversations(id) ON DELETE CASCADE,
+    token TEXT NOT NULL UNIQUE,
+    visibility share_visibility NOT NULL,
+    expires_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT updated_at('llm.conversation_shares');
+
+CREATE INDEX idx_conversation_shares_conversation_id ON llm.conversation_shares (conversation_id);
+
+COMMENT ON COLUMN llm.conversation_shares.team_id IS 'The conversation''s team, so the audit trail shows links to the team';
+COMMENT ON COLUMN llm.conversation_shares.token IS 'The secret part of the link, so links can''t be guessed from ids';
+COMMENT ON COLUMN llm.conversation_shares.expires_at IS 'The link stops working after this. NULL never expires';
+
+CREATE TRIGGER audit_config_change AFTER INSERT OR UPDATE OR DELETE ON llm.conversation_shares
+  FOR EACH ROW EXECUTE PROCEDURE ops.audit_config_change('id');
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON llm.conversation_shares TO application_user;
+GRANT USAGE, SELECT ON llm.conversation_shares_id_seq TO application_user;
+GRANT SELECT ON llm.conversation_shares TO application_readonly;
+GRANT SELECT ON llm.conversation_shares_id_seq TO application_readonly;
+
+-- migrate:down
+
+DROP TRIGGER audit_config_change ON llm.conversation_shares;
+DROP TABLE llm.conversation_shares;
+DROP TYPE share_visibility;
@@ -0,0 +1,9 @@
+-- migrate:up
+
+-- Whatever a webhook URL returned could be shown back to the team, so responses are no
+-- longer kept.
+ALTER TABLE ops.webhook_deliveries DROP COLUMN response_body;
+
+-- migrate:down
+
+ALTER TABLE ops.webhook_deliveries ADD COLUMN response_body TEXT;
@@ -0,0 +1,121 @@
+-- migrate:up
+
+-- Spending is kept apart from token usage, which is deleted along with its chat,
+-- conversation or API key. Otherwise deleting conversations would lower this month's
+-- spending and lift a budget that has stopped.
+CREATE TABLE llm.cost_ledger (
+    id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    team_id INT NOT NULL REFERENCES iam.teams(id) ON DELETE CASCADE,
+    user_id INT REFERENCES iam.users(id) ON DELETE SET NULL,
+    prompt_id INT REFERENCES assistants.prompts(id) ON DELETE SET NULL,
+    model_id INT REFERENCES model_registry.models(id) ON DELETE SET NULL,
+    api_key_id INT,
+    type token_usage_type NOT NULL,
+    tokens INT NOT NULL,
+    cost_micros BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_cost_ledger_team_created_at ON llm.cost_ledger (team_id, created_at);
+
+COMMENT ON TABLE llm.cost_ledger IS 'What each use of a model cost, written when token usage is recorded';
+COMMENT ON COLUMN llm.cost_ledger.api_key_id IS 'The API key used, if any. Not a foreign key, so usage stays charged to a key after it is deleted';
+
+-- Runs as the owner, as the application can read the ledger but not change it.
+CREATE FUNCTION llm.record_cost()
+   RETURNS TRIGGER
+   LANGUAGE PLPGSQL
+   SECURITY DEFINER
+   SET search_path = pg_catalog, public
+AS $$
+BEGIN
+  INSERT INTO llm.cost_ledger
+    (team_id, user_id, prompt_id, model_id, api_key_id, type, tokens, cost_micros, created_at)
+  SELECT conv.team_id, conv.user_id, c.prompt_id, p.model_id, NULL,
+    NEW.type, NEW.tokens, NEW.cost_micros, NEW.created_at
+  FROM llm.chats c
+  JOIN llm.conversations conv ON conv.id = c.conversation_id
+  LEFT JOIN assistants.prompts p ON p.id = c.prompt_id
+  WHERE c.id = NEW.chat_id
+  UNION ALL
+  SELECT k.team_id, k.user_id, k.prompt_id, p.model_id, k.id,
+    NEW.type, NEW.tokens, NEW.cost_micros, NEW.created_at
+  FROM iam.api_keys k
+  LEFT JOIN assistants.prompts p ON p.id = k.prompt_id
+  WHERE k.id = NEW.api_key_id;
+  RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER record_cost AFTER INSERT ON llm.token_usage_metrics
+  FOR EACH ROW EXECUTE PROCEDURE llm.record_cost();
+
+INSERT INTO llm.cost_ledger
+    (team_id, user_id, prompt_id, model_id, api_key_id, type, tokens, cost_micros, created_at)
+SELECT c.team_id, c.user_id, c.prompt_id, p.model_id, c.api_key_id,
+    c.type, c.tokens, c.cost_micros, c.created_at
+FROM llm.token_usage_costs c
+LEFT JOIN assistants.prompts p ON p.id = c.prompt_id
+ORDER BY c.created_at;
+
+DROP VIEW llm.token_usage_costs;
+
+GRANT SELECT ON llm.cost_ledger TO application_user;
+GRANT SELECT ON llm.cost_ledger TO application_readonly;
+
+-- Budgets can also limit what is spent through one API key.
+ALTER TABLE llm.budgets
+    ADD COLUMN api_key_id INT REFERENCES iam.api_keys(id) ON DELETE CASCADE,
+    ADD CONSTRAINT budgets_user_or_api_key CHECK (user_id IS NULL OR api_key_id IS NULL);
+
+DROP INDEX llm.idx_budgets_team_user;
+CREATE UNIQUE INDEX idx_budgets_team_user
+    ON llm.budgets (team_id, COALESCE(user_id, 0), COALESCE(api_key_id, 0));
+
+COMMENT ON COLUMN llm.budgets.api_key_id IS 'The API key whose spending is limited, otherwise NULL';
+
+-- migrate:down
+
+DROP INDEX llm.idx_budgets_team_user;
+DELETE FROM llm.budgets WHERE api_key_id IS NOT NULL;
+ALTER TABLE llm.budgets
+    DROP CONSTRAINT budgets_user_or_api_key,
+    DROP COLUMN api_key_id;
+CREATE UNIQUE INDEX idx_budgets_team_user ON llm.budgets (team_id, COALESCE(user_id, 0));
+
+CREATE VIEW llm.token_usage_costs AS
+SELECT
+    tum.id,
+    tum.created_at,
+    tum.type,
+    tum.tokens,
+    tum.cost_micros,
+    conv.team_id,
+    conv.user_id,
+    c.prompt_id,
+    NULL::INT AS api_key_id
+FROM llm.token_usage_metrics tum
+JOIN llm.chats c ON c.id = tum.chat_id
+JOIN llm.conversations conv ON conv.id = c.conversation_id
+
+UNION ALL
+
+SELECT
+    tum.id,
+    tum.created_at,
+    tum.type,
+    tum.tokens,
+    tum.cost_micros,
+    k.team_id,
+    k.user_id,
+    k.prompt_id,
+    k.id AS api_key_id
+FROM llm.token_usage_metrics tum
+JOIN iam.api_keys k ON k.id = tum.api_key_id;
+
+GRANT SELECT ON llm.token_usage_costs TO application_user;
+GRANT SELECT ON llm.token_usage_costs TO application_readonly;
+
+DROP TRIGGER record_cost ON llm.token_usage_metrics;
+DROP FUNCTION llm.record_cost();
+DROP TABLE llm.cost_ledger;
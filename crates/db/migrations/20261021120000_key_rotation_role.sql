@@ -0,0 +1,43 @@
+-- migrate:up
+
+-- Rotating keys re-encrypts every table and deletes old data keys, so the application
+-- roles mustn't be able to start it. Only the role the rotate-keys command connects as
+-- (KEY_ROTATION_DATABASE_URL) can.
+
+-- Only created in development. In production it is created by the infrastructure as
+-- code with an unguessable password, like the application roles.
+DO $$
+BEGIN
+   IF NOT EXISTS (
+      SELECT FROM pg_catalog.pg_roles
+      WHERE  rolname = 'key_rotation') THEN
+
+      CREATE ROLE key_rotation LOGIN PASSWORD 'testpassword';
+   END IF;
+END
+$$;
+
+GRANT USAGE ON SCHEMA encryption TO key_rotation;
+
+REVOKE EXECUTE ON FUNCTION encryption.rewrap_data_keys() FROM PUBLIC;
+REVOKE EXECUTE ON FUNCTION encryption.rotate_data_key() FROM PUBLIC;
+REVOKE EXECUTE ON FUNCTION encryption.reencrypt_batch(int) FROM PUBLIC;
+REVOKE EXECUTE ON FUNCTION encryption.delete_retired_keys() FROM PUBLIC;
+
+GRANT EXECUTE ON FUNCTION encryption.rewrap_data_keys() TO key_rotation;
+GRANT EXECUTE ON FUNCTION encryption.rotate_data_key() TO key_rotation;
+GRANT EXECUTE ON FUNCTION encryption.reencrypt_batch(int) TO key_rotation;
+GRANT EXECUTE ON FUNCTION encryption.delete_retired_keys() TO key_rotation;
+
+-- migrate:down
+
+REVOKE EXECUTE ON FUNCTION encryption.rewrap_data_keys() FROM key_rotation;
+REVOKE EXECUTE ON FUNCTION encryption.rotate_data_key() FROM key_rotation;
+REVOKE EXECUTE ON FUNCTION encryption.reencrypt_batch(int) FROM key_rotation;
+REVOKE EXECUTE ON FUNCTION encryption.delete_retired_keys() FROM key_rotation;
+REVOKE USAGE ON SCHEMA encryption FROM key_rotation;
+
+GRANT EXECUTE ON FUNCTION encryption.rewrap_data_keys() TO PUBLIC;
+GRANT EXECUTE ON FUNCTION encryption.rotate_data_key() TO PUBLIC;
+GRANT EXECUTE ON FUNCTION encryption.reencrypt_batch(int) TO PUBLIC;
+GRANT EXECUTE ON FUNCTION encryption.delete_retired_keys() TO PUBLIC;
@@ -0,0 +1,26 @@
+-- migrate:up
+
+-- A workspace's snapshot is only used by its row, so it goes when the row is deleted,
+-- including when its conversation is, or when a new snapshot replaces it.
+CREATE FUNCTION llm.delete_workspace_object()
+   RETURNS TRIGGER
+   LANGUAGE PLPGSQL
+AS $$
+BEGIN
+  DELETE FROM storage.objects WHERE id = OLD.object_id;
+  RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER delete_workspace_object AFTER DELETE ON llm.conversation_workspaces
+  FOR EACH ROW EXECUTE PROCEDURE llm.delete_workspace_object();
+
+CREATE TRIGGER replace_workspace_object AFTER UPDATE OF object_id ON llm.conversation_workspaces
+  FOR EACH ROW WHEN (OLD.object_id IS DISTINCT FROM NEW.object_id)
+  EXECUTE PROCEDURE llm.delete_workspace_object();
+
+-- migrate:down
+
+DROP TRIGGER replace_workspace_object ON llm.conversation_workspaces;
+DROP TRIGGER delete_workspace_object ON llm.conversation_workspaces;
+DROP FUNCTION llm.delete_workspace_object();
@@ -0,0 +1,70 @@
+-- migrate:up
+
+-- The primary endpoint used to keep a plaintext copy of its model's API key. It now reads
+-- the key from the model, and the keys of the other endpoints are encrypted.
+CREATE OR REPLACE FUNCTION model_registry.sync_primary_endpoint()
+   RETURNS TRIGGER
+   LANGUAGE PLPGSQL
+AS $$
+BEGIN
+  INSERT INTO model_registry.model_endpoints
+    (model_id, is_primary, provider_kind, base_url, priority, weight)
+  VALUES
+    (NEW.id, TRUE, NEW.provider_kind, NEW.base_url, 0, 1)
+  ON CONFLICT (model_id) WHERE is_primary DO UPDATE SET
+    provider_kind = EXCLUDED.provider_kind,
+    base_url = EXCLUDED.base_url;
+
+  RETURN NEW;
+END;
+$$;
+
+DROP TRIGGER sync_primary_endpoint ON model_registry.models;
+CREATE TRIGGER sync_primary_endpoint
+  AFTER INSERT OR UPDATE OF provider_kind, base_url
+  ON model_registry.models
+  FOR EACH ROW
+  EXECUTE PROCEDURE model_registry.sync_primary_endpoint();
+
+UPDATE model_registry.model_endpoints SET api_key = NULL WHERE is_primary;
+
+-- Keys saved before this are encrypted by the next rotate-keys.
+INSERT INTO encryption.encrypted_columns (table_name, key_column, column_name, is_bytes) VALUES
+    ('model_registry.model_endpoints', 'id', 'api_key', FALSE);
+
+COMMENT ON COLUMN model_registry.model_endpoints.api_key IS 'Encrypted. NULL for the primary endpoint, which uses the key of its model';
+
+-- migrate:down
+
+DELETE FROM encryption.encrypted_columns
+WHERE table_name = 'model_registry.model_endpoints' AND column_name = 'api_key';
+
+UPDATE model_registry.model_endpoints e
+SET api_key = m.api_key
+FROM model_registry.models m
+WHERE m.id = e.model_id AND e.is_primary;
+
+CREATE OR REPLACE FUNCTION model_registry.sync_primary_endpoint()
+   RETURNS TRIGGER
+   LANGUAGE PLPGSQL
+AS $$
+BEGIN
+  INSERT INTO model_registry.model_endpoints
+    (model_id, is_primary, provider_kind, base_url, api_key, priority, weight)
+  VALUES
+    (NEW.id, TRUE, NEW.provider_kind, NEW.base_url, NEW.api_key, 0, 1)
+  ON CONFLICT (model_id) WHERE is_primary DO UPDATE SET
+    provider_kind = EXCLUDED.provider_kind,
+    base_url = EXCLUDED.base_url,
+    api_key = EXCLUDED.api_key;
+
+  RETURN NEW;
+END;
+$$;
+
+DROP TRIGGER sync_primary_endpoint ON model_registry.models;
+CREATE TRIGGER sync_primary_endpoint
+  AFTER INSERT OR UPDATE OF provider_kind, base_url, api_key
+  ON model_registry.models
+  FOR EACH ROW
+  EXECUTE PROCEDURE model_registry.sync_primary_endpoint();
@@ -0,0 +1,11 @@
+-- migrate:up
+
+-- Webhook secrets are written with encrypt_text, so rotate-keys has to re-encrypt them
+-- before the old data key is deleted.
+INSERT INTO encryption.encrypted_columns (table_name, key_column, column_name, is_bytes) VALUES
+    ('ops.webhooks', 'id', 'secret', FALSE);
+
+-- migrate:down
+
+DELETE FROM encryption.encrypted_columns
+WHERE table_name = 'ops.webhooks' AND column_name = 'secret';
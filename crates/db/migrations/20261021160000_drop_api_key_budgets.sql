@@ -0,0 +1,22 @@
+-- migrate:up
+
+-- Nothing refuses requests made with an API key, so budgets only limit a team or one of
+-- its members. What was spent through each key is still in the cost ledger.
+DROP INDEX llm.idx_budgets_team_user;
+DELETE FROM llm.budgets WHERE api_key_id IS NOT NULL;
+ALTER TABLE llm.budgets
+    DROP CONSTRAINT budgets_user_or_api_key,
+    DROP COLUMN api_key_id;
+CREATE UNIQUE INDEX idx_budgets_team_user ON llm.budgets (team_id, COALESCE(user_id, 0));
+
+-- migrate:down
+
+ALTER TABLE llm.budgets
+    ADD COLUMN api_key_id INT REFERENCES iam.api_keys(id) ON DELETE CASCADE,
+    ADD CONSTRAINT budgets_user_or_api_key CHECK (user_id IS NULL OR api_key_id IS NULL);
+
+DROP INDEX llm.idx_budgets_team_user;
+CREATE UNIQUE INDEX idx_budgets_team_user
+    ON llm.budgets (team_id, COALESCE(user_id, 0), COALESCE(api_key_id, 0));
+
+COMMENT ON COLUMN llm.budgets.api_key_id IS 'The API key whose spending is limited, otherwise NULL';
@@ -0,0 +1,24 @@
+-- migrate:up
+
+-- Audit rows are streamed to sinks in the order of the transaction that wrote them, and
+-- only once that transaction and every one before it has finished. Ids are taken before
+-- commit, so a cursor on the id alone skips rows from transactions that commit late.
+ALTER TABLE ops.audit_trail ADD COLUMN xact_id BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE ops.audit_trail ALTER COLUMN xact_id SET DEFAULT pg_current_xact_id()::text::BIGINT;
+CREATE INDEX idx_audit_trail_xact_id ON ops.audit_trail (xact_id, id);
+
+COMMENT ON COLUMN ops.audit_trail.xact_id IS 'The transaction that wrote the row, 0 for rows written before it was recorded';
+
+-- A sink is leased while a batch is sent instead of holding a lock on its cursor.
+ALTER TABLE ops.audit_sink_cursors
+    ADD COLUMN last_xact_id BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN leased_until TIMESTAMPTZ;
+
+-- migrate:down
+
+ALTER TABLE ops.audit_sink_cursors
+    DROP COLUMN last_xact_id,
+    DROP COLUMN leased_until;
+
+DROP INDEX ops.idx_audit_trail_xact_id;
+ALTER TABLE ops.audit_trail DROP COLUMN xact_id;
@@ -0,0 +1,218 @@
+//! The connection pool, wrapped so every transaction and the statements run on it show up
+//! as spans in traces. Connections and transactions deref to the deadpool types, so
+//! anything not covered here still works as before.
+
+use async_trait::async_trait;
+use cornucopia_async::GenericClient;
+use std::ops::{Deref, DerefMut};
+use tokio_postgres::types::{BorrowToSql, ToSql};
+use tokio_postgres::{Error, Row, RowStream, Statement, ToStatement};
+use tracing::{Instrument, Span};
+
+#[derive(Clone, Debug)]
+pub struct Pool(deadpool_postgres::Pool);
+
+impl Pool {
+    pub fn new(pool: deadpool_postgres::Pool) -> Self {
+        Self(pool)
+    }
+
+    pub async fn get(&self) -> Result<Client, deadpool_postgres::PoolError> {
+        Ok(Client(self.0.get().await?))
+    }
+}
+
+impl Deref for Pool {
+    type Target = deadpool_postgres::Pool;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// A connection checked out of the pool. It goes back to the pool when dropped.
+pub struct Client(deadpool_postgres::Object);
+
+impl Client {
+    /// Database spans only join a trace that has already started, so the background jobs
+    /// that poll every few seconds don't start traces of their own.
+    pub async fn transaction(&mut self) -> Result<Transaction<'_>, Error> {
+        let span = if Span::current().is_none() {
+            Span::none()
+        } else {
+            tracing::info_span!(
+                "db.transaction",
+                otel.kind = "client",
+                db.system.name = "postgresql"
+            )
+        };
+        let inner = self.0.transaction().instrument(span.clone()).await?;
+        Ok(Transaction { inner, span })
+    }
+
+    fn parent_span(&self) -> Option<&Span> {
+        None
+    }
+}
+
+impl Deref for Client {
+    type Target = deadpool_postgres::Object;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Client {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// A transaction whose span lasts until it is committed, rolled back or dropped.
+pub struct Transaction<'a> {
+    inner: deadpool_postgres::Transaction<'a>,
+    span: Span,
+}
+
+impl Transaction<'_> {
+    pub async fn commit(self) -> Result<(), Error> {
+        let span = self.span.clone();
+        self.inner.commit().instrument(span).await
+    }
+
+    pub async fn rollback(self) -> Result<(), Error> {
+        let span = self.span.clone();
+        self.inner.rollback().instrument(span).await
+    }
+
+    fn parent_span(&self) -> Option<&Span> {
+        Some(&self.span)
+    }
+}
+
+impl<'a> Deref for Transaction<'a> {
+    type Target = deadpool_postgres::Transaction<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl DerefMut for Transaction<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+/// Statements run in a transaction belong to its span, and others to the current span.
+fn query_span(transaction: Option<&Span>) -> Span {
+    let parent = transaction.cloned().unwrap_or_else(Span::current);
+    if parent.is_none() {
+        return Span::none();
+    }
+    tracing::info_span!(
+        parent: &parent,
+        "db.query",
+        otel.kind = "client",
+        db.system.name = "postgresql"
+    )
+}
+
+/// Implements cornucopia's client trait by running each statement in a `db.query` span.
+macro_rules! traced_generic_client {
+    ($client:ty, $inner:ty) => {
+        #[async_trait]
+        impl GenericClient for $client {
+            async fn prepare(&self, query: &str) -> Result<Statement, Error> {
+                <$inner as GenericClient>::prepare(&**self, query).await
+            }
+
+            async fn execute<T>(
+                &self,
+                query: &T,
+                params: &[&(dyn ToSql + Sync)],
+            ) -> Result<u64, Error>
+            where
+                T: ?Sized + ToStatement + Sync + Send,
+            {
+                <$inner as GenericClient>::execute(&**self, query, params)
+                    .instrument(query_span(self.parent_span()))
+                    .await
+            }
+
+            async fn query_one<T>(
+                &self,
+                statement: &T,
+                params: &[&(dyn ToSql + Sync)],
+            ) -> Result<Row, Error>
+            where
+                T: ?Sized + ToStatement + Sync + Send,
+            {
+                <$inner as GenericClient>::query_one(&**self, statement, params)
+                    .instrument(query_span(self.parent_span()))
+                    .await
+            }
+
+            async fn query_opt<T>(
+                &self,
+                statement: &T,
+                params: &[&(dyn ToSql + Sync)],
+            ) -> Result<Option<Row>, Error>
+            where
+                T: ?Sized + ToStatement + Sync + Send,
+            {
+                <$inner as GenericClient>::query_opt(&**self, statement, params)
+                    .instrument(query_span(self.parent_span()))
+                    .await
+            }
+
+            async fn query<T>(
+                &self,
+                query: &T,
+                params: &[&(dyn ToSql + Sync)],
+            ) -> Result<Vec<Row>, Error>
+            where
+                T: ?Sized + ToStatement + Sync + Send,
+            {
+                <$inner as GenericClient>::query(&**self, query, params)
+                    .instrument(query_span(self.parent_span()))
+                    .await
+            }
+
+            async fn query_raw<T, P, I>(&self, statement: &T, params: I) -> Result<RowStream, Error>
+            where
+                T: ?Sized + ToStatement + Sync + Send,
+                P: BorrowToSql,
+                I: IntoIterator<Item = P> + Sync + Send,
+                I::IntoIter: ExactSizeIterator,
+            {
+                <$inner as GenericClient>::query_raw(&**self, statement, params)
+                    .instrument(query_span(self.parent_span()))
+                    .await
+            }
+        }
+    };
+}
+
+traced_generic_client!(Client, deadpool_postgres::Object);
+traced_generic_client!(Transaction<'_>, deadpool_postgres::Transaction<'_>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statements_only_join_a_trace_that_has_started() {
+        let subscriber = tracing_subscriber::registry();
+        tracing::subscriber::with_default(subscriber, || {
+            assert!(query_span(None).is_none());
+
+            let request = tracing::info_span!("request");
+            let _entered = request.enter();
+            assert!(!query_span(None).is_none());
+
+            assert!(query_span(Some(&Span::none())).is_none());
+        });
+    }
+}
@@ -31,7 +31,7 @@ WHERE
 
 --! get_content : AttachmentData
 SELECT
-    o.object_data,
+    decrypt_bytes(o.object_data) AS object_data,
     o.file_name,
     o.mime_type
 FROM
@@ -49,7 +49,7 @@ AND
 
 --! get_latest_content : AttachmentData
 SELECT
-    o.object_data,
+    decrypt_bytes(o.object_data) AS object_data,
     o.file_name,
     o.mime_type
 FROM
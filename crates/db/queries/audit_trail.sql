@@ -1,18 +1,106 @@
---! audit(id?, action?, access_type?, user_id?) : AuditTrail()
+--: AuditTrail(team_id?, ip_address?, object_type?, object_id?, before?, after?)
+
+--! audit(id?, action?, access_type?, user_id?, object_type?, from_date?, to_date?) : AuditTrail
 SELECT 
-    id,
-    COALESCE((SELECT email from iam.users u WHERE u.id = user_id), '') as email,
+    a.id,
+    COALESCE((SELECT email from iam.users u WHERE u.id = a.user_id), '') as email,
     -- Convert times to ISO 8601 string.
-    trim(both '"' from to_json(created_at)::text) as created_at,
-    action, 
-    access_type
+    trim(both '"' from to_json(a.created_at)::text) as created_at,
+    a.action, 
+    a.access_type,
+    a.team_id,
+    a.ip_address,
+    c.object_type,
+    c.object_id,
+    c.before::text AS before,
+    c.after::text AS after,
+    a.xact_id
 FROM 
-    ops.audit_trail
+    ops.audit_trail a
+LEFT JOIN
+    ops.audit_trail_config_changes c ON c.audit_id = a.id
 WHERE 
     -- The inputs are optional in which case we can use COALESCE to skip
-    id < COALESCE(:id, 2147483647)
-    AND action = COALESCE(:action, action)
-    AND access_type = COALESCE(:access_type, access_type)
-    AND user_id = COALESCE(:user_id, user_id)
-ORDER BY created_at DESC
-LIMIT :limit;
\ No newline at end of file
+    a.id < COALESCE(:id, 2147483647)
+    AND a.action = COALESCE(:action, a.action)
+    AND a.access_type = COALESCE(:access_type, a.access_type)
+    AND a.user_id = COALESCE(:user_id, a.user_id)
+    AND (:object_type::text IS NULL OR c.object_type = :object_type)
+    -- Dates are YYYY-MM-DD and both ends are included
+    AND a.created_at >= COALESCE(:from_date::text::date, '-infinity'::date)
+    AND a.created_at < COALESCE(:to_date::text::date + 1, 'infinity'::date)
+ORDER BY a.created_at DESC
+LIMIT :limit;
+
+--! audit_after : AuditTrail
+SELECT 
+    a.id,
+    COALESCE((SELECT email from iam.users u WHERE u.id = a.user_id), '') as email,
+    trim(both '"' from to_json(a.created_at)::text) as created_at,
+    a.action, 
+    a.access_type,
+    a.team_id,
+    a.ip_address,
+    c.object_type,
+    c.object_id,
+    c.before::text AS before,
+    c.after::text AS after,
+    a.xact_id
+FROM 
+    ops.audit_trail a
+LEFT JOIN
+    ops.audit_trail_config_changes c ON c.audit_id = a.id
+WHERE 
+    (a.xact_id, a.id) > (:after_xact_id, :after_id)
+    -- Only rows from transactions older than any still running, so none can appear
+    -- behind the cursor later
+    AND a.xact_id < pg_snapshot_xmin(pg_current_snapshot())::text::BIGINT
+ORDER BY a.xact_id, a.id
+LIMIT :limit;
+
+--! config_object_types
+SELECT DISTINCT
+    object_type
+FROM
+    ops.audit_trail_config_changes
+ORDER BY
+    object_type;
+
+--! insert_sink_cursor
+INSERT INTO ops.audit_sink_cursors (sink)
+VALUES (:sink)
+ON CONFLICT (sink) DO NOTHING;
+
+-- Leases the sink's cursor so only one server sends at a time, without keeping a
+-- transaction open while it does.
+--! claim_sink_cursor
+UPDATE
+    ops.audit_sink_cursors
+SET
+    leased_until = NOW() + (:lease_secs::INT * INTERVAL '1 second')
+WHERE
+    sink = :sink
+AND
+    (leased_until IS NULL OR leased_until < NOW())
+RETURNING
+    last_xact_id,
+    last_audit_id;
+
+--! update_sink_cursor
+UPDATE
+    ops.audit_sink_cursors
+SET
+    last_xact_id = :last_xact_id,
+    last_audit_id = :last_audit_id,
+    leased_until = NULL,
+    updated_at = NOW()
+WHERE
+    sink = :sink;
+
+--! release_sink_cursor
+UPDATE
+    ops.audit_sink_cursors
+SET
+    leased_until = NULL
+WHERE
+    sink = :sink;
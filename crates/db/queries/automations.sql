@@ -0,0 +1,251 @@
+--: Automation(cron?, dataset_id?, dataset_name?, webhook_token?, notify_email?, notify_webhook_url?, next_run_at?, last_run_status?, last_run_at?)
+--: AutomationRun(conversation_id?, error?, started_at?, finished_at?)
+--: DueSchedule()
+--: ClaimedRun(notify_email?, notify_webhook_url?)
+
+--! automations : Automation
+SELECT
+    a.id,
+    a.name,
+    a.prompt_id,
+    (SELECT name FROM assistants.prompts WHERE id = a.prompt_id) AS assistant_name,
+    a.prompt_template,
+    a.trigger_type,
+    a.cron,
+    a.dataset_id,
+    (SELECT name FROM rag.datasets WHERE id = a.dataset_id) AS dataset_name,
+    a.webhook_token,
+    a.notify_email,
+    a.notify_webhook_url,
+    a.enabled,
+    trim(both '"' from to_json(a.next_run_at)::text) AS next_run_at,
+    (SELECT r.status FROM automations.automation_runs r WHERE r.automation_id = a.id ORDER BY r.id DESC LIMIT 1) AS last_run_status,
+    (SELECT trim(both '"' from to_json(r.created_at)::text) FROM automations.automation_runs r WHERE r.automation_id = a.id ORDER BY r.id DESC LIMIT 1) AS last_run_at
+FROM
+    automations.automations a
+WHERE
+    a.team_id = :team_id
+AND
+    a.user_id = current_app_user()
+ORDER BY a.id;
+
+--! automation : Automation
+SELECT
+    a.id,
+    a.name,
+    a.prompt_id,
+    (SELECT name FROM assistants.prompts WHERE id = a.prompt_id) AS assistant_name,
+    a.prompt_template,
+    a.trigger_type,
+    a.cron,
+    a.dataset_id,
+    (SELECT name FROM rag.datasets WHERE id = a.dataset_id) AS dataset_name,
+    a.webhook_token,
+    a.notify_email,
+    a.notify_webhook_url,
+    a.enabled,
+    trim(both '"' from to_json(a.next_run_at)::text) AS next_run_at,
+    (SELECT r.status FROM automations.automation_runs r WHERE r.automation_id = a.id ORDER BY r.id DESC LIMIT 1) AS last_run_status,
+    (SELECT trim(both '"' from to_json(r.created_at)::text) FROM automations.automation_runs r WHERE r.automation_id = a.id ORDER BY r.id DESC LIMIT 1) AS last_run_at
+FROM
+    automations.automations a
+WHERE
+    a.id = :id
+AND
+    a.team_id = :team_id
+AND
+    a.user_id = current_app_user();
+
+--! insert(cron?, dataset_id?, webhook_token?, notify_email?, notify_webhook_url?, next_run_at?)
+INSERT INTO automations.automations
+    (team_id, user_id, prompt_id, name, prompt_template, trigger_type, cron, dataset_id,
+     webhook_token, notify_email, notify_webhook_url, next_run_at)
+VALUES
+    (:team_id, current_app_user(), :prompt_id, :name, :prompt_template, :trigger_type, :cron, :dataset_id,
+     :webhook_token, :notify_email, :notify_webhook_url, :next_run_at)
+RETURNING id;
+
+--! update(cron?, dataset_id?, webhook_token?, notify_email?, notify_webhook_url?, next_run_at?)
+UPDATE automations.automations
+SET
+    prompt_id = :prompt_id,
+    name = :name,
+    prompt_template = :prompt_template,
+    trigger_type = :trigger_type,
+    cron = :cron,
+    dataset_id = :dataset_id,
+    webhook_token = :webhook_token,
+    notify_email = :notify_email,
+    notify_webhook_url = :notify_webhook_url,
+    enabled = :enabled,
+    next_run_at = :next_run_at
+WHERE
+    id = :id
+AND
+    team_id = :team_id
+AND
+    user_id = current_app_user();
+
+--! delete
+DELETE FROM
+    automations.automations
+WHERE
+    id = :id
+AND
+    team_id = :team_id
+AND
+    user_id = current_app_user();
+
+--! runs : AutomationRun
+SELECT
+    r.id,
+    r.trigger_type,
+    jsonb_pretty(r.event) AS event,
+    r.status,
+    r.attempts,
+    r.conversation_id,
+    r.error,
+    trim(both '"' from to_json(r.created_at)::text) AS created_at,
+    trim(both '"' from to_json(r.started_at)::text) AS started_at,
+    trim(both '"' from to_json(r.finished_at)::text) AS finished_at
+FROM
+    automations.automation_runs r
+JOIN automations.automations a ON a.id = r.automation_id
+WHERE
+    r.automation_id = :automation_id
+AND
+    a.team_id = :team_id
+AND
+    a.user_id = current_app_user()
+ORDER BY r.id DESC
+LIMIT :limit;
+
+--! enqueue_run
+INSERT INTO automations.automation_runs
+    (automation_id, trigger_type, event)
+SELECT
+    a.id, a.trigger_type, :event
+FROM
+    automations.automations a
+WHERE
+    a.id = :automation_id
+AND
+    a.team_id = :team_id
+AND
+    a.user_id = current_app_user();
+
+-- Inbound webhooks authenticate with the automation's token rather than a user.
+--! enqueue_webhook_run
+INSERT INTO automations.automation_runs
+    (automation_id, trigger_type, event)
+SELECT
+    a.id, 'Webhook', :event
+FROM
+    automations.automations a
+WHERE
+    a.webhook_token = :webhook_token
+AND
+    a.trigger_type = 'Webhook'
+AND
+    a.enabled
+RETURNING id;
+
+-- Schedules that are due. The rows stay locked until the transaction that queues their
+-- runs and moves them on commits, so only one replica fires each one.
+--! due_schedules : DueSchedule
+SELECT
+    a.id,
+    a.cron,
+    a.next_run_at AS scheduled_for
+FROM
+    automations.automations a
+WHERE
+    a.trigger_type = 'Schedule'
+AND
+    a.enabled
+AND
+    a.next_run_at <= NOW()
+ORDER BY a.next_run_at
+LIMIT :limit
+FOR UPDATE SKIP LOCKED;
+
+--! schedule_run
+INSERT INTO automations.automation_runs
+    (automation_id, trigger_type, event)
+VALUES
+    (:automation_id, 'Schedule', :event);
+
+--! set_next_run(next_run_at?)
+UPDATE automations.automations
+SET
+    next_run_at = :next_run_at
+WHERE
+    id = :id;
+
+-- Takes queued runs and holds them while they run. A worker that stops before recording
+-- the result leaves them to be picked up again once the hold expires. Each claim adds an
+-- attempt, so the attempt number says which claim holds the run.
+--! claim_runs : ClaimedRun
+UPDATE automations.automation_runs r
+SET
+    status = 'Running',
+    attempts = r.attempts + 1,
+    locked_until = NOW() + (:lease_secs::INT * INTERVAL '1 second'),
+    started_at = NOW()
+FROM
+    automations.automations a
+JOIN iam.users u ON u.id = a.user_id
+WHERE
+    a.id = r.automation_id
+AND
+    r.id IN (
+        SELECT queued.id
+        FROM automations.automation_runs queued
+        WHERE queued.status = 'Pending'
+        OR (queued.status = 'Running' AND queued.locked_until < NOW())
+        ORDER BY queued.created_at
+        LIMIT :limit
+        FOR UPDATE OF queued SKIP LOCKED
+    )
+RETURNING
+    r.id,
+    r.trigger_type,
+    r.event,
+    r.attempts,
+    trim(both '"' from to_json(r.created_at)::text) AS created_at,
+    a.id AS automation_id,
+    a.name,
+    a.team_id,
+    a.prompt_id,
+    a.prompt_template,
+    a.notify_email,
+    a.notify_webhook_url,
+    u.openid_sub AS owner_sub;
+
+--! extend_run_lease
+UPDATE automations.automation_runs
+SET
+    locked_until = NOW() + (:lease_secs::INT * INTERVAL '1 second')
+WHERE
+    id = :id
+AND
+    attempts = :attempts
+AND
+    status = 'Running';
+
+-- Only the claim still holding the run can finish it, so a run picked up again after its
+-- hold expired is recorded once.
+--! finish_run(conversation_id?, error?)
+UPDATE automations.automation_runs
+SET
+    status = :status,
+    conversation_id = :conversation_id,
+    error = :error,
+    locked_until = NULL,
+    finished_at = NOW()
+WHERE
+    id = :id
+AND
+    attempts = :attempts
+AND
+    status = 'Running';
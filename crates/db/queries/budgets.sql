@@ -0,0 +1,153 @@
+--: Budget(user_id?, email?, stop_percent?, alert_email?)
+--: StoppedBudget(user_id?)
+--: BudgetAlert(email?, team_name?, stop_percent?, alert_email?)
+--: TeamCost(email?, assistant?, api_key_id?, api_key?, model?)
+
+--! budgets : Budget
+SELECT
+    b.id,
+    b.user_id,
+    u.email,
+    b.monthly_limit_micros,
+    b.warn_percent,
+    b.stop_percent,
+    b.alert_email,
+    spent.micros AS spent_micros
+FROM
+    llm.budgets b
+LEFT JOIN iam.users u ON u.id = b.user_id
+CROSS JOIN LATERAL (
+    SELECT COALESCE(SUM(c.cost_micros), 0)::BIGINT AS micros
+    FROM llm.cost_ledger c
+    WHERE c.team_id = b.team_id
+    AND (b.user_id IS NULL OR c.user_id = b.user_id)
+    AND c.created_at >= date_trunc('month', NOW(), 'UTC')
+) spent
+WHERE
+    b.team_id = :team_id
+ORDER BY b.user_id NULLS FIRST, u.email;
+
+-- Changing a budget sends its alerts again if spending is still over them.
+--! upsert_budget(user_id?, stop_percent?, alert_email?)
+INSERT INTO llm.budgets
+    (team_id, user_id, monthly_limit_micros, warn_percent, stop_percent, alert_email)
+SELECT
+    :team_id, :user_id, :monthly_limit_micros, :warn_percent, :stop_percent, :alert_email
+WHERE
+    :user_id::INT IS NULL
+OR
+    :user_id IN (SELECT tu.user_id FROM iam.team_users tu WHERE tu.team_id = :team_id)
+ON CONFLICT (team_id, COALESCE(user_id, 0)) DO UPDATE SET
+    monthly_limit_micros = EXCLUDED.monthly_limit_micros,
+    warn_percent = EXCLUDED.warn_percent,
+    stop_percent = EXCLUDED.stop_percent,
+    alert_email = EXCLUDED.alert_email,
+    warned_month = NULL,
+    stopped_month = NULL;
+
+--! delete_budget
+DELETE FROM
+    llm.budgets
+WHERE
+    id = :id
+AND
+    team_id = :team_id;
+
+-- The budget, if any, that stops the chat's user from spending more this month.
+--! stopped_budget : StoppedBudget
+SELECT
+    b.id,
+    b.user_id
+FROM
+    llm.chats ch
+JOIN llm.conversations conv ON conv.id = ch.conversation_id
+JOIN llm.budgets b ON b.team_id = conv.team_id
+    AND (b.user_id IS NULL OR b.user_id = conv.user_id)
+CROSS JOIN LATERAL (
+    SELECT COALESCE(SUM(c.cost_micros), 0) AS micros
+    FROM llm.cost_ledger c
+    WHERE c.team_id = b.team_id
+    AND (b.user_id IS NULL OR c.user_id = b.user_id)
+    AND c.created_at >= date_trunc('month', NOW(), 'UTC')
+) spent
+WHERE
+    ch.id = :chat_id
+AND
+    b.stop_percent IS NOT NULL
+AND
+    spent.micros * 100 >= b.monthly_limit_micros * b.stop_percent
+ORDER BY b.user_id NULLS FIRST
+LIMIT 1;
+
+-- Marks the budgets that have crossed a threshold they haven't alerted for this month,
+-- so each alert goes out once however many servers are running.
+--! claim_budget_alerts : BudgetAlert
+WITH spending AS (
+    SELECT
+        b.id,
+        date_trunc('month', NOW(), 'UTC')::DATE AS month,
+        spent.micros,
+        spent.micros * 100 >= b.monthly_limit_micros * b.warn_percent AS warn,
+        COALESCE(spent.micros * 100 >= b.monthly_limit_micros * b.stop_percent, false) AS stop
+    FROM
+        llm.budgets b
+    CROSS JOIN LATERAL (
+        SELECT COALESCE(SUM(c.cost_micros), 0)::BIGINT AS micros
+        FROM llm.cost_ledger c
+        WHERE c.team_id = b.team_id
+        AND (b.user_id IS NULL OR c.user_id = b.user_id)
+        AND c.created_at >= date_trunc('month', NOW(), 'UTC')
+    ) spent
+)
+UPDATE llm.budgets b
+SET
+    warned_month = CASE WHEN s.warn THEN s.month ELSE b.warned_month END,
+    stopped_month = CASE WHEN s.stop THEN s.month ELSE b.stopped_month END
+FROM
+    spending s
+WHERE
+    s.id = b.id
+AND (
+    (s.warn AND b.warned_month IS DISTINCT FROM s.month)
+    OR (s.stop AND b.stopped_month IS DISTINCT FROM s.month)
+)
+RETURNING
+    b.id,
+    b.team_id,
+    (SELECT t.name FROM iam.teams t WHERE t.id = b.team_id) AS team_name,
+    (SELECT u.email FROM iam.users u WHERE u.id = b.user_id) AS email,
+    CASE WHEN s.stop THEN 'budget.exceeded' ELSE 'budget.warning' END AS event_type,
+    to_char(s.month, 'YYYY-MM') AS month,
+    b.monthly_limit_micros,
+    b.warn_percent,
+    b.stop_percent,
+    s.micros AS spent_micros,
+    b.alert_email;
+
+-- A month of the team's usage, split by who or what it is charged to. The month is
+-- given as YYYY-MM, in UTC.
+--! team_costs : TeamCost
+SELECT
+    u.email,
+    p.name AS assistant,
+    c.api_key_id,
+    k.name AS api_key,
+    m.name AS model,
+    SUM(CASE WHEN c.type = 'Prompt' THEN c.tokens ELSE 0 END)::BIGINT AS prompt_tokens,
+    SUM(CASE WHEN c.type = 'Completion' THEN c.tokens ELSE 0 END)::BIGINT AS completion_tokens,
+    SUM(c.cost_micros)::BIGINT AS cost_micros
+FROM
+    llm.cost_ledger c
+LEFT JOIN iam.users u ON u.id = c.user_id
+LEFT JOIN assistants.prompts p ON p.id = c.prompt_id
+LEFT JOIN model_registry.models m ON m.id = c.model_id
+LEFT JOIN iam.api_keys k ON k.id = c.api_key_id
+WHERE
+    c.team_id = :team_id
+AND
+    c.created_at >= to_date(:month, 'YYYY-MM')::TIMESTAMP AT TIME ZONE 'UTC'
+AND
+    c.created_at < (to_date(:month, 'YYYY-MM') + INTERVAL '1 month') AT TIME ZONE 'UTC'
+GROUP BY
+    c.user_id, u.email, c.prompt_id, p.name, c.api_key_id, k.name, c.model_id, m.name
+ORDER BY cost_micros DESC, u.email;
@@ -105,6 +105,39 @@ AND
     id = :chat_id
 ORDER BY id;
 
+-- The chats behind a share link that hasn't expired, for whoever has the link.
+--! shared_chats : Chat
+SELECT
+    id,
+    conversation_id,
+    decrypt_text(content) as content,
+    role,
+    tool_call_id,
+    decrypt_text(tool_calls) as tool_calls,
+    prompt_id,
+    (SELECT name FROM model_registry.models WHERE id IN (SELECT model_id FROM assistants.prompts WHERE id = prompt_id)) as model_name,
+    status,
+    (
+        SELECT json_agg(json_build_object(
+            'name', o.file_name,
+            'type', o.mime_type,
+            'size', o.file_size
+        ))
+        FROM llm.chats_attachments ca
+        JOIN storage.objects o ON ca.object_id = o.id
+        WHERE ca.chat_id = chats.id
+    ) as attachments,
+    created_at,
+    updated_at
+FROM
+    llm.chats
+WHERE
+    conversation_id IN (
+        SELECT conversation_id FROM llm.conversation_shares
+        WHERE token = :token AND (expires_at IS NULL OR expires_at > NOW())
+    )
+ORDER BY id;
+
 --! set_chat_status
 UPDATE llm.chats
 SET
@@ -113,4 +146,21 @@ WHERE
     id = :chat_id
 AND
     -- Make sure the chat belongs to the user
-    conversation_id IN (SELECT id FROM llm.conversations WHERE user_id = current_app_user());
\ No newline at end of file
+    conversation_id IN (SELECT id FROM llm.conversations WHERE user_id = current_app_user());
+
+--! set_chat_content
+UPDATE llm.chats
+SET
+    content = encrypt_text(:content)
+WHERE
+    id = :chat_id
+AND
+    -- Make sure the chat belongs to the user
+    conversation_id IN (SELECT id FROM llm.conversations WHERE user_id = current_app_user());
+
+--! set_model_endpoint(model_endpoint_id?)
+UPDATE llm.chats
+SET
+    model_endpoint_id = :model_endpoint_id
+WHERE
+    id = :chat_id;
@@ -1,46 +1,33 @@
---! unprocessed_chunks : Chunk(api_key?)
-SELECT
-    id,
-    decrypt_text(text) as text,
-    (SELECT 
-        base_url 
-    FROM 
-        model_registry.models 
-    WHERE 
-        id IN (SELECT embeddings_model_id FROM rag.datasets ds WHERE ds.id IN
-        (SELECT dataset_id FROM rag.documents d WHERE d.id = document_id))
-    ) as base_url,
-    (SELECT 
-        api_key 
-    FROM 
-        model_registry.models 
-    WHERE 
-        id IN (SELECT embeddings_model_id FROM rag.datasets ds WHERE ds.id IN
-        (SELECT dataset_id FROM rag.documents d WHERE d.id = document_id))
-    ) as api_key,
-    (SELECT 
-        name 
-    FROM 
-        model_registry.models 
-    WHERE 
-        id IN (SELECT embeddings_model_id FROM rag.datasets ds WHERE ds.id IN
-        (SELECT dataset_id FROM rag.documents d WHERE d.id = document_id))
-    ) as model,
-    (SELECT 
-        context_size 
-    FROM 
-        model_registry.models 
-    WHERE 
-        id IN (SELECT embeddings_model_id FROM rag.datasets ds WHERE ds.id IN
-        (SELECT dataset_id FROM rag.documents d WHERE d.id = document_id))
-    ) as context_size
+-- Leases chunks waiting for embeddings to a worker, highest priority dataset first.
+--! claim_chunks : Chunk(api_key?)
+UPDATE rag.chunks c
+SET
+    locked_until = NOW() + (:lease_secs::INT * INTERVAL '1 second')
 FROM
-    rag.chunks
+    rag.documents d
+    JOIN rag.datasets ds ON ds.id = d.dataset_id
+    JOIN model_registry.models m ON m.id = ds.embeddings_model_id
 WHERE
-    processed IS NOT TRUE
-ORDER BY
-    id
-LIMIT :limit;
+    d.id = c.document_id
+AND
+    c.id IN (
+        SELECT waiting.id
+        FROM rag.chunks waiting
+        JOIN rag.documents wd ON wd.id = waiting.document_id
+        JOIN rag.datasets wds ON wds.id = wd.dataset_id
+        WHERE waiting.processed IS NOT TRUE
+        AND (waiting.locked_until IS NULL OR waiting.locked_until < NOW())
+        ORDER BY wds.priority DESC, waiting.id
+        LIMIT :limit
+        FOR UPDATE OF waiting SKIP LOCKED
+    )
+RETURNING
+    c.id,
+    decrypt_text(c.text) as text,
+    m.base_url,
+    m.api_key,
+    m.name as model,
+    m.context_size;
 
 --: DocumentChunk()
 
@@ -49,7 +36,8 @@ SELECT
     c.id,
     c.document_id,
     c.page_number,
-    decrypt_text(c.text) AS text
+    decrypt_text(c.text) AS text,
+    c.metadata
 FROM
     rag.chunks c
     INNER JOIN rag.documents d ON d.id = c.document_id
@@ -0,0 +1,62 @@
+--: ConversationShare(expires_at?)
+--: SharedConversation(team_name?)
+
+--! shares : ConversationShare
+SELECT
+    s.id,
+    s.token,
+    s.visibility,
+    s.expires_at,
+    s.created_at
+FROM
+    llm.conversation_shares s
+JOIN llm.conversations c ON c.id = s.conversation_id
+WHERE
+    s.conversation_id = :conversation_id
+AND
+    c.user_id = current_app_user()
+ORDER BY s.created_at DESC;
+
+-- Only the owner of a conversation can share it.
+--! insert_share(expires_in_days?)
+INSERT INTO llm.conversation_shares
+    (team_id, conversation_id, token, visibility, expires_at)
+SELECT
+    c.team_id, c.id, :token, :visibility, NOW() + make_interval(days => :expires_in_days::INT)
+FROM
+    llm.conversations c
+WHERE
+    c.id = :conversation_id
+AND
+    c.user_id = current_app_user();
+
+--! delete_share
+DELETE FROM
+    llm.conversation_shares s
+USING
+    llm.conversations c
+WHERE
+    c.id = s.conversation_id
+AND
+    s.id = :id
+AND
+    s.conversation_id = :conversation_id
+AND
+    c.user_id = current_app_user();
+
+-- Share links are checked by their token rather than the user, so the queries below
+-- don't filter on current_app_user(). Expired links find nothing.
+--! shared_conversation : SharedConversation
+SELECT
+    s.conversation_id,
+    s.team_id,
+    (SELECT t.name FROM iam.teams t WHERE t.id = s.team_id) AS team_name,
+    s.visibility,
+    c.created_at
+FROM
+    llm.conversation_shares s
+JOIN llm.conversations c ON c.id = s.conversation_id
+WHERE
+    s.token = :token
+AND
+    (s.expires_at IS NULL OR s.expires_at > NOW());
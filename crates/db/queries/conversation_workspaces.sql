@@ -0,0 +1,65 @@
+--: ConversationWorkspace()
+--: PreviousWorkspace()
+
+--! snapshot : ConversationWorkspace
+SELECT
+    w.object_id,
+    w.size_bytes,
+    decrypt_bytes(o.object_data) AS object_data
+FROM
+    llm.conversation_workspaces w
+JOIN
+    storage.objects o ON o.id = w.object_id
+JOIN
+    llm.conversations c ON c.id = w.conversation_id
+WHERE
+    w.conversation_id = :conversation_id
+AND
+    w.expires_at > NOW()
+AND
+    c.user_id = current_app_user();
+
+--! previous_object : PreviousWorkspace
+SELECT
+    w.object_id,
+    o.file_hash
+FROM
+    llm.conversation_workspaces w
+JOIN
+    storage.objects o ON o.id = w.object_id
+JOIN
+    llm.conversations c ON c.id = w.conversation_id
+WHERE
+    w.conversation_id = :conversation_id
+AND
+    c.user_id = current_app_user();
+
+--! upsert
+INSERT INTO llm.conversation_workspaces (
+    conversation_id,
+    object_id,
+    size_bytes,
+    expires_at
+)
+SELECT
+    c.id,
+    :object_id,
+    :size_bytes,
+    NOW() + make_interval(secs => :ttl_seconds)
+FROM
+    llm.conversations c
+WHERE
+    c.id = :conversation_id
+AND
+    c.user_id = current_app_user()
+ON CONFLICT (conversation_id) DO UPDATE SET
+    object_id = EXCLUDED.object_id,
+    size_bytes = EXCLUDED.size_bytes,
+    expires_at = EXCLUDED.expires_at;
+
+-- Run periodically for every team. Removing the object cascades to the workspace row.
+--! delete_expired
+DELETE FROM storage.objects
+WHERE id IN (
+    SELECT object_id FROM llm.conversation_workspaces WHERE expires_at <= NOW()
+);
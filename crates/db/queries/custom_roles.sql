@@ -0,0 +1,85 @@
+--: CustomRole()
+
+--! custom_roles : CustomRole
+SELECT
+    cr.id,
+    cr.team_id,
+    cr.name,
+    cr.description,
+    cr.permissions,
+    (SELECT COUNT(*) FROM iam.team_users_custom_roles m WHERE m.custom_role_id = cr.id) as members
+FROM
+    iam.custom_roles cr
+WHERE
+    cr.team_id = :team_id
+ORDER BY cr.name;
+
+--! insert_custom_role
+INSERT INTO iam.custom_roles (team_id, name, description, permissions, created_by)
+VALUES (:team_id, :name, :description, :permissions, current_app_user())
+RETURNING id;
+
+--! update_custom_role
+UPDATE iam.custom_roles
+SET
+    name = :name,
+    description = :description,
+    permissions = :permissions
+WHERE
+    id = :id
+AND
+    team_id = :team_id;
+
+--! delete_custom_role
+DELETE FROM iam.custom_roles
+WHERE
+    id = :id
+AND
+    team_id = :team_id;
+
+--! member_custom_roles : (user_id, custom_role_id)
+SELECT
+    user_id,
+    custom_role_id
+FROM
+    iam.team_users_custom_roles
+WHERE
+    team_id = :team_id;
+
+-- Sets Team Manager and Collaborator, keeping any other built in role.
+--! set_member_roles
+UPDATE iam.team_users
+SET
+    roles = ARRAY(
+        SELECT r FROM UNNEST(roles) r WHERE r NOT IN ('TeamManager', 'Collaborator')
+    ) || :roles::role[]
+WHERE
+    user_id = :user_id
+AND
+    team_id = :team_id;
+
+--! clear_member_custom_roles
+DELETE FROM iam.team_users_custom_roles
+WHERE
+    user_id = :user_id
+AND
+    team_id = :team_id;
+
+-- Roles of another team are skipped rather than rejected by the foreign key.
+--! add_member_custom_roles
+INSERT INTO iam.team_users_custom_roles (user_id, team_id, custom_role_id)
+SELECT :user_id, :team_id, cr.id
+FROM iam.custom_roles cr
+WHERE cr.team_id = :team_id
+AND cr.id = ANY(:custom_role_ids::INT[])
+ON CONFLICT DO NOTHING;
+
+-- For comparing custom roles with the built in ones.
+--! built_in_permissions : (role, permission)
+SELECT
+    role,
+    permission
+FROM
+    iam.roles_permissions
+WHERE
+    role IN ('TeamManager', 'Collaborator');
@@ -11,6 +11,7 @@ SELECT
     combine_under_n_chars,
     new_after_n_chars,
     multipage_sections,
+    priority,
     (SELECT COUNT(id) FROM rag.documents WHERE dataset_id = d.id) as count,
     (SELECT name FROM model_registry.models WHERE id = d.embeddings_model_id) as embeddings_model_name,
     created_at,
@@ -67,6 +68,7 @@ SELECT
     combine_under_n_chars,
     new_after_n_chars,
     multipage_sections,
+    priority,
     (SELECT COUNT(id) FROM rag.documents WHERE dataset_id = d.id) as count,
     (SELECT name FROM model_registry.models WHERE id = d.embeddings_model_id) as embeddings_model_name,
     created_at,
@@ -89,6 +91,7 @@ SELECT
     combine_under_n_chars,
     new_after_n_chars,
     multipage_sections,
+    priority,
     (SELECT COUNT(id) FROM rag.documents WHERE dataset_id = d.id) as count,
     (SELECT name FROM model_registry.models WHERE id = d.embeddings_model_id) as embeddings_model_name,
     created_at,
@@ -126,6 +129,7 @@ SELECT
     combine_under_n_chars,
     new_after_n_chars,
     multipage_sections,
+    priority,
     (SELECT COUNT(id) FROM rag.documents WHERE dataset_id = d.id) as count,
     (SELECT name FROM model_registry.models WHERE id = d.embeddings_model_id) as embeddings_model_name,
     created_at,
@@ -146,6 +150,7 @@ INSERT INTO
         combine_under_n_chars,
         new_after_n_chars,
         multipage_sections,
+        priority,
         visibility,
         created_by
     )
@@ -157,6 +162,7 @@ VALUES(
     :combine_under_n_chars,
     :new_after_n_chars,
     :multipage_sections,
+    :priority,
     :visibility,
     current_app_user())
 RETURNING id;
@@ -198,7 +204,8 @@ SET
     chunking_strategy = :chunking_strategy,
     combine_under_n_chars = :combine_under_n_chars,
     new_after_n_chars = :new_after_n_chars,
-    multipage_sections = :multipage_sections
+    multipage_sections = :multipage_sections,
+    priority = :priority
 WHERE
     id = :id
 AND
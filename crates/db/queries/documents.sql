@@ -1,25 +1,98 @@
 --: UnprocessedDocument(content?, object_id?)
 
---! unprocessed_documents : UnprocessedDocument
-SELECT
+-- Leases documents waiting to be chunked to a worker, highest priority dataset first.
+-- Documents another worker holds are skipped rather than waited on.
+--! claim_documents : UnprocessedDocument
+UPDATE rag.documents d
+SET
+    attempts = d.attempts + 1,
+    locked_by = :worker,
+    locked_until = NOW() + (:lease_secs::INT * INTERVAL '1 second')
+WHERE
+    d.id IN (
+        SELECT waiting.id
+        FROM rag.documents waiting
+        JOIN rag.datasets ds ON ds.id = waiting.dataset_id
+        WHERE waiting.chunked_at IS NULL
+        AND waiting.failure_reason IS NULL
+        AND waiting.attempts < :max_attempts
+        AND (waiting.locked_until IS NULL OR waiting.locked_until < NOW())
+        ORDER BY ds.priority DESC, waiting.id
+        LIMIT :limit
+        FOR UPDATE OF waiting SKIP LOCKED
+    )
+RETURNING
     d.id,
     d.dataset_id,
     d.file_name,
     d.content,
-    d.object_id
-FROM
-    rag.documents d
+    d.object_id,
+    d.attempts;
+
+--! extend_document_lease
+UPDATE rag.documents
+SET
+    locked_until = NOW() + (:lease_secs::INT * INTERVAL '1 second')
 WHERE
-    failure_reason IS NULL
-    AND
-    id NOT IN (SELECT document_id FROM rag.chunks WHERE document_id = d.id)
-ORDER BY
-    id
-LIMIT :limit;
+    id = :id
+AND
+    locked_by = :worker;
+
+-- Only the worker still holding the lease can finish the document, so the chunks of a
+-- worker that lost it are rolled back.
+--! finish_document
+UPDATE rag.documents
+SET
+    chunked_at = NOW(),
+    locked_by = NULL,
+    locked_until = NULL
+WHERE
+    id = :id
+AND
+    locked_by = :worker;
 
 --! fail_document
-UPDATE rag.documents SET failure_reason = :failure_reason WHERE id = :id;
-    
+UPDATE rag.documents
+SET
+    failure_reason = :failure_reason,
+    locked_by = NULL,
+    locked_until = NULL
+WHERE id = :id;
+
+-- Documents whose workers kept stopping part way through, such as files that crash the
+-- parser.
+--! give_up_documents
+UPDATE rag.documents
+SET
+    failure_reason = 'Processing stopped ' || attempts || ' times before it finished',
+    locked_by = NULL,
+    locked_until = NULL
+WHERE
+    chunked_at IS NULL
+AND
+    failure_reason IS NULL
+AND
+    attempts >= :max_attempts
+AND
+    locked_until < NOW();
+
+--! queue_depth : QueueDepth()
+SELECT
+    (SELECT COUNT(*) FROM rag.documents
+        WHERE chunked_at IS NULL AND failure_reason IS NULL
+        AND (locked_until IS NULL OR locked_until < NOW())) AS documents_waiting,
+    (SELECT COUNT(*) FROM rag.documents
+        WHERE chunked_at IS NULL AND failure_reason IS NULL
+        AND locked_until >= NOW()) AS documents_in_progress,
+    (SELECT COUNT(*) FROM rag.chunks
+        WHERE processed IS NOT TRUE
+        AND (locked_until IS NULL OR locked_until < NOW())) AS chunks_waiting,
+    (SELECT COUNT(*) FROM rag.chunks
+        WHERE processed IS NOT TRUE
+        AND locked_until >= NOW()) AS chunks_in_progress,
+    (SELECT COUNT(*) FROM llm.chat_embeddings
+        WHERE processed IS NOT TRUE) AS chats_waiting;
+
 --! documents : Document(failure_reason?)
 SELECT
     id,
@@ -0,0 +1,28 @@
+--: EncryptedColumnCount()
+
+--! rewrap_data_keys
+SELECT encryption.rewrap_data_keys();
+
+--! rotate_data_key
+SELECT encryption.rotate_data_key();
+
+--! reencrypt_batch : EncryptedColumnCount
+SELECT
+    table_name,
+    column_name,
+    reencrypted AS count
+FROM
+    encryption.reencrypt_batch(:batch_size);
+
+--! remaining : EncryptedColumnCount
+SELECT
+    table_name,
+    column_name,
+    remaining AS count
+FROM
+    encryption.remaining()
+WHERE
+    remaining > 0;
+
+--! delete_retired_keys
+SELECT encryption.delete_retired_keys();
@@ -24,11 +24,35 @@ AND
 ORDER BY
     go.path;
 
+-- The files generated in the conversation behind a share link that hasn't expired.
+--! shared_outputs : GeneratedOutput
+SELECT
+    go.id,
+    go.conversation_id,
+    go.object_id,
+    go.path,
+    go.file_name,
+    go.mime_type,
+    go.file_size,
+    go.file_hash,
+    go.created_at,
+    go.updated_at
+FROM
+    llm.generated_outputs go
+JOIN
+    llm.conversation_shares s ON s.conversation_id = go.conversation_id
+WHERE
+    s.token = :token
+AND
+    (s.expires_at IS NULL OR s.expires_at > NOW())
+ORDER BY
+    go.path;
+
 --! get_content : GeneratedOutputData
 SELECT
     go.id,
     go.path,
-    o.object_data,
+    decrypt_bytes(o.object_data) AS object_data,
     o.file_name,
     o.mime_type,
     o.file_size,
@@ -1,36 +1,53 @@
 --: History(prompt_id?)
 
---! search_history : History
-WITH search_results AS (
-    SELECT
-        c.conversation_id,
-        c.prompt_id,
-        c.created_at,
-        decrypt_text(c.content) as content,
-        p.prompt_type
-    FROM
-        llm.chats c
-    JOIN
-        llm.conversations conv ON c.conversation_id = conv.id
-    JOIN
-        assistants.prompts p ON c.prompt_id = p.id
-    WHERE
-        conv.user_id = :user_id
-    AND LOWER(decrypt_text(c.content)) LIKE LOWER('%' || :search_term || '%')
-)
+--: HistoryEmbeddingsModel(api_key?)
+
+-- The model history is embedded with, so a search can embed its query the same way.
+--! history_embeddings_model : HistoryEmbeddingsModel
 SELECT
-    sr.conversation_id as id,
-    sr.prompt_id as prompt_id,
-    LEFT(COALESCE(sr.content), 255) as summary,
-    trim(both '"' from to_json(sr.created_at)::text) as created_at_iso,
-    sr.created_at,
-    sr.prompt_type
+    id,
+    base_url,
+    api_key,
+    name as model,
+    context_size
 FROM
-    search_results sr
-ORDER BY
-    sr.created_at DESC
-LIMIT :limit;
+    model_registry.models
+WHERE
+    id = llm.history_embeddings_model(:team_id);
+
+--: ChatEmbedding(api_key?)
 
+-- Leases conversation turns waiting for embeddings to a rag-engine worker. Newest
+-- first, so recent turns can be searched while older history is backfilled.
+--! claim_chat_embeddings : ChatEmbedding
+UPDATE llm.chat_embeddings ce
+SET
+    locked_until = NOW() + (:lease_secs::INT * INTERVAL '1 second'),
+    model_id = m.id
+FROM
+    llm.chats c
+    JOIN llm.conversations conv ON conv.id = c.conversation_id
+    JOIN model_registry.models m ON m.id = llm.history_embeddings_model(conv.team_id)
+WHERE
+    c.id = ce.chat_id
+AND
+    ce.chat_id IN (
+        SELECT waiting.chat_id
+        FROM llm.chat_embeddings waiting
+        WHERE waiting.processed IS NOT TRUE
+        AND (waiting.locked_until IS NULL OR waiting.locked_until < NOW())
+        ORDER BY waiting.chat_id DESC
+        LIMIT :limit
+        FOR UPDATE OF waiting SKIP LOCKED
+    )
+RETURNING
+    ce.chat_id as id,
+    decrypt_text(c.content) as text,
+    m.id as model_id,
+    m.base_url,
+    m.api_key,
+    m.name as model,
+    m.context_size;
 
 --! history : History
 WITH summary AS (
@@ -22,3 +22,34 @@ WHERE
     locale = :locale
 ORDER BY
     key;
+
+--! locales
+SELECT DISTINCT
+    locale
+FROM
+    ops.translations
+ORDER BY
+    locale;
+
+--! upsert_translation
+INSERT INTO ops.translations (
+    locale,
+    key,
+    value
+)
+VALUES (
+    :locale,
+    :key,
+    :value
+)
+ON CONFLICT (locale, key) DO UPDATE SET
+    value = EXCLUDED.value,
+    updated_at = NOW();
+
+--! delete_translation
+DELETE FROM
+    ops.translations
+WHERE
+    locale = :locale
+AND
+    key = :key;
@@ -1,7 +1,7 @@
---: Invitation()
+--: Invitation(custom_role_id?)
 --: InviteSummary()
 
---! insert_invitation
+--! insert_invitation(custom_role_id?)
 INSERT INTO 
     iam.invitations (
         team_id, 
@@ -10,7 +10,8 @@ INSERT INTO
         last_name, 
         invitation_selector, 
         invitation_verifier_hash, 
-        roles)
+        roles,
+        custom_role_id)
     VALUES(
         :team_id, 
         :email, 
@@ -18,7 +19,9 @@ INSERT INTO
         :last_name, 
         :invitation_selector, 
         :invitation_verifier_hash, 
-        :roles);
+        :roles,
+        -- Only a role of the team the invitation is for.
+        (SELECT id FROM iam.custom_roles WHERE id = :custom_role_id AND team_id = :team_id));
 
 --! get_invitation : Invitation
 SELECT
@@ -30,6 +33,7 @@ SELECT
     invitation_selector, 
     invitation_verifier_hash,
     roles,
+    custom_role_id,
     created_at
 FROM 
     iam.invitations 
@@ -46,6 +50,7 @@ SELECT
     invitation_selector, 
     invitation_verifier_hash,
     roles,
+    custom_role_id,
     created_at
 FROM 
     iam.invitations 
@@ -78,6 +83,7 @@ SELECT
     invitation_verifier_hash,
     team_id,
     roles,
+    custom_role_id,
     created_at  
 FROM 
     iam.invitations 
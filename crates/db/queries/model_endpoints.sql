@@ -0,0 +1,89 @@
+--: ModelEndpoint(api_key?, last_error?)
+
+-- The primary endpoint uses the model's own key.
+--! model_endpoints : ModelEndpoint
+SELECT
+    e.id,
+    e.model_id,
+    e.is_primary,
+    e.provider_kind,
+    e.base_url,
+    CASE WHEN e.is_primary THEN m.api_key ELSE decrypt_text(e.api_key) END AS api_key,
+    e.priority,
+    e.weight,
+    e.consecutive_failures,
+    COALESCE(e.circuit_open_until > NOW(), FALSE) AS circuit_open,
+    e.last_error
+FROM
+    model_registry.model_endpoints e
+JOIN model_registry.models m ON m.id = e.model_id
+WHERE
+    e.model_id = :model_id
+ORDER BY
+    e.priority, e.id;
+
+--! endpoint_health
+SELECT
+    model_id,
+    COUNT(*)::int AS endpoints,
+    COUNT(*) FILTER (WHERE circuit_open_until > NOW())::int AS open_circuits,
+    COUNT(*) FILTER (WHERE consecutive_failures > 0)::int AS failing
+FROM
+    model_registry.model_endpoints
+GROUP BY
+    model_id;
+
+--! insert(api_key?)
+INSERT INTO model_registry.model_endpoints (
+    model_id,
+    provider_kind,
+    base_url,
+    api_key,
+    priority,
+    weight
+)
+VALUES (
+    :model_id,
+    :provider_kind,
+    :base_url,
+    encrypt_text(:api_key),
+    :priority,
+    :weight
+)
+RETURNING id;
+
+--! delete
+DELETE FROM
+    model_registry.model_endpoints
+WHERE
+    id = :id
+AND
+    model_id = :model_id
+AND
+    -- The primary endpoint is managed through the model itself
+    NOT is_primary;
+
+--! record_success
+UPDATE
+    model_registry.model_endpoints
+SET
+    consecutive_failures = 0,
+    circuit_open_until = NULL,
+    last_success_at = NOW()
+WHERE
+    id = :id;
+
+--! record_failure
+UPDATE
+    model_registry.model_endpoints
+SET
+    consecutive_failures = consecutive_failures + 1,
+    last_error = :last_error,
+    last_failure_at = NOW(),
+    circuit_open_until = CASE
+        WHEN consecutive_failures + 1 >= :failure_threshold
+        THEN NOW() + make_interval(secs => :cooldown_seconds)
+        ELSE circuit_open_until
+    END
+WHERE
+    id = :id;
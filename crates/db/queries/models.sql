@@ -6,6 +6,7 @@ SELECT
     id,
     name,
     model_type,
+    provider_kind,
     base_url,
     api_key,
     tpm_limit,
@@ -23,6 +24,7 @@ SELECT DISTINCT
     m.id,
     m.name,
     m.model_type,
+    m.provider_kind,
     m.base_url,
     m.api_key,
     m.tpm_limit,
@@ -30,6 +32,9 @@ SELECT DISTINCT
     m.context_size,
     m.created_at,
     m.updated_at,
+    m.guard_format,
+    m.input_price_micros,
+    m.output_price_micros,
     COALESCE(p.name, '') AS display_name,
     COALESCE(p.description, '') AS description,
     COALESCE(p.disclaimer, '') AS disclaimer,
@@ -50,6 +55,7 @@ SELECT DISTINCT
     m.id,
     m.name,
     m.model_type,
+    m.provider_kind,
     m.base_url,
     m.api_key,
     m.tpm_limit,
@@ -57,6 +63,9 @@ SELECT DISTINCT
     m.context_size,
     m.created_at,
     m.updated_at,
+    m.guard_format,
+    m.input_price_micros,
+    m.output_price_micros,
     COALESCE(p.name, '') AS display_name,
     COALESCE(p.description, '') AS description,
     COALESCE(p.disclaimer, '') AS disclaimer,
@@ -81,6 +90,7 @@ SELECT
     id,
     name,
     model_type,
+    provider_kind,
     base_url,
     api_key,
     tpm_limit,
@@ -100,6 +110,7 @@ SELECT
     id,
     name,
     model_type,
+    provider_kind,
     base_url,
     api_key,
     tpm_limit,
@@ -120,6 +131,7 @@ SELECT
     id,
     name,
     model_type,
+    provider_kind,
     base_url,
     api_key,
     tpm_limit,
@@ -138,6 +150,7 @@ SELECT
     id,
     name,
     model_type,
+    provider_kind,
     base_url,
     api_key,
     tpm_limit,
@@ -154,10 +167,27 @@ WHERE
 ORDER BY updated_at;
 
 
+--! guard_model : GuardModel(api_key?)
+SELECT
+    id,
+    name,
+    provider_kind,
+    base_url,
+    api_key,
+    guard_format
+FROM
+    model_registry.models
+WHERE
+    model_type = 'Guard'
+ORDER BY updated_at
+LIMIT 1;
+
+
 --! insert(api_key?)
 INSERT INTO model_registry.models (
     name,
     model_type,
+    provider_kind,
     base_url,
     api_key,
     tpm_limit,
@@ -167,6 +197,7 @@ INSERT INTO model_registry.models (
 VALUES(
     :name, 
     :model_type,
+    :provider_kind,
     :base_url, 
     :api_key, 
     :tpm_limit,
@@ -181,6 +212,7 @@ UPDATE
 SET 
     name = :name,
     model_type = :model_type,
+    provider_kind = :provider_kind,
     base_url = :base_url,
     api_key = :api_key,
     tpm_limit = :tpm_limit,
@@ -189,6 +221,23 @@ SET
 WHERE
     id = :id;
 
+--! set_guard_format
+UPDATE
+    model_registry.models
+SET
+    guard_format = :guard_format
+WHERE
+    id = :id;
+
+--! set_prices
+UPDATE
+    model_registry.models
+SET
+    input_price_micros = :input_price_micros,
+    output_price_micros = :output_price_micros
+WHERE
+    id = :id;
+
 --! delete
 DELETE FROM
     model_registry.models
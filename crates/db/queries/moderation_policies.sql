@@ -0,0 +1,35 @@
+--: ModerationPolicy()
+
+--! moderation_policies : ModerationPolicy
+SELECT
+    flag_type,
+    action
+FROM
+    llm.moderation_policies
+WHERE
+    team_id = :team_id
+ORDER BY flag_type;
+
+--! upsert
+INSERT INTO llm.moderation_policies
+    (team_id, flag_type, action)
+VALUES
+    (:team_id, :flag_type, :action)
+ON CONFLICT (team_id, flag_type) DO UPDATE SET
+    action = EXCLUDED.action;
+
+--! output_moderation
+SELECT
+    output_moderation
+FROM
+    iam.teams
+WHERE
+    id = :team_id;
+
+--! set_output_moderation
+UPDATE
+    iam.teams
+SET
+    output_moderation = :output_moderation
+WHERE
+    id = :team_id;
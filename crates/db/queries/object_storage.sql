@@ -4,7 +4,7 @@
 SELECT 
     object_name,
     team_id,
-    object_data,
+    decrypt_bytes(object_data) AS object_data,
     mime_type,
     file_name,
     file_size,
@@ -20,7 +20,7 @@ LIMIT 1;
 SELECT 
     object_name,
     team_id,
-    object_data,
+    decrypt_bytes(object_data) AS object_data,
     mime_type,
     file_name,
     file_size,
@@ -46,7 +46,7 @@ INSERT INTO storage.objects (
 ) VALUES (
     :object_name,
     :team_id,
-    :object_data,
+    encrypt_bytes(:object_data),
     :mime_type,
     :file_name,
     :file_size,
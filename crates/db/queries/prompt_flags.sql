@@ -1,3 +1,23 @@
 --! insert_prompt_flag
-INSERT INTO llm.prompt_flags (chat_id, flag_type)
-VALUES (:chat_id, :flag_type);
+INSERT INTO llm.prompt_flags (chat_id, flag_type, direction, action)
+VALUES (:chat_id, :flag_type, :direction, :action);
+
+--! team_prompt_flags : PromptFlag()
+SELECT
+    f.id,
+    f.chat_id,
+    c.conversation_id,
+    f.flag_type,
+    f.direction,
+    f.action,
+    u.email,
+    trim(both '"' from to_json(f.created_at)::text) as created_at
+FROM
+    llm.prompt_flags f
+JOIN llm.chats c ON c.id = f.chat_id
+JOIN llm.conversations conv ON conv.id = c.conversation_id
+JOIN iam.users u ON u.id = conv.user_id
+WHERE
+    conv.team_id = :team_id
+ORDER BY f.id DESC
+LIMIT :limit;
@@ -165,6 +165,7 @@ SELECT
     p.trim_ratio,
     p.temperature,
     p.prompt_type,
+    p.persistent_workspace,
     -- Convert times to ISO 8601 string.
     trim(both '"' from to_json(p.created_at)::text) as created_at,
     trim(both '"' from to_json(p.updated_at)::text) as updated_at,
@@ -389,6 +390,34 @@ AND
         )
     );
 
+--! set_persistent_workspace
+UPDATE assistants.prompts
+SET
+    persistent_workspace = :persistent_workspace
+WHERE
+    id = :id
+AND
+    team_id
+    IN (SELECT team_id FROM iam.team_users WHERE user_id = current_app_user());
+
+--! persistent_workspace
+SELECT
+    persistent_workspace
+FROM
+    assistants.prompts
+WHERE
+    id = :prompt_id;
+
+--! model_context_size
+SELECT
+    m.context_size
+FROM
+    assistants.prompts p
+JOIN
+    model_registry.models m ON m.id = p.model_id
+WHERE
+    p.id = :prompt_id;
+
 --! delete
 DELETE FROM
     assistants.prompts
@@ -10,6 +10,7 @@ SELECT
     default_model_context_size,
     default_model_description,
     base_url,
+    provider_kind,
     api_key_optional,
     default_embeddings_model_name,
     default_embeddings_model_display_name,
@@ -32,6 +33,7 @@ SELECT
     default_model_context_size,
     default_model_description,
     base_url,
+    provider_kind,
     api_key_optional,
     default_embeddings_model_name,
     default_embeddings_model_display_name,
@@ -53,6 +55,7 @@ INSERT INTO model_registry.providers (
     default_model_context_size,
     default_model_description,
     base_url,
+    provider_kind,
     api_key_optional,
     default_embeddings_model_name,
     default_embeddings_model_display_name,
@@ -67,6 +70,7 @@ VALUES (
     :default_model_context_size,
     :default_model_description,
     :base_url,
+    :provider_kind,
     :api_key_optional,
     :default_embeddings_model_name,
     :default_embeddings_model_display_name,
@@ -86,6 +90,7 @@ SET
     default_model_context_size = :default_model_context_size,
     default_model_description = :default_model_description,
     base_url = :base_url,
+    provider_kind = :provider_kind,
     api_key_optional = :api_key_optional,
     default_embeddings_model_name = :default_embeddings_model_name,
     default_embeddings_model_display_name = :default_embeddings_model_display_name,
@@ -0,0 +1,162 @@
+--: RetentionPolicy(conversation_days?, attachment_days?, generated_output_days?, token_usage_days?, last_run_at?)
+
+--! retention_policy : RetentionPolicy
+SELECT
+    team_id,
+    conversation_days,
+    attachment_days,
+    generated_output_days,
+    token_usage_days,
+    last_run_at
+FROM
+    ops.retention_policies
+WHERE
+    team_id = :team_id;
+
+--! upsert_retention_policy(conversation_days?, attachment_days?, generated_output_days?, token_usage_days?)
+INSERT INTO ops.retention_policies (
+    team_id,
+    conversation_days,
+    attachment_days,
+    generated_output_days,
+    token_usage_days,
+    updated_by
+)
+VALUES (
+    :team_id,
+    :conversation_days,
+    :attachment_days,
+    :generated_output_days,
+    :token_usage_days,
+    current_app_user()
+)
+ON CONFLICT (team_id) DO UPDATE SET
+    conversation_days = EXCLUDED.conversation_days,
+    attachment_days = EXCLUDED.attachment_days,
+    generated_output_days = EXCLUDED.generated_output_days,
+    token_usage_days = EXCLUDED.token_usage_days,
+    updated_by = EXCLUDED.updated_by;
+
+--: DuePolicy(conversation_days?, attachment_days?, generated_output_days?, token_usage_days?)
+
+-- Policies not enforced in the last hour. Marking them straight away means other
+-- replicas skip them.
+--! claim_due_policies : DuePolicy
+UPDATE ops.retention_policies p
+SET
+    last_run_at = NOW()
+FROM
+    iam.teams t
+WHERE
+    t.id = p.team_id
+AND
+    p.team_id IN (
+        SELECT due.team_id
+        FROM ops.retention_policies due
+        WHERE due.last_run_at IS NULL OR due.last_run_at < NOW() - INTERVAL '1 hour'
+        ORDER BY due.team_id
+        LIMIT :limit
+        FOR UPDATE OF due SKIP LOCKED
+    )
+RETURNING
+    p.team_id,
+    p.conversation_days,
+    p.attachment_days,
+    p.generated_output_days,
+    p.token_usage_days,
+    COALESCE(p.updated_by, t.created_by_user_id) as audit_user_id;
+
+-- The files of conversations about to be deleted by purge_conversations. They aren't
+-- removed with the conversation, as storage.objects doesn't reference it.
+--! purge_conversation_objects
+DELETE FROM storage.objects
+WHERE id IN (
+    SELECT ca.object_id
+    FROM llm.chats_attachments ca
+    JOIN llm.chats ch ON ch.id = ca.chat_id
+    WHERE ch.conversation_id IN (
+        SELECT c.id FROM llm.conversations c
+        WHERE c.team_id = :team_id
+        AND COALESCE(
+            (SELECT MAX(ch.created_at) FROM llm.chats ch WHERE ch.conversation_id = c.id),
+            c.created_at
+        ) < NOW() - (:days::INT * INTERVAL '1 day')
+    )
+    UNION
+    SELECT o.object_id
+    FROM llm.generated_outputs o
+    WHERE o.conversation_id IN (
+        SELECT c.id FROM llm.conversations c
+        WHERE c.team_id = :team_id
+        AND COALESCE(
+            (SELECT MAX(ch.created_at) FROM llm.chats ch WHERE ch.conversation_id = c.id),
+            c.created_at
+        ) < NOW() - (:days::INT * INTERVAL '1 day')
+    )
+    UNION
+    SELECT w.object_id
+    FROM llm.conversation_workspaces w
+    WHERE w.conversation_id IN (
+        SELECT c.id FROM llm.conversations c
+        WHERE c.team_id = :team_id
+        AND COALESCE(
+            (SELECT MAX(ch.created_at) FROM llm.chats ch WHERE ch.conversation_id = c.id),
+            c.created_at
+        ) < NOW() - (:days::INT * INTERVAL '1 day')
+    )
+);
+
+-- Conversations nobody has added to for :days days. Their chats and token metrics go
+-- with them.
+--! purge_conversations
+DELETE FROM llm.conversations c
+WHERE c.team_id = :team_id
+AND COALESCE(
+    (SELECT MAX(ch.created_at) FROM llm.chats ch WHERE ch.conversation_id = c.id),
+    c.created_at
+) < NOW() - (:days::INT * INTERVAL '1 day');
+
+--! purge_attachments
+DELETE FROM storage.objects
+WHERE id IN (
+    SELECT ca.object_id
+    FROM llm.chats_attachments ca
+    JOIN llm.chats ch ON ch.id = ca.chat_id
+    JOIN llm.conversations c ON c.id = ch.conversation_id
+    WHERE c.team_id = :team_id
+    AND ch.created_at < NOW() - (:days::INT * INTERVAL '1 day')
+);
+
+--! purge_generated_outputs
+DELETE FROM storage.objects
+WHERE id IN (
+    SELECT o.object_id
+    FROM llm.generated_outputs o
+    JOIN llm.conversations c ON c.id = o.conversation_id
+    WHERE c.team_id = :team_id
+    AND o.created_at < NOW() - (:days::INT * INTERVAL '1 day')
+);
+
+--! purge_token_usage
+DELETE FROM llm.token_usage_metrics t
+WHERE t.created_at < NOW() - (:days::INT * INTERVAL '1 day')
+AND (
+    t.chat_id IN (
+        SELECT ch.id
+        FROM llm.chats ch
+        JOIN llm.conversations c ON c.id = ch.conversation_id
+        WHERE c.team_id = :team_id
+    )
+    OR t.api_key_id IN (SELECT id FROM iam.api_keys WHERE team_id = :team_id)
+);
+
+-- Records what a purge deleted, as a change to the team's retention policy.
+--! record_purge
+WITH audit AS (
+    INSERT INTO ops.audit_trail (user_id, team_id, access_type, action)
+    VALUES (:user_id, :team_id, 'UserInterface', 'RetentionPurge')
+    RETURNING id
+)
+INSERT INTO ops.audit_trail_config_changes (audit_id, object_type, object_id, after)
+SELECT id, 'ops.retention_policies', :team_id::TEXT, :deleted::JSONB
+FROM audit;
@@ -0,0 +1,296 @@
+--: ScimUser(first_name?, last_name?, scim_external_id?)
+--: ScimGroup(external_id?, team_id?, team_name?, custom_role_id?, custom_role_name?)
+
+--! scim_users(user_name?, external_id?) : ScimUser
+SELECT
+    id,
+    email,
+    first_name,
+    last_name,
+    active,
+    scim_external_id,
+    created_at,
+    updated_at
+FROM
+    iam.users
+WHERE
+    (:user_name::text IS NULL OR LOWER(email) = LOWER(:user_name))
+AND
+    (:external_id::text IS NULL OR scim_external_id = :external_id)
+ORDER BY id
+OFFSET :offset
+LIMIT :limit;
+
+--! count_scim_users(user_name?, external_id?)
+SELECT
+    COUNT(*)
+FROM
+    iam.users
+WHERE
+    (:user_name::text IS NULL OR LOWER(email) = LOWER(:user_name))
+AND
+    (:external_id::text IS NULL OR scim_external_id = :external_id);
+
+--! scim_user : ScimUser
+SELECT
+    id,
+    email,
+    first_name,
+    last_name,
+    active,
+    scim_external_id,
+    created_at,
+    updated_at
+FROM
+    iam.users
+WHERE
+    id = :id;
+
+-- They get a subject when they first sign in, see users.claim_provisioned_user.
+--! insert_scim_user(first_name?, last_name?, scim_external_id?)
+INSERT INTO iam.users
+    (email, first_name, last_name, active, scim_external_id)
+VALUES
+    (:email, :first_name, :last_name, :active, :scim_external_id)
+RETURNING id;
+
+--! update_scim_user(first_name?, last_name?, scim_external_id?)
+UPDATE iam.users
+SET
+    email = :email,
+    first_name = :first_name,
+    last_name = :last_name,
+    active = :active,
+    scim_external_id = :scim_external_id,
+    updated_at = NOW()
+WHERE
+    id = :id;
+
+-- Both kinds, as MCP keys are API keys without an assistant.
+--! revoke_api_keys
+DELETE FROM
+    iam.api_keys
+WHERE
+    user_id = :user_id;
+
+-- Every team but their own, where what they made stays until they are erased.
+--! leave_teams
+DELETE FROM
+    iam.team_users
+WHERE
+    user_id = :user_id
+AND
+    team_id NOT IN (SELECT id FROM iam.teams WHERE created_by_user_id = :user_id);
+
+--! leave_scim_groups
+DELETE FROM
+    iam.scim_group_members
+WHERE
+    user_id = :user_id;
+
+--! scim_groups(display_name?) : ScimGroup
+SELECT
+    g.id,
+    g.display_name,
+    g.external_id,
+    g.team_id,
+    (SELECT name FROM iam.teams t WHERE t.id = g.team_id) AS team_name,
+    g.roles,
+    g.custom_role_id,
+    (SELECT name FROM iam.custom_roles cr WHERE cr.id = g.custom_role_id) AS custom_role_name,
+    g.created_at,
+    g.updated_at
+FROM
+    iam.scim_groups g
+WHERE
+    (:display_name::text IS NULL OR LOWER(g.display_name) = LOWER(:display_name))
+ORDER BY g.display_name
+OFFSET :offset
+LIMIT :limit;
+
+--! count_scim_groups(display_name?)
+SELECT
+    COUNT(*)
+FROM
+    iam.scim_groups
+WHERE
+    (:display_name::text IS NULL OR LOWER(display_name) = LOWER(:display_name));
+
+--! scim_group : ScimGroup
+SELECT
+    g.id,
+    g.display_name,
+    g.external_id,
+    g.team_id,
+    (SELECT name FROM iam.teams t WHERE t.id = g.team_id) AS team_name,
+    g.roles,
+    g.custom_role_id,
+    (SELECT name FROM iam.custom_roles cr WHERE cr.id = g.custom_role_id) AS custom_role_name,
+    g.created_at,
+    g.updated_at
+FROM
+    iam.scim_groups g
+WHERE
+    g.id = :id;
+
+--! insert_scim_group(external_id?)
+INSERT INTO iam.scim_groups
+    (display_name, external_id)
+VALUES
+    (:display_name, :external_id)
+RETURNING id;
+
+--! update_scim_group(external_id?)
+UPDATE iam.scim_groups
+SET
+    display_name = :display_name,
+    external_id = :external_id
+WHERE
+    id = :id;
+
+--! delete_scim_group
+DELETE FROM
+    iam.scim_groups
+WHERE
+    id = :id;
+
+-- The custom role must be one of the team's own.
+--! map_scim_group(team_id?, custom_role_id?)
+UPDATE iam.scim_groups
+SET
+    team_id = :team_id,
+    roles = :roles,
+    custom_role_id = (
+        SELECT id FROM iam.custom_roles WHERE id = :custom_role_id AND team_id = :team_id
+    )
+WHERE
+    id = :id;
+
+--! scim_group_members : (group_id, user_id, email)
+SELECT
+    m.group_id,
+    m.user_id,
+    u.email
+FROM
+    iam.scim_group_members m
+JOIN
+    iam.users u ON u.id = m.user_id
+WHERE
+    m.group_id = ANY(:group_ids::INT[])
+ORDER BY u.email;
+
+-- Ids that aren't users are skipped.
+--! add_scim_group_members
+INSERT INTO iam.scim_group_members (group_id, user_id)
+SELECT :group_id, u.id
+FROM iam.users u
+WHERE u.id = ANY(:user_ids::INT[])
+ON CONFLICT DO NOTHING;
+
+--! remove_scim_group_members
+DELETE FROM
+    iam.scim_group_members
+WHERE
+    group_id = :group_id
+AND
+    user_id = ANY(:user_ids::INT[]);
+
+-- The teams each group can be mapped to, for system administrators.
+--! mappable_teams : (name?)
+SELECT
+    id,
+    name
+FROM
+    iam.teams
+ORDER BY name, id;
+
+--! mappable_custom_roles : (id, team_id, name)
+SELECT
+    id,
+    team_id,
+    name
+FROM
+    iam.custom_roles
+ORDER BY name;
+
+-- Syncing a user's memberships with their groups. Memberships a group granted go
+-- when no group grants them, and a group takes over a membership from an invite.
+
+--! sync_remove_memberships
+DELETE FROM
+    iam.team_users tu
+WHERE
+    tu.user_id = :user_id
+AND
+    tu.scim_managed
+AND NOT EXISTS (
+    SELECT 1 FROM iam.scim_group_members m
+    JOIN iam.scim_groups g ON g.id = m.group_id
+    WHERE m.user_id = :user_id AND g.team_id = tu.team_id
+);
+
+-- Sets Team Manager and Collaborator from the groups, keeping any other built in role.
+--! sync_grant_memberships
+INSERT INTO iam.team_users (user_id, team_id, roles, scim_managed)
+SELECT
+    :user_id::INT,
+    g.team_id,
+    ARRAY(
+        SELECT DISTINCT r
+        FROM iam.scim_group_members m2
+        JOIN iam.scim_groups g2 ON g2.id = m2.group_id, UNNEST(g2.roles) r
+        WHERE m2.user_id = :user_id AND g2.team_id = g.team_id
+    ),
+    true
+FROM
+    iam.scim_group_members m
+JOIN
+    iam.scim_groups g ON g.id = m.group_id
+WHERE
+    m.user_id = :user_id
+AND
+    g.team_id IS NOT NULL
+GROUP BY g.team_id
+ON CONFLICT (user_id, team_id) DO UPDATE
+SET
+    roles = ARRAY(
+        SELECT r FROM UNNEST(iam.team_users.roles) r WHERE r NOT IN ('TeamManager', 'Collaborator')
+    ) || EXCLUDED.roles,
+    scim_managed = true;
+
+--! sync_remove_custom_roles
+DELETE FROM
+    iam.team_users_custom_roles c
+USING
+    iam.team_users tu
+WHERE
+    c.user_id = :user_id
+AND
+    tu.user_id = c.user_id
+AND
+    tu.team_id = c.team_id
+AND
+    tu.scim_managed
+AND NOT EXISTS (
+    SELECT 1 FROM iam.scim_group_members m
+    JOIN iam.scim_groups g ON g.id = m.group_id
+    WHERE m.user_id = :user_id
+    AND g.team_id = c.team_id
+    AND g.custom_role_id = c.custom_role_id
+);
+
+--! sync_grant_custom_roles
+INSERT INTO iam.team_users_custom_roles (user_id, team_id, custom_role_id)
+SELECT DISTINCT
+    :user_id::INT,
+    g.team_id,
+    g.custom_role_id
+FROM
+    iam.scim_group_members m
+JOIN
+    iam.scim_groups g ON g.id = m.group_id
+JOIN
+    iam.custom_roles cr ON cr.id = g.custom_role_id AND cr.team_id = g.team_id
+WHERE
+    m.user_id = :user_id
+ON CONFLICT DO NOTHING;
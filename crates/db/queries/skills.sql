@@ -66,7 +66,7 @@ SELECT
     s.description,
     s.is_system,
     sf.relative_path,
-    COALESCE(o.object_data, sf.contents) AS object_data
+    COALESCE(decrypt_bytes(o.object_data), sf.contents) AS object_data
 FROM
     context.skills s
 JOIN
@@ -0,0 +1,139 @@
+--: ToolCallPolicyRow()
+--: ToolCallApproval(chat_id?, integration_id?, request_body?, response?, decided_at?)
+
+--! policies : ToolCallPolicyRow
+SELECT
+    integration_id,
+    operation_id,
+    policy
+FROM
+    integrations.tool_call_policies
+WHERE
+    team_id = :team_id
+ORDER BY integration_id, operation_id;
+
+--! set_policy
+INSERT INTO integrations.tool_call_policies
+    (team_id, integration_id, operation_id, policy)
+SELECT
+    i.team_id, i.id, :operation_id, :policy
+FROM
+    integrations.integrations i
+WHERE
+    i.id = :integration_id
+AND
+    i.team_id = :team_id
+ON CONFLICT (integration_id, operation_id) DO UPDATE SET
+    policy = EXCLUDED.policy;
+
+--! clear_policy
+DELETE FROM
+    integrations.tool_call_policies
+WHERE
+    integration_id = :integration_id
+AND
+    operation_id = :operation_id
+AND
+    team_id = :team_id;
+
+--! insert(integration_id?, request_body?)
+INSERT INTO llm.tool_call_approvals
+    (
+        team_id, conversation_id, integration_id, function_name, operation_id,
+        method, url, request_body, arguments, requested_by
+    )
+SELECT
+    c.team_id, c.id, :integration_id, :function_name, :operation_id,
+    :method, :url, :request_body, :arguments, current_app_user()
+FROM
+    llm.conversations c
+WHERE
+    c.id = :conversation_id
+AND
+    -- Make sure the conversation belongs to the user
+    c.user_id = current_app_user()
+RETURNING id;
+
+--! attach_to_chat
+UPDATE llm.tool_call_approvals
+SET
+    chat_id = :chat_id
+WHERE
+    id = :id
+AND
+    chat_id IS NULL
+AND
+    conversation_id IN (SELECT id FROM llm.conversations WHERE user_id = current_app_user());
+
+--! approval : ToolCallApproval
+SELECT
+    id,
+    conversation_id,
+    chat_id,
+    integration_id,
+    function_name,
+    operation_id,
+    method,
+    url,
+    request_body,
+    arguments,
+    status,
+    response,
+    decided_at,
+    created_at
+FROM
+    llm.tool_call_approvals
+WHERE
+    id = :id
+AND
+    -- Make sure the approval belongs to the user
+    conversation_id IN (SELECT id FROM llm.conversations WHERE user_id = current_app_user());
+
+--! pending : ToolCallApproval
+SELECT
+    id,
+    conversation_id,
+    chat_id,
+    integration_id,
+    function_name,
+    operation_id,
+    method,
+    url,
+    request_body,
+    arguments,
+    status,
+    response,
+    decided_at,
+    created_at
+FROM
+    llm.tool_call_approvals
+WHERE
+    status = 'Pending'
+AND
+    conversation_id = :conversation_id
+AND
+    -- Make sure the approval belongs to the user
+    conversation_id IN (SELECT id FROM llm.conversations WHERE user_id = current_app_user())
+ORDER BY id;
+
+--! decide
+UPDATE llm.tool_call_approvals
+SET
+    status = :status,
+    decided_by = current_app_user(),
+    decided_at = NOW()
+WHERE
+    id = :id
+AND
+    status = 'Pending'
+AND
+    conversation_id IN (SELECT id FROM llm.conversations WHERE user_id = current_app_user());
+
+--! set_response
+UPDATE llm.tool_call_approvals
+SET
+    response = :response
+WHERE
+    id = :id
+AND
+    conversation_id IN (SELECT id FROM llm.conversations WHERE user_id = current_app_user());
@@ -0,0 +1,238 @@
+--: ExportConversation(project_name?)
+
+-- Everything a user can download about themselves, across all their teams.
+--! export_conversations : ExportConversation
+SELECT
+    c.id,
+    t.name as team_name,
+    (SELECT name FROM assistants.projects WHERE id = c.project_id) as project_name,
+    c.created_at
+FROM
+    llm.conversations c
+JOIN iam.teams t ON t.id = c.team_id
+WHERE
+    c.user_id = current_app_user()
+ORDER BY c.id;
+
+--! export_conversation : ExportConversation
+SELECT
+    c.id,
+    t.name as team_name,
+    (SELECT name FROM assistants.projects WHERE id = c.project_id) as project_name,
+    c.created_at
+FROM
+    llm.conversations c
+JOIN iam.teams t ON t.id = c.team_id
+WHERE
+    c.id = :conversation_id
+AND
+    c.user_id = current_app_user();
+
+--: ExportChat(content?, tool_calls?)
+
+--! export_chats : ExportChat
+SELECT
+    ch.id,
+    ch.conversation_id,
+    ch.role,
+    ch.status,
+    decrypt_text(ch.content) as content,
+    decrypt_text(ch.tool_calls) as tool_calls,
+    ch.created_at
+FROM
+    llm.chats ch
+JOIN llm.conversations c ON c.id = ch.conversation_id
+WHERE
+    c.user_id = current_app_user()
+ORDER BY ch.conversation_id, ch.id;
+
+--! export_files : ExportFile()
+SELECT
+    'attachments' as folder,
+    ch.conversation_id,
+    ca.object_id,
+    o.file_name
+FROM
+    llm.chats_attachments ca
+JOIN llm.chats ch ON ch.id = ca.chat_id
+JOIN llm.conversations c ON c.id = ch.conversation_id
+JOIN storage.objects o ON o.id = ca.object_id
+WHERE
+    c.user_id = current_app_user()
+UNION ALL
+SELECT
+    'outputs' as folder,
+    go.conversation_id::BIGINT,
+    go.object_id,
+    go.file_name
+FROM
+    llm.generated_outputs go
+JOIN llm.conversations c ON c.id = go.conversation_id
+WHERE
+    c.user_id = current_app_user();
+
+--! record_export
+INSERT INTO ops.audit_trail (user_id, team_id, access_type, action)
+VALUES (current_app_user(), :team_id, 'UserInterface', 'ExportUserData');
+
+--: ErasureUser(first_name?, last_name?)
+
+--! erasure_user : ErasureUser
+SELECT
+    id,
+    email,
+    first_name,
+    last_name
+FROM
+    iam.users
+WHERE
+    LOWER(email) = LOWER(:email);
+
+--! erasure_counts : ErasureCounts()
+SELECT
+    (SELECT COUNT(*) FROM llm.conversations WHERE user_id = :user_id) as conversations,
+    (SELECT COUNT(*) FROM llm.chats ch
+        JOIN llm.conversations c ON c.id = ch.conversation_id
+        WHERE c.user_id = :user_id) as chats,
+    (SELECT COUNT(*) FROM storage.objects WHERE created_by = :user_id) as objects,
+    (SELECT COUNT(*) FROM iam.api_keys WHERE user_id = :user_id) as api_keys,
+    (SELECT COUNT(*) FROM rag.datasets
+        WHERE created_by = :user_id AND visibility = 'Private') as private_datasets,
+    (SELECT COUNT(*) FROM assistants.prompts
+        WHERE created_by = :user_id AND visibility = 'Private') as private_assistants,
+    (SELECT COUNT(*) FROM iam.team_users WHERE user_id = :user_id) as teams;
+
+-- Teams nobody else belongs to are the user's own, so they go with everything in them.
+--! erase_sole_member_teams
+DELETE FROM iam.teams t
+WHERE t.id IN (SELECT team_id FROM iam.team_users WHERE user_id = :user_id)
+AND NOT EXISTS (
+    SELECT 1 FROM iam.team_users other
+    WHERE other.team_id = t.id AND other.user_id <> :user_id
+);
+
+-- Teams the user owns pass to another member, a team manager where there is one.
+--! transfer_team_ownership
+UPDATE iam.teams t
+SET created_by_user_id = (
+    SELECT other.user_id
+    FROM iam.team_users other
+    WHERE other.team_id = t.id AND other.user_id <> :user_id
+    ORDER BY ('TeamManager' = ANY(other.roles)) DESC, other.user_id
+    LIMIT 1
+)
+WHERE t.created_by_user_id = :user_id
+AND EXISTS (
+    SELECT 1 FROM iam.team_users other
+    WHERE other.team_id = t.id AND other.user_id <> :user_id
+);
+
+-- Datasets and assistants have no foreign key to the user, so the private ones are
+-- deleted here. Their documents, chunks and projects go with them.
+--! erase_private_datasets
+DELETE FROM rag.datasets
+WHERE created_by = :user_id
+AND visibility = 'Private';
+
+--! erase_private_prompts
+DELETE FROM assistants.prompts
+WHERE created_by = :user_id
+AND visibility = 'Private';
+
+-- Content the user shared with a team stays with the team, owned by its owner. The
+-- rest is deleted with the user.
+--! reassign_datasets
+UPDATE rag.datasets d
+SET created_by = t.created_by_user_id
+FROM iam.teams t
+WHERE t.id = d.team_id
+AND d.created_by = :user_id
+AND d.visibility <> 'Private'
+AND t.created_by_user_id <> :user_id;
+
+--! reassign_prompts
+UPDATE assistants.prompts p
+SET created_by = t.created_by_user_id
+FROM iam.teams t
+WHERE t.id = p.team_id
+AND p.created_by = :user_id
+AND p.visibility <> 'Private'
+AND t.created_by_user_id <> :user_id;
+
+--! reassign_projects
+UPDATE assistants.projects p
+SET created_by = t.created_by_user_id
+FROM iam.teams t
+WHERE t.id = p.team_id
+AND p.created_by = :user_id
+AND p.visibility <> 'Private'
+AND t.created_by_user_id <> :user_id;
+
+--! reassign_integrations
+UPDATE integrations.integrations i
+SET created_by = t.created_by_user_id
+FROM iam.teams t
+WHERE t.id = i.team_id
+AND i.created_by = :user_id
+AND i.visibility <> 'Private'
+AND t.created_by_user_id <> :user_id;
+
+--! reassign_skills
+UPDATE context.skills s
+SET created_by = t.created_by_user_id
+FROM iam.teams t
+WHERE t.id = s.team_id
+AND s.created_by = :user_id
+AND s.visibility <> 'Private'
+AND t.created_by_user_id <> :user_id;
+
+--! reassign_webhooks
+UPDATE ops.webhooks w
+SET created_by = t.created_by_user_id
+FROM iam.teams t
+WHERE t.id = w.team_id
+AND w.created_by = :user_id
+AND t.created_by_user_id <> :user_id;
+
+-- The documents, assistant icons and skill files of what the team keeps.
+--! reassign_objects
+UPDATE storage.objects o
+SET created_by = t.created_by_user_id
+FROM iam.teams t
+WHERE t.id = o.team_id
+AND o.created_by = :user_id
+AND t.created_by_user_id <> :user_id
+AND (
+    EXISTS (
+        SELECT 1 FROM rag.documents doc
+        JOIN rag.datasets ds ON ds.id = doc.dataset_id
+        WHERE doc.object_id = o.id AND ds.visibility <> 'Private'
+    )
+    OR EXISTS (
+        SELECT 1 FROM assistants.prompts p
+        WHERE p.image_icon_object_id = o.id AND p.created_by <> :user_id
+    )
+    OR EXISTS (
+        SELECT 1 FROM context.skill_files f
+        JOIN context.skills s ON s.id = f.skill_id
+        WHERE f.object_id = o.id AND s.created_by <> :user_id
+    )
+);
+
+--! erase_invitations
+DELETE FROM iam.invitations WHERE LOWER(email) = LOWER(:email);
+
+-- Kept without a team, so it outlives the teams that were deleted.
+--! record_erasure
+WITH audit AS (
+    INSERT INTO ops.audit_trail (user_id, access_type, action)
+    VALUES (:user_id, 'UserInterface', 'EraseUserData')
+    RETURNING id
+)
+INSERT INTO ops.audit_trail_config_changes (audit_id, object_type, object_id, after)
+SELECT id, 'iam.users', :user_id::TEXT, :erased::JSONB
+FROM audit;
+
+-- Conversations, chats, files, API keys, connections and memberships cascade.
+--! erase_user
+DELETE FROM iam.users WHERE id = :user_id;
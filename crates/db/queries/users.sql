@@ -1,6 +1,6 @@
---! user : (first_name?, last_name?)
+--! user : (first_name?, last_name?, locale?)
 SELECT 
-    id, email, first_name, last_name, system_admin
+    id, email, first_name, last_name, system_admin, locale
 FROM 
     iam.users
 WHERE
@@ -12,9 +12,23 @@ INSERT INTO
 VALUES(:openid_sub, :email, :first_name, :last_name) 
 RETURNING id;
 
+-- Users provisioned over SCIM get their subject when they first sign in.
+--! claim_provisioned_user(first_name?, last_name?)
+UPDATE
+    iam.users
+SET
+    openid_sub = :openid_sub,
+    first_name = COALESCE(first_name, :first_name),
+    last_name = COALESCE(last_name, :last_name)
+WHERE
+    LOWER(email) = LOWER(:email)
+AND
+    openid_sub IS NULL
+RETURNING id;
+
 --! user_by_openid_sub : (first_name?, last_name?)
 SELECT 
-    id, email, first_name, last_name, system_admin
+    id, email, first_name, last_name, system_admin, active
 FROM 
     iam.users
 WHERE
@@ -36,6 +50,22 @@ SET
 WHERE
     id = :current_user_id;
 
+--! set_locale(locale?)
+UPDATE
+    iam.users
+SET
+    locale = :locale
+WHERE
+    id = :current_user_id;
+
+--! locale_by_openid_sub : (locale?)
+SELECT
+    locale
+FROM
+    iam.users
+WHERE
+    openid_sub = :openid_sub;
+
 --! count_users
 SELECT
     count(id)
@@ -59,4 +89,12 @@ OR (
         WHERE system_admin = true AND id = current_app_user()
     )
     AND role = 'SystemAdministrator'
-);
+)
+UNION
+SELECT
+    UNNEST(cr.permissions) as permission
+FROM
+    iam.custom_roles cr
+JOIN iam.team_users_custom_roles tucr ON tucr.custom_role_id = cr.id
+WHERE
+    tucr.team_id = :team_id AND tucr.user_id = current_app_user();
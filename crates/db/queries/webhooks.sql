@@ -0,0 +1,194 @@
+--: Webhook()
+--: WebhookDelivery(response_status?, last_error?, delivered_at?)
+--: ClaimedDelivery()
+
+--! webhooks : Webhook
+SELECT
+    w.id,
+    w.url,
+    w.description,
+    decrypt_text(w.secret) AS secret,
+    w.events,
+    w.active,
+    (SELECT COUNT(*) FROM ops.webhook_deliveries d WHERE d.webhook_id = w.id AND d.status = 'Pending') AS pending,
+    (SELECT COUNT(*) FROM ops.webhook_deliveries d WHERE d.webhook_id = w.id AND d.status = 'Failed') AS failed,
+    trim(both '"' from to_json(w.created_at)::text) AS created_at
+FROM
+    ops.webhooks w
+WHERE
+    w.team_id = :team_id
+ORDER BY w.id;
+
+--! webhook : Webhook
+SELECT
+    w.id,
+    w.url,
+    w.description,
+    decrypt_text(w.secret) AS secret,
+    w.events,
+    w.active,
+    (SELECT COUNT(*) FROM ops.webhook_deliveries d WHERE d.webhook_id = w.id AND d.status = 'Pending') AS pending,
+    (SELECT COUNT(*) FROM ops.webhook_deliveries d WHERE d.webhook_id = w.id AND d.status = 'Failed') AS failed,
+    trim(both '"' from to_json(w.created_at)::text) AS created_at
+FROM
+    ops.webhooks w
+WHERE
+    w.id = :id
+AND
+    w.team_id = :team_id;
+
+--! insert
+INSERT INTO ops.webhooks
+    (team_id, url, description, secret, events, created_by)
+VALUES
+    (:team_id, :url, :description, encrypt_text(:secret), :events, current_app_user())
+RETURNING id;
+
+--! update
+UPDATE ops.webhooks
+SET
+    url = :url,
+    description = :description,
+    events = :events,
+    active = :active
+WHERE
+    id = :id
+AND
+    team_id = :team_id;
+
+--! delete
+DELETE FROM
+    ops.webhooks
+WHERE
+    id = :id
+AND
+    team_id = :team_id;
+
+--! deliveries : WebhookDelivery
+SELECT
+    d.id,
+    d.event_id,
+    d.event_type,
+    jsonb_pretty(d.payload) AS payload,
+    d.status,
+    d.attempts,
+    d.response_status,
+    d.last_error,
+    trim(both '"' from to_json(d.created_at)::text) AS created_at,
+    trim(both '"' from to_json(d.delivered_at)::text) AS delivered_at
+FROM
+    ops.webhook_deliveries d
+JOIN ops.webhooks w ON w.id = d.webhook_id
+WHERE
+    d.webhook_id = :webhook_id
+AND
+    w.team_id = :team_id
+ORDER BY d.id DESC
+LIMIT :limit;
+
+--! replay
+UPDATE ops.webhook_deliveries d
+SET
+    status = 'Pending',
+    attempts = 0,
+    next_attempt_at = NOW(),
+    last_error = NULL
+FROM
+    ops.webhooks w
+WHERE
+    w.id = d.webhook_id
+AND
+    d.id = :id
+AND
+    d.webhook_id = :webhook_id
+AND
+    w.team_id = :team_id;
+
+--! enqueue_test
+INSERT INTO ops.webhook_deliveries
+    (webhook_id, event_type, payload)
+SELECT
+    w.id, 'webhook.test', :payload
+FROM
+    ops.webhooks w
+WHERE
+    w.id = :webhook_id
+AND
+    w.team_id = :team_id;
+
+--! enqueue_event
+SELECT ops.enqueue_webhook_event(:team_id, :event_type, :payload);
+
+--! enqueue_connection_failed
+SELECT
+    ops.enqueue_webhook_event(
+        oc.team_id,
+        'integration.connection_failed',
+        jsonb_build_object(
+            'connection_id', oc.external_id,
+            'integration_id', i.id,
+            'integration', i.name,
+            'user', u.email,
+            'error', :error::text
+        )
+    )
+FROM
+    integrations.oauth2_connections oc
+JOIN integrations.integrations i ON i.id = oc.integration_id
+JOIN iam.users u ON u.id = oc.user_id
+WHERE
+    oc.id = :connection_id;
+
+-- Takes due deliveries and holds them for a few minutes. A worker that stops before
+-- recording the result leaves them to be retried once the hold expires.
+--! claim_deliveries : ClaimedDelivery
+UPDATE ops.webhook_deliveries d
+SET
+    attempts = d.attempts + 1,
+    next_attempt_at = NOW() + INTERVAL '5 minutes'
+FROM
+    ops.webhooks w
+WHERE
+    w.id = d.webhook_id
+AND
+    d.id IN (
+        SELECT due.id
+        FROM ops.webhook_deliveries due
+        JOIN ops.webhooks hook ON hook.id = due.webhook_id
+        WHERE due.status = 'Pending'
+        AND due.next_attempt_at <= NOW()
+        AND hook.active
+        ORDER BY due.next_attempt_at
+        LIMIT :limit
+        FOR UPDATE OF due SKIP LOCKED
+    )
+RETURNING
+    d.id,
+    d.event_id,
+    d.event_type,
+    d.payload,
+    d.attempts,
+    trim(both '"' from to_json(d.created_at)::text) AS created_at,
+    w.team_id,
+    w.url,
+    decrypt_text(w.secret) AS secret;
+
+--! delivered
+UPDATE ops.webhook_deliveries
+SET
+    status = 'Delivered',
+    response_status = :response_status,
+    last_error = NULL,
+    delivered_at = NOW()
+WHERE
+    id = :id;
+
+--! attempt_failed(response_status?)
+UPDATE ops.webhook_deliveries
+SET
+    status = :status,
+    response_status = :response_status,
+    last_error = :last_error,
+    next_attempt_at = NOW() + (:retry_in_secs::INT * INTERVAL '1 second')
+WHERE
+    id = :id;
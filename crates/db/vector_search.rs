@@ -5,15 +5,19 @@ use crate::Transaction;
 pub struct RelatedContext {
     pub chunk_id: i32,
     pub chunk_text: String,
+    pub page_number: i32,
+    pub metadata: serde_json::Value,
 }
 
 // Query the vector database using a similarity search.
-// The prompt decides how we use the datasets
+// The prompt decides how we use the datasets, and a filter such as
+// {"is_table": true} keeps only the chunks whose metadata contains it.
 pub async fn get_related_context(
     transaction: &Transaction<'_>,
     prompt_id: i32,
     limit: i32,
     embeddings: Vec<f32>,
+    filter: Option<&serde_json::Value>,
 ) -> Result<Vec<RelatedContext>, TokioPostgresError> {
     // Which datasets does the prompt use
     let datasets = prompts::prompt_datasets()
@@ -32,18 +36,21 @@ pub async fn get_related_context(
             "
                     SELECT 
                         id,
-                        text 
+                        text,
+                        page_number,
+                        metadata
                     FROM 
                         rag.chunks
                     WHERE
                         document_id IN (
                             SELECT id FROM rag.documents WHERE dataset_id = ANY($1)
                         )
+                        AND ($4::jsonb IS NULL OR metadata @> $4)
                     ORDER BY 
                         embeddings <-> $2 
                     LIMIT $3;
                     ",
-            &[&datasets, &embedding_data, &(limit as i64)],
+            &[&datasets, &embedding_data, &(limit as i64), &filter],
         )
         .await?;
 
@@ -53,6 +60,8 @@ pub async fn get_related_context(
         .map(|content| RelatedContext {
             chunk_id: content.get(0),
             chunk_text: content.get(1),
+            page_number: content.get(2),
+            metadata: content.get(3),
         })
         .collect();
 
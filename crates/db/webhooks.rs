@@ -0,0 +1,61 @@
+//! The events a team can subscribe a webhook to.
+//!
+//! Events are queued in `ops.webhook_deliveries` by `ops.enqueue_webhook_event`, mostly
+//! from triggers, so rag-engine and the web server raise them the same way. The web
+//! server sends them.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WebhookEvent {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+pub const EVENTS: [WebhookEvent; 7] = [
+    WebhookEvent {
+        name: "document.processed",
+        description: "A document has been chunked and embedded and is ready to use",
+    },
+    WebhookEvent {
+        name: "document.failed",
+        description: "A document couldn't be loaded or parsed",
+    },
+    WebhookEvent {
+        name: "chat.flagged",
+        description: "A guard model flagged a prompt or a response",
+    },
+    WebhookEvent {
+        name: "integration.connection_failed",
+        description: "An integration's OAuth2 connection could no longer be refreshed",
+    },
+    WebhookEvent {
+        name: "team.member_joined",
+        description: "Someone joined the team",
+    },
+    WebhookEvent {
+        name: "budget.warning",
+        description: "Spending reached a budget's warning threshold this month",
+    },
+    WebhookEvent {
+        name: "budget.exceeded",
+        description: "Spending reached a budget's stop threshold and chats are refused",
+    },
+];
+
+/// Sent by "Send test", whatever the webhook subscribes to.
+pub const TEST_EVENT: &str = "webhook.test";
+
+pub fn is_event(name: &str) -> bool {
+    EVENTS.iter().any(|event| event.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_catalogued_events_can_be_subscribed() {
+        assert!(is_event("document.processed"));
+        assert!(!is_event(TEST_EVENT));
+        assert!(!is_event("document.deleted"));
+    }
+}
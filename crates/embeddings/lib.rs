@@ -0,0 +1,84 @@
+//! Embeddings for the text of chunks, conversation turns and search queries, shared by
+//! rag-engine, the web server and the tool runtime.
+
+use rig::client::EmbeddingsClient;
+use rig::embeddings::EmbeddingModel;
+use rig::providers::{ollama, openai};
+
+/// Cuts the text to the model's context size in characters, or 256 when the size isn't
+/// set.
+pub fn trim_to_context_length(input: &str, context_length: i32) -> String {
+    if input.is_empty() {
+        return String::new();
+    }
+    let effective_context_length = if context_length <= 0 {
+        256
+    } else {
+        context_length
+    };
+    let char_count = input.chars().count() as i32;
+    if char_count <= effective_context_length {
+        return input.to_string();
+    }
+    input
+        .chars()
+        .take(effective_context_length as usize)
+        .collect()
+}
+
+/// Embeds the text with an OpenAI compatible endpoint when there is an API key, and
+/// with Ollama otherwise.
+pub async fn get_embeddings_via_rig(
+    input: &str,
+    api_end_point: &str,
+    model: &str,
+    context_length: i32,
+    api_key: Option<&str>,
+) -> Result<Vec<f32>, String> {
+    let text = String::from_utf8_lossy(input.as_bytes()).to_string();
+    let trimmed_text = trim_to_context_length(&text, context_length);
+
+    let normalized_base_url = api_end_point
+        .strip_suffix("/embeddings")
+        .or_else(|| api_end_point.strip_suffix("/v1/embeddings"))
+        .map(|s| s.trim_end_matches('/').to_string())
+        .unwrap_or_else(|| api_end_point.trim_end_matches('/').to_string());
+
+    let embedding = if let Some(key) = api_key.filter(|k| !k.trim().is_empty()) {
+        let client = openai::Client::builder()
+            .api_key(key)
+            .base_url(&normalized_base_url)
+            .build()
+            .map_err(|err| err.to_string())?;
+        client
+            .embedding_model(model)
+            .embed_text(&trimmed_text)
+            .await
+            .map_err(|err| err.to_string())?
+    } else {
+        let client = ollama::Client::builder()
+            .api_key("")
+            .base_url(&normalized_base_url)
+            .build()
+            .map_err(|err| err.to_string())?;
+        client
+            .embedding_model(model)
+            .embed_text(&trimmed_text)
+            .await
+            .map_err(|err| err.to_string())?
+    };
+
+    Ok(embedding.vec.into_iter().map(|v| v as f32).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_is_cut_to_the_context_size() {
+        assert_eq!(trim_to_context_length("héllo world", 5), "héllo");
+        assert_eq!(trim_to_context_length("short", 100), "short");
+        assert_eq!(trim_to_context_length(&"a".repeat(300), 0).len(), 256);
+    }
+}
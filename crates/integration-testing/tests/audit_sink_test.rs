@@ -0,0 +1,53 @@
+// The audit sink's cursor against a real database: an event from a transaction that is
+// still open holds back the events after it, instead of being skipped once they are sent.
+use db::queries::audit_trail;
+
+const AUDIT_INSERT: &str = "INSERT INTO ops.audit_trail (user_id, access_type, action)
+    VALUES (0, 'UserInterface', 'UpdateConfiguration') RETURNING id, xact_id";
+
+#[tokio::test]
+async fn events_wait_for_earlier_transactions() {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL not set");
+    let pool = db::create_pool(&database_url);
+    let reader = pool.get().await.unwrap();
+    let mut slow_client = pool.get().await.unwrap();
+    let fast_client = pool.get().await.unwrap();
+
+    let slow = slow_client.transaction().await.unwrap();
+    let row = slow.query_one(AUDIT_INSERT, &[]).await.unwrap();
+    let (slow_id, slow_xact_id): (i32, i64) = (row.get(0), row.get(1));
+
+    let row = fast_client.query_one(AUDIT_INSERT, &[]).await.unwrap();
+    let fast_id: i32 = row.get(0);
+
+    let ids_after = |rows: Vec<db::AuditTrail>| -> Vec<i32> {
+        rows.into_iter()
+            .map(|row| row.id)
+            .filter(|id| *id == slow_id || *id == fast_id)
+            .collect()
+    };
+
+    let rows = audit_trail::audit_after()
+        .bind(&reader, &(slow_xact_id - 1), &0, &10_000)
+        .all()
+        .await
+        .unwrap();
+    assert_eq!(ids_after(rows), Vec::<i32>::new());
+
+    slow.commit().await.unwrap();
+
+    let rows = audit_trail::audit_after()
+        .bind(&reader, &(slow_xact_id - 1), &0, &10_000)
+        .all()
+        .await
+        .unwrap();
+    assert_eq!(ids_after(rows), vec![slow_id, fast_id]);
+
+    reader
+        .execute(
+            "DELETE FROM ops.audit_trail WHERE id = ANY($1)",
+            &[&vec![slow_id, fast_id]],
+        )
+        .await
+        .unwrap();
+}
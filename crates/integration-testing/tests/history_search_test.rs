@@ -0,0 +1,117 @@
+// Searches chat history against a real database. Everything happens in one transaction
+// that is rolled back.
+use db::history_search::{search_history, KEYWORD_WINDOW};
+
+#[tokio::test]
+async fn keywords_are_searched_in_recent_turns_only() {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL not set");
+    let pool = db::create_pool(&database_url);
+    let mut client = pool.get().await.unwrap();
+    let transaction = client.transaction().await.unwrap();
+
+    std::env::set_var("CUSTOMER_KEY", "history-search-test-key");
+    std::env::remove_var("CUSTOMER_KEY_PREVIOUS");
+    db::customer_keys::set_local_keys(&transaction)
+        .await
+        .unwrap();
+
+    let user_id: i32 = transaction
+        .query_one(
+            "INSERT INTO iam.users (openid_sub, email) VALUES ('history-search-test', 'history@test.com') RETURNING id",
+            &[],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    db::authz::set_row_level_security_user_id(&transaction, "history-search-test".to_string())
+        .await
+        .unwrap();
+    let team_id: i32 = transaction
+        .query_one(
+            "INSERT INTO iam.teams (created_by_user_id) VALUES ($1) RETURNING id",
+            &[&user_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    let model_id: i32 = transaction
+        .query_one(
+            "INSERT INTO model_registry.models (model_type, name, base_url, context_size)
+            VALUES ('LLM', 'history-search-test', 'http://localhost', 2048)
+            RETURNING id",
+            &[],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    // The seeded prompts are inserted with their ids, so don't rely on the sequence.
+    let prompt_id: i32 = transaction
+        .query_one(
+            "INSERT INTO assistants.prompts
+                (id, team_id, model_id, visibility, name, max_history_items, max_chunks,
+                trim_ratio, created_by, category_id)
+            VALUES ((SELECT COALESCE(MAX(id), 0) + 1 FROM assistants.prompts), $1, $2, 'Private', 'history-search-test', 3, 10, 80, $3,
+                (SELECT MIN(id) FROM assistants.categories))
+            RETURNING id",
+            &[&team_id, &model_id, &user_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    let old_conversation: i64 = transaction
+        .query_one(
+            "INSERT INTO llm.conversations (user_id, team_id) VALUES ($1, $2) RETURNING id",
+            &[&user_id, &team_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    let recent_conversation: i64 = transaction
+        .query_one(
+            "INSERT INTO llm.conversations (user_id, team_id) VALUES ($1, $2) RETURNING id",
+            &[&user_id, &team_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+
+    let insert_turns = "INSERT INTO llm.chats (conversation_id, prompt_id, role, content)
+        SELECT $1, $2, 'User', encrypt_text($3) FROM generate_series(1, $4)";
+    transaction
+        .execute(
+            insert_turns,
+            &[&old_conversation, &prompt_id, &"an old needle", &1],
+        )
+        .await
+        .unwrap();
+    // Push the old turn out of the window.
+    transaction
+        .execute(
+            insert_turns,
+            &[
+                &recent_conversation,
+                &prompt_id,
+                &"nothing to see",
+                &(KEYWORD_WINDOW as i32),
+            ],
+        )
+        .await
+        .unwrap();
+    transaction
+        .execute(
+            insert_turns,
+            &[&recent_conversation, &prompt_id, &"a recent Needle", &1],
+        )
+        .await
+        .unwrap();
+
+    let found = search_history(&transaction, user_id, team_id, "needle", None, 10)
+        .await
+        .unwrap();
+
+    let ids: Vec<i64> = found.iter().map(|c| c.id).collect();
+    assert_eq!(ids, vec![recent_conversation]);
+    assert_eq!(found[0].snippets, vec!["a recent Needle".to_string()]);
+
+    transaction.rollback().await.unwrap();
+}
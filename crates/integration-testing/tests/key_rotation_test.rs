@@ -0,0 +1,171 @@
+// Rotates the customer key against a real database. Everything happens in one
+// transaction that is rolled back, so the keys of the database under test are untouched.
+use db::encryption::{rotate_keys, RotationOptions};
+
+const OLD_KEY: &str = "rotation-test-old-key";
+const NEW_KEY: &str = "rotation-test-new-key";
+
+#[tokio::test]
+async fn rotate_customer_key() {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL not set");
+    let pool = db::create_pool(&database_url);
+    let mut client = pool.get().await.unwrap();
+    let transaction = client.transaction().await.unwrap();
+
+    // Start from no data keys so the test doesn't depend on the keys already in use.
+    transaction
+        .execute("DELETE FROM encryption.data_keys", &[])
+        .await
+        .unwrap();
+
+    std::env::set_var("CUSTOMER_KEY", OLD_KEY);
+    std::env::remove_var("CUSTOMER_KEY_PREVIOUS");
+    db::customer_keys::set_local_keys(&transaction)
+        .await
+        .unwrap();
+
+    let user_id: i32 = transaction
+        .query_one(
+            "INSERT INTO iam.users (openid_sub, email) VALUES ('rotation-test', 'rotation@test.com') RETURNING id",
+            &[],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    db::authz::set_row_level_security_user_id(&transaction, "rotation-test".to_string())
+        .await
+        .unwrap();
+    let team_id: i32 = transaction
+        .query_one(
+            "INSERT INTO iam.teams (created_by_user_id) VALUES ($1) RETURNING id",
+            &[&user_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+
+    let secret_id: i32 = transaction
+        .query_one(
+            "INSERT INTO iam.oauth_clients (client_id, client_secret, provider, provider_url)
+            VALUES ('rotation-test', encrypt_text('current-secret'), 'Test', 'https://rotation.test/current')
+            RETURNING id",
+            &[],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    // Written before envelope encryption, directly with the root key.
+    let legacy_id: i32 = transaction
+        .query_one(
+            "INSERT INTO iam.oauth_clients (client_id, client_secret, provider, provider_url)
+            VALUES ('rotation-test', pgp_sym_encrypt('legacy-secret', $1)::text, 'Test', 'https://rotation.test/legacy')
+            RETURNING id",
+            &[&OLD_KEY],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    let object_id: i32 = transaction
+        .query_one(
+            "INSERT INTO storage.objects (object_name, team_id, object_data, mime_type, file_name, file_size, file_hash, created_by)
+            VALUES ('rotation-test', $1, encrypt_bytes($2), 'text/plain', 'rotation.txt', 11, 'hash', $3)
+            RETURNING id",
+            &[&team_id, &b"object data".as_slice(), &user_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+
+    let webhook_id: i32 = transaction
+        .query_one(
+            "INSERT INTO ops.webhooks (team_id, url, secret, events, created_by)
+            VALUES ($1, 'https://rotation.test/hook', encrypt_text('whsec_test'), ARRAY['document.processed'], $2)
+            RETURNING id",
+            &[&team_id, &user_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+
+    let stored: String = transaction
+        .query_one(
+            "SELECT client_secret FROM iam.oauth_clients WHERE id = $1",
+            &[&secret_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert!(stored.starts_with("enc:v"));
+    assert_ne!(stored, "current-secret");
+
+    std::env::set_var("CUSTOMER_KEY", NEW_KEY);
+    std::env::set_var("CUSTOMER_KEY_PREVIOUS", OLD_KEY);
+    let report = rotate_keys(
+        &transaction,
+        &RotationOptions {
+            batch_size: 1,
+            new_data_key: true,
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(report.rewrapped_keys, 1);
+    let version = report.data_key_version.expect("expected a new data key");
+    assert!(report.reencrypted["iam.oauth_clients.client_secret"] >= 2);
+    assert!(report.reencrypted["storage.objects.object_data"] >= 1);
+    assert!(report.reencrypted["ops.webhooks.secret"] >= 1);
+    assert_eq!(report.deleted_keys, 1);
+
+    // Only the new key from here on.
+    std::env::remove_var("CUSTOMER_KEY_PREVIOUS");
+    transaction
+        .execute(
+            "SELECT set_config('encryption.previous_root_key', '', false)",
+            &[],
+        )
+        .await
+        .unwrap();
+
+    for (id, secret) in [(secret_id, "current-secret"), (legacy_id, "legacy-secret")] {
+        let row = transaction
+            .query_one(
+                "SELECT client_secret, decrypt_text(client_secret) FROM iam.oauth_clients WHERE id = $1",
+                &[&id],
+            )
+            .await
+            .unwrap();
+        let stored: String = row.get(0);
+        let decrypted: String = row.get(1);
+        assert!(stored.starts_with(&format!("enc:v{version}:")));
+        assert_eq!(decrypted, secret);
+    }
+
+    let data: Vec<u8> = transaction
+        .query_one(
+            "SELECT decrypt_bytes(object_data) FROM storage.objects WHERE id = $1",
+            &[&object_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(data, b"object data");
+
+    // Deliveries are still signed with the secret once the old data key is gone. The
+    // expected signature matches webhook_delivery::signature("whsec_test", 1700000000, ..).
+    let signature: String = transaction
+        .query_one(
+            "SELECT encode(hmac('1700000000.{\"a\":1}', decrypt_text(secret), 'sha256'), 'hex')
+            FROM ops.webhooks WHERE id = $1",
+            &[&webhook_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(
+        signature,
+        "38877139021993b830af32feea6e18a8da83eb2f6e49ee50bd9e4cf4ca4d3789"
+    );
+
+    transaction.rollback().await.unwrap();
+}
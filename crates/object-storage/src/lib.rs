@@ -1,4 +1,4 @@
-use db::{queries::object_storage, ObjectStorage, Pool, TokioPostgresError};
+use db::{queries::object_storage, ObjectStorage, Pool, TokioPostgresError, Transaction};
 use image::imageops::FilterType;
 
 #[derive(Clone)]
@@ -78,6 +78,23 @@ async fn upload_db(
     team_id: i32,
     file_name: &str,
     bytes: &[u8],
+) -> Result<i32, StorageError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let id = insert(&transaction, user_id, team_id, file_name, bytes).await?;
+    transaction.commit().await?;
+
+    Ok(id)
+}
+
+/// Stores the object in the database as part of the caller's transaction, so it is rolled
+/// back with whatever was going to refer to it.
+pub async fn insert(
+    transaction: &Transaction<'_>,
+    user_id: i32,
+    team_id: i32,
+    file_name: &str,
+    bytes: &[u8],
 ) -> Result<i32, StorageError> {
     if file_name.is_empty() || bytes.is_empty() {
         return Err(StorageError::InvalidInput(
@@ -95,12 +112,11 @@ async fn upload_db(
     let file_size = bytes.len() as i64;
     let file_hash = format!("{:x}", md5::compute(bytes));
 
-    let mut client = pool.get().await?;
-    let transaction = client.transaction().await?;
+    db::customer_keys::set_local_keys(transaction).await?;
 
     let id = object_storage::insert()
         .bind(
-            &transaction,
+            transaction,
             &object_name,
             &team_id,
             &bytes,
@@ -113,8 +129,6 @@ async fn upload_db(
         .one()
         .await?;
 
-    transaction.commit().await?;
-
     Ok(id)
 }
 
@@ -168,6 +182,7 @@ pub async fn get(config: &StorageConfig, id: i32) -> Result<ObjectStorage, Stora
 async fn get_db(pool: Pool, id: i32) -> Result<ObjectStorage, StorageError> {
     let mut client = pool.get().await?;
     let transaction = client.transaction().await?;
+    db::customer_keys::set_local_keys(&transaction).await?;
 
     let object = object_storage::get().bind(&transaction, &id).one().await?;
 
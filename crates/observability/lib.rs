@@ -0,0 +1,187 @@
+//! Tracing for the Bionic binaries. Logs always go to stdout, and when
+//! `OTEL_EXPORTER_OTLP_ENDPOINT` is set spans are also exported to an OpenTelemetry
+//! collector over OTLP/HTTP.
+//!
+//! The exporter reads the standard `OTEL_*` environment variables, so the service name,
+//! resource attributes, headers and sampler are configured the same way as for any other
+//! OpenTelemetry SDK.
+
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::layer::{Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+const ENDPOINT_VARS: [&str; 2] = [
+    "OTEL_EXPORTER_OTLP_ENDPOINT",
+    "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+];
+
+/// Keeps the exporter running. Dropping it flushes the spans that haven't been sent yet.
+pub struct Telemetry {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Telemetry {
+    pub fn exporting(&self) -> bool {
+        self.provider.is_some()
+    }
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take() {
+            if let Err(err) = provider.shutdown() {
+                eprintln!("Failed to flush traces: {err}");
+            }
+        }
+    }
+}
+
+/// Installs the global subscriber. `service_name` is used unless `OTEL_SERVICE_NAME` is set.
+pub fn init(service_name: &'static str, filter: EnvFilter) -> Telemetry {
+    let provider = if otlp_configured() {
+        match tracer_provider(service_name) {
+            Ok(provider) => Some(provider),
+            Err(err) => {
+                eprintln!("Not exporting traces: {err}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Log lines stay in the logs, as they can hold message text and tool arguments.
+    let otel = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer(service_name))
+            .with_filter(filter_fn(|metadata| metadata.is_span()))
+    });
+
+    // The SDK reports export failures as warnings.
+    let filter = filter.add_directive("opentelemetry=warn".parse().unwrap());
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(otel)
+        .init();
+
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+    Telemetry { provider }
+}
+
+fn otlp_configured() -> bool {
+    ENDPOINT_VARS.iter().any(|name| {
+        std::env::var(name)
+            .map(|value| !value.trim().is_empty())
+            .unwrap_or(false)
+    })
+}
+
+fn tracer_provider(
+    service_name: &'static str,
+) -> Result<SdkTracerProvider, opentelemetry_otlp::ExporterBuildError> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_protocol(opentelemetry_otlp::Protocol::HttpBinary)
+        .build()?;
+
+    let mut resource = Resource::builder();
+    if std::env::var("OTEL_SERVICE_NAME").is_err() {
+        resource = resource.with_service_name(service_name);
+    }
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource.build())
+        .build())
+}
+
+/// The trace context headers for an outgoing request, so the service it calls can continue
+/// the current trace. Empty when spans aren't being exported.
+pub fn trace_headers() -> Vec<(String, String)> {
+    let context = tracing::Span::current().context();
+    let mut headers = HeaderPairs(Vec::new());
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut headers)
+    });
+    headers.0
+}
+
+/// Continues the caller's trace in `span` when the request came with a `traceparent` header.
+pub fn set_parent_from(span: &tracing::Span, headers: &http::HeaderMap) {
+    let context = opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(headers))
+    });
+    if let Err(err) = span.set_parent(context) {
+        tracing::debug!("Not able to continue the caller's trace: {err}");
+    }
+}
+
+struct HeaderPairs(Vec<(String, String)>);
+
+impl Injector for HeaderPairs {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.push((key.to_string(), value));
+    }
+}
+
+struct HeaderExtractor<'a>(&'a http::HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TraceContextExt;
+
+    #[test]
+    fn trace_context_round_trips_through_headers() {
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let provider = SdkTracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let caller = tracing::info_span!("caller");
+            let _entered = caller.enter();
+            let mut map = http::HeaderMap::new();
+            for (name, value) in trace_headers() {
+                map.insert(
+                    http::HeaderName::try_from(name).unwrap(),
+                    value.parse().unwrap(),
+                );
+            }
+            assert!(map.contains_key("traceparent"));
+
+            let callee = tracing::info_span!("callee");
+            set_parent_from(&callee, &map);
+            assert_eq!(
+                callee.context().span().span_context().trace_id(),
+                caller.context().span().span_context().trace_id()
+            );
+        });
+    }
+
+    #[test]
+    fn nothing_is_sent_without_a_trace() {
+        assert!(trace_headers().is_empty());
+    }
+}
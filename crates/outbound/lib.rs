@@ -0,0 +1,144 @@
+//! Requests to URLs that Bionic doesn't control, such as webhooks and automation
+//! notifications that team members enter, or images a model returns a link to.
+//!
+//! Those URLs mustn't reach the network Bionic runs in, so the client only connects to
+//! public addresses. Hosts are checked when the request is made rather than when the URL
+//! is saved, as a name can resolve to somewhere else later. Redirects aren't followed.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use url::{Host, Url};
+
+/// A client that only connects to public addresses.
+pub fn client(timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .redirect(reqwest::redirect::Policy::none())
+        // A proxy would be the host we check, not the destination.
+        .no_proxy()
+        .dns_resolver(PublicOnly)
+        .build()
+        .unwrap_or_default()
+}
+
+/// Refuses URLs that aren't http(s), or that name a private address directly. Names are
+/// checked by the client's resolver when it connects.
+pub fn check_url(url: &str) -> Result<(), String> {
+    let url = Url::parse(url).map_err(|e| format!("Not a valid URL: {e}"))?;
+    if url.scheme() != "https" && url.scheme() != "http" {
+        return Err("Only http(s) URLs can be called".to_string());
+    }
+    let ip = match url.host() {
+        Some(Host::Ipv4(ip)) => IpAddr::V4(ip),
+        Some(Host::Ipv6(ip)) => IpAddr::V6(ip),
+        Some(Host::Domain(_)) => return Ok(()),
+        None => return Err("The URL has no host".to_string()),
+    };
+    if is_public(ip) {
+        Ok(())
+    } else {
+        Err(format!("{ip} is not a public address"))
+    }
+}
+
+struct PublicOnly;
+
+impl Resolve for PublicOnly {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{host} does not resolve to a public address").into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// False for loopback, private, link-local (which includes cloud metadata services),
+/// shared, multicast and reserved addresses.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // Carrier-grade NAT
+        || (a == 100 && (64..128).contains(&b))
+        // IETF protocol assignments
+        || (a == 192 && b == 0 && ip.octets()[2] == 0)
+        // Benchmarking
+        || (a == 198 && (18..20).contains(&b))
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local, including fd00:ec2::254
+        || (first & 0xfe00) == 0xfc00
+        // Link-local
+        || (first & 0xffc0) == 0xfe80
+        // NAT64, which maps onto IPv4 addresses
+        || (first == 0x64 && ip.segments()[1] == 0xff9b)
+        // Documentation
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn internal_addresses_are_refused() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00:ec2::254",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+        assert!(is_public("93.184.215.14".parse().unwrap()));
+        assert!(is_public(
+            "2606:2800:21f:cb07:6820:80da:af6b:8b2c".parse().unwrap()
+        ));
+    }
+
+    #[test]
+    fn urls_naming_internal_addresses_are_refused() {
+        assert!(check_url("https://hooks.example.com/bionic").is_ok());
+        assert!(check_url("http://169.254.169.254/latest/meta-data/").is_err());
+        assert!(check_url("http://[::1]:8080/").is_err());
+        assert!(check_url("file:///etc/passwd").is_err());
+    }
+}
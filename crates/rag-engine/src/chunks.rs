@@ -1,5 +1,34 @@
+use serde::Serialize;
+use serde_json::Value;
+
 #[derive(Debug, Clone)]
 pub struct ChunkText {
     pub text: String,
     pub page_number: Option<i32>,
+    pub metadata: ChunkMetadata,
+}
+
+/// Stored with the chunk in `rag.chunks.metadata`, so that results can say where they
+/// came from and searches can be narrowed with a JSON filter.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChunkMetadata {
+    /// The headings the chunk is under, outermost first.
+    pub heading_path: Vec<String>,
+    /// What the document engine says the chunk is, such as `Table` or `CompositeElement`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element_type: Option<String>,
+    /// Character offsets of the chunk in the extracted text of its page, or of the whole
+    /// document when it has no pages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_offset: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_offset: Option<usize>,
+    pub is_table: bool,
+    pub is_figure: bool,
+    /// Where a document taken from the web was fetched from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    /// Where the chunk is drawn on the page, as the document engine reported it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coordinates: Option<Value>,
 }
@@ -7,12 +7,26 @@ pub struct Config {
     pub unstructured_endpoint: String,
     pub kreuzberg_endpoint: String,
     pub batch_size: i64,
+    /// How many documents are processed at once by this replica.
+    pub workers: usize,
+    /// Names this replica's leases, so a lease it lost can't be finished by it.
+    pub worker_id: String,
+    /// How long a lease lasts without a heartbeat before another worker takes over.
+    pub lease_secs: i32,
+    /// Documents are failed after their workers stop this many times.
+    pub max_attempts: i32,
+    /// Expired leases don't raise a notification, so the queue is also checked this often.
+    pub poll_interval_secs: u64,
+    /// Serves /healthz and /metrics.
+    pub port: u16,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChunkingEngine {
     UnstructuredApi,
     KreuzbergApi,
+    /// Extracts and chunks in process, without a document service.
+    Native,
 }
 
 impl Default for Config {
@@ -29,6 +43,7 @@ impl Config {
             Ok(value) if value.eq_ignore_ascii_case("UNSTRUCTURED_API") => {
                 ChunkingEngine::UnstructuredApi
             }
+            Ok(value) if value.eq_ignore_ascii_case("NATIVE") => ChunkingEngine::Native,
             _ => ChunkingEngine::KreuzbergApi,
         };
 
@@ -45,12 +60,41 @@ impl Config {
             .and_then(|v| v.parse().ok())
             .unwrap_or(10);
 
+        let workers = env_or("RAG_WORKERS", 1).max(1);
+
+        let worker_id = format!(
+            "{}-{}",
+            env::var("HOSTNAME").unwrap_or_else(|_| "rag-engine".to_string()),
+            std::process::id()
+        );
+
+        let lease_secs = env_or("RAG_LEASE_SECONDS", 300).max(10);
+
+        let max_attempts = env_or("RAG_MAX_ATTEMPTS", 3).max(1);
+
+        let poll_interval_secs = env_or("RAG_POLL_SECONDS", 30).max(1);
+
+        let port = env_or("PORT", 7703);
+
         Config {
             app_database_url,
             chunking_engine,
             unstructured_endpoint,
             kreuzberg_endpoint,
             batch_size,
+            workers,
+            worker_id,
+            lease_secs,
+            max_attempts,
+            poll_interval_secs,
+            port,
         }
     }
 }
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
@@ -0,0 +1,103 @@
+//! `/healthz` for probes and `/metrics` for Prometheus.
+
+use crate::queue::Metrics;
+use axum::extract::Extension;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router};
+use db::{queries, Pool};
+use serde_json::json;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+pub async fn serve(port: u16, pool: Pool, metrics: Arc<Metrics>) {
+    let app = Router::new()
+        .route("/healthz", get(healthz))
+        .route("/metrics", get(metrics_text))
+        .layer(Extension(pool))
+        .layer(Extension(metrics));
+
+    let listener = match tokio::net::TcpListener::bind(("0.0.0.0", port)).await {
+        Ok(listener) => listener,
+        Err(error) => {
+            tracing::error!("Not able to serve /healthz on port {port}: {error}");
+            return;
+        }
+    };
+    tracing::info!("Serving /healthz and /metrics on port {port}");
+    if let Err(error) = axum::serve(listener, app).await {
+        tracing::error!("Health server stopped: {error}");
+    }
+}
+
+/// Healthy while the database answers and at least one worker is running. Losing the
+/// listener only slows pickup to the poll interval, so it is reported but not fatal.
+async fn healthz(
+    Extension(pool): Extension<Pool>,
+    Extension(metrics): Extension<Arc<Metrics>>,
+) -> impl IntoResponse {
+    let database = match pool.get().await {
+        Ok(client) => client.simple_query("SELECT 1").await.is_ok(),
+        Err(_) => false,
+    };
+    let workers = metrics.workers_running.load(Ordering::Relaxed);
+    let listening = metrics.listening.load(Ordering::Relaxed);
+
+    let status = if database && workers > 0 {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(json!({
+            "database": database,
+            "workers": workers,
+            "listening": listening,
+        })),
+    )
+}
+
+async fn metrics_text(
+    Extension(pool): Extension<Pool>,
+    Extension(metrics): Extension<Arc<Metrics>>,
+) -> Result<String, StatusCode> {
+    let client = pool
+        .get()
+        .await
+        .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+    let depth = queries::documents::queue_depth()
+        .bind(&client)
+        .one()
+        .await
+        .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let mut text = String::new();
+    for (name, value) in [
+        ("rag_documents_waiting", depth.documents_waiting),
+        ("rag_documents_in_progress", depth.documents_in_progress),
+        ("rag_chunks_waiting", depth.chunks_waiting),
+        ("rag_chunks_in_progress", depth.chunks_in_progress),
+        ("rag_chats_waiting", depth.chats_waiting),
+    ] {
+        text.push_str(&format!("{name} {value}\n"));
+    }
+    for (name, counter) in [
+        ("rag_documents_chunked_total", &metrics.documents_chunked),
+        ("rag_documents_failed_total", &metrics.documents_failed),
+        ("rag_chunks_embedded_total", &metrics.chunks_embedded),
+        ("rag_chunks_failed_total", &metrics.chunks_failed),
+        ("rag_chats_embedded_total", &metrics.chats_embedded),
+        ("rag_chats_failed_total", &metrics.chats_failed),
+    ] {
+        text.push_str(&format!("{name} {}\n", counter.load(Ordering::Relaxed)));
+    }
+    text.push_str(&format!(
+        "rag_workers_running {}\n",
+        metrics.workers_running.load(Ordering::Relaxed)
+    ));
+
+    Ok(text)
+}
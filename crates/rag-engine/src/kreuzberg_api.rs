@@ -74,12 +74,15 @@ pub async fn document_to_chunks(
     }
 
     let chunker_type = match chunking_strategy {
-        ChunkingStrategy::ByTitle => "markdown",
+        ChunkingStrategy::ByTitle
+        | ChunkingStrategy::MarkdownHeading
+        | ChunkingStrategy::TablePreserving => "markdown",
+        ChunkingStrategy::RecursiveCharacter | ChunkingStrategy::SentenceWindow => "text",
     };
 
     let chunk_url = format!("{}/chunk", kreuzberg_endpoint);
     let chunk_request = ChunkRequest {
-        text: content,
+        text: content.clone(),
         chunker_type: chunker_type.to_string(),
         config: ChunkConfig {
             max_characters: chunk_size,
@@ -95,12 +98,27 @@ pub async fn document_to_chunks(
     }
 
     let chunk_response: ChunkResponse = chunk_response.json().await?;
+    // The chunks are trimmed slices of the content, so they are found by searching on
+    // from where the previous chunk started.
+    let mut searched_to = 0;
     let chunks = chunk_response
         .chunks
         .into_iter()
-        .map(|chunk| ChunkText {
-            text: chunk.content,
-            page_number: chunk.first_page.map(|page| page as i32),
+        .map(|chunk| {
+            let span = content[searched_to..].find(&chunk.content).map(|found| {
+                (
+                    searched_to + found,
+                    searched_to + found + chunk.content.len(),
+                )
+            });
+            if let Some((start, _)) = span {
+                searched_to = start;
+            }
+            ChunkText {
+                metadata: crate::native::text_metadata(&content, &chunk.content, span),
+                text: chunk.content,
+                page_number: chunk.first_page.map(|page| page as i32),
+            }
         })
         .collect();
 
@@ -1,4 +1,5 @@
 //! RAG Engine library for document processing and embedding
 
+pub mod chunks;
 pub mod config;
 pub mod unstructured;
@@ -0,0 +1,491 @@
+//! Splits extracted text into chunks of at most `size` characters, repeating up to
+//! `overlap` characters of one chunk at the start of the next so that context isn't
+//! lost at the boundary.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Where text is split, from the largest unit to the smallest.
+const SEPARATORS: [&str; 5] = ["\n\n", "\n", ". ", " ", ""];
+
+#[derive(Debug, Clone, Copy)]
+pub struct Chunker {
+    size: usize,
+    overlap: usize,
+}
+
+/// A chunk and the byte range of the text it was cut from. Headings and table headers
+/// repeated at the top of a chunk aren't part of the range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A piece of the text being chunked, with the byte range it came from.
+#[derive(Debug, Clone)]
+struct Piece {
+    text: String,
+    start: usize,
+    end: usize,
+}
+
+impl Piece {
+    fn new(text: &str, start: usize) -> Self {
+        Self {
+            text: text.to_string(),
+            start,
+            end: start + text.len(),
+        }
+    }
+}
+
+impl Chunker {
+    pub fn new(size: usize, overlap: usize) -> Self {
+        let size = size.max(1);
+        // An overlap of half a chunk or more would mostly repeat the previous chunk.
+        let overlap = overlap.min(size / 2);
+        Self { size, overlap }
+    }
+
+    /// Splits on paragraphs, then lines, sentences, words and finally characters,
+    /// stopping at the largest unit that fits.
+    pub fn recursive_character(&self, text: &str) -> Vec<Chunk> {
+        finish(self.split(text, 0, &SEPARATORS))
+    }
+
+    /// Windows of whole sentences. Sentences longer than a chunk are split on words.
+    pub fn sentence_window(&self, text: &str) -> Vec<Chunk> {
+        let mut sentences = vec![];
+        for (start, sentence) in text.split_sentence_bound_indices() {
+            if length(sentence) <= self.size {
+                sentences.push(Piece::new(sentence, start));
+            } else {
+                sentences.extend(self.split(sentence, start, &SEPARATORS[3..]));
+            }
+        }
+        finish(self.merge(&sentences))
+    }
+
+    /// A chunk per section, with the headings the section is under at the top of each
+    /// of its chunks. Long sections are split as `recursive_character` would.
+    pub fn markdown_heading(&self, text: &str) -> Vec<Chunk> {
+        let mut chunks = vec![];
+        let mut headings: Vec<(usize, &str)> = vec![];
+        let mut body = String::new();
+        let mut body_start = 0;
+
+        let mut flush = |headings: &[(usize, &str)], body: &mut String, body_start: usize| {
+            if !body.trim().is_empty() {
+                let prefix: String = headings
+                    .iter()
+                    .map(|(_, heading)| format!("{heading}\n"))
+                    .collect();
+                let budget = self.size.saturating_sub(length(&prefix)).max(self.size / 2);
+                let section = Chunker::new(budget, self.overlap);
+                for piece in finish(section.split(body, body_start, &SEPARATORS)) {
+                    chunks.push(Chunk {
+                        text: format!("{prefix}\n{}", piece.text).trim().to_string(),
+                        ..piece
+                    });
+                }
+            }
+            body.clear();
+        };
+
+        let mut next_heading = heading_lines(text).into_iter().peekable();
+        let mut offset = 0;
+        for line in text.split_inclusive('\n') {
+            let line_start = offset;
+            offset += line.len();
+            if let Some((_, level, heading)) =
+                next_heading.next_if(|(start, _, _)| *start == line_start)
+            {
+                flush(&headings, &mut body, body_start);
+                headings.retain(|(parent, _)| *parent < level);
+                headings.push((level, heading));
+                continue;
+            }
+            if body.is_empty() {
+                body_start = line_start;
+            }
+            body.push_str(line);
+        }
+        flush(&headings, &mut body, body_start);
+
+        if chunks.is_empty() {
+            // Nothing but headings, or no text at all.
+            return self.recursive_character(text);
+        }
+        chunks
+    }
+
+    /// Like `recursive_character`, but Markdown and drawn tables are only split between
+    /// rows and each part of a table starts with its header.
+    pub fn table_preserving(&self, text: &str) -> Vec<Chunk> {
+        let mut units = vec![];
+        let mut prose = String::new();
+        let mut prose_start = 0;
+        let mut table: Vec<&str> = vec![];
+        let mut table_start = 0;
+
+        let mut offset = 0;
+        for line in text.split_inclusive('\n') {
+            let line_start = offset;
+            offset += line.len();
+            if is_table_line(line) {
+                if !prose.is_empty() {
+                    units.extend(self.split(&std::mem::take(&mut prose), prose_start, &SEPARATORS));
+                }
+                if table.is_empty() {
+                    table_start = line_start;
+                }
+                table.push(line);
+            } else {
+                if !table.is_empty() {
+                    units.extend(self.table_parts(&std::mem::take(&mut table), table_start));
+                }
+                if prose.is_empty() {
+                    prose_start = line_start;
+                }
+                prose.push_str(line);
+            }
+        }
+        if !prose.is_empty() {
+            units.extend(self.split(&prose, prose_start, &SEPARATORS));
+        }
+        if !table.is_empty() {
+            units.extend(self.table_parts(&table, table_start));
+        }
+
+        // Tables are kept whole, so the pieces are joined without overlap.
+        finish(Chunker::new(self.size, 0).merge(&units))
+            .into_iter()
+            .map(|chunk| Chunk {
+                text: chunk.text.replace("\n\n\n", "\n\n"),
+                ..chunk
+            })
+            .collect()
+    }
+
+    fn table_parts(&self, lines: &[&str], start: usize) -> Vec<Piece> {
+        let table: String = lines.concat();
+        if length(&table) <= self.size {
+            // Blank lines keep the table apart from the text it is joined to.
+            return vec![Piece {
+                text: format!("\n{}\n\n", table.trim_end()),
+                start,
+                end: start + table.trim_end().len(),
+            }];
+        }
+
+        let header_lines = if lines.len() > 1 && is_separator_row(lines[1]) {
+            2
+        } else {
+            1
+        };
+        let header: String = lines[..header_lines].concat();
+        let mut parts = vec![];
+        let mut part = header.clone();
+        let mut part_start = start;
+        let mut row_start = start + header.len();
+        let mut part_end = row_start;
+        for row in &lines[header_lines..] {
+            if length(&part) + length(row) > self.size && part.len() > header.len() {
+                parts.push(Piece {
+                    text: format!("\n{}\n\n", part.trim_end()),
+                    start: part_start,
+                    end: part_end,
+                });
+                part = header.clone();
+                part_start = row_start;
+            }
+            part.push_str(row);
+            part_end = row_start + row.trim_end().len();
+            row_start += row.len();
+        }
+        if part.len() > header.len() {
+            parts.push(Piece {
+                text: format!("\n{}\n\n", part.trim_end()),
+                start: part_start,
+                end: part_end,
+            });
+        }
+        parts
+    }
+
+    /// Splits on the first separator found in the text, then splits any piece that is
+    /// still too long on the separators after it. Pieces keep their separators, and
+    /// `offset` is where the text starts in the document.
+    fn split(&self, text: &str, offset: usize, separators: &[&str]) -> Vec<Piece> {
+        let Some(index) = separators
+            .iter()
+            .position(|separator| separator.is_empty() || text.contains(separator))
+        else {
+            return vec![Piece::new(text, offset)];
+        };
+        let separator = separators[index];
+        let smaller = &separators[index + 1..];
+
+        let pieces: Vec<&str> = if separator.is_empty() {
+            text.graphemes(true).collect()
+        } else {
+            text.split_inclusive(separator).collect()
+        };
+
+        let mut chunks = vec![];
+        let mut fitting = vec![];
+        let mut start = offset;
+        for piece in pieces {
+            let piece_start = start;
+            start += piece.len();
+            if length(piece) <= self.size {
+                fitting.push(Piece::new(piece, piece_start));
+            } else {
+                chunks.extend(self.merge(&std::mem::take(&mut fitting)));
+                if smaller.is_empty() {
+                    chunks.push(Piece::new(piece, piece_start));
+                } else {
+                    chunks.extend(self.split(piece, piece_start, smaller));
+                }
+            }
+        }
+        chunks.extend(self.merge(&fitting));
+        chunks
+    }
+
+    /// Joins pieces that fit in a chunk. Each new chunk starts with as many of the
+    /// previous chunk's last pieces as fit in the overlap.
+    fn merge(&self, pieces: &[Piece]) -> Vec<Piece> {
+        let mut chunks = vec![];
+        let mut window: std::collections::VecDeque<&Piece> = Default::default();
+        let mut total = 0;
+
+        for piece in pieces {
+            let piece_length = length(&piece.text);
+            if total + piece_length > self.size && !window.is_empty() {
+                chunks.push(join(&window));
+                while total > self.overlap || (total > 0 && total + piece_length > self.size) {
+                    let Some(first) = window.pop_front() else {
+                        break;
+                    };
+                    total -= length(&first.text);
+                }
+            }
+            window.push_back(piece);
+            total += piece_length;
+        }
+        if !window.is_empty() {
+            chunks.push(join(&window));
+        }
+        chunks
+    }
+}
+
+fn join(window: &std::collections::VecDeque<&Piece>) -> Piece {
+    Piece {
+        text: window.iter().map(|piece| piece.text.as_str()).collect(),
+        start: window.front().map_or(0, |piece| piece.start),
+        end: window.back().map_or(0, |piece| piece.end),
+    }
+}
+
+/// The headings `offset` is under, outermost first and without their `#`s.
+pub fn heading_path(text: &str, offset: usize) -> Vec<String> {
+    let mut path: Vec<(usize, &str)> = vec![];
+    for (start, level, heading) in heading_lines(text) {
+        if start > offset {
+            break;
+        }
+        path.retain(|(parent, _)| *parent < level);
+        path.push((level, heading));
+    }
+    path.into_iter()
+        .map(|(_, heading)| heading.trim_start_matches('#').trim().to_string())
+        .collect()
+}
+
+/// Where each Markdown heading starts, its level and its line. Lines in code fences
+/// aren't headings.
+fn heading_lines(text: &str) -> Vec<(usize, usize, &str)> {
+    let mut headings = vec![];
+    let mut fence: Option<&str> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+        } else if trimmed.starts_with("```") {
+            fence = Some("```");
+        } else if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+        } else if let Some(level) = heading_level(trimmed) {
+            headings.push((line_start, level, trimmed.trim_end()));
+        }
+    }
+    headings
+}
+
+fn length(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Trims the chunks and drops empty ones. A chunk that is a slice of the text has its
+/// range trimmed too.
+fn finish(chunks: Vec<Piece>) -> Vec<Chunk> {
+    chunks
+        .into_iter()
+        .filter(|piece| !piece.text.trim().is_empty())
+        .map(|piece| {
+            let text = piece.text.trim();
+            let (mut start, mut end) = (piece.start, piece.end);
+            if piece.text.len() == end - start {
+                start += piece.text.len() - piece.text.trim_start().len();
+                end -= piece.text.len() - piece.text.trim_end().len();
+            }
+            Chunk {
+                text: text.to_string(),
+                start,
+                end,
+            }
+        })
+        .collect()
+}
+
+/// `## Title` is level 2. A `#` without a space after it, like `#hashtag`, isn't a heading.
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let rest = &line[level..];
+    ((1..=6).contains(&level) && (rest.starts_with(' ') || rest.trim().is_empty())).then_some(level)
+}
+
+/// Markdown rows start with `|`. Tables drawn with box characters, as HTML tables are
+/// written, use `│` and `─`.
+pub fn is_table_line(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with('|')
+        || line.contains('│')
+        || line.starts_with(['─', '┌', '├', '└', '┬', '┴', '┼'])
+}
+
+fn is_separator_row(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty()
+        && line
+            .chars()
+            .all(|c| matches!(c, '|' | '-' | ':' | ' ' | '─' | '┼' | '├' | '┤'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(chunks: Vec<Chunk>) -> Vec<String> {
+        chunks.into_iter().map(|chunk| chunk.text).collect()
+    }
+
+    #[test]
+    fn recursive_character_prefers_paragraphs() {
+        let text = "First paragraph is here.\n\nSecond one is a bit longer than that.\n\nThird.";
+        let chunks = texts(Chunker::new(40, 0).recursive_character(text));
+        assert_eq!(
+            chunks,
+            vec![
+                "First paragraph is here.",
+                "Second one is a bit longer than that.",
+                "Third."
+            ]
+        );
+        assert!(chunks.iter().all(|chunk| length(chunk) <= 40));
+    }
+
+    #[test]
+    fn long_words_are_split_on_characters() {
+        let chunks = texts(Chunker::new(4, 0).recursive_character("abcdefghij"));
+        assert_eq!(chunks, vec!["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn sentence_windows_overlap_by_whole_sentences() {
+        let text = "One is first. Two is next. Three follows. Four ends it.";
+        let chunks = texts(Chunker::new(30, 15).sentence_window(text));
+        assert_eq!(
+            chunks,
+            vec![
+                "One is first. Two is next.",
+                "Two is next. Three follows.",
+                "Three follows. Four ends it."
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_sections_carry_their_headings() {
+        let text = "# Guide\nIntro.\n## Install\nRun it.\n```\n# not a heading\n```\n## Use\nAsk.\n# Other\nMore.";
+        let chunks = texts(Chunker::new(200, 0).markdown_heading(text));
+        assert_eq!(
+            chunks,
+            vec![
+                "# Guide\n\nIntro.",
+                "# Guide\n## Install\n\nRun it.\n```\n# not a heading\n```",
+                "# Guide\n## Use\n\nAsk.",
+                "# Other\n\nMore."
+            ]
+        );
+    }
+
+    #[test]
+    fn tables_are_split_between_rows_with_their_header() {
+        let text = "Prices:\n| Item | Price |\n|---|---|\n| Tea | 2 |\n| Cake | 3 |\n| Scone | 4 |\nThanks.";
+        let chunks = texts(Chunker::new(50, 10).table_preserving(text));
+        assert_eq!(
+            chunks,
+            vec![
+                "Prices:\n\n| Item | Price |\n|---|---|\n| Tea | 2 |",
+                "| Item | Price |\n|---|---|\n| Cake | 3 |",
+                "| Item | Price |\n|---|---|\n| Scone | 4 |\n\nThanks."
+            ]
+        );
+    }
+
+    #[test]
+    fn chunks_point_back_at_their_text() {
+        let text = "# Guide\nIntro.\n## Install\nRun it.\n| A | B |\n|---|---|\n| 1 | 2 |\n";
+        for chunks in [
+            Chunker::new(12, 4).recursive_character(text),
+            Chunker::new(12, 4).sentence_window(text),
+        ] {
+            for chunk in chunks {
+                assert_eq!(&text[chunk.start..chunk.end], chunk.text);
+            }
+        }
+
+        let sections = Chunker::new(200, 0).markdown_heading(text);
+        assert_eq!(
+            &text[sections[1].start..sections[1].end],
+            "Run it.\n| A | B |\n|---|---|\n| 1 | 2 |"
+        );
+        assert_eq!(
+            heading_path(text, sections[1].start),
+            vec!["Guide", "Install"]
+        );
+    }
+
+    #[test]
+    fn heading_paths_skip_code_fences() {
+        let text = "# Guide\n```\n# comment\n```\n## Use\nAsk.\n# Other\nMore.";
+        assert_eq!(
+            heading_path(text, text.find("Ask").unwrap()),
+            vec!["Guide", "Use"]
+        );
+        assert_eq!(
+            heading_path(text, text.find("More").unwrap()),
+            vec!["Other"]
+        );
+        assert_eq!(heading_path(text, 0), vec!["Guide"]);
+    }
+}
@@ -0,0 +1,595 @@
+//! Turns documents into text for the native chunkers.
+//!
+//! Structure the chunkers can use is kept as Markdown: headings start with `#` and
+//! tables are written with `|` between the cells, one row per line.
+
+use std::io::{Cursor, Read};
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use zip::ZipArchive;
+
+/// The text of one page, or of the whole document when it doesn't have pages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    pub number: Option<i32>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Markdown,
+    Html,
+    Csv,
+    Tsv,
+    Json,
+    Docx,
+    Xlsx,
+    Pdf,
+}
+
+/// Works out the format from the extension, then from the content.
+pub fn detect(bytes: &[u8], file_name: &str) -> Option<Format> {
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase())
+        .unwrap_or_default();
+    let format = match extension.as_str() {
+        "txt" | "text" | "log" => Some(Format::Text),
+        "md" | "markdown" => Some(Format::Markdown),
+        "html" | "htm" | "xhtml" => Some(Format::Html),
+        "csv" => Some(Format::Csv),
+        "tsv" | "tab" => Some(Format::Tsv),
+        "json" => Some(Format::Json),
+        "docx" => Some(Format::Docx),
+        "xlsx" => Some(Format::Xlsx),
+        "pdf" => Some(Format::Pdf),
+        _ => None,
+    };
+    if format.is_some() {
+        return format;
+    }
+
+    match infer::get(bytes).map(|kind| kind.mime_type()) {
+        Some("application/pdf") => Some(Format::Pdf),
+        Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document") => {
+            Some(Format::Docx)
+        }
+        Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet") => {
+            Some(Format::Xlsx)
+        }
+        Some("text/html") => Some(Format::Html),
+        Some(_) => None,
+        None => std::str::from_utf8(bytes).is_ok().then_some(Format::Text),
+    }
+}
+
+pub fn extract(bytes: &[u8], file_name: &str) -> Result<Vec<Page>, String> {
+    let format = detect(bytes, file_name)
+        .ok_or_else(|| format!("The native chunking engine can't read {file_name}"))?;
+
+    let pages = match format {
+        Format::Text | Format::Markdown => vec![whole(decode(bytes))],
+        Format::Html => vec![whole(
+            html2text::from_read(bytes, 1000).map_err(|e| e.to_string())?,
+        )],
+        Format::Csv => vec![whole(delimited_to_markdown(&decode(bytes), ','))],
+        Format::Tsv => vec![whole(delimited_to_markdown(&decode(bytes), '\t'))],
+        Format::Json => vec![whole(json_to_text(bytes))],
+        Format::Docx => vec![whole(docx_to_markdown(bytes)?)],
+        Format::Xlsx => xlsx_to_markdown(bytes)?,
+        Format::Pdf => pdf_extract::extract_text_from_mem_by_pages(bytes)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .enumerate()
+            .map(|(index, text)| Page {
+                number: Some(index as i32 + 1),
+                text,
+            })
+            .collect(),
+    };
+
+    let pages: Vec<Page> = pages
+        .into_iter()
+        .filter(|page| !page.text.trim().is_empty())
+        .collect();
+    if pages.is_empty() {
+        // Most likely a scanned PDF, which needs OCR from one of the API engines.
+        return Err(format!("{file_name} has no text to extract"));
+    }
+    Ok(pages)
+}
+
+fn whole(text: String) -> Page {
+    Page { number: None, text }
+}
+
+fn decode(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// JSON is pretty printed so that each field is on its own line.
+fn json_to_text(bytes: &[u8]) -> String {
+    serde_json::from_slice::<serde_json::Value>(bytes)
+        .ok()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+        .unwrap_or_else(|| decode(bytes))
+}
+
+/// Reads CSV or TSV, with quoted fields, and writes it as a Markdown table.
+fn delimited_to_markdown(text: &str, delimiter: char) -> String {
+    let mut rows: Vec<Vec<String>> = vec![];
+    let mut row = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            c if c == delimiter && !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows.retain(|row| row.iter().any(|cell| !cell.trim().is_empty()));
+    markdown_table(&rows)
+}
+
+/// The first row is the header.
+fn markdown_table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return String::new();
+    }
+
+    let line = |cells: &[String]| {
+        let cells: Vec<String> = (0..columns)
+            .map(|index| {
+                cells
+                    .get(index)
+                    .map(|cell| {
+                        cell.split_whitespace()
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                            .replace('|', "\\|")
+                    })
+                    .unwrap_or_default()
+            })
+            .collect();
+        format!("| {} |", cells.join(" | "))
+    };
+
+    let mut lines = vec![line(&rows[0]), format!("|{}", "---|".repeat(columns))];
+    lines.extend(rows[1..].iter().map(|row| line(row)));
+    lines.join("\n")
+}
+
+/// The most we'll decompress from one file in a docx or xlsx, so a small upload can't
+/// expand to fill memory.
+const MAX_ZIP_FILE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Excel's limit, column XFD.
+const MAX_COLUMNS: usize = 16_384;
+
+/// `None` when the archive doesn't have the file.
+fn read_zip_file(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    name: &str,
+) -> Result<Option<String>, String> {
+    let Ok(file) = archive.by_name(name) else {
+        return Ok(None);
+    };
+    // The size in the header can't be trusted, so stop reading past the limit.
+    let mut text = String::new();
+    file.take(MAX_ZIP_FILE_BYTES + 1)
+        .read_to_string(&mut text)
+        .map_err(|e| e.to_string())?;
+    if text.len() as u64 > MAX_ZIP_FILE_BYTES {
+        return Err(format!(
+            "{name} is larger than {} MB uncompressed",
+            MAX_ZIP_FILE_BYTES / 1024 / 1024
+        ));
+    }
+    Ok(Some(text))
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+    element
+        .try_get_attribute(name)
+        .ok()
+        .flatten()
+        .map(|attribute| String::from_utf8_lossy(&attribute.value).into_owned())
+}
+
+/// Paragraphs of `word/document.xml`, with heading styles as Markdown headings and
+/// tables as Markdown tables.
+fn docx_to_markdown(bytes: &[u8]) -> Result<String, String> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    let xml = read_zip_file(&mut archive, "word/document.xml")?
+        .ok_or("The document has no word/document.xml")?;
+
+    let mut reader = Reader::from_reader(xml.as_bytes());
+    let mut buf = vec![];
+    let mut blocks: Vec<String> = vec![];
+    let mut paragraph = String::new();
+    let mut heading: Option<usize> = None;
+    let mut list_item = false;
+    let mut in_text = false;
+    // Cells of the outermost table being read. Nested tables are read as text.
+    let mut table: Vec<Vec<String>> = vec![];
+    let mut table_depth = 0;
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|e| e.to_string())?;
+        match &event {
+            Event::Start(element) | Event::Empty(element) => match element.local_name().as_ref() {
+                b"t" => in_text = matches!(event, Event::Start(_)),
+                b"tab" => paragraph.push('\t'),
+                b"br" | b"cr" => paragraph.push('\n'),
+                b"pStyle" => {
+                    heading = attribute(element, "w:val").and_then(|style| {
+                        if style == "Title" {
+                            Some(1)
+                        } else {
+                            style
+                                .strip_prefix("Heading")
+                                .and_then(|level| level.parse::<usize>().ok())
+                                .map(|level| level.clamp(1, 6))
+                        }
+                    })
+                }
+                b"numPr" => list_item = true,
+                b"tbl" => table_depth += 1,
+                b"tr" if table_depth == 1 => table.push(vec![]),
+                b"tc" if table_depth == 1 => {
+                    if let Some(row) = table.last_mut() {
+                        row.push(String::new());
+                    }
+                }
+                _ => {}
+            },
+            Event::Text(text) if in_text => {
+                paragraph.push_str(&text.unescape().map_err(|e| e.to_string())?);
+            }
+            Event::End(element) => match element.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" => {
+                    let text = std::mem::take(&mut paragraph);
+                    let text = text.trim();
+                    if table_depth > 0 {
+                        if let Some(cell) = table.last_mut().and_then(|row| row.last_mut()) {
+                            if !cell.is_empty() && !text.is_empty() {
+                                cell.push(' ');
+                            }
+                            cell.push_str(text);
+                        }
+                    } else if !text.is_empty() {
+                        blocks.push(match (heading, list_item) {
+                            (Some(level), _) => format!("{} {text}", "#".repeat(level)),
+                            (None, true) => format!("- {text}"),
+                            (None, false) => text.to_string(),
+                        });
+                    }
+                    heading = None;
+                    list_item = false;
+                }
+                b"tbl" => {
+                    table_depth -= 1;
+                    if table_depth == 0 {
+                        blocks.push(markdown_table(&std::mem::take(&mut table)));
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(blocks.join("\n\n"))
+}
+
+/// Each worksheet as a page, with the sheet name as a heading over a Markdown table.
+fn xlsx_to_markdown(bytes: &[u8]) -> Result<Vec<Page>, String> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    let shared_strings = read_zip_file(&mut archive, "xl/sharedStrings.xml")?
+        .map(|xml| shared_strings(&xml))
+        .transpose()?
+        .unwrap_or_default();
+    let workbook =
+        read_zip_file(&mut archive, "xl/workbook.xml")?.ok_or("The workbook has no sheets")?;
+    let relationships = read_zip_file(&mut archive, "xl/_rels/workbook.xml.rels")?
+        .ok_or("The workbook has no relationships")?;
+
+    let targets = elements(&relationships, b"Relationship", &["Id", "Target"])?;
+    let mut pages = vec![];
+    for (index, sheet) in elements(&workbook, b"sheet", &["name", "r:id"])?
+        .into_iter()
+        .enumerate()
+    {
+        let [name, id] = [&sheet[0], &sheet[1]];
+        let Some(target) = targets.iter().find(|target| &target[0] == id) else {
+            continue;
+        };
+        let path = match target[1].strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("xl/{}", target[1]),
+        };
+        let Some(xml) = read_zip_file(&mut archive, &path)? else {
+            continue;
+        };
+        let rows = sheet_rows(&xml, &shared_strings)?;
+        if rows.is_empty() {
+            continue;
+        }
+        pages.push(Page {
+            number: Some(index as i32 + 1),
+            text: format!("## {name}\n\n{}", markdown_table(&rows)),
+        });
+    }
+    Ok(pages)
+}
+
+/// The given attributes of every element called `name`.
+fn elements(xml: &str, name: &[u8], attributes: &[&str]) -> Result<Vec<Vec<String>>, String> {
+    let mut reader = Reader::from_reader(xml.as_bytes());
+    let mut buf = vec![];
+    let mut found = vec![];
+    loop {
+        match reader
+            .read_event_into(&mut buf)
+            .map_err(|e| e.to_string())?
+        {
+            Event::Start(element) | Event::Empty(element) if element.name().as_ref() == name => {
+                found.push(
+                    attributes
+                        .iter()
+                        .map(|attribute_name| {
+                            attribute(&element, attribute_name).unwrap_or_default()
+                        })
+                        .collect(),
+                );
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(found)
+}
+
+fn shared_strings(xml: &str) -> Result<Vec<String>, String> {
+    let mut reader = Reader::from_reader(xml.as_bytes());
+    let mut buf = vec![];
+    let mut strings = vec![];
+    let mut in_text = false;
+    loop {
+        match reader
+            .read_event_into(&mut buf)
+            .map_err(|e| e.to_string())?
+        {
+            Event::Start(element) => match element.local_name().as_ref() {
+                b"si" => strings.push(String::new()),
+                b"t" => in_text = true,
+                _ => {}
+            },
+            Event::Text(text) if in_text => {
+                if let Some(string) = strings.last_mut() {
+                    string.push_str(&text.unescape().map_err(|e| e.to_string())?);
+                }
+            }
+            Event::End(element) if element.local_name().as_ref() == b"t" => in_text = false,
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(strings)
+}
+
+fn sheet_rows(xml: &str, shared_strings: &[String]) -> Result<Vec<Vec<String>>, String> {
+    let mut reader = Reader::from_reader(xml.as_bytes());
+    let mut buf = vec![];
+    let mut rows: Vec<Vec<String>> = vec![];
+    // The column and type of the cell being read.
+    let mut cell: Option<(usize, String)> = None;
+    let mut in_value = false;
+    loop {
+        match reader
+            .read_event_into(&mut buf)
+            .map_err(|e| e.to_string())?
+        {
+            Event::Start(element) => match element.local_name().as_ref() {
+                b"row" => rows.push(vec![]),
+                b"c" => {
+                    let column = match attribute(&element, "r") {
+                        Some(reference) => column_index(&reference),
+                        None => rows.last().map(Vec::len),
+                    };
+                    let kind = attribute(&element, "t").unwrap_or_default();
+                    // Cells past the last column Excel allows are skipped.
+                    cell = column
+                        .filter(|column| *column < MAX_COLUMNS)
+                        .map(|column| (column, kind));
+                }
+                b"v" | b"t" => in_value = true,
+                _ => {}
+            },
+            Event::Text(text) if in_value => {
+                if let (Some((column, kind)), Some(row)) = (&cell, rows.last_mut()) {
+                    let text = text.unescape().map_err(|e| e.to_string())?;
+                    let value = match kind.as_str() {
+                        "s" => text
+                            .trim()
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|index| shared_strings.get(index))
+                            .cloned()
+                            .unwrap_or_default(),
+                        "b" if text.trim() == "1" => "TRUE".to_string(),
+                        "b" => "FALSE".to_string(),
+                        _ => text.into_owned(),
+                    };
+                    if row.len() <= *column {
+                        row.resize(column + 1, String::new());
+                    }
+                    row[*column].push_str(&value);
+                }
+            }
+            Event::End(element) => match element.local_name().as_ref() {
+                b"v" | b"t" => in_value = false,
+                b"c" => cell = None,
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    rows.retain(|row| row.iter().any(|cell| !cell.trim().is_empty()));
+    Ok(rows)
+}
+
+/// `C7` is column 2. `None` past the last column Excel allows.
+fn column_index(reference: &str) -> Option<usize> {
+    let mut index = 0;
+    for letter in reference.chars().take_while(char::is_ascii_alphabetic) {
+        index = index * 26 + (letter.to_ascii_uppercase() as usize - 'A' as usize + 1);
+        if index > MAX_COLUMNS {
+            return None;
+        }
+    }
+    Some(index.saturating_sub(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(vec![]));
+        for (name, content) in files {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn csv_becomes_a_markdown_table() {
+        let csv = "name,notes\r\nWidget,\"Red, large\"\nGadget,\"Says \"\"hi\"\"\"\n";
+        assert_eq!(
+            delimited_to_markdown(csv, ','),
+            "| name | notes |\n|---|---|\n| Widget | Red, large |\n| Gadget | Says \"hi\" |"
+        );
+    }
+
+    #[test]
+    fn docx_keeps_headings_lists_and_tables() {
+        let document = r#"<w:document xmlns:w="w"><w:body>
+            <w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Leave</w:t></w:r></w:p>
+            <w:p><w:r><w:t xml:space="preserve">Ask your </w:t></w:r><w:r><w:t>manager &amp; HR.</w:t></w:r></w:p>
+            <w:p><w:pPr><w:numPr/></w:pPr><w:r><w:t>Annual</w:t></w:r></w:p>
+            <w:tbl>
+                <w:tr><w:tc><w:p><w:r><w:t>Type</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Days</w:t></w:r></w:p></w:tc></w:tr>
+                <w:tr><w:tc><w:p><w:r><w:t>Annual</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>25</w:t></w:r></w:p></w:tc></w:tr>
+            </w:tbl>
+        </w:body></w:document>"#;
+        let docx = zip(&[("word/document.xml", document)]);
+
+        let pages = extract(&docx, "policy.docx").unwrap();
+        assert_eq!(
+            pages,
+            vec![Page {
+                number: None,
+                text: "# Leave\n\nAsk your manager & HR.\n\n- Annual\n\n| Type | Days |\n|---|---|\n| Annual | 25 |".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn xlsx_sheets_become_pages() {
+        let xlsx = zip(&[
+            (
+                "xl/workbook.xml",
+                r#"<workbook xmlns:r="r"><sheets><sheet name="Prices" sheetId="1" r:id="rId1"/></sheets></workbook>"#,
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<Relationships><Relationship Id="rId1" Target="worksheets/sheet1.xml"/></Relationships>"#,
+            ),
+            (
+                "xl/sharedStrings.xml",
+                r#"<sst><si><t>Item</t></si><si><t>Price</t></si><si><r><t>Tea</t></r><r><t> pot</t></r></si></sst>"#,
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                r#"<worksheet><sheetData>
+                    <row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" t="s"><v>1</v></c></row>
+                    <row r="2"><c r="A2" t="s"><v>2</v></c><c r="C2"><v>12.5</v></c></row>
+                </sheetData></worksheet>"#,
+            ),
+        ]);
+
+        let pages = extract(&xlsx, "prices.xlsx").unwrap();
+        assert_eq!(
+            pages,
+            vec![Page {
+                number: Some(1),
+                text: "## Prices\n\n| Item | Price |  |\n|---|---|---|\n| Tea pot |  | 12.5 |"
+                    .to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn xlsx_limits_columns_and_file_sizes() {
+        assert_eq!(column_index("XFD1"), Some(MAX_COLUMNS - 1));
+        assert_eq!(column_index("XFE1"), None);
+        assert_eq!(column_index("ZZZZZZZZZZZZZZZZ1"), None);
+
+        let rows = sheet_rows(
+            r#"<sheetData><row r="1"><c r="A1"><v>1</v></c><c r="ZZZZZZZZ1"><v>2</v></c></row></sheetData>"#,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(rows, vec![vec!["1".to_string()]]);
+
+        let large = "a".repeat(MAX_ZIP_FILE_BYTES as usize + 1);
+        let docx = zip(&[("word/document.xml", &large)]);
+        assert!(extract(&docx, "large.docx").is_err());
+    }
+
+    #[test]
+    fn unknown_binary_files_are_rejected() {
+        assert_eq!(detect(b"# Notes", "notes.md"), Some(Format::Markdown));
+        assert_eq!(detect(b"plain words", "README"), Some(Format::Text));
+        assert_eq!(detect(b"%PDF-1.7", "upload"), Some(Format::Pdf));
+        assert!(extract(&[0xFF, 0xD8, 0xFF, 0xE0, 0, 0], "photo.jpg").is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//! The native chunking engine, which extracts and chunks documents inside rag-engine
+//! so that no document service has to be deployed alongside it.
+
+mod chunkers;
+mod extract;
+
+use crate::chunks::{ChunkMetadata, ChunkText};
+use chunkers::{Chunk, Chunker};
+use db::types::ChunkingStrategy;
+use std::error::Error;
+
+/// Each chunk starts with the last tenth of the one before it, so a sentence cut at a
+/// boundary still has its context.
+const OVERLAP_DIVISOR: u32 = 10;
+
+pub async fn document_to_chunks(
+    bytes: Vec<u8>,
+    file_name: &str,
+    chunk_size: u32,
+    chunking_strategy: &ChunkingStrategy,
+) -> Result<Vec<ChunkText>, Box<dyn Error>> {
+    let file_name = file_name.to_string();
+    let chunking_strategy = *chunking_strategy;
+    let chunker = Chunker::new(chunk_size as usize, (chunk_size / OVERLAP_DIVISOR) as usize);
+
+    // Parsing is CPU bound, and a malformed PDF can panic, so it runs on its own thread.
+    let chunks = tokio::task::spawn_blocking(move || {
+        chunk_document(&bytes, &file_name, chunker, chunking_strategy)
+    })
+    .await
+    .map_err(|e| format!("Extraction stopped: {e}"))??;
+
+    Ok(chunks)
+}
+
+fn chunk_document(
+    bytes: &[u8],
+    file_name: &str,
+    chunker: Chunker,
+    chunking_strategy: ChunkingStrategy,
+) -> Result<Vec<ChunkText>, String> {
+    let mut chunks = vec![];
+    for page in extract::extract(bytes, file_name)? {
+        let page_chunks = match chunking_strategy {
+            ChunkingStrategy::RecursiveCharacter => chunker.recursive_character(&page.text),
+            ChunkingStrategy::SentenceWindow => chunker.sentence_window(&page.text),
+            // Titles are headings once a document has been extracted as Markdown.
+            ChunkingStrategy::ByTitle | ChunkingStrategy::MarkdownHeading => {
+                chunker.markdown_heading(&page.text)
+            }
+            ChunkingStrategy::TablePreserving => chunker.table_preserving(&page.text),
+        };
+        chunks.extend(page_chunks.into_iter().map(|Chunk { text, start, end }| {
+            let metadata = text_metadata(&page.text, &text, Some((start, end)));
+            ChunkText {
+                text,
+                page_number: page.number,
+                metadata,
+            }
+        }));
+    }
+    Ok(chunks)
+}
+
+/// Metadata for a chunk of Markdown or plain text, `span` being the byte range of
+/// `source` the chunk was cut from when that is known.
+pub(crate) fn text_metadata(
+    source: &str,
+    text: &str,
+    span: Option<(usize, usize)>,
+) -> ChunkMetadata {
+    let is_table = text.lines().any(chunkers::is_table_line);
+    let is_figure = text.contains("![");
+    let element_type = if is_table {
+        "Table"
+    } else if is_figure {
+        "Figure"
+    } else {
+        "Text"
+    };
+    let characters = |byte: usize| source[..byte.min(source.len())].chars().count();
+
+    ChunkMetadata {
+        heading_path: span
+            .map(|(start, _)| chunkers::heading_path(source, start))
+            .unwrap_or_default(),
+        element_type: Some(element_type.to_string()),
+        start_offset: span.map(|(start, _)| characters(start)),
+        end_offset: span.map(|(_, end)| characters(end)),
+        is_table,
+        is_figure,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_carry_their_place_in_the_document() {
+        let markdown =
+            "# Menu\n## Drinks\n| Item | Price |\n|---|---|\n| Tea | 2 |\n## Café\nOpen daily.";
+        let chunks = chunk_document(
+            markdown.as_bytes(),
+            "menu.md",
+            Chunker::new(200, 0),
+            ChunkingStrategy::MarkdownHeading,
+        )
+        .unwrap();
+
+        let drinks = &chunks[0].metadata;
+        assert_eq!(drinks.heading_path, vec!["Menu", "Drinks"]);
+        assert!(drinks.is_table);
+        assert_eq!(drinks.element_type.as_deref(), Some("Table"));
+
+        let cafe = &chunks[1].metadata;
+        assert_eq!(cafe.heading_path, vec!["Menu", "Café"]);
+        assert!(!cafe.is_table);
+        let start = cafe.start_offset.unwrap();
+        let end = cafe.end_offset.unwrap();
+        let text: String = markdown.chars().skip(start).take(end - start).collect();
+        assert_eq!(text, "Open daily.");
+    }
+}
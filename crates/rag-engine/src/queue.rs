@@ -0,0 +1,72 @@
+//! Wakes the workers when documents, chunks or conversation turns are queued, and counts
+//! what they do.
+
+use futures::{stream, StreamExt};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Notify};
+use tokio_postgres::{AsyncMessage, NoTls};
+
+/// Raised by the triggers on `rag.documents`, `rag.chunks` and `llm.chats`.
+const CHANNEL: &str = "rag_ingestion";
+
+/// Counters for /metrics and /healthz.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    pub documents_chunked: AtomicU64,
+    pub documents_failed: AtomicU64,
+    pub chunks_embedded: AtomicU64,
+    pub chunks_failed: AtomicU64,
+    pub chats_embedded: AtomicU64,
+    pub chats_failed: AtomicU64,
+    pub workers_running: AtomicUsize,
+    pub listening: AtomicBool,
+}
+
+/// Listens for notifications on its own connection, as pooled connections drop them,
+/// and reconnects when the connection is lost.
+pub async fn listen(database_url: String, wake: Arc<Notify>, metrics: Arc<Metrics>) {
+    loop {
+        if let Err(error) = listen_once(&database_url, &wake, &metrics).await {
+            tracing::warn!("Lost the {CHANNEL} listener: {error}");
+        }
+        metrics.listening.store(false, Ordering::Relaxed);
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
+
+async fn listen_once(
+    database_url: &str,
+    wake: &Notify,
+    metrics: &Metrics,
+) -> Result<(), tokio_postgres::Error> {
+    let (client, mut connection) = tokio_postgres::connect(database_url, NoTls).await?;
+
+    // The connection has to be polled for the client's queries to complete, so it runs
+    // on its own task and passes the notifications back.
+    let (notifications, mut received) = mpsc::unbounded_channel();
+    let connection = tokio::spawn(async move {
+        let mut messages = stream::poll_fn(move |cx| connection.poll_message(cx));
+        while let Some(message) = messages.next().await {
+            if let AsyncMessage::Notification(_) = message? {
+                let _ = notifications.send(());
+            }
+        }
+        Ok::<(), tokio_postgres::Error>(())
+    });
+
+    client.batch_execute(&format!("LISTEN {CHANNEL}")).await?;
+    metrics.listening.store(true, Ordering::Relaxed);
+    // Anything inserted while we weren't listening.
+    wake.notify_waiters();
+
+    while received.recv().await.is_some() {
+        wake.notify_waiters();
+    }
+
+    match connection.await {
+        Ok(result) => result,
+        Err(_) => Ok(()),
+    }
+}
@@ -0,0 +1,55 @@
+use db::Model;
+use std::error::Error;
+
+/// Returns the mime type of an audio document, sniffing the bytes before
+/// falling back to the file extension.
+pub fn audio_mime_type(bytes: &[u8], file_name: &str) -> Option<String> {
+    let detected_mime = infer::get(bytes).map(|kind| kind.mime_type().to_string());
+    let guessed_mime = mime_guess::from_path(file_name)
+        .first()
+        .map(|mime| mime.essence_str().to_string());
+
+    detected_mime
+        .into_iter()
+        .chain(guessed_mime)
+        .find(|mime| mime.starts_with("audio/") || mime == "video/webm")
+}
+
+/// Converts audio into text with the speech to text model.
+pub async fn transcribe(
+    bytes: Vec<u8>,
+    file_name: &str,
+    mime_type: &str,
+    model: Option<&Model>,
+) -> Result<String, Box<dyn Error>> {
+    let model = model.ok_or("no speech to text model is configured")?;
+    let text = speech_to_text::transcribe(model, file_name, mime_type, bytes).await?;
+    if text.is_empty() {
+        return Err("transcription returned empty text".into());
+    }
+
+    Ok(text)
+}
+
+/// The chunking engines receive the transcript as a plain text file.
+pub fn transcript_file_name(file_name: &str) -> String {
+    format!("{file_name}.txt")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audio_mime_type() {
+        assert_eq!(
+            audio_mime_type(b"", "standup.mp3").as_deref(),
+            Some("audio/mpeg")
+        );
+        assert_eq!(
+            audio_mime_type(b"RIFF\0\0\0\0WAVEfmt ", "upload.bin").as_deref(),
+            Some("audio/x-wav")
+        );
+        assert!(audio_mime_type(b"%PDF-1.7", "report.pdf").is_none());
+    }
+}
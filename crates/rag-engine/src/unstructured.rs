@@ -2,6 +2,9 @@ use std::error::Error;
 
 use reqwest::{multipart, Client};
 use serde::Deserialize;
+use serde_json::Value;
+
+use crate::chunks::{ChunkMetadata, ChunkText};
 
 #[derive(Deserialize, Debug)]
 #[allow(dead_code)]
@@ -9,6 +12,11 @@ pub struct MetaData {
     pub filename: String,
     pub filetype: String,
     pub page_number: Option<i32>,
+    /// Set when the document was fetched from the web.
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub coordinates: Option<Value>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -21,6 +29,28 @@ pub struct Unstructured {
     pub text: String,
 }
 
+impl From<Unstructured> for ChunkText {
+    fn from(element: Unstructured) -> Self {
+        let is_table = element.type_of.starts_with("Table");
+        let is_figure = matches!(
+            element.type_of.as_str(),
+            "Image" | "Figure" | "FigureCaption"
+        );
+        ChunkText {
+            text: element.text,
+            page_number: element.metadata.page_number,
+            metadata: ChunkMetadata {
+                element_type: Some(element.type_of),
+                is_table,
+                is_figure,
+                source_url: element.metadata.url,
+                coordinates: element.metadata.coordinates,
+                ..Default::default()
+            },
+        }
+    }
+}
+
 /***
  * Additional Parameters:
  *
@@ -0,0 +1,430 @@
+//! A worker leases documents and chunks them, then leases chunks and embeds them, then
+//! embeds conversation turns for history search, until the queue is empty. Leases are taken with `FOR UPDATE SKIP LOCKED`, so any number of
+//! workers, in this replica or others, can run at once without doing the same work.
+
+use crate::chunks::ChunkText;
+use crate::config::{ChunkingEngine, Config};
+use crate::queue::Metrics;
+use crate::transcription;
+use db::queries::documents::UnprocessedDocument;
+use db::{queries, ModelType, Pool};
+use object_storage::StorageConfig;
+use std::error::Error;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::field::Empty;
+use tracing::Instrument;
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+pub struct Worker {
+    pub id: String,
+    pub pool: Pool,
+    pub config: Arc<Config>,
+    pub wake: Arc<Notify>,
+    pub metrics: Arc<Metrics>,
+}
+
+/// Counts the worker as running until its task ends, even if it panics.
+struct Running(Arc<Metrics>);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.0.workers_running.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Worker {
+    pub async fn run(self) {
+        self.metrics.workers_running.fetch_add(1, Ordering::Relaxed);
+        let _running = Running(self.metrics.clone());
+
+        loop {
+            // Registered before the queue is drained, so a notification that arrives
+            // while we work isn't missed.
+            let notified = self.wake.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Err(error) = self.drain().await {
+                tracing::error!("Worker {} stopped draining the queue: {}", self.id, error);
+            }
+
+            // Expired leases don't raise a notification, so the queue is polled as well.
+            tokio::select! {
+                _ = notified => {}
+                _ = tokio::time::sleep(Duration::from_secs(self.config.poll_interval_secs)) => {}
+            }
+        }
+    }
+
+    async fn drain(&self) -> Result<(), BoxError> {
+        let mut client = self.pool.get().await?;
+        // Chunks are encrypted as they are stored and documents decrypted as they are loaded.
+        db::customer_keys::set_session_keys(&client).await?;
+
+        loop {
+            queries::documents::give_up_documents()
+                .bind(&client, &self.config.max_attempts)
+                .await?;
+
+            // One at a time, as only the document being worked on has its lease extended.
+            let Some(document) = queries::documents::claim_documents()
+                .bind(
+                    &client,
+                    &self.id,
+                    &self.config.lease_secs,
+                    &self.config.max_attempts,
+                    &1,
+                )
+                .opt()
+                .await?
+            else {
+                break;
+            };
+
+            let heartbeat = tokio::spawn(heartbeat(
+                self.pool.clone(),
+                self.id.clone(),
+                document.id,
+                self.config.lease_secs,
+            ));
+            let ingest = tracing::info_span!(
+                "rag.ingest_document",
+                otel.status_code = Empty,
+                otel.status_message = Empty,
+                rag.document_id = document.id,
+                rag.dataset_id = document.dataset_id,
+                rag.chunks = Empty,
+            );
+            let chunks = self
+                .chunk_document(&client, &document)
+                .instrument(ingest.clone())
+                .await;
+            heartbeat.abort();
+
+            match chunks? {
+                Ok(chunks) => {
+                    ingest.record("rag.chunks", chunks.len());
+                    self.store_chunks(&mut client, &document, chunks)
+                        .instrument(ingest)
+                        .await?;
+                }
+                Err(error) => {
+                    ingest.record("otel.status_code", "ERROR");
+                    ingest.record("otel.status_message", error.as_str());
+                    queries::documents::fail_document()
+                        .bind(&client, &error, &document.id)
+                        .await?;
+                    self.metrics
+                        .documents_failed
+                        .fetch_add(1, Ordering::Relaxed);
+                    tracing::error!(error);
+                }
+            }
+        }
+
+        loop {
+            let claimed = queries::chunks::claim_chunks()
+                .bind(&client, &self.config.lease_secs, &self.config.batch_size)
+                .all()
+                .await?;
+
+            if claimed.is_empty() {
+                break;
+            }
+
+            for embedding in claimed {
+                match embeddings::get_embeddings_via_rig(
+                    &embedding.text,
+                    &embedding.base_url,
+                    &embedding.model,
+                    embedding.context_size,
+                    embedding.api_key.as_deref(),
+                )
+                .instrument(tracing::info_span!(
+                    "rag.embed_chunk",
+                    rag.chunk_id = embedding.id
+                ))
+                .await
+                {
+                    Ok(embeddings) => {
+                        let embedding_data = pgvector::Vector::from(embeddings);
+                        client
+                            .execute(
+                                "
+                                UPDATE rag.chunks SET (processed, embeddings) = (TRUE, $1)
+                                WHERE id = $2
+                                ",
+                                &[&embedding_data, &embedding.id],
+                            )
+                            .await?;
+                        self.metrics.chunks_embedded.fetch_add(1, Ordering::Relaxed);
+                        tracing::info!("Processing embedding id {:?}", embedding.id);
+                    }
+                    Err(error) => {
+                        tracing::error!(
+                            "Failed to process embedding id {:?}: {:?}",
+                            embedding.id,
+                            error
+                        );
+                        client
+                            .execute(
+                                "
+                                UPDATE rag.chunks SET processed = TRUE
+                                WHERE id = $1
+                                ",
+                                &[&embedding.id],
+                            )
+                            .await?;
+                        self.metrics.chunks_failed.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+
+        loop {
+            let claimed = queries::history::claim_chat_embeddings()
+                .bind(&client, &self.config.lease_secs, &self.config.batch_size)
+                .all()
+                .await?;
+
+            if claimed.is_empty() {
+                break;
+            }
+
+            for chat in claimed {
+                match embeddings::get_embeddings_via_rig(
+                    &chat.text,
+                    &chat.base_url,
+                    &chat.model,
+                    chat.context_size,
+                    chat.api_key.as_deref(),
+                )
+                .instrument(tracing::info_span!("rag.embed_chat", rag.chat_id = chat.id))
+                .await
+                {
+                    Ok(embeddings) => {
+                        let embedding_data = pgvector::Vector::from(embeddings);
+                        client
+                            .execute(
+                                "
+                                UPDATE llm.chat_embeddings
+                                SET (processed, embeddings, model_id) = (TRUE, $1, $2)
+                                WHERE chat_id = $3
+                                ",
+                                &[&embedding_data, &chat.model_id, &chat.id],
+                            )
+                            .await?;
+                        self.metrics.chats_embedded.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(error) => {
+                        tracing::error!("Failed to embed chat id {:?}: {:?}", chat.id, error);
+                        // Still searchable by keyword.
+                        client
+                            .execute(
+                                "
+                                UPDATE llm.chat_embeddings SET processed = TRUE
+                                WHERE chat_id = $1
+                                ",
+                                &[&chat.id],
+                            )
+                            .await?;
+                        self.metrics.chats_failed.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stores a document's chunks, unless another worker has taken the document over.
+    async fn store_chunks(
+        &self,
+        client: &mut db::Client,
+        document: &UnprocessedDocument,
+        chunks: Vec<ChunkText>,
+    ) -> Result<(), BoxError> {
+        let transaction = client.transaction().await?;
+        for text in chunks {
+            let metadata = serde_json::to_value(&text.metadata)?;
+            transaction
+                .execute(
+                    "
+                    INSERT INTO rag.chunks (
+                        document_id,
+                        page_number,
+                        text,
+                        metadata
+                    )
+                    VALUES
+                        ($1, $2, encrypt_text($3), $4)",
+                    &[
+                        &document.id,
+                        &text.page_number.unwrap_or(0),
+                        &text.text,
+                        &metadata,
+                    ],
+                )
+                .await?;
+        }
+        let finished = queries::documents::finish_document()
+            .bind(&transaction, &document.id, &self.id)
+            .await?;
+        if finished == 1 {
+            transaction.commit().await?;
+            self.metrics
+                .documents_chunked
+                .fetch_add(1, Ordering::Relaxed);
+        } else {
+            // Another worker took over after our lease ran out, and its
+            // chunks are the ones kept.
+            transaction.rollback().await?;
+            tracing::warn!(
+                "Worker {} lost the lease on document {}",
+                self.id,
+                document.id
+            );
+        }
+        Ok(())
+    }
+
+    /// The document's chunks, or why it can't be chunked. The outer error is for
+    /// problems that aren't the document's fault, such as losing the database.
+    async fn chunk_document(
+        &self,
+        client: &tokio_postgres::Client,
+        document: &UnprocessedDocument,
+    ) -> Result<Result<Vec<ChunkText>, String>, BoxError> {
+        let dataset = queries::datasets::pipeline_dataset()
+            .bind(client, &document.dataset_id)
+            .one()
+            .await?;
+
+        let storage_config = StorageConfig::database(self.pool.clone());
+        let bytes = match load_document_bytes(&storage_config, document)
+            .instrument(tracing::info_span!("rag.load_document"))
+            .await
+        {
+            Ok(bytes) => bytes,
+            Err(error) => return Ok(Err(format!("Not able to load document bytes: {}", error))),
+        };
+
+        // Audio is transcribed first so recordings are chunked like any text document.
+        let (bytes, file_name) = match transcription::audio_mime_type(&bytes, &document.file_name) {
+            Some(mime_type) => {
+                let model = queries::models::models()
+                    .bind(client, &ModelType::SpeechToText)
+                    .all()
+                    .await?
+                    .into_iter()
+                    .next();
+                match transcription::transcribe(
+                    bytes,
+                    &document.file_name,
+                    &mime_type,
+                    model.as_ref(),
+                )
+                .instrument(tracing::info_span!("rag.transcribe"))
+                .await
+                .map_err(|error| error.to_string())
+                {
+                    Ok(text) => (
+                        text.into_bytes(),
+                        transcription::transcript_file_name(&document.file_name),
+                    ),
+                    Err(error) => {
+                        return Ok(Err(format!("Not able to transcribe audio: {}", error)))
+                    }
+                }
+            }
+            None => (bytes, document.file_name.clone()),
+        };
+
+        let chunk_span = tracing::info_span!(
+            "rag.chunk",
+            rag.chunking_engine = ?self.config.chunking_engine
+        );
+        let structured_data = match self.config.chunking_engine {
+            ChunkingEngine::UnstructuredApi => crate::unstructured::document_to_chunks(
+                bytes,
+                &file_name,
+                dataset.combine_under_n_chars as u32,
+                dataset.new_after_n_chars as u32,
+                dataset.multipage_sections,
+                &self.config.unstructured_endpoint,
+            )
+            .instrument(chunk_span)
+            .await
+            .map(|chunks| chunks.into_iter().map(ChunkText::from).collect()),
+            ChunkingEngine::KreuzbergApi => {
+                crate::kreuzberg_api::document_to_chunks(
+                    bytes,
+                    &file_name,
+                    dataset.new_after_n_chars as u32,
+                    dataset.combine_under_n_chars as u32,
+                    &dataset.chunking_strategy,
+                    &self.config.kreuzberg_endpoint,
+                )
+                .instrument(chunk_span)
+                .await
+            }
+            ChunkingEngine::Native => {
+                crate::native::document_to_chunks(
+                    bytes,
+                    &file_name,
+                    dataset.new_after_n_chars as u32,
+                    &dataset.chunking_strategy,
+                )
+                .instrument(chunk_span)
+                .await
+            }
+        };
+
+        Ok(structured_data.map_err(|error| format!("Not able to parse document {}", error)))
+    }
+}
+
+/// Extends the lease on a document every third of the lease until it is aborted.
+async fn heartbeat(pool: Pool, worker: String, document_id: i32, lease_secs: i32) {
+    let every = Duration::from_secs((lease_secs / 3).max(1) as u64);
+    loop {
+        tokio::time::sleep(every).await;
+        let extended = match pool.get().await {
+            Ok(client) => queries::documents::extend_document_lease()
+                .bind(&client, &lease_secs, &document_id, &worker)
+                .await
+                .map_err(|error| error.to_string()),
+            Err(error) => Err(error.to_string()),
+        };
+        match extended {
+            Ok(0) => {
+                tracing::warn!("Worker {worker} no longer holds document {document_id}");
+                return;
+            }
+            Ok(_) => {}
+            Err(error) => tracing::warn!("Not able to extend the lease on {document_id}: {error}"),
+        }
+    }
+}
+
+async fn load_document_bytes(
+    storage_config: &StorageConfig,
+    document: &UnprocessedDocument,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    if let Some(content) = &document.content {
+        return Ok(content.clone());
+    }
+
+    if let Some(object_id) = document.object_id {
+        let object = object_storage::get(storage_config, object_id).await?;
+        if let Some(bytes) = object.object_data {
+            return Ok(bytes);
+        }
+    }
+
+    Err("document bytes missing".into())
+}
@@ -0,0 +1,192 @@
+//! The client for OpenAI compatible `/audio/transcriptions` endpoints, shared by the web
+//! server, which transcribes recordings and audio attachments, and rag-engine, which
+//! transcribes audio documents.
+
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use db::Model;
+use reqwest::header::AUTHORIZATION;
+use reqwest::multipart::{Form, Part};
+use serde::Deserialize;
+
+/// Keep uploads in line with the OpenAI transcription API limit.
+pub const MAX_AUDIO_BYTES: usize = 25 * 1024 * 1024;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A long recording can take the model a few minutes to transcribe.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(600);
+
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+#[derive(Debug)]
+pub enum TranscriptionError {
+    TooLarge,
+    Request(String),
+}
+
+impl fmt::Display for TranscriptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranscriptionError::TooLarge => write!(
+                f,
+                "Audio files are limited to {} MB",
+                MAX_AUDIO_BYTES / 1024 / 1024
+            ),
+            TranscriptionError::Request(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for TranscriptionError {}
+
+#[derive(Deserialize)]
+struct TranscriptionResponse {
+    text: String,
+}
+
+/// Builds the request, for callers that pass the model's response straight on.
+pub fn request(
+    model: &Model,
+    file_name: &str,
+    mime_type: &str,
+    bytes: Vec<u8>,
+    language: Option<String>,
+) -> Result<reqwest::RequestBuilder, TranscriptionError> {
+    endpoint_request(
+        &model.name,
+        &model.base_url,
+        model.api_key.as_deref(),
+        file_name,
+        mime_type,
+        bytes,
+        language,
+    )
+}
+
+fn endpoint_request(
+    name: &str,
+    base_url: &str,
+    api_key: Option<&str>,
+    file_name: &str,
+    mime_type: &str,
+    bytes: Vec<u8>,
+    language: Option<String>,
+) -> Result<reqwest::RequestBuilder, TranscriptionError> {
+    if bytes.len() > MAX_AUDIO_BYTES {
+        return Err(TranscriptionError::TooLarge);
+    }
+
+    let part = Part::bytes(bytes)
+        .file_name(file_name.to_string())
+        .mime_str(mime_type)
+        .map_err(|e| TranscriptionError::Request(e.to_string()))?;
+    let mut form = Form::new()
+        .text("model", name.to_string())
+        .text("response_format", "json")
+        .part("file", part);
+    if let Some(language) = language {
+        form = form.text("language", language);
+    }
+
+    let mut request = client()
+        .post(format!(
+            "{}/audio/transcriptions",
+            base_url.trim_end_matches('/')
+        ))
+        .multipart(form);
+    if let Some(api_key) = api_key.filter(|key| !key.is_empty()) {
+        request = request.header(AUTHORIZATION, format!("Bearer {}", api_key));
+    }
+    Ok(request)
+}
+
+fn client() -> &'static reqwest::Client {
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+/// Sends the audio to the model and returns the text.
+pub async fn transcribe(
+    model: &Model,
+    file_name: &str,
+    mime_type: &str,
+    bytes: Vec<u8>,
+) -> Result<String, TranscriptionError> {
+    let response = request(model, file_name, mime_type, bytes, None)?
+        .send()
+        .await
+        .map_err(|e| TranscriptionError::Request(format!("transcription request failed: {e}")))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(TranscriptionError::Request(format!(
+            "transcription failed with HTTP {status}: {}",
+            body.chars().take(300).collect::<String>()
+        )));
+    }
+
+    let transcription: TranscriptionResponse = response
+        .json()
+        .await
+        .map_err(|e| TranscriptionError::Request(format!("invalid transcription response: {e}")))?;
+
+    Ok(transcription.text.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::CONTENT_TYPE;
+
+    fn build(
+        api_key: Option<&str>,
+        bytes: Vec<u8>,
+    ) -> Result<reqwest::Request, TranscriptionError> {
+        endpoint_request(
+            "whisper-1",
+            "http://models.test/v1/",
+            api_key,
+            "meeting.mp3",
+            "audio/mpeg",
+            bytes,
+            Some("en".to_string()),
+        )
+        .map(|request| request.build().unwrap())
+    }
+
+    #[test]
+    fn audio_over_the_limit_is_refused() {
+        assert!(build(None, vec![0; MAX_AUDIO_BYTES]).is_ok());
+        assert!(matches!(
+            build(None, vec![0; MAX_AUDIO_BYTES + 1]),
+            Err(TranscriptionError::TooLarge)
+        ));
+    }
+
+    #[test]
+    fn request_is_a_multipart_post_to_the_transcriptions_endpoint() {
+        let request = build(Some("sk-test"), vec![1, 2, 3]).unwrap();
+        assert_eq!(request.method(), reqwest::Method::POST);
+        assert_eq!(
+            request.url().as_str(),
+            "http://models.test/v1/audio/transcriptions"
+        );
+        assert_eq!(request.headers()[AUTHORIZATION], "Bearer sk-test");
+        assert!(request.headers()[CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("multipart/form-data; boundary="));
+
+        let request = build(Some(""), vec![1, 2, 3]).unwrap();
+        assert!(request.headers().get(AUTHORIZATION).is_none());
+    }
+}
@@ -0,0 +1,356 @@
+//! Human-in-the-loop approval for integration calls with side effects.
+//!
+//! Every OpenAPI operation resolves to a policy: an override for the operation, then one
+//! for the whole integration, and otherwise GET is allowed while every other method needs
+//! confirmation. A call that needs confirmation is stored with the exact request and the
+//! Python call raises instead of sending it. When the user decides, the call is replayed
+//! (or dropped) and the outcome is added to the tool result the model reads next.
+
+use crate::builtin_tools::monty::RuntimeFunctionRegistry;
+use crate::builtin_tools::openapi_tool_adapter::RequestPreview;
+use db::{queries, ToolCallApprovalStatus, ToolCallPolicy, ToolCallPolicyRow};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// The run_bash result key listing calls that are waiting for the user.
+pub const PENDING_APPROVALS_KEY: &str = "pending_approvals";
+/// The tool result key decided calls are appended to.
+pub const APPROVAL_RESULTS_KEY: &str = "approval_results";
+/// Policies set for a whole integration use this operation id.
+pub const ALL_OPERATIONS: &str = "*";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingApproval {
+    pub approval_id: i32,
+    pub function_name: String,
+    pub method: String,
+    pub url: String,
+}
+
+/// The team's policy overrides, keyed by integration and operation id.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ApprovalPolicies {
+    overrides: HashMap<(i32, String), ToolCallPolicy>,
+}
+
+impl ApprovalPolicies {
+    pub(crate) fn new(rows: Vec<ToolCallPolicyRow>) -> Self {
+        Self {
+            overrides: rows
+                .into_iter()
+                .map(|row| ((row.integration_id, row.operation_id), row.policy))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn resolve(
+        &self,
+        integration_id: Option<i32>,
+        operation_id: &str,
+        method: &str,
+    ) -> ToolCallPolicy {
+        integration_id
+            .and_then(|integration_id| {
+                self.overrides
+                    .get(&(integration_id, operation_id.to_string()))
+                    .or_else(|| {
+                        self.overrides
+                            .get(&(integration_id, ALL_OPERATIONS.to_string()))
+                    })
+            })
+            .copied()
+            .unwrap_or_else(|| default_policy(method))
+    }
+}
+
+/// The policy used when a team hasn't set one.
+pub fn default_policy(method: &str) -> ToolCallPolicy {
+    if matches!(
+        method.to_ascii_uppercase().as_str(),
+        "GET" | "HEAD" | "OPTIONS"
+    ) {
+        ToolCallPolicy::Allow
+    } else {
+        ToolCallPolicy::Confirm
+    }
+}
+
+/// The calls a tool result says are waiting for a decision.
+pub fn pending_approvals(tool_result: &str) -> Vec<PendingApproval> {
+    serde_json::from_str::<Value>(tool_result)
+        .ok()
+        .and_then(|value| value.get(PENDING_APPROVALS_KEY).cloned())
+        .and_then(|pending| serde_json::from_value(pending).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) struct ApprovalRequest<'a> {
+    pub(crate) function_name: &'a str,
+    pub(crate) operation_id: &'a str,
+    pub(crate) integration_id: Option<i32>,
+    pub(crate) preview: &'a RequestPreview,
+    pub(crate) arguments: &'a Value,
+}
+
+/// Stores a call that needs the user's approval.
+pub(crate) async fn record(
+    pool: &db::Pool,
+    sub: &str,
+    conversation_id: i64,
+    request: ApprovalRequest<'_>,
+) -> Result<PendingApproval, String> {
+    let mut client = pool.get().await.map_err(|err| err.to_string())?;
+    let transaction = client.transaction().await.map_err(|err| err.to_string())?;
+    db::authz::set_row_level_security_user_id(&transaction, sub.to_string())
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let approval_id = queries::tool_call_approvals::insert()
+        .bind(
+            &transaction,
+            &request.integration_id,
+            &request.function_name,
+            &request.operation_id,
+            &request.preview.method,
+            &request.preview.url,
+            &request.preview.body,
+            request.arguments,
+            &conversation_id,
+        )
+        .one()
+        .await
+        .map_err(|err| err.to_string())?;
+
+    transaction.commit().await.map_err(|err| err.to_string())?;
+
+    Ok(PendingApproval {
+        approval_id,
+        function_name: request.function_name.to_string(),
+        method: request.preview.method.clone(),
+        url: request.preview.url.clone(),
+    })
+}
+
+/// Records the user's decision, sends the request if it was approved and adds the
+/// outcome to the tool result that made the call. Returns the outcome.
+pub async fn decide(
+    pool: &db::Pool,
+    sub: &str,
+    approval_id: i32,
+    approve: bool,
+) -> Result<Value, String> {
+    let status = if approve {
+        ToolCallApprovalStatus::Approved
+    } else {
+        ToolCallApprovalStatus::Rejected
+    };
+
+    // Claim the approval first so a double submit can't send the request twice.
+    let approval = {
+        let mut client = pool.get().await.map_err(|err| err.to_string())?;
+        let transaction = client.transaction().await.map_err(|err| err.to_string())?;
+        db::authz::set_row_level_security_user_id(&transaction, sub.to_string())
+            .await
+            .map_err(|err| err.to_string())?;
+        let approval = queries::tool_call_approvals::approval()
+            .bind(&transaction, &approval_id)
+            .one()
+            .await
+            .map_err(|err| err.to_string())?;
+        let claimed = queries::tool_call_approvals::decide()
+            .bind(&transaction, &status, &approval_id)
+            .await
+            .map_err(|err| err.to_string())?;
+        if claimed == 0 {
+            return Err("This request has already been decided".to_string());
+        }
+        transaction.commit().await.map_err(|err| err.to_string())?;
+        approval
+    };
+
+    let outcome = if approve {
+        let result = match RuntimeFunctionRegistry::load_for_conversation(
+            pool,
+            sub,
+            approval.conversation_id,
+        )
+        .await
+        {
+            Ok(registry) => {
+                registry
+                    .replay(
+                        &approval.function_name,
+                        &approval.operation_id,
+                        approval.integration_id,
+                        &approval.arguments,
+                    )
+                    .await
+            }
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(response) => json!({
+                "approval_id": approval.id,
+                "function_name": approval.function_name,
+                "status": "approved",
+                "response": response
+            }),
+            Err(err) => json!({
+                "approval_id": approval.id,
+                "function_name": approval.function_name,
+                "status": "approved",
+                "error": err
+            }),
+        }
+    } else {
+        json!({
+            "approval_id": approval.id,
+            "function_name": approval.function_name,
+            "status": "rejected",
+            "message": "The user rejected this request so it was not sent."
+        })
+    };
+
+    let mut client = pool.get().await.map_err(|err| err.to_string())?;
+    let transaction = client.transaction().await.map_err(|err| err.to_string())?;
+    db::authz::set_row_level_security_user_id(&transaction, sub.to_string())
+        .await
+        .map_err(|err| err.to_string())?;
+    queries::tool_call_approvals::set_response()
+        .bind(&transaction, &outcome.to_string(), &approval.id)
+        .await
+        .map_err(|err| err.to_string())?;
+    if let Some(chat_id) = approval.chat_id {
+        let chat = queries::chats::chat()
+            .bind(&transaction, &chat_id)
+            .one()
+            .await
+            .map_err(|err| err.to_string())?;
+        let content = append_outcome(chat.content.as_deref().unwrap_or_default(), &outcome);
+        queries::chats::set_chat_content()
+            .bind(&transaction, &content, &chat_id)
+            .await
+            .map_err(|err| err.to_string())?;
+    }
+    transaction.commit().await.map_err(|err| err.to_string())?;
+
+    Ok(outcome)
+}
+
+/// Links the calls a tool result is waiting on to the chat that stores the result.
+pub async fn attach_to_chat(
+    transaction: &db::Transaction<'_>,
+    chat_id: i32,
+    approvals: &[PendingApproval],
+) -> Result<(), db::TokioPostgresError> {
+    for approval in approvals {
+        queries::tool_call_approvals::attach_to_chat()
+            .bind(transaction, &chat_id, &approval.approval_id)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Adds a decision to a stored tool result, keeping everything else the model saw.
+fn append_outcome(tool_result: &str, outcome: &Value) -> String {
+    let mut value = match serde_json::from_str::<Value>(tool_result) {
+        Ok(Value::Object(object)) => Value::Object(object),
+        _ => json!({ "output": tool_result }),
+    };
+    match value[APPROVAL_RESULTS_KEY].as_array_mut() {
+        Some(results) => results.push(outcome.clone()),
+        None => value[APPROVAL_RESULTS_KEY] = json!([outcome]),
+    }
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_get_methods_need_confirmation_by_default() {
+        let policies = ApprovalPolicies::default();
+
+        assert_eq!(
+            policies.resolve(Some(1), "listEmails", "GET"),
+            ToolCallPolicy::Allow
+        );
+        for method in ["POST", "PUT", "PATCH", "DELETE"] {
+            assert_eq!(
+                policies.resolve(Some(1), "sendEmail", method),
+                ToolCallPolicy::Confirm
+            );
+        }
+    }
+
+    #[test]
+    fn operation_overrides_beat_integration_overrides() {
+        let policies = ApprovalPolicies::new(vec![
+            ToolCallPolicyRow {
+                integration_id: 1,
+                operation_id: ALL_OPERATIONS.to_string(),
+                policy: ToolCallPolicy::Deny,
+            },
+            ToolCallPolicyRow {
+                integration_id: 1,
+                operation_id: "createTicket".to_string(),
+                policy: ToolCallPolicy::Allow,
+            },
+        ]);
+
+        assert_eq!(
+            policies.resolve(Some(1), "createTicket", "POST"),
+            ToolCallPolicy::Allow
+        );
+        assert_eq!(
+            policies.resolve(Some(1), "listTickets", "GET"),
+            ToolCallPolicy::Deny
+        );
+        assert_eq!(
+            policies.resolve(Some(2), "createTicket", "POST"),
+            ToolCallPolicy::Confirm
+        );
+        assert_eq!(
+            policies.resolve(None, "createTicket", "POST"),
+            ToolCallPolicy::Confirm
+        );
+    }
+
+    #[test]
+    fn pending_approvals_are_read_from_tool_results() {
+        let result = json!({
+            "stdout": "",
+            PENDING_APPROVALS_KEY: [{
+                "approval_id": 7,
+                "function_name": "mail_sendemail",
+                "method": "POST",
+                "url": "https://mail.example.com/send"
+            }]
+        });
+
+        let pending = pending_approvals(&result.to_string());
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].approval_id, 7);
+        assert!(pending_approvals("not json").is_empty());
+        assert!(pending_approvals(r#"{"stdout": "ok"}"#).is_empty());
+    }
+
+    #[test]
+    fn outcomes_are_appended_to_tool_results() {
+        let first = append_outcome(r#"{"stdout":"hi"}"#, &json!({"approval_id": 1}));
+        let second = append_outcome(&first, &json!({"approval_id": 2}));
+        let value: Value = serde_json::from_str(&second).unwrap();
+
+        assert_eq!(value["stdout"], "hi");
+        assert_eq!(
+            value[APPROVAL_RESULTS_KEY],
+            json!([{"approval_id": 1}, {"approval_id": 2}])
+        );
+
+        let plain: Value =
+            serde_json::from_str(&append_outcome("text", &json!({"approval_id": 3}))).unwrap();
+        assert_eq!(plain["output"], "text");
+    }
+}
@@ -1,14 +1,15 @@
+use crate::builtin_tools::approvals;
+use crate::builtin_tools::lazy_fs::{AttachmentSource, DatasetSource, LazyFs};
+use crate::builtin_tools::result_budget;
+use crate::builtin_tools::workspace;
 use crate::skills;
 use crate::types::ToolDefinition;
 use bashkit::{
     async_trait, Bash, Builtin, BuiltinContext, ExecResult, ExecutionLimits, FileSystem, FileType,
-    InMemoryFs, PythonLimits,
+    InMemoryFs, MountableFs, PythonLimits,
 };
 use db::{queries, Pool, Transaction};
 use object_storage::StorageConfig;
-use rig::client::EmbeddingsClient;
-use rig::embeddings::EmbeddingModel;
-use rig::providers::{ollama, openai};
 use rig::tool::{ToolDyn, ToolError};
 use rig::wasm_compat::WasmBoxedFuture;
 use serde::{Deserialize, Serialize};
@@ -16,18 +17,19 @@ use serde_json::{json, Value};
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
+use tracing::field::Empty;
+use tracing::Instrument;
 
 const DEFAULT_TIMEOUT_MS: u64 = 5_000;
 const MAX_TIMEOUT_MS: u64 = 30_000;
 const MAX_COMMANDS: usize = 1_000;
 const MAX_STDOUT_BYTES: usize = 2 * 1024 * 1024;
 const MAX_STDERR_BYTES: usize = 512 * 1024;
-const CHUNKS_PER_DOCUMENT_LIMIT: i64 = 1_000;
-const HOME_DIR: &str = "/home/user";
+pub(super) const HOME_DIR: &str = "/home/user";
 const SKILLS_DIR: &str = "/home/user/skills";
 const FUNCTIONS_DIR: &str = "/home/user/functions";
-const DATASETS_DIR: &str = "/home/user/datasets";
-const ATTACHMENTS_DIR: &str = "/home/user/attachments";
+pub(super) const DATASETS_DIR: &str = "/home/user/datasets";
+pub(super) const ATTACHMENTS_DIR: &str = "/home/user/attachments";
 const OUTPUT_DIR: &str = "/home/user/output";
 const MAX_OUTPUT_FILES: usize = 50;
 const MAX_OUTPUT_FILE_BYTES: u64 = 5 * 1024 * 1024;
@@ -38,61 +40,6 @@ struct RunBashArgs {
     timeout_ms: Option<u64>,
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct DatasetManifest {
-    datasets: Vec<DatasetEntry>,
-}
-
-#[derive(Debug, Clone, Serialize)]
-struct DatasetEntry {
-    dataset_id: i32,
-    name: String,
-    path: String,
-}
-
-#[derive(Debug, Clone, Serialize)]
-struct DatasetMetadata {
-    dataset_id: i32,
-    name: String,
-}
-
-#[derive(Debug, Clone, Serialize)]
-struct DatasetFiles {
-    files: Vec<FileEntry>,
-}
-
-#[derive(Debug, Clone, Serialize)]
-struct FileEntry {
-    document_id: i32,
-    name: String,
-    size: i32,
-    chunks: i64,
-    path: String,
-}
-
-#[derive(Debug, Clone, Serialize)]
-struct FileMetadata {
-    document_id: i32,
-    dataset_id: i32,
-    name: String,
-    size: i32,
-    chunks: i64,
-}
-
-#[derive(Debug, Clone, Serialize)]
-struct AttachmentManifest {
-    files: Vec<AttachmentEntry>,
-}
-
-#[derive(Debug, Clone, Serialize)]
-struct AttachmentEntry {
-    file_id: i32,
-    name: String,
-    path: String,
-    mime_type: String,
-    size: i64,
-}
-
 #[derive(Debug, Clone, Serialize)]
 struct OutputEntry {
     id: i32,
@@ -164,7 +111,7 @@ impl ToolDyn for BashkitTool {
 pub fn get_tool_definition() -> ToolDefinition {
     ToolDefinition {
         name: "run_bash".to_string(),
-        description: "Run shell commands in Bashkit, an in-process sandboxed bash runtime with a virtual filesystem. Use /home/user/attachments to inspect uploaded chat files, /home/user/skills to read available skill instructions, and /home/user/datasets to inspect assistant datasets. To use an integration, list /home/user/functions, then cat the relevant .md file; it contains the exact function names, parameters, and usage examples. Use python3 for dependency-free Python through Monty inside Bashkit. Use /home/user/output for generated files that should persist across tool calls and appear in the chat. Use rag-search 'query' to find relevant chunks and rag-read /home/user/datasets/.../chunks/<id>.txt to read a chunk. The filesystem is fresh for each call except /home/user/output, network access is disabled, and host files are not mounted.".to_string(),
+        description: "Run shell commands in Bashkit, an in-process sandboxed bash runtime with a virtual filesystem. Use /home/user/attachments to inspect uploaded chat files, /home/user/skills to read available skill instructions, and /home/user/datasets to inspect assistant datasets. To use an integration, list /home/user/functions, then cat the relevant .md file; it contains the exact function names, parameters, and usage examples. Integration calls that change data may need the user's approval; when one raises PermissionError saying it is waiting for approval, stop and tell the user instead of retrying. Use python3 for dependency-free Python through Monty inside Bashkit. Use /home/user/output for generated files that should persist across tool calls and appear in the chat. Use rag-search 'query' [--filter '{\"is_table\": true}'] to find relevant chunks, narrowed to those whose metadata contains the filter, and rag-read /home/user/datasets/.../chunks/<id>.txt to read a chunk or <id>.json for its metadata. Use generate-image 'prompt' [--size WxH] [--output /home/user/output/<name>.png] to create an image with the team's image model; the image is saved to /home/user/output and shown in the chat. Output too long for the model's context is cut to a head and tail preview and saved in full under /home/user/output/tool-results (see stdout_spilled_to). The filesystem is fresh for each call except /home/user/output. When the assistant has a persistent workspace, /home/user/work, environment variables and the working directory also carry over between calls in the conversation, up to a size quota. Network access is disabled and host files are not mounted.".to_string(),
         parameters: json!({
             "type": "object",
             "properties": {
@@ -237,9 +184,11 @@ pub fn preview_vfs_tree(
 |           `-- <document_id>\n\
 |               |-- metadata.json\n\
 |               `-- chunks\n\
-|                   `-- <chunk_id>.txt\n\
+|                   |-- <chunk_id>.txt\n\
+|                   `-- <chunk_id>.json     # heading path, page, table flag\n\
 |-- output                      # persists for this conversation\n\
 |   `-- <generated_file_or_directory>\n\
+|-- work                        # persists when the assistant has a persistent workspace\n\
 |-- functions                   # callable function catalogues\n",
     );
 
@@ -320,7 +269,30 @@ async fn execute_run_bash(
         .unwrap_or(DEFAULT_TIMEOUT_MS)
         .clamp(100, MAX_TIMEOUT_MS);
 
-    let fs: std::sync::Arc<dyn FileSystem> = std::sync::Arc::new(InMemoryFs::new());
+    // Datasets and attachments are mounted lazily so only the paths a command touches are
+    // fetched. Python functions share the same view of the filesystem as bash.
+    let mountable = std::sync::Arc::new(MountableFs::new(std::sync::Arc::new(InMemoryFs::new())));
+    mountable
+        .mount(
+            DATASETS_DIR,
+            std::sync::Arc::new(LazyFs::new(DatasetSource::new(
+                tool.pool.clone(),
+                tool.sub.clone(),
+                tool.prompt_id,
+            ))),
+        )
+        .map_err(|e| json!({"error": "Failed to mount datasets", "details": e.to_string()}))?;
+    mountable
+        .mount(
+            ATTACHMENTS_DIR,
+            std::sync::Arc::new(LazyFs::new(AttachmentSource::new(
+                tool.pool.clone(),
+                tool.sub.clone(),
+                tool.conversation_id,
+            ))),
+        )
+        .map_err(|e| json!({"error": "Failed to mount attachments", "details": e.to_string()}))?;
+    let fs: std::sync::Arc<dyn FileSystem> = mountable;
     let function_registry = std::sync::Arc::new(
         crate::builtin_tools::monty::RuntimeFunctionRegistry::load_for_conversation(
             &tool.pool,
@@ -332,7 +304,8 @@ async fn execute_run_bash(
     );
     let function_catalogue = function_registry.function_catalogue();
     let external_function_names = function_registry.external_function_names();
-    let external_function_handler = function_registry.python_external_handler_with_fs(fs.clone());
+    let external_function_handler =
+        std::sync::Arc::clone(&function_registry).python_external_handler_with_fs(fs.clone());
 
     let started = Instant::now();
     let mut bash = Bash::builder()
@@ -358,6 +331,15 @@ async fn execute_run_bash(
             }),
         )
         .builtin("rag-read", Box::new(RagReadBuiltin))
+        .builtin(
+            "generate-image",
+            Box::new(
+                crate::builtin_tools::image_generation::GenerateImageBuiltin {
+                    pool: tool.pool.clone(),
+                    sub: tool.sub.clone(),
+                },
+            ),
+        )
         .python_with_external_handler(
             PythonLimits::default().max_duration(Duration::from_millis(timeout)),
             external_function_names,
@@ -367,45 +349,97 @@ async fn execute_run_bash(
 
     seed_custom_skills(&tool.pool, &tool.sub, &bash).await?;
     seed_function_catalogue(&bash, function_catalogue).await?;
-    seed_datasets(
-        &tool.pool,
-        &tool.sub,
-        tool.prompt_id,
-        tool.conversation_id,
-        &bash,
-    )
-    .await?;
-    seed_attachments(&tool.pool, &tool.sub, tool.conversation_id, &bash).await?;
     seed_outputs(&tool.pool, &tool.sub, tool.conversation_id, &bash).await?;
 
+    let persistent_workspace = workspace::is_enabled(&tool.pool, &tool.sub, tool.prompt_id).await?;
+    if persistent_workspace {
+        workspace::restore(&tool.pool, &tool.sub, tool.conversation_id, &mut bash)
+            .instrument(tracing::info_span!("bashkit.restore_workspace"))
+            .await?;
+    }
+
+    let exec_span = tracing::info_span!(
+        "bashkit.exec",
+        otel.status_code = Empty,
+        otel.status_message = Empty,
+        bashkit.timeout_ms = timeout,
+        process.exit.code = Empty,
+    );
     let result = tokio::time::timeout(
         Duration::from_millis(timeout),
         bash.exec(&arguments.commands),
     )
+    .instrument(exec_span.clone())
     .await
-    .map_err(|_| json!({"error": "bash execution timed out"}))?
-    .map_err(|err| json!({"error": "bash execution failed", "details": err.to_string()}))?;
+    .map_err(|_| exec_failed(&exec_span, json!({"error": "bash execution timed out"})))?
+    .map_err(|err| {
+        exec_failed(
+            &exec_span,
+            json!({"error": "bash execution failed", "details": err.to_string()}),
+        )
+    })?;
+    exec_span.record("process.exit.code", result.exit_code);
+
+    // Spill oversized output before outputs are persisted so the full text is kept.
+    let token_budget = result_budget::token_budget(&tool.pool, &tool.sub, tool.prompt_id).await?;
+    let stdout =
+        result_budget::cap(bash.fs().as_ref(), "stdout", result.stdout, token_budget).await;
+    let stderr = result_budget::cap(
+        bash.fs().as_ref(),
+        "stderr",
+        result.stderr,
+        token_budget / 4,
+    )
+    .await;
 
-    let output_sync_result =
-        persist_outputs(&tool.pool, &tool.sub, tool.conversation_id, &bash).await;
+    let output_sync_result = persist_outputs(&tool.pool, &tool.sub, tool.conversation_id, &bash)
+        .instrument(tracing::info_span!("bashkit.persist_outputs"))
+        .await;
 
     let mut response = json!({
-        "stdout": result.stdout,
-        "stderr": result.stderr,
+        "stdout": stdout.text,
+        "stderr": stderr.text,
         "exit_code": result.exit_code,
         "duration_ms": started.elapsed().as_millis(),
         "stdout_truncated": result.stdout_truncated,
         "stderr_truncated": result.stderr_truncated
     });
+    stdout.annotate("stdout", &mut response);
+    stderr.annotate("stderr", &mut response);
 
     match output_sync_result {
         Ok(outputs) => response["outputs"] = json!(outputs),
         Err(err) => response["output_error"] = err,
     }
 
+    let pending_approvals = function_registry.take_pending_approvals();
+    if !pending_approvals.is_empty() {
+        response[approvals::PENDING_APPROVALS_KEY] = json!(pending_approvals);
+    }
+
+    if persistent_workspace {
+        match workspace::save(&tool.pool, &tool.sub, tool.conversation_id, &bash)
+            .instrument(tracing::info_span!("bashkit.save_workspace"))
+            .await
+        {
+            Ok(summary) => response["workspace"] = summary,
+            Err(err) => response["workspace_error"] = err,
+        }
+    }
+
     Ok(response)
 }
 
+/// Marks the exec span as failed and passes the error on.
+fn exec_failed(span: &tracing::Span, error: Value) -> Value {
+    span.record("otel.status_code", "ERROR");
+    span.record(
+        "otel.status_message",
+        error["error"].as_str().unwrap_or_default(),
+    );
+    error
+}
+
 async fn seed_custom_skills(pool: &Pool, sub: &str, bash: &Bash) -> Result<(), serde_json::Value> {
     let mut client = pool
         .get()
@@ -467,222 +501,6 @@ async fn seed_function_catalogue(
     Ok(())
 }
 
-async fn seed_datasets(
-    pool: &Pool,
-    sub: &str,
-    prompt_id: i32,
-    _conversation_id: i64,
-    bash: &Bash,
-) -> Result<(), serde_json::Value> {
-    let mut client = pool
-        .get()
-        .await
-        .map_err(|e| json!({"error": "Failed to get DB client", "details": e.to_string()}))?;
-    let transaction = client
-        .transaction()
-        .await
-        .map_err(|e| json!({"error": "Failed to start transaction", "details": e.to_string()}))?;
-
-    db::authz::set_row_level_security_user_id(&transaction, sub.to_string())
-        .await
-        .map_err(|e| json!({"error": "Failed to set RLS", "details": e.to_string()}))?;
-
-    let fs = bash.fs();
-    fs.mkdir(Path::new(DATASETS_DIR), true)
-        .await
-        .map_err(|e| json!({"error": "Failed to seed Bashkit VFS", "details": e.to_string()}))?;
-
-    let datasets = queries::prompts::prompt_datasets()
-        .bind(&transaction, &prompt_id)
-        .all()
-        .await
-        .map_err(|e| json!({"error": "Failed to get datasets", "details": e.to_string()}))?;
-
-    let mut dataset_entries = Vec::new();
-    for dataset in datasets {
-        let dataset_path = format!("{}/{}", DATASETS_DIR, dataset.dataset_id);
-        fs.mkdir(Path::new(&dataset_path), true).await.map_err(
-            |e| json!({"error": "Failed to seed dataset directory", "details": e.to_string()}),
-        )?;
-
-        dataset_entries.push(DatasetEntry {
-            dataset_id: dataset.dataset_id,
-            name: dataset.name.clone(),
-            path: dataset_path.clone(),
-        });
-
-        write_json(
-            fs.as_ref(),
-            &format!("{dataset_path}/metadata.json"),
-            &DatasetMetadata {
-                dataset_id: dataset.dataset_id,
-                name: dataset.name.clone(),
-            },
-        )
-        .await?;
-
-        let documents = dataset_documents(&transaction, prompt_id, dataset.dataset_id).await?;
-
-        let mut file_entries = Vec::new();
-        for document in documents {
-            let file_path = format!("{dataset_path}/files/{}", document.id);
-            let chunks_path = format!("{file_path}/chunks");
-            fs.mkdir(Path::new(&chunks_path), true).await.map_err(
-                |e| json!({"error": "Failed to seed document directory", "details": e.to_string()}),
-            )?;
-
-            file_entries.push(FileEntry {
-                document_id: document.id,
-                name: document.file_name.clone(),
-                size: document.content_size,
-                chunks: document.chunk_count,
-                path: file_path.clone(),
-            });
-
-            write_json(
-                fs.as_ref(),
-                &format!("{file_path}/metadata.json"),
-                &FileMetadata {
-                    document_id: document.id,
-                    dataset_id: dataset.dataset_id,
-                    name: document.file_name,
-                    size: document.content_size,
-                    chunks: document.chunk_count,
-                },
-            )
-            .await?;
-
-            let chunks =
-                document_chunks(&transaction, prompt_id, dataset.dataset_id, document.id).await?;
-
-            for chunk in chunks {
-                let path = format!("{chunks_path}/{}.txt", chunk.id);
-                fs.write_file(Path::new(&path), chunk.text.as_bytes())
-                    .await
-                    .map_err(
-                        |e| json!({"error": "Failed to seed chunk file", "details": e.to_string()}),
-                    )?;
-            }
-        }
-
-        write_json(
-            fs.as_ref(),
-            &format!("{dataset_path}/files.json"),
-            &DatasetFiles {
-                files: file_entries,
-            },
-        )
-        .await?;
-    }
-
-    write_json(
-        fs.as_ref(),
-        &format!("{DATASETS_DIR}/index.json"),
-        &DatasetManifest {
-            datasets: dataset_entries,
-        },
-    )
-    .await?;
-
-    transaction
-        .commit()
-        .await
-        .map_err(|e| json!({"error": "Failed to commit transaction", "details": e.to_string()}))?;
-
-    Ok(())
-}
-
-async fn write_json<T: Serialize + ?Sized>(
-    fs: &dyn FileSystem,
-    path: &str,
-    value: &T,
-) -> Result<(), serde_json::Value> {
-    let bytes = serde_json::to_vec_pretty(value)
-        .map_err(|e| json!({"error": "Failed to serialize VFS JSON", "details": e.to_string()}))?;
-    fs.write_file(Path::new(path), &bytes)
-        .await
-        .map_err(|e| json!({"error": "Failed to write Bashkit VFS file", "details": e.to_string()}))
-}
-
-async fn seed_attachments(
-    pool: &Pool,
-    sub: &str,
-    conversation_id: i64,
-    bash: &Bash,
-) -> Result<(), serde_json::Value> {
-    let mut client = pool
-        .get()
-        .await
-        .map_err(|e| json!({"error": "Failed to get DB client", "details": e.to_string()}))?;
-    let transaction = client
-        .transaction()
-        .await
-        .map_err(|e| json!({"error": "Failed to start transaction", "details": e.to_string()}))?;
-
-    db::authz::set_row_level_security_user_id(&transaction, sub.to_string())
-        .await
-        .map_err(|e| json!({"error": "Failed to set RLS", "details": e.to_string()}))?;
-
-    let fs = bash.fs();
-    fs.mkdir(Path::new(ATTACHMENTS_DIR), true).await.map_err(
-        |e| json!({"error": "Failed to seed attachments directory", "details": e.to_string()}),
-    )?;
-
-    let attachments = queries::attachments::get_by_conversation()
-        .bind(&transaction, &conversation_id)
-        .all()
-        .await
-        .map_err(|e| json!({"error": "Failed to get attachments", "details": e.to_string()}))?;
-
-    let planned_paths = plan_attachment_paths(
-        attachments
-            .iter()
-            .map(|attachment| attachment.file_name.as_str()),
-    );
-    let mut entries = Vec::new();
-
-    for (attachment, path) in attachments.iter().zip(planned_paths) {
-        let data = queries::attachments::get_content()
-            .bind(&transaction, &attachment.id)
-            .one()
-            .await
-            .map_err(|e| {
-                json!({
-                    "error": "Failed to get attachment content",
-                    "details": e.to_string()
-                })
-            })?;
-
-        fs.write_file(Path::new(&path), &data.object_data)
-            .await
-            .map_err(
-                |e| json!({"error": "Failed to seed attachment file", "details": e.to_string()}),
-            )?;
-
-        entries.push(AttachmentEntry {
-            file_id: attachment.id,
-            name: attachment.file_name.clone(),
-            path,
-            mime_type: attachment.mime_type.clone(),
-            size: attachment.file_size,
-        });
-    }
-
-    write_json(
-        fs.as_ref(),
-        &format!("{ATTACHMENTS_DIR}/index.json"),
-        &AttachmentManifest { files: entries },
-    )
-    .await?;
-
-    transaction
-        .commit()
-        .await
-        .map_err(|e| json!({"error": "Failed to commit transaction", "details": e.to_string()}))?;
-
-    Ok(())
-}
-
 async fn seed_outputs(
     pool: &Pool,
     sub: &str,
@@ -953,7 +771,7 @@ async fn upsert_output(
     Ok(id)
 }
 
-async fn conversation_owner_and_team_id(
+pub(super) async fn conversation_owner_and_team_id(
     transaction: &Transaction<'_>,
     conversation_id: i64,
 ) -> Result<(i32, i32), serde_json::Value> {
@@ -967,7 +785,7 @@ async fn conversation_owner_and_team_id(
     Ok((row.get(0), row.get(1)))
 }
 
-async fn write_vfs_file(
+pub(super) async fn write_vfs_file(
     fs: &dyn FileSystem,
     path: &str,
     contents: &[u8],
@@ -1007,12 +825,14 @@ fn output_mime_type(file_name: &str) -> String {
         .collect()
 }
 
-fn db_conversation_id(conversation_id: i64) -> Result<i32, serde_json::Value> {
+pub(super) fn db_conversation_id(conversation_id: i64) -> Result<i32, serde_json::Value> {
     i32::try_from(conversation_id)
         .map_err(|_| json!({"error": "conversation_id is outside the supported range"}))
 }
 
-fn plan_attachment_paths<'a>(file_names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+pub(super) fn plan_attachment_paths<'a>(
+    file_names: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
     let mut used = HashSet::new();
     file_names
         .into_iter()
@@ -1089,93 +909,6 @@ async fn conversation_team_id(
     Ok(row.get(0))
 }
 
-struct SeedDocument {
-    id: i32,
-    file_name: String,
-    content_size: i32,
-    chunk_count: i64,
-}
-
-struct SeedChunk {
-    id: i32,
-    text: String,
-}
-
-async fn dataset_documents(
-    transaction: &Transaction<'_>,
-    prompt_id: i32,
-    dataset_id: i32,
-) -> Result<Vec<SeedDocument>, serde_json::Value> {
-    let rows = transaction
-        .query(
-            "
-            SELECT
-                d.id,
-                d.file_name,
-                d.content_size,
-                (SELECT COUNT(id) FROM rag.chunks WHERE document_id = d.id) AS chunk_count
-            FROM rag.documents d
-            WHERE d.dataset_id = $1
-              AND d.dataset_id IN (
-                  SELECT dataset_id FROM assistants.prompt_dataset WHERE prompt_id = $2
-              )
-            ORDER BY d.updated_at DESC
-            ",
-            &[&dataset_id, &prompt_id],
-        )
-        .await
-        .map_err(|e| json!({"error": "Failed to get dataset files", "details": e.to_string()}))?;
-
-    Ok(rows
-        .into_iter()
-        .map(|row| SeedDocument {
-            id: row.get(0),
-            file_name: row.get(1),
-            content_size: row.get(2),
-            chunk_count: row.get(3),
-        })
-        .collect())
-}
-
-async fn document_chunks(
-    transaction: &Transaction<'_>,
-    prompt_id: i32,
-    dataset_id: i32,
-    document_id: i32,
-) -> Result<Vec<SeedChunk>, serde_json::Value> {
-    let rows = transaction
-        .query(
-            "
-            SELECT c.id, decrypt_text(c.text) AS text
-            FROM rag.chunks c
-            INNER JOIN rag.documents d ON d.id = c.document_id
-            WHERE c.document_id = $1
-              AND d.dataset_id = $2
-              AND d.dataset_id IN (
-                  SELECT dataset_id FROM assistants.prompt_dataset WHERE prompt_id = $3
-              )
-            ORDER BY c.page_number ASC, c.id ASC
-            LIMIT $4
-            ",
-            &[
-                &document_id,
-                &dataset_id,
-                &prompt_id,
-                &CHUNKS_PER_DOCUMENT_LIMIT,
-            ],
-        )
-        .await
-        .map_err(|e| json!({"error": "Failed to get document chunks", "details": e.to_string()}))?;
-
-    Ok(rows
-        .into_iter()
-        .map(|row| SeedChunk {
-            id: row.get(0),
-            text: row.get(1),
-        })
-        .collect())
-}
-
 struct RagReadBuiltin;
 
 #[async_trait]
@@ -1183,14 +916,14 @@ impl Builtin for RagReadBuiltin {
     async fn execute(&self, ctx: BuiltinContext<'_>) -> bashkit::Result<ExecResult> {
         let Some(path) = ctx.args.first() else {
             return Ok(ExecResult::err(
-                "usage: rag-read /home/user/datasets/.../chunks/<id>.txt\n",
+                "usage: rag-read /home/user/datasets/.../chunks/<id>.txt|<id>.json\n",
                 2,
             ));
         };
 
         if parse_chunk_path(path).is_none() {
             return Ok(ExecResult::err(
-                "rag-read only accepts /home/user/datasets/{dataset_id}/files/{document_id}/chunks/{chunk_id}.txt\n",
+                "rag-read only accepts /home/user/datasets/{dataset_id}/files/{document_id}/chunks/{chunk_id}.txt or .json\n",
                 2,
             ));
         }
@@ -1202,7 +935,7 @@ impl Builtin for RagReadBuiltin {
     }
 
     fn llm_hint(&self) -> Option<&'static str> {
-        Some("rag-read PATH: read a dataset chunk file from /home/user/datasets after rag-search returns paths.")
+        Some("rag-read PATH: read a dataset chunk file from /home/user/datasets after rag-search returns paths. The .json file next to each chunk holds its metadata.")
     }
 }
 
@@ -1216,9 +949,15 @@ struct RagSearchBuiltin {
 #[async_trait]
 impl Builtin for RagSearchBuiltin {
     async fn execute(&self, ctx: BuiltinContext<'_>) -> bashkit::Result<ExecResult> {
-        let (query, limit) = parse_rag_search_args(ctx.args);
+        let (query, limit, filter) = match parse_rag_search_args(ctx.args) {
+            Ok(args) => args,
+            Err(err) => return Ok(ExecResult::err(format!("{err}\n"), 2)),
+        };
         if query.trim().is_empty() {
-            return Ok(ExecResult::err("usage: rag-search QUERY [--limit N]\n", 2));
+            return Ok(ExecResult::err(
+                "usage: rag-search QUERY [--limit N] [--filter JSON]\n",
+                2,
+            ));
         }
 
         match execute_rag_search(
@@ -1228,6 +967,7 @@ impl Builtin for RagSearchBuiltin {
             self.prompt_id,
             &query,
             limit,
+            filter.as_ref(),
         )
         .await
         {
@@ -1237,12 +977,13 @@ impl Builtin for RagSearchBuiltin {
     }
 
     fn llm_hint(&self) -> Option<&'static str> {
-        Some("rag-search QUERY [--limit N]: search assistant datasets and return matching chunk paths as JSON.")
+        Some("rag-search QUERY [--limit N] [--filter JSON]: search assistant datasets and return matching chunk paths and metadata as JSON. The filter keeps chunks whose metadata contains it, e.g. '{\"heading_path\": [\"Pricing\"]}'.")
     }
 }
 
-fn parse_rag_search_args(args: &[String]) -> (String, i32) {
+fn parse_rag_search_args(args: &[String]) -> Result<(String, i32, Option<Value>), String> {
     let mut limit = 5;
+    let mut filter = None;
     let mut query_parts = Vec::new();
     let mut iter = args.iter();
     while let Some(arg) = iter.next() {
@@ -1250,11 +991,18 @@ fn parse_rag_search_args(args: &[String]) -> (String, i32) {
             if let Some(value) = iter.next().and_then(|value| value.parse::<i32>().ok()) {
                 limit = value.clamp(1, 20);
             }
+        } else if arg == "--filter" {
+            let value = iter
+                .next()
+                .and_then(|value| serde_json::from_str::<Value>(value).ok())
+                .filter(Value::is_object)
+                .ok_or("rag-search: --filter needs a JSON object")?;
+            filter = Some(value);
         } else {
             query_parts.push(arg.as_str());
         }
     }
-    (query_parts.join(" "), limit)
+    Ok((query_parts.join(" "), limit, filter))
 }
 
 async fn execute_rag_search(
@@ -1264,6 +1012,7 @@ async fn execute_rag_search(
     prompt_id: i32,
     query: &str,
     limit: i32,
+    filter: Option<&Value>,
 ) -> Result<Value, serde_json::Value> {
     let mut client = pool
         .get()
@@ -1278,7 +1027,15 @@ async fn execute_rag_search(
         .await
         .map_err(|e| json!({"error": "Failed to set RLS", "details": e.to_string()}))?;
 
-    let chunks = search_context(&transaction, prompt_id, conversation_id, query, limit).await;
+    let chunks = search_context(
+        &transaction,
+        prompt_id,
+        conversation_id,
+        query,
+        limit,
+        filter,
+    )
+    .await;
 
     if chunks.is_ok() {
         transaction
@@ -1298,6 +1055,7 @@ async fn search_context(
     conversation_id: i64,
     query: &str,
     limit: i32,
+    filter: Option<&Value>,
 ) -> Result<Value, serde_json::Value> {
     let team_id = conversation_team_id(transaction, conversation_id).await?;
     let prompt = queries::prompts::prompt()
@@ -1315,7 +1073,7 @@ async fn search_context(
         _ => return Err(json!({"error": "Prompt missing embeddings configuration"})),
     };
 
-    let embeddings = get_embeddings_via_rig(
+    let embeddings = embeddings::get_embeddings_via_rig(
         query,
         &base_url,
         &model,
@@ -1325,7 +1083,7 @@ async fn search_context(
     .await
     .map_err(|e| json!({"error": "Failed to get embeddings", "details": e}))?;
 
-    let related = db::get_related_context(transaction, prompt_id, limit, embeddings)
+    let related = db::get_related_context(transaction, prompt_id, limit, embeddings, filter)
         .await
         .map_err(|e| json!({"error": "Failed to search context", "details": e.to_string()}))?;
 
@@ -1349,6 +1107,8 @@ async fn search_context(
             chunks.push(json!({
                 "chunk_id": chunk.chunk_id,
                 "path": path.vfs_path(),
+                "page_number": chunk.page_number,
+                "metadata": chunk.metadata,
                 "preview": chunk.chunk_text.chars().take(300).collect::<String>()
             }));
         }
@@ -1412,7 +1172,11 @@ fn parse_chunk_path(path: &str) -> Option<ChunkPath> {
 
     let dataset_id = parts[4].parse().ok()?;
     let document_id = parts[6].parse().ok()?;
-    let chunk_id = parts[8].strip_suffix(".txt")?.parse().ok()?;
+    let chunk_id = parts[8]
+        .strip_suffix(".txt")
+        .or_else(|| parts[8].strip_suffix(".json"))?
+        .parse()
+        .ok()?;
 
     Some(ChunkPath {
         dataset_id,
@@ -1421,68 +1185,6 @@ fn parse_chunk_path(path: &str) -> Option<ChunkPath> {
     })
 }
 
-fn trim_to_context_length(input: &str, context_length: i32) -> String {
-    if input.is_empty() {
-        return String::new();
-    }
-    let effective_context_length = if context_length <= 0 {
-        256
-    } else {
-        context_length
-    };
-    let char_count = input.chars().count() as i32;
-    if char_count <= effective_context_length {
-        return input.to_string();
-    }
-    input
-        .chars()
-        .take(effective_context_length as usize)
-        .collect()
-}
-
-async fn get_embeddings_via_rig(
-    input: &str,
-    api_end_point: &str,
-    model: &str,
-    context_length: i32,
-    api_key: Option<&str>,
-) -> Result<Vec<f32>, String> {
-    let text = String::from_utf8_lossy(input.as_bytes()).to_string();
-    let trimmed_text = trim_to_context_length(&text, context_length);
-
-    let normalized_base_url = api_end_point
-        .strip_suffix("/embeddings")
-        .or_else(|| api_end_point.strip_suffix("/v1/embeddings"))
-        .map(|s| s.trim_end_matches('/').to_string())
-        .unwrap_or_else(|| api_end_point.trim_end_matches('/').to_string());
-
-    let embedding = if let Some(key) = api_key.filter(|k| !k.trim().is_empty()) {
-        let client = openai::Client::builder()
-            .api_key(key)
-            .base_url(&normalized_base_url)
-            .build()
-            .map_err(|e| e.to_string())?;
-        client
-            .embedding_model(model)
-            .embed_text(&trimmed_text)
-            .await
-            .map_err(|e| e.to_string())?
-    } else {
-        let client = ollama::Client::builder()
-            .api_key("")
-            .base_url(&normalized_base_url)
-            .build()
-            .map_err(|e| e.to_string())?;
-        client
-            .embedding_model(model)
-            .embed_text(&trimmed_text)
-            .await
-            .map_err(|e| e.to_string())?
-    };
-
-    Ok(embedding.vec.into_iter().map(|v| v as f32).collect())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1493,6 +1195,7 @@ mod tests {
         assert_eq!(tool.name, "run_bash");
         assert!(tool.description.contains("/home/user/attachments"));
         assert!(tool.description.contains("/home/user/functions"));
+        assert!(tool.description.contains("generate-image"));
         assert!(tool.description.contains("cat the relevant .md file"));
         assert!(tool
             .description
@@ -1603,9 +1306,29 @@ mod tests {
             "--limit".to_string(),
             "7".to_string(),
         ];
-        let (query, limit) = parse_rag_search_args(&args);
+        let (query, limit, filter) = parse_rag_search_args(&args).unwrap();
         assert_eq!(query, "quarterly sales");
         assert_eq!(limit, 7);
+        assert!(filter.is_none());
+    }
+
+    #[test]
+    fn test_parse_rag_search_filter() {
+        let args = vec![
+            "prices".to_string(),
+            "--filter".to_string(),
+            r#"{"is_table": true}"#.to_string(),
+        ];
+        let (query, _, filter) = parse_rag_search_args(&args).unwrap();
+        assert_eq!(query, "prices");
+        assert_eq!(filter, Some(json!({"is_table": true})));
+
+        let args = vec![
+            "prices".to_string(),
+            "--filter".to_string(),
+            "[]".to_string(),
+        ];
+        assert!(parse_rag_search_args(&args).is_err());
     }
 
     #[test]
@@ -1614,6 +1337,7 @@ mod tests {
         assert_eq!(path.dataset_id, 1);
         assert_eq!(path.document_id, 2);
         assert_eq!(path.chunk_id, 3);
+        assert!(parse_chunk_path("/home/user/datasets/1/files/2/chunks/3.json").is_some());
     }
 
     #[test]
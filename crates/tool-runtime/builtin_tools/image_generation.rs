@@ -0,0 +1,420 @@
+use base64::Engine;
+use bashkit::{async_trait, Builtin, BuiltinContext, ExecResult, FileSystem};
+use db::{queries, ModelType, Pool};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use serde::Deserialize;
+use serde_json::json;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+const OUTPUT_DIR: &str = "/home/user/output";
+const DEFAULT_SIZE: &str = "1024x1024";
+const MAX_PROMPT_CHARS: usize = 4_000;
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GenerateImageArgs {
+    prompt: String,
+    size: String,
+    output: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImageModel {
+    pub name: String,
+    pub base_url: String,
+    pub api_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ImageGenerationResponse {
+    data: Vec<ImageGenerationData>,
+}
+
+#[derive(Deserialize)]
+struct ImageGenerationData {
+    b64_json: Option<String>,
+    url: Option<String>,
+}
+
+/// Bashkit builtin that renders an image with the team's configured Image model
+/// and writes it into /home/user/output so it is persisted as a generated output.
+pub(crate) struct GenerateImageBuiltin {
+    pub(crate) pool: Pool,
+    pub(crate) sub: String,
+}
+
+#[async_trait]
+impl Builtin for GenerateImageBuiltin {
+    async fn execute(&self, ctx: BuiltinContext<'_>) -> bashkit::Result<ExecResult> {
+        let args = match parse_generate_image_args(ctx.args, ctx.stdin) {
+            Ok(args) => args,
+            Err(err) => return Ok(ExecResult::err(format!("{err}\n"), 2)),
+        };
+
+        let path = match output_path(ctx.cwd, &args) {
+            Ok(path) => path,
+            Err(err) => return Ok(ExecResult::err(format!("{err}\n"), 2)),
+        };
+
+        let model = match image_model(&self.pool, &self.sub).await {
+            Ok(Some(model)) => model,
+            Ok(None) => {
+                return Ok(ExecResult::err(
+                    "No image generation model is configured for this team\n",
+                    1,
+                ))
+            }
+            Err(err) => return Ok(ExecResult::err(format!("{err}\n"), 1)),
+        };
+
+        let bytes = match generate_image(&model, &args.prompt, &args.size).await {
+            Ok(bytes) => bytes,
+            Err(err) => return Ok(ExecResult::err(format!("{err}\n"), 1)),
+        };
+
+        if let Err(err) = write_image(ctx.fs.as_ref(), &path, &bytes).await {
+            return Ok(ExecResult::err(format!("{err}\n"), 1));
+        }
+
+        let result = json!({
+            "path": path.to_string_lossy(),
+            "size": args.size,
+            "bytes": bytes.len(),
+            "model": model.name,
+        });
+        Ok(ExecResult::ok(format!("{result}\n")))
+    }
+
+    fn llm_hint(&self) -> Option<&'static str> {
+        Some("generate-image PROMPT [--size WxH] [--output PATH]: create an image with the team's image model and save it under /home/user/output.")
+    }
+}
+
+fn parse_generate_image_args(
+    args: &[String],
+    stdin: Option<&str>,
+) -> Result<GenerateImageArgs, String> {
+    let usage = "usage: generate-image PROMPT [--size WxH] [--output /home/user/output/<name>.png]";
+    let mut size = DEFAULT_SIZE.to_string();
+    let mut output = None;
+    let mut prompt_parts = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--size" => {
+                let value = iter.next().ok_or_else(|| usage.to_string())?;
+                if !is_valid_size(value) {
+                    return Err(format!("invalid --size {value}, expected WIDTHxHEIGHT"));
+                }
+                size = value.clone();
+            }
+            "--output" | "-o" => {
+                output = Some(iter.next().ok_or_else(|| usage.to_string())?.clone());
+            }
+            _ => prompt_parts.push(arg.as_str()),
+        }
+    }
+
+    let mut prompt = prompt_parts.join(" ");
+    if prompt.trim().is_empty() {
+        prompt = stdin.unwrap_or_default().to_string();
+    }
+    let prompt = prompt
+        .trim()
+        .chars()
+        .take(MAX_PROMPT_CHARS)
+        .collect::<String>();
+    if prompt.is_empty() {
+        return Err(usage.to_string());
+    }
+
+    Ok(GenerateImageArgs {
+        prompt,
+        size,
+        output,
+    })
+}
+
+fn is_valid_size(size: &str) -> bool {
+    size.split_once('x').is_some_and(|(width, height)| {
+        width.parse::<u32>().is_ok_and(|width| width > 0)
+            && height.parse::<u32>().is_ok_and(|height| height > 0)
+    })
+}
+
+fn output_path(cwd: &Path, args: &GenerateImageArgs) -> Result<PathBuf, String> {
+    let path = match args.output.as_deref() {
+        Some(output) => resolve_path(cwd, output),
+        None => PathBuf::from(format!(
+            "{OUTPUT_DIR}/{}.png",
+            image_file_stem(&args.prompt)
+        )),
+    };
+
+    if !path.starts_with(OUTPUT_DIR) || path == Path::new(OUTPUT_DIR) {
+        return Err(format!("--output must be a file inside {OUTPUT_DIR}"));
+    }
+
+    Ok(path)
+}
+
+fn resolve_path(cwd: &Path, path: &str) -> PathBuf {
+    let mut resolved = PathBuf::from("/");
+    for component in cwd.join(path).components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::Normal(part) => resolved.push(part),
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => {}
+        }
+    }
+    resolved
+}
+
+fn image_file_stem(prompt: &str) -> String {
+    let slug = prompt
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() {
+                ch.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .take(6)
+        .collect::<Vec<_>>()
+        .join("-");
+    let hash = format!("{:x}", md5::compute(prompt.as_bytes()));
+
+    if slug.is_empty() {
+        format!("image-{}", &hash[..8])
+    } else {
+        format!("{slug}-{}", &hash[..8])
+    }
+}
+
+async fn write_image(fs: &dyn FileSystem, path: &Path, bytes: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs.mkdir(parent, true)
+            .await
+            .map_err(|err| format!("failed to create {}: {err}", parent.display()))?;
+    }
+    fs.write_file(path, bytes)
+        .await
+        .map_err(|err| format!("failed to write {}: {err}", path.display()))
+}
+
+/// Returns the first Image model visible to the user, if any.
+pub async fn image_model(pool: &Pool, sub: &str) -> Result<Option<ImageModel>, String> {
+    let mut client = pool.get().await.map_err(|err| err.to_string())?;
+    let transaction = client.transaction().await.map_err(|err| err.to_string())?;
+    db::authz::set_row_level_security_user_id(&transaction, sub.to_string())
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let model = queries::models::models()
+        .bind(&transaction, &ModelType::Image)
+        .all()
+        .await
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .next()
+        .map(|model| ImageModel {
+            name: model.name,
+            base_url: model.base_url,
+            api_key: model.api_key,
+        });
+
+    transaction.commit().await.map_err(|err| err.to_string())?;
+
+    Ok(model)
+}
+
+/// Calls an OpenAI compatible `/images/generations` endpoint and returns the image bytes.
+pub async fn generate_image(
+    model: &ImageModel,
+    prompt: &str,
+    size: &str,
+) -> Result<Vec<u8>, String> {
+    let client = reqwest::Client::new();
+    let mut request = client
+        .post(format!(
+            "{}/images/generations",
+            model.base_url.trim_end_matches('/')
+        ))
+        .header(CONTENT_TYPE, "application/json")
+        .body(
+            json!({
+                "model": model.name,
+                "prompt": prompt,
+                "size": size,
+                "n": 1,
+                "response_format": "b64_json"
+            })
+            .to_string(),
+        );
+    if let Some(api_key) = model.api_key.as_deref().filter(|key| !key.is_empty()) {
+        request = request.header(AUTHORIZATION, format!("Bearer {api_key}"));
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|err| format!("image generation request failed: {err}"))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!(
+            "image generation failed with HTTP {status}: {}",
+            body.chars().take(300).collect::<String>()
+        ));
+    }
+
+    let response: ImageGenerationResponse = response
+        .json()
+        .await
+        .map_err(|err| format!("invalid image generation response: {err}"))?;
+    let image = response
+        .data
+        .into_iter()
+        .next()
+        .ok_or_else(|| "image generation returned no images".to_string())?;
+
+    let bytes = match (image.b64_json, image.url) {
+        (Some(encoded), _) => base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(|err| format!("invalid base64 image data: {err}"))?,
+        (None, Some(url)) => download_image(&url).await?,
+        (None, None) => return Err("image generation returned no image data".to_string()),
+    };
+
+    if bytes.len() > MAX_IMAGE_BYTES {
+        return Err(format!(
+            "generated image is larger than {MAX_IMAGE_BYTES} bytes"
+        ));
+    }
+
+    Ok(bytes)
+}
+
+/// Downloads an image the model returned a link to. The link comes from outside, so it
+/// may only reach public addresses, and the download stops once it's too large to keep.
+async fn download_image(url: &str) -> Result<Vec<u8>, String> {
+    outbound::check_url(url).map_err(|err| format!("failed to download generated image: {err}"))?;
+    let mut response = outbound::client(DOWNLOAD_TIMEOUT)
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| format!("failed to download generated image: {err}"))?;
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|err| format!("failed to download generated image: {err}"))?
+    {
+        push_image_chunk(&mut bytes, &chunk)?;
+    }
+    Ok(bytes)
+}
+
+fn push_image_chunk(bytes: &mut Vec<u8>, chunk: &[u8]) -> Result<(), String> {
+    if bytes.len() + chunk.len() > MAX_IMAGE_BYTES {
+        return Err(format!(
+            "generated image is larger than {MAX_IMAGE_BYTES} bytes"
+        ));
+    }
+    bytes.extend_from_slice(chunk);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_generate_image_args() {
+        let parsed = parse_generate_image_args(
+            &args(&[
+                "a", "red", "fox", "--size", "512x512", "--output", "fox.png",
+            ]),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(parsed.prompt, "a red fox");
+        assert_eq!(parsed.size, "512x512");
+        assert_eq!(parsed.output.as_deref(), Some("fox.png"));
+    }
+
+    #[test]
+    fn test_parse_generate_image_args_reads_prompt_from_stdin() {
+        let parsed = parse_generate_image_args(&[], Some("a lighthouse at dusk\n")).unwrap();
+
+        assert_eq!(parsed.prompt, "a lighthouse at dusk");
+        assert_eq!(parsed.size, DEFAULT_SIZE);
+    }
+
+    #[test]
+    fn test_parse_generate_image_args_rejects_bad_input() {
+        assert!(parse_generate_image_args(&[], None).is_err());
+        assert!(parse_generate_image_args(&args(&["cat", "--size", "big"]), None).is_err());
+    }
+
+    #[test]
+    fn test_output_path_is_scoped_to_output_dir() {
+        let parsed = GenerateImageArgs {
+            prompt: "A red fox!".to_string(),
+            size: DEFAULT_SIZE.to_string(),
+            output: None,
+        };
+        let path = output_path(Path::new("/home/user"), &parsed).unwrap();
+        assert!(path
+            .to_string_lossy()
+            .starts_with("/home/user/output/a-red-fox-"));
+        assert_eq!(path.extension().unwrap(), "png");
+
+        let relative = GenerateImageArgs {
+            output: Some("output/fox.png".to_string()),
+            ..parsed.clone()
+        };
+        assert_eq!(
+            output_path(Path::new("/home/user"), &relative).unwrap(),
+            PathBuf::from("/home/user/output/fox.png")
+        );
+
+        let escaping = GenerateImageArgs {
+            output: Some("/home/user/output/../skills/fox.png".to_string()),
+            ..parsed
+        };
+        assert!(output_path(Path::new("/home/user"), &escaping).is_err());
+    }
+
+    #[test]
+    fn test_image_download_stops_past_the_limit() {
+        let mut bytes = Vec::new();
+        push_image_chunk(&mut bytes, &vec![0; MAX_IMAGE_BYTES - 1]).unwrap();
+        push_image_chunk(&mut bytes, &[0]).unwrap();
+        assert!(push_image_chunk(&mut bytes, &[0]).is_err());
+        assert_eq!(bytes.len(), MAX_IMAGE_BYTES);
+    }
+
+    #[tokio::test]
+    async fn test_image_links_to_internal_addresses_are_refused() {
+        let err = download_image("http://169.254.169.254/latest/meta-data/")
+            .await
+            .unwrap_err();
+        assert!(err.contains("not a public address"), "{err}");
+    }
+}
@@ -0,0 +1,952 @@
+//! Read-only Bashkit filesystems that load directory listings and file contents on first use.
+//! Datasets and attachments are mounted this way so a command only fetches the paths it touches.
+use super::bashkit::{plan_attachment_paths, ATTACHMENTS_DIR, DATASETS_DIR};
+use bashkit::{
+    async_trait, DirEntry, FileSystem, FileSystemExt, FileType, Metadata, Result as FsResult,
+};
+use db::{queries, Pool, Transaction};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{Error as IoError, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{MappedMutexGuard, Mutex as AsyncMutex, MutexGuard};
+
+/// The most chunks of one document that are listed and loaded.
+const CHUNKS_PER_DOCUMENT_LIMIT: i64 = 1_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum LazyEntry {
+    Directory(String),
+    /// Files whose size isn't known up front report their size once loaded.
+    File {
+        name: String,
+        size: Option<u64>,
+    },
+}
+
+impl LazyEntry {
+    fn name(&self) -> &str {
+        match self {
+            LazyEntry::Directory(name) => name,
+            LazyEntry::File { name, .. } => name,
+        }
+    }
+}
+
+/// Where a [`LazyFs`] gets its data. Paths are relative to the mount point and start with `/`.
+#[async_trait]
+pub(crate) trait LazySource: Send + Sync {
+    /// Entries directly under `dir`, or `None` when `dir` isn't a directory.
+    async fn list(&self, dir: &str) -> Result<Option<Vec<LazyEntry>>, String>;
+    /// Contents of `path`, or `None` when it isn't a file.
+    async fn read(&self, path: &str) -> Result<Option<Vec<u8>>, String>;
+}
+
+type Listing = Option<Arc<Vec<LazyEntry>>>;
+type Contents = Option<Arc<Vec<u8>>>;
+
+pub(crate) struct LazyFs<S> {
+    source: S,
+    listings: Mutex<HashMap<String, Listing>>,
+    contents: Mutex<HashMap<String, Contents>>,
+}
+
+impl<S: LazySource> LazyFs<S> {
+    pub(crate) fn new(source: S) -> Self {
+        Self {
+            source,
+            listings: Mutex::new(HashMap::new()),
+            contents: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn listing(&self, dir: &str) -> FsResult<Listing> {
+        if let Some(listing) = self.listings.lock().unwrap().get(dir) {
+            return Ok(listing.clone());
+        }
+        let listing = self
+            .source
+            .list(dir)
+            .await
+            .map_err(IoError::other)?
+            .map(Arc::new);
+        self.listings
+            .lock()
+            .unwrap()
+            .insert(dir.to_string(), listing.clone());
+        Ok(listing)
+    }
+
+    async fn contents(&self, path: &str) -> FsResult<Contents> {
+        if let Some(contents) = self.contents.lock().unwrap().get(path) {
+            return Ok(contents.clone());
+        }
+        let contents = self
+            .source
+            .read(path)
+            .await
+            .map_err(IoError::other)?
+            .map(Arc::new);
+        self.contents
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), contents.clone());
+        Ok(contents)
+    }
+
+    async fn entry(&self, path: &str) -> FsResult<Option<LazyEntry>> {
+        if path == "/" {
+            return Ok(Some(LazyEntry::Directory(String::new())));
+        }
+        let (parent, name) = split_path(path);
+        Ok(self
+            .listing(parent)
+            .await?
+            .and_then(|entries| entries.iter().find(|entry| entry.name() == name).cloned()))
+    }
+
+    async fn metadata(&self, path: &str, entry: &LazyEntry) -> FsResult<Metadata> {
+        match entry {
+            LazyEntry::Directory(_) => Ok(directory_metadata()),
+            LazyEntry::File {
+                size: Some(size), ..
+            } => Ok(file_metadata(*size)),
+            LazyEntry::File { size: None, .. } => {
+                let size = self
+                    .contents(path)
+                    .await?
+                    .map(|contents| contents.len() as u64)
+                    .unwrap_or(0);
+                Ok(file_metadata(size))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<S: LazySource> FileSystem for LazyFs<S> {
+    async fn read_file(&self, path: &Path) -> FsResult<Vec<u8>> {
+        let path = normalize(path);
+        match self.entry(&path).await? {
+            Some(LazyEntry::File { .. }) => match self.contents(&path).await? {
+                Some(contents) => Ok(contents.as_ref().clone()),
+                None => Err(not_found(&path)),
+            },
+            Some(LazyEntry::Directory(_)) => {
+                Err(IoError::other(format!("{path}: is a directory")).into())
+            }
+            None => Err(not_found(&path)),
+        }
+    }
+
+    async fn write_file(&self, path: &Path, _content: &[u8]) -> FsResult<()> {
+        Err(read_only(path))
+    }
+
+    async fn append_file(&self, path: &Path, _content: &[u8]) -> FsResult<()> {
+        Err(read_only(path))
+    }
+
+    async fn mkdir(&self, path: &Path, recursive: bool) -> FsResult<()> {
+        // mkdir -p on a directory that already exists succeeds everywhere else too.
+        let existing = self.entry(&normalize(path)).await?;
+        if recursive && matches!(existing, Some(LazyEntry::Directory(_))) {
+            return Ok(());
+        }
+        Err(read_only(path))
+    }
+
+    async fn remove(&self, path: &Path, _recursive: bool) -> FsResult<()> {
+        Err(read_only(path))
+    }
+
+    async fn stat(&self, path: &Path) -> FsResult<Metadata> {
+        let path = normalize(path);
+        match self.entry(&path).await? {
+            Some(entry) => self.metadata(&path, &entry).await,
+            None => Err(not_found(&path)),
+        }
+    }
+
+    async fn read_dir(&self, path: &Path) -> FsResult<Vec<DirEntry>> {
+        let path = normalize(path);
+        let Some(entries) = self.listing(&path).await? else {
+            return Err(not_found(&path));
+        };
+        // Listing a directory doesn't load its files, so unknown sizes show as zero here.
+        Ok(entries
+            .iter()
+            .map(|entry| DirEntry {
+                name: entry.name().to_string(),
+                metadata: match entry {
+                    LazyEntry::Directory(_) => directory_metadata(),
+                    LazyEntry::File { size, .. } => file_metadata(size.unwrap_or(0)),
+                },
+            })
+            .collect())
+    }
+
+    async fn exists(&self, path: &Path) -> FsResult<bool> {
+        Ok(self.entry(&normalize(path)).await?.is_some())
+    }
+
+    async fn rename(&self, from: &Path, _to: &Path) -> FsResult<()> {
+        Err(read_only(from))
+    }
+
+    async fn copy(&self, _from: &Path, to: &Path) -> FsResult<()> {
+        Err(read_only(to))
+    }
+
+    async fn symlink(&self, _target: &Path, link: &Path) -> FsResult<()> {
+        Err(read_only(link))
+    }
+
+    async fn read_link(&self, path: &Path) -> FsResult<PathBuf> {
+        Err(IoError::new(
+            ErrorKind::InvalidInput,
+            format!("{}: not a symlink", path.display()),
+        )
+        .into())
+    }
+
+    async fn chmod(&self, path: &Path, _mode: u32) -> FsResult<()> {
+        Err(read_only(path))
+    }
+}
+
+impl<S: LazySource> FileSystemExt for LazyFs<S> {}
+
+fn normalize(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        "/".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+fn split_path(path: &str) -> (&str, &str) {
+    match path.rsplit_once('/') {
+        Some(("", name)) => ("/", name),
+        Some((parent, name)) => (parent, name),
+        None => ("/", path),
+    }
+}
+
+fn directory_metadata() -> Metadata {
+    Metadata {
+        file_type: FileType::Directory,
+        size: 0,
+        mode: 0o555,
+        ..Metadata::default()
+    }
+}
+
+fn file_metadata(size: u64) -> Metadata {
+    Metadata {
+        file_type: FileType::File,
+        size,
+        mode: 0o444,
+        ..Metadata::default()
+    }
+}
+
+fn not_found(path: &str) -> bashkit::Error {
+    IoError::new(
+        ErrorKind::NotFound,
+        format!("{path}: no such file or directory"),
+    )
+    .into()
+}
+
+fn read_only(path: &Path) -> bashkit::Error {
+    IoError::new(
+        ErrorKind::PermissionDenied,
+        format!("{}: read-only file system", path.display()),
+    )
+    .into()
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(value).map_err(|e| e.to_string())
+}
+
+async fn set_rls(transaction: &Transaction<'_>, sub: &str) -> Result<(), String> {
+    db::authz::set_row_level_security_user_id(transaction, sub.to_string())
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("Failed to set RLS: {e}"))
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DatasetManifest {
+    datasets: Vec<DatasetEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DatasetEntry {
+    dataset_id: i32,
+    name: String,
+    path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DatasetMetadata {
+    dataset_id: i32,
+    name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DatasetFiles {
+    files: Vec<FileEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FileEntry {
+    document_id: i32,
+    name: String,
+    size: i32,
+    chunks: i64,
+    path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FileMetadata {
+    document_id: i32,
+    dataset_id: i32,
+    name: String,
+    size: i32,
+    chunks: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ChunkMetadata {
+    chunk_id: i32,
+    document_id: i32,
+    dataset_id: i32,
+    page_number: i32,
+    metadata: serde_json::Value,
+}
+
+#[derive(Debug, Clone)]
+struct DatasetDocument {
+    id: i32,
+    file_name: String,
+    content_size: i32,
+    chunk_count: i64,
+}
+
+#[derive(Debug, Clone)]
+struct DocumentChunk {
+    id: i32,
+    text: String,
+    page_number: i32,
+    metadata: serde_json::Value,
+}
+
+type DocumentChunks = Arc<Vec<DocumentChunk>>;
+
+/// The assistant's datasets, mounted at /home/user/datasets.
+pub(crate) struct DatasetSource {
+    pool: Pool,
+    sub: String,
+    prompt_id: i32,
+    /// Taken from the pool on first use and kept for the rest of the call.
+    client: AsyncMutex<Option<db::Client>>,
+    datasets: Mutex<Option<Vec<(i32, String)>>>,
+    documents: Mutex<HashMap<i32, Vec<DatasetDocument>>>,
+    /// A document's chunks, loaded together the first time one of them is touched.
+    chunks: Mutex<HashMap<(i32, i32), DocumentChunks>>,
+}
+
+impl DatasetSource {
+    pub(crate) fn new(pool: Pool, sub: String, prompt_id: i32) -> Self {
+        Self {
+            pool,
+            sub,
+            prompt_id,
+            client: AsyncMutex::new(None),
+            datasets: Mutex::new(None),
+            documents: Mutex::new(HashMap::new()),
+            chunks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn client(&self) -> Result<MappedMutexGuard<'_, db::Client>, String> {
+        let mut client = self.client.lock().await;
+        if client.is_none() {
+            *client = Some(self.pool.get().await.map_err(|e| e.to_string())?);
+        }
+        MutexGuard::try_map(client, Option::as_mut)
+            .map_err(|_| "No database connection".to_string())
+    }
+
+    async fn datasets(&self) -> Result<Vec<(i32, String)>, String> {
+        if let Some(datasets) = self.datasets.lock().unwrap().as_ref() {
+            return Ok(datasets.clone());
+        }
+        let mut client = self.client().await?;
+        let transaction = client.transaction().await.map_err(|e| e.to_string())?;
+        set_rls(&transaction, &self.sub).await?;
+
+        let datasets: Vec<(i32, String)> = queries::prompts::prompt_datasets()
+            .bind(&transaction, &self.prompt_id)
+            .all()
+            .await
+            .map_err(|e| format!("Failed to get datasets: {e}"))?
+            .into_iter()
+            .map(|dataset| (dataset.dataset_id, dataset.name))
+            .collect();
+
+        transaction.commit().await.map_err(|e| e.to_string())?;
+        *self.datasets.lock().unwrap() = Some(datasets.clone());
+        Ok(datasets)
+    }
+
+    async fn dataset(&self, dataset_id: i32) -> Result<Option<(i32, String)>, String> {
+        Ok(self
+            .datasets()
+            .await?
+            .into_iter()
+            .find(|(id, _)| *id == dataset_id))
+    }
+
+    async fn dataset_from(&self, dataset_id: &str) -> Result<Option<(i32, String)>, String> {
+        match dataset_id.parse() {
+            Ok(dataset_id) => self.dataset(dataset_id).await,
+            Err(_) => Ok(None),
+        }
+    }
+
+    async fn documents(&self, dataset_id: i32) -> Result<Vec<DatasetDocument>, String> {
+        if let Some(documents) = self.documents.lock().unwrap().get(&dataset_id) {
+            return Ok(documents.clone());
+        }
+        let mut client = self.client().await?;
+        let transaction = client.transaction().await.map_err(|e| e.to_string())?;
+        set_rls(&transaction, &self.sub).await?;
+
+        let rows = transaction
+            .query(
+                "
+                SELECT
+                    d.id,
+                    d.file_name,
+                    d.content_size,
+                    (SELECT COUNT(id) FROM rag.chunks WHERE document_id = d.id) AS chunk_count
+                FROM rag.documents d
+                WHERE d.dataset_id = $1
+                  AND d.dataset_id IN (
+                      SELECT dataset_id FROM assistants.prompt_dataset WHERE prompt_id = $2
+                  )
+                ORDER BY d.updated_at DESC
+                ",
+                &[&dataset_id, &self.prompt_id],
+            )
+            .await
+            .map_err(|e| format!("Failed to get dataset files: {e}"))?;
+
+        transaction.commit().await.map_err(|e| e.to_string())?;
+        let documents: Vec<DatasetDocument> = rows
+            .into_iter()
+            .map(|row| DatasetDocument {
+                id: row.get(0),
+                file_name: row.get(1),
+                content_size: row.get(2),
+                chunk_count: row.get(3),
+            })
+            .collect();
+        self.documents
+            .lock()
+            .unwrap()
+            .insert(dataset_id, documents.clone());
+        Ok(documents)
+    }
+
+    async fn document(
+        &self,
+        dataset_id: i32,
+        document_id: i32,
+    ) -> Result<Option<DatasetDocument>, String> {
+        if self.dataset(dataset_id).await?.is_none() {
+            return Ok(None);
+        }
+        Ok(self
+            .documents(dataset_id)
+            .await?
+            .into_iter()
+            .find(|document| document.id == document_id))
+    }
+
+    async fn chunks(&self, dataset_id: i32, document_id: i32) -> Result<DocumentChunks, String> {
+        if let Some(chunks) = self.chunks.lock().unwrap().get(&(dataset_id, document_id)) {
+            return Ok(chunks.clone());
+        }
+        let mut client = self.client().await?;
+        let transaction = client.transaction().await.map_err(|e| e.to_string())?;
+        set_rls(&transaction, &self.sub).await?;
+
+        let rows = transaction
+            .query(
+                "
+                SELECT c.id, decrypt_text(c.text) AS text, c.page_number, c.metadata
+                FROM rag.chunks c
+                INNER JOIN rag.documents d ON d.id = c.document_id
+                WHERE c.document_id = $1
+                  AND d.dataset_id = $2
+                  AND d.dataset_id IN (
+                      SELECT dataset_id FROM assistants.prompt_dataset WHERE prompt_id = $3
+                  )
+                ORDER BY c.page_number ASC, c.id ASC
+                LIMIT $4
+                ",
+                &[
+                    &document_id,
+                    &dataset_id,
+                    &self.prompt_id,
+                    &CHUNKS_PER_DOCUMENT_LIMIT,
+                ],
+            )
+            .await
+            .map_err(|e| format!("Failed to get document chunks: {e}"))?;
+
+        transaction.commit().await.map_err(|e| e.to_string())?;
+        let chunks: DocumentChunks = Arc::new(
+            rows.into_iter()
+                .map(|row| DocumentChunk {
+                    id: row.get(0),
+                    text: row.get(1),
+                    page_number: row.get(2),
+                    metadata: row.get(3),
+                })
+                .collect(),
+        );
+        self.chunks
+            .lock()
+            .unwrap()
+            .insert((dataset_id, document_id), chunks.clone());
+        Ok(chunks)
+    }
+
+    async fn chunk(
+        &self,
+        dataset_id: i32,
+        document_id: i32,
+        chunk_id: i32,
+    ) -> Result<Option<DocumentChunk>, String> {
+        Ok(self
+            .chunks(dataset_id, document_id)
+            .await?
+            .iter()
+            .find(|chunk| chunk.id == chunk_id)
+            .cloned())
+    }
+}
+
+#[async_trait]
+impl LazySource for DatasetSource {
+    async fn list(&self, dir: &str) -> Result<Option<Vec<LazyEntry>>, String> {
+        let parts: Vec<&str> = dir.split('/').filter(|part| !part.is_empty()).collect();
+        match parts.as_slice() {
+            [] => {
+                let mut entries = vec![unsized_file("index.json")];
+                for (dataset_id, _) in self.datasets().await? {
+                    entries.push(LazyEntry::Directory(dataset_id.to_string()));
+                }
+                Ok(Some(entries))
+            }
+            [dataset_id] => match dataset_id.parse().ok() {
+                Some(dataset_id) if self.dataset(dataset_id).await?.is_some() => Ok(Some(vec![
+                    unsized_file("metadata.json"),
+                    unsized_file("files.json"),
+                    LazyEntry::Directory("files".to_string()),
+                ])),
+                _ => Ok(None),
+            },
+            [dataset_id, "files"] => match dataset_id.parse().ok() {
+                Some(dataset_id) if self.dataset(dataset_id).await?.is_some() => Ok(Some(
+                    self.documents(dataset_id)
+                        .await?
+                        .into_iter()
+                        .map(|document| LazyEntry::Directory(document.id.to_string()))
+                        .collect(),
+                )),
+                _ => Ok(None),
+            },
+            [dataset_id, "files", document_id] => {
+                match (dataset_id.parse().ok(), document_id.parse().ok()) {
+                    (Some(dataset_id), Some(document_id))
+                        if self.document(dataset_id, document_id).await?.is_some() =>
+                    {
+                        Ok(Some(vec![
+                            unsized_file("metadata.json"),
+                            LazyEntry::Directory("chunks".to_string()),
+                        ]))
+                    }
+                    _ => Ok(None),
+                }
+            }
+            [dataset_id, "files", document_id, "chunks"] => {
+                match (dataset_id.parse().ok(), document_id.parse().ok()) {
+                    (Some(dataset_id), Some(document_id))
+                        if self.document(dataset_id, document_id).await?.is_some() =>
+                    {
+                        Ok(Some(
+                            self.chunks(dataset_id, document_id)
+                                .await?
+                                .iter()
+                                .flat_map(|chunk| {
+                                    let chunk_id = chunk.id;
+                                    [
+                                        unsized_file(&format!("{chunk_id}.txt")),
+                                        unsized_file(&format!("{chunk_id}.json")),
+                                    ]
+                                })
+                                .collect(),
+                        ))
+                    }
+                    _ => Ok(None),
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn read(&self, path: &str) -> Result<Option<Vec<u8>>, String> {
+        let parts: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+        match parts.as_slice() {
+            ["index.json"] => {
+                let datasets = self
+                    .datasets()
+                    .await?
+                    .into_iter()
+                    .map(|(dataset_id, name)| DatasetEntry {
+                        dataset_id,
+                        name,
+                        path: format!("{DATASETS_DIR}/{dataset_id}"),
+                    })
+                    .collect();
+                to_json(&DatasetManifest { datasets }).map(Some)
+            }
+            [dataset_id, "metadata.json"] => {
+                let Some((dataset_id, name)) = self.dataset_from(dataset_id).await? else {
+                    return Ok(None);
+                };
+                to_json(&DatasetMetadata { dataset_id, name }).map(Some)
+            }
+            [dataset_id, "files.json"] => {
+                let Some((dataset_id, _)) = self.dataset_from(dataset_id).await? else {
+                    return Ok(None);
+                };
+                let files = self
+                    .documents(dataset_id)
+                    .await?
+                    .into_iter()
+                    .map(|document| FileEntry {
+                        document_id: document.id,
+                        name: document.file_name,
+                        size: document.content_size,
+                        chunks: document.chunk_count,
+                        path: format!("{DATASETS_DIR}/{dataset_id}/files/{}", document.id),
+                    })
+                    .collect();
+                to_json(&DatasetFiles { files }).map(Some)
+            }
+            [dataset_id, "files", document_id, "metadata.json"] => {
+                let (Ok(dataset_id), Ok(document_id)) = (dataset_id.parse(), document_id.parse())
+                else {
+                    return Ok(None);
+                };
+                let Some(document) = self.document(dataset_id, document_id).await? else {
+                    return Ok(None);
+                };
+                to_json(&FileMetadata {
+                    document_id: document.id,
+                    dataset_id,
+                    name: document.file_name,
+                    size: document.content_size,
+                    chunks: document.chunk_count,
+                })
+                .map(Some)
+            }
+            [dataset_id, "files", document_id, "chunks", chunk_file]
+                if chunk_file.ends_with(".json") =>
+            {
+                let (Ok(dataset_id), Ok(document_id), Some(Ok(chunk_id))) = (
+                    dataset_id.parse(),
+                    document_id.parse(),
+                    chunk_file.strip_suffix(".json").map(str::parse),
+                ) else {
+                    return Ok(None);
+                };
+                match self.chunk(dataset_id, document_id, chunk_id).await? {
+                    Some(chunk) => to_json(&ChunkMetadata {
+                        chunk_id,
+                        document_id,
+                        dataset_id,
+                        page_number: chunk.page_number,
+                        metadata: chunk.metadata,
+                    })
+                    .map(Some),
+                    None => Ok(None),
+                }
+            }
+            [dataset_id, "files", document_id, "chunks", chunk_file] => {
+                let (Ok(dataset_id), Ok(document_id), Some(Ok(chunk_id))) = (
+                    dataset_id.parse(),
+                    document_id.parse(),
+                    chunk_file.strip_suffix(".txt").map(str::parse),
+                ) else {
+                    return Ok(None);
+                };
+                Ok(self
+                    .chunk(dataset_id, document_id, chunk_id)
+                    .await?
+                    .map(|chunk| chunk.text.into_bytes()))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Generated JSON and chunk files, whose size is only known once they're loaded.
+fn unsized_file(name: &str) -> LazyEntry {
+    LazyEntry::File {
+        name: name.to_string(),
+        size: None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AttachmentManifest {
+    files: Vec<AttachmentEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AttachmentEntry {
+    file_id: i32,
+    name: String,
+    path: String,
+    mime_type: String,
+    size: i64,
+}
+
+/// Files uploaded to the conversation, mounted at /home/user/attachments.
+pub(crate) struct AttachmentSource {
+    pool: Pool,
+    sub: String,
+    conversation_id: i64,
+    attachments: Mutex<Option<Vec<AttachmentEntry>>>,
+}
+
+impl AttachmentSource {
+    pub(crate) fn new(pool: Pool, sub: String, conversation_id: i64) -> Self {
+        Self {
+            pool,
+            sub,
+            conversation_id,
+            attachments: Mutex::new(None),
+        }
+    }
+
+    async fn attachments(&self) -> Result<Vec<AttachmentEntry>, String> {
+        if let Some(attachments) = self.attachments.lock().unwrap().as_ref() {
+            return Ok(attachments.clone());
+        }
+        let mut client = self.pool.get().await.map_err(|e| e.to_string())?;
+        let transaction = client.transaction().await.map_err(|e| e.to_string())?;
+        set_rls(&transaction, &self.sub).await?;
+
+        let attachments = queries::attachments::get_by_conversation()
+            .bind(&transaction, &self.conversation_id)
+            .all()
+            .await
+            .map_err(|e| format!("Failed to get attachments: {e}"))?;
+
+        transaction.commit().await.map_err(|e| e.to_string())?;
+        let paths = plan_attachment_paths(
+            attachments
+                .iter()
+                .map(|attachment| attachment.file_name.as_str()),
+        );
+        let attachments: Vec<AttachmentEntry> = attachments
+            .into_iter()
+            .zip(paths)
+            .map(|(attachment, path)| AttachmentEntry {
+                file_id: attachment.id,
+                name: attachment.file_name,
+                path,
+                mime_type: attachment.mime_type,
+                size: attachment.file_size,
+            })
+            .collect();
+        *self.attachments.lock().unwrap() = Some(attachments.clone());
+        Ok(attachments)
+    }
+
+    async fn content(&self, file_id: i32) -> Result<Vec<u8>, String> {
+        let mut client = self.pool.get().await.map_err(|e| e.to_string())?;
+        let transaction = client.transaction().await.map_err(|e| e.to_string())?;
+        set_rls(&transaction, &self.sub).await?;
+
+        let data = queries::attachments::get_content()
+            .bind(&transaction, &file_id)
+            .one()
+            .await
+            .map_err(|e| format!("Failed to get attachment content: {e}"))?;
+
+        transaction.commit().await.map_err(|e| e.to_string())?;
+        Ok(data.object_data)
+    }
+}
+
+#[async_trait]
+impl LazySource for AttachmentSource {
+    async fn list(&self, dir: &str) -> Result<Option<Vec<LazyEntry>>, String> {
+        if dir != "/" {
+            return Ok(None);
+        }
+        let mut entries = vec![unsized_file("index.json")];
+        for attachment in self.attachments().await? {
+            entries.push(LazyEntry::File {
+                name: attachment_file_name(&attachment.path).to_string(),
+                size: Some(attachment.size.max(0) as u64),
+            });
+        }
+        Ok(Some(entries))
+    }
+
+    async fn read(&self, path: &str) -> Result<Option<Vec<u8>>, String> {
+        let attachments = self.attachments().await?;
+        if path == "/index.json" {
+            return to_json(&AttachmentManifest { files: attachments }).map(Some);
+        }
+        match attachments.iter().find(|attachment| {
+            attachment_file_name(&attachment.path) == path.trim_start_matches('/')
+        }) {
+            Some(attachment) => self.content(attachment.file_id).await.map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+fn attachment_file_name(path: &str) -> &str {
+    path.strip_prefix(ATTACHMENTS_DIR)
+        .unwrap_or(path)
+        .trim_start_matches('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bashkit::{Bash, InMemoryFs, MountableFs};
+
+    /// Serves /notes/<n>.txt and records every path it was asked for.
+    #[derive(Default)]
+    struct FakeSource {
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl FakeSource {
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl LazySource for FakeSource {
+        async fn list(&self, dir: &str) -> Result<Option<Vec<LazyEntry>>, String> {
+            self.calls.lock().unwrap().push(format!("list {dir}"));
+            match dir {
+                "/" => Ok(Some(vec![LazyEntry::Directory("notes".to_string())])),
+                "/notes" => Ok(Some(
+                    (1..=3)
+                        .map(|n| LazyEntry::File {
+                            name: format!("{n}.txt"),
+                            size: None,
+                        })
+                        .collect(),
+                )),
+                _ => Ok(None),
+            }
+        }
+
+        async fn read(&self, path: &str) -> Result<Option<Vec<u8>>, String> {
+            self.calls.lock().unwrap().push(format!("read {path}"));
+            Ok(path
+                .strip_prefix("/notes/")
+                .and_then(|name| name.strip_suffix(".txt"))
+                .map(|n| format!("note {n}\n").into_bytes()))
+        }
+    }
+
+    fn bash_with(source: Arc<LazyFs<FakeSource>>) -> Bash {
+        let fs = Arc::new(MountableFs::new(Arc::new(InMemoryFs::new())));
+        fs.mount(DATASETS_DIR, source).unwrap();
+        Bash::builder().fs(fs).cwd("/").build()
+    }
+
+    #[tokio::test]
+    async fn test_lazy_fs_only_loads_touched_paths() {
+        let lazy = Arc::new(LazyFs::new(FakeSource::default()));
+        let mut bash = bash_with(lazy.clone());
+
+        let result = bash
+            .exec("ls /home/user/datasets/notes; cat /home/user/datasets/notes/2.txt; cat /home/user/datasets/notes/2.txt")
+            .await
+            .unwrap();
+
+        assert_eq!(result.stdout, "1.txt\n2.txt\n3.txt\nnote 2\nnote 2\n");
+        let calls = lazy.source.calls();
+        assert_eq!(
+            calls
+                .iter()
+                .filter(|call| call.starts_with("read"))
+                .collect::<Vec<_>>(),
+            vec!["read /notes/2.txt"]
+        );
+        assert_eq!(
+            calls.iter().filter(|call| *call == "list /notes").count(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lazy_fs_stat_and_missing_paths() {
+        let lazy = LazyFs::new(FakeSource::default());
+
+        let metadata = lazy.stat(Path::new("/notes/3.txt")).await.unwrap();
+        assert_eq!(metadata.file_type, FileType::File);
+        assert_eq!(metadata.size, 7);
+        assert_eq!(
+            lazy.stat(Path::new("/notes")).await.unwrap().file_type,
+            FileType::Directory
+        );
+        assert!(!lazy.exists(Path::new("/notes/4.txt")).await.unwrap());
+        assert!(lazy.read_file(Path::new("/missing/1.txt")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_lazy_fs_is_read_only() {
+        let lazy = Arc::new(LazyFs::new(FakeSource::default()));
+        let mut bash = bash_with(lazy.clone());
+
+        let result = bash
+            .exec("echo x > /home/user/datasets/notes/1.txt; rm /home/user/datasets/notes/2.txt; mkdir -p /home/user/datasets/notes")
+            .await
+            .unwrap();
+
+        assert!(result.stderr.contains("read-only"));
+        assert!(lazy.write_file(Path::new("/new.txt"), b"x").await.is_err());
+        assert!(lazy.mkdir(Path::new("/notes"), true).await.is_ok());
+    }
+
+    #[test]
+    fn test_split_path() {
+        assert_eq!(split_path("/index.json"), ("/", "index.json"));
+        assert_eq!(split_path("/1/files/2"), ("/1/files", "2"));
+    }
+}
@@ -1,5 +1,10 @@
+pub mod approvals;
 pub mod bashkit;
+pub mod image_generation;
+pub mod lazy_fs;
 pub mod monty;
 pub mod openapi_tool_adapter;
+pub(crate) mod result_budget;
 pub mod time_date;
 pub mod web;
+pub mod workspace;
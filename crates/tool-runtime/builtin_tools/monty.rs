@@ -1,14 +1,17 @@
+use crate::builtin_tools::approvals::{self, ApprovalPolicies, ApprovalRequest, PendingApproval};
 use base64::Engine;
 use bashkit::{
     ExcType, ExtFunctionResult, FileSystem, MontyException, MontyObject, PythonExternalFnHandler,
 };
+use db::ToolCallPolicy;
 use rig::tool::{ToolDyn, ToolError};
 use serde_json::{json, Map, Value};
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 const FUNCTIONS_DIR: &str = "/home/user/functions";
 const WEB_FUNCTION_NAME: &str = "web_open_url";
+const WEB_OUTPUT_DIR: &str = "/home/user/output/web";
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RuntimeFunctionFile {
@@ -28,15 +31,25 @@ struct RuntimeOperation {
     description: String,
     parameters: Value,
     byte_parameters: Vec<(String, String)>,
+    /// The team integration the operation belongs to. System integrations have none.
+    integration_id: Option<i32>,
     executor: OperationExecutor,
 }
 
 #[derive(Clone)]
 enum OperationExecutor {
-    OpenApiTool(Arc<dyn ToolDyn>),
+    OpenApiTool(Arc<crate::OpenApiTool>),
     OpenUrl,
 }
 
+/// Where calls that need approval are recorded.
+#[derive(Clone)]
+struct ApprovalContext {
+    pool: db::Pool,
+    sub: String,
+    conversation_id: i64,
+}
+
 #[derive(Clone)]
 struct IntegrationInfo {
     name: String,
@@ -48,6 +61,9 @@ struct IntegrationInfo {
 pub struct RuntimeFunctionRegistry {
     integrations: Vec<IntegrationInfo>,
     functions: HashMap<String, RuntimeOperation>,
+    policies: ApprovalPolicies,
+    approvals: Option<ApprovalContext>,
+    pending_approvals: Arc<Mutex<Vec<PendingApproval>>>,
 }
 
 impl RuntimeFunctionRegistry {
@@ -85,7 +101,13 @@ impl RuntimeFunctionRegistry {
 
         transaction.commit().await.map_err(|err| err.to_string())?;
 
-        Self::load_for_team(pool, sub, team_id).await
+        let mut registry = Self::load_for_team(pool, sub, team_id).await?;
+        registry.approvals = Some(ApprovalContext {
+            pool: pool.clone(),
+            sub: sub.to_string(),
+            conversation_id,
+        });
+        Ok(registry)
     }
 
     pub async fn load_for_team(pool: &db::Pool, sub: &str, team_id: i32) -> Result<Self, String> {
@@ -105,6 +127,11 @@ impl RuntimeFunctionRegistry {
             .all()
             .await
             .map_err(|err| err.to_string())?;
+        let policies = db::queries::tool_call_approvals::policies()
+            .bind(&transaction, &team_id)
+            .all()
+            .await
+            .map_err(|err| err.to_string())?;
 
         transaction.commit().await.map_err(|err| err.to_string())?;
 
@@ -131,7 +158,7 @@ impl RuntimeFunctionRegistry {
             let token_provider = system_spec
                 .api_key
                 .map(|key| Arc::new(crate::StaticTokenProvider::new(key)) as Arc<_>);
-            let tools = match openapi.create_tools(token_provider) {
+            let tools = match openapi.create_openapi_tools(token_provider) {
                 Ok(tools) => tools,
                 Err(err) => {
                     tracing::warn!(
@@ -149,7 +176,7 @@ impl RuntimeFunctionRegistry {
                     &format!("{}_{}", slug, tool.name()),
                     &mut used_function_names,
                 );
-                let operation = runtime_operation(operation_name.clone(), tool);
+                let operation = runtime_operation(operation_name.clone(), None, tool);
                 functions.insert(operation_name, operation.clone());
                 operations.push(operation);
             }
@@ -182,7 +209,7 @@ impl RuntimeFunctionRegistry {
                 &integration,
                 &openapi,
             );
-            let tools = match openapi.create_tools(token_provider) {
+            let tools = match openapi.create_openapi_tools(token_provider) {
                 Ok(tools) => tools,
                 Err(err) => {
                     tracing::warn!(
@@ -203,7 +230,11 @@ impl RuntimeFunctionRegistry {
                     &format!("{}_{}", slug, tool.name()),
                     &mut used_function_names,
                 );
-                let operation = runtime_operation(operation_name.clone(), tool);
+                let operation = runtime_operation(
+                    operation_name.clone(),
+                    Some(integration.integration_id),
+                    tool,
+                );
                 functions.insert(operation_name, operation.clone());
                 operations.push(operation);
             }
@@ -215,7 +246,9 @@ impl RuntimeFunctionRegistry {
             });
         }
 
-        Ok(Self::with_builtin_functions(integrations, functions))
+        let mut registry = Self::with_builtin_functions(integrations, functions);
+        registry.policies = ApprovalPolicies::new(policies);
+        Ok(registry)
     }
 
     fn with_builtin_functions(
@@ -224,15 +257,22 @@ impl RuntimeFunctionRegistry {
     ) -> Self {
         let web_operation = RuntimeOperation {
             function_name: WEB_FUNCTION_NAME.to_string(),
-            description: "Fetch and read text content from a URL supplied by the user.".to_string(),
+            description: "Fetch a URL supplied by the user and read it. Web pages are reduced to their main content as Markdown and PDFs to text. Long pages are returned a slice at a time: pass the returned next_offset as offset to keep reading. When a page doesn't fit in one call the full text is also saved under /home/user/output/web (see saved_to).".to_string(),
             parameters: json!({
                 "type": "object",
                 "properties": {
-                    "url": {"type": "string", "description": "The URL to fetch"}
+                    "url": {"type": "string", "description": "The URL to fetch"},
+                    "offset": {"type": "integer", "description": "Character offset to start reading from (default 0)"},
+                    "length": {"type": "integer", "description": format!(
+                        "Maximum characters to return (default {}, at most {})",
+                        crate::builtin_tools::web::DEFAULT_PAGE_CHARS,
+                        crate::builtin_tools::web::MAX_PAGE_CHARS
+                    )}
                 },
                 "required": ["url"]
             }),
             byte_parameters: Vec::new(),
+            integration_id: None,
             executor: OperationExecutor::OpenUrl,
         };
 
@@ -246,9 +286,56 @@ impl RuntimeFunctionRegistry {
         Self {
             integrations,
             functions,
+            policies: ApprovalPolicies::default(),
+            approvals: None,
+            pending_approvals: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
+    /// Calls made since the last take which are waiting for the user's approval.
+    pub fn take_pending_approvals(&self) -> Vec<PendingApproval> {
+        std::mem::take(&mut *self.pending_approvals.lock().unwrap())
+    }
+
+    /// Sends a call the user approved. The function must still resolve to the same
+    /// operation it did when the call was made, and the team mustn't have disabled it
+    /// since.
+    pub(crate) async fn replay(
+        &self,
+        function_name: &str,
+        operation_id: &str,
+        integration_id: Option<i32>,
+        arguments: &Value,
+    ) -> Result<Value, String> {
+        let tool = match self.functions.get(function_name) {
+            Some(RuntimeOperation {
+                integration_id: current_integration_id,
+                executor: OperationExecutor::OpenApiTool(tool),
+                ..
+            }) if tool.operation_id() == operation_id
+                && *current_integration_id == integration_id =>
+            {
+                tool
+            }
+            _ => {
+                return Err(format!(
+                    "{function_name} is no longer available, so the request was not sent"
+                ))
+            }
+        };
+        let method = tool.http_method().unwrap_or_default();
+        if self.policies.resolve(integration_id, operation_id, &method) == ToolCallPolicy::Deny {
+            return Err(format!(
+                "{function_name} has been disabled for this team, so the request was not sent"
+            ));
+        }
+        let result = tool
+            .call(arguments.to_string())
+            .await
+            .map_err(tool_error_to_string)?;
+        Ok(serde_json::from_str(&result).unwrap_or(Value::String(result)))
+    }
+
     pub fn external_function_names(&self) -> Vec<String> {
         let mut names = self.functions.keys().cloned().collect::<Vec<_>>();
         names.sort();
@@ -323,7 +410,7 @@ Use run_bash to list `/home/user/functions`, then cat the relevant `.md` file be
         };
 
         if !operation.byte_parameters.is_empty() {
-            let Some(fs) = fs else {
+            let Some(fs) = fs.as_ref() else {
                 return ExtFunctionResult::Error(value_error(
                     "file-backed functions require a Bashkit filesystem".to_string(),
                 ));
@@ -354,13 +441,30 @@ Use run_bash to list `/home/user/functions`, then cat the relevant `.md` file be
         }
 
         match &operation.executor {
-            OperationExecutor::OpenApiTool(tool) => match tool.call(arguments.to_string()).await {
-                Ok(result) => match serde_json::from_str::<Value>(&result) {
-                    Ok(value) => ExtFunctionResult::Return(json_to_monty(&value)),
-                    Err(_) => ExtFunctionResult::Return(MontyObject::String(result)),
-                },
-                Err(err) => ExtFunctionResult::Error(value_error(tool_error_to_string(err))),
-            },
+            OperationExecutor::OpenApiTool(tool) => {
+                let method = tool.http_method().unwrap_or_default();
+                match self
+                    .policies
+                    .resolve(operation.integration_id, tool.operation_id(), &method)
+                {
+                    ToolCallPolicy::Allow => {}
+                    ToolCallPolicy::Deny => {
+                        return ExtFunctionResult::Error(permission_error(format!(
+                            "{name} is disabled for this team"
+                        )))
+                    }
+                    ToolCallPolicy::Confirm => {
+                        return self.request_approval(operation, tool, &arguments).await
+                    }
+                }
+                match tool.call(arguments.to_string()).await {
+                    Ok(result) => match serde_json::from_str::<Value>(&result) {
+                        Ok(value) => ExtFunctionResult::Return(json_to_monty(&value)),
+                        Err(_) => ExtFunctionResult::Return(MontyObject::String(result)),
+                    },
+                    Err(err) => ExtFunctionResult::Error(value_error(tool_error_to_string(err))),
+                }
+            }
             OperationExecutor::OpenUrl => {
                 let Some(url) = arguments.get("url").and_then(Value::as_str) else {
                     return ExtFunctionResult::Error(value_error(
@@ -368,12 +472,82 @@ Use run_bash to list `/home/user/functions`, then cat the relevant `.md` file be
                     ));
                 };
 
-                match crate::builtin_tools::web::open_url(url.to_string()).await {
-                    Ok(content) => ExtFunctionResult::Return(json_to_monty(&json!({
-                        "content": content
-                    }))),
-                    Err(err) => ExtFunctionResult::Error(value_error(err.to_string())),
+                let offset = arguments.get("offset").and_then(Value::as_u64).unwrap_or(0) as usize;
+                let length = arguments
+                    .get("length")
+                    .and_then(Value::as_u64)
+                    .map(|length| length as usize)
+                    .unwrap_or(crate::builtin_tools::web::DEFAULT_PAGE_CHARS);
+
+                let page = match crate::builtin_tools::web::open_url(url.to_string()).await {
+                    Ok(page) => page,
+                    Err(err) => return ExtFunctionResult::Error(value_error(err.to_string())),
+                };
+                let mut result = page.slice(offset, length);
+                if result.get("next_offset").is_some() || offset > 0 {
+                    if let Some(fs) = fs {
+                        let path = web_output_path(&page.url);
+                        match super::bashkit::write_vfs_file(
+                            fs.as_ref(),
+                            &path,
+                            page.content.as_bytes(),
+                        )
+                        .await
+                        {
+                            Ok(()) => result["saved_to"] = Value::String(path),
+                            Err(err) => tracing::warn!("Failed to save web page: {err}"),
+                        }
+                    }
                 }
+                ExtFunctionResult::Return(json_to_monty(&result))
+            }
+        }
+    }
+
+    /// Records the exact request for the user to approve and stops the script, so
+    /// nothing that depends on the call runs with a made up result.
+    async fn request_approval(
+        &self,
+        operation: &RuntimeOperation,
+        tool: &crate::OpenApiTool,
+        arguments: &Value,
+    ) -> ExtFunctionResult {
+        let Some(context) = &self.approvals else {
+            return ExtFunctionResult::Error(permission_error(format!(
+                "{} needs the user's approval, which isn't available here",
+                operation.function_name
+            )));
+        };
+        let preview = match tool.preview_request(arguments) {
+            Ok(preview) => preview,
+            Err(err) => return ExtFunctionResult::Error(value_error(err.to_string())),
+        };
+        let request = ApprovalRequest {
+            function_name: &operation.function_name,
+            operation_id: tool.operation_id(),
+            integration_id: operation.integration_id,
+            preview: &preview,
+            arguments,
+        };
+        match approvals::record(
+            &context.pool,
+            &context.sub,
+            context.conversation_id,
+            request,
+        )
+        .await
+        {
+            Ok(pending) => {
+                let message = format!(
+                    "{} {} is waiting for the user's approval (request {}). It has not been sent. \
+Stop and tell the user; the outcome will be added to this tool result once they decide.",
+                    pending.method, pending.url, pending.approval_id
+                );
+                self.pending_approvals.lock().unwrap().push(pending);
+                ExtFunctionResult::Error(permission_error(message))
+            }
+            Err(err) => {
+                ExtFunctionResult::Error(value_error(format!("failed to request approval: {err}")))
             }
         }
     }
@@ -437,7 +611,25 @@ fn function_markdown(integration: &IntegrationInfo) -> String {
     markdown
 }
 
-fn runtime_operation(function_name: String, tool: Arc<dyn ToolDyn>) -> RuntimeOperation {
+/// Where the full text of a page too long to return in one call is saved.
+fn web_output_path(url: &str) -> String {
+    let host = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| "page".to_string());
+    let host: String = host
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let digest = format!("{:x}", md5::compute(url.as_bytes()));
+    format!("{WEB_OUTPUT_DIR}/{host}-{}.md", &digest[..8])
+}
+
+fn runtime_operation(
+    function_name: String,
+    integration_id: Option<i32>,
+    tool: Arc<crate::OpenApiTool>,
+) -> RuntimeOperation {
     let original_parameters = tool.parameters();
     let (parameters, byte_parameters) = expose_file_parameters(original_parameters);
     RuntimeOperation {
@@ -445,6 +637,7 @@ fn runtime_operation(function_name: String, tool: Arc<dyn ToolDyn>) -> RuntimeOp
         description: tool.description(),
         parameters,
         byte_parameters,
+        integration_id,
         executor: OperationExecutor::OpenApiTool(tool),
     }
 }
@@ -712,6 +905,10 @@ fn value_error(error: String) -> MontyException {
     MontyException::new(ExcType::ValueError, Some(error))
 }
 
+fn permission_error(error: String) -> MontyException {
+    MontyException::new(ExcType::PermissionError, Some(error))
+}
+
 fn tool_error_to_string(error: ToolError) -> String {
     match error {
         ToolError::JsonError(err) => err.to_string(),
@@ -740,10 +937,26 @@ mod tests {
             .expect("expected web catalogue file");
         let markdown = String::from_utf8(web_file.contents.clone()).unwrap();
         assert!(markdown.contains("web_open_url"));
-        assert!(markdown.contains("parameters: url"));
+        assert!(markdown.contains("parameters: length, offset, url"));
         assert!(markdown.contains("do not use `from functions import ...`"));
     }
 
+    #[test]
+    fn web_pages_are_saved_under_the_output_directory() {
+        let path = web_output_path("https://docs.example.com/guide?page=2");
+
+        assert!(path.starts_with("/home/user/output/web/docs-example-com-"));
+        assert!(path.ends_with(".md"));
+        assert_eq!(
+            path,
+            web_output_path("https://docs.example.com/guide?page=2")
+        );
+        assert_ne!(
+            path,
+            web_output_path("https://docs.example.com/guide?page=3")
+        );
+    }
+
     #[test]
     fn function_catalogue_summarizes_integrations_without_schemas() {
         let operation = RuntimeOperation {
@@ -751,6 +964,7 @@ mod tests {
             description: "List recent enterprise email messages".to_string(),
             parameters: json!({"type": "object"}),
             byte_parameters: Vec::new(),
+            integration_id: None,
             executor: OperationExecutor::OpenUrl,
         };
         let registry = RuntimeFunctionRegistry::with_builtin_functions(
@@ -796,6 +1010,7 @@ mod tests {
                 }
             }),
             byte_parameters: vec![("files".to_string(), "file_path".to_string())],
+            integration_id: None,
             executor: OperationExecutor::OpenUrl,
         };
         let registry = RuntimeFunctionRegistry::with_builtin_functions(
@@ -851,4 +1066,50 @@ mod tests {
 
         assert_eq!(args["url"], "https://example.com");
     }
+
+    #[tokio::test]
+    async fn approved_calls_are_not_sent_once_the_operation_is_denied() {
+        let spec = serde_json::from_value(json!({
+            "openapi": "3.0.3",
+            "info": {"title": "Tickets", "version": "1.0.0"},
+            "paths": {
+                "/tickets": {
+                    "post": {
+                        "operationId": "createTicket",
+                        "responses": {"200": {"description": "Created"}}
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        let tool = crate::OpenApiTool::new(
+            crate::types::ToolDefinition {
+                name: "createTicket".to_string(),
+                description: "Create a ticket".to_string(),
+                parameters: json!({"type": "object"}),
+            },
+            "http://127.0.0.1:1".to_string(),
+            spec,
+            "createTicket".to_string(),
+            "Authorization".to_string(),
+            None,
+        );
+        let operation =
+            runtime_operation("tickets_createticket".to_string(), Some(7), Arc::new(tool));
+        let mut registry = RuntimeFunctionRegistry::with_builtin_functions(
+            Vec::new(),
+            HashMap::from([("tickets_createticket".to_string(), operation)]),
+        );
+        registry.policies = ApprovalPolicies::new(vec![db::ToolCallPolicyRow {
+            integration_id: 7,
+            operation_id: "createTicket".to_string(),
+            policy: ToolCallPolicy::Deny,
+        }]);
+
+        let err = registry
+            .replay("tickets_createticket", "createTicket", Some(7), &json!({}))
+            .await
+            .unwrap_err();
+        assert!(err.contains("has been disabled"), "{err}");
+    }
 }
@@ -18,6 +18,7 @@ use rig::wasm_compat::WasmBoxedFuture;
 use serde_json::Value;
 use std::collections::HashSet;
 use std::sync::{Arc, Mutex, OnceLock};
+use tracing::Instrument;
 
 #[derive(Clone, Debug)]
 pub enum HttpRequestBody {
@@ -209,6 +210,42 @@ impl OpenApiTool {
         ))
     }
 
+    /// The operation id this tool calls.
+    pub fn operation_id(&self) -> &str {
+        &self.operation_id
+    }
+
+    /// The HTTP method of the operation, e.g. `POST`.
+    pub fn http_method(&self) -> Option<String> {
+        self.find_operation_details()
+            .ok()
+            .map(|(_, method, _)| method.to_uppercase())
+    }
+
+    /// Resolve the request that `call` would send for these arguments, without
+    /// sending it. Credentials are not included.
+    pub fn preview_request(&self, arguments: &Value) -> Result<RequestPreview, Value> {
+        let request = prepare_request(self, arguments)?;
+        let body = request.body.map(|body| match body {
+            HttpRequestBody::Json(value) => value,
+            HttpRequestBody::Multipart { fields, files } => serde_json::json!({
+                "fields": fields
+                    .into_iter()
+                    .map(|(name, value)| (name, Value::String(value)))
+                    .collect::<serde_json::Map<_, _>>(),
+                "files": files
+                    .into_iter()
+                    .map(|(name, bytes)| serde_json::json!({"name": name, "bytes": bytes.len()}))
+                    .collect::<Vec<_>>()
+            }),
+        });
+        Ok(RequestPreview {
+            method: request.method.to_string(),
+            url: request.url.to_string(),
+            body,
+        })
+    }
+
     /// Produce Authorization header if bearer token is present
     async fn build_auth_header(&self) -> Option<(String, String)> {
         if let Some(provider) = &self.token_provider {
@@ -237,16 +274,22 @@ impl OpenApiTool {
     }
 }
 
-async fn execute_openapi_tool(
-    tool: &OpenApiTool,
-    arguments: &Value,
-) -> Result<serde_json::Value, serde_json::Value> {
-    tracing::info!(
-        "Executing OpenAPI tool {} with arguments: {}",
-        tool.name(),
-        arguments
-    );
+/// The request described by a tool call, ready to send.
+struct PreparedRequest {
+    method: Method,
+    url: Url,
+    body: Option<HttpRequestBody>,
+}
+
+/// What a tool call would send, for showing to a user before it runs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestPreview {
+    pub method: String,
+    pub url: String,
+    pub body: Option<Value>,
+}
 
+fn prepare_request(tool: &OpenApiTool, arguments: &Value) -> Result<PreparedRequest, Value> {
     // Find operation details by operation_id
     let (path, method, operation) = tool
         .find_operation_details()
@@ -287,7 +330,6 @@ async fn execute_openapi_tool(
             }
         }
     }
-    tracing::debug!("Making request to URL: {} using method: {}", url, method);
 
     // Determine if we should send a request body
     let body_obj = request_body_params.as_object();
@@ -337,14 +379,74 @@ async fn execute_openapi_tool(
         None
     };
 
-    let mut response = tool
-        .client
-        .send(
-            http_method.clone(),
-            url.clone(),
-            tool.collect_headers().await,
-            body.clone(),
-        )
+    Ok(PreparedRequest {
+        method: http_method,
+        url,
+        body,
+    })
+}
+
+/// Sends the request in its own span and passes the trace on, so the integration's spans
+/// join the conversation's trace.
+async fn send_request(
+    tool: &OpenApiTool,
+    method: Method,
+    url: Url,
+    body: Option<HttpRequestBody>,
+) -> Result<HttpResponse, String> {
+    let span = tracing::info_span!(
+        "http.client",
+        otel.name = %method,
+        otel.kind = "client",
+        otel.status_code = tracing::field::Empty,
+        http.request.method = %method,
+        server.address = url.host_str().unwrap_or_default(),
+        http.response.status_code = tracing::field::Empty,
+    );
+    let response = async {
+        let mut headers = tool.collect_headers().await;
+        headers.extend(observability::trace_headers());
+        tool.client.send(method, url, headers, body).await
+    }
+    .instrument(span.clone())
+    .await;
+
+    match &response {
+        Ok(response) => {
+            span.record("http.response.status_code", response.status.as_u16());
+            if response.status.is_server_error() {
+                span.record("otel.status_code", "ERROR");
+            }
+        }
+        Err(_) => {
+            span.record("otel.status_code", "ERROR");
+        }
+    }
+    response
+}
+
+async fn execute_openapi_tool(
+    tool: &OpenApiTool,
+    arguments: &Value,
+) -> Result<serde_json::Value, serde_json::Value> {
+    tracing::info!(
+        "Executing OpenAPI tool {} with arguments: {}",
+        tool.name(),
+        arguments
+    );
+
+    let PreparedRequest {
+        method: http_method,
+        url,
+        body,
+    } = prepare_request(tool, arguments)?;
+    tracing::debug!(
+        "Making request to URL: {} using method: {}",
+        url,
+        http_method
+    );
+
+    let mut response = send_request(tool, http_method.clone(), url.clone(), body.clone())
         .await
         .map_err(|e| crate::json_error("Failed to make request", e))?;
 
@@ -352,9 +454,7 @@ async fn execute_openapi_tool(
         if let Some(provider) = &tool.token_provider {
             tracing::info!("Received 401 response; forcing token refresh and retrying");
             provider.force_refresh().await;
-            response = tool
-                .client
-                .send(http_method, url, tool.collect_headers().await, body.clone())
+            response = send_request(tool, http_method, url, body.clone())
                 .await
                 .map_err(|e| crate::json_error("Failed to make request", e))?;
         }
@@ -669,6 +769,37 @@ mod tests {
         assert_eq!(tool.name(), "createUser");
     }
 
+    #[test]
+    fn test_preview_request_resolves_url_and_body() {
+        let spec = create_uk_police_api_spec();
+        let tool_def = ToolDefinition {
+            name: "getPoliceForceDetails".to_string(),
+            description: "Get details of a UK police force".to_string(),
+            parameters: json!({}),
+        };
+
+        let tool = OpenApiTool::new(
+            tool_def,
+            "https://data.police.uk".to_string(),
+            spec,
+            "getPoliceForceDetails".to_string(),
+            "Authorization".to_string(),
+            Some(Arc::new(StaticTokenProvider::new("secret".to_string()))),
+        );
+
+        let preview = tool
+            .preview_request(&json!({"id": "leicestershire", "note": "hi"}))
+            .unwrap();
+        assert_eq!(tool.http_method().as_deref(), Some("GET"));
+        assert_eq!(preview.method, "GET");
+        assert_eq!(
+            preview.url,
+            "https://data.police.uk/api/forces/leicestershire"
+        );
+        assert_eq!(preview.body, Some(json!({"note": "hi"})));
+        assert!(!format!("{preview:?}").contains("secret"));
+    }
+
     #[test]
     fn test_substitute_path_parameters() {
         let spec = create_uk_police_api_spec();
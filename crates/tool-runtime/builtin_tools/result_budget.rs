@@ -0,0 +1,226 @@
+//! Keeps run_bash results within a token budget so a huge command output can't push the
+//! conversation out of the model's context.
+//!
+//! The budget is a share of the assistant model's context size. Output over the budget is
+//! written to `/home/user/output/tool-results` and the result keeps a head and tail
+//! preview that points at the file.
+
+use crate::builtin_tools::bashkit::write_vfs_file;
+use crate::token_count::token_count_from_string;
+use bashkit::FileSystem;
+use db::{queries, Pool};
+use serde_json::{json, Value};
+
+const SPILL_DIR: &str = "/home/user/output/tool-results";
+/// Tool results may use this fraction of the model's context.
+const CONTEXT_FRACTION: usize = 8;
+const MIN_RESULT_TOKENS: usize = 256;
+const MAX_RESULT_TOKENS: usize = 32_000;
+/// The most of a spilled output that is kept inline.
+const MAX_PREVIEW_TOKENS: usize = 1_000;
+
+/// A stream after it has been fitted into the budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CappedOutput {
+    pub(crate) text: String,
+    pub(crate) tokens: usize,
+    pub(crate) spilled_to: Option<String>,
+}
+
+impl CappedOutput {
+    /// Adds the fields describing a spilled stream to a run_bash response.
+    pub(crate) fn annotate(&self, stream: &str, response: &mut Value) {
+        if let Some(path) = &self.spilled_to {
+            response[format!("{stream}_spilled_to")] = json!(path);
+            response[format!("{stream}_tokens")] = json!(self.tokens);
+        }
+    }
+}
+
+/// The token budget for tool results of a model with the given context size.
+pub(crate) fn budget_for_context(context_size: usize) -> usize {
+    (context_size / CONTEXT_FRACTION).clamp(MIN_RESULT_TOKENS, MAX_RESULT_TOKENS)
+}
+
+/// The token budget for tool results of the assistant's model.
+pub(crate) async fn token_budget(
+    pool: &Pool,
+    sub: &str,
+    prompt_id: i32,
+) -> Result<usize, serde_json::Value> {
+    let mut client = pool
+        .get()
+        .await
+        .map_err(|e| json!({"error": "Failed to get DB client", "details": e.to_string()}))?;
+    let transaction = client
+        .transaction()
+        .await
+        .map_err(|e| json!({"error": "Failed to start transaction", "details": e.to_string()}))?;
+
+    db::authz::set_row_level_security_user_id(&transaction, sub.to_string())
+        .await
+        .map_err(|e| json!({"error": "Failed to set RLS", "details": e.to_string()}))?;
+
+    let context_size = queries::prompts::model_context_size()
+        .bind(&transaction, &prompt_id)
+        .opt()
+        .await
+        .map_err(
+            |e| json!({"error": "Failed to get model context size", "details": e.to_string()}),
+        )?
+        .unwrap_or(0);
+
+    transaction
+        .commit()
+        .await
+        .map_err(|e| json!({"error": "Failed to commit transaction", "details": e.to_string()}))?;
+
+    Ok(budget_for_context(context_size.max(0) as usize))
+}
+
+/// Fits one output stream into the budget, spilling the full text to a file when it
+/// doesn't fit.
+pub(crate) async fn cap(
+    fs: &dyn FileSystem,
+    stream: &str,
+    text: String,
+    budget: usize,
+) -> CappedOutput {
+    // Every token covers at least one byte, so short outputs don't need counting.
+    if text.len() <= budget {
+        return CappedOutput {
+            text,
+            tokens: 0,
+            spilled_to: None,
+        };
+    }
+    let tokens = token_count_from_string(&text).max(0) as usize;
+    if tokens <= budget {
+        return CappedOutput {
+            text,
+            tokens,
+            spilled_to: None,
+        };
+    }
+
+    let path = format!(
+        "{SPILL_DIR}/{}-{stream}.txt",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S%3f")
+    );
+    let spilled_to = match write_vfs_file(fs, &path, text.as_bytes()).await {
+        Ok(()) => Some(path),
+        Err(err) => {
+            tracing::warn!("Failed to spill {stream}: {err}");
+            None
+        }
+    };
+
+    let (head, tail) = head_and_tail(&text, tokens, budget.min(MAX_PREVIEW_TOKENS));
+    let omitted = match &spilled_to {
+        Some(path) => format!(
+            "[... {stream} is {tokens} tokens, too long to show in full. It is saved in {path}; read it with head, tail, grep or sed ...]"
+        ),
+        None => format!("[... {stream} is {tokens} tokens, too long to show in full ...]"),
+    };
+
+    CappedOutput {
+        text: format!("{head}\n{omitted}\n{tail}"),
+        tokens,
+        spilled_to,
+    }
+}
+
+/// The start and end of a text, about `preview_tokens` in total, cut at line breaks
+/// where there is one nearby.
+fn head_and_tail(text: &str, tokens: usize, preview_tokens: usize) -> (&str, &str) {
+    let chars_per_token = text.len() as f64 / tokens.max(1) as f64;
+    let head_bytes = ((preview_tokens * 2 / 3) as f64 * chars_per_token) as usize;
+    let tail_bytes = ((preview_tokens / 3) as f64 * chars_per_token) as usize;
+
+    let mut head_end = floor_char_boundary(text, head_bytes);
+    if let Some(newline) = text[..head_end].rfind('\n') {
+        if newline > head_end / 2 {
+            head_end = newline;
+        }
+    }
+
+    let mut tail_start = floor_char_boundary(text, text.len().saturating_sub(tail_bytes));
+    tail_start = tail_start.max(head_end);
+    if let Some(newline) = text[tail_start..].find('\n') {
+        if newline < (text.len() - tail_start) / 2 {
+            tail_start += newline + 1;
+        }
+    }
+
+    (&text[..head_end], &text[tail_start..])
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bashkit::InMemoryFs;
+    use std::path::Path;
+
+    #[test]
+    fn budget_scales_with_context_size() {
+        assert_eq!(budget_for_context(0), MIN_RESULT_TOKENS);
+        assert_eq!(budget_for_context(128_000), 16_000);
+        assert_eq!(budget_for_context(1_000_000), MAX_RESULT_TOKENS);
+    }
+
+    #[tokio::test]
+    async fn small_outputs_are_left_alone() {
+        let fs = InMemoryFs::new();
+        let capped = cap(&fs, "stdout", "hello\n".to_string(), 256).await;
+
+        assert_eq!(capped.text, "hello\n");
+        assert!(capped.spilled_to.is_none());
+    }
+
+    #[tokio::test]
+    async fn large_outputs_spill_to_a_file_with_a_preview() {
+        let fs = InMemoryFs::new();
+        let text = (0..5_000)
+            .map(|line| format!("line {line} of the build log"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let capped = cap(&fs, "stdout", text.clone(), 300).await;
+
+        let path = capped.spilled_to.clone().expect("expected a spill file");
+        assert!(path.starts_with("/home/user/output/tool-results/"));
+        assert!(path.ends_with("-stdout.txt"));
+        assert_eq!(
+            fs.read_file(Path::new(&path)).await.unwrap(),
+            text.as_bytes()
+        );
+
+        assert!(capped.text.starts_with("line 0 of the build log\n"));
+        assert!(capped.text.ends_with("line 4999 of the build log"));
+        assert!(capped.text.contains(&path));
+        assert!(token_count_from_string(&capped.text) < 400);
+
+        let mut response = json!({});
+        capped.annotate("stdout", &mut response);
+        assert_eq!(response["stdout_spilled_to"], path);
+        assert_eq!(response["stdout_tokens"], capped.tokens);
+    }
+
+    #[test]
+    fn preview_respects_char_boundaries() {
+        let text = "é".repeat(1_000);
+        let (head, tail) = head_and_tail(&text, 1_000, 90);
+
+        assert!(!head.is_empty());
+        assert!(!tail.is_empty());
+        assert!(head.len() + tail.len() < text.len());
+    }
+}
@@ -1,10 +1,24 @@
+//! The reader behind `web_open_url`.
+//!
+//! HTML pages go through readability-style main content extraction and come back as
+//! Markdown, PDFs are converted to text and other text responses are returned as is.
+//! Extracted pages are cached for a short while so reading a long page a slice at a time
+//! only fetches it once. Limits come from the environment (see [`WebFetchConfig`]).
+
+use dom_smoothie::{Config as ReadabilityConfig, Readability, TextMode};
 use futures_util::StreamExt;
 use reqwest::header::CONTENT_TYPE;
-use reqwest::Url;
+use reqwest::{redirect, Client, Url};
+use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
-const MAX_CONTENT_BYTES: usize = 1000; // final output limit
-const MAX_FETCH_BYTES: usize = 64 * 1024; // fetch enough HTML to reach body text
+/// Characters returned when the caller doesn't ask for a length.
+pub const DEFAULT_PAGE_CHARS: usize = 8_000;
+/// The most characters a single call returns.
+pub const MAX_PAGE_CHARS: usize = 32_000;
 
 /// Error type returned by the web tool
 #[derive(Debug)]
@@ -24,11 +38,213 @@ impl fmt::Display for WebToolError {
 
 impl std::error::Error for WebToolError {}
 
-/// Fetches the plain text content from the given URL
-pub async fn open_url(url: String) -> Result<String, WebToolError> {
+/// Limits for fetching pages, read from the environment once.
+///
+/// - `WEB_FETCH_MAX_REDIRECTS` (default 5)
+/// - `WEB_FETCH_MAX_BYTES` (default 5 MB)
+/// - `WEB_FETCH_TIMEOUT_SECS` (default 30)
+/// - `WEB_FETCH_CACHE_TTL_SECS` (default 300, 0 disables the cache)
+/// - `WEB_FETCH_CACHE_ENTRIES` (default 64)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebFetchConfig {
+    pub max_redirects: usize,
+    pub max_fetch_bytes: usize,
+    pub timeout: Duration,
+    pub cache_ttl: Duration,
+    pub cache_entries: usize,
+}
+
+impl Default for WebFetchConfig {
+    fn default() -> Self {
+        Self {
+            max_redirects: 5,
+            max_fetch_bytes: 5 * 1024 * 1024,
+            timeout: Duration::from_secs(30),
+            cache_ttl: Duration::from_secs(300),
+            cache_entries: 64,
+        }
+    }
+}
+
+impl WebFetchConfig {
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        let number = |name: &str| lookup(name).and_then(|value| value.trim().parse::<u64>().ok());
+
+        Self {
+            max_redirects: number("WEB_FETCH_MAX_REDIRECTS")
+                .map(|value| value as usize)
+                .unwrap_or(defaults.max_redirects),
+            max_fetch_bytes: number("WEB_FETCH_MAX_BYTES")
+                .map(|value| value as usize)
+                .unwrap_or(defaults.max_fetch_bytes),
+            timeout: number("WEB_FETCH_TIMEOUT_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.timeout),
+            cache_ttl: number("WEB_FETCH_CACHE_TTL_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.cache_ttl),
+            cache_entries: number("WEB_FETCH_CACHE_ENTRIES")
+                .map(|value| value as usize)
+                .unwrap_or(defaults.cache_entries),
+        }
+    }
+}
+
+/// The readable content of a page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebPage {
+    /// The URL after redirects.
+    pub url: String,
+    pub title: Option<String>,
+    pub content_type: String,
+    pub content: String,
+    /// Set when the response was larger than `WEB_FETCH_MAX_BYTES` and was cut off.
+    pub truncated: bool,
+}
+
+impl WebPage {
+    /// The number of characters in the content.
+    pub fn total_chars(&self) -> usize {
+        self.content.chars().count()
+    }
+
+    /// A window of the content as returned to the model, with the offset to continue from.
+    pub fn slice(&self, offset: usize, length: usize) -> Value {
+        let length = length.clamp(1, MAX_PAGE_CHARS);
+        let total = self.total_chars();
+        let content: String = self.content.chars().skip(offset).take(length).collect();
+        let end = (offset + length).min(total);
+
+        let mut value = json!({
+            "url": self.url,
+            "title": self.title,
+            "content_type": self.content_type,
+            "content": content,
+            "offset": offset.min(total),
+            "total_length": total,
+        });
+        if end < total {
+            value["next_offset"] = json!(end);
+        }
+        if self.truncated {
+            value["truncated"] = json!(true);
+        }
+        value
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ContentKind {
+    Html,
+    Pdf,
+    Text,
+}
+
+fn content_kind(content_type: &str) -> Option<ContentKind> {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    match mime.as_str() {
+        "" | "text/plain" => Some(ContentKind::Text),
+        "text/html" | "application/xhtml+xml" => Some(ContentKind::Html),
+        "application/pdf" => Some(ContentKind::Pdf),
+        "application/json" | "application/xml" | "application/javascript" => {
+            Some(ContentKind::Text)
+        }
+        _ if mime.starts_with("text/") || mime.ends_with("+json") || mime.ends_with("+xml") => {
+            Some(ContentKind::Text)
+        }
+        _ => None,
+    }
+}
+
+fn config() -> &'static WebFetchConfig {
+    static CONFIG: OnceLock<WebFetchConfig> = OnceLock::new();
+    CONFIG.get_or_init(WebFetchConfig::from_env)
+}
+
+fn client() -> Result<&'static Client, WebToolError> {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    if let Some(client) = CLIENT.get() {
+        return Ok(client);
+    }
+    let config = config();
+    let client = Client::builder()
+        .redirect(redirect::Policy::limited(config.max_redirects))
+        .timeout(config.timeout)
+        .user_agent(concat!("bionic-gpt/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| WebToolError::Request(e.to_string()))?;
+    Ok(CLIENT.get_or_init(|| client))
+}
+
+type PageCache = Mutex<HashMap<String, (Instant, Arc<WebPage>)>>;
+
+fn cache() -> &'static PageCache {
+    static CACHE: OnceLock<PageCache> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn cached_page(url: &str, config: &WebFetchConfig) -> Option<Arc<WebPage>> {
+    if config.cache_ttl.is_zero() {
+        return None;
+    }
+    let mut cache = cache().lock().unwrap();
+    match cache.get(url) {
+        Some((fetched_at, page)) if fetched_at.elapsed() < config.cache_ttl => {
+            Some(Arc::clone(page))
+        }
+        Some(_) => {
+            cache.remove(url);
+            None
+        }
+        None => None,
+    }
+}
+
+fn cache_page(url: &str, page: &Arc<WebPage>, config: &WebFetchConfig) {
+    if config.cache_ttl.is_zero() || config.cache_entries == 0 {
+        return;
+    }
+    let mut cache = cache().lock().unwrap();
+    cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < config.cache_ttl);
+    while cache.len() >= config.cache_entries {
+        let Some(oldest) = cache
+            .iter()
+            .min_by_key(|(_, (fetched_at, _))| *fetched_at)
+            .map(|(url, _)| url.clone())
+        else {
+            break;
+        };
+        cache.remove(&oldest);
+    }
+    cache.insert(url.to_string(), (Instant::now(), Arc::clone(page)));
+}
+
+/// Fetches a URL and extracts its readable content.
+pub async fn open_url(url: String) -> Result<Arc<WebPage>, WebToolError> {
     let parsed = Url::parse(&url).map_err(|_| WebToolError::InvalidUrl(url.clone()))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(WebToolError::InvalidUrl(url));
+    }
+
+    let config = config();
+    if let Some(page) = cached_page(parsed.as_str(), config) {
+        return Ok(page);
+    }
 
-    let response = reqwest::get(parsed)
+    let response = client()?
+        .get(parsed.clone())
+        .send()
         .await
         .map_err(|e| WebToolError::Request(e.to_string()))?;
 
@@ -36,6 +252,7 @@ pub async fn open_url(url: String) -> Result<String, WebToolError> {
         return Err(WebToolError::Request(format!("HTTP {}", response.status())));
     }
 
+    let final_url = response.url().to_string();
     let content_type = response
         .headers()
         .get(CONTENT_TYPE)
@@ -43,50 +260,85 @@ pub async fn open_url(url: String) -> Result<String, WebToolError> {
         .unwrap_or("")
         .to_ascii_lowercase();
 
-    let is_html = content_type.contains("text/html");
-    let is_text = content_type.starts_with("text/") || content_type.is_empty();
-
-    if !is_html && !is_text {
+    let Some(kind) = content_kind(&content_type) else {
         return Err(WebToolError::Request(format!(
             "Unsupported content type: {}",
             content_type
         )));
-    }
+    };
 
     let mut stream = response.bytes_stream();
     let mut buffer: Vec<u8> = Vec::new();
+    let mut truncated = false;
     while let Some(chunk) = stream.next().await {
         let chunk = chunk.map_err(|e| WebToolError::Request(e.to_string()))?;
-        if buffer.len() + chunk.len() > MAX_FETCH_BYTES {
-            let remaining = MAX_FETCH_BYTES - buffer.len();
+        if buffer.len() + chunk.len() > config.max_fetch_bytes {
+            let remaining = config.max_fetch_bytes - buffer.len();
             buffer.extend_from_slice(&chunk[..remaining]);
+            truncated = true;
             break;
         } else {
             buffer.extend_from_slice(&chunk);
         }
     }
 
-    let body = String::from_utf8_lossy(&buffer).to_string();
-
-    let parsed = if is_html {
-        html2text::from_read(body.as_bytes(), 120)
-            .map_err(|e| WebToolError::Request(e.to_string()))?
-    } else {
-        body
+    let (title, content) = match kind {
+        ContentKind::Html => {
+            let html = String::from_utf8_lossy(&buffer).to_string();
+            extract_html(&html, &final_url)?
+        }
+        ContentKind::Pdf => {
+            if truncated {
+                return Err(WebToolError::Request(format!(
+                    "PDF is larger than {} bytes",
+                    config.max_fetch_bytes
+                )));
+            }
+            (None, extract_pdf(buffer).await?)
+        }
+        ContentKind::Text => (None, String::from_utf8_lossy(&buffer).to_string()),
     };
 
-    Ok(truncate_bytes(parsed, MAX_CONTENT_BYTES))
+    let page = Arc::new(WebPage {
+        url: final_url,
+        title,
+        content_type,
+        content,
+        truncated,
+    });
+    cache_page(parsed.as_str(), &page, config);
+    Ok(page)
 }
 
-fn truncate_bytes(mut text: String, max_bytes: usize) -> String {
-    if text.len() <= max_bytes {
-        return text;
-    }
+/// Extracts the main content of an HTML page as Markdown, falling back to the whole
+/// page as text when no article can be found.
+fn extract_html(html: &str, url: &str) -> Result<(Option<String>, String), WebToolError> {
+    let readability_config = ReadabilityConfig {
+        text_mode: TextMode::Markdown,
+        ..Default::default()
+    };
+    let article = Readability::new(html, Some(url), Some(readability_config))
+        .and_then(|mut readability| readability.parse());
 
-    while text.len() > max_bytes {
-        text.pop();
+    match article {
+        Ok(article) if !article.text_content.trim().is_empty() => {
+            let title = Some(article.title).filter(|title| !title.trim().is_empty());
+            Ok((title, article.text_content.trim().to_string()))
+        }
+        _ => {
+            let text = html2text::from_read(html.as_bytes(), 120)
+                .map_err(|e| WebToolError::Request(e.to_string()))?;
+            Ok((None, text))
+        }
     }
-    text
+}
+
+async fn extract_pdf(bytes: Vec<u8>) -> Result<String, WebToolError> {
+    // PDF parsing is CPU bound and can panic on malformed files, so keep it off the runtime.
+    tokio::task::spawn_blocking(move || pdf_extract::extract_text_from_mem(&bytes))
+        .await
+        .map_err(|_| WebToolError::Request("Unable to read PDF".to_string()))?
+        .map_err(|e| WebToolError::Request(format!("Unable to read PDF: {e}")))
 }
 
 #[cfg(test)]
@@ -97,5 +349,76 @@ mod tests {
     async fn test_open_url_invalid() {
         let result = open_url("not a url".to_string()).await;
         assert!(result.is_err());
+        let result = open_url("file:///etc/passwd".to_string()).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_extract_html_keeps_main_content_as_markdown() {
+        let paragraph = "Rust is a general purpose programming language that emphasises performance, type safety and concurrency. ";
+        let html = format!(
+            "<html><head><title>About Rust</title></head><body>\
+<nav><a href=\"/\">Home</a> <a href=\"/about\">About</a></nav>\
+<article><h1>About Rust</h1><h2>History</h2><p>{}</p><p>{}</p><p>It has <strong>no garbage collector</strong>.</p></article>\
+<footer>Copyright and cookie settings</footer></body></html>",
+            paragraph.repeat(4),
+            paragraph.repeat(4)
+        );
+
+        let (title, content) = extract_html(&html, "https://example.com/rust").unwrap();
+
+        assert_eq!(title.as_deref(), Some("About Rust"));
+        assert!(content.contains("## History"));
+        assert!(content.contains("**no garbage collector**"));
+        assert!(!content.contains("cookie settings"));
+    }
+
+    #[test]
+    fn test_slice_pages_through_content() {
+        let page = WebPage {
+            url: "https://example.com".to_string(),
+            title: None,
+            content_type: "text/plain".to_string(),
+            content: "abcdefghij".to_string(),
+            truncated: false,
+        };
+
+        let first = page.slice(0, 4);
+        assert_eq!(first["content"], "abcd");
+        assert_eq!(first["next_offset"], 4);
+        assert_eq!(first["total_length"], 10);
+
+        let last = page.slice(8, 4);
+        assert_eq!(last["content"], "ij");
+        assert!(last.get("next_offset").is_none());
+    }
+
+    #[test]
+    fn test_content_kinds() {
+        assert_eq!(
+            content_kind("text/html; charset=utf-8"),
+            Some(ContentKind::Html)
+        );
+        assert_eq!(content_kind("application/pdf"), Some(ContentKind::Pdf));
+        assert_eq!(content_kind("application/ld+json"), Some(ContentKind::Text));
+        assert_eq!(content_kind(""), Some(ContentKind::Text));
+        assert_eq!(content_kind("image/png"), None);
+    }
+
+    #[test]
+    fn test_config_from_env_lookup() {
+        let config = WebFetchConfig::from_lookup(|name| match name {
+            "WEB_FETCH_MAX_REDIRECTS" => Some("2".to_string()),
+            "WEB_FETCH_CACHE_TTL_SECS" => Some("0".to_string()),
+            "WEB_FETCH_MAX_BYTES" => Some("not a number".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(config.max_redirects, 2);
+        assert!(config.cache_ttl.is_zero());
+        assert_eq!(
+            config.max_fetch_bytes,
+            WebFetchConfig::default().max_fetch_bytes
+        );
     }
 }
@@ -0,0 +1,361 @@
+//! Opt-in persistent workspace for run_bash. When an assistant enables it, the contents of
+//! /home/user/work and the shell state (environment, variables, cwd) are snapshotted to object
+//! storage after every call and restored before the next one in the same conversation.
+use super::bashkit::{
+    conversation_owner_and_team_id, db_conversation_id, write_vfs_file, HOME_DIR,
+};
+use base64::Engine;
+use bashkit::{Bash, FileType, ShellState};
+use db::{queries, Pool};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+const WORK_DIR: &str = "/home/user/work";
+/// Total size of the files under /home/user/work that will be snapshotted.
+const MAX_WORKSPACE_BYTES: u64 = 25 * 1024 * 1024;
+const MAX_WORKSPACE_FILES: usize = 1_000;
+/// Snapshots expire this long after the last run_bash call that saved them.
+const WORKSPACE_TTL_SECONDS: f64 = 7.0 * 24.0 * 60.0 * 60.0;
+const SNAPSHOT_FILE_NAME: &str = "workspace.json";
+
+#[derive(Serialize, Deserialize)]
+struct WorkspaceSnapshot {
+    shell: ShellState,
+    directories: Vec<String>,
+    files: Vec<WorkspaceFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WorkspaceFile {
+    path: String,
+    mode: u32,
+    contents: String,
+}
+
+pub(crate) async fn is_enabled(
+    pool: &Pool,
+    sub: &str,
+    prompt_id: i32,
+) -> Result<bool, serde_json::Value> {
+    let mut client = pool
+        .get()
+        .await
+        .map_err(|e| json!({"error": "Failed to get DB client", "details": e.to_string()}))?;
+    let transaction = client
+        .transaction()
+        .await
+        .map_err(|e| json!({"error": "Failed to start transaction", "details": e.to_string()}))?;
+
+    db::authz::set_row_level_security_user_id(&transaction, sub.to_string())
+        .await
+        .map_err(|e| json!({"error": "Failed to set RLS", "details": e.to_string()}))?;
+
+    let enabled = queries::prompts::persistent_workspace()
+        .bind(&transaction, &prompt_id)
+        .opt()
+        .await
+        .map_err(|e| json!({"error": "Failed to get workspace setting", "details": e.to_string()}))?
+        .unwrap_or(false);
+
+    transaction
+        .commit()
+        .await
+        .map_err(|e| json!({"error": "Failed to commit transaction", "details": e.to_string()}))?;
+
+    Ok(enabled)
+}
+
+/// Seeds /home/user/work and the shell state from the conversation's latest snapshot, if it
+/// hasn't expired.
+pub(crate) async fn restore(
+    pool: &Pool,
+    sub: &str,
+    conversation_id: i64,
+    bash: &mut Bash,
+) -> Result<(), serde_json::Value> {
+    let conversation_id = db_conversation_id(conversation_id)?;
+    let mut client = pool
+        .get()
+        .await
+        .map_err(|e| json!({"error": "Failed to get DB client", "details": e.to_string()}))?;
+    let transaction = client
+        .transaction()
+        .await
+        .map_err(|e| json!({"error": "Failed to start transaction", "details": e.to_string()}))?;
+
+    db::authz::set_row_level_security_user_id(&transaction, sub.to_string())
+        .await
+        .map_err(|e| json!({"error": "Failed to set RLS", "details": e.to_string()}))?;
+
+    let workspace = queries::conversation_workspaces::snapshot()
+        .bind(&transaction, &conversation_id)
+        .opt()
+        .await
+        .map_err(|e| json!({"error": "Failed to get workspace", "details": e.to_string()}))?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|e| json!({"error": "Failed to commit transaction", "details": e.to_string()}))?;
+
+    bash.fs().mkdir(Path::new(WORK_DIR), true).await.map_err(
+        |e| json!({"error": "Failed to seed workspace directory", "details": e.to_string()}),
+    )?;
+
+    let Some(workspace) = workspace else {
+        return Ok(());
+    };
+    let snapshot: WorkspaceSnapshot = serde_json::from_slice(&workspace.object_data)
+        .map_err(|e| json!({"error": "Failed to read workspace", "details": e.to_string()}))?;
+    apply(snapshot, bash).await
+}
+
+/// Snapshots /home/user/work and the shell state, returning a summary for the tool response.
+/// A workspace over the quota is not saved, so the previous snapshot stays in place.
+pub(crate) async fn save(
+    pool: &Pool,
+    sub: &str,
+    conversation_id: i64,
+    bash: &Bash,
+) -> Result<Value, serde_json::Value> {
+    let conversation_id_i32 = db_conversation_id(conversation_id)?;
+    let (snapshot, size_bytes) = capture(bash, MAX_WORKSPACE_BYTES).await?;
+    let file_count = snapshot.files.len();
+    let bytes = serde_json::to_vec(&snapshot)
+        .map_err(|e| json!({"error": "Failed to serialize workspace", "details": e.to_string()}))?;
+    let hash = format!("{:x}", md5::compute(&bytes));
+
+    let mut client = pool
+        .get()
+        .await
+        .map_err(|e| json!({"error": "Failed to get DB client", "details": e.to_string()}))?;
+    let transaction = client
+        .transaction()
+        .await
+        .map_err(|e| json!({"error": "Failed to start transaction", "details": e.to_string()}))?;
+
+    db::authz::set_row_level_security_user_id(&transaction, sub.to_string())
+        .await
+        .map_err(|e| json!({"error": "Failed to set RLS", "details": e.to_string()}))?;
+
+    let (user_id, team_id) = conversation_owner_and_team_id(&transaction, conversation_id).await?;
+    let previous = queries::conversation_workspaces::previous_object()
+        .bind(&transaction, &conversation_id_i32)
+        .opt()
+        .await
+        .map_err(|e| json!({"error": "Failed to get workspace", "details": e.to_string()}))?;
+
+    // An unchanged workspace only needs its expiry pushed back. A new snapshot is stored in
+    // the same transaction as the row, and the old one is deleted by a trigger when the row
+    // moves to the new one.
+    let object_id = match &previous {
+        Some(previous) if previous.file_hash == hash => previous.object_id,
+        _ => object_storage::insert(&transaction, user_id, team_id, SNAPSHOT_FILE_NAME, &bytes)
+            .await
+            .map_err(|e| json!({"error": "Failed to store workspace", "details": e.to_string()}))?,
+    };
+
+    queries::conversation_workspaces::upsert()
+        .bind(
+            &transaction,
+            &object_id,
+            &(bytes.len() as i64),
+            &WORKSPACE_TTL_SECONDS,
+            &conversation_id,
+        )
+        .await
+        .map_err(|e| json!({"error": "Failed to save workspace", "details": e.to_string()}))?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|e| json!({"error": "Failed to commit transaction", "details": e.to_string()}))?;
+
+    Ok(json!({
+        "path": WORK_DIR,
+        "files": file_count,
+        "size_bytes": size_bytes,
+        "quota_bytes": MAX_WORKSPACE_BYTES,
+    }))
+}
+
+async fn capture(
+    bash: &Bash,
+    quota_bytes: u64,
+) -> Result<(WorkspaceSnapshot, u64), serde_json::Value> {
+    let fs = bash.fs();
+    fs.mkdir(Path::new(WORK_DIR), true).await.map_err(
+        |e| json!({"error": "Failed to inspect workspace directory", "details": e.to_string()}),
+    )?;
+
+    let mut pending = vec![PathBuf::from(WORK_DIR)];
+    let mut directories = Vec::new();
+    let mut entries = Vec::new();
+    let mut size_bytes = 0;
+
+    while let Some(dir) = pending.pop() {
+        for entry in fs.read_dir(&dir).await.map_err(
+            |e| json!({"error": "Failed to read workspace directory", "path": dir.display().to_string(), "details": e.to_string()}),
+        )? {
+            let path = dir.join(&entry.name);
+            if entry.metadata.file_type == FileType::Directory {
+                directories.push(path.to_string_lossy().to_string());
+                pending.push(path);
+            } else if entry.metadata.file_type == FileType::File {
+                size_bytes += entry.metadata.size;
+                entries.push((path, entry.metadata.mode));
+            }
+        }
+    }
+
+    if size_bytes > quota_bytes || entries.len() > MAX_WORKSPACE_FILES {
+        return Err(json!({
+            "error": "Workspace is over its quota and was not saved; remove files from /home/user/work",
+            "size_bytes": size_bytes,
+            "quota_bytes": quota_bytes,
+            "files": entries.len(),
+            "max_files": MAX_WORKSPACE_FILES
+        }));
+    }
+
+    let mut files = Vec::with_capacity(entries.len());
+    for (path, mode) in entries {
+        let bytes = fs.read_file(&path).await.map_err(
+            |e| json!({"error": "Failed to read workspace file", "path": path.display().to_string(), "details": e.to_string()}),
+        )?;
+        files.push(WorkspaceFile {
+            path: path.to_string_lossy().to_string(),
+            mode,
+            contents: base64::engine::general_purpose::STANDARD.encode(bytes),
+        });
+    }
+    directories.sort();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok((
+        WorkspaceSnapshot {
+            shell: bash.shell_state(),
+            directories,
+            files,
+        },
+        size_bytes,
+    ))
+}
+
+async fn apply(snapshot: WorkspaceSnapshot, bash: &mut Bash) -> Result<(), serde_json::Value> {
+    let fs = bash.fs();
+    for directory in snapshot
+        .directories
+        .iter()
+        .filter(|path| is_workspace_path(path))
+    {
+        fs.mkdir(Path::new(directory), true).await.map_err(
+            |e| json!({"error": "Failed to restore workspace directory", "details": e.to_string()}),
+        )?;
+    }
+    for file in snapshot
+        .files
+        .iter()
+        .filter(|file| is_workspace_path(&file.path))
+    {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&file.contents)
+            .map_err(
+                |e| json!({"error": "Failed to restore workspace file", "path": file.path, "details": e.to_string()}),
+            )?;
+        write_vfs_file(fs.as_ref(), &file.path, &bytes).await?;
+        let _ = fs.chmod(Path::new(&file.path), file.mode).await;
+    }
+
+    // The previous cwd may have been outside the workspace and not be recreated this call.
+    let mut shell = snapshot.shell;
+    if !fs.exists(&shell.cwd).await.unwrap_or(false) {
+        shell.cwd = PathBuf::from(HOME_DIR);
+    }
+    bash.restore_shell_state(&shell);
+    Ok(())
+}
+
+fn is_workspace_path(path: &str) -> bool {
+    path.starts_with(&format!("{WORK_DIR}/")) && !path.split('/').any(|part| part == "..")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bashkit::InMemoryFs;
+    use std::sync::Arc;
+
+    fn bash() -> Bash {
+        Bash::builder()
+            .fs(Arc::new(InMemoryFs::new()))
+            .cwd(HOME_DIR)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_workspace_round_trip_restores_files_env_and_cwd() {
+        let mut first = bash();
+        first.fs().mkdir(Path::new(WORK_DIR), true).await.unwrap();
+        first
+            .exec("mkdir -p work/data/empty && echo 'a,b' > work/data/input.csv && export STAGE=cleaned && cd work/data")
+            .await
+            .unwrap();
+
+        let (snapshot, size_bytes) = capture(&first, MAX_WORKSPACE_BYTES).await.unwrap();
+        assert_eq!(size_bytes, 4);
+        let bytes = serde_json::to_vec(&snapshot).unwrap();
+
+        let mut second = bash();
+        apply(serde_json::from_slice(&bytes).unwrap(), &mut second)
+            .await
+            .unwrap();
+        let result = second
+            .exec("pwd; echo $STAGE; cat input.csv; ls -d empty")
+            .await
+            .unwrap();
+
+        assert_eq!(result.stdout, "/home/user/work/data\ncleaned\na,b\nempty\n");
+    }
+
+    #[tokio::test]
+    async fn test_workspace_over_quota_is_not_captured() {
+        let first = bash();
+        first.fs().mkdir(Path::new(WORK_DIR), true).await.unwrap();
+        first
+            .fs()
+            .write_file(Path::new("/home/user/work/big.bin"), &[0; 64])
+            .await
+            .unwrap();
+
+        let error = capture(&first, 32).await.err().unwrap();
+
+        assert_eq!(error["size_bytes"], 64);
+        assert_eq!(error["quota_bytes"], 32);
+    }
+
+    #[tokio::test]
+    async fn test_missing_cwd_falls_back_to_home() {
+        let mut first = bash();
+        first
+            .exec("mkdir -p /tmp/scratch && cd /tmp/scratch")
+            .await
+            .unwrap();
+        let (snapshot, _) = capture(&first, MAX_WORKSPACE_BYTES).await.unwrap();
+
+        let mut second = bash();
+        apply(snapshot, &mut second).await.unwrap();
+        let result = second.exec("pwd").await.unwrap();
+
+        assert_eq!(result.stdout, "/home/user\n");
+    }
+
+    #[test]
+    fn test_is_workspace_path() {
+        assert!(is_workspace_path("/home/user/work/notes.txt"));
+        assert!(!is_workspace_path("/home/user/work"));
+        assert!(!is_workspace_path("/home/user/output/chart.png"));
+        assert!(!is_workspace_path("/home/user/work/../skills/x.md"));
+    }
+}
@@ -25,6 +25,7 @@ pub fn json_error(kind: &str, err: impl ToString) -> serde_json::Value {
 }
 
 // Re-export key types for convenience
+pub use builtin_tools::approvals::PendingApproval;
 pub use builtin_tools::openapi_tool_adapter::OpenApiTool;
 pub use openapi_tool_factory::{BionicOpenAPI, IntegrationTools, OAuth2Config};
 pub use tool_auth::{OAuth2TokenProvider, StaticTokenProvider, TokenProvider};
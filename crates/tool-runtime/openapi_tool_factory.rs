@@ -405,7 +405,20 @@ impl BionicOpenAPI {
         &self,
         token_provider: Option<Arc<dyn crate::tool_auth::TokenProvider>>,
     ) -> Result<Vec<Arc<dyn ToolDyn>>, String> {
-        let mut tools: Vec<Arc<dyn ToolDyn>> = Vec::new();
+        Ok(self
+            .create_openapi_tools(token_provider)?
+            .into_iter()
+            .map(|tool| tool as Arc<dyn ToolDyn>)
+            .collect())
+    }
+
+    /// Like `create_tools`, but keeps the concrete type so callers can inspect the
+    /// requests a tool would make.
+    pub fn create_openapi_tools(
+        &self,
+        token_provider: Option<Arc<dyn crate::tool_auth::TokenProvider>>,
+    ) -> Result<Vec<Arc<crate::OpenApiTool>>, String> {
+        let mut tools = Vec::new();
         let integration_tools = self.create_tool_definitions();
         let base_url = integration_tools
             .base_url
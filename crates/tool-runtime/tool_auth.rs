@@ -140,6 +140,16 @@ impl OAuth2TokenProvider {
             Ok(t) => t,
             Err(e) => {
                 tracing::error!("Failed to refresh token: {}", e);
+                // The connection needs the user to reconnect, let the team know.
+                let queued = db::queries::webhooks::enqueue_connection_failed()
+                    .bind(&transaction, &e.to_string(), &self.connection_id)
+                    .opt()
+                    .await;
+                if let Err(e) = queued {
+                    tracing::error!("Failed to queue connection webhook: {}", e);
+                } else if let Err(e) = transaction.commit().await {
+                    tracing::error!("Failed to commit connection webhook: {}", e);
+                }
                 return;
             }
         };
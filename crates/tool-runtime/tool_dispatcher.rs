@@ -5,7 +5,8 @@ use rig::tool::ToolDyn;
 use rig::OneOrMany;
 use serde_json::{json, Value};
 use std::sync::Arc;
-use tracing::{debug, error, info, trace, warn};
+use tracing::field::Empty;
+use tracing::{debug, error, info, trace, warn, Instrument, Span};
 
 /// Execute a tool call and return a message with the result
 pub async fn execute_tool_calls(
@@ -64,6 +65,19 @@ pub async fn execute_tool_call_with_tools(
     tools: &[Arc<dyn ToolDyn>],
     tool_call: &ToolCall,
 ) -> ToolResult {
+    let span = tracing::info_span!(
+        "gen_ai.execute_tool",
+        otel.name = format!("execute_tool {}", tool_call.function.name),
+        otel.status_code = Empty,
+        otel.status_message = Empty,
+        gen_ai.operation.name = "execute_tool",
+        gen_ai.tool.name = %tool_call.function.name,
+        gen_ai.tool.call.id = %tool_call.id,
+    );
+    call_tool(tools, tool_call).instrument(span).await
+}
+
+async fn call_tool(tools: &[Arc<dyn ToolDyn>], tool_call: &ToolCall) -> ToolResult {
     let tool_name = &tool_call.function.name;
     info!("Executing tool call: {}", tool_name);
     debug!("Tool call arguments: {}", tool_call.function.arguments);
@@ -89,15 +103,23 @@ pub async fn execute_tool_call_with_tools(
             };
         } else if let Err(e) = result {
             error!("Tool execution failed: {}", e);
+            record_failure(&e.to_string());
             return to_error_result(tool_call, json!({"error": e.to_string()}));
         }
     } else {
         warn!("Tool not found: {}", tool_name);
+        record_failure("Unknown tool");
     }
 
     to_error_result(tool_call, json!({"error": "Problem calling tool"}))
 }
 
+fn record_failure(message: &str) {
+    let span = Span::current();
+    span.record("otel.status_code", "ERROR");
+    span.record("otel.status_message", message);
+}
+
 fn to_error_result(tool_call: &ToolCall, error: Value) -> ToolResult {
     debug!("Returning error result for tool call");
     ToolResult {
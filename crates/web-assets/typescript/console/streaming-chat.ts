@@ -1,6 +1,9 @@
 // SSE event contract for `/completions/{chatId}`:
 // - { type: "text_delta", data: { delta: string } }
 // - { type: "done", data: {} }
+// - { type: "approval_required", data: { approvals: { approval_id: number, function_name: string, method: string, url: string }[] } }
+//   (integration calls are waiting for the user; the reloaded page shows the approval card)
+// - { type: "retracted", data: { message: string } } (output moderation withdrew the draft)
 // - { type: "error", data: { message: string } }
 //
 // The stream endpoint is backend-owned for persistence. This client only shows a
@@ -100,6 +103,24 @@ async function streamResult(chatId: string, element: HTMLElement) {
                 return true;
             }
 
+            if (json.type === 'approval_required') {
+                const approvals = Array.isArray(json?.data?.approvals) ? json.data.approvals : [];
+                const requests = approvals
+                    .map((approval: { method?: string; url?: string }) => `${approval.method ?? ''} ${approval.url ?? ''}`.trim())
+                    .join('\n');
+                appendText(`\n\nWaiting for your approval:\n${requests}`);
+                finalizeUiState();
+                return true;
+            }
+
+            if (json.type === 'retracted') {
+                const message = String(json?.data?.message ?? 'This response was withheld');
+                element.replaceChildren(document.createTextNode(message));
+                hasStarted = true;
+                finalizeUiState();
+                return true;
+            }
+
             if (json.type === 'error') {
                 const message = String(json?.data?.message ?? 'Unknown streaming error');
                 appendText(`\n\n${message}`);
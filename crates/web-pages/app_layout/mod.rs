@@ -20,8 +20,11 @@ pub enum SideBar {
     None,
     ApiKeys,
     AuditTrail,
+    Automations,
     Console,
+    Costs,
     Datasets,
+    DirectorySync,
     DocumentPipelines,
     Guardrails,
     History,
@@ -36,11 +39,15 @@ pub enum SideBar {
     Providers,
     Profile,
     RateLimits,
+    Retention,
+    Roles,
     Switch,
     Security,
     Skills,
     SystemPrompt,
+    Translations,
     WebSearch,
+    Webhooks,
 }
 
 impl std::fmt::Display for SideBar {
@@ -80,6 +80,7 @@ pub fn render(params: &SidebarParams, labels: &SidebarLabels) -> Element {
             }
         }
         if rbac.can_view_prompts()
+            || rbac.can_manage_automations()
             || (rbac.can_manage_projects() && params.enable_projects)
         {
             NavGroup {
@@ -95,6 +96,16 @@ pub fn render(params: &SidebarParams, labels: &SidebarLabels) -> Element {
                             disabled: setup_required
                         }
                     }
+                    if rbac.can_manage_automations() {
+                        NavItem {
+                            id: SideBar::Automations.to_string(),
+                            selected_item_id: selected_item.clone(),
+                            href: crate::routes::automations::Index { team_id: team_id.clone() },
+                            icon: nav_automations_svg.name,
+                            title: "Automations",
+                            disabled: setup_required
+                        }
+                    }
                     if rbac.can_manage_projects() && params.enable_projects {
                         NavItem {
                             id: SideBar::Projects.to_string(),
@@ -11,19 +11,31 @@ pub fn render(params: &SidebarParams, _labels: &SidebarLabels) -> Element {
     let setup_required = params.setup_required;
 
     rsx!(
-        if rbac.can_use_api_keys() {
+        if rbac.can_use_api_keys() || rbac.can_manage_webhooks() {
             NavGroup {
                 heading: "Developers",
                 content:  rsx!(
-                    NavItem {
-                        id: SideBar::ApiKeys.to_string(),
-                        selected_item_id: selected_item.clone(),
-                        href: crate::routes::api_keys::Index { team_id: team_id.clone() },
-                        icon: nav_api_keys_svg.name,
-                        title: "API Keys",
-                        disabled: setup_required
+                    if rbac.can_use_api_keys() {
+                        NavItem {
+                            id: SideBar::ApiKeys.to_string(),
+                            selected_item_id: selected_item.clone(),
+                            href: crate::routes::api_keys::Index { team_id: team_id.clone() },
+                            icon: nav_api_keys_svg.name,
+                            title: "API Keys",
+                            disabled: setup_required
+                        }
                     }
-                    if rbac.can_manage_document_pipelines() {
+                    if rbac.can_manage_webhooks() {
+                        NavItem {
+                            id: SideBar::Webhooks.to_string(),
+                            selected_item_id: selected_item.clone(),
+                            href: crate::routes::webhooks::Index { team_id: team_id.clone() },
+                            icon: nav_audit_svg.name,
+                            title: "Webhooks",
+                            disabled: setup_required
+                        }
+                    }
+                    if rbac.can_use_api_keys() && rbac.can_manage_document_pipelines() {
                         NavItem {
                             id: SideBar::DocumentPipelines.to_string(),
                             selected_item_id: selected_item.clone(),
@@ -36,17 +48,53 @@ pub fn render(params: &SidebarParams, _labels: &SidebarLabels) -> Element {
                 )
             }
         }
-        if rbac.can_view_teams() {
+        if rbac.can_view_teams()
+            || rbac.can_manage_roles()
+            || rbac.can_manage_retention()
+            || rbac.can_manage_budgets()
+        {
             NavGroup {
                 heading: "Collaboration",
                 content:  rsx!(
-                    NavItem {
-                        id: SideBar::Switch.to_string(),
-                        selected_item_id: selected_item.clone(),
-                        href: crate::routes::teams::Switch { team_id: team_id.clone() },
-                        icon: nav_teams_svg.name,
-                        title: "Teams",
-                        disabled: setup_required
+                    if rbac.can_view_teams() {
+                        NavItem {
+                            id: SideBar::Switch.to_string(),
+                            selected_item_id: selected_item.clone(),
+                            href: crate::routes::teams::Switch { team_id: team_id.clone() },
+                            icon: nav_teams_svg.name,
+                            title: "Teams",
+                            disabled: setup_required
+                        }
+                    }
+                    if rbac.can_manage_roles() {
+                        NavItem {
+                            id: SideBar::Roles.to_string(),
+                            selected_item_id: selected_item.clone(),
+                            href: crate::routes::roles::Index { team_id: team_id.clone() },
+                            icon: nav_teams_svg.name,
+                            title: "Roles",
+                            disabled: setup_required
+                        }
+                    }
+                    if rbac.can_manage_retention() {
+                        NavItem {
+                            id: SideBar::Retention.to_string(),
+                            selected_item_id: selected_item.clone(),
+                            href: crate::routes::retention::Index { team_id: team_id.clone() },
+                            icon: nav_audit_svg.name,
+                            title: "Data Retention",
+                            disabled: setup_required
+                        }
+                    }
+                    if rbac.can_manage_budgets() {
+                        NavItem {
+                            id: SideBar::Costs.to_string(),
+                            selected_item_id: selected_item.clone(),
+                            href: crate::routes::costs::Index { team_id: team_id.clone() },
+                            icon: limits_svg.name,
+                            title: "Costs & Budgets",
+                            disabled: setup_required
+                        }
                     }
                 )
             }
@@ -71,6 +119,14 @@ pub fn render(params: &SidebarParams, _labels: &SidebarLabels) -> Element {
                         title: "Rate Limits",
                         disabled: setup_required
                     }
+                    NavItem {
+                        id: SideBar::Guardrails.to_string(),
+                        selected_item_id: selected_item.clone(),
+                        href: crate::routes::guardrails::Index { team_id: team_id.clone() },
+                        icon: limits_svg.name,
+                        title: "Guardrails",
+                        disabled: setup_required
+                    }
                     if rbac.is_sys_admin {
                         NavItem {
                             id: SideBar::Providers.to_string(),
@@ -137,6 +193,22 @@ pub fn render(params: &SidebarParams, _labels: &SidebarLabels) -> Element {
                             title: "Categories",
                             disabled: setup_required
                         }
+                        NavItem {
+                            id: SideBar::DirectorySync.to_string(),
+                            selected_item_id: selected_item.clone(),
+                            href: crate::routes::directory_sync::Index { team_id: team_id.clone() },
+                            icon: nav_teams_svg.name,
+                            title: "Directory Sync",
+                            disabled: setup_required
+                        }
+                        NavItem {
+                            id: SideBar::Translations.to_string(),
+                            selected_item_id: selected_item.clone(),
+                            href: crate::routes::translations::Index { team_id: team_id.clone() },
+                            icon: nav_translations_svg.name,
+                            title: "Translations",
+                            disabled: setup_required
+                        }
                     }
                 )
             }
@@ -6,7 +6,14 @@ use dioxus::prelude::*;
 pub static DRAW_TRIGGER: &str = "filter-audit-drawer";
 
 #[component]
-pub fn FilterDrawer(team_users: Vec<Member>, reset_search: bool, submit_action: String) -> Element {
+pub fn FilterDrawer(
+    team_users: Vec<Member>,
+    object_types: Vec<String>,
+    reset_search: bool,
+    submit_action: String,
+    export_csv_action: String,
+    export_json_action: String,
+) -> Element {
     rsx! {
         form {
             class: "remember",
@@ -79,6 +86,45 @@ pub fn FilterDrawer(team_users: Vec<Member>, reset_search: bool, submit_action:
                             }
                         }
 
+                        Fieldset {
+                            legend: "Configuration",
+                            help_text: "Only show changes to this kind of configuration",
+                            Select {
+                                class: "w-full",
+                                name: "object_type",
+                                option {
+                                    value: "",
+                                    "Any"
+                                }
+                                for object_type in object_types {
+                                    option {
+                                        value: "{object_type}",
+                                        "{object_type}"
+                                    }
+                                }
+                            }
+                        }
+
+                        div {
+                            class: "flex gap-4",
+                            Fieldset {
+                                legend: "From",
+                                input {
+                                    class: "input",
+                                    "type": "date",
+                                    name: "from_date"
+                                }
+                            }
+                            Fieldset {
+                                legend: "To",
+                                input {
+                                    class: "input",
+                                    "type": "date",
+                                    name: "to_date"
+                                }
+                            }
+                        }
+
                         input {
                             "type": "hidden",
                             name: "id",
@@ -93,6 +139,18 @@ pub fn FilterDrawer(team_users: Vec<Member>, reset_search: bool, submit_action:
                             button_size: ButtonSize::Small,
                             "Cancel"
                         }
+                        button {
+                            class: "btn btn-sm",
+                            "type": "submit",
+                            formaction: "{export_csv_action}",
+                            "Export CSV"
+                        }
+                        button {
+                            class: "btn btn-sm",
+                            "type": "submit",
+                            formaction: "{export_json_action}",
+                            "Export JSON"
+                        }
                         Button {
                             button_type: ButtonType::Submit,
                             button_scheme: ButtonScheme::Primary,
@@ -6,7 +6,7 @@ use db::{AuditAccessType, AuditAction};
 
 const AUDIT_ACCESS: [AuditAccessType; 2] = [AuditAccessType::UserInterface, AuditAccessType::API];
 
-const AUDIT_ACTION: [AuditAction; 10] = [
+const AUDIT_ACTION: [AuditAction; 18] = [
     AuditAction::CreateMember,
     AuditAction::CreateInvite,
     AuditAction::DeleteMember,
@@ -17,6 +17,14 @@ const AUDIT_ACTION: [AuditAction; 10] = [
     AuditAction::CreatePipelineKey,
     AuditAction::DeletePipelineKey,
     AuditAction::TextGeneration,
+    AuditAction::ApproveToolCall,
+    AuditAction::RejectToolCall,
+    AuditAction::CreateConfiguration,
+    AuditAction::UpdateConfiguration,
+    AuditAction::DeleteConfiguration,
+    AuditAction::RetentionPurge,
+    AuditAction::ExportUserData,
+    AuditAction::EraseUserData,
 ];
 
 pub fn position_to_access_type(num: usize) -> AuditAccessType {
@@ -46,5 +54,13 @@ pub fn audit_action_to_string(audit_action: AuditAction) -> String {
         AuditAction::CreatePipelineKey => "Create Pipeline Key".to_owned(),
         AuditAction::DeletePipelineKey => "Delete Pipeline Key".to_owned(),
         AuditAction::TextGeneration => "Text Generation".to_owned(),
+        AuditAction::ApproveToolCall => "Approve Tool Call".to_owned(),
+        AuditAction::RejectToolCall => "Reject Tool Call".to_owned(),
+        AuditAction::CreateConfiguration => "Create Configuration".to_owned(),
+        AuditAction::UpdateConfiguration => "Update Configuration".to_owned(),
+        AuditAction::DeleteConfiguration => "Delete Configuration".to_owned(),
+        AuditAction::RetentionPurge => "Retention Purge".to_owned(),
+        AuditAction::ExportUserData => "Export User Data".to_owned(),
+        AuditAction::EraseUserData => "Erase User Data".to_owned(),
     }
 }
@@ -10,11 +10,21 @@ use crate::{
 
 pub fn page(
     team_users: Vec<Member>,
+    object_types: Vec<String>,
     audits: Vec<AuditTrail>,
     team_id: String,
     rbac: Rbac,
     reset_search: bool,
 ) -> String {
+    let export = |format: &str| {
+        crate::routes::audit_trail::Export {
+            team_id: team_id.clone(),
+            format: format.to_string(),
+        }
+        .to_string()
+    };
+    let (export_csv_action, export_json_action) = (export("csv"), export("json"));
+
     let page = rsx! {
 
         AdminLayout {
@@ -33,7 +43,7 @@ pub fn page(
                 Button {
                     popover_target: super::filter::DRAW_TRIGGER,
                     button_scheme: ButtonScheme::Neutral,
-                    "Filter"
+                    "Filter & Export"
                 }
             },
             super::table::AuditTable {
@@ -41,8 +51,11 @@ pub fn page(
             }
             super::filter::FilterDrawer {
                 team_users: team_users.clone(),
+                object_types,
                 reset_search: reset_search,
-                submit_action: crate::routes::audit_trail::Index {team_id: team_id.clone()}
+                submit_action: crate::routes::audit_trail::Index {team_id: team_id.clone()},
+                export_csv_action,
+                export_json_action
             }
         }
     };
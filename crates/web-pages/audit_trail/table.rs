@@ -21,6 +21,11 @@ pub fn AuditTable(audits: Vec<AuditTrail>) -> Element {
                             class: "max-sm:hidden",
                             "Access Type"
                         }
+                        th {
+                            class: "max-sm:hidden",
+                            "IP Address"
+                        }
+                        th { "Change" }
                         th {
                             class: "text-right",
                             "Action"
@@ -48,6 +53,31 @@ pub fn AuditTable(audits: Vec<AuditTrail>) -> Element {
                                         {super::access_type_to_string(audit.access_type)}
                                     }
                                 }
+                                td {
+                                    class: "max-sm:hidden",
+                                    {audit.ip_address.clone().unwrap_or_default()}
+                                }
+                                td {
+                                    if let Some(object_type) = &audit.object_type {
+                                        details {
+                                            summary {
+                                                class: "cursor-pointer",
+                                                code { "{object_type}" }
+                                                if let Some(object_id) = &audit.object_id {
+                                                    " {object_id}"
+                                                }
+                                            }
+                                            if let Some(before) = &audit.before {
+                                                div { class: "text-xs opacity-80 mt-2", "Before" }
+                                                pre { class: "text-xs whitespace-pre-wrap break-all", "{before}" }
+                                            }
+                                            if let Some(after) = &audit.after {
+                                                div { class: "text-xs opacity-80 mt-2", "After" }
+                                                pre { class: "text-xs whitespace-pre-wrap break-all", "{after}" }
+                                            }
+                                        }
+                                    }
+                                }
                                 td {
                                     class: "text-right",
                                     Badge {
@@ -0,0 +1,41 @@
+pub mod page;
+pub mod upsert;
+pub mod view;
+
+use daisy_rsx::BadgeColor;
+use db::{AutomationRunStatus, AutomationTrigger};
+
+pub fn run_status_badge(status: AutomationRunStatus) -> (BadgeColor, &'static str) {
+    match status {
+        AutomationRunStatus::Pending => (BadgeColor::Neutral, "Pending"),
+        AutomationRunStatus::Running => (BadgeColor::Info, "Running"),
+        AutomationRunStatus::Success => (BadgeColor::Success, "Success"),
+        AutomationRunStatus::AwaitingApproval => (BadgeColor::Warning, "Awaiting Approval"),
+        AutomationRunStatus::Failed => (BadgeColor::Error, "Failed"),
+    }
+}
+
+pub fn trigger_to_string(trigger: AutomationTrigger) -> &'static str {
+    match trigger {
+        AutomationTrigger::Schedule => "Schedule",
+        AutomationTrigger::DocumentProcessed => "DocumentProcessed",
+        AutomationTrigger::Webhook => "Webhook",
+    }
+}
+
+pub fn string_to_trigger(trigger: &str) -> Option<AutomationTrigger> {
+    match trigger {
+        "Schedule" => Some(AutomationTrigger::Schedule),
+        "DocumentProcessed" => Some(AutomationTrigger::DocumentProcessed),
+        "Webhook" => Some(AutomationTrigger::Webhook),
+        _ => None,
+    }
+}
+
+pub fn trigger_label(trigger: AutomationTrigger) -> &'static str {
+    match trigger {
+        AutomationTrigger::Schedule => "Schedule",
+        AutomationTrigger::DocumentProcessed => "Document processed",
+        AutomationTrigger::Webhook => "Inbound webhook",
+    }
+}
@@ -0,0 +1,205 @@
+#![allow(non_snake_case)]
+use super::upsert::{AutomationForm, Upsert};
+use super::{run_status_badge, trigger_label};
+use crate::app_layout::{Layout, SideBar};
+use crate::components::confirm_modal::ConfirmModal;
+use crate::SectionIntroduction;
+use assets::files::*;
+use daisy_rsx::*;
+use db::authz::Rbac;
+use db::queries::datasets::Dataset;
+use db::{Automation, AutomationTrigger, Prompt};
+use dioxus::prelude::*;
+
+pub fn page(
+    team_id: String,
+    rbac: Rbac,
+    automations: Vec<Automation>,
+    assistants: Vec<Prompt>,
+    datasets: Vec<Dataset>,
+) -> String {
+    let page = rsx! {
+        Layout {
+            section_class: "p-4",
+            selected_item: SideBar::Automations,
+            team_id: team_id.clone(),
+            rbac: rbac,
+            title: "Automations",
+            header: rsx!(
+                Breadcrumb {
+                    items: vec![BreadcrumbItem { text: "Automations".into(), href: None }]
+                }
+                Button {
+                    prefix_image_src: "{button_plus_svg.name}",
+                    popover_target: "new-automation",
+                    button_scheme: ButtonScheme::Primary,
+                    "New Automation"
+                }
+            ),
+            div {
+                class: "p-4 max-w-4xl w-full mx-auto",
+                SectionIntroduction {
+                    header: "Automations".to_string(),
+                    subtitle: "Run an assistant on a schedule, when a document is processed or when another system calls in. Each run is a conversation you can open from here, and the answer can be emailed or posted to a webhook.".to_string(),
+                    is_empty: automations.is_empty(),
+                    empty_text: "No automations yet. Add one to have an assistant work without you.".to_string(),
+                }
+
+                if !automations.is_empty() {
+                    Card {
+                        class: "mt-5 has-data-table",
+                        CardHeader { title: "Automations" }
+                        CardBody {
+                            table {
+                                class: "table table-sm",
+                                thead {
+                                    th { "Name" }
+                                    th { "Trigger" }
+                                    th { "Last Run" }
+                                    th { class: "text-right", "Action" }
+                                }
+                                tbody {
+                                    for automation in &automations {
+                                        AutomationRow {
+                                            team_id: team_id.clone(),
+                                            automation: automation.clone()
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                for automation in automations {
+                    Upsert {
+                        trigger_id: format!("edit-automation-{}", automation.id),
+                        action: crate::routes::automations::Edit { team_id: team_id.clone(), id: automation.id }.to_string(),
+                        form: AutomationForm {
+                            name: automation.name.clone(),
+                            prompt_id: automation.prompt_id,
+                            prompt_template: automation.prompt_template.clone(),
+                            trigger_type: automation.trigger_type,
+                            cron: automation.cron.clone().unwrap_or_default(),
+                            dataset_id: automation.dataset_id,
+                            notify_email: automation.notify_email.clone().unwrap_or_default(),
+                            notify_webhook_url: automation.notify_webhook_url.clone().unwrap_or_default(),
+                            enabled: automation.enabled,
+                        },
+                        assistants: assistants.clone(),
+                        datasets: datasets.clone(),
+                        is_new: false
+                    }
+                    ConfirmModal {
+                        action: crate::routes::automations::RunNow { team_id: team_id.clone(), id: automation.id }.to_string(),
+                        trigger_id: format!("run-automation-{}", automation.id),
+                        submit_label: "Run".to_string(),
+                        heading: "Run this Automation now?".to_string(),
+                        warning: "A run is queued with an empty event and starts within a few seconds.".to_string(),
+                        hidden_fields: vec![],
+                    }
+                    ConfirmModal {
+                        action: crate::routes::automations::Delete { team_id: team_id.clone(), id: automation.id }.to_string(),
+                        trigger_id: format!("delete-automation-{}", automation.id),
+                        submit_label: "Delete".to_string(),
+                        heading: "Delete this Automation?".to_string(),
+                        warning: "Its run history is removed. Conversations it started are kept.".to_string(),
+                        hidden_fields: vec![],
+                    }
+                }
+
+                Upsert {
+                    trigger_id: "new-automation",
+                    action: crate::routes::automations::New { team_id: team_id.clone() }.to_string(),
+                    form: AutomationForm::default(),
+                    assistants,
+                    datasets,
+                    is_new: true
+                }
+            }
+        }
+    };
+
+    crate::render(page)
+}
+
+#[component]
+fn AutomationRow(team_id: String, automation: Automation) -> Element {
+    let view = crate::routes::automations::View {
+        team_id: team_id.clone(),
+        id: automation.id,
+    }
+    .to_string();
+    let detail = match automation.trigger_type {
+        AutomationTrigger::Schedule => automation.cron.clone(),
+        AutomationTrigger::DocumentProcessed => automation.dataset_name.clone(),
+        AutomationTrigger::Webhook => None,
+    };
+    let last_run = automation.last_run_status.map(run_status_badge);
+
+    rsx!(
+        tr {
+            td {
+                a { class: "link", href: view.clone(), "{automation.name}" }
+                p { class: "text-xs opacity-70", "{automation.assistant_name}" }
+            }
+            td {
+                div {
+                    class: "flex flex-wrap gap-1 items-center",
+                    Badge { badge_color: BadgeColor::Neutral, {trigger_label(automation.trigger_type)} }
+                    if let Some(detail) = detail {
+                        code { class: "text-xs", "{detail}" }
+                    }
+                    if !automation.enabled {
+                        Badge { badge_color: BadgeColor::Warning, "Paused" }
+                    }
+                }
+            }
+            td {
+                if let Some((badge_color, label)) = last_run {
+                    Badge { badge_color, "{label}" }
+                    if let Some(last_run_at) = automation.last_run_at.clone() {
+                        span {
+                            class: "ml-2 text-xs",
+                            RelativeTime {
+                                format: RelativeTimeFormat::Relative,
+                                datetime: last_run_at
+                            }
+                        }
+                    }
+                } else {
+                    span { class: "text-xs opacity-70", "Never" }
+                }
+            }
+            td {
+                class: "text-right",
+                DropDown {
+                    direction: Direction::Left,
+                    button_text: "...",
+                    DropDownLink {
+                        href: view.clone(),
+                        "Runs"
+                    }
+                    DropDownLink {
+                        popover_target: format!("run-automation-{}", automation.id),
+                        href: "#",
+                        target: "_top",
+                        "Run Now"
+                    }
+                    DropDownLink {
+                        popover_target: format!("edit-automation-{}", automation.id),
+                        href: "#",
+                        target: "_top",
+                        "Edit"
+                    }
+                    DropDownLink {
+                        popover_target: format!("delete-automation-{}", automation.id),
+                        href: "#",
+                        target: "_top",
+                        "Delete"
+                    }
+                }
+            }
+        }
+    )
+}
@@ -0,0 +1,190 @@
+#![allow(non_snake_case)]
+use super::{trigger_label, trigger_to_string};
+use daisy_rsx::{select::SelectOption, *};
+use db::queries::datasets::Dataset;
+use db::{AutomationTrigger, Prompt};
+use dioxus::prelude::*;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct AutomationForm {
+    pub name: String,
+    pub prompt_id: i32,
+    pub prompt_template: String,
+    pub trigger_type: AutomationTrigger,
+    pub cron: String,
+    pub dataset_id: Option<i32>,
+    pub notify_email: String,
+    pub notify_webhook_url: String,
+    pub enabled: bool,
+}
+
+impl Default for AutomationForm {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            prompt_id: Default::default(),
+            prompt_template: Default::default(),
+            trigger_type: AutomationTrigger::Schedule,
+            cron: "0 8 * * 1-5".to_string(),
+            dataset_id: None,
+            notify_email: Default::default(),
+            notify_webhook_url: Default::default(),
+            enabled: true,
+        }
+    }
+}
+
+/// Creates an automation, or edits one when `action` is its edit route.
+#[component]
+pub fn Upsert(
+    trigger_id: String,
+    action: String,
+    form: AutomationForm,
+    assistants: Vec<Prompt>,
+    datasets: Vec<Dataset>,
+    is_new: bool,
+) -> Element {
+    let triggers = [
+        AutomationTrigger::Schedule,
+        AutomationTrigger::DocumentProcessed,
+        AutomationTrigger::Webhook,
+    ];
+    let selected_trigger = trigger_to_string(form.trigger_type);
+    let selected_dataset = form.dataset_id.map(|id| id.to_string()).unwrap_or_default();
+
+    rsx!(
+        Modal {
+            submit_action: action,
+            trigger_id,
+            ModalBody {
+                class: "flex flex-col gap-4",
+                h3 {
+                    class: "font-bold text-lg mb-4",
+                    if is_new { "New Automation" } else { "Edit Automation" }
+                }
+                Fieldset {
+                    legend: "Name",
+                    Input {
+                        input_type: InputType::Text,
+                        class: "w-full",
+                        placeholder: "Morning digest",
+                        name: "name",
+                        value: form.name,
+                        required: true,
+                    }
+                }
+                Fieldset {
+                    legend: "Assistant",
+                    help_text: "The run is a conversation with this assistant, as you",
+                    Select {
+                        class: "w-full",
+                        name: "prompt_id",
+                        required: true,
+                        for assistant in assistants {
+                            SelectOption {
+                                value: "{assistant.id}",
+                                selected_value: "{form.prompt_id}",
+                                "{assistant.name}"
+                            }
+                        }
+                    }
+                }
+                Fieldset {
+                    legend: "Prompt",
+                    help_text: "{{{{date}}}} is today's date, {{{{event}}}} is the whole event and paths such as {{{{document.file_name}}}} or {{{{payload.ticket.id}}}} pick out fields",
+                    TextArea {
+                        class: "mt-3 w-full",
+                        name: "prompt_template",
+                        rows: "6",
+                        required: true,
+                        "{form.prompt_template}"
+                    }
+                }
+                Fieldset {
+                    legend: "Trigger",
+                    Select {
+                        class: "w-full",
+                        name: "trigger_type",
+                        for trigger in triggers {
+                            SelectOption {
+                                value: "{trigger_to_string(trigger)}",
+                                selected_value: "{selected_trigger}",
+                                {trigger_label(trigger)}
+                            }
+                        }
+                    }
+                }
+                Fieldset {
+                    legend: "Schedule",
+                    help_text: "A cron expression in UTC, used by the schedule trigger. 0 8 * * 1-5 is 08:00 on weekdays",
+                    Input {
+                        input_type: InputType::Text,
+                        class: "w-full font-mono",
+                        name: "cron",
+                        value: form.cron,
+                    }
+                }
+                Fieldset {
+                    legend: "Dataset",
+                    help_text: "Used by the document processed trigger",
+                    Select {
+                        class: "w-full",
+                        name: "dataset_id",
+                        SelectOption {
+                            value: "",
+                            selected_value: "{selected_dataset}",
+                            "None"
+                        }
+                        for dataset in datasets {
+                            SelectOption {
+                                value: "{dataset.id}",
+                                selected_value: "{selected_dataset}",
+                                "{dataset.name}"
+                            }
+                        }
+                    }
+                }
+                Fieldset {
+                    legend: "Email the result to",
+                    Input {
+                        input_type: InputType::Email,
+                        class: "w-full",
+                        placeholder: "team@example.com",
+                        name: "notify_email",
+                        value: form.notify_email,
+                    }
+                }
+                Fieldset {
+                    legend: "POST the result to",
+                    Input {
+                        input_type: InputType::Text,
+                        class: "w-full",
+                        placeholder: "https://ops.example.com/hooks/digest",
+                        name: "notify_webhook_url",
+                        value: form.notify_webhook_url,
+                    }
+                }
+                if !is_new {
+                    label {
+                        class: "flex items-center gap-2",
+                        input {
+                            "type": "checkbox",
+                            class: "checkbox checkbox-sm",
+                            name: "enabled",
+                            checked: form.enabled,
+                        }
+                        "Enabled"
+                    }
+                }
+                ModalAction {
+                    Button { class: "cancel-modal", button_scheme: ButtonScheme::Warning, "Cancel" }
+                    Button {
+                        button_type: ButtonType::Submit,
+                        button_scheme: ButtonScheme::Primary,
+                        if is_new { "Create Automation" } else { "Save" }
+                    }
+                }
+            }
+        }
+    )
+}
@@ -0,0 +1,179 @@
+#![allow(non_snake_case)]
+use super::{run_status_badge, trigger_label};
+use crate::app_layout::{Layout, SideBar};
+use daisy_rsx::*;
+use db::authz::Rbac;
+use db::{Automation, AutomationRun, AutomationTrigger};
+use dioxus::prelude::*;
+
+pub fn page(
+    team_id: String,
+    rbac: Rbac,
+    base_url: String,
+    automation: Automation,
+    runs: Vec<AutomationRun>,
+) -> String {
+    let trigger_url = format!("{}/v1/automations/trigger", base_url.trim_end_matches('/'));
+
+    let page = rsx! {
+        Layout {
+            section_class: "p-4",
+            selected_item: SideBar::Automations,
+            team_id: team_id.clone(),
+            rbac: rbac,
+            title: "Automations",
+            header: rsx!(
+                Breadcrumb {
+                    items: vec![
+                        BreadcrumbItem {
+                            text: "Automations".into(),
+                            href: Some(crate::routes::automations::Index { team_id: team_id.clone() }.to_string())
+                        },
+                        BreadcrumbItem { text: automation.name.clone(), href: None }
+                    ]
+                }
+                form {
+                    method: "post",
+                    action: crate::routes::automations::RunNow { team_id: team_id.clone(), id: automation.id }.to_string(),
+                    Button {
+                        button_type: ButtonType::Submit,
+                        button_scheme: ButtonScheme::Primary,
+                        "Run Now"
+                    }
+                }
+            ),
+            div {
+                class: "p-4 max-w-4xl w-full mx-auto",
+                Card {
+                    CardHeader { title: trigger_label(automation.trigger_type) }
+                    CardBody {
+                        class: "flex flex-col gap-2",
+                        match automation.trigger_type {
+                            AutomationTrigger::Schedule => rsx!(
+                                p {
+                                    class: "text-sm",
+                                    "Runs at "
+                                    code { {automation.cron.clone().unwrap_or_default()} }
+                                    " UTC."
+                                }
+                                if let Some(next_run_at) = automation.next_run_at.clone() {
+                                    p {
+                                        class: "text-sm opacity-80",
+                                        "Next run "
+                                        RelativeTime {
+                                            format: RelativeTimeFormat::Relative,
+                                            datetime: next_run_at
+                                        }
+                                    }
+                                }
+                            ),
+                            AutomationTrigger::DocumentProcessed => rsx!(
+                                p {
+                                    class: "text-sm",
+                                    "Runs when a document in "
+                                    strong { {automation.dataset_name.clone().unwrap_or_default()} }
+                                    " has been processed. "
+                                    code { "{{{{document.file_name}}}}" }
+                                    " and "
+                                    code { "{{{{dataset.name}}}}" }
+                                    " can be used in the prompt."
+                                }
+                            ),
+                            AutomationTrigger::Webhook => rsx!(
+                                Input {
+                                    input_type: InputType::Text,
+                                    class: "w-full font-mono",
+                                    name: "webhook_token",
+                                    value: automation.webhook_token.clone().unwrap_or_default(),
+                                    readonly: true,
+                                }
+                                p {
+                                    class: "text-sm opacity-80",
+                                    "POST a JSON body with this token as a bearer token. The body is available to the prompt as "
+                                    code { "{{{{payload}}}}" }
+                                    ", and fields as paths such as "
+                                    code { "{{{{payload.ticket.id}}}}" }
+                                    "."
+                                }
+                                pre {
+                                    class: "text-xs whitespace-pre-wrap",
+                                    "curl -X POST {trigger_url} \\\n  -H 'Authorization: Bearer {automation.webhook_token.clone().unwrap_or_default()}' \\\n  -H 'Content-Type: application/json' \\\n  -d '{{\"ticket\": {{\"id\": 42}}}}'"
+                                }
+                            ),
+                        }
+                        if !automation.enabled {
+                            p { class: "text-sm text-warning", "This automation is paused." }
+                        }
+                    }
+                }
+                Card {
+                    class: "mt-5 has-data-table",
+                    CardHeader { title: "Runs" }
+                    CardBody {
+                        if runs.is_empty() {
+                            p { class: "p-4 text-sm opacity-70", "This automation hasn't run yet." }
+                        } else {
+                            table {
+                                class: "table table-sm",
+                                thead {
+                                    th { "Run" }
+                                    th { "Status" }
+                                    th { "Attempts" }
+                                    th { "Created" }
+                                    th { class: "text-right", "Conversation" }
+                                }
+                                tbody {
+                                    for run in runs {
+                                        RunRow {
+                                            team_id: team_id.clone(),
+                                            run
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    crate::render(page)
+}
+
+#[component]
+fn RunRow(team_id: String, run: AutomationRun) -> Element {
+    let (badge_color, status) = run_status_badge(run.status);
+
+    rsx!(
+        tr {
+            td {
+                details {
+                    summary { class: "text-xs cursor-pointer", {trigger_label(run.trigger_type)} }
+                    pre { class: "text-xs whitespace-pre-wrap", "{run.event}" }
+                }
+                if let Some(error) = &run.error {
+                    p { class: "text-xs text-error", "{error}" }
+                }
+            }
+            td { Badge { badge_color, "{status}" } }
+            td { "{run.attempts}" }
+            td {
+                RelativeTime {
+                    format: RelativeTimeFormat::Relative,
+                    datetime: run.created_at.clone()
+                }
+            }
+            td {
+                class: "text-right",
+                if let Some(conversation_id) = run.conversation_id {
+                    a {
+                        class: "link",
+                        href: crate::routes::console::Conversation { team_id: team_id.clone(), conversation_id }.to_string(),
+                        "Open"
+                    }
+                }
+            }
+        }
+    )
+}
@@ -0,0 +1,93 @@
+#![allow(non_snake_case)]
+use crate::routes;
+use assets::files::*;
+use daisy_rsx::*;
+use db::ToolCallApproval;
+use dioxus::prelude::*;
+
+/// An integration call the model made which only runs once the user approves it.
+#[component]
+pub fn ApprovalTimeline(team_id: String, approval: ToolCallApproval) -> Element {
+    let body = approval
+        .request_body
+        .as_ref()
+        .map(|body| serde_json::to_string_pretty(body).unwrap_or_else(|_| body.to_string()));
+    let action = routes::console::DecideToolCall {
+        team_id,
+        id: approval.id,
+    }
+    .to_string();
+
+    rsx! {
+        TimeLine {
+            TimeLineBadge {
+                image_src: tools_svg.name
+            }
+            TimeLineBody {
+                Card {
+                    class: "border border-warning",
+                    CardHeader {
+                        title: "Approval needed"
+                    }
+                    CardBody {
+                        p {
+                            class: "text-sm mb-3",
+                            "The assistant wants to call "
+                            strong { "{approval.function_name}" }
+                            ". Nothing has been sent yet."
+                        }
+                        div {
+                            class: "font-mono text-sm break-all mb-2",
+                            Badge {
+                                badge_style: BadgeStyle::Outline,
+                                badge_size: BadgeSize::Sm,
+                                class: "mr-2",
+                                "{approval.method}"
+                            }
+                            "{approval.url}"
+                        }
+                        if let Some(body) = body {
+                            pre {
+                                class: "text-xs bg-base-200 rounded p-3 overflow-x-auto mb-3",
+                                "{body}"
+                            }
+                        }
+                        div {
+                            class: "flex gap-2 justify-end",
+                            form {
+                                method: "post",
+                                action: "{action}",
+                                input {
+                                    "type": "hidden",
+                                    name: "approve",
+                                    value: "false"
+                                }
+                                Button {
+                                    button_type: ButtonType::Submit,
+                                    button_scheme: ButtonScheme::Error,
+                                    button_size: ButtonSize::Small,
+                                    "Reject"
+                                }
+                            }
+                            form {
+                                method: "post",
+                                action: "{action}",
+                                input {
+                                    "type": "hidden",
+                                    name: "approve",
+                                    value: "true"
+                                }
+                                Button {
+                                    button_type: ButtonType::Submit,
+                                    button_scheme: ButtonScheme::Primary,
+                                    button_size: ButtonSize::Small,
+                                    "Approve and send"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
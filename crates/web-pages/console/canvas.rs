@@ -32,6 +32,13 @@ pub fn is_canvas_output(output: &GeneratedOutputPayload) -> bool {
     output.file_name == "CANVAS.md" && output.path.ends_with("/CANVAS.md")
 }
 
+pub fn is_image_output(output: &GeneratedOutputPayload) -> bool {
+    matches!(
+        output.mime_type.as_str(),
+        "image/png" | "image/jpeg" | "image/gif" | "image/webp"
+    )
+}
+
 fn canvas_title(path: &str) -> String {
     path.strip_suffix("/CANVAS.md")
         .and_then(|path| path.rsplit('/').next())
@@ -66,11 +73,40 @@ pub fn CanvasOutput(team_id: String, output: GeneratedOutputPayload) -> Element
     }
 }
 
+#[component]
+pub fn ImageOutput(team_id: String, output: GeneratedOutputPayload) -> Element {
+    let src = crate::routes::console::GeneratedOutputFile {
+        team_id,
+        id: output.id,
+    }
+    .to_string();
+
+    rsx! {
+        figure {
+            class: "border border-base-300 bg-base-100 rounded-lg overflow-hidden",
+            a {
+                href: "{src}",
+                target: "_blank",
+                img {
+                    class: "w-full max-h-[60vh] object-contain bg-base-200",
+                    alt: "{output.file_name}",
+                    loading: "lazy",
+                    src: "{src}"
+                }
+            }
+            figcaption {
+                class: "px-3 py-2 border-t border-base-300 font-mono text-xs text-base-content/70 break-all",
+                "{output.path}"
+            }
+        }
+    }
+}
+
 #[component]
 pub fn GeneratedFiles(outputs: Vec<GeneratedOutputPayload>) -> Element {
     let files = outputs
         .into_iter()
-        .filter(|output| !is_canvas_output(output))
+        .filter(|output| !is_canvas_output(output) && !is_image_output(output))
         .collect::<Vec<_>>();
 
     rsx! {
@@ -133,4 +169,19 @@ mod tests {
 
         assert!(is_canvas_output(&output));
     }
+
+    #[test]
+    fn test_image_output_detection() {
+        let mut output = GeneratedOutputPayload {
+            id: 1,
+            path: "/home/user/output/fox.png".to_string(),
+            file_name: "fox.png".to_string(),
+            mime_type: "image/png".to_string(),
+            size: 42,
+        };
+        assert!(is_image_output(&output));
+
+        output.mime_type = "image/svg+xml".to_string();
+        assert!(!is_image_output(&output));
+    }
 }
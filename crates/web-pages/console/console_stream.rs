@@ -8,6 +8,7 @@ use dioxus::prelude::*;
 use std::collections::HashMap;
 use tool_runtime::{parse_reasoning, parse_tool_calls, ToolCall};
 
+use super::approval_timeline::ApprovalTimeline;
 use super::reasoning_timeline::ReasoningTimeline;
 use super::response_timeline::ResponseTimeline;
 use super::tool_call_timeline::ToolCallTimeline;
@@ -59,6 +60,32 @@ pub fn ConsoleStream(
                         }
                     }
                 },
+                PendingChatState::AwaitingApproval(tool_chats, approvals) => rsx! {
+                    div {
+                        class: "flex flex-col {CONSOLE_CONTENT_WIDTH}",
+                        for tool_chat in tool_chats {
+                            ToolCallTimeline {
+                                team_id: team_id.clone(),
+                                chat_id: tool_chat.id as i64,
+                                pending: true,
+                                tool_call_id: tool_chat.tool_call_id.clone(),
+                                tool_call: tool_chat
+                                    .tool_call_id
+                                    .as_ref()
+                                    .and_then(|id| tool_call_index.get(id))
+                                    .cloned(),
+                                response: tool_chat.content.clone(),
+                            }
+                        }
+                        // The model carries on once every call has been decided.
+                        for approval in approvals {
+                            ApprovalTimeline {
+                                team_id: team_id.clone(),
+                                approval
+                            }
+                        }
+                    }
+                },
                 PendingChatState::PendingUserChat(pending_chat) => rsx! {
                     div {
                         class: "flex flex-col {CONSOLE_CONTENT_WIDTH}",
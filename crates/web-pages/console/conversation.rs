@@ -9,6 +9,7 @@ use daisy_rsx::*;
 use db::authz::Rbac;
 use db::queries::capabilities::Capability;
 use db::queries::prompts::{Prompt, SinglePrompt};
+use db::ConversationShare;
 use dioxus::prelude::*;
 
 pub fn page(
@@ -21,6 +22,7 @@ pub fn page(
     conversation_id: i64,
     is_tts_disabled: bool,
     capabilities: Vec<Capability>,
+    shares: Vec<ConversationShare>,
 ) -> String {
     // Rerverse it because that's how we display it.
     let chat_history: Vec<ChatWithChunks> = chat_history.into_iter().rev().collect();
@@ -42,7 +44,8 @@ pub fn page(
                     rbac: rbac.clone(),
                     conversation_id: conversation_id,
                     prompts,
-                    prompt: prompt.clone()
+                    prompt: prompt.clone(),
+                    shares
                 }
             )
         }
@@ -58,7 +61,16 @@ fn Head(
     conversation_id: i64,
     prompts: Vec<Prompt>,
     prompt: SinglePrompt,
+    shares: Vec<ConversationShare>,
 ) -> Element {
+    let export = |format: &str| {
+        crate::routes::console::Export {
+            team_id: team_id.clone(),
+            conversation_id,
+            format: format.to_string(),
+        }
+        .to_string()
+    };
     rsx! {
 
         ModelPopup {
@@ -68,6 +80,25 @@ fn Head(
         }
         div {
             class: "flex flex-row",
+            DropDown {
+                class: "dropdown-end mr-2",
+                direction: Direction::Bottom,
+                button_text: "...",
+                DropDownLink {
+                    popover_target: format!("share-conv-{}", conversation_id),
+                    href: "#",
+                    target: "_top",
+                    "Share"
+                }
+                DropDownLink { href: export("md"), "Export as Markdown" }
+                DropDownLink { href: export("json"), "Export as JSON" }
+                DropDownLink { href: export("html"), "Export as HTML" }
+            }
+            super::share::Share {
+                team_id: team_id.clone(),
+                conversation_id,
+                shares
+            }
             if rbac.can_delete_chat() {
                 Button {
                     class: "btn-circle mr-2 p-1",
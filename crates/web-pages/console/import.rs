@@ -0,0 +1,34 @@
+#![allow(non_snake_case)]
+use daisy_rsx::*;
+use dioxus::prelude::*;
+
+#[component]
+pub fn Import(team_id: String) -> Element {
+    rsx!(
+        form {
+            action: crate::routes::console::Import { team_id }.to_string(),
+            method: "post",
+            enctype: "multipart/form-data",
+            Modal {
+                trigger_id: "import-conversation",
+                ModalBody {
+                    class: "flex flex-col gap-4",
+                    h3 { class: "font-bold text-lg mb-4", "Import a Conversation" }
+                    FileInput {
+                        class: "w-full",
+                        name: "file",
+                        required: true,
+                    }
+                    Alert {
+                        alert_color: AlertColor::Default,
+                        "Choose a conversation exported as JSON. It becomes a new conversation of yours in this team, with its tool calls and thinking. Files and sources aren't imported."
+                    }
+                    ModalAction {
+                        Button { class: "cancel-modal", button_scheme: ButtonScheme::Warning, "Cancel" }
+                        Button { button_type: ButtonType::Submit, button_scheme: ButtonScheme::Primary, "Import" }
+                    }
+                }
+            }
+        }
+    )
+}
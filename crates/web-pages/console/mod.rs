@@ -1,7 +1,9 @@
+pub mod approval_timeline;
 pub mod canvas;
 pub mod console_stream;
 pub mod conversation;
 pub mod empty_stream;
+pub mod import;
 pub mod layout;
 pub mod model_popup;
 pub mod page;
@@ -9,9 +11,12 @@ pub mod prompt_form;
 pub mod prompt_modal;
 pub mod reasoning_timeline;
 pub mod response_timeline;
+pub mod share;
 pub mod tool_call_timeline;
+pub mod transcript;
 
 use db::queries::{chats::Chat, chats_chunks::ChatChunks};
+use db::ToolCallApproval;
 use tool_runtime::ToolCall;
 
 #[derive(PartialEq, Clone, Debug)]
@@ -29,6 +34,8 @@ pub struct PendingChat {
 #[derive(PartialEq, Clone, Debug)]
 pub enum PendingChatState {
     PendingToolChats(Vec<Chat>, i32),
+    /// Tool chats whose integration calls are waiting for the user to approve them.
+    AwaitingApproval(Vec<Chat>, Vec<ToolCallApproval>),
     PendingUserChat(Box<PendingChat>),
     None,
 }
@@ -37,6 +44,7 @@ impl PendingChatState {
     pub fn shall_we_call_the_model(&self) -> bool {
         match self {
             PendingChatState::PendingToolChats(_, _) => true,
+            PendingChatState::AwaitingApproval(_, _) => false,
             PendingChatState::PendingUserChat(_) => true,
             PendingChatState::None => false,
         }
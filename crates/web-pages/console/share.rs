@@ -0,0 +1,115 @@
+#![allow(non_snake_case)]
+use daisy_rsx::*;
+use db::{ConversationShare, ShareVisibility};
+use dioxus::prelude::*;
+
+/// The conversation's share links, with a form to make another.
+#[component]
+pub fn Share(team_id: String, conversation_id: i64, shares: Vec<ConversationShare>) -> Element {
+    rsx!(
+        Modal {
+            trigger_id: "share-conv-{conversation_id}",
+            ModalBody {
+                class: "flex flex-col gap-4",
+                h3 { class: "font-bold text-lg mb-4", "Share this Conversation" }
+                if !shares.is_empty() {
+                    table {
+                        class: "table table-sm",
+                        thead {
+                            th { "Link" }
+                            th { "Expires" }
+                            th {}
+                        }
+                        tbody {
+                            for share in &shares {
+                                ShareRow {
+                                    team_id: team_id.clone(),
+                                    conversation_id,
+                                    share: share.clone()
+                                }
+                            }
+                        }
+                    }
+                }
+                form {
+                    action: crate::routes::console::CreateShare { team_id: team_id.clone(), conversation_id }.to_string(),
+                    method: "post",
+                    class: "flex flex-col gap-4",
+                    Fieldset {
+                        legend: "Who can open it",
+                        Select {
+                            class: "w-full",
+                            name: "visibility",
+                            SelectOption {
+                                value: "Team",
+                                "Members of this team who can view chats"
+                            }
+                            SelectOption {
+                                value: "Public",
+                                "Anyone with the link"
+                            }
+                        }
+                    }
+                    Fieldset {
+                        legend: "Expires after (days)",
+                        help_text: "Leave blank for a link that works until it's revoked",
+                        Input {
+                            input_type: InputType::Number,
+                            class: "w-full",
+                            name: "expires_in_days",
+                        }
+                    }
+                    ModalAction {
+                        Button { class: "cancel-modal", button_scheme: ButtonScheme::Warning, "Close" }
+                        Button { button_type: ButtonType::Submit, button_scheme: ButtonScheme::Primary, "Create Link" }
+                    }
+                }
+            }
+        }
+    )
+}
+
+#[component]
+fn ShareRow(team_id: String, conversation_id: i64, share: ConversationShare) -> Element {
+    let href = match share.visibility {
+        ShareVisibility::Team => crate::routes::console::TeamShare {
+            team_id: team_id.clone(),
+            token: share.token.clone(),
+        }
+        .to_string(),
+        ShareVisibility::Public => crate::routes::console::PublicShare {
+            token: share.token.clone(),
+        }
+        .to_string(),
+    };
+    rsx!(
+        tr {
+            td {
+                a { href: "{href}", target: "_blank", class: "link", "{share.visibility:?} link" }
+            }
+            td {
+                if let Some(expires_at) = share.expires_at {
+                    RelativeTime {
+                        format: RelativeTimeFormat::Relative,
+                        datetime: expires_at.to_rfc3339()
+                    }
+                } else {
+                    "Never"
+                }
+            }
+            td {
+                class: "text-right",
+                form {
+                    action: crate::routes::console::DeleteShare { team_id, conversation_id, id: share.id }.to_string(),
+                    method: "post",
+                    Button {
+                        button_type: ButtonType::Submit,
+                        button_scheme: ButtonScheme::Error,
+                        button_size: ButtonSize::Small,
+                        "Revoke"
+                    }
+                }
+            }
+        }
+    )
+}
@@ -6,7 +6,10 @@ use dioxus::prelude::*;
 use serde_json::Value;
 use tool_runtime::ToolCall;
 
-use super::canvas::{is_canvas_output, parse_generated_outputs, CanvasOutput, GeneratedFiles};
+use super::canvas::{
+    is_canvas_output, is_image_output, parse_generated_outputs, CanvasOutput, GeneratedFiles,
+    ImageOutput,
+};
 
 fn format_json_string(raw: &str) -> String {
     if let Ok(value) = serde_json::from_str::<serde_json::Value>(raw) {
@@ -58,6 +61,11 @@ pub fn ToolCallTimeline(
         .filter(|output| is_canvas_output(output))
         .cloned()
         .collect::<Vec<_>>();
+    let image_outputs = generated_outputs
+        .iter()
+        .filter(|output| is_image_output(output))
+        .cloned()
+        .collect::<Vec<_>>();
 
     rsx! {
         TimeLine {
@@ -100,6 +108,12 @@ pub fn ToolCallTimeline(
                             output
                         }
                     }
+                    for output in image_outputs {
+                        ImageOutput {
+                            team_id: team_id.clone(),
+                            output
+                        }
+                    }
                     GeneratedFiles {
                         outputs: generated_outputs
                     }
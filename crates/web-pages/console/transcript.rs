@@ -0,0 +1,601 @@
+//! A conversation as one document, for exports and share links.
+//!
+//! The JSON export is the `Transcript` itself, and is what imports read back. Markdown
+//! and HTML are written from it, the HTML as a single file with the generated outputs
+//! embedded so it can be opened or shared without Bionic.
+#![allow(non_snake_case)]
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use db::ChatRole;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use tool_runtime::{parse_reasoning, parse_tool_calls, Reasoning, ToolCall};
+
+use super::ChatWithChunks;
+
+pub const FORMAT: &str = "bionic-conversation";
+pub const VERSION: u32 = 1;
+
+/// Text outputs larger than this are offered as downloads rather than shown.
+const MAX_TEXT_EMBED: usize = 100_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Transcript {
+    pub format: String,
+    pub version: u32,
+    #[serde(default)]
+    pub team: Option<String>,
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    pub messages: Vec<Message>,
+    #[serde(default)]
+    pub outputs: Vec<Output>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Message {
+    /// User, Assistant or Tool.
+    pub role: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasoning: Vec<Reasoning>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// The call a Tool message answers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub citations: Vec<Citation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+    #[serde(default)]
+    pub created_at: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Citation {
+    pub file_name: String,
+    pub page_number: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Attachment {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub mime_type: String,
+    pub size: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Output {
+    pub path: String,
+    pub mime_type: String,
+    pub file_size: i64,
+    /// Only the HTML export and share links carry the file itself.
+    #[serde(skip)]
+    pub data: Option<Vec<u8>>,
+}
+
+impl Transcript {
+    pub fn new(
+        team: Option<String>,
+        project: Option<String>,
+        created_at: Option<String>,
+        chats: Vec<ChatWithChunks>,
+        outputs: Vec<Output>,
+    ) -> Self {
+        let messages = chats
+            .into_iter()
+            .filter(|chat| {
+                matches!(
+                    chat.chat.role,
+                    ChatRole::User | ChatRole::Assistant | ChatRole::Tool
+                )
+            })
+            .map(|ChatWithChunks { chat, chunks }| {
+                let is_assistant = chat.role == ChatRole::Assistant;
+                Message {
+                    role: format!("{:?}", chat.role),
+                    model: is_assistant.then_some(chat.model_name),
+                    reasoning: parse_reasoning(chat.tool_calls.as_deref()),
+                    tool_calls: parse_tool_calls(chat.tool_calls.as_deref()),
+                    tool_call_id: chat.tool_call_id,
+                    citations: chunks
+                        .into_iter()
+                        .map(|chunk| Citation {
+                            file_name: chunk.file_name,
+                            page_number: chunk.page_number,
+                        })
+                        .collect(),
+                    attachments: chat
+                        .attachments
+                        .and_then(|attachments| serde_json::from_value(attachments).ok())
+                        .unwrap_or_default(),
+                    content: chat.content,
+                    created_at: Some(chat.created_at.to_rfc3339()),
+                }
+            })
+            .collect();
+
+        Transcript {
+            format: FORMAT.to_string(),
+            version: VERSION,
+            team,
+            project,
+            created_at,
+            messages,
+            outputs,
+        }
+    }
+
+    /// The first thing the user asked, which is what the history page shows too.
+    pub fn title(&self) -> String {
+        let title = self
+            .messages
+            .iter()
+            .find(|message| message.role == "User")
+            .and_then(|message| message.content.as_deref())
+            .and_then(|content| content.lines().find(|line| !line.trim().is_empty()))
+            .unwrap_or("Conversation")
+            .trim();
+        match title.char_indices().nth(80) {
+            Some((end, _)) => format!("{}…", &title[..end]),
+            None => title.to_string(),
+        }
+    }
+}
+
+fn reasoning_text(reasoning: &[Reasoning]) -> String {
+    reasoning
+        .iter()
+        .map(Reasoning::display_text)
+        .filter(|text| !text.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn arguments_text(tool_call: &ToolCall) -> String {
+    serde_json::to_string_pretty(&tool_call.function.arguments)
+        .unwrap_or_else(|_| tool_call.function.arguments.to_string())
+}
+
+fn citation_text(citation: &Citation) -> String {
+    format!("{} (page {})", citation.file_name, citation.page_number)
+}
+
+/// A fence longer than any run of backticks in the text, so the text can't close it.
+fn fence(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn fenced(language: &str, text: &str) -> String {
+    let fence = fence(text);
+    format!("{fence}{language}\n{}\n{fence}\n\n", text.trim_end())
+}
+
+pub fn to_markdown(transcript: &Transcript) -> String {
+    let mut markdown = format!("# {}\n\n", transcript.title());
+    let details: Vec<&str> = [
+        &transcript.team,
+        &transcript.project,
+        &transcript.created_at,
+    ]
+    .into_iter()
+    .flatten()
+    .map(String::as_str)
+    .collect();
+    if !details.is_empty() {
+        markdown.push_str(&format!("_{}_\n\n", details.join(" · ")));
+    }
+
+    for message in &transcript.messages {
+        match message.role.as_str() {
+            "Assistant" => {
+                match &message.model {
+                    Some(model) => markdown.push_str(&format!("## Assistant ({model})\n\n")),
+                    None => markdown.push_str("## Assistant\n\n"),
+                }
+                let reasoning = reasoning_text(&message.reasoning);
+                if !reasoning.is_empty() {
+                    for line in reasoning.lines() {
+                        markdown.push_str(&format!("> {line}\n"));
+                    }
+                    markdown.push('\n');
+                }
+                if let Some(content) = message.content.as_deref().filter(|c| !c.is_empty()) {
+                    markdown.push_str(&format!("{}\n\n", content.trim_end()));
+                }
+                for tool_call in &message.tool_calls {
+                    markdown.push_str(&format!("Called `{}`\n\n", tool_call.function.name));
+                    markdown.push_str(&fenced("json", &arguments_text(tool_call)));
+                }
+            }
+            "Tool" => {
+                markdown.push_str("## Tool Result\n\n");
+                markdown.push_str(&fenced("", message.content.as_deref().unwrap_or_default()));
+            }
+            _ => {
+                markdown.push_str("## User\n\n");
+                markdown.push_str(&format!(
+                    "{}\n\n",
+                    message.content.as_deref().unwrap_or_default().trim_end()
+                ));
+                for attachment in &message.attachments {
+                    markdown.push_str(&format!("- Attached {}\n", attachment.name));
+                }
+                if !message.attachments.is_empty() {
+                    markdown.push('\n');
+                }
+            }
+        }
+        if !message.citations.is_empty() {
+            markdown.push_str("Sources:\n\n");
+            for citation in &message.citations {
+                markdown.push_str(&format!("- {}\n", citation_text(citation)));
+            }
+            markdown.push('\n');
+        }
+    }
+
+    if !transcript.outputs.is_empty() {
+        markdown.push_str("## Generated Files\n\n");
+        for output in &transcript.outputs {
+            markdown.push_str(&format!("- {} ({} bytes)\n", output.path, output.file_size));
+        }
+    }
+
+    markdown
+}
+
+#[derive(Debug, PartialEq)]
+enum Embed {
+    Image(String),
+    Text(String),
+    Download(String),
+}
+
+/// Raster images are shown, readable text is printed and anything else, including SVG
+/// and HTML that could run script, is only offered as a download.
+fn embed(output: &Output) -> Option<Embed> {
+    let data = output.data.as_ref()?;
+    let mime_type = output.mime_type.as_str();
+    if matches!(
+        mime_type,
+        "image/png" | "image/jpeg" | "image/gif" | "image/webp"
+    ) {
+        return Some(Embed::Image(format!(
+            "data:{mime_type};base64,{}",
+            STANDARD.encode(data)
+        )));
+    }
+    let is_text = (mime_type.starts_with("text/") && mime_type != "text/html")
+        || mime_type == "application/json";
+    if is_text && data.len() <= MAX_TEXT_EMBED {
+        if let Ok(text) = std::str::from_utf8(data) {
+            return Some(Embed::Text(text.to_string()));
+        }
+    }
+    Some(Embed::Download(format!(
+        "data:application/octet-stream;base64,{}",
+        STANDARD.encode(data)
+    )))
+}
+
+fn markdown_to_html(markdown: &str) -> String {
+    let mut options = comrak::Options::default();
+    options.extension.table = true;
+    options.extension.strikethrough = true;
+    options.extension.tagfilter = true;
+    options.extension.tasklist = true;
+    options.extension.autolink = true;
+    comrak::markdown_to_html(markdown, &options)
+}
+
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; line-height: 1.5; color: #1f2937; margin: 0; background: #f9fafb; }
+main { max-width: 48rem; margin: 0 auto; padding: 2rem 1rem; }
+h1 { font-size: 1.5rem; margin-bottom: 0.25rem; }
+h2 { font-size: 0.875rem; text-transform: uppercase; color: #6b7280; margin: 0 0 0.5rem; }
+.details { color: #6b7280; margin-top: 0; }
+section { background: #fff; border: 1px solid #e5e7eb; border-radius: 0.5rem; padding: 1rem; margin: 1rem 0; }
+section.user { background: #eef2ff; }
+pre { background: #f3f4f6; padding: 0.75rem; border-radius: 0.375rem; overflow-x: auto; white-space: pre-wrap; word-break: break-word; }
+details { margin: 0.5rem 0; }
+summary { cursor: pointer; color: #4b5563; }
+img { max-width: 100%; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #e5e7eb; padding: 0.25rem 0.5rem; }
+ul.sources { color: #6b7280; font-size: 0.875rem; }
+";
+
+/// A read-only page of the conversation with everything it needs inlined.
+pub fn page(transcript: Transcript) -> String {
+    let title = transcript.title();
+    let details: Vec<String> = [
+        transcript.team.clone(),
+        transcript.project.clone(),
+        transcript.created_at.clone(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let details = details.join(" · ");
+
+    let page = rsx! {
+        head {
+            meta { charset: "utf-8" }
+            meta { name: "viewport", content: "width=device-width, initial-scale=1" }
+            title { "{title}" }
+            style { dangerous_inner_html: STYLE }
+        }
+        body {
+            main {
+                h1 { "{title}" }
+                p { class: "details", "{details}" }
+                for message in transcript.messages {
+                    MessageSection { message }
+                }
+                if !transcript.outputs.is_empty() {
+                    section {
+                        h2 { "Generated Files" }
+                        for output in transcript.outputs {
+                            OutputFile { output }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    crate::render(page)
+}
+
+#[component]
+fn MessageSection(message: Message) -> Element {
+    let content = message.content.clone().unwrap_or_default();
+    let reasoning = reasoning_text(&message.reasoning);
+
+    rsx! {
+        match message.role.as_str() {
+            "Assistant" => rsx! {
+                section {
+                    h2 {
+                        if let Some(model) = &message.model {
+                            "Assistant · {model}"
+                        } else {
+                            "Assistant"
+                        }
+                    }
+                    if !reasoning.is_empty() {
+                        details {
+                            summary { "Thinking" }
+                            pre { "{reasoning}" }
+                        }
+                    }
+                    if !content.is_empty() {
+                        div { dangerous_inner_html: markdown_to_html(&content) }
+                    }
+                    for tool_call in &message.tool_calls {
+                        details {
+                            summary { "Called {tool_call.function.name}" }
+                            pre { {arguments_text(tool_call)} }
+                        }
+                    }
+                    Sources { citations: message.citations.clone() }
+                }
+            },
+            "Tool" => rsx! {
+                section {
+                    details {
+                        summary { "Tool Result" }
+                        pre { "{content}" }
+                    }
+                }
+            },
+            _ => rsx! {
+                section {
+                    class: "user",
+                    h2 { "User" }
+                    div { style: "white-space: pre-wrap", "{content}" }
+                    if !message.attachments.is_empty() {
+                        ul {
+                            class: "sources",
+                            for attachment in &message.attachments {
+                                li { "Attached {attachment.name}" }
+                            }
+                        }
+                    }
+                }
+            },
+        }
+    }
+}
+
+#[component]
+fn Sources(citations: Vec<Citation>) -> Element {
+    if citations.is_empty() {
+        return rsx! {};
+    }
+    rsx! {
+        ul {
+            class: "sources",
+            for citation in &citations {
+                li { {citation_text(citation)} }
+            }
+        }
+    }
+}
+
+#[component]
+fn OutputFile(output: Output) -> Element {
+    let file_name = output
+        .path
+        .rsplit('/')
+        .next()
+        .unwrap_or(&output.path)
+        .to_string();
+    rsx! {
+        match embed(&output) {
+            Some(Embed::Image(src)) => rsx! {
+                figure {
+                    img { src, alt: "{output.path}" }
+                    figcaption { "{output.path}" }
+                }
+            },
+            Some(Embed::Text(text)) => rsx! {
+                details {
+                    summary { "{output.path}" }
+                    pre { "{text}" }
+                }
+            },
+            Some(Embed::Download(href)) => rsx! {
+                p {
+                    a { href, download: "{file_name}", "{output.path}" }
+                }
+            },
+            None => rsx! {
+                p { "{output.path}" }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use db::queries::chats_chunks::ChatChunks;
+    use db::{Chat, ChatStatus};
+    use tool_runtime::ToolCallFunction;
+
+    fn chat(id: i32, role: ChatRole, content: &str, tool_calls: Option<String>) -> ChatWithChunks {
+        let epoch = chrono::DateTime::UNIX_EPOCH.fixed_offset();
+        ChatWithChunks {
+            chat: Chat {
+                id,
+                conversation_id: 1,
+                content: Some(content.to_string()),
+                role,
+                tool_call_id: None,
+                tool_calls,
+                prompt_id: 1,
+                model_name: "gpt-4o".to_string(),
+                status: ChatStatus::Success,
+                attachments: None,
+                created_at: epoch,
+                updated_at: epoch,
+            },
+            chunks: Vec::new(),
+        }
+    }
+
+    fn transcript() -> Transcript {
+        let tool_call = ToolCall {
+            id: "call_1".to_string(),
+            call_id: None,
+            function: ToolCallFunction {
+                name: "search".to_string(),
+                arguments: serde_json::json!({ "q": "rust" }),
+            },
+            signature: None,
+            additional_params: None,
+        };
+        let mut answer = chat(
+            2,
+            ChatRole::Assistant,
+            "Here is ```code```",
+            tool_runtime::serialize_assistant_tool_state(
+                Some(&[tool_call]),
+                Some(&[Reasoning::new("Looked it up.")]),
+            ),
+        );
+        answer.chunks.push(ChatChunks {
+            chunk_id: 9,
+            chat_id: 2,
+            page_number: 4,
+            file_name: "handbook.pdf".to_string(),
+        });
+        Transcript::new(
+            Some("Research".to_string()),
+            None,
+            None,
+            vec![
+                chat(1, ChatRole::User, "What is Rust?\nThanks", None),
+                answer,
+                chat(3, ChatRole::System, "You are helpful", None),
+            ],
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn transcripts_keep_tool_calls_reasoning_and_citations() {
+        let transcript = transcript();
+        assert_eq!(transcript.title(), "What is Rust?");
+        assert_eq!(transcript.messages.len(), 2);
+        let answer = &transcript.messages[1];
+        assert_eq!(answer.model.as_deref(), Some("gpt-4o"));
+        assert_eq!(answer.tool_calls[0].function.name, "search");
+        assert_eq!(reasoning_text(&answer.reasoning), "Looked it up.");
+        assert_eq!(citation_text(&answer.citations[0]), "handbook.pdf (page 4)");
+
+        let json = serde_json::to_string(&transcript).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Transcript>(&json).unwrap(),
+            transcript
+        );
+    }
+
+    #[test]
+    fn markdown_fences_outlast_backticks_in_the_text() {
+        assert_eq!(fence("plain"), "```");
+        assert_eq!(fence("has ```` four"), "`````");
+
+        let markdown = to_markdown(&transcript());
+        assert!(markdown.starts_with("# What is Rust?\n\n_Research_\n\n## User\n"));
+        assert!(markdown.contains("## Assistant (gpt-4o)\n\n> Looked it up.\n"));
+        assert!(markdown.contains("Called `search`"));
+        assert!(markdown.contains("- handbook.pdf (page 4)"));
+    }
+
+    #[test]
+    fn only_raster_images_and_text_are_shown() {
+        let output = |mime_type: &str, data: &[u8]| Output {
+            path: "out/file".to_string(),
+            mime_type: mime_type.to_string(),
+            file_size: data.len() as i64,
+            data: Some(data.to_vec()),
+        };
+        assert_eq!(
+            embed(&output("image/png", b"png")),
+            Some(Embed::Image("data:image/png;base64,cG5n".to_string()))
+        );
+        assert_eq!(
+            embed(&output("text/csv", b"a,b")),
+            Some(Embed::Text("a,b".to_string()))
+        );
+        assert!(matches!(
+            embed(&output("image/svg+xml", b"<svg/>")),
+            Some(Embed::Download(_))
+        ));
+        assert!(matches!(
+            embed(&output("text/html", b"<script>")),
+            Some(Embed::Download(_))
+        ));
+    }
+
+    #[test]
+    fn pages_escape_what_people_typed() {
+        let mut transcript = transcript();
+        transcript.messages[0].content = Some("<script>alert(1)</script>".to_string());
+        transcript.messages[1].content = Some("<img src=x onerror=alert(1)>".to_string());
+        let html = page(transcript);
+        assert!(!html.contains("<script>alert"));
+        assert!(!html.contains("<img src=x"));
+    }
+}
@@ -0,0 +1,152 @@
+pub mod page;
+pub mod upsert;
+
+use db::TeamCost;
+
+/// Prices, costs and budgets are stored in micros, millionths of a currency unit.
+const MICROS: i64 = 1_000_000;
+
+/// Shows at least cents, and any smaller digits a price has.
+pub fn micros_to_string(micros: i64) -> String {
+    let sign = if micros < 0 { "-" } else { "" };
+    let micros = micros.unsigned_abs();
+    let fraction = format!("{:06}", micros % MICROS as u64);
+    let fraction = format!("{:0<2}", fraction.trim_end_matches('0'));
+    format!("{sign}{}.{fraction}", micros / MICROS as u64)
+}
+
+/// Parses an amount such as `2.50`, to at most six decimal places.
+pub fn string_to_micros(amount: &str) -> Option<i64> {
+    let amount = amount.trim();
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > 6
+        || !digits(whole)
+        || !digits(fraction)
+    {
+        return None;
+    }
+    let whole: i64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().ok()?
+    };
+    let fraction: i64 = format!("{fraction:0<6}").parse().ok()?;
+    whole.checked_mul(MICROS)?.checked_add(fraction)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CostTotal {
+    pub name: String,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub cost_micros: i64,
+}
+
+/// Adds up the month's usage under the name `group` gives each row, most expensive first.
+pub fn rollup(costs: &[TeamCost], group: impl Fn(&TeamCost) -> String) -> Vec<CostTotal> {
+    let mut totals: Vec<CostTotal> = Vec::new();
+    for cost in costs {
+        let name = group(cost);
+        let index = match totals.iter().position(|total| total.name == name) {
+            Some(index) => index,
+            None => {
+                totals.push(CostTotal {
+                    name,
+                    prompt_tokens: 0,
+                    completion_tokens: 0,
+                    cost_micros: 0,
+                });
+                totals.len() - 1
+            }
+        };
+        totals[index].prompt_tokens += cost.prompt_tokens;
+        totals[index].completion_tokens += cost.completion_tokens;
+        totals[index].cost_micros += cost.cost_micros;
+    }
+    totals.sort_by(|a, b| b.cost_micros.cmp(&a.cost_micros).then(a.name.cmp(&b.name)));
+    totals
+}
+
+/// Usage through the console has no API key.
+pub fn api_key_label(cost: &TeamCost) -> String {
+    match (&cost.api_key, cost.api_key_id) {
+        (Some(name), _) => name.clone(),
+        (None, Some(_)) => "Deleted API key".to_string(),
+        (None, None) => "Console".to_string(),
+    }
+}
+
+pub fn user_label(cost: &TeamCost) -> String {
+    cost.email.clone().unwrap_or_else(|| "Unknown".to_string())
+}
+
+pub fn assistant_label(cost: &TeamCost) -> String {
+    cost.assistant.clone().unwrap_or_else(|| "None".to_string())
+}
+
+pub fn model_label(cost: &TeamCost) -> String {
+    cost.model.clone().unwrap_or_else(|| "None".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amounts_show_cents_and_smaller_digits() {
+        assert_eq!(micros_to_string(0), "0.00");
+        assert_eq!(micros_to_string(3_000_000), "3.00");
+        assert_eq!(micros_to_string(150_000), "0.15");
+        assert_eq!(micros_to_string(1_234), "0.001234");
+        assert_eq!(micros_to_string(-2_500_000), "-2.50");
+    }
+
+    #[test]
+    fn amounts_parse_to_micros() {
+        assert_eq!(string_to_micros("3"), Some(3_000_000));
+        assert_eq!(string_to_micros(" 2.5 "), Some(2_500_000));
+        assert_eq!(string_to_micros(".000001"), Some(1));
+        assert_eq!(string_to_micros("0.0000001"), None);
+        assert_eq!(string_to_micros("-1"), None);
+        assert_eq!(string_to_micros("1,000"), None);
+        assert_eq!(string_to_micros(""), None);
+        assert_eq!(string_to_micros("."), None);
+    }
+
+    #[test]
+    fn rollups_add_up_each_name() {
+        let cost = |email: &str, api_key: Option<&str>, cost_micros| TeamCost {
+            email: Some(email.to_string()),
+            assistant: None,
+            api_key_id: api_key.map(|_| 1),
+            api_key: api_key.map(str::to_string),
+            model: None,
+            prompt_tokens: 10,
+            completion_tokens: 5,
+            cost_micros,
+        };
+        let costs = vec![
+            cost("a@example.com", None, 100),
+            cost("b@example.com", Some("ci"), 300),
+            cost("a@example.com", Some("ci"), 250),
+        ];
+
+        let by_user = rollup(&costs, user_label);
+        assert_eq!(by_user[0].name, "a@example.com");
+        assert_eq!(by_user[0].cost_micros, 350);
+        assert_eq!(by_user[0].prompt_tokens, 20);
+        assert_eq!(by_user[1].cost_micros, 300);
+
+        let by_key = rollup(&costs, api_key_label);
+        assert_eq!(by_key[0].name, "ci");
+        assert_eq!(by_key[1].name, "Console");
+
+        let deleted_key = TeamCost {
+            api_key: None,
+            ..cost("a@example.com", Some("old"), 10)
+        };
+        assert_eq!(api_key_label(&deleted_key), "Deleted API key");
+    }
+}
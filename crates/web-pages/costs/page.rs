@@ -0,0 +1,242 @@
+#![allow(non_snake_case)]
+use super::upsert::{BudgetForm, Upsert};
+use super::{
+    api_key_label, assistant_label, micros_to_string, model_label, rollup, user_label, CostTotal,
+};
+use crate::app_layout::{AdminLayout, SideBar};
+use crate::components::confirm_modal::ConfirmModal;
+use crate::SectionIntroduction;
+use assets::files::*;
+use daisy_rsx::*;
+use db::authz::Rbac;
+use db::{Budget, Member, TeamCost};
+use dioxus::prelude::*;
+
+/// The team's costs for `month` (YYYY-MM) and its budgets for the current month.
+pub fn page(
+    team_id: String,
+    rbac: Rbac,
+    month: String,
+    costs: Vec<TeamCost>,
+    budgets: Vec<Budget>,
+    members: Vec<Member>,
+) -> String {
+    let total: i64 = costs.iter().map(|cost| cost.cost_micros).sum();
+    let breakdowns = [
+        ("By User", rollup(&costs, user_label)),
+        ("By Assistant", rollup(&costs, assistant_label)),
+        ("By API Key", rollup(&costs, api_key_label)),
+        ("By Model", rollup(&costs, model_label)),
+    ];
+
+    let page = rsx! {
+        AdminLayout {
+            section_class: "p-4",
+            selected_item: SideBar::Costs,
+            team_id: team_id.clone(),
+            rbac: rbac,
+            title: "Costs & Budgets",
+            header: rsx!(
+                Breadcrumb {
+                    items: vec![BreadcrumbItem { text: "Costs & Budgets".into(), href: None }]
+                }
+                Button {
+                    prefix_image_src: "{button_plus_svg.name}",
+                    popover_target: "new-budget",
+                    button_scheme: ButtonScheme::Primary,
+                    "New Budget"
+                }
+            ),
+            div {
+                class: "p-4 max-w-5xl w-full mx-auto",
+                SectionIntroduction {
+                    header: "Costs & Budgets".to_string(),
+                    subtitle: "What this team's usage cost at each model's prices, and monthly budgets that warn and then stop chats when spending reaches them.".to_string(),
+                    is_empty: false,
+                    empty_text: "".to_string(),
+                }
+
+                Card {
+                    class: "mt-5 has-data-table",
+                    CardHeader { title: "Budgets" }
+                    CardBody {
+                        if budgets.is_empty() {
+                            p { class: "p-4", "No budgets yet, so spending isn't limited." }
+                        } else {
+                            table {
+                                class: "table table-sm",
+                                thead {
+                                    th { "Limits" }
+                                    th { class: "text-right", "Spent this month" }
+                                    th { class: "text-right", "Monthly limit" }
+                                    th { class: "text-right", "Warn" }
+                                    th { class: "text-right", "Stop" }
+                                    th { "Alert email" }
+                                    th { class: "text-right", "Action" }
+                                }
+                                tbody {
+                                    for budget in &budgets {
+                                        tr {
+                                            td {
+                                                if let Some(email) = &budget.email {
+                                                    "{email}"
+                                                } else {
+                                                    "The whole team"
+                                                }
+                                            }
+                                            td {
+                                                class: "text-right",
+                                                "{micros_to_string(budget.spent_micros)} ({budget.spent_micros * 100 / budget.monthly_limit_micros}%)"
+                                            }
+                                            td { class: "text-right", "{micros_to_string(budget.monthly_limit_micros)}" }
+                                            td { class: "text-right", "{budget.warn_percent}%" }
+                                            td {
+                                                class: "text-right",
+                                                if let Some(stop_percent) = budget.stop_percent {
+                                                    "{stop_percent}%"
+                                                } else {
+                                                    "Never"
+                                                }
+                                            }
+                                            td { {budget.alert_email.clone().unwrap_or_default()} }
+                                            td {
+                                                class: "text-right",
+                                                DropDown {
+                                                    direction: Direction::Left,
+                                                    button_text: "...",
+                                                    DropDownLink {
+                                                        popover_target: format!("edit-budget-{}", budget.id),
+                                                        href: "#",
+                                                        target: "_top",
+                                                        "Edit"
+                                                    }
+                                                    DropDownLink {
+                                                        popover_target: format!("delete-budget-{}", budget.id),
+                                                        href: "#",
+                                                        target: "_top",
+                                                        "Delete"
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                Card {
+                    class: "mt-5",
+                    CardHeader { title: "Usage Costs" }
+                    CardBody {
+                        div {
+                            class: "flex flex-wrap items-end justify-between gap-4",
+                            form {
+                                method: "get",
+                                action: crate::routes::costs::Index { team_id: team_id.clone() }.to_string(),
+                                class: "flex items-end gap-2",
+                                Fieldset {
+                                    legend: "Month (UTC)",
+                                    input {
+                                        "type": "month",
+                                        class: "input input-bordered",
+                                        name: "month",
+                                        value: "{month}",
+                                    }
+                                }
+                                Button {
+                                    button_type: ButtonType::Submit,
+                                    "Show"
+                                }
+                            }
+                            div {
+                                class: "flex items-center gap-4",
+                                strong { "Total {micros_to_string(total)}" }
+                                a {
+                                    class: "btn btn-sm",
+                                    href: crate::routes::costs::Export { team_id: team_id.clone(), month: month.clone() }.to_string(),
+                                    "Download CSV"
+                                }
+                            }
+                        }
+                        if costs.is_empty() {
+                            p { class: "mt-4", "No usage was recorded in {month}." }
+                        }
+                    }
+                }
+
+                if !costs.is_empty() {
+                    for (title, totals) in breakdowns {
+                        CostTable { title, totals }
+                    }
+                }
+
+                for budget in &budgets {
+                    Upsert {
+                        trigger_id: format!("edit-budget-{}", budget.id),
+                        action: crate::routes::costs::UpsertBudget { team_id: team_id.clone() }.to_string(),
+                        form: BudgetForm {
+                            user_id: budget.user_id.map(|id| id.to_string()).unwrap_or_default(),
+                            monthly_limit: micros_to_string(budget.monthly_limit_micros),
+                            warn_percent: budget.warn_percent.to_string(),
+                            stop_percent: budget.stop_percent.map(|p| p.to_string()).unwrap_or_default(),
+                            alert_email: budget.alert_email.clone().unwrap_or_default(),
+                        },
+                        members: members.clone(),
+                        is_new: false
+                    }
+                    ConfirmModal {
+                        action: crate::routes::costs::DeleteBudget { team_id: team_id.clone(), id: budget.id }.to_string(),
+                        trigger_id: format!("delete-budget-{}", budget.id),
+                        submit_label: "Delete".to_string(),
+                        heading: "Delete this Budget?".to_string(),
+                        warning: "Spending it covers will no longer be limited or alerted on.".to_string(),
+                        hidden_fields: vec![],
+                    }
+                }
+
+                Upsert {
+                    trigger_id: "new-budget",
+                    action: crate::routes::costs::UpsertBudget { team_id: team_id.clone() }.to_string(),
+                    form: BudgetForm::default(),
+                    members: members.clone(),
+                    is_new: true
+                }
+            }
+        }
+    };
+
+    crate::render(page)
+}
+
+#[component]
+fn CostTable(title: String, totals: Vec<CostTotal>) -> Element {
+    rsx!(
+        Card {
+            class: "mt-5 has-data-table",
+            CardHeader { title: "{title}" }
+            CardBody {
+                table {
+                    class: "table table-sm",
+                    thead {
+                        th { "Name" }
+                        th { class: "text-right", "Prompt tokens" }
+                        th { class: "text-right", "Completion tokens" }
+                        th { class: "text-right", "Cost" }
+                    }
+                    tbody {
+                        for total in &totals {
+                            tr {
+                                td { "{total.name}" }
+                                td { class: "text-right", "{total.prompt_tokens}" }
+                                td { class: "text-right", "{total.completion_tokens}" }
+                                td { class: "text-right", "{micros_to_string(total.cost_micros)}" }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
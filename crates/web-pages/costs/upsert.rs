@@ -0,0 +1,122 @@
+#![allow(non_snake_case)]
+use daisy_rsx::*;
+use db::Member;
+use dioxus::prelude::*;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct BudgetForm {
+    /// Blank for the whole team.
+    pub user_id: String,
+    pub monthly_limit: String,
+    pub warn_percent: String,
+    /// Blank to only send alerts.
+    pub stop_percent: String,
+    pub alert_email: String,
+}
+
+impl Default for BudgetForm {
+    fn default() -> Self {
+        Self {
+            user_id: String::new(),
+            monthly_limit: String::new(),
+            warn_percent: "80".to_string(),
+            stop_percent: "100".to_string(),
+            alert_email: String::new(),
+        }
+    }
+}
+
+/// Sets the budget for the whole team or one member, replacing any they already have.
+#[component]
+pub fn Upsert(
+    trigger_id: String,
+    action: String,
+    form: BudgetForm,
+    members: Vec<Member>,
+    is_new: bool,
+) -> Element {
+    rsx!(
+        Modal {
+            submit_action: action,
+            trigger_id,
+            ModalBody {
+                class: "flex flex-col gap-4",
+                h3 {
+                    class: "font-bold text-lg mb-4",
+                    if is_new { "New Budget" } else { "Edit Budget" }
+                }
+                Fieldset {
+                    legend: "Who it limits",
+                    Select {
+                        class: "w-full",
+                        name: "user_id",
+                        SelectOption {
+                            value: "",
+                            selected_value: form.user_id.clone(),
+                            "The whole team"
+                        }
+                        for member in &members {
+                            SelectOption {
+                                value: "{member.id}",
+                                selected_value: form.user_id.clone(),
+                                "{member.email}"
+                            }
+                        }
+                    }
+                }
+                Fieldset {
+                    legend: "Monthly limit",
+                    help_text: "In the currency your model prices are set in",
+                    Input {
+                        input_type: InputType::Text,
+                        class: "w-full",
+                        placeholder: "500.00",
+                        name: "monthly_limit",
+                        value: form.monthly_limit,
+                        required: true,
+                    }
+                }
+                Fieldset {
+                    legend: "Warn at (% of the limit)",
+                    help_text: "Alerts go out once spending reaches this share",
+                    Input {
+                        input_type: InputType::Number,
+                        class: "w-full",
+                        name: "warn_percent",
+                        value: form.warn_percent,
+                        required: true,
+                    }
+                }
+                Fieldset {
+                    legend: "Stop at (% of the limit)",
+                    help_text: "Chats are refused from this share until next month. Leave blank to only alert",
+                    Input {
+                        input_type: InputType::Number,
+                        class: "w-full",
+                        name: "stop_percent",
+                        value: form.stop_percent,
+                    }
+                }
+                Fieldset {
+                    legend: "Alert email",
+                    help_text: "Optional. Alerts also go to the team's webhooks for budget events",
+                    Input {
+                        input_type: InputType::Email,
+                        class: "w-full",
+                        placeholder: "finance@example.com",
+                        name: "alert_email",
+                        value: form.alert_email,
+                    }
+                }
+                ModalAction {
+                    Button { class: "cancel-modal", button_scheme: ButtonScheme::Warning, "Cancel" }
+                    Button {
+                        button_type: ButtonType::Submit,
+                        button_scheme: ButtonScheme::Primary,
+                        if is_new { "Create Budget" } else { "Save" }
+                    }
+                }
+            }
+        }
+    )
+}
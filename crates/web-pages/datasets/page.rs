@@ -78,9 +78,11 @@ pub fn page(
                     name: "".to_string(),
                     models: models.clone(),
                     team_id: team_id.clone(),
+                    chunking_strategy: ChunkingStrategy::ByTitle,
                     combine_under_n_chars: 500,
                     new_after_n_chars: 1000,
                     _multipage_sections: true,
+                    priority: 0,
                     visibility: db::Visibility::Private,
                     can_set_visibility_to_company,
                     locale: locale.to_string()
@@ -100,9 +102,7 @@ fn DatasetCard(dataset: Dataset, team_id: String) -> Element {
     }
     .to_string();
     let document_count = usize::try_from(dataset.count).unwrap_or(0);
-    let chunking_label = match dataset.chunking_strategy {
-        ChunkingStrategy::ByTitle => "By Title",
-    };
+    let chunking_label = crate::chunking_strategy_to_string(dataset.chunking_strategy);
     let avatar_initial = dataset.name.chars().next().unwrap_or('D').to_string();
 
     rsx!(CardItem {
@@ -1,7 +1,8 @@
 #![allow(non_snake_case)]
 use crate::i18n;
-use daisy_rsx::*;
+use daisy_rsx::{select::SelectOption, *};
 use db::queries::models;
+use db::types::ChunkingStrategy;
 use db::Visibility;
 use dioxus::prelude::*;
 
@@ -12,9 +13,11 @@ pub fn Upsert(
     models: Vec<models::Model>,
     name: String,
     team_id: String,
+    chunking_strategy: ChunkingStrategy,
     combine_under_n_chars: i32,
     new_after_n_chars: i32,
     _multipage_sections: bool,
+    priority: i32,
     visibility: Visibility,
     can_set_visibility_to_company: bool,
     locale: String,
@@ -126,14 +129,16 @@ pub fn Upsert(
                             Fieldset {
                                 legend: "Select the Chunking Strategy",
                                 legend_class: "mt-4",
-                                help_text: "These are the chunking strategies supported by unstructured.",
+                                help_text: "Unstructured always chunks by title. The native engine offers every strategy.",
                                 Select {
                                     class: "w-full",
                                     name: "chunking_strategy",
-                                    value: "By Title",
-                                    option {
-                                        value: "By Title",
-                                        "By Title"
+                                    for strategy in crate::CHUNKING_STRATEGIES {
+                                        SelectOption {
+                                            value: "{crate::chunking_strategy_to_string(strategy)}",
+                                            selected_value: "{crate::chunking_strategy_to_string(chunking_strategy)}",
+                                            {crate::chunking_strategy_to_string(strategy)}
+                                        }
                                     }
                                 }
                             }
@@ -163,6 +168,19 @@ pub fn Upsert(
                                 }
                             }
 
+                            Fieldset {
+                                legend: "Processing Priority",
+                                legend_class: "mt-4",
+                                help_text: "Documents in datasets with a higher priority are processed first, from 0 to 10",
+                                Input {
+                                    input_type: InputType::Number,
+                                    class: "w-full",
+                                    value: "{priority}",
+                                    required: true,
+                                    name: "priority"
+                                }
+                            }
+
                             Fieldset {
                                 legend: "Multipage Sections",
                                 legend_class: "mt-4",
@@ -0,0 +1,141 @@
+#![allow(non_snake_case)]
+use crate::app_layout::{AdminLayout, SideBar};
+use crate::SectionIntroduction;
+use daisy_rsx::*;
+use db::authz::Rbac;
+use db::{MappableCustomRoles, MappableTeams, Role, ScimGroup, ScimGroupMembers};
+use dioxus::prelude::*;
+
+/// The mapping select's value for a group: blank, or the team id and then the role
+/// the way invitations name it, e.g. `12:TeamManager` or `12:5` for a custom role.
+pub fn mapping_value(group: &ScimGroup) -> String {
+    match (group.team_id, group.custom_role_id) {
+        (None, _) => String::new(),
+        (Some(team_id), Some(custom_role_id)) => format!("{team_id}:{custom_role_id}"),
+        (Some(team_id), None) if group.roles.contains(&Role::TeamManager) => {
+            format!("{team_id}:TeamManager")
+        }
+        (Some(team_id), None) => format!("{team_id}:Collaborator"),
+    }
+}
+
+pub fn page(
+    team_id: String,
+    rbac: Rbac,
+    endpoint: Option<String>,
+    groups: Vec<ScimGroup>,
+    members: Vec<ScimGroupMembers>,
+    teams: Vec<MappableTeams>,
+    custom_roles: Vec<MappableCustomRoles>,
+) -> String {
+    let team_name = |team: &MappableTeams| {
+        team.name
+            .clone()
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| format!("Team {}", team.id))
+    };
+
+    let page = rsx! {
+        AdminLayout {
+            section_class: "p-4",
+            selected_item: SideBar::DirectorySync,
+            team_id: team_id.clone(),
+            rbac: rbac,
+            title: "Directory Sync",
+            header: rsx!(
+                Breadcrumb {
+                    items: vec![BreadcrumbItem { text: "Directory Sync".into(), href: None }]
+                }
+            ),
+            div {
+                class: "p-4 max-w-5xl w-full mx-auto",
+                SectionIntroduction {
+                    header: "Directory Sync".to_string(),
+                    subtitle: "Your identity provider creates and deactivates users over SCIM, and pushes its groups here. Map each group to a team and role, and its members join that team.".to_string(),
+                    is_empty: groups.is_empty(),
+                    empty_text: "No groups have been pushed by your identity provider yet.".to_string(),
+                }
+
+                Card {
+                    class: "mt-5",
+                    CardHeader { title: "SCIM Endpoint" }
+                    CardBody {
+                        if let Some(endpoint) = &endpoint {
+                            p { "Give your identity provider this base URL and the token set in SCIM_TOKEN." }
+                            code { class: "block mt-2", "{endpoint}" }
+                        } else {
+                            p { "SCIM is turned off. Set SCIM_TOKEN on the web server to turn it on." }
+                        }
+                    }
+                }
+
+                if !groups.is_empty() {
+                    Card {
+                        class: "mt-5 has-data-table",
+                        CardHeader { title: "Groups" }
+                        CardBody {
+                            table {
+                                class: "table table-sm",
+                                thead {
+                                    th { "Group" }
+                                    th { "Members" }
+                                    th { "Team and Role" }
+                                }
+                                tbody {
+                                    for group in &groups {
+                                        tr {
+                                            td { "{group.display_name}" }
+                                            td { "{members.iter().filter(|member| member.group_id == group.id).count()}" }
+                                            td {
+                                                form {
+                                                    method: "post",
+                                                    action: crate::routes::directory_sync::Map { team_id: team_id.clone(), id: group.id }.to_string(),
+                                                    class: "flex gap-2",
+                                                    Select {
+                                                        class: "w-full",
+                                                        name: "mapping",
+                                                        SelectOption {
+                                                            value: "",
+                                                            selected_value: mapping_value(group),
+                                                            "Not mapped"
+                                                        }
+                                                        for team in &teams {
+                                                            SelectOption {
+                                                                value: "{team.id}:Collaborator",
+                                                                selected_value: mapping_value(group),
+                                                                "{team_name(team)}: Collaborator"
+                                                            }
+                                                            SelectOption {
+                                                                value: "{team.id}:TeamManager",
+                                                                selected_value: mapping_value(group),
+                                                                "{team_name(team)}: Team Manager"
+                                                            }
+                                                            for role in custom_roles.iter().filter(|role| role.team_id == team.id) {
+                                                                SelectOption {
+                                                                    value: "{team.id}:{role.id}",
+                                                                    selected_value: mapping_value(group),
+                                                                    "{team_name(team)}: {role.name}"
+                                                                }
+                                                            }
+                                                        }
+                                                    }
+                                                    Button {
+                                                        button_type: ButtonType::Submit,
+                                                        button_scheme: ButtonScheme::Primary,
+                                                        "Save"
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    crate::render(page)
+}
@@ -135,9 +135,11 @@ pub fn page(
                         name: dataset_name.clone(),
                         models: models.clone(),
                         team_id: team_id.clone(),
+                        chunking_strategy: dataset.chunking_strategy,
                         combine_under_n_chars: dataset.combine_under_n_chars,
                         new_after_n_chars: dataset.new_after_n_chars,
                         _multipage_sections: true,
+                        priority: dataset.priority,
                         visibility: dataset.visibility,
                         can_set_visibility_to_company,
                         locale: locale.to_string()
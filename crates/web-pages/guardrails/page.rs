@@ -0,0 +1,228 @@
+#![allow(non_snake_case)]
+use crate::app_layout::{AdminLayout, SideBar};
+use daisy_rsx::{select::SelectOption, *};
+use db::authz::Rbac;
+use db::{
+    ModerationAction, ModerationDirection, ModerationPolicy, OutputModeration, PromptFlag,
+    PromptFlagType,
+};
+use dioxus::prelude::*;
+
+/// The hazard categories guard models report, in Llama Guard order.
+pub const CATEGORIES: [(PromptFlagType, &str, &str); 14] = [
+    (PromptFlagType::S1, "S1", "Violent Crimes"),
+    (PromptFlagType::S2, "S2", "Non-Violent Crimes"),
+    (PromptFlagType::S3, "S3", "Sex-Related Crimes"),
+    (PromptFlagType::S4, "S4", "Child Sexual Exploitation"),
+    (PromptFlagType::S5, "S5", "Defamation"),
+    (PromptFlagType::S6, "S6", "Specialized Advice"),
+    (PromptFlagType::S7, "S7", "Privacy"),
+    (PromptFlagType::S8, "S8", "Intellectual Property"),
+    (PromptFlagType::S9, "S9", "Indiscriminate Weapons"),
+    (PromptFlagType::S10, "S10", "Hate"),
+    (PromptFlagType::S11, "S11", "Suicide & Self-Harm"),
+    (PromptFlagType::S12, "S12", "Sexual Content"),
+    (PromptFlagType::S13, "S13", "Elections"),
+    (PromptFlagType::S14, "S14", "Code Interpreter Abuse"),
+];
+
+pub fn category_code(flag_type: PromptFlagType) -> &'static str {
+    CATEGORIES
+        .iter()
+        .find(|(category, _, _)| *category == flag_type)
+        .map(|(_, code, _)| *code)
+        .unwrap_or_default()
+}
+
+pub fn page(
+    team_id: String,
+    rbac: Rbac,
+    output_moderation: OutputModeration,
+    policies: Vec<ModerationPolicy>,
+    flags: Vec<PromptFlag>,
+    has_guard_model: bool,
+) -> String {
+    let output_moderation = crate::output_moderation_to_string(output_moderation);
+
+    let page = rsx! {
+        AdminLayout {
+            section_class: "p-4",
+            selected_item: SideBar::Guardrails,
+            team_id: team_id.clone(),
+            rbac: rbac,
+            title: "Guardrails",
+            header: rsx! {
+                Breadcrumb {
+                    items: vec![BreadcrumbItem {
+                        text: "Guardrails".into(),
+                        href: None
+                    }]
+                }
+            },
+
+            if !has_guard_model {
+                div {
+                    class: "alert alert-warning mb-6",
+                    "Add a model with the Guard type before turning on moderation."
+                }
+            }
+
+            form {
+                method: "post",
+                action: crate::routes::guardrails::Update { team_id: team_id.clone() }.to_string(),
+                Card {
+                    class: "mb-6",
+                    CardHeader { title: "Response Moderation" }
+                    CardBody {
+                        Fieldset {
+                            legend: "Check assistant responses",
+                            help_text: "Questions are checked for models with the Guarded capability. Responses can be checked once they are complete, or every few hundred characters as they stream. Blocked responses are withdrawn from the chat.",
+                            Select {
+                                name: "output_moderation",
+                                value: output_moderation.clone(),
+                                SelectOption { value: "Off", selected_value: output_moderation.clone(), "Off" }
+                                SelectOption { value: "OnCompletion", selected_value: output_moderation.clone(), "When the response is complete" }
+                                SelectOption { value: "Streaming", selected_value: output_moderation.clone(), "While the response streams" }
+                            }
+                        }
+                    }
+                }
+
+                Card {
+                    class: "has-data-table mb-6",
+                    CardHeader { title: "Category Policy" }
+                    CardBody {
+                        table {
+                            class: "table table-sm",
+                            thead {
+                                th { "Category" }
+                                th { "Description" }
+                                th {
+                                    class: "text-right",
+                                    "When flagged"
+                                }
+                            }
+                            tbody {
+                                for (flag_type, code, description) in CATEGORIES {
+                                    PolicyRow {
+                                        code,
+                                        description,
+                                        action: crate::moderation_action_to_string(
+                                            policies
+                                                .iter()
+                                                .find(|policy| policy.flag_type == flag_type)
+                                                .map(|policy| policy.action)
+                                                .unwrap_or(ModerationAction::Block)
+                                        )
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                div {
+                    class: "flex justify-end mb-6",
+                    Button {
+                        button_type: ButtonType::Submit,
+                        button_scheme: ButtonScheme::Primary,
+                        "Save Guardrails"
+                    }
+                }
+            }
+
+            FlagTable { flags }
+        }
+    };
+
+    crate::render(page)
+}
+
+#[component]
+fn PolicyRow(code: &'static str, description: &'static str, action: String) -> Element {
+    rsx!(
+        tr {
+            td {
+                input { "type": "hidden", name: "flag_type", value: code }
+                "{code}"
+            }
+            td { "{description}" }
+            td {
+                class: "text-right",
+                Select {
+                    name: "action",
+                    value: action.clone(),
+                    SelectOption { value: "Block", selected_value: action.clone(), "Block" }
+                    SelectOption { value: "Flag", selected_value: action.clone(), "Flag only" }
+                }
+            }
+        }
+    )
+}
+
+#[component]
+fn FlagTable(flags: Vec<PromptFlag>) -> Element {
+    rsx!(
+        Card {
+            class: "has-data-table",
+            CardHeader { title: "Recent Flags" }
+            CardBody {
+                if flags.is_empty() {
+                    p { class: "p-4 text-sm opacity-80", "Nothing has been flagged yet." }
+                } else {
+                    table {
+                        class: "table table-sm",
+                        thead {
+                            th { "When" }
+                            th { "User" }
+                            th { "Category" }
+                            th { "Checked" }
+                            th {
+                                class: "text-right",
+                                "Outcome"
+                            }
+                        }
+                        tbody {
+                            for flag in flags {
+                                tr {
+                                    td {
+                                        RelativeTime {
+                                            format: RelativeTimeFormat::Relative,
+                                            datetime: &flag.created_at
+                                        }
+                                    }
+                                    td { "{flag.email}" }
+                                    td { {category_code(flag.flag_type)} }
+                                    td {
+                                        Badge {
+                                            badge_color: BadgeColor::Neutral,
+                                            badge_style: BadgeStyle::Outline,
+                                            badge_size: BadgeSize::Sm,
+                                            if flag.direction == ModerationDirection::Output { "Response" } else { "Question" }
+                                        }
+                                    }
+                                    td {
+                                        class: "text-right",
+                                        if flag.action == ModerationAction::Block {
+                                            Badge {
+                                                badge_color: BadgeColor::Error,
+                                                badge_size: BadgeSize::Sm,
+                                                "Blocked"
+                                            }
+                                        } else {
+                                            Badge {
+                                                badge_color: BadgeColor::Warning,
+                                                badge_size: BadgeSize::Sm,
+                                                "Flagged"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
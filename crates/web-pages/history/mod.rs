@@ -96,3 +96,121 @@ pub fn bucket_history(histories: Vec<History>) -> (Vec<HistoryBucket>, usize) {
 
     (buckets, total_count)
 }
+
+/// Part of a search snippet, highlighted when it matches a search word.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Segment {
+    pub text: String,
+    pub highlighted: bool,
+}
+
+/// Cuts about `width` characters out of `content` around the first search word it
+/// contains, and splits it so the search words can be highlighted.
+pub fn snippet(content: &str, words: &[String], width: usize) -> Vec<Segment> {
+    let chars: Vec<char> = content
+        .chars()
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .collect();
+    let lower: Vec<char> = chars.iter().map(|c| lowercase(*c)).collect();
+    let words: Vec<Vec<char>> = words
+        .iter()
+        .filter(|word| !word.is_empty())
+        .map(|word| word.chars().map(lowercase).collect())
+        .collect();
+
+    // Which characters are part of a search word.
+    let mut matched = vec![false; chars.len()];
+    for word in &words {
+        let mut at = 0;
+        while at + word.len() <= lower.len() {
+            if lower[at..at + word.len()] == word[..] {
+                matched[at..at + word.len()].fill(true);
+                at += word.len();
+            } else {
+                at += 1;
+            }
+        }
+    }
+
+    let first = matched.iter().position(|m| *m).unwrap_or(0);
+    let start = first.saturating_sub(width / 3);
+    let end = (start + width).min(chars.len());
+
+    let mut segments: Vec<Segment> = Vec::new();
+    if start > 0 {
+        segments.push(Segment {
+            text: "…".to_string(),
+            highlighted: false,
+        });
+    }
+    for index in start..end {
+        match segments.last_mut() {
+            Some(last) if last.highlighted == matched[index] => last.text.push(chars[index]),
+            _ => segments.push(Segment {
+                text: chars[index].to_string(),
+                highlighted: matched[index],
+            }),
+        }
+    }
+    if end < chars.len() {
+        match segments.last_mut() {
+            Some(last) if !last.highlighted => last.text.push('…'),
+            _ => segments.push(Segment {
+                text: "…".to_string(),
+                highlighted: false,
+            }),
+        }
+    }
+    segments
+}
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    fn render(segments: &[Segment]) -> String {
+        segments
+            .iter()
+            .map(|segment| {
+                if segment.highlighted {
+                    format!("[{}]", segment.text)
+                } else {
+                    segment.text.clone()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn highlights_every_search_word_ignoring_case() {
+        let segments = snippet(
+            "Rust traits and rust enums",
+            &words(&["rust", "enums"]),
+            100,
+        );
+        assert_eq!(render(&segments), "[Rust] traits and [rust] [enums]");
+    }
+
+    #[test]
+    fn window_starts_near_the_first_match() {
+        let content = format!("{}needle{}", "a".repeat(100), "b".repeat(100));
+        let segments = snippet(&content, &words(&["needle"]), 30);
+        let rendered = render(&segments);
+        assert!(rendered.starts_with("…aaaaaaaaaa[needle]"));
+        assert!(rendered.ends_with("b…"));
+    }
+
+    #[test]
+    fn no_match_shows_the_start() {
+        let segments = snippet("found by meaning\nnot by words", &words(&["absent"]), 10);
+        assert_eq!(render(&segments), "found by m…");
+    }
+}
@@ -28,16 +28,26 @@ pub fn page(rbac: Rbac, team_id: String, history: Vec<History>, locale: &str) ->
                         href: None
                     }]
                 }
-                Button {
-                    prefix_image_src: "{button_plus_svg.name}",
-                    popover_target: "search-history",
-                    button_scheme: ButtonScheme::Primary,
-                    "{search_label}"
+                div {
+                    class: "flex gap-2",
+                    Button {
+                        popover_target: "import-conversation",
+                        "Import"
+                    }
+                    Button {
+                        prefix_image_src: "{button_plus_svg.name}",
+                        popover_target: "search-history",
+                        button_scheme: ButtonScheme::Primary,
+                        "{search_label}"
+                    }
                 }
             },
             super::form::Form {
                 team_id: team_id.clone()
             }
+            crate::console::import::Import {
+                team_id: team_id.clone()
+            }
             div {
                 class: "p-4 max-w-3xl w-full mx-auto",
                 SectionIntroduction {
@@ -3,12 +3,19 @@ use crate::app_layout::{Layout, SideBar};
 use crate::{i18n, SectionIntroduction};
 use daisy_rsx::*;
 use db::authz::Rbac;
-use db::History;
+use db::history_search::{search_words, ConversationMatch};
+use db::PromptType;
 use dioxus::prelude::*;
 
-pub fn page(rbac: Rbac, team_id: String, history: Vec<History>, locale: &str) -> String {
-    let buckets = super::bucket_history(history);
+pub fn page(
+    rbac: Rbac,
+    team_id: String,
+    results: Vec<ConversationMatch>,
+    search: String,
+    locale: &str,
+) -> String {
     let history_label = i18n::histories(locale);
+    let words = search_words(&search);
     let page = rsx! {
         Layout {
             section_class: "p-4",
@@ -32,15 +39,51 @@ pub fn page(rbac: Rbac, team_id: String, history: Vec<History>, locale: &str) ->
             ),
             SectionIntroduction {
                 header: "Search Results".to_string(),
-                subtitle: "Browse through your chat history search results.".to_string(),
-                is_empty: buckets.1 == 0,
+                subtitle: format!("Conversations about \"{}\", best matches first.", search),
+                is_empty: results.is_empty(),
                 empty_text: "We didn't find any results for your search. Please try again with a different query.".to_string(),
             }
 
-            if buckets.1 > 0 {
-                super::history_table::HistoryTable {
-                    team_id: team_id.clone(),
-                    buckets: buckets.0
+            div {
+                class: "p-4 max-w-3xl w-full mx-auto",
+                for result in results {
+                    Card {
+                        class: "mt-6",
+                        CardHeader {
+                            title: "Conversation",
+                            RelativeTime {
+                                format: RelativeTimeFormat::Relative,
+                                datetime: result.created_at.to_rfc3339()
+                            }
+                        }
+                        CardBody {
+                            for content in result.snippets {
+                                p {
+                                    class: "mb-2",
+                                    for segment in super::snippet(&content, &words, 200) {
+                                        if segment.highlighted {
+                                            mark { "{segment.text}" }
+                                        } else {
+                                            "{segment.text}"
+                                        }
+                                    }
+                                }
+                            }
+                            if result.prompt_type == PromptType::Model {
+                                a {
+                                    class: "link",
+                                    href: crate::routes::console::Conversation{team_id: team_id.clone(), conversation_id: result.id}.to_string(),
+                                    "Open conversation"
+                                }
+                            } else {
+                                a {
+                                    class: "link",
+                                    href: crate::routes::prompts::Conversation{team_id: team_id.clone(), prompt_id: result.prompt_id, conversation_id: result.id }.to_string(),
+                                    "Open conversation"
+                                }
+                            }
+                        }
+                    }
                 }
             }
 
@@ -0,0 +1,140 @@
+#![allow(non_snake_case)]
+use daisy_rsx::*;
+use db::{ToolCallPolicy, ToolCallPolicyRow};
+use dioxus::prelude::*;
+use tool_runtime::builtin_tools::approvals::{default_policy, ALL_OPERATIONS};
+
+/// An action of the integration and the HTTP method it uses.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ActionMethod {
+    pub operation_id: String,
+    pub method: String,
+}
+
+/// Lets the team choose which actions run straight away and which wait for the user.
+#[component]
+pub fn ApprovalPolicySection(
+    team_id: String,
+    integration_id: i32,
+    can_manage: bool,
+    actions: Vec<ActionMethod>,
+    policies: Vec<ToolCallPolicyRow>,
+) -> Element {
+    let policy_for = |operation_id: &str| {
+        policies
+            .iter()
+            .find(|row| row.operation_id == operation_id)
+            .map(|row| policy_value(row.policy))
+            .unwrap_or("Default")
+            .to_string()
+    };
+    let action = crate::routes::integrations::SetToolCallPolicy {
+        team_id,
+        integration_id,
+    }
+    .to_string();
+
+    rsx! {
+        div {
+            class: "mb-8",
+            h2 {
+                class: "font-semibold",
+                "Approvals"
+            }
+            p {
+                class: "text-sm opacity-80 mt-1 mb-4",
+                "By default actions that only read (GET) run straight away and every other action waits for the user to approve the exact request. An action's own setting overrides the one for all actions."
+            }
+            PolicyRow {
+                action: action.clone(),
+                can_manage,
+                operation_id: ALL_OPERATIONS.to_string(),
+                label: "All actions".to_string(),
+                method: None,
+                default_label: "Default (by method)".to_string(),
+                value: policy_for(ALL_OPERATIONS)
+            }
+            for action_method in actions {
+                PolicyRow {
+                    action: action.clone(),
+                    can_manage,
+                    operation_id: action_method.operation_id.clone(),
+                    label: action_method.operation_id.clone(),
+                    default_label: format!(
+                        "Default ({})",
+                        policy_value(default_policy(&action_method.method))
+                    ),
+                    method: Some(action_method.method.clone()),
+                    value: policy_for(&action_method.operation_id)
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn PolicyRow(
+    action: String,
+    can_manage: bool,
+    operation_id: String,
+    label: String,
+    method: Option<String>,
+    default_label: String,
+    value: String,
+) -> Element {
+    rsx! {
+        form {
+            method: "post",
+            action: "{action}",
+            class: "flex items-center justify-between gap-4 py-2 border-b border-base-300 text-sm",
+            input {
+                "type": "hidden",
+                name: "operation_id",
+                value: "{operation_id}"
+            }
+            div {
+                class: "flex items-center gap-2",
+                if let Some(method) = method {
+                    Badge {
+                        badge_style: BadgeStyle::Outline,
+                        badge_size: BadgeSize::Sm,
+                        "{method}"
+                    }
+                }
+                span { "{label}" }
+            }
+            if can_manage {
+                div {
+                    class: "flex items-center gap-2",
+                    Select {
+                        name: "policy",
+                        value: value.clone(),
+                        SelectOption { value: "Default", selected_value: value.clone(), "{default_label}" }
+                        SelectOption { value: "Allow", selected_value: value.clone(), "Allow" }
+                        SelectOption { value: "Confirm", selected_value: value.clone(), "Confirm" }
+                        SelectOption { value: "Deny", selected_value: value.clone(), "Deny" }
+                    }
+                    Button {
+                        button_type: ButtonType::Submit,
+                        button_scheme: ButtonScheme::Primary,
+                        button_size: ButtonSize::Small,
+                        "Save"
+                    }
+                }
+            } else {
+                span {
+                    class: "opacity-80",
+                    if value == "Default" { "{default_label}" } else { "{value}" }
+                }
+            }
+        }
+    }
+}
+
+fn policy_value(policy: ToolCallPolicy) -> &'static str {
+    match policy {
+        ToolCallPolicy::Allow => "Allow",
+        ToolCallPolicy::Confirm => "Confirm",
+        ToolCallPolicy::Deny => "Deny",
+    }
+}
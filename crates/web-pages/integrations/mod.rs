@@ -1,6 +1,7 @@
 pub mod actions_section;
 pub mod api_key_cards;
 pub mod api_key_form;
+pub mod approval_policy;
 pub mod connections_section;
 pub mod integration_card;
 pub mod integration_header;
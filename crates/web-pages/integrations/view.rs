@@ -1,11 +1,12 @@
 #![allow(non_snake_case)]
 use super::actions_section::ActionsSection;
+use super::approval_policy::{ActionMethod, ApprovalPolicySection};
 use super::connections_section::ConnectionsSection;
 use super::integration_header::IntegrationHeader;
 use crate::app_layout::{Layout, SideBar};
 use crate::i18n;
 use daisy_rsx::*;
-use db::{authz::Rbac, ApiKeyConnection, Integration, Oauth2Connection};
+use db::{authz::Rbac, ApiKeyConnection, Integration, Oauth2Connection, ToolCallPolicyRow};
 use dioxus::prelude::*;
 use tool_runtime::BionicOpenAPI;
 use tool_runtime::ToolDefinition;
@@ -20,6 +21,8 @@ pub fn view(
     api_key_connections: Vec<ApiKeyConnection>,
     oauth2_connections: Vec<Oauth2Connection>,
     oauth_client_configured: bool,
+    action_methods: Vec<ActionMethod>,
+    tool_call_policies: Vec<ToolCallPolicyRow>,
     locale: &str,
 ) -> String {
     let integrations_label = i18n::integrations(locale);
@@ -64,13 +67,21 @@ pub fn view(
                 ConnectionsSection {
                     team_id: team_id.clone(),
                     integration_id: integration.id,
-                    rbac,
+                    rbac: rbac.clone(),
                     openapi: openapi.clone(),
                     api_key_connections,
                     oauth2_connections,
                     oauth_client_configured
                 }
 
+                ApprovalPolicySection {
+                    team_id: team_id.clone(),
+                    integration_id: integration.id,
+                    can_manage: rbac.can_manage_integrations(),
+                    actions: action_methods,
+                    policies: tool_call_policies
+                }
+
                 ActionsSection {
                     logo_url: openapi.clone().get_logo_url(),
                     tool_definitions
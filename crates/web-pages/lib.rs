@@ -1,16 +1,21 @@
-use db::Visibility;
+use db::types::ChunkingStrategy;
+use db::{GuardFormat, ModerationAction, OutputModeration, ProviderKind, Visibility};
 use dioxus::prelude::Element;
 
 pub mod api_keys;
 pub mod app_layout;
 pub mod assistants;
 pub mod audit_trail;
+pub mod automations;
 pub mod categories;
 pub mod charts;
 pub mod components;
 pub mod console;
+pub mod costs;
 pub mod datasets;
+pub mod directory_sync;
 pub mod documents;
+pub mod guardrails;
 pub mod history;
 pub mod i18n;
 pub mod integrations;
@@ -26,6 +31,8 @@ pub mod profile_popup;
 pub mod projects;
 pub mod providers;
 pub mod rate_limits;
+pub mod retention;
+pub mod roles;
 pub mod shared;
 pub use components::section_introduction::SectionIntroduction;
 pub mod skills;
@@ -33,7 +40,9 @@ pub mod snackbar;
 pub mod system_prompt;
 pub mod team;
 pub mod teams;
+pub mod translations;
 pub mod web_search;
+pub mod webhooks;
 
 pub fn render(page: Element) -> String {
     let html = dioxus_ssr::render_element(page);
@@ -57,3 +66,95 @@ pub fn string_to_visibility(visibility: &str) -> Visibility {
         _ => Visibility::Private,
     }
 }
+
+pub const CHUNKING_STRATEGIES: [ChunkingStrategy; 5] = [
+    ChunkingStrategy::ByTitle,
+    ChunkingStrategy::RecursiveCharacter,
+    ChunkingStrategy::SentenceWindow,
+    ChunkingStrategy::MarkdownHeading,
+    ChunkingStrategy::TablePreserving,
+];
+
+pub fn chunking_strategy_to_string(chunking_strategy: ChunkingStrategy) -> String {
+    match chunking_strategy {
+        ChunkingStrategy::ByTitle => "By Title".to_string(),
+        ChunkingStrategy::RecursiveCharacter => "Recursive Character".to_string(),
+        ChunkingStrategy::SentenceWindow => "Sentence Window".to_string(),
+        ChunkingStrategy::MarkdownHeading => "Markdown Heading".to_string(),
+        ChunkingStrategy::TablePreserving => "Table Preserving".to_string(),
+    }
+}
+
+pub fn string_to_chunking_strategy(chunking_strategy: &str) -> ChunkingStrategy {
+    match chunking_strategy {
+        "Recursive Character" => ChunkingStrategy::RecursiveCharacter,
+        "Sentence Window" => ChunkingStrategy::SentenceWindow,
+        "Markdown Heading" => ChunkingStrategy::MarkdownHeading,
+        "Table Preserving" => ChunkingStrategy::TablePreserving,
+        _ => ChunkingStrategy::ByTitle,
+    }
+}
+
+pub fn provider_kind_to_string(provider_kind: ProviderKind) -> String {
+    match provider_kind {
+        ProviderKind::OpenAI => "OpenAI".to_string(),
+        ProviderKind::Anthropic => "Anthropic".to_string(),
+        ProviderKind::Gemini => "Gemini".to_string(),
+        ProviderKind::AzureOpenAI => "AzureOpenAI".to_string(),
+        ProviderKind::Ollama => "Ollama".to_string(),
+    }
+}
+
+pub fn string_to_provider_kind(provider_kind: &str) -> ProviderKind {
+    match provider_kind {
+        "Anthropic" => ProviderKind::Anthropic,
+        "Gemini" => ProviderKind::Gemini,
+        "AzureOpenAI" => ProviderKind::AzureOpenAI,
+        "Ollama" => ProviderKind::Ollama,
+        _ => ProviderKind::OpenAI,
+    }
+}
+
+pub fn guard_format_to_string(guard_format: GuardFormat) -> String {
+    match guard_format {
+        GuardFormat::LlamaGuard => "LlamaGuard".to_string(),
+        GuardFormat::Json => "Json".to_string(),
+    }
+}
+
+pub fn string_to_guard_format(guard_format: &str) -> GuardFormat {
+    match guard_format {
+        "Json" => GuardFormat::Json,
+        _ => GuardFormat::LlamaGuard,
+    }
+}
+
+pub fn output_moderation_to_string(output_moderation: OutputModeration) -> String {
+    match output_moderation {
+        OutputModeration::Off => "Off".to_string(),
+        OutputModeration::OnCompletion => "OnCompletion".to_string(),
+        OutputModeration::Streaming => "Streaming".to_string(),
+    }
+}
+
+pub fn string_to_output_moderation(output_moderation: &str) -> OutputModeration {
+    match output_moderation {
+        "OnCompletion" => OutputModeration::OnCompletion,
+        "Streaming" => OutputModeration::Streaming,
+        _ => OutputModeration::Off,
+    }
+}
+
+pub fn moderation_action_to_string(action: ModerationAction) -> String {
+    match action {
+        ModerationAction::Block => "Block".to_string(),
+        ModerationAction::Flag => "Flag".to_string(),
+    }
+}
+
+pub fn string_to_moderation_action(action: &str) -> ModerationAction {
+    match action {
+        "Flag" => ModerationAction::Flag,
+        _ => ModerationAction::Block,
+    }
+}
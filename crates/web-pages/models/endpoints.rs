@@ -0,0 +1,193 @@
+#![allow(non_snake_case)]
+use daisy_rsx::{select::SelectOption, *};
+use db::{EndpointHealth, ModelEndpoint};
+use dioxus::prelude::*;
+
+/// Summarises endpoint health for the models list.
+#[component]
+pub fn HealthBadge(health: EndpointHealth) -> Element {
+    if health.open_circuits > 0 {
+        rsx!(Badge {
+            badge_color: BadgeColor::Error,
+            badge_size: BadgeSize::Sm,
+            "{health.open_circuits} of {health.endpoints} endpoints down"
+        })
+    } else if health.failing > 0 {
+        rsx!(Badge {
+            badge_color: BadgeColor::Warning,
+            badge_size: BadgeSize::Sm,
+            "Degraded"
+        })
+    } else if health.endpoints > 1 {
+        rsx!(Badge {
+            badge_color: BadgeColor::Success,
+            badge_style: BadgeStyle::Outline,
+            badge_size: BadgeSize::Sm,
+            "{health.endpoints} endpoints"
+        })
+    } else {
+        rsx!()
+    }
+}
+
+#[component]
+pub fn ModelEndpoints(team_id: String, model_id: i32, endpoints: Vec<ModelEndpoint>) -> Element {
+    rsx!(
+        Card {
+            class: "has-data-table mt-6",
+            CardHeader {
+                title: "Endpoints"
+            }
+            CardBody {
+                p {
+                    class: "text-sm opacity-80 mb-4",
+                    "Chats go to the lowest priority endpoints first, spread by weight. Connection errors, rate limits and server errors fail over to the next endpoint."
+                }
+                table {
+                    class: "table table-sm",
+                    thead {
+                        th { "Base URL" }
+                        th { "Priority" }
+                        th { "Weight" }
+                        th { "Health" }
+                        th {
+                            class: "text-right",
+                            "Action"
+                        }
+                    }
+                    tbody {
+                        for endpoint in endpoints {
+                            tr {
+                                td {
+                                    div { "{endpoint.base_url}" }
+                                    if endpoint.is_primary {
+                                        div { class: "text-xs opacity-70", "Primary (edit above)" }
+                                    }
+                                }
+                                td { "{endpoint.priority}" }
+                                td { "{endpoint.weight}" }
+                                td {
+                                    EndpointStatus { endpoint: endpoint.clone() }
+                                }
+                                td {
+                                    class: "text-right",
+                                    if !endpoint.is_primary {
+                                        form {
+                                            method: "post",
+                                            action: crate::routes::models::DeleteEndpoint {
+                                                team_id: team_id.clone(),
+                                                model_id,
+                                                id: endpoint.id
+                                            }.to_string(),
+                                            Button {
+                                                button_type: ButtonType::Submit,
+                                                button_scheme: ButtonScheme::Error,
+                                                button_size: ButtonSize::Small,
+                                                "Remove"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                form {
+                    method: "post",
+                    action: crate::routes::models::NewEndpoint { team_id: team_id.clone(), model_id }.to_string(),
+                    class: "mt-6 flex flex-col gap-4",
+                    div {
+                        class: "grid grid-cols-1 md:grid-cols-2 gap-4",
+                        Fieldset {
+                            legend: "Base URL",
+                            help_text: "Another deployment of the same model.",
+                            Input {
+                                input_type: InputType::Text,
+                                name: "base_url",
+                                required: true
+                            }
+                        }
+                        Fieldset {
+                            legend: "API Key",
+                            help_text: "Leave blank if the endpoint doesn't need one.",
+                            Input {
+                                input_type: InputType::Password,
+                                name: "api_key"
+                            }
+                        }
+                        Fieldset {
+                            legend: "API Format",
+                            Select {
+                                name: "provider_kind",
+                                value: "OpenAI",
+                                SelectOption { value: "OpenAI", selected_value: "OpenAI", "OpenAI Compatible" }
+                                SelectOption { value: "Anthropic", selected_value: "OpenAI", "Anthropic" }
+                                SelectOption { value: "Gemini", selected_value: "OpenAI", "Google Gemini" }
+                                SelectOption { value: "AzureOpenAI", selected_value: "OpenAI", "Azure OpenAI" }
+                                SelectOption { value: "Ollama", selected_value: "OpenAI", "Ollama" }
+                            }
+                        }
+                        div {
+                            class: "grid grid-cols-2 gap-4",
+                            Fieldset {
+                                legend: "Priority",
+                                help_text: "0 shares load with the primary.",
+                                Input {
+                                    input_type: InputType::Number,
+                                    name: "priority",
+                                    value: "1",
+                                    required: true
+                                }
+                            }
+                            Fieldset {
+                                legend: "Weight",
+                                Input {
+                                    input_type: InputType::Number,
+                                    name: "weight",
+                                    value: "1",
+                                    required: true
+                                }
+                            }
+                        }
+                    }
+                    div {
+                        class: "flex justify-end",
+                        Button {
+                            button_type: ButtonType::Submit,
+                            button_scheme: ButtonScheme::Primary,
+                            "Add Endpoint"
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
+
+#[component]
+fn EndpointStatus(endpoint: ModelEndpoint) -> Element {
+    let last_error = endpoint.last_error.clone().unwrap_or_default();
+    if endpoint.circuit_open {
+        rsx!(Badge {
+            badge_color: BadgeColor::Error,
+            badge_size: BadgeSize::Sm,
+            "Circuit open"
+        }
+        div { class: "text-xs opacity-70 truncate max-w-xs", title: "{last_error}", "{last_error}" })
+    } else if endpoint.consecutive_failures > 0 {
+        rsx!(Badge {
+            badge_color: BadgeColor::Warning,
+            badge_size: BadgeSize::Sm,
+            "{endpoint.consecutive_failures} recent failures"
+        }
+        div { class: "text-xs opacity-70 truncate max-w-xs", title: "{last_error}", "{last_error}" })
+    } else {
+        rsx!(Badge {
+            badge_color: BadgeColor::Success,
+            badge_style: BadgeStyle::Outline,
+            badge_size: BadgeSize::Sm,
+            "Healthy"
+        })
+    }
+}
@@ -1,3 +1,4 @@
+pub mod endpoints;
 pub mod model_card;
 pub mod model_type;
 pub mod page;
@@ -2,6 +2,7 @@
 use crate::components::card_item::{CardItem, CountLabel};
 use daisy_rsx::*;
 use db::queries::models::ModelWithPrompt;
+use db::EndpointHealth;
 use dioxus::prelude::*;
 
 #[component]
@@ -12,6 +13,7 @@ pub fn ModelCard(
     has_vision: bool,
     has_tool_use: bool,
     has_guard: bool,
+    health: Option<EndpointHealth>,
 ) -> Element {
     let display_name = if model.display_name.is_empty() {
         model.name.clone()
@@ -28,6 +30,9 @@ pub fn ModelCard(
             if has_vision { Badge { badge_style: BadgeStyle::Outline, badge_size: BadgeSize::Sm, "Vision" } }
             if has_tool_use { Badge { badge_style: BadgeStyle::Outline, badge_size: BadgeSize::Sm, "Tools" } }
             if has_guard { Badge { badge_style: BadgeStyle::Outline, badge_size: BadgeSize::Sm, "Guarded" } }
+            if let Some(health) = health {
+                super::endpoints::HealthBadge { health }
+            }
         })),
         footer: None,
         image_src: None,
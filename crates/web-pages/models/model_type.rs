@@ -33,6 +33,15 @@ pub fn Model(model_type: ModelType) -> Element {
                 "Text To Speech"
             }
         ),
+        ModelType::SpeechToText => rsx!(
+            Badge {
+                class: "truncate",
+                badge_color: BadgeColor::Warning,
+                badge_style: BadgeStyle::Outline,
+                badge_size: BadgeSize::Sm,
+                "Speech To Text"
+            }
+        ),
         ModelType::Image => rsx!(
             Badge {
                 class: "truncate",
@@ -7,6 +7,7 @@ use assets::files::button_plus_svg;
 use daisy_rsx::*;
 use db::authz::Rbac;
 use db::queries::models::ModelWithPrompt;
+use db::EndpointHealth;
 use dioxus::prelude::*;
 
 pub fn page(
@@ -14,6 +15,7 @@ pub fn page(
     rbac: Rbac,
     setup_required: bool,
     models_with_capabilities: Vec<(ModelWithPrompt, bool, bool, bool, bool)>,
+    endpoint_health: Vec<EndpointHealth>,
 ) -> String {
     let page = rsx! {
         AdminLayout {
@@ -65,7 +67,8 @@ pub fn page(
                                 has_function_calling: *fc,
                                 has_vision: *vis,
                                 has_tool_use: *tool,
-                                has_guard: *guard
+                                has_guard: *guard,
+                                health: endpoint_health.iter().find(|health| health.model_id == model.id).cloned()
                             }
                         }
                     }
@@ -2,7 +2,7 @@
 use crate::app_layout::{AdminLayout, SideBar};
 use daisy_rsx::{select::SelectOption, *};
 use db::authz::Rbac;
-use db::Visibility;
+use db::{ModelEndpoint, Visibility};
 use dioxus::prelude::*;
 use serde::Deserialize;
 use validator::Validate;
@@ -14,11 +14,15 @@ pub struct ModelForm {
     pub name: String,
     pub display_name: String,
     pub model_type: String,
+    pub provider_kind: String,
+    pub guard_format: String,
     pub base_url: String,
     pub api_key: String,
     pub tpm_limit: i32,
     pub rpm_limit: i32,
     pub context_size_bytes: i32,
+    pub input_price: String,
+    pub output_price: String,
     pub visibility: String,
     pub disclaimer: String,
     pub description: String,
@@ -34,7 +38,13 @@ pub struct ModelForm {
     pub error: Option<String>,
 }
 
-pub fn page(team_id: String, rbac: Rbac, setup_required: bool, form: ModelForm) -> String {
+pub fn page(
+    team_id: String,
+    rbac: Rbac,
+    setup_required: bool,
+    form: ModelForm,
+    endpoints: Vec<ModelEndpoint>,
+) -> String {
     let page = rsx! {
         AdminLayout {
             section_class: "p-4",
@@ -153,16 +163,34 @@ pub fn page(team_id: String, rbac: Rbac, setup_required: bool, form: ModelForm)
                                         SelectOption { value: "Embeddings", selected_value: form.model_type.clone(), "Embeddings Model" }
                                         SelectOption { value: "Image", selected_value: form.model_type.clone(), "Image Generation" }
                                         SelectOption { value: "TextToSpeech", selected_value: form.model_type.clone(), "Text To Speech" }
+                                        SelectOption { value: "SpeechToText", selected_value: form.model_type.clone(), "Speech To Text" }
                                         SelectOption { value: "Guard", selected_value: form.model_type.clone(), "Guard" }
                                     }
                                 }
                             }
+                            div {
+                                class: "flex flex-col",
+                                Fieldset {
+                                    legend: "API Format",
+                                    legend_class: "mt-4",
+                                    help_text: "Chat models are called through the native API for this format.",
+                                    Select {
+                                        name: "provider_kind",
+                                        value: form.provider_kind.clone(),
+                                        SelectOption { value: "OpenAI", selected_value: form.provider_kind.clone(), "OpenAI Compatible" }
+                                        SelectOption { value: "Anthropic", selected_value: form.provider_kind.clone(), "Anthropic" }
+                                        SelectOption { value: "Gemini", selected_value: form.provider_kind.clone(), "Google Gemini" }
+                                        SelectOption { value: "AzureOpenAI", selected_value: form.provider_kind.clone(), "Azure OpenAI" }
+                                        SelectOption { value: "Ollama", selected_value: form.provider_kind.clone(), "Ollama" }
+                                    }
+                                }
+                            }
                             div {
                                 class: "flex flex-col",
                                 Fieldset {
                                     legend: "The Base URL of the model",
                                     legend_class: "mt-4",
-                                    help_text: "The URL location of the OpenAI compatible API",
+                                    help_text: "The URL location of the model API",
                                     Input {
                                         input_type: InputType::Text,
                                         name: "base_url",
@@ -244,6 +272,28 @@ pub fn page(team_id: String, rbac: Rbac, setup_required: bool, form: ModelForm)
                                     required: true
                                 }
                             }
+                            Fieldset {
+                                legend: "Input price per million tokens",
+                                legend_class: "mt-4",
+                                help_text: "What your provider charges for prompt tokens. Used for costs and budgets",
+                                Input {
+                                    input_type: InputType::Text,
+                                    name: "input_price",
+                                    placeholder: "0.00",
+                                    value: "{form.input_price}"
+                                }
+                            }
+                            Fieldset {
+                                legend: "Output price per million tokens",
+                                legend_class: "mt-4",
+                                help_text: "What your provider charges for completion tokens",
+                                Input {
+                                    input_type: InputType::Text,
+                                    name: "output_price",
+                                    placeholder: "0.00",
+                                    value: "{form.output_price}"
+                                }
+                            }
                             Fieldset {
                                 legend: "Context Size",
                                 legend_class: "mt-4",
@@ -255,6 +305,17 @@ pub fn page(team_id: String, rbac: Rbac, setup_required: bool, form: ModelForm)
                                     required: true
                                 }
                             }
+                            Fieldset {
+                                legend: "Guard Response Format",
+                                legend_class: "mt-4",
+                                help_text: "Only used by Guard models. How the guard reports unsafe content.",
+                                Select {
+                                    name: "guard_format",
+                                    value: form.guard_format.clone(),
+                                    SelectOption { value: "LlamaGuard", selected_value: form.guard_format.clone(), "Llama Guard (safe / unsafe S1..S14)" }
+                                    SelectOption { value: "Json", selected_value: form.guard_format.clone(), "JSON ({{\"safe\": false, \"categories\": [\"S1\"]}})" }
+                                }
+                            }
                         }
                     }
 
@@ -386,6 +447,13 @@ pub fn page(team_id: String, rbac: Rbac, setup_required: bool, form: ModelForm)
                         }
                     }
                 }
+                if let Some(model_id) = form.id {
+                    super::endpoints::ModelEndpoints {
+                        team_id: team_id.clone(),
+                        model_id,
+                        endpoints
+                    }
+                }
             }
         }
     };
@@ -27,6 +27,7 @@ pub struct PromptForm {
     pub max_completion_tokens: Option<i32>,
     pub trim_ratio: i32,
     pub temperature: f32,
+    pub persistent_workspace: bool,
     #[serde(skip)]
     pub error: Option<String>,
     #[serde(skip)]
@@ -443,6 +444,24 @@ pub fn page(
                                         }
                                     }
                                 }
+
+                                div {
+                                    class: "form-control",
+                                    label {
+                                        class: "label cursor-pointer justify-start gap-3",
+                                        input {
+                                            "type": "checkbox",
+                                            name: "persistent_workspace",
+                                            class: "checkbox",
+                                            checked: prompt.persistent_workspace
+                                        }
+                                        span { class: "label-text", "Persistent workspace" }
+                                    }
+                                    p {
+                                        class: "text-xs opacity-70",
+                                        "Keep /home/user/work, environment variables and the working directory between bash tool calls in a conversation."
+                                    }
+                                }
                             }
                         }
                     }
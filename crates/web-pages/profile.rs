@@ -14,7 +14,18 @@ fn Page(
     last_name: String,
     users_name_or_email: String,
     form_action: String,
+    locale: String,
+    locales: Vec<String>,
 ) -> Element {
+    let locale_action = crate::routes::profile::SetLocale {
+        team_id: team_id.clone(),
+    }
+    .to_string();
+    let export_action = crate::routes::profile::Export {
+        team_id: team_id.clone(),
+    }
+    .to_string();
+
     rsx! {
         Layout {
             section_class: "p-4",
@@ -72,11 +83,65 @@ fn Page(
                     }
                 }
             }
+
+            Card {
+                class: "mt-4",
+                CardHeader {
+                    title: "Language"
+                }
+                CardBody {
+                    form {
+                        method: "post",
+                        action: "{locale_action}",
+                        div {
+                            class: "flex flex-col",
+                            Select {
+                                name: "locale",
+                                label: "Language",
+                                help_text: "Used instead of your browser's language",
+                                value: locale.clone(),
+                                SelectOption { value: "", selected_value: locale.clone(), "Browser default" }
+                                for option in locales {
+                                    SelectOption { value: option.clone(), selected_value: locale.clone(), "{option}" }
+                                }
+                            }
+                            Button {
+                                class: "mt-3",
+                                button_type: ButtonType::Submit,
+                                button_scheme: ButtonScheme::Primary,
+                                "Update Language"
+                            }
+                        }
+                    }
+                }
+            }
+
+            Card {
+                class: "mt-4",
+                CardHeader {
+                    title: "Your Data"
+                }
+                CardBody {
+                    p {
+                        class: "mb-3",
+                        "Download a zip of your conversations, the files you attached and the files generated for you, from every team you belong to."
+                    }
+                    form {
+                        method: "post",
+                        action: "{export_action}",
+                        Button {
+                            button_type: ButtonType::Submit,
+                            button_scheme: ButtonScheme::Primary,
+                            "Download My Data"
+                        }
+                    }
+                }
+            }
         }
     }
 }
 
-pub fn profile(user: User, team_id: String, rbac: Rbac) -> String {
+pub fn profile(user: User, team_id: String, rbac: Rbac, locales: Vec<String>) -> String {
     let (mut first_name, mut last_name) = ("".to_string(), "".to_string());
     if let (Some(first), Some(last)) = (user.first_name, user.last_name) {
         first_name = first;
@@ -89,6 +154,8 @@ pub fn profile(user: User, team_id: String, rbac: Rbac) -> String {
         user.email
     };
 
+    let locale = user.locale.unwrap_or_default();
+
     let form_action = crate::routes::profile::SetDetails {
         team_id: team_id.clone(),
     }
@@ -101,7 +168,9 @@ pub fn profile(user: User, team_id: String, rbac: Rbac) -> String {
             first_name,
             last_name,
             users_name_or_email,
-            form_action
+            form_action,
+            locale,
+            locales
         }
     };
     crate::render(page)
@@ -1,6 +1,6 @@
 #![allow(non_snake_case)]
 use crate::app_layout::{AdminLayout, SideBar};
-use daisy_rsx::*;
+use daisy_rsx::{select::SelectOption, *};
 use db::authz::Rbac;
 use dioxus::prelude::*;
 use serde::Deserialize;
@@ -16,6 +16,7 @@ pub struct ProviderForm {
     pub default_model_context_size: i32,
     pub default_model_description: String,
     pub base_url: String,
+    pub provider_kind: String,
     pub api_key_optional: bool,
     pub default_embeddings_model_name: String,
     pub default_embeddings_model_display_name: String,
@@ -100,6 +101,20 @@ pub fn page(team_id: String, rbac: Rbac, form: ProviderForm) -> String {
                                     }
                                 }
                             }
+                            Fieldset {
+                                legend: "API Format",
+                                legend_class: "mt-4",
+                                help_text: "The API this provider speaks. Models created from it inherit this setting.",
+                                Select {
+                                    name: "provider_kind",
+                                    value: form.provider_kind.clone(),
+                                    SelectOption { value: "OpenAI", selected_value: form.provider_kind.clone(), "OpenAI Compatible" }
+                                    SelectOption { value: "Anthropic", selected_value: form.provider_kind.clone(), "Anthropic" }
+                                    SelectOption { value: "Gemini", selected_value: form.provider_kind.clone(), "Google Gemini" }
+                                    SelectOption { value: "AzureOpenAI", selected_value: form.provider_kind.clone(), "Azure OpenAI" }
+                                    SelectOption { value: "Ollama", selected_value: form.provider_kind.clone(), "Ollama" }
+                                }
+                            }
                             div {
                                 class: "flex flex-col",
                                 Fieldset {
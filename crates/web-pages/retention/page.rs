@@ -0,0 +1,114 @@
+#![allow(non_snake_case)]
+use crate::app_layout::{AdminLayout, SideBar};
+use crate::SectionIntroduction;
+use daisy_rsx::*;
+use db::authz::Rbac;
+use db::RetentionPolicy;
+use dioxus::prelude::*;
+
+pub fn page(team_id: String, rbac: Rbac, policy: Option<RetentionPolicy>) -> String {
+    let days = |value: Option<i32>| value.map(|days| days.to_string()).unwrap_or_default();
+    let conversation_days = days(policy.and_then(|p| p.conversation_days));
+    let attachment_days = days(policy.and_then(|p| p.attachment_days));
+    let generated_output_days = days(policy.and_then(|p| p.generated_output_days));
+    let token_usage_days = days(policy.and_then(|p| p.token_usage_days));
+    let last_run_at = policy.and_then(|p| p.last_run_at);
+
+    let page = rsx! {
+        AdminLayout {
+            section_class: "p-4",
+            selected_item: SideBar::Retention,
+            team_id: team_id.clone(),
+            rbac: rbac,
+            title: "Data Retention",
+            header: rsx!(
+                Breadcrumb {
+                    items: vec![BreadcrumbItem { text: "Data Retention".into(), href: None }]
+                }
+            ),
+            div {
+                class: "p-4 max-w-4xl w-full mx-auto",
+                SectionIntroduction {
+                    header: "Data Retention".to_string(),
+                    subtitle: "Choose how long this team keeps conversations, files and usage records. Anything older is deleted automatically, and each purge is recorded in the audit trail.".to_string(),
+                    is_empty: false,
+                    empty_text: "".to_string(),
+                }
+
+                Card {
+                    class: "mt-5",
+                    CardHeader { title: "Retention Policy" }
+                    CardBody {
+                        form {
+                            method: "post",
+                            action: crate::routes::retention::Update { team_id: team_id.clone() }.to_string(),
+                            class: "flex flex-col gap-4",
+                            Fieldset {
+                                legend: "Conversations (days)",
+                                help_text: "Conversations nobody has added to for this long are deleted with their files. Leave blank to keep them forever",
+                                Input {
+                                    input_type: InputType::Number,
+                                    class: "w-full",
+                                    name: "conversation_days",
+                                    value: conversation_days,
+                                }
+                            }
+                            Fieldset {
+                                legend: "Attachments (days)",
+                                help_text: "Files attached to chats. Leave blank to keep them as long as their conversation",
+                                Input {
+                                    input_type: InputType::Number,
+                                    class: "w-full",
+                                    name: "attachment_days",
+                                    value: attachment_days,
+                                }
+                            }
+                            Fieldset {
+                                legend: "Generated files (days)",
+                                help_text: "Files created by tools such as the code sandbox. Leave blank to keep them as long as their conversation",
+                                Input {
+                                    input_type: InputType::Number,
+                                    class: "w-full",
+                                    name: "generated_output_days",
+                                    value: generated_output_days,
+                                }
+                            }
+                            Fieldset {
+                                legend: "Token usage (days)",
+                                help_text: "Usage records behind the token usage charts, rate limits, costs and budgets. Leave blank to keep them forever",
+                                Input {
+                                    input_type: InputType::Number,
+                                    class: "w-full",
+                                    name: "token_usage_days",
+                                    value: token_usage_days,
+                                }
+                            }
+                            div {
+                                class: "flex items-center justify-between",
+                                p {
+                                    class: "text-sm opacity-70",
+                                    if let Some(last_run_at) = last_run_at {
+                                        "Last enforced "
+                                        RelativeTime {
+                                            format: RelativeTimeFormat::Relative,
+                                            datetime: last_run_at.to_rfc3339()
+                                        }
+                                    } else {
+                                        "Not enforced yet"
+                                    }
+                                }
+                                Button {
+                                    button_type: ButtonType::Submit,
+                                    button_scheme: ButtonScheme::Primary,
+                                    "Save Policy"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    crate::render(page)
+}
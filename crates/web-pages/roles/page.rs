@@ -0,0 +1,146 @@
+#![allow(non_snake_case)]
+use super::upsert::{RoleForm, Upsert};
+use crate::app_layout::{AdminLayout, SideBar};
+use crate::components::confirm_modal::ConfirmModal;
+use crate::SectionIntroduction;
+use assets::files::*;
+use daisy_rsx::*;
+use db::authz::Rbac;
+use db::custom_roles::GRANTABLE;
+use db::{CustomRole, Permission};
+use dioxus::prelude::*;
+
+/// The roles as a matrix of permissions, with the built in roles first for comparison.
+pub fn page(
+    team_id: String,
+    rbac: Rbac,
+    roles: Vec<CustomRole>,
+    team_manager: Vec<Permission>,
+    collaborator: Vec<Permission>,
+) -> String {
+    let page = rsx! {
+        AdminLayout {
+            section_class: "p-4",
+            selected_item: SideBar::Roles,
+            team_id: team_id.clone(),
+            rbac: rbac,
+            title: "Roles",
+            header: rsx!(
+                Breadcrumb {
+                    items: vec![BreadcrumbItem { text: "Roles".into(), href: None }]
+                }
+                Button {
+                    prefix_image_src: "{button_plus_svg.name}",
+                    popover_target: "new-role",
+                    button_scheme: ButtonScheme::Primary,
+                    "New Role"
+                }
+            ),
+            div {
+                class: "p-4 max-w-5xl w-full mx-auto",
+                SectionIntroduction {
+                    header: "Roles".to_string(),
+                    subtitle: "Combine permissions into roles of your own, such as a dataset curator or a read only reviewer, and give them to people when you invite them or from the team's members.".to_string(),
+                    is_empty: roles.is_empty(),
+                    empty_text: "No custom roles yet. Team members have the built in roles below.".to_string(),
+                }
+
+                Card {
+                    class: "mt-5 has-data-table",
+                    CardHeader { title: "Permissions" }
+                    CardBody {
+                        table {
+                            class: "table table-sm",
+                            thead {
+                                th { "Permission" }
+                                th { class: "text-center", "Team Manager" }
+                                th { class: "text-center", "Collaborator" }
+                                for role in &roles {
+                                    th {
+                                        class: "text-center",
+                                        div {
+                                            class: "flex items-center justify-center gap-1",
+                                            span { title: "{role.description}", "{role.name}" }
+                                            DropDown {
+                                                direction: Direction::Left,
+                                                button_text: "...",
+                                                DropDownLink {
+                                                    popover_target: format!("edit-role-{}", role.id),
+                                                    href: "#",
+                                                    target: "_top",
+                                                    "Edit"
+                                                }
+                                                DropDownLink {
+                                                    popover_target: format!("delete-role-{}", role.id),
+                                                    href: "#",
+                                                    target: "_top",
+                                                    "Delete"
+                                                }
+                                            }
+                                        }
+                                        p { class: "text-xs font-normal opacity-70", "{role.members} members" }
+                                    }
+                                }
+                            }
+                            tbody {
+                                for grantable in GRANTABLE {
+                                    tr {
+                                        td {
+                                            strong { "{grantable.label}" }
+                                            p { class: "text-xs opacity-70", "{grantable.description}" }
+                                        }
+                                        Granted { granted: team_manager.contains(&grantable.permission) }
+                                        Granted { granted: collaborator.contains(&grantable.permission) }
+                                        for role in &roles {
+                                            Granted { granted: role.permissions.contains(&grantable.permission) }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                for role in roles {
+                    Upsert {
+                        trigger_id: format!("edit-role-{}", role.id),
+                        action: crate::routes::roles::Edit { team_id: team_id.clone(), id: role.id }.to_string(),
+                        form: RoleForm {
+                            name: role.name.clone(),
+                            description: role.description.clone(),
+                            permissions: role.permissions.clone(),
+                        },
+                        is_new: false
+                    }
+                    ConfirmModal {
+                        action: crate::routes::roles::Delete { team_id: team_id.clone(), id: role.id }.to_string(),
+                        trigger_id: format!("delete-role-{}", role.id),
+                        submit_label: "Delete".to_string(),
+                        heading: "Delete this Role?".to_string(),
+                        warning: format!("The {} members with this role will lose its permissions, and invitations for it will be for no role.", role.members),
+                        hidden_fields: vec![],
+                    }
+                }
+
+                Upsert {
+                    trigger_id: "new-role",
+                    action: crate::routes::roles::New { team_id: team_id.clone() }.to_string(),
+                    form: RoleForm::default(),
+                    is_new: true
+                }
+            }
+        }
+    };
+
+    crate::render(page)
+}
+
+#[component]
+fn Granted(granted: bool) -> Element {
+    rsx!(
+        td {
+            class: "text-center",
+            if granted { "✓" }
+        }
+    )
+}
@@ -0,0 +1,78 @@
+#![allow(non_snake_case)]
+use daisy_rsx::*;
+use db::custom_roles::GRANTABLE;
+use db::Permission;
+use dioxus::prelude::*;
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct RoleForm {
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<Permission>,
+}
+
+/// Creates a role, or edits one when `action` is its edit route.
+#[component]
+pub fn Upsert(trigger_id: String, action: String, form: RoleForm, is_new: bool) -> Element {
+    rsx!(
+        Modal {
+            submit_action: action,
+            trigger_id,
+            ModalBody {
+                class: "flex flex-col gap-4",
+                h3 {
+                    class: "font-bold text-lg mb-4",
+                    if is_new { "New Role" } else { "Edit Role" }
+                }
+                Fieldset {
+                    legend: "Name",
+                    Input {
+                        input_type: InputType::Text,
+                        class: "w-full",
+                        placeholder: "Dataset Curator",
+                        name: "name",
+                        value: form.name,
+                        required: true,
+                    }
+                }
+                Fieldset {
+                    legend: "Description",
+                    Input {
+                        input_type: InputType::Text,
+                        class: "w-full",
+                        placeholder: "Who this role is for",
+                        name: "description",
+                        value: form.description,
+                    }
+                }
+                Fieldset {
+                    legend: "Permissions",
+                    for grantable in GRANTABLE {
+                        label {
+                            class: "flex items-start gap-2 mt-2",
+                            input {
+                                "type": "checkbox",
+                                class: "checkbox checkbox-sm",
+                                name: "permissions",
+                                value: grantable.name,
+                                checked: form.permissions.contains(&grantable.permission),
+                            }
+                            div {
+                                strong { "{grantable.label}" }
+                                p { class: "text-xs opacity-70", "{grantable.description}" }
+                            }
+                        }
+                    }
+                }
+                ModalAction {
+                    Button { class: "cancel-modal", button_scheme: ButtonScheme::Warning, "Cancel" }
+                    Button {
+                        button_type: ButtonType::Submit,
+                        button_scheme: ButtonScheme::Primary,
+                        if is_new { "Create Role" } else { "Save" }
+                    }
+                }
+            }
+        }
+    )
+}
@@ -42,6 +42,23 @@ pub mod rate_limits {
     }
 }
 
+pub mod guardrails {
+    use axum_extra::routing::TypedPath;
+    use serde::Deserialize;
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/guardrails")]
+    pub struct Index {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/guardrails/update")]
+    pub struct Update {
+        pub team_id: String,
+    }
+}
+
 pub mod api_keys {
     use axum_extra::routing::TypedPath;
     use serde::Deserialize;
@@ -75,6 +92,13 @@ pub mod audit_trail {
     pub struct Index {
         pub team_id: String,
     }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/audit_trail/export/{format}")]
+    pub struct Export {
+        pub team_id: String,
+        pub format: String,
+    }
 }
 
 pub mod system_prompt {
@@ -158,12 +182,69 @@ pub mod console {
         pub id: i64,
     }
 
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/tool_call_approvals/{id}")]
+    pub struct DecideToolCall {
+        pub team_id: String,
+        pub id: i32,
+    }
+
     #[derive(TypedPath, Deserialize)]
     #[typed_path("/o/{team_id}/generated_outputs/{id}/canvas")]
     pub struct GeneratedOutputCanvas {
         pub team_id: String,
         pub id: i32,
     }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/generated_outputs/{id}/file")]
+    pub struct GeneratedOutputFile {
+        pub team_id: String,
+        pub id: i32,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/console/{conversation_id}/export/{format}")]
+    pub struct Export {
+        pub team_id: String,
+        pub conversation_id: i64,
+        pub format: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/console/import")]
+    pub struct Import {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/console/{conversation_id}/shares")]
+    pub struct CreateShare {
+        pub team_id: String,
+        pub conversation_id: i64,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/console/{conversation_id}/shares/{id}/delete")]
+    pub struct DeleteShare {
+        pub team_id: String,
+        pub conversation_id: i64,
+        pub id: i32,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/shared/{token}")]
+    pub struct TeamShare {
+        pub team_id: String,
+        pub token: String,
+    }
+
+    /// Public links are the only console page that doesn't need a sign in.
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/shared/{token}")]
+    pub struct PublicShare {
+        pub token: String,
+    }
 }
 
 pub mod prompts {
@@ -294,6 +375,20 @@ pub mod models {
         pub team_id: String,
         pub id: i32,
     }
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/models/{model_id}/endpoints/new")]
+    pub struct NewEndpoint {
+        pub team_id: String,
+        pub model_id: i32,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/models/{model_id}/endpoints/delete/{id}")]
+    pub struct DeleteEndpoint {
+        pub team_id: String,
+        pub model_id: i32,
+        pub id: i32,
+    }
 }
 
 pub mod providers {
@@ -394,6 +489,13 @@ pub mod integrations {
         pub integration_id: i32,
     }
 
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/integrations/{integration_id}/tool_call_policy")]
+    pub struct SetToolCallPolicy {
+        pub team_id: String,
+        pub integration_id: i32,
+    }
+
     #[derive(TypedPath, Deserialize)]
     #[typed_path(
         "/o/{team_id}/integrations/{integration_id}/connections/api-key/{connection_id}/delete"
@@ -700,6 +802,12 @@ pub mod team {
     pub struct SetName {
         pub team_id: String,
     }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/set_roles")]
+    pub struct SetRoles {
+        pub team_id: String,
+    }
 }
 
 pub mod profile {
@@ -712,6 +820,12 @@ pub mod profile {
         pub team_id: String,
     }
 
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/set_locale")]
+    pub struct SetLocale {
+        pub team_id: String,
+    }
+
     #[derive(TypedPath, Deserialize)]
     #[typed_path("/o/{team_id}/profile")]
     pub struct Profile {
@@ -723,6 +837,12 @@ pub mod profile {
     pub struct ProfilePopup {
         pub team_id: String,
     }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/profile/export")]
+    pub struct Export {
+        pub team_id: String,
+    }
 }
 
 pub mod oauth_clients {
@@ -817,3 +937,248 @@ pub mod web_search {
         pub id: i32,
     }
 }
+
+pub mod translations {
+    use axum_extra::routing::TypedPath;
+    use serde::Deserialize;
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/translations")]
+    pub struct Index {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/translations/locale/{locale}")]
+    pub struct Locale {
+        pub team_id: String,
+        pub locale: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/translations/upsert")]
+    pub struct Upsert {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/translations/delete")]
+    pub struct Delete {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/translations/import")]
+    pub struct Import {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/translations/locale/{locale}/export/{format}")]
+    pub struct Export {
+        pub team_id: String,
+        pub locale: String,
+        pub format: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/translations/reload")]
+    pub struct Reload {
+        pub team_id: String,
+    }
+}
+
+pub mod webhooks {
+    use axum_extra::routing::TypedPath;
+    use serde::Deserialize;
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/webhooks")]
+    pub struct Index {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/webhooks/new")]
+    pub struct New {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/webhooks/{id}")]
+    pub struct View {
+        pub team_id: String,
+        pub id: i32,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/webhooks/{id}/edit")]
+    pub struct Edit {
+        pub team_id: String,
+        pub id: i32,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/webhooks/{id}/delete")]
+    pub struct Delete {
+        pub team_id: String,
+        pub id: i32,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/webhooks/{id}/test")]
+    pub struct Test {
+        pub team_id: String,
+        pub id: i32,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/webhooks/{id}/deliveries/{delivery_id}/replay")]
+    pub struct Replay {
+        pub team_id: String,
+        pub id: i32,
+        pub delivery_id: i32,
+    }
+}
+
+pub mod automations {
+    use axum_extra::routing::TypedPath;
+    use serde::Deserialize;
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/automations")]
+    pub struct Index {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/automations/new")]
+    pub struct New {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/automations/{id}")]
+    pub struct View {
+        pub team_id: String,
+        pub id: i32,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/automations/{id}/edit")]
+    pub struct Edit {
+        pub team_id: String,
+        pub id: i32,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/automations/{id}/delete")]
+    pub struct Delete {
+        pub team_id: String,
+        pub id: i32,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/automations/{id}/run")]
+    pub struct RunNow {
+        pub team_id: String,
+        pub id: i32,
+    }
+}
+
+pub mod retention {
+    use axum_extra::routing::TypedPath;
+    use serde::Deserialize;
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/retention")]
+    pub struct Index {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/retention/update")]
+    pub struct Update {
+        pub team_id: String,
+    }
+}
+
+pub mod roles {
+    use axum_extra::routing::TypedPath;
+    use serde::Deserialize;
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/roles")]
+    pub struct Index {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/roles/new")]
+    pub struct New {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/roles/{id}/edit")]
+    pub struct Edit {
+        pub team_id: String,
+        pub id: i32,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/roles/{id}/delete")]
+    pub struct Delete {
+        pub team_id: String,
+        pub id: i32,
+    }
+}
+
+pub mod directory_sync {
+    use axum_extra::routing::TypedPath;
+    use serde::Deserialize;
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/directory_sync")]
+    pub struct Index {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/directory_sync/{id}/map")]
+    pub struct Map {
+        pub team_id: String,
+        pub id: i32,
+    }
+}
+
+pub mod costs {
+    use axum_extra::routing::TypedPath;
+    use serde::Deserialize;
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/costs")]
+    pub struct Index {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/costs/export/{month}")]
+    pub struct Export {
+        pub team_id: String,
+        pub month: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/costs/budgets")]
+    pub struct UpsertBudget {
+        pub team_id: String,
+    }
+
+    #[derive(TypedPath, Deserialize)]
+    #[typed_path("/o/{team_id}/costs/budgets/{id}/delete")]
+    pub struct DeleteBudget {
+        pub team_id: String,
+        pub id: i32,
+    }
+}
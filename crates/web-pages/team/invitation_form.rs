@@ -1,9 +1,14 @@
 #![allow(non_snake_case)]
 use daisy_rsx::*;
+use db::CustomRole;
 use dioxus::prelude::*;
 
 #[component]
-pub fn InvitationForm(submit_action: String) -> Element {
+pub fn InvitationForm(
+    submit_action: String,
+    can_invite_managers: bool,
+    custom_roles: Vec<CustomRole>,
+) -> Element {
     rsx! {
         // The form to create an invitation
         form {
@@ -51,22 +56,32 @@ pub fn InvitationForm(submit_action: String) -> Element {
                                 name: "last_name"
                             }
                         }
-                        Alert {
-                            alert_color: AlertColor::Success,
-                            class: "mt-4 flex flex-col items-start",
-                            label {
-                                input {
-                                    "type": "checkbox",
-                                    name: "admin"
+                        Fieldset {
+                            legend: "Role",
+                            legend_class: "mt-4",
+                            help_text: "Team Managers can invite new team members and manage the team's settings",
+                            Select {
+                                class: "w-full",
+                                name: "role",
+                                SelectOption {
+                                    value: "Collaborator",
+                                    selected_value: "Collaborator",
+                                    "Collaborator"
                                 }
-                                strong {
-                                    class: "ml-2",
-                                    "Invite as Team Manager"
+                                if can_invite_managers {
+                                    SelectOption {
+                                        value: "TeamManager",
+                                        selected_value: "Collaborator",
+                                        "Team Manager"
+                                    }
+                                }
+                                for role in custom_roles {
+                                    SelectOption {
+                                        value: "{role.id}",
+                                        selected_value: "Collaborator",
+                                        "{role.name}"
+                                    }
                                 }
-                            }
-                            p {
-                                class: "note",
-                                "Team Managers can invite new team members"
                             }
                         }
                     }
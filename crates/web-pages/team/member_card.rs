@@ -5,7 +5,7 @@ use db::{Invitation, Member};
 use dioxus::prelude::*;
 
 #[component]
-pub fn MemberCard(member: Member, rbac: Rbac) -> Element {
+pub fn MemberCard(member: Member, custom_roles: Vec<String>, rbac: Rbac) -> Element {
     let name = match (&member.first_name, &member.last_name) {
         (Some(f), Some(l)) => format!("{} {}", f, l),
         _ => member.email.clone(),
@@ -39,18 +39,31 @@ pub fn MemberCard(member: Member, rbac: Rbac) -> Element {
                 for role in member.roles.clone() {
                     crate::team::team_role::Role { role }
                 }
+                for name in custom_roles {
+                    crate::team::team_role::CustomRole { name }
+                }
             }
-            if rbac.can_make_invitations() && rbac.email != member.email {
+            if (rbac.can_make_invitations() || rbac.can_manage_roles()) && rbac.email != member.email {
                 div {
                     class: "flex flex-col justify-center ml-4",
                     DropDown {
                         direction: Direction::Left,
                         button_text: "...",
-                        DropDownLink {
-                            popover_target: format!("remove-member-trigger-{}-{}", member.id, member.team_id),
-                            href: "#",
-                            target: "_top",
-                            "Remove User From Team"
+                        if rbac.can_manage_roles() {
+                            DropDownLink {
+                                popover_target: format!("member-roles-trigger-{}-{}", member.id, member.team_id),
+                                href: "#",
+                                target: "_top",
+                                "Change Roles"
+                            }
+                        }
+                        if rbac.can_make_invitations() {
+                            DropDownLink {
+                                popover_target: format!("remove-member-trigger-{}-{}", member.id, member.team_id),
+                                href: "#",
+                                target: "_top",
+                                "Remove User From Team"
+                            }
                         }
                     }
                 }
@@ -60,7 +73,7 @@ pub fn MemberCard(member: Member, rbac: Rbac) -> Element {
 }
 
 #[component]
-pub fn InvitePendingCard(invite: Invitation, rbac: Rbac) -> Element {
+pub fn InvitePendingCard(invite: Invitation, custom_role: Option<String>, rbac: Rbac) -> Element {
     let name = format!("{} {}", invite.first_name, invite.last_name);
     rsx!(
         Card {
@@ -90,6 +103,9 @@ pub fn InvitePendingCard(invite: Invitation, rbac: Rbac) -> Element {
                 for role in invite.roles.clone() {
                     crate::team::team_role::Role { role }
                 }
+                if let Some(name) = custom_role {
+                    crate::team::team_role::CustomRole { name }
+                }
             }
             if rbac.can_make_invitations() {
                 div {
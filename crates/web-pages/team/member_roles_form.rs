@@ -0,0 +1,89 @@
+#![allow(non_snake_case)]
+use daisy_rsx::*;
+use db::{CustomRole, Member, Role};
+use dioxus::prelude::*;
+
+/// Chooses a member's built in and custom roles.
+#[component]
+pub fn MemberRolesForm(
+    submit_action: String,
+    member: Member,
+    custom_roles: Vec<CustomRole>,
+    assigned: Vec<i32>,
+) -> Element {
+    rsx! {
+        form {
+            method: "post",
+            action: "{submit_action}",
+            Modal {
+                trigger_id: format!("member-roles-trigger-{}-{}", member.id, member.team_id),
+                ModalBody {
+                    h3 {
+                        class: "font-bold text-lg mb-4",
+                        "Roles for {member.email}"
+                    }
+                    input { "type": "hidden", name: "user_id", value: "{member.id}" }
+                    Fieldset {
+                        legend: "Built in roles",
+                        RoleCheckbox {
+                            name: "roles",
+                            value: "TeamManager",
+                            label: "Team Manager",
+                            checked: member.roles.contains(&Role::TeamManager),
+                        }
+                        RoleCheckbox {
+                            name: "roles",
+                            value: "Collaborator",
+                            label: "Collaborator",
+                            checked: member.roles.contains(&Role::Collaborator),
+                        }
+                    }
+                    if !custom_roles.is_empty() {
+                        Fieldset {
+                            legend: "Custom roles",
+                            legend_class: "mt-4",
+                            for role in custom_roles {
+                                RoleCheckbox {
+                                    name: "custom_role_ids",
+                                    value: "{role.id}",
+                                    label: "{role.name}",
+                                    checked: assigned.contains(&role.id),
+                                }
+                            }
+                        }
+                    }
+                    ModalAction {
+                        Button {
+                            class: "cancel-modal",
+                            button_scheme: ButtonScheme::Warning,
+                            button_size: ButtonSize::Small,
+                            "Cancel"
+                        }
+                        Button {
+                            button_type: ButtonType::Submit,
+                            button_scheme: ButtonScheme::Primary,
+                            "Save Roles"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn RoleCheckbox(name: String, value: String, label: String, checked: bool) -> Element {
+    rsx! {
+        label {
+            class: "flex items-center gap-2 mt-2",
+            input {
+                "type": "checkbox",
+                class: "checkbox checkbox-sm",
+                name: "{name}",
+                value: "{value}",
+                checked,
+            }
+            "{label}"
+        }
+    }
+}
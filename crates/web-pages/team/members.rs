@@ -5,9 +5,10 @@ use crate::components::confirm_modal::ConfirmModal;
 use assets::files::*;
 use daisy_rsx::*;
 use db::authz::Rbac;
-use db::{Invitation, Member, Team, User};
+use db::{CustomRole, Invitation, Member, MemberCustomRoles, Team, User};
 use dioxus::prelude::*;
 
+#[allow(clippy::too_many_arguments)]
 pub fn page(
     rbac: Rbac,
     members: Vec<Member>,
@@ -15,7 +16,22 @@ pub fn page(
     team: Team,
     user: User,
     team_name: String,
+    custom_roles: Vec<CustomRole>,
+    member_roles: Vec<MemberCustomRoles>,
 ) -> String {
+    let assigned = |user_id: i32| -> Vec<i32> {
+        member_roles
+            .iter()
+            .filter(|assignment| assignment.user_id == user_id)
+            .map(|assignment| assignment.custom_role_id)
+            .collect()
+    };
+    let role_name = |id: i32| {
+        custom_roles
+            .iter()
+            .find(|role| role.id == id)
+            .map(|role| role.name.clone())
+    };
     let team_public_id = db::team_public_id::encode(team.id).unwrap_or_else(|| team.id.to_string());
     let breadcrumb_team_name = team
         .name
@@ -41,11 +57,13 @@ pub fn page(
                         }
                     ]
                 }
-                Button {
-                    prefix_image_src: "{button_plus_svg.name}",
-                    popover_target: "create-invite-form",
-                    button_scheme: ButtonScheme::Primary,
-                    "Invite New Team Member"
+                if rbac.can_make_invitations() {
+                    Button {
+                        prefix_image_src: "{button_plus_svg.name}",
+                        popover_target: "create-invite-form",
+                        button_scheme: ButtonScheme::Primary,
+                        "Invite New Team Member"
+                    }
                 }
             ),
             div {
@@ -101,10 +119,29 @@ pub fn page(
                 div {
                     class: "mt-5 space-y-2",
                     for member in &members {
-                        MemberCard { member: member.clone(), rbac: rbac.clone() }
+                        MemberCard {
+                            member: member.clone(),
+                            custom_roles: assigned(member.id).into_iter().filter_map(role_name).collect::<Vec<_>>(),
+                            rbac: rbac.clone()
+                        }
                     }
                     for invite in &invites {
-                        InvitePendingCard { invite: invite.clone(), rbac: rbac.clone() }
+                        InvitePendingCard {
+                            invite: invite.clone(),
+                            custom_role: invite.custom_role_id.and_then(role_name),
+                            rbac: rbac.clone()
+                        }
+                    }
+                }
+
+                if rbac.can_manage_roles() {
+                    for member in members.iter().filter(|member| member.email != rbac.email) {
+                        super::member_roles_form::MemberRolesForm {
+                            submit_action: crate::routes::team::SetRoles{team_id: team_public_id.clone()}.to_string(),
+                            member: member.clone(),
+                            custom_roles: custom_roles.clone(),
+                            assigned: assigned(member.id),
+                        }
                     }
                 }
 
@@ -138,7 +175,13 @@ pub fn page(
 
                 // The form to create an invitation
                 super::invitation_form::InvitationForm {
-                    submit_action: crate::routes::team::CreateInvite{team_id:team_public_id.clone()}.to_string()
+                    submit_action: crate::routes::team::CreateInvite{team_id:team_public_id.clone()}.to_string(),
+                    can_invite_managers: rbac.can_manage_roles(),
+                    custom_roles: custom_roles
+                        .iter()
+                        .filter(|role| db::custom_roles::can_grant(&rbac, &role.permissions))
+                        .cloned()
+                        .collect::<Vec<_>>()
                 }
 
                 // Form to set he org name
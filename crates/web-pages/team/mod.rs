@@ -1,5 +1,6 @@
 pub mod invitation_form;
 pub mod member_card;
+pub mod member_roles_form;
 pub mod members;
 pub mod remove_warning;
 pub mod team_name_form;
@@ -35,3 +35,16 @@ pub fn Role(role: DBRole) -> Element {
         ),
     }
 }
+
+#[component]
+pub fn CustomRole(name: String) -> Element {
+    rsx!(
+        Badge {
+            class: "mr-2",
+            badge_color: BadgeColor::Info,
+            badge_style: BadgeStyle::Outline,
+            badge_size: BadgeSize::Sm,
+            "{name}"
+        }
+    )
+}
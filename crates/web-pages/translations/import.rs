@@ -0,0 +1,44 @@
+#![allow(non_snake_case)]
+use daisy_rsx::*;
+use dioxus::prelude::*;
+
+#[component]
+pub fn Import(team_id: String, locale: String) -> Element {
+    rsx!(
+        form {
+            action: crate::routes::translations::Import { team_id }.to_string(),
+            method: "post",
+            enctype: "multipart/form-data",
+            Modal {
+                trigger_id: "import-translations",
+                ModalBody {
+                    class: "flex flex-col gap-4",
+                    h3 { class: "font-bold text-lg mb-4", "Import Translations" }
+                    Fieldset {
+                        legend: "Locale",
+                        Input {
+                            input_type: InputType::Text,
+                            class: "w-full",
+                            name: "locale",
+                            value: locale,
+                            required: true,
+                        }
+                    }
+                    FileInput {
+                        class: "w-full",
+                        name: "file",
+                        required: true,
+                    }
+                    Alert {
+                        alert_color: AlertColor::Default,
+                        "JSON (.json), gettext (.po) and Fluent (.ftl) files are supported. Keys in the file replace the existing translations for the locale, other keys are left alone."
+                    }
+                    ModalAction {
+                        Button { class: "cancel-modal", button_scheme: ButtonScheme::Warning, "Cancel" }
+                        Button { button_type: ButtonType::Submit, button_scheme: ButtonScheme::Primary, "Import" }
+                    }
+                }
+            }
+        }
+    )
+}
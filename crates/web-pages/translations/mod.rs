@@ -0,0 +1,3 @@
+pub mod import;
+pub mod page;
+pub mod upsert;
@@ -0,0 +1,220 @@
+#![allow(non_snake_case)]
+use crate::app_layout::{AdminLayout, SideBar};
+use crate::components::confirm_modal::ConfirmModal;
+use crate::SectionIntroduction;
+use assets::files::*;
+use daisy_rsx::*;
+use db::authz::Rbac;
+use db::i18n::LocaleCoverage;
+use dioxus::prelude::*;
+
+/// A key in the selected locale, with the English text to translate from.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TranslationRow {
+    pub key: String,
+    pub value: Option<String>,
+    pub reference: Option<String>,
+}
+
+pub fn page(
+    team_id: String,
+    rbac: Rbac,
+    locale: String,
+    coverage: Vec<LocaleCoverage>,
+    rows: Vec<TranslationRow>,
+) -> String {
+    let export = |format: &str| {
+        crate::routes::translations::Export {
+            team_id: team_id.clone(),
+            locale: locale.clone(),
+            format: format.to_string(),
+        }
+        .to_string()
+    };
+    let (export_json, export_po, export_ftl) = (export("json"), export("po"), export("ftl"));
+
+    let page = rsx! {
+        AdminLayout {
+            section_class: "p-4",
+            selected_item: SideBar::Translations,
+            team_id: team_id.clone(),
+            rbac: rbac.clone(),
+            title: "Translations",
+            header: rsx!(
+                Breadcrumb {
+                    items: vec![BreadcrumbItem { text: "Translations".into(), href: None }]
+                }
+                div {
+                    class: "flex gap-2",
+                    form {
+                        method: "post",
+                        action: crate::routes::translations::Reload { team_id: team_id.clone() }.to_string(),
+                        Button {
+                            button_type: ButtonType::Submit,
+                            button_scheme: ButtonScheme::Neutral,
+                            "Reload Cache"
+                        }
+                    }
+                    Button {
+                        popover_target: "import-translations",
+                        button_scheme: ButtonScheme::Neutral,
+                        "Import"
+                    }
+                    Button {
+                        prefix_image_src: "{button_plus_svg.name}",
+                        popover_target: "new-translation",
+                        button_scheme: ButtonScheme::Primary,
+                        "Add Translation"
+                    }
+                }
+            ),
+            div {
+                class: "p-4 max-w-4xl w-full mx-auto",
+                SectionIntroduction {
+                    header: "Translations".to_string(),
+                    subtitle: "Edit the text shown in each language. Changes apply straight away; use Reload Cache after changing translations directly in the database.".to_string(),
+                    is_empty: false,
+                    empty_text: "".to_string(),
+                }
+                Card {
+                    class: "mt-5 has-data-table",
+                    CardHeader { title: "Coverage" }
+                    CardBody {
+                        table {
+                            class: "table table-sm",
+                            thead {
+                                th { "Locale" }
+                                th { "Translated" }
+                                th { "Missing" }
+                                th { class: "text-right", "Coverage" }
+                            }
+                            tbody {
+                                for locale_coverage in &coverage {
+                                    tr {
+                                        td {
+                                            a {
+                                                class: "link",
+                                                href: crate::routes::translations::Locale {
+                                                    team_id: team_id.clone(),
+                                                    locale: locale_coverage.locale.clone()
+                                                }.to_string(),
+                                                "{locale_coverage.locale}"
+                                            }
+                                        }
+                                        td { "{locale_coverage.translated} / {locale_coverage.total}" }
+                                        td { "{locale_coverage.missing.len()}" }
+                                        td {
+                                            class: "text-right",
+                                            Badge {
+                                                badge_color: if locale_coverage.missing.is_empty() { BadgeColor::Success } else { BadgeColor::Warning },
+                                                "{locale_coverage.percent()}%"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                Card {
+                    class: "mt-5 has-data-table",
+                    CardHeader { title: "Translations for {locale}" }
+                    CardBody {
+                        div {
+                            class: "flex gap-4 text-sm p-4",
+                            span { "Export:" }
+                            a { class: "link", href: "{export_json}", "JSON" }
+                            a { class: "link", href: "{export_po}", "gettext (PO)" }
+                            a { class: "link", href: "{export_ftl}", "Fluent" }
+                        }
+                        table {
+                            class: "table table-sm",
+                            thead {
+                                th { "Key" }
+                                th { "Text" }
+                                if locale != "en" {
+                                    th { "English" }
+                                }
+                                th { class: "text-right", "Action" }
+                            }
+                            tbody {
+                                for (index, row) in rows.iter().enumerate() {
+                                    tr {
+                                        td { code { "{row.key}" } }
+                                        td {
+                                            if let Some(value) = &row.value {
+                                                "{value}"
+                                            } else {
+                                                Badge { badge_color: BadgeColor::Warning, "Missing" }
+                                            }
+                                        }
+                                        if locale != "en" {
+                                            td { class: "opacity-80", {row.reference.clone().unwrap_or_default()} }
+                                        }
+                                        td {
+                                            class: "text-right",
+                                            DropDown {
+                                                direction: Direction::Left,
+                                                button_text: "...",
+                                                DropDownLink {
+                                                    popover_target: format!("edit-translation-{index}"),
+                                                    href: "#",
+                                                    target: "_top",
+                                                    if row.value.is_some() { "Edit" } else { "Add" }
+                                                }
+                                                if row.value.is_some() {
+                                                    DropDownLink {
+                                                        popover_target: format!("delete-translation-{index}"),
+                                                        href: "#",
+                                                        target: "_top",
+                                                        "Delete"
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                for (index, row) in rows.into_iter().enumerate() {
+                    super::upsert::Upsert {
+                        trigger_id: format!("edit-translation-{index}"),
+                        team_id: team_id.clone(),
+                        locale: locale.clone(),
+                        translation_key: row.key.clone(),
+                        value: row.value.clone().unwrap_or_default(),
+                        reference: if locale == "en" { None } else { row.reference }
+                    }
+                    if row.value.is_some() {
+                        ConfirmModal {
+                            action: crate::routes::translations::Delete { team_id: team_id.clone() }.to_string(),
+                            trigger_id: format!("delete-translation-{index}"),
+                            submit_label: "Delete".to_string(),
+                            heading: "Delete this Translation?".to_string(),
+                            warning: format!("The {locale} text for {} will be removed.", row.key),
+                            hidden_fields: vec![
+                                ("locale".into(), locale.clone()),
+                                ("key".into(), row.key),
+                            ],
+                        }
+                    }
+                }
+                super::upsert::Upsert {
+                    trigger_id: "new-translation",
+                    team_id: team_id.clone(),
+                    locale: locale.clone(),
+                    translation_key: "".to_string(),
+                    value: "".to_string(),
+                    reference: None
+                }
+                super::import::Import {
+                    team_id: team_id.clone(),
+                    locale: locale.clone()
+                }
+            }
+        }
+    };
+    crate::render(page)
+}
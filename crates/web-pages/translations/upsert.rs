@@ -0,0 +1,59 @@
+#![allow(non_snake_case)]
+use daisy_rsx::*;
+use dioxus::prelude::*;
+
+#[component]
+pub fn Upsert(
+    trigger_id: String,
+    team_id: String,
+    locale: String,
+    translation_key: String,
+    value: String,
+    reference: Option<String>,
+) -> Element {
+    rsx!(
+        Modal {
+            submit_action: crate::routes::translations::Upsert { team_id: team_id.clone() }.to_string(),
+            trigger_id,
+            ModalBody {
+                class: "flex flex-col gap-4",
+                h3 { class: "font-bold text-lg mb-4", "Translation" }
+                Fieldset {
+                    legend: "Locale",
+                    help_text: "A language tag such as en, de or pt-BR",
+                    Input {
+                        input_type: InputType::Text,
+                        class: "w-full",
+                        name: "locale",
+                        value: locale,
+                        required: true,
+                    }
+                }
+                Fieldset {
+                    legend: "Key",
+                    Input {
+                        input_type: InputType::Text,
+                        class: "w-full",
+                        name: "key",
+                        value: translation_key,
+                        required: true,
+                    }
+                }
+                if let Some(reference) = reference {
+                    Alert {
+                        alert_color: AlertColor::Default,
+                        "English: {reference}"
+                    }
+                }
+                Fieldset {
+                    legend: "Text",
+                    TextArea { class: "w-full", name: "value", rows: "4", required: true, "{value}" }
+                }
+                ModalAction {
+                    Button { class: "cancel-modal", button_scheme: ButtonScheme::Warning, "Cancel" }
+                    Button { button_type: ButtonType::Submit, button_scheme: ButtonScheme::Primary, "Save" }
+                }
+            }
+        }
+    )
+}
@@ -0,0 +1,14 @@
+pub mod page;
+pub mod upsert;
+pub mod view;
+
+use daisy_rsx::BadgeColor;
+use db::WebhookDeliveryStatus;
+
+pub fn delivery_status_badge(status: WebhookDeliveryStatus) -> (BadgeColor, &'static str) {
+    match status {
+        WebhookDeliveryStatus::Pending => (BadgeColor::Info, "Pending"),
+        WebhookDeliveryStatus::Delivered => (BadgeColor::Success, "Delivered"),
+        WebhookDeliveryStatus::Failed => (BadgeColor::Error, "Failed"),
+    }
+}
@@ -0,0 +1,168 @@
+#![allow(non_snake_case)]
+use super::upsert::{Upsert, WebhookForm};
+use crate::app_layout::{AdminLayout, SideBar};
+use crate::components::confirm_modal::ConfirmModal;
+use crate::SectionIntroduction;
+use assets::files::*;
+use daisy_rsx::*;
+use db::authz::Rbac;
+use db::Webhook;
+use dioxus::prelude::*;
+
+pub fn page(team_id: String, rbac: Rbac, webhooks: Vec<Webhook>) -> String {
+    let page = rsx! {
+        AdminLayout {
+            section_class: "p-4",
+            selected_item: SideBar::Webhooks,
+            team_id: team_id.clone(),
+            rbac: rbac,
+            title: "Webhooks",
+            header: rsx!(
+                Breadcrumb {
+                    items: vec![BreadcrumbItem { text: "Webhooks".into(), href: None }]
+                }
+                Button {
+                    prefix_image_src: "{button_plus_svg.name}",
+                    popover_target: "new-webhook",
+                    button_scheme: ButtonScheme::Primary,
+                    "New Webhook"
+                }
+            ),
+            div {
+                class: "p-4 max-w-4xl w-full mx-auto",
+                SectionIntroduction {
+                    header: "Webhooks".to_string(),
+                    subtitle: "Notify other systems when documents are processed, chats are flagged, integrations disconnect or people join the team. Each request is signed with the webhook's secret and failed deliveries are retried.".to_string(),
+                    is_empty: webhooks.is_empty(),
+                    empty_text: "No webhooks yet. Add one to start receiving events.".to_string(),
+                }
+
+                if !webhooks.is_empty() {
+                    Card {
+                        class: "mt-5 has-data-table",
+                        CardHeader { title: "Webhooks" }
+                        CardBody {
+                            table {
+                                class: "table table-sm",
+                                thead {
+                                    th { "Endpoint" }
+                                    th { "Events" }
+                                    th { "Status" }
+                                    th { class: "text-right", "Action" }
+                                }
+                                tbody {
+                                    for webhook in &webhooks {
+                                        tr {
+                                            td {
+                                                a {
+                                                    class: "link",
+                                                    href: crate::routes::webhooks::View { team_id: team_id.clone(), id: webhook.id }.to_string(),
+                                                    "{webhook.url}"
+                                                }
+                                                if !webhook.description.is_empty() {
+                                                    p { class: "text-xs opacity-70", "{webhook.description}" }
+                                                }
+                                            }
+                                            td {
+                                                div {
+                                                    class: "flex flex-wrap gap-1",
+                                                    for event in &webhook.events {
+                                                        Badge { badge_color: BadgeColor::Neutral, "{event}" }
+                                                    }
+                                                }
+                                            }
+                                            td {
+                                                div {
+                                                    class: "flex flex-wrap gap-1",
+                                                    if webhook.active {
+                                                        Badge { badge_color: BadgeColor::Success, "Active" }
+                                                    } else {
+                                                        Badge { badge_color: BadgeColor::Warning, "Paused" }
+                                                    }
+                                                    if webhook.failed > 0 {
+                                                        Badge { badge_color: BadgeColor::Error, "{webhook.failed} failed" }
+                                                    }
+                                                    if webhook.pending > 0 {
+                                                        Badge { badge_color: BadgeColor::Info, "{webhook.pending} pending" }
+                                                    }
+                                                }
+                                            }
+                                            td {
+                                                class: "text-right",
+                                                DropDown {
+                                                    direction: Direction::Left,
+                                                    button_text: "...",
+                                                    DropDownLink {
+                                                        href: crate::routes::webhooks::View { team_id: team_id.clone(), id: webhook.id }.to_string(),
+                                                        "Deliveries"
+                                                    }
+                                                    DropDownLink {
+                                                        popover_target: format!("edit-webhook-{}", webhook.id),
+                                                        href: "#",
+                                                        target: "_top",
+                                                        "Edit"
+                                                    }
+                                                    DropDownLink {
+                                                        popover_target: format!("test-webhook-{}", webhook.id),
+                                                        href: "#",
+                                                        target: "_top",
+                                                        "Send Test"
+                                                    }
+                                                    DropDownLink {
+                                                        popover_target: format!("delete-webhook-{}", webhook.id),
+                                                        href: "#",
+                                                        target: "_top",
+                                                        "Delete"
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                for webhook in webhooks {
+                    Upsert {
+                        trigger_id: format!("edit-webhook-{}", webhook.id),
+                        action: crate::routes::webhooks::Edit { team_id: team_id.clone(), id: webhook.id }.to_string(),
+                        form: WebhookForm {
+                            url: webhook.url.clone(),
+                            description: webhook.description.clone(),
+                            events: webhook.events.clone(),
+                            active: webhook.active,
+                        },
+                        is_new: false
+                    }
+                    ConfirmModal {
+                        action: crate::routes::webhooks::Test { team_id: team_id.clone(), id: webhook.id }.to_string(),
+                        trigger_id: format!("test-webhook-{}", webhook.id),
+                        submit_label: "Send".to_string(),
+                        heading: "Send a Test Event?".to_string(),
+                        warning: format!("A webhook.test event will be sent to {}.", webhook.url),
+                        hidden_fields: vec![],
+                    }
+                    ConfirmModal {
+                        action: crate::routes::webhooks::Delete { team_id: team_id.clone(), id: webhook.id }.to_string(),
+                        trigger_id: format!("delete-webhook-{}", webhook.id),
+                        submit_label: "Delete".to_string(),
+                        heading: "Delete this Webhook?".to_string(),
+                        warning: "Events will no longer be sent to this endpoint and its delivery log will be removed.".to_string(),
+                        hidden_fields: vec![],
+                    }
+                }
+
+                Upsert {
+                    trigger_id: "new-webhook",
+                    action: crate::routes::webhooks::New { team_id: team_id.clone() }.to_string(),
+                    form: WebhookForm::default(),
+                    is_new: true
+                }
+            }
+        }
+    };
+
+    crate::render(page)
+}
@@ -0,0 +1,91 @@
+#![allow(non_snake_case)]
+use daisy_rsx::*;
+use db::webhooks::EVENTS;
+use dioxus::prelude::*;
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct WebhookForm {
+    pub url: String,
+    pub description: String,
+    pub events: Vec<String>,
+    pub active: bool,
+}
+
+/// Creates a webhook, or edits one when `action` is its edit route.
+#[component]
+pub fn Upsert(trigger_id: String, action: String, form: WebhookForm, is_new: bool) -> Element {
+    rsx!(
+        Modal {
+            submit_action: action,
+            trigger_id,
+            ModalBody {
+                class: "flex flex-col gap-4",
+                h3 {
+                    class: "font-bold text-lg mb-4",
+                    if is_new { "New Webhook" } else { "Edit Webhook" }
+                }
+                Fieldset {
+                    legend: "Endpoint URL",
+                    help_text: "Events are POSTed here as JSON",
+                    Input {
+                        input_type: InputType::Text,
+                        class: "w-full",
+                        placeholder: "https://ops.example.com/hooks/bionic",
+                        name: "url",
+                        value: form.url,
+                        required: true,
+                    }
+                }
+                Fieldset {
+                    legend: "Description",
+                    Input {
+                        input_type: InputType::Text,
+                        class: "w-full",
+                        placeholder: "What receives these events",
+                        name: "description",
+                        value: form.description,
+                    }
+                }
+                Fieldset {
+                    legend: "Events",
+                    for event in EVENTS {
+                        label {
+                            class: "flex items-start gap-2 mt-2",
+                            input {
+                                "type": "checkbox",
+                                class: "checkbox checkbox-sm",
+                                name: "events",
+                                value: event.name,
+                                checked: form.events.iter().any(|name| name == event.name),
+                            }
+                            div {
+                                code { "{event.name}" }
+                                p { class: "text-xs opacity-70", "{event.description}" }
+                            }
+                        }
+                    }
+                }
+                if !is_new {
+                    label {
+                        class: "flex items-center gap-2",
+                        input {
+                            "type": "checkbox",
+                            class: "checkbox checkbox-sm",
+                            name: "active",
+                            checked: form.active,
+                        }
+                        "Active"
+                    }
+                }
+                ModalAction {
+                    Button { class: "cancel-modal", button_scheme: ButtonScheme::Warning, "Cancel" }
+                    Button {
+                        button_type: ButtonType::Submit,
+                        button_scheme: ButtonScheme::Primary,
+                        if is_new { "Create Webhook" } else { "Save" }
+                    }
+                }
+            }
+        }
+    )
+}
@@ -0,0 +1,156 @@
+#![allow(non_snake_case)]
+use super::delivery_status_badge;
+use crate::app_layout::{AdminLayout, SideBar};
+use daisy_rsx::*;
+use db::authz::Rbac;
+use db::{Webhook, WebhookDelivery, WebhookDeliveryStatus};
+use dioxus::prelude::*;
+
+pub fn page(
+    team_id: String,
+    rbac: Rbac,
+    webhook: Webhook,
+    deliveries: Vec<WebhookDelivery>,
+) -> String {
+    let page = rsx! {
+        AdminLayout {
+            section_class: "p-4",
+            selected_item: SideBar::Webhooks,
+            team_id: team_id.clone(),
+            rbac: rbac,
+            title: "Webhooks",
+            header: rsx!(
+                Breadcrumb {
+                    items: vec![
+                        BreadcrumbItem {
+                            text: "Webhooks".into(),
+                            href: Some(crate::routes::webhooks::Index { team_id: team_id.clone() }.to_string())
+                        },
+                        BreadcrumbItem { text: webhook.url.clone(), href: None }
+                    ]
+                }
+                form {
+                    method: "post",
+                    action: crate::routes::webhooks::Test { team_id: team_id.clone(), id: webhook.id }.to_string(),
+                    Button {
+                        button_type: ButtonType::Submit,
+                        button_scheme: ButtonScheme::Primary,
+                        "Send Test"
+                    }
+                }
+            ),
+            div {
+                class: "p-4 max-w-4xl w-full mx-auto",
+                Card {
+                    CardHeader { title: "Signing Secret" }
+                    CardBody {
+                        class: "flex flex-col gap-2",
+                        Input {
+                            input_type: InputType::Text,
+                            class: "w-full font-mono",
+                            name: "secret",
+                            value: webhook.secret.clone(),
+                            readonly: true,
+                        }
+                        p {
+                            class: "text-sm opacity-80",
+                            "Each request has an "
+                            code { "X-Bionic-Signature" }
+                            " header of the form "
+                            code { "t=<timestamp>,v1=<signature>" }
+                            ". The signature is the hex HMAC-SHA256 of "
+                            code { "<timestamp>.<body>" }
+                            " using this secret. "
+                            code { "X-Bionic-Delivery" }
+                            " is the event id, which stays the same when a delivery is retried."
+                        }
+                    }
+                }
+                Card {
+                    class: "mt-5 has-data-table",
+                    CardHeader { title: "Deliveries" }
+                    CardBody {
+                        if deliveries.is_empty() {
+                            p { class: "p-4 text-sm opacity-70", "Nothing has been sent yet." }
+                        } else {
+                            table {
+                                class: "table table-sm",
+                                thead {
+                                    th { "Event" }
+                                    th { "Status" }
+                                    th { "Attempts" }
+                                    th { "Response" }
+                                    th { "Created" }
+                                    th { class: "text-right", "Action" }
+                                }
+                                tbody {
+                                    for delivery in deliveries {
+                                        DeliveryRow {
+                                            team_id: team_id.clone(),
+                                            webhook_id: webhook.id,
+                                            delivery
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    crate::render(page)
+}
+
+#[component]
+fn DeliveryRow(team_id: String, webhook_id: i32, delivery: WebhookDelivery) -> Element {
+    let (badge_color, status) = delivery_status_badge(delivery.status);
+    let response = delivery
+        .response_status
+        .map(|status| status.to_string())
+        .unwrap_or_else(|| "-".to_string());
+
+    rsx!(
+        tr {
+            td {
+                code { "{delivery.event_type}" }
+                details {
+                    summary { class: "text-xs opacity-70 cursor-pointer", "{delivery.event_id}" }
+                    pre { class: "text-xs whitespace-pre-wrap", "{delivery.payload}" }
+                    if let Some(error) = &delivery.last_error {
+                        p { class: "text-xs text-error", "{error}" }
+                    }
+                }
+            }
+            td { Badge { badge_color, "{status}" } }
+            td { "{delivery.attempts}" }
+            td { "{response}" }
+            td {
+                RelativeTime {
+                    format: RelativeTimeFormat::Relative,
+                    datetime: delivery.created_at.clone()
+                }
+            }
+            td {
+                class: "text-right",
+                if delivery.status != WebhookDeliveryStatus::Pending {
+                    form {
+                        method: "post",
+                        action: crate::routes::webhooks::Replay {
+                            team_id: team_id.clone(),
+                            id: webhook_id,
+                            delivery_id: delivery.id
+                        }.to_string(),
+                        Button {
+                            button_type: ButtonType::Submit,
+                            button_scheme: ButtonScheme::Neutral,
+                            button_size: ButtonSize::Small,
+                            "Replay"
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
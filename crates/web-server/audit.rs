@@ -0,0 +1,262 @@
+//! Audit events outside the database: the client address of each request, and the
+//! shape events take when they're exported or sent to a SIEM.
+
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    extract::{ConnectInfo, Request},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use db::AuditTrail;
+use ipnet::IpNet;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Makes the client address available to the audit trail for the rest of the request.
+pub async fn client_ip(request: Request, next: Next) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(address)| address.ip());
+    let trusted_proxies = request
+        .extensions()
+        .get::<crate::config::Config>()
+        .map(|config| config.trusted_proxies.as_slice())
+        .unwrap_or_default();
+    let client_ip = client_ip_from(request.headers(), peer, trusted_proxies);
+    db::audit::with_client_ip(client_ip, next.run(request)).await
+}
+
+// Anyone can send forwarding headers, so they are only believed from a trusted proxy.
+// Each proxy appends the address it saw, so X-Forwarded-For is read from the right and
+// the client is the first address that isn't a trusted proxy. Entries left of anything
+// that doesn't parse were written by the client and are ignored. X-Real-IP is only used
+// when there is no X-Forwarded-For.
+fn client_ip_from(
+    headers: &HeaderMap,
+    peer: Option<IpAddr>,
+    trusted_proxies: &[IpNet],
+) -> Option<String> {
+    let is_trusted = |ip: &IpAddr| trusted_proxies.iter().any(|proxy| proxy.contains(ip));
+    let Some(peer) = peer.filter(is_trusted) else {
+        return peer.map(|ip| ip.to_string());
+    };
+
+    let forwarded: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect();
+    if forwarded.is_empty() {
+        let real_ip = headers
+            .get("x-real-ip")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<IpAddr>().ok());
+        return Some(real_ip.unwrap_or(peer).to_string());
+    }
+
+    let mut client = peer;
+    for address in forwarded.iter().rev() {
+        let Ok(ip) = address.parse::<IpAddr>() else {
+            break;
+        };
+        client = ip;
+        if !is_trusted(&ip) {
+            break;
+        }
+    }
+    Some(client.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AuditEvent {
+    pub id: i32,
+    pub created_at: String,
+    pub user: String,
+    pub team_id: Option<i32>,
+    pub access_type: String,
+    pub action: String,
+    pub ip_address: Option<String>,
+    pub object_type: Option<String>,
+    pub object_id: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl From<AuditTrail> for AuditEvent {
+    fn from(audit: AuditTrail) -> Self {
+        let json = |text: Option<String>| text.and_then(|text| serde_json::from_str(&text).ok());
+        Self {
+            id: audit.id,
+            created_at: audit.created_at,
+            user: audit.email,
+            team_id: audit.team_id,
+            access_type: format!("{:?}", audit.access_type),
+            action: format!("{:?}", audit.action),
+            ip_address: audit.ip_address,
+            object_type: audit.object_type,
+            object_id: audit.object_id,
+            before: json(audit.before),
+            after: json(audit.after),
+        }
+    }
+}
+
+const CSV_HEADER: &str =
+    "id,created_at,user,team_id,access_type,action,ip_address,object_type,object_id,before,after";
+
+pub fn to_csv(events: &[AuditEvent]) -> String {
+    let mut csv = format!("{CSV_HEADER}\n");
+    for event in events {
+        let fields = [
+            event.id.to_string(),
+            event.created_at.clone(),
+            event.user.clone(),
+            event.team_id.map(|id| id.to_string()).unwrap_or_default(),
+            event.access_type.clone(),
+            event.action.clone(),
+            event.ip_address.clone().unwrap_or_default(),
+            event.object_type.clone().unwrap_or_default(),
+            event.object_id.clone().unwrap_or_default(),
+            event
+                .before
+                .as_ref()
+                .map(Value::to_string)
+                .unwrap_or_default(),
+            event
+                .after
+                .as_ref()
+                .map(Value::to_string)
+                .unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+pub(crate) fn csv_field(field: &str) -> String {
+    // Leading formula characters are neutralised so spreadsheets don't evaluate them.
+    let field = if field.starts_with(['=', '+', '-', '@']) {
+        format!("'{field}")
+    } else {
+        field.to_string()
+    };
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use serde_json::json;
+
+    #[test]
+    fn forwarded_address_wins_over_a_trusted_peer() {
+        let trusted: Vec<IpNet> = vec!["10.0.0.0/8".parse().unwrap()];
+        let peer = Some("10.0.0.1".parse().unwrap());
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            client_ip_from(&headers, peer, &trusted),
+            Some("10.0.0.1".to_string())
+        );
+
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("198.51.100.4, 203.0.113.9, 10.0.0.2"),
+        );
+        assert_eq!(
+            client_ip_from(&headers, peer, &trusted),
+            Some("203.0.113.9".to_string())
+        );
+
+        headers.insert("x-forwarded-for", HeaderValue::from_static("not an ip"));
+        assert_eq!(
+            client_ip_from(&headers, peer, &trusted),
+            Some("10.0.0.1".to_string())
+        );
+    }
+
+    #[test]
+    fn unparseable_forwarded_entries_do_not_hide_the_address_the_proxy_saw() {
+        let trusted: Vec<IpNet> = vec!["10.0.0.0/8".parse().unwrap()];
+        let peer = Some("10.0.0.1".parse().unwrap());
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("garbage, 203.0.113.9"),
+        );
+        headers.insert("x-real-ip", HeaderValue::from_static("192.0.2.66"));
+        assert_eq!(
+            client_ip_from(&headers, peer, &trusted),
+            Some("203.0.113.9".to_string())
+        );
+
+        // Nothing left of what the client wrote is believed.
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("192.0.2.66, garbage, 10.0.0.7"),
+        );
+        assert_eq!(
+            client_ip_from(&headers, peer, &trusted),
+            Some("10.0.0.7".to_string())
+        );
+
+        headers.remove("x-forwarded-for");
+        assert_eq!(
+            client_ip_from(&headers, peer, &trusted),
+            Some("192.0.2.66".to_string())
+        );
+    }
+
+    #[test]
+    fn forwarded_headers_from_other_peers_are_ignored() {
+        let peer = Some("198.51.100.4".parse().unwrap());
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.9"));
+        headers.insert("x-real-ip", HeaderValue::from_static("203.0.113.9"));
+
+        assert_eq!(
+            client_ip_from(&headers, peer, &[]),
+            Some("198.51.100.4".to_string())
+        );
+        assert_eq!(
+            client_ip_from(&headers, peer, &["10.0.0.0/8".parse().unwrap()]),
+            Some("198.51.100.4".to_string())
+        );
+    }
+
+    #[test]
+    fn csv_quotes_and_neutralises_fields() {
+        let event = AuditEvent {
+            id: 7,
+            created_at: "2026-10-19T10:00:00Z".to_string(),
+            user: "=cmd@example.com".to_string(),
+            team_id: Some(3),
+            access_type: "UserInterface".to_string(),
+            action: "UpdateConfiguration".to_string(),
+            ip_address: Some("203.0.113.9".to_string()),
+            object_type: Some("model_registry.models".to_string()),
+            object_id: Some("4".to_string()),
+            before: Some(json!({"name": "a"})),
+            after: Some(json!({"name": "b"})),
+        };
+
+        let csv = to_csv(&[event]);
+        let row = csv.lines().nth(1).unwrap();
+
+        assert_eq!(
+            row,
+            r#"7,2026-10-19T10:00:00Z,'=cmd@example.com,3,UserInterface,UpdateConfiguration,203.0.113.9,model_registry.models,4,"{""name"":""a""}","{""name"":""b""}""#
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! Streams the audit trail to a SIEM.
+//!
+//! Set `AUDIT_SINK_URL` to one of
+//!
+//! - `syslog+udp://host:514` or `syslog+tcp://host:514` for RFC 5424 syslog with the
+//!   event as JSON in the message, or
+//! - an `http://` or `https://` URL that gets batches of events POSTed as a JSON array.
+//!   `AUDIT_SINK_TOKEN` is sent as a bearer token when set.
+//!
+//! Events are sent in order, `AUDIT_SINK_BATCH_SIZE` (default 100) at a time, every
+//! `AUDIT_SINK_INTERVAL_SECS` (default 5). Events go in the order their transactions
+//! started, once every earlier transaction has finished, so a long transaction holds
+//! back the events after it rather than having them skipped. The position is stored in
+//! `ops.audit_sink_cursors`, so a failed batch is retried with backoff and nothing is lost
+//! across restarts. Delivery is at least once; the event id can be used to deduplicate.
+
+use std::env;
+use std::time::Duration;
+
+use db::{queries, Pool};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+
+use crate::audit::AuditEvent;
+
+const DEFAULT_BATCH_SIZE: i64 = 100;
+const DEFAULT_INTERVAL_SECS: u64 = 5;
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+// How long one server has the sink to itself while it sends a batch.
+const LEASE_SECS: i32 = 120;
+// Facility 13 (log audit), severity 6 (informational).
+const SYSLOG_PRIORITY: u8 = 13 * 8 + 6;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SinkTarget {
+    SyslogUdp(String),
+    SyslogTcp(String),
+    Webhook(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditSinkConfig {
+    pub url: String,
+    pub target: SinkTarget,
+    pub token: Option<String>,
+    pub batch_size: i64,
+    pub interval: Duration,
+}
+
+impl AuditSinkConfig {
+    pub fn from_env() -> Option<Self> {
+        let url = env::var("AUDIT_SINK_URL")
+            .ok()
+            .filter(|url| !url.is_empty())?;
+        let target = match parse_target(&url) {
+            Some(target) => target,
+            None => {
+                tracing::error!(
+                    "AUDIT_SINK_URL {url} is not a syslog+udp, syslog+tcp or http(s) URL"
+                );
+                return None;
+            }
+        };
+        Some(Self {
+            url,
+            target,
+            token: env::var("AUDIT_SINK_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty()),
+            batch_size: env::var("AUDIT_SINK_BATCH_SIZE")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .filter(|size| *size > 0)
+                .unwrap_or(DEFAULT_BATCH_SIZE),
+            interval: Duration::from_secs(
+                env::var("AUDIT_SINK_INTERVAL_SECS")
+                    .ok()
+                    .and_then(|value| value.parse().ok())
+                    .unwrap_or(DEFAULT_INTERVAL_SECS),
+            ),
+        })
+    }
+}
+
+fn parse_target(url: &str) -> Option<SinkTarget> {
+    if let Some(address) = url.strip_prefix("syslog+udp://") {
+        Some(SinkTarget::SyslogUdp(
+            address.trim_end_matches('/').to_string(),
+        ))
+    } else if let Some(address) = url.strip_prefix("syslog+tcp://") {
+        Some(SinkTarget::SyslogTcp(
+            address.trim_end_matches('/').to_string(),
+        ))
+    } else if url.starts_with("http://") || url.starts_with("https://") {
+        Some(SinkTarget::Webhook(url.to_string()))
+    } else {
+        None
+    }
+}
+
+/// Starts sending audit events in the background when a sink is configured.
+pub fn spawn(pool: Pool) {
+    if let Some(config) = AuditSinkConfig::from_env() {
+        tracing::info!("Streaming the audit trail to {}", config.url);
+        tokio::spawn(run(pool, config));
+    }
+}
+
+async fn run(pool: Pool, config: AuditSinkConfig) {
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .unwrap_or_default();
+    let mut delay = config.interval;
+
+    loop {
+        match send_batch(&pool, &config, &http).await {
+            // A full batch means there is probably more waiting.
+            Ok(sent) if sent as i64 == config.batch_size => continue,
+            Ok(_) => delay = config.interval,
+            Err(err) => {
+                delay = (delay * 2).clamp(Duration::from_secs(1), MAX_BACKOFF);
+                tracing::warn!("Failed to send audit events to {}: {err}", config.url);
+            }
+        }
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Sends the next batch and moves the cursor past it. Returns how many were sent.
+async fn send_batch(
+    pool: &Pool,
+    config: &AuditSinkConfig,
+    http: &reqwest::Client,
+) -> Result<usize, String> {
+    let client = pool.get().await.map_err(|e| e.to_string())?;
+
+    queries::audit_trail::insert_sink_cursor()
+        .bind(&client, &config.url)
+        .await
+        .map_err(|e| e.to_string())?;
+    // Another replica has the lease and is sending.
+    let Some(cursor) = queries::audit_trail::claim_sink_cursor()
+        .bind(&client, &LEASE_SECS, &config.url)
+        .opt()
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(0);
+    };
+
+    let result = send_after(&client, config, http, cursor).await;
+    if result.is_err() {
+        let _ = queries::audit_trail::release_sink_cursor()
+            .bind(&client, &config.url)
+            .await;
+    }
+    result
+}
+
+async fn send_after(
+    client: &db::Client,
+    config: &AuditSinkConfig,
+    http: &reqwest::Client,
+    cursor: queries::audit_trail::ClaimSinkCursor,
+) -> Result<usize, String> {
+    let rows = queries::audit_trail::audit_after()
+        .bind(
+            client,
+            &cursor.last_xact_id,
+            &cursor.last_audit_id,
+            &config.batch_size,
+        )
+        .all()
+        .await
+        .map_err(|e| e.to_string())?;
+    let Some((last_xact_id, last_audit_id)) = rows.last().map(|row| (row.xact_id, row.id)) else {
+        queries::audit_trail::release_sink_cursor()
+            .bind(client, &config.url)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(0);
+    };
+    let events: Vec<AuditEvent> = rows.into_iter().map(AuditEvent::from).collect();
+
+    // Finish well inside the lease, so no other server starts on the same events.
+    tokio::time::timeout(
+        Duration::from_secs(LEASE_SECS as u64 / 2),
+        send(config, http, &events),
+    )
+    .await
+    .map_err(|_| "timed out sending".to_string())??;
+
+    queries::audit_trail::update_sink_cursor()
+        .bind(client, &last_xact_id, &last_audit_id, &config.url)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(events.len())
+}
+
+async fn send(
+    config: &AuditSinkConfig,
+    http: &reqwest::Client,
+    events: &[AuditEvent],
+) -> Result<(), String> {
+    match &config.target {
+        SinkTarget::SyslogUdp(address) => {
+            let socket = UdpSocket::bind("0.0.0.0:0")
+                .await
+                .map_err(|e| e.to_string())?;
+            socket.connect(address).await.map_err(|e| e.to_string())?;
+            for event in events {
+                socket
+                    .send(syslog_message(event).as_bytes())
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        }
+        SinkTarget::SyslogTcp(address) => {
+            let mut stream = TcpStream::connect(address)
+                .await
+                .map_err(|e| e.to_string())?;
+            let mut messages = String::new();
+            for event in events {
+                messages.push_str(&syslog_message(event));
+                messages.push('\n');
+            }
+            stream
+                .write_all(messages.as_bytes())
+                .await
+                .map_err(|e| e.to_string())?;
+            stream.shutdown().await.map_err(|e| e.to_string())
+        }
+        SinkTarget::Webhook(url) => {
+            let mut request = http.post(url).json(events);
+            if let Some(token) = &config.token {
+                request = request.bearer_auth(token);
+            }
+            let response = request.send().await.map_err(|e| e.to_string())?;
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(format!("webhook responded with {}", response.status()))
+            }
+        }
+    }
+}
+
+fn syslog_message(event: &AuditEvent) -> String {
+    let hostname = env::var("HOSTNAME")
+        .ok()
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| "-".to_string());
+    format!(
+        "<{SYSLOG_PRIORITY}>1 {} {hostname} bionic-gpt - audit - {}",
+        event.created_at,
+        serde_json::to_string(event).unwrap_or_default()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    fn event(id: i32) -> AuditEvent {
+        AuditEvent {
+            id,
+            created_at: "2026-10-19T10:00:00+00:00".to_string(),
+            user: "admin@example.com".to_string(),
+            team_id: None,
+            access_type: "UserInterface".to_string(),
+            action: "UpdateConfiguration".to_string(),
+            ip_address: None,
+            object_type: Some("ops.runtime_settings".to_string()),
+            object_id: Some("default_system_prompt".to_string()),
+            before: None,
+            after: None,
+        }
+    }
+
+    #[test]
+    fn targets_are_parsed_from_the_url() {
+        assert_eq!(
+            parse_target("syslog+udp://siem:514"),
+            Some(SinkTarget::SyslogUdp("siem:514".to_string()))
+        );
+        assert_eq!(
+            parse_target("syslog+tcp://siem:6514/"),
+            Some(SinkTarget::SyslogTcp("siem:6514".to_string()))
+        );
+        assert_eq!(
+            parse_target("https://siem.example.com/ingest"),
+            Some(SinkTarget::Webhook(
+                "https://siem.example.com/ingest".to_string()
+            ))
+        );
+        assert_eq!(parse_target("ftp://siem"), None);
+    }
+
+    #[tokio::test]
+    async fn tcp_syslog_sends_one_line_per_event() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let received = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut text = String::new();
+            socket.read_to_string(&mut text).await.unwrap();
+            text
+        });
+
+        let config = AuditSinkConfig {
+            url: format!("syslog+tcp://{address}"),
+            target: SinkTarget::SyslogTcp(address),
+            token: None,
+            batch_size: 10,
+            interval: Duration::from_secs(1),
+        };
+        send(&config, &reqwest::Client::new(), &[event(1), event(2)])
+            .await
+            .unwrap();
+
+        let text = received.await.unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("<110>1 2026-10-19T10:00:00+00:00 "));
+        assert!(lines[1].contains(" bionic-gpt - audit - {\"id\":2,"));
+    }
+}
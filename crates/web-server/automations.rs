@@ -0,0 +1,429 @@
+//! Runs automations in the background.
+//!
+//! Schedules that are due queue a run and move on to their next time. Processed
+//! documents queue runs from a trigger in the database and inbound webhooks from
+//! `POST /v1/automations/trigger`. Each run fills in the prompt template from what
+//! started it and sends it to the assistant as the owner, in a new conversation, so it
+//! has the owner's datasets and integrations. The result is then emailed or POSTed to a
+//! webhook when the automation asks for it.
+
+use std::time::Duration;
+
+use agent_runtime::headless::{self, HeadlessOutcome};
+use chrono::{DateTime, Utc};
+use db::{authz, queries, AutomationRunStatus, ChatRole, ChatStatus, ClaimedRun, Pool};
+use lettre::Message;
+use serde_json::{json, Value};
+use web_pages::routes::console::Conversation;
+
+use crate::config::Config;
+use crate::cron::Schedule;
+
+const BATCH_SIZE: usize = 5;
+const SCHEDULE_BATCH_SIZE: i64 = 100;
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+/// How long a claimed run is held. It is extended while the run is going.
+const LEASE_SECS: i32 = 300;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// A run that keeps stopping the worker part way through is given up on.
+const MAX_ATTEMPTS: i32 = 3;
+
+/// Starts scheduling and running automations in the background.
+pub fn spawn(pool: Pool, config: Config) {
+    tokio::spawn(run(pool, config));
+}
+
+async fn run(pool: Pool, config: Config) {
+    let http = outbound::client(REQUEST_TIMEOUT);
+
+    loop {
+        if let Err(err) = schedule_due(&pool).await {
+            tracing::warn!("Failed to schedule automations: {err}");
+        }
+        match run_queued(&pool, &config, &http).await {
+            // A full batch means there is probably more waiting.
+            Ok(ran) if ran == BATCH_SIZE => continue,
+            Ok(_) => {}
+            Err(err) => tracing::warn!("Failed to run automations: {err}"),
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Queues a run for every schedule that is due and moves each one to its next time.
+async fn schedule_due(pool: &Pool) -> Result<usize, String> {
+    let mut client = pool.get().await.map_err(|e| e.to_string())?;
+    let transaction = client.transaction().await.map_err(|e| e.to_string())?;
+
+    let due = queries::automations::due_schedules()
+        .bind(&transaction, &SCHEDULE_BATCH_SIZE)
+        .all()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let now = Utc::now();
+    for schedule in &due {
+        let event = json!({ "scheduled_for": schedule.scheduled_for.to_rfc3339() });
+        queries::automations::schedule_run()
+            .bind(&transaction, &schedule.id, &event)
+            .await
+            .map_err(|e| e.to_string())?;
+        // Runs missed while the server was down aren't made up, the next one is from now.
+        let next_run_at = next_run(&schedule.cron, now);
+        queries::automations::set_next_run()
+            .bind(&transaction, &next_run_at, &schedule.id)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    transaction.commit().await.map_err(|e| e.to_string())?;
+    Ok(due.len())
+}
+
+/// When a schedule fires next, or `None` when it never will.
+pub fn next_run(cron: &str, after: DateTime<Utc>) -> Option<DateTime<chrono::FixedOffset>> {
+    cron.parse::<Schedule>()
+        .ok()?
+        .next_after(after)
+        .map(|time| time.fixed_offset())
+}
+
+/// Runs the queued runs and records how they went. Returns how many were run.
+async fn run_queued(pool: &Pool, config: &Config, http: &reqwest::Client) -> Result<usize, String> {
+    let client = pool.get().await.map_err(|e| e.to_string())?;
+
+    let mut ran = 0;
+    while ran < BATCH_SIZE {
+        // One at a time, as only the run being worked on has its lease extended. Claiming
+        // commits straight away so other replicas skip the run.
+        let Some(run) = queries::automations::claim_runs()
+            .bind(&client, &LEASE_SECS, &1)
+            .opt()
+            .await
+            .map_err(|e| e.to_string())?
+        else {
+            break;
+        };
+        ran += 1;
+
+        let heartbeat = tokio::spawn(heartbeat(pool.clone(), run.id, run.attempts));
+        let result = execute(pool, &run).await;
+        heartbeat.abort();
+
+        if let Err(error) = &result.outcome {
+            tracing::warn!(
+                "Automation {} run {} failed: {error}",
+                run.automation_id,
+                run.id
+            );
+        }
+
+        let (status, error) = match &result.outcome {
+            Ok(HeadlessOutcome::Completed(_)) => (AutomationRunStatus::Success, None),
+            Ok(HeadlessOutcome::AwaitingApproval) => (AutomationRunStatus::AwaitingApproval, None),
+            Err(error) => (AutomationRunStatus::Failed, Some(error.clone())),
+        };
+        let finished = queries::automations::finish_run()
+            .bind(
+                &client,
+                &status,
+                &result.conversation_id,
+                &error,
+                &run.id,
+                &run.attempts,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        if finished == 0 {
+            tracing::warn!(
+                "Automation run {} was taken over before it finished",
+                run.id
+            );
+            continue;
+        }
+
+        notify(config, http, &run, &result).await;
+    }
+
+    Ok(ran)
+}
+
+/// Extends the lease on a run every third of the lease until it is aborted.
+async fn heartbeat(pool: Pool, run_id: i32, attempts: i32) {
+    let every = Duration::from_secs((LEASE_SECS / 3) as u64);
+    loop {
+        tokio::time::sleep(every).await;
+        let extended = match pool.get().await {
+            Ok(client) => queries::automations::extend_run_lease()
+                .bind(&client, &LEASE_SECS, &run_id, &attempts)
+                .await
+                .map_err(|error| error.to_string()),
+            Err(error) => Err(error.to_string()),
+        };
+        match extended {
+            Ok(0) => {
+                tracing::warn!("Automation run {run_id} is no longer held by this worker");
+                return;
+            }
+            Ok(_) => {}
+            Err(error) => tracing::warn!("Not able to extend the lease on run {run_id}: {error}"),
+        }
+    }
+}
+
+struct RunResult {
+    conversation_id: Option<i64>,
+    outcome: Result<HeadlessOutcome, String>,
+}
+
+async fn execute(pool: &Pool, run: &ClaimedRun) -> RunResult {
+    if run.attempts > MAX_ATTEMPTS {
+        return RunResult {
+            conversation_id: None,
+            outcome: Err(format!("Gave up after {MAX_ATTEMPTS} attempts")),
+        };
+    }
+
+    let started = match start_conversation(pool, run).await {
+        Ok(started) => started,
+        Err(error) => {
+            return RunResult {
+                conversation_id: None,
+                outcome: Err(error),
+            }
+        }
+    };
+    let (conversation_id, chat_id) = started;
+
+    RunResult {
+        conversation_id: Some(conversation_id),
+        outcome: headless::run_chat(pool, &run.owner_sub, conversation_id, chat_id).await,
+    }
+}
+
+/// Checks the owner can still run the automation in the team and adds the prompt to a
+/// new conversation of theirs.
+async fn start_conversation(pool: &Pool, run: &ClaimedRun) -> Result<(i64, i32), String> {
+    let mut client = pool.get().await.map_err(|e| e.to_string())?;
+    let transaction = client.transaction().await.map_err(|e| e.to_string())?;
+
+    let owner = authz::Authentication {
+        sub: run.owner_sub.clone(),
+        email: String::new(),
+        given_name: None,
+        family_name: None,
+    };
+    let rbac = authz::get_permissions(&transaction, &owner, run.team_id)
+        .await
+        .map_err(|_| "The owner is no longer in the team".to_string())?;
+    if !rbac.can_manage_automations() {
+        return Err("The owner can no longer run automations in this team".to_string());
+    }
+    queries::prompts::prompt()
+        .bind(&transaction, &run.prompt_id, &run.team_id)
+        .one()
+        .await
+        .map_err(|_| "The assistant is no longer available to the owner".to_string())?;
+
+    let prompt = render_prompt(&run.prompt_template, &run.event);
+    let conversation_id = queries::conversations::create_conversation()
+        .bind(&transaction, &run.team_id)
+        .one()
+        .await
+        .map_err(|e| e.to_string())?;
+    let chat_id = queries::chats::new_chat()
+        .bind(
+            &transaction,
+            &conversation_id,
+            &run.prompt_id,
+            &None::<String>,
+            &None::<String>,
+            &prompt,
+            &ChatRole::User,
+            &ChatStatus::Pending,
+        )
+        .one()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    transaction.commit().await.map_err(|e| e.to_string())?;
+    Ok((conversation_id, chat_id))
+}
+
+/// Fills `{{path}}` placeholders from the event, for example `{{document.file_name}}`.
+/// `{{event}}` is the whole event and `{{date}}` today's date in UTC. Placeholders
+/// with nothing to fill them are left empty.
+pub fn render_prompt(template: &str, event: &Value) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        let path = rest[start + 2..start + end].trim();
+        let value = match path {
+            "event" => Some(event),
+            "date" => {
+                rendered.push_str(&Utc::now().format("%Y-%m-%d").to_string());
+                None
+            }
+            path => path.split('.').try_fold(event, |value, key| value.get(key)),
+        };
+        match value {
+            Some(Value::String(text)) => rendered.push_str(text),
+            Some(Value::Null) | None => {}
+            Some(value) => rendered.push_str(&value.to_string()),
+        }
+        rest = &rest[start + end + 2..];
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+async fn notify(config: &Config, http: &reqwest::Client, run: &ClaimedRun, result: &RunResult) {
+    if run.notify_email.is_none() && run.notify_webhook_url.is_none() {
+        return;
+    }
+
+    let conversation_url = match (
+        result.conversation_id,
+        db::team_public_id::encode(run.team_id),
+    ) {
+        (Some(conversation_id), Some(team_id)) => Some(format!(
+            "{}{}",
+            config.base_url,
+            Conversation {
+                team_id,
+                conversation_id
+            }
+        )),
+        _ => None,
+    };
+    let (status, output, error) = match &result.outcome {
+        Ok(HeadlessOutcome::Completed(output)) => ("success", Some(output.as_str()), None),
+        Ok(HeadlessOutcome::AwaitingApproval) => ("awaiting_approval", None, None),
+        Err(error) => ("failed", None, Some(error.as_str())),
+    };
+
+    // The URL was checked when it was saved, but addresses that count as internal can
+    // change. Names are checked again by the client when it connects.
+    let notify_webhook_url =
+        run.notify_webhook_url
+            .as_ref()
+            .filter(|url| match outbound::check_url(url) {
+                Ok(()) => true,
+                Err(err) => {
+                    tracing::warn!("Not posting automation {} result: {err}", run.automation_id);
+                    false
+                }
+            });
+    if let Some(url) = notify_webhook_url {
+        let body = json!({
+            "automation_id": run.automation_id,
+            "automation": run.name,
+            "run_id": run.id,
+            "trigger": format!("{:?}", run.trigger_type),
+            "status": status,
+            "output": output,
+            "error": error,
+            "conversation_url": conversation_url,
+        });
+        let sent = http
+            .post(url)
+            .header("User-Agent", "Bionic-Automations")
+            .json(&body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(err) = sent {
+            tracing::warn!(
+                "Failed to POST automation {} result: {err}",
+                run.automation_id
+            );
+        }
+    }
+
+    if let (Some(to), Some(smtp_config)) = (&run.notify_email, &config.smtp_config) {
+        let mut body = match (output, error) {
+            (Some(output), _) => output.to_string(),
+            (None, Some(error)) => format!("The run failed: {error}"),
+            (None, None) => "The run is waiting for your approval of an integration call.".into(),
+        };
+        if let Some(url) = &conversation_url {
+            body.push_str(&format!("\n\nOpen the conversation: {url}"));
+        }
+        let email = to.parse().map_err(|e| format!("{e}")).and_then(|to| {
+            Message::builder()
+                .from(smtp_config.from_email.clone())
+                .to(to)
+                .subject(format!("{} ({status})", run.name))
+                .body(body)
+                .map_err(|e| e.to_string())
+        });
+        let smtp_config = smtp_config.clone();
+        let sent = match email {
+            Ok(email) => {
+                tokio::task::spawn_blocking(move || crate::email::deliver(&smtp_config, &email))
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|sent| sent)
+            }
+            Err(err) => Err(err),
+        };
+        if let Err(err) = sent {
+            tracing::warn!(
+                "Failed to email automation {} result: {err}",
+                run.automation_id
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_are_filled_from_the_event() {
+        let event = json!({
+            "document": { "id": 12, "file_name": "q3-report.pdf" },
+            "dataset": { "name": "Finance" },
+            "payload": { "ticket": "OPS-7", "tags": ["urgent"] }
+        });
+
+        assert_eq!(
+            render_prompt(
+                "Summarise {{ document.file_name }} ({{document.id}}) from {{dataset.name}}.",
+                &event
+            ),
+            "Summarise q3-report.pdf (12) from Finance."
+        );
+        assert_eq!(
+            render_prompt(
+                "{{payload.ticket}} {{payload.tags}} {{payload.missing}}!",
+                &event
+            ),
+            r#"OPS-7 ["urgent"] !"#
+        );
+        assert_eq!(
+            render_prompt("Unclosed {{ brace", &event),
+            "Unclosed {{ brace"
+        );
+        assert!(render_prompt("Digest for {{date}}", &event).starts_with("Digest for 20"));
+    }
+
+    #[test]
+    fn schedules_move_to_their_next_time() {
+        let after = DateTime::parse_from_rfc3339("2026-10-19T08:00:00Z")
+            .unwrap()
+            .to_utc();
+        assert_eq!(
+            next_run("0 8 * * *", after).map(|time| time.to_rfc3339()),
+            Some("2026-10-20T08:00:00+00:00".to_string())
+        );
+        assert_eq!(next_run("not cron", after), None);
+    }
+}
@@ -0,0 +1,182 @@
+//! Sends budget alerts.
+//!
+//! Every minute the budgets whose spending has crossed their warning or stop threshold
+//! this month are claimed, so each alert goes out once per month. Alerts are queued for
+//! the team's webhooks as `budget.warning` or `budget.exceeded`, and emailed to the
+//! budget's alert address if it has one. Chats are stopped in the chat path, not here.
+
+use std::time::Duration;
+
+use db::{queries, BudgetAlert, Pool};
+use lettre::Message;
+use serde_json::{json, Value};
+use web_pages::costs::micros_to_string;
+
+use crate::config::Config;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Starts sending budget alerts in the background.
+pub fn spawn(pool: Pool, config: Config) {
+    tokio::spawn(run(pool, config));
+}
+
+async fn run(pool: Pool, config: Config) {
+    loop {
+        if let Err(err) = alert_due(&pool, &config).await {
+            tracing::warn!("Failed to send budget alerts: {err}");
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Claims the alerts that are due, queues their webhooks and then emails them.
+async fn alert_due(pool: &Pool, config: &Config) -> Result<usize, String> {
+    let mut client = pool.get().await.map_err(|e| e.to_string())?;
+    let transaction = client.transaction().await.map_err(|e| e.to_string())?;
+
+    let alerts = queries::budgets::claim_budget_alerts()
+        .bind(&transaction)
+        .all()
+        .await
+        .map_err(|e| e.to_string())?;
+    for alert in &alerts {
+        queries::webhooks::enqueue_event()
+            .bind(
+                &transaction,
+                &alert.team_id,
+                &alert.event_type,
+                &payload(alert),
+            )
+            .one()
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    transaction.commit().await.map_err(|e| e.to_string())?;
+
+    for alert in &alerts {
+        email(config, alert).await;
+    }
+
+    Ok(alerts.len())
+}
+
+fn percent_spent(alert: &BudgetAlert) -> i64 {
+    alert.spent_micros * 100 / alert.monthly_limit_micros
+}
+
+fn payload(alert: &BudgetAlert) -> Value {
+    json!({
+        "budget_id": alert.id,
+        "month": alert.month,
+        "user": alert.email,
+        "limit": micros_to_string(alert.monthly_limit_micros),
+        "spent": micros_to_string(alert.spent_micros),
+        "percent_spent": percent_spent(alert),
+        "warn_percent": alert.warn_percent,
+        "stop_percent": alert.stop_percent,
+    })
+}
+
+/// The subject and body of the alert email.
+fn message(alert: &BudgetAlert) -> (String, String) {
+    let team = alert.team_name.as_deref().unwrap_or("Your team");
+    let who = match &alert.email {
+        Some(email) => format!("{email} in {team}"),
+        None => team.to_string(),
+    };
+    let spent = format!(
+        "{who} has spent {} of the {} budget for {} ({}%).",
+        micros_to_string(alert.spent_micros),
+        micros_to_string(alert.monthly_limit_micros),
+        alert.month,
+        percent_spent(alert)
+    );
+    if alert.event_type == "budget.exceeded" {
+        (
+            format!("Budget reached: {who}"),
+            format!(
+                "{spent}\n\nChats are refused until next month, or until the budget is raised."
+            ),
+        )
+    } else {
+        (
+            format!("Budget warning: {who}"),
+            format!(
+                "{spent}\n\nThis is over the {}% warning threshold.",
+                alert.warn_percent
+            ),
+        )
+    }
+}
+
+async fn email(config: &Config, alert: &BudgetAlert) {
+    let (Some(to), Some(smtp_config)) = (&alert.alert_email, &config.smtp_config) else {
+        return;
+    };
+    let (subject, body) = message(alert);
+    let email = to.parse().map_err(|e| format!("{e}")).and_then(|to| {
+        Message::builder()
+            .from(smtp_config.from_email.clone())
+            .to(to)
+            .subject(subject)
+            .body(body)
+            .map_err(|e| e.to_string())
+    });
+    let smtp_config = smtp_config.clone();
+    let sent = match email {
+        Ok(email) => {
+            tokio::task::spawn_blocking(move || crate::email::deliver(&smtp_config, &email))
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|sent| sent)
+        }
+        Err(err) => Err(err),
+    };
+    if let Err(err) = sent {
+        tracing::warn!("Failed to email budget {} alert: {err}", alert.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(event_type: &str, email: Option<&str>) -> BudgetAlert {
+        BudgetAlert {
+            id: 3,
+            team_id: 1,
+            team_name: Some("Research".to_string()),
+            email: email.map(str::to_string),
+            event_type: event_type.to_string(),
+            month: "2026-10".to_string(),
+            monthly_limit_micros: 200_000_000,
+            warn_percent: 80,
+            stop_percent: Some(100),
+            spent_micros: 170_500_000,
+            alert_email: None,
+        }
+    }
+
+    #[test]
+    fn payloads_give_amounts_as_decimals() {
+        let payload = payload(&alert("budget.warning", None));
+        assert_eq!(payload["limit"], "200.00");
+        assert_eq!(payload["spent"], "170.50");
+        assert_eq!(payload["percent_spent"], 85);
+        assert_eq!(payload["user"], Value::Null);
+    }
+
+    #[test]
+    fn emails_say_who_spent_and_what_happens_next() {
+        let (subject, body) = message(&alert("budget.warning", None));
+        assert_eq!(subject, "Budget warning: Research");
+        assert!(
+            body.starts_with("Research has spent 170.50 of the 200.00 budget for 2026-10 (85%).")
+        );
+
+        let (subject, body) = message(&alert("budget.exceeded", Some("ana@example.com")));
+        assert_eq!(subject, "Budget reached: ana@example.com in Research");
+        assert!(body.contains("Chats are refused until next month"));
+    }
+}
@@ -60,6 +60,10 @@ pub struct Config {
     pub base_url: String,
     // Enable projects feature
     pub enable_projects: bool,
+    // The bearer token the identity provider uses for SCIM, which is off without it
+    pub scim_token: Option<String>,
+    // Proxies whose X-Forwarded-For and X-Real-IP headers give the client's address
+    pub trusted_proxies: Vec<ipnet::IpNet>,
 }
 
 impl Default for Config {
@@ -101,6 +105,14 @@ impl Config {
 
         let app_database_url = env::var("APP_DATABASE_URL").expect("APP_DATABASE_URL not set");
 
+        let scim_token = env::var("SCIM_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty());
+
+        let trusted_proxies = env::var("TRUSTED_PROXIES")
+            .map(|proxies| parse_trusted_proxies(&proxies))
+            .unwrap_or_default();
+
         Config {
             max_upload_size_mb,
             port,
@@ -111,6 +123,8 @@ impl Config {
             enable_barricade,
             base_url,
             enable_projects,
+            scim_token,
+            trusted_proxies,
         }
     }
 
@@ -119,3 +133,18 @@ impl Config {
         format!("{}{}", self.base_url, OAuth2Callback {})
     }
 }
+
+/// A comma separated list of addresses and CIDR ranges, such as `10.0.0.0/8, 192.0.2.7`.
+fn parse_trusted_proxies(proxies: &str) -> Vec<ipnet::IpNet> {
+    proxies
+        .split(',')
+        .map(str::trim)
+        .filter(|proxy| !proxy.is_empty())
+        .map(|proxy| {
+            proxy
+                .parse::<ipnet::IpNet>()
+                .or_else(|_| proxy.parse::<std::net::IpAddr>().map(ipnet::IpNet::from))
+                .expect("TRUSTED_PROXIES must be IP addresses or CIDR ranges")
+        })
+        .collect()
+}
@@ -0,0 +1,218 @@
+//! Cron expressions for scheduled automations.
+//!
+//! Five fields, minute, hour, day of month, month and day of week, evaluated in UTC.
+//! Each field takes `*`, a number, a range `1-5`, a step `*/15` or `8-18/2`, or a comma
+//! separated list of those. Day of week runs from 0 (Sunday) to 6 and 7 is also Sunday.
+//! As in cron, when both day fields are restricted a day matching either one runs.
+//! `@hourly`, `@daily`, `@weekly` and `@monthly` are shorthands.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+
+// Far enough ahead for `0 0 29 2 *` to find a leap year.
+const SEARCH_DAYS: i64 = 8 * 366;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields.as_slice() else {
+            return Err(format!(
+                "A cron expression has 5 fields, this one has {}",
+                fields.len()
+            ));
+        };
+
+        let mut weekdays = parse_field(weekday, 0, 7, "day of week")?;
+        // 7 is another way to write Sunday.
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, "minute")?,
+            hours: parse_field(hour, 0, 23, "hour")?,
+            days: parse_field(day, 1, 31, "day of month")?,
+            months: parse_field(month, 1, 12, "month")?,
+            weekdays,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+}
+
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<u64, String> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("{step} isn't a valid step for the {name}"))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let number = |value: &str| -> Result<u32, String> {
+            value
+                .parse()
+                .ok()
+                .filter(|value| (min..=max).contains(value))
+                .ok_or_else(|| format!("The {name} must be between {min} and {max}, not {value}"))
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (number(start)?, number(end)?)
+        } else {
+            let start = number(range)?;
+            // `5/15` means from 5 to the end in steps of 15.
+            (start, if step > 1 { max } else { start })
+        };
+        if start > end {
+            return Err(format!("The {name} range {range} runs backwards"));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+impl Schedule {
+    /// The first time after `after` that the schedule fires, to the minute.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut time = Utc
+            .with_ymd_and_hms(
+                after.year(),
+                after.month(),
+                after.day(),
+                after.hour(),
+                after.minute(),
+                0,
+            )
+            .single()?
+            + Duration::minutes(1);
+        let give_up = time + Duration::days(SEARCH_DAYS);
+
+        while time < give_up {
+            if !self.matches_day(&time) {
+                time = Utc
+                    .with_ymd_and_hms(time.year(), time.month(), time.day(), 0, 0, 0)
+                    .single()?
+                    + Duration::days(1);
+            } else if self.hours & (1 << time.hour()) == 0 {
+                time = time.with_minute(0)? + Duration::hours(1);
+            } else if self.minutes & (1 << time.minute()) == 0 {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+
+    fn matches_day(&self, time: &DateTime<Utc>) -> bool {
+        if self.months & (1 << time.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << time.day()) != 0;
+        let weekday = self.weekdays & (1 << time.weekday().num_days_from_sunday()) != 0;
+        if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time).unwrap().to_utc()
+    }
+
+    fn next(expression: &str, after: &str) -> String {
+        expression
+            .parse::<Schedule>()
+            .unwrap()
+            .next_after(at(after))
+            .unwrap()
+            .to_rfc3339()
+    }
+
+    #[test]
+    fn finds_the_next_matching_minute() {
+        assert_eq!(
+            next("0 8 * * *", "2026-10-19T07:59:30Z"),
+            "2026-10-19T08:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 8 * * *", "2026-10-19T08:00:00Z"),
+            "2026-10-20T08:00:00+00:00"
+        );
+        assert_eq!(
+            next("*/15 9-17 * * 1-5", "2026-10-17T12:00:00Z"),
+            "2026-10-19T09:00:00+00:00"
+        );
+        assert_eq!(
+            next("@monthly", "2026-12-15T00:00:00Z"),
+            "2027-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 0 29 2 *", "2026-03-01T00:00:00Z"),
+            "2028-02-29T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // The 1st of the month or any Sunday; 2026-11-01 is a Sunday, then the 8th.
+        assert_eq!(
+            next("30 6 1 * 7", "2026-10-26T00:00:00Z"),
+            "2026-11-01T06:30:00+00:00"
+        );
+        assert_eq!(
+            next("30 6 1 * 7", "2026-11-01T07:00:00Z"),
+            "2026-11-08T06:30:00+00:00"
+        );
+    }
+
+    #[test]
+    fn invalid_expressions_are_rejected() {
+        assert!("* * * *".parse::<Schedule>().is_err());
+        assert!("60 * * * *".parse::<Schedule>().is_err());
+        assert!("*/0 * * * *".parse::<Schedule>().is_err());
+        assert!("0 18-9 * * *".parse::<Schedule>().is_err());
+        assert!("0 9 * * mon".parse::<Schedule>().is_err());
+        assert!("0 0 31 2 *"
+            .parse::<Schedule>()
+            .unwrap()
+            .next_after(Utc::now())
+            .is_none());
+    }
+}
@@ -1,27 +1,34 @@
 use lettre::transport::smtp::authentication::Credentials;
 use lettre::{Message, SmtpTransport, Transport};
 
+use super::config::SmtpConfig;
+
 pub fn send_email(config: &super::config::Config, email: Message) {
     if let Some(smtp_config) = &config.smtp_config {
-        let creds = Credentials::new(smtp_config.username.clone(), smtp_config.password.clone());
-
-        let sender = if smtp_config.tls_off {
-            SmtpTransport::builder_dangerous(smtp_config.host.clone())
-                .port(smtp_config.port)
-                .credentials(creds)
-                .build()
-        } else {
-            SmtpTransport::relay(&smtp_config.host)
-                .unwrap()
-                .port(smtp_config.port)
-                .credentials(creds)
-                .build()
-        };
-
         // Send the email
-        match sender.send(&email) {
+        match deliver(smtp_config, &email) {
             Ok(_) => println!("Email sent successfully!"),
             Err(e) => panic!("Could not send email: {:?}", e),
         }
     }
 }
+
+/// Sends an email, leaving the caller to decide what a failure means.
+pub fn deliver(smtp_config: &SmtpConfig, email: &Message) -> Result<(), String> {
+    let creds = Credentials::new(smtp_config.username.clone(), smtp_config.password.clone());
+
+    let sender = if smtp_config.tls_off {
+        SmtpTransport::builder_dangerous(smtp_config.host.clone())
+            .port(smtp_config.port)
+            .credentials(creds)
+            .build()
+    } else {
+        SmtpTransport::relay(&smtp_config.host)
+            .map_err(|e| e.to_string())?
+            .port(smtp_config.port)
+            .credentials(creds)
+            .build()
+    };
+
+    sender.send(email).map(|_| ()).map_err(|e| e.to_string())
+}
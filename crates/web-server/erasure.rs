@@ -0,0 +1,221 @@
+//! `web-server erase-user <email> [--yes]`
+//!
+//! Deletes a user and everything that is theirs alone: their conversations, files, API
+//! keys, private datasets, assistants, integrations and skills, and any team nobody
+//! else belongs to. What
+//! they shared with a team stays with the team and passes to its owner, and teams they
+//! owned pass to another member. Without `--yes` it only shows what would be deleted.
+
+use db::queries::user_data;
+use db::Transaction;
+use serde_json::json;
+
+#[derive(Debug, PartialEq)]
+struct EraseOptions {
+    email: String,
+    confirmed: bool,
+}
+
+/// Runs the erasure and returns the process exit code.
+pub async fn run(pool: &db::Pool, args: impl Iterator<Item = String>) -> i32 {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}\nusage: web-server erase-user <email> [--yes]");
+            return 2;
+        }
+    };
+
+    let mut client = match pool.get().await {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("Unable to connect to the database: {err}");
+            return 1;
+        }
+    };
+
+    let transaction = match client.transaction().await {
+        Ok(transaction) => transaction,
+        Err(err) => {
+            eprintln!("Unable to start a transaction: {err}");
+            return 1;
+        }
+    };
+
+    match erase(&transaction, &options).await {
+        Ok(false) => 1,
+        Ok(true) if !options.confirmed => {
+            println!("Nothing was deleted. Run again with --yes to erase this user.");
+            0
+        }
+        Ok(true) => match transaction.commit().await {
+            Ok(()) => {
+                println!("Erased {}", options.email);
+                0
+            }
+            Err(err) => {
+                eprintln!("Erasure failed: {err}");
+                1
+            }
+        },
+        Err(err) => {
+            eprintln!("Erasure failed: {err}");
+            1
+        }
+    }
+}
+
+/// Erases the user in the transaction, which is only committed once it is confirmed.
+/// Returns false when there is no such user.
+async fn erase(
+    transaction: &Transaction<'_>,
+    options: &EraseOptions,
+) -> Result<bool, db::TokioPostgresError> {
+    db::customer_keys::set_local_keys(transaction).await?;
+
+    let Some(user) = user_data::erasure_user()
+        .bind(transaction, &options.email.as_str())
+        .opt()
+        .await?
+    else {
+        eprintln!("No user has the email {}", options.email);
+        return Ok(false);
+    };
+
+    // The audit triggers record who made each change, and here it is the user.
+    transaction
+        .execute(
+            "SELECT set_config('row_level_security.user_id', $1, true)",
+            &[&user.id.to_string()],
+        )
+        .await?;
+
+    let counts = user_data::erasure_counts()
+        .bind(transaction, &user.id)
+        .one()
+        .await?;
+    println!(
+        "{} {} <{}> (id {})",
+        user.first_name.as_deref().unwrap_or(""),
+        user.last_name.as_deref().unwrap_or(""),
+        user.email,
+        user.id
+    );
+    println!("Conversations: {}", counts.conversations);
+    println!("Chats: {}", counts.chats);
+    println!("Files: {}", counts.objects);
+    println!("API keys: {}", counts.api_keys);
+    println!("Private datasets: {}", counts.private_datasets);
+    println!("Private assistants: {}", counts.private_assistants);
+    println!("Team memberships: {}", counts.teams);
+
+    if !options.confirmed {
+        return Ok(true);
+    }
+
+    let deleted_teams = user_data::erase_sole_member_teams()
+        .bind(transaction, &user.id)
+        .await?;
+    let transferred_teams = user_data::transfer_team_ownership()
+        .bind(transaction, &user.id)
+        .await?;
+    user_data::erase_private_datasets()
+        .bind(transaction, &user.id)
+        .await?;
+    user_data::erase_private_prompts()
+        .bind(transaction, &user.id)
+        .await?;
+    user_data::reassign_datasets()
+        .bind(transaction, &user.id)
+        .await?;
+    user_data::reassign_prompts()
+        .bind(transaction, &user.id)
+        .await?;
+    user_data::reassign_projects()
+        .bind(transaction, &user.id)
+        .await?;
+    user_data::reassign_integrations()
+        .bind(transaction, &user.id)
+        .await?;
+    // Before the files, so the files of what the team keeps stay with it.
+    user_data::reassign_skills()
+        .bind(transaction, &user.id)
+        .await?;
+    user_data::reassign_webhooks()
+        .bind(transaction, &user.id)
+        .await?;
+    user_data::reassign_objects()
+        .bind(transaction, &user.id)
+        .await?;
+    user_data::erase_invitations()
+        .bind(transaction, &user.email.as_str())
+        .await?;
+
+    let erased = json!({
+        "conversations": counts.conversations,
+        "chats": counts.chats,
+        "objects": counts.objects,
+        "api_keys": counts.api_keys,
+        "private_datasets": counts.private_datasets,
+        "private_assistants": counts.private_assistants,
+        "deleted_teams": deleted_teams,
+        "transferred_teams": transferred_teams,
+    });
+    user_data::record_erasure()
+        .bind(transaction, &user.id, &erased)
+        .await?;
+    user_data::erase_user().bind(transaction, &user.id).await?;
+
+    Ok(true)
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<EraseOptions, String> {
+    let mut email = None;
+    let mut confirmed = false;
+    for arg in args {
+        match arg.as_str() {
+            "--yes" => confirmed = true,
+            other if other.starts_with("--") => return Err(format!("Unknown argument: {other}")),
+            other if email.is_none() => email = Some(other.to_string()),
+            other => return Err(format!("Unexpected argument: {other}")),
+        }
+    }
+    Ok(EraseOptions {
+        email: email.ok_or("The user's email is required")?,
+        confirmed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> impl Iterator<Item = String> {
+        values
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn parses_erasure_arguments() {
+        assert_eq!(
+            parse_args(args(&["jo@example.com"])),
+            Ok(EraseOptions {
+                email: "jo@example.com".into(),
+                confirmed: false
+            })
+        );
+        assert_eq!(
+            parse_args(args(&["--yes", "jo@example.com"])),
+            Ok(EraseOptions {
+                email: "jo@example.com".into(),
+                confirmed: true
+            })
+        );
+        assert!(parse_args(args(&[])).is_err());
+        assert!(parse_args(args(&["a@example.com", "b@example.com"])).is_err());
+        assert!(parse_args(args(&["--force", "jo@example.com"])).is_err());
+    }
+}
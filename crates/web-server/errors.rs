@@ -94,6 +94,12 @@ impl From<MultipartError> for CustomError {
 
 impl From<db::TokioPostgresError> for CustomError {
     fn from(err: db::TokioPostgresError) -> CustomError {
+        if err
+            .as_db_error()
+            .is_some_and(|db_err| db_err.message() == db::authz::DEACTIVATED)
+        {
+            return CustomError::Authentication(db::authz::DEACTIVATED.to_string());
+        }
         log_db_error("database error", &err);
         CustomError::Database(err.to_string())
     }
@@ -90,6 +90,7 @@ pub async fn new_assistant_loader(
         max_completion_tokens: None,
         trim_ratio: 80,
         temperature: 0.7,
+        persistent_workspace: false,
         description: "".to_string(),
         disclaimer: "LLMs can make mistakes. Check important info.".to_string(),
         example1: None,
@@ -181,6 +182,7 @@ pub async fn edit_assistant_loader(
         max_completion_tokens: prompt.max_completion_tokens,
         trim_ratio: prompt.trim_ratio,
         temperature: prompt.temperature.unwrap_or(0.7),
+        persistent_workspace: prompt.persistent_workspace,
         description: prompt.description,
         disclaimer: prompt.disclaimer,
         example1: prompt.example1,
@@ -1,5 +1,11 @@
+use crate::audit::{to_csv, AuditEvent};
 use crate::{CustomError, Jwt};
-use axum::{extract::Extension, response::Html};
+use axum::{
+    body::Body,
+    extract::Extension,
+    http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    response::{Html, Response},
+};
 use db::authz;
 use db::queries;
 use db::Pool;
@@ -10,13 +16,18 @@ use axum_extra::routing::RouterExt;
 use serde::Deserialize;
 use web_pages::{
     audit_trail::{position_to_access_type, position_to_audit_action},
-    routes::audit_trail::Index,
+    routes::audit_trail::{Export, Index},
 };
 
 pub const PAGE_SIZE: i64 = 10;
+/// The most events one export returns; narrow the filter for more.
+pub const EXPORT_LIMIT: i64 = 10_000;
 
 pub fn routes() -> Router {
-    Router::new().typed_get(loader).typed_post(filter_action)
+    Router::new()
+        .typed_get(loader)
+        .typed_post(filter_action)
+        .typed_post(export_action)
 }
 
 pub async fn loader(
@@ -39,22 +50,55 @@ pub async fn loader(
         .all()
         .await?;
 
-    let audits = queries::audit_trail::audit()
-        .bind(&transaction, &None, &None, &None, &None, &(PAGE_SIZE + 1))
+    let object_types = queries::audit_trail::config_object_types()
+        .bind(&transaction)
         .all()
         .await?;
 
-    let html = web_pages::audit_trail::page::page(team_users, audits, team_id, rbac, true);
+    let audits = audit(&transaction, &Filter::default(), PAGE_SIZE + 1).await?;
+
+    let html =
+        web_pages::audit_trail::page::page(team_users, object_types, audits, team_id, rbac, true);
 
     Ok(Html(html))
 }
 
 #[derive(Deserialize, Default, Debug)]
+#[serde(default)]
 pub struct Filter {
     pub id: i32,
     pub user: i32,
     pub access_type: usize,
     pub action: usize,
+    pub object_type: String,
+    // YYYY-MM-DD
+    pub from_date: String,
+    pub to_date: String,
+}
+
+fn non_empty(value: &str) -> Option<&str> {
+    Some(value.trim()).filter(|value| !value.is_empty())
+}
+
+async fn audit(
+    transaction: &db::Transaction<'_>,
+    filter: &Filter,
+    limit: i64,
+) -> Result<Vec<db::AuditTrail>, CustomError> {
+    Ok(queries::audit_trail::audit()
+        .bind(
+            transaction,
+            &filter.get_id(),
+            &filter.convert_to_action(),
+            &filter.convert_to_access_type(),
+            &filter.get_user(),
+            &non_empty(&filter.object_type),
+            &non_empty(&filter.from_date),
+            &non_empty(&filter.to_date),
+            &limit,
+        )
+        .all()
+        .await?)
 }
 
 impl Filter {
@@ -110,19 +154,61 @@ pub async fn filter_action(
         .all()
         .await?;
 
-    let audits = queries::audit_trail::audit()
-        .bind(
-            &transaction,
-            &filter_form.get_id(),
-            &filter_form.convert_to_action(),
-            &filter_form.convert_to_access_type(),
-            &filter_form.get_user(),
-            &(PAGE_SIZE + 1),
-        )
+    let object_types = queries::audit_trail::config_object_types()
+        .bind(&transaction)
         .all()
         .await?;
 
-    let html = web_pages::audit_trail::page::page(team_users, audits, team_id, rbac, false);
+    let audits = audit(&transaction, &filter_form, PAGE_SIZE + 1).await?;
+
+    let html =
+        web_pages::audit_trail::page::page(team_users, object_types, audits, team_id, rbac, false);
 
     Ok(Html(html))
 }
+
+pub async fn export_action(
+    Export { team_id, format }: Export,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(filter_form): Form<Filter>,
+) -> Result<Response, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let (rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_view_audit_trail() {
+        return Err(CustomError::Authorization);
+    }
+
+    // Exports start from the newest event, not the page the filter was on.
+    let filter = Filter {
+        id: 0,
+        ..filter_form
+    };
+    let events: Vec<AuditEvent> = audit(&transaction, &filter, EXPORT_LIMIT)
+        .await?
+        .into_iter()
+        .map(AuditEvent::from)
+        .collect();
+
+    let (content_type, body) = match format.as_str() {
+        "csv" => ("text/csv; charset=utf-8", to_csv(&events)),
+        "json" => ("application/json", serde_json::to_string_pretty(&events)?),
+        _ => {
+            return Err(CustomError::FaultySetup(format!(
+                "Unknown export format {format}"
+            )))
+        }
+    };
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, content_type)
+        .header(
+            CONTENT_DISPOSITION,
+            format!("attachment; filename=\"audit-trail.{format}\""),
+        )
+        .body(Body::from(body))
+        .unwrap())
+}
@@ -0,0 +1,403 @@
+use axum::extract::Extension;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{Html, IntoResponse};
+use axum::routing::post;
+use axum::{Json, Router};
+use axum_extra::extract::Form;
+use axum_extra::routing::RouterExt;
+use chrono::Utc;
+use db::{authz, queries, AutomationTrigger, Pool, PromptType, Transaction};
+use rand::{distr::Alphanumeric, rng, RngExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use web_pages::automations::string_to_trigger;
+use web_pages::routes::automations::{Delete, Edit, Index, New, RunNow, View};
+
+use crate::config::Config;
+use crate::{CustomError, Jwt};
+
+/// How many runs the automation's page lists.
+const RECENT_RUNS: i64 = 50;
+
+pub fn routes() -> Router {
+    Router::new()
+        .typed_get(loader)
+        .typed_get(view_loader)
+        .typed_post(new_action)
+        .typed_post(edit_action)
+        .typed_post(delete_action)
+        .typed_post(run_now_action)
+        .route("/v1/automations/trigger", post(trigger))
+}
+
+pub async fn loader(
+    Index { team_id }: Index,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<Html<String>, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_automations() {
+        return Err(CustomError::Authorization);
+    }
+
+    let automations = queries::automations::automations()
+        .bind(&transaction, &team_id_num)
+        .all()
+        .await?;
+
+    let assistants = queries::prompts::prompts()
+        .bind(&transaction, &team_id_num, &PromptType::Assistant)
+        .all()
+        .await?;
+
+    let datasets = queries::datasets::datasets()
+        .bind(&transaction)
+        .all()
+        .await?;
+
+    let html = web_pages::automations::page::page(team_id, rbac, automations, assistants, datasets);
+
+    Ok(Html(html))
+}
+
+pub async fn view_loader(
+    View { team_id, id }: View,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Extension(config): Extension<Config>,
+) -> Result<Html<String>, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_automations() {
+        return Err(CustomError::Authorization);
+    }
+
+    let automation = queries::automations::automation()
+        .bind(&transaction, &id, &team_id_num)
+        .one()
+        .await?;
+
+    let runs = queries::automations::runs()
+        .bind(&transaction, &id, &team_id_num, &RECENT_RUNS)
+        .all()
+        .await?;
+
+    let html = web_pages::automations::view::page(team_id, rbac, config.base_url, automation, runs);
+
+    Ok(Html(html))
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct AutomationForm {
+    pub name: String,
+    pub prompt_id: i32,
+    pub prompt_template: String,
+    pub trigger_type: String,
+    #[serde(default)]
+    pub cron: String,
+    #[serde(default)]
+    pub dataset_id: String,
+    #[serde(default)]
+    pub notify_email: String,
+    #[serde(default)]
+    pub notify_webhook_url: String,
+    pub enabled: Option<String>,
+}
+
+/// The form once it has been checked, with the fields its trigger doesn't use cleared.
+struct ValidAutomation {
+    trigger_type: AutomationTrigger,
+    cron: Option<String>,
+    dataset_id: Option<i32>,
+    notify_email: Option<String>,
+    notify_webhook_url: Option<String>,
+    next_run_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+
+impl AutomationForm {
+    /// Checks the form against what the user can see, or says what is wrong with it.
+    async fn validate(
+        &self,
+        transaction: &Transaction<'_>,
+        team_id: i32,
+    ) -> Result<ValidAutomation, &'static str> {
+        if self.name.trim().is_empty() || self.prompt_template.trim().is_empty() {
+            return Err("Automations need a name and a prompt");
+        }
+
+        let assistants = queries::prompts::prompts()
+            .bind(transaction, &team_id, &PromptType::Assistant)
+            .all()
+            .await
+            .map_err(|_| "The assistants could not be loaded")?;
+        if !assistants.iter().any(|prompt| prompt.id == self.prompt_id) {
+            return Err("Choose one of your assistants");
+        }
+
+        let trigger_type =
+            string_to_trigger(&self.trigger_type).ok_or("Choose a trigger for the automation")?;
+
+        let notify_email =
+            Some(self.notify_email.trim().to_string()).filter(|email| !email.is_empty());
+        if notify_email
+            .as_ref()
+            .is_some_and(|email| !email.contains('@'))
+        {
+            return Err("The email address isn't valid");
+        }
+        let notify_webhook_url =
+            Some(self.notify_webhook_url.trim().to_string()).filter(|url| !url.is_empty());
+        if notify_webhook_url
+            .as_ref()
+            .is_some_and(|url| outbound::check_url(url).is_err())
+        {
+            return Err("Results can only be posted to a public http(s) URL");
+        }
+
+        let mut valid = ValidAutomation {
+            trigger_type,
+            cron: None,
+            dataset_id: None,
+            notify_email,
+            notify_webhook_url,
+            next_run_at: None,
+        };
+
+        match trigger_type {
+            AutomationTrigger::Schedule => {
+                let cron = self.cron.trim().to_string();
+                valid.next_run_at = crate::automations::next_run(&cron, Utc::now());
+                if valid.next_run_at.is_none() {
+                    return Err("The schedule isn't a cron expression that ever runs");
+                }
+                valid.cron = Some(cron);
+            }
+            AutomationTrigger::DocumentProcessed => {
+                let datasets = queries::datasets::datasets()
+                    .bind(transaction)
+                    .all()
+                    .await
+                    .map_err(|_| "The datasets could not be loaded")?;
+                let dataset_id = self
+                    .dataset_id
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|id| datasets.iter().any(|dataset| dataset.id == *id))
+                    .ok_or("Choose the dataset whose documents start the automation")?;
+                valid.dataset_id = Some(dataset_id);
+            }
+            AutomationTrigger::Webhook => {}
+        }
+
+        Ok(valid)
+    }
+}
+
+fn webhook_token() -> String {
+    let token: String = rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+    format!("auto_{token}")
+}
+
+pub async fn new_action(
+    New { team_id }: New,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(form): Form<AutomationForm>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_automations() {
+        return Err(CustomError::Authorization);
+    }
+
+    let index = Index { team_id }.to_string();
+
+    let valid = match form.validate(&transaction, team_id_num).await {
+        Ok(valid) => valid,
+        Err(message) => return crate::layout::redirect_and_snackbar(&index, message),
+    };
+
+    let webhook_token = (valid.trigger_type == AutomationTrigger::Webhook).then(webhook_token);
+
+    queries::automations::insert()
+        .bind(
+            &transaction,
+            &team_id_num,
+            &form.prompt_id,
+            &form.name.trim(),
+            &form.prompt_template.trim(),
+            &valid.trigger_type,
+            &valid.cron,
+            &valid.dataset_id,
+            &webhook_token,
+            &valid.notify_email,
+            &valid.notify_webhook_url,
+            &valid.next_run_at,
+        )
+        .one()
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&index, "Automation Created")
+}
+
+pub async fn edit_action(
+    Edit { team_id, id }: Edit,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(form): Form<AutomationForm>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_automations() {
+        return Err(CustomError::Authorization);
+    }
+
+    let index = Index { team_id }.to_string();
+
+    let valid = match form.validate(&transaction, team_id_num).await {
+        Ok(valid) => valid,
+        Err(message) => return crate::layout::redirect_and_snackbar(&index, message),
+    };
+
+    let automation = queries::automations::automation()
+        .bind(&transaction, &id, &team_id_num)
+        .one()
+        .await?;
+
+    // Whoever calls the automation keeps working after an edit.
+    let webhook_token = (valid.trigger_type == AutomationTrigger::Webhook)
+        .then(|| automation.webhook_token.unwrap_or_else(webhook_token));
+
+    queries::automations::update()
+        .bind(
+            &transaction,
+            &form.prompt_id,
+            &form.name.trim(),
+            &form.prompt_template.trim(),
+            &valid.trigger_type,
+            &valid.cron,
+            &valid.dataset_id,
+            &webhook_token,
+            &valid.notify_email,
+            &valid.notify_webhook_url,
+            &form.enabled.is_some(),
+            &valid.next_run_at,
+            &id,
+            &team_id_num,
+        )
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&index, "Automation Updated")
+}
+
+pub async fn delete_action(
+    Delete { team_id, id }: Delete,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_automations() {
+        return Err(CustomError::Authorization);
+    }
+
+    queries::automations::delete()
+        .bind(&transaction, &id, &team_id_num)
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&Index { team_id }.to_string(), "Automation Deleted")
+}
+
+pub async fn run_now_action(
+    RunNow { team_id, id }: RunNow,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_automations() {
+        return Err(CustomError::Authorization);
+    }
+
+    let event = json!({ "started_by": rbac.email });
+
+    queries::automations::enqueue_run()
+        .bind(&transaction, &event, &id, &team_id_num)
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&View { team_id, id }.to_string(), "Run Queued")
+}
+
+/// Queues a run of the webhook automation whose token is the bearer token. The JSON
+/// body is the event's `payload`.
+pub async fn trigger(
+    Extension(pool): Extension<Pool>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Result<impl IntoResponse, CustomError> {
+    let Some(token) = headers
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return Err(CustomError::Authentication(
+            "You need an automation token".to_string(),
+        ));
+    };
+
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let event = json!({ "payload": body });
+
+    let run_id = queries::automations::enqueue_webhook_run()
+        .bind(&transaction, &event, &token.trim())
+        .opt()
+        .await?;
+
+    transaction.commit().await?;
+
+    match run_id {
+        Some(run_id) => Ok((StatusCode::ACCEPTED, Json(json!({ "run_id": run_id })))),
+        None => Err(CustomError::Authentication(
+            "No enabled automation has this token".to_string(),
+        )),
+    }
+}
@@ -73,6 +73,11 @@ pub async fn conversation(
         .bind(&transaction, &prompt.model_id)
         .all()
         .await?;
+    let shares = queries::conversation_shares::shares()
+        .bind(&transaction, &conversation_id)
+        .all()
+        .await?;
+
     let html = console::conversation::page(
         team_id,
         rbac,
@@ -83,6 +88,7 @@ pub async fn conversation(
         conversation_id,
         is_tts_disabled,
         capabilities,
+        shares,
     );
 
     Ok(Html(html))
@@ -0,0 +1,87 @@
+use crate::{CustomError, Jwt};
+use axum::{
+    extract::{Extension, Form},
+    response::IntoResponse,
+};
+use db::queries::{chats, prompts, tool_call_approvals};
+use db::Pool;
+use db::{authz, PromptType};
+use serde::Deserialize;
+use validator::Validate;
+use web_pages::routes::console::DecideToolCall;
+
+#[derive(Deserialize, Validate, Default, Debug)]
+pub struct Decision {
+    pub approve: bool,
+}
+
+/// The user approved or rejected an integration call. Approved calls are sent now and
+/// either way the outcome is added to the tool result, after which the conversation
+/// page picks the model back up.
+pub async fn decide_tool_call(
+    DecideToolCall { team_id, id }: DecideToolCall,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(decision): Form<Decision>,
+) -> Result<impl IntoResponse, CustomError> {
+    let sub = current_user.sub.clone();
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (_permissions, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    let approval = tool_call_approvals::approval()
+        .bind(&transaction, &id)
+        .one()
+        .await?;
+
+    let prompt = match approval.chat_id {
+        Some(chat_id) => {
+            let chat = chats::chat().bind(&transaction, &chat_id).one().await?;
+            Some(
+                prompts::prompt()
+                    .bind(&transaction, &chat.prompt_id, &team_id_num)
+                    .one()
+                    .await?,
+            )
+        }
+        None => None,
+    };
+
+    transaction.commit().await?;
+
+    let url = match prompt {
+        Some(prompt) if prompt.prompt_type == PromptType::Assistant => {
+            web_pages::routes::prompts::Conversation {
+                team_id,
+                conversation_id: approval.conversation_id,
+                prompt_id: prompt.id,
+            }
+            .to_string()
+        }
+        _ => web_pages::routes::console::Conversation {
+            team_id,
+            conversation_id: approval.conversation_id,
+        }
+        .to_string(),
+    };
+
+    let message = match tool_runtime::builtin_tools::approvals::decide(
+        &pool,
+        &sub,
+        approval.id,
+        decision.approve,
+    )
+    .await
+    {
+        Ok(outcome) if outcome.get("error").is_some() => {
+            "The request was approved but failed".to_string()
+        }
+        Ok(_) if decision.approve => "Request approved and sent".to_string(),
+        Ok(_) => "Request rejected".to_string(),
+        Err(err) => err,
+    };
+
+    crate::layout::redirect_and_snackbar(&url, message)
+}
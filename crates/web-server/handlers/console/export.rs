@@ -0,0 +1,177 @@
+use crate::{CustomError, Jwt};
+use axum::body::Body;
+use axum::extract::Extension;
+use axum::http::header::{
+    CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_SECURITY_POLICY, CONTENT_TYPE,
+    X_CONTENT_TYPE_OPTIONS,
+};
+use axum::response::{IntoResponse, Response};
+use db::queries::{chats::Chat, chats_chunks};
+use db::{authz, queries, GeneratedOutput, Pool, Transaction};
+use web_pages::console::transcript::{self, Output, Transcript};
+use web_pages::console::ChatWithChunks;
+use web_pages::routes::console::Export;
+
+/// Transcripts only need their inline styles and embedded images.
+pub const TRANSCRIPT_CSP: &str = "default-src 'none'; img-src data:; style-src 'unsafe-inline'";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Markdown,
+    Json,
+    Html,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Some(Self::Markdown),
+            "json" => Some(Self::Json),
+            "html" => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Json => "json",
+            Self::Html => "html",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::Json => "application/json",
+            Self::Html => "text/html; charset=utf-8",
+        }
+    }
+}
+
+/// Adds the document chunks each chat cited.
+pub async fn with_chunks(
+    transaction: &Transaction<'_>,
+    chats: Vec<Chat>,
+) -> Result<Vec<ChatWithChunks>, CustomError> {
+    let mut chats_with_chunks = Vec::with_capacity(chats.len());
+    for chat in chats {
+        let chunks = chats_chunks::chunks_chats()
+            .bind(transaction, &chat.id)
+            .all()
+            .await?;
+        chats_with_chunks.push(ChatWithChunks { chat, chunks });
+    }
+    Ok(chats_with_chunks)
+}
+
+/// The generated files, with their contents when they are to be embedded.
+pub async fn outputs(
+    storage_config: &object_storage::StorageConfig,
+    generated_outputs: Vec<GeneratedOutput>,
+    with_data: bool,
+) -> Result<Vec<Output>, CustomError> {
+    let mut outputs = Vec::with_capacity(generated_outputs.len());
+    for generated in generated_outputs {
+        let data = if with_data {
+            object_storage::get(storage_config, generated.object_id)
+                .await?
+                .object_data
+        } else {
+            None
+        };
+        outputs.push(Output {
+            path: generated.path,
+            mime_type: generated.mime_type,
+            file_size: generated.file_size,
+            data,
+        });
+    }
+    Ok(outputs)
+}
+
+pub async fn export(
+    Export {
+        team_id,
+        conversation_id,
+        format,
+    }: Export,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Extension(storage_config): Extension<object_storage::StorageConfig>,
+) -> Result<impl IntoResponse, CustomError> {
+    let Some(format) = Format::from_name(&format) else {
+        return Err(CustomError::FaultySetup(format!(
+            "Unknown export format {format}"
+        )));
+    };
+
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_view_chats() {
+        return Err(CustomError::Authorization);
+    }
+
+    // Only finds the user's own conversations.
+    let conversation = queries::user_data::export_conversation()
+        .bind(&transaction, &conversation_id)
+        .one()
+        .await?;
+    let chats = queries::chats::chats()
+        .bind(&transaction, &conversation_id)
+        .all()
+        .await?;
+    // Generated outputs keep the conversation id as an INT.
+    let outputs_conversation_id = i32::try_from(conversation_id)
+        .map_err(|_| CustomError::FaultySetup("Conversation id out of range".to_string()))?;
+    let generated_outputs = queries::generated_outputs::list_by_conversation()
+        .bind(&transaction, &outputs_conversation_id)
+        .all()
+        .await?;
+
+    let transcript = Transcript::new(
+        Some(conversation.team_name),
+        conversation.project_name,
+        Some(conversation.created_at.to_rfc3339()),
+        with_chunks(&transaction, chats).await?,
+        outputs(&storage_config, generated_outputs, format == Format::Html).await?,
+    );
+
+    let body = match format {
+        Format::Markdown => transcript::to_markdown(&transcript),
+        Format::Json => serde_json::to_string_pretty(&transcript)?,
+        Format::Html => transcript::page(transcript),
+    };
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, format.content_type())
+        .header(
+            CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"conversation-{conversation_id}.{}\"",
+                format.extension()
+            ),
+        )
+        .header(CONTENT_SECURITY_POLICY, TRANSCRIPT_CSP)
+        .header(X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .header(CACHE_CONTROL, "no-store")
+        .body(Body::from(body))
+        .unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Format;
+
+    #[test]
+    fn formats_are_named_by_extension() {
+        assert_eq!(Format::from_name("MD"), Some(Format::Markdown));
+        assert_eq!(Format::from_name("json"), Some(Format::Json));
+        assert_eq!(Format::from_name("html"), Some(Format::Html));
+        assert_eq!(Format::from_name("pdf"), None);
+    }
+}
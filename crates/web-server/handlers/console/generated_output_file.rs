@@ -0,0 +1,70 @@
+use crate::{CustomError, Jwt};
+use axum::body::Body;
+use axum::extract::Extension;
+use axum::http::header::{
+    CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_SECURITY_POLICY, CONTENT_TYPE,
+    X_CONTENT_TYPE_OPTIONS,
+};
+use axum::response::{IntoResponse, Response};
+use db::{authz, queries, Pool};
+use web_pages::routes::console::GeneratedOutputFile;
+
+const FILE_CSP: &str = "default-src 'none'; sandbox";
+
+pub async fn generated_output_file(
+    GeneratedOutputFile { team_id, id }: GeneratedOutputFile,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (_rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    let output = queries::generated_outputs::get_content()
+        .bind(&transaction, &id)
+        .one()
+        .await?;
+
+    let (content_type, disposition) = if is_inline_mime_type(&output.mime_type) {
+        (output.mime_type.clone(), "inline")
+    } else {
+        ("application/octet-stream".to_string(), "attachment")
+    };
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, content_type)
+        .header(
+            CONTENT_DISPOSITION,
+            format!(
+                "{disposition}; filename=\"{}\"",
+                output.file_name.replace(['"', '\\'], "_")
+            ),
+        )
+        .header(CONTENT_SECURITY_POLICY, FILE_CSP)
+        .header(X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .header(CACHE_CONTROL, "private, max-age=3600")
+        .body(Body::from(output.object_data))
+        .unwrap())
+}
+
+fn is_inline_mime_type(mime_type: &str) -> bool {
+    matches!(
+        mime_type,
+        "image/png" | "image/jpeg" | "image/gif" | "image/webp"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_raster_images_are_served_inline() {
+        assert!(is_inline_mime_type("image/png"));
+        assert!(is_inline_mime_type("image/webp"));
+        assert!(!is_inline_mime_type("image/svg+xml"));
+        assert!(!is_inline_mime_type("text/html"));
+    }
+}
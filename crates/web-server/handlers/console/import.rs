@@ -0,0 +1,188 @@
+use crate::{CustomError, Jwt};
+use agent_runtime::user_config::UserConfig;
+use axum::extract::{Extension, Multipart};
+use axum::response::IntoResponse;
+use db::{authz, queries, ChatRole, ChatStatus, Pool, PromptType};
+use tool_runtime::serialize_assistant_tool_state;
+use web_pages::console::transcript::{Transcript, FORMAT, VERSION};
+use web_pages::routes::console::{Conversation, Import};
+
+#[derive(Debug, PartialEq)]
+struct ImportedChat {
+    role: ChatRole,
+    content: String,
+    tool_call_id: Option<String>,
+    tool_calls: Option<String>,
+}
+
+/// Reads a JSON export back into the chats of a new conversation.
+fn imported_chats(json: &str) -> Result<Vec<ImportedChat>, &'static str> {
+    let transcript: Transcript =
+        serde_json::from_str(json).map_err(|_| "The file isn't a conversation exported as JSON")?;
+    if transcript.format != FORMAT {
+        return Err("The file isn't a conversation exported as JSON");
+    }
+    if transcript.version > VERSION {
+        return Err("The file was exported by a newer version of Bionic");
+    }
+    if transcript.messages.is_empty() {
+        return Err("The conversation has no messages");
+    }
+
+    transcript
+        .messages
+        .into_iter()
+        .map(|message| {
+            let role = match message.role.as_str() {
+                "User" => ChatRole::User,
+                "Assistant" => ChatRole::Assistant,
+                "Tool" => ChatRole::Tool,
+                _ => return Err("Messages must be from the User, the Assistant or a Tool"),
+            };
+            Ok(ImportedChat {
+                role,
+                content: message.content.unwrap_or_default(),
+                tool_call_id: message.tool_call_id,
+                tool_calls: serialize_assistant_tool_state(
+                    Some(&message.tool_calls),
+                    Some(&message.reasoning),
+                ),
+            })
+        })
+        .collect()
+}
+
+pub async fn import(
+    Import { team_id }: Import,
+    current_user: Jwt,
+    user_config: UserConfig,
+    Extension(pool): Extension<Pool>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_view_chats() {
+        return Err(CustomError::Authorization);
+    }
+
+    let mut file = None;
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() == Some("file") {
+            file = Some(field.text().await?);
+        }
+    }
+
+    let history = web_pages::routes::history::Index {
+        team_id: team_id.clone(),
+    }
+    .to_string();
+    let Some(json) = file else {
+        return crate::layout::redirect_and_snackbar(&history, "No file was uploaded");
+    };
+    let chats = match imported_chats(&json) {
+        Ok(chats) => chats,
+        Err(message) => return crate::layout::redirect_and_snackbar(&history, message),
+    };
+
+    // Imported chats are answered by the user's default assistant from now on.
+    let prompts = queries::prompts::prompts()
+        .bind(&transaction, &team_id_num, &PromptType::Model)
+        .all()
+        .await?;
+    let prompt_id = user_config
+        .default_prompt
+        .filter(|id| prompts.iter().any(|prompt| prompt.id == *id))
+        .or_else(|| prompts.first().map(|prompt| prompt.id))
+        .ok_or_else(|| CustomError::FaultySetup("No model prompts configured".to_string()))?;
+
+    let conversation_id = queries::conversations::create_conversation()
+        .bind(&transaction, &team_id_num)
+        .one()
+        .await?;
+
+    for chat in &chats {
+        queries::chats::new_chat()
+            .bind(
+                &transaction,
+                &conversation_id,
+                &prompt_id,
+                &chat.tool_call_id,
+                &chat.tool_calls,
+                &chat.content,
+                &chat.role,
+                &ChatStatus::Success,
+            )
+            .one()
+            .await?;
+    }
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(
+        &Conversation {
+            team_id,
+            conversation_id,
+        }
+        .to_string(),
+        "Conversation Imported",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn imports_keep_tool_calls_and_reasoning() {
+        let json = json!({
+            "format": "bionic-conversation",
+            "version": 1,
+            "messages": [
+                { "role": "User", "content": "Weather?" },
+                {
+                    "role": "Assistant",
+                    "content": "",
+                    "reasoning": [{ "id": null, "content": [{ "type": "text", "content": { "text": "Look it up." } }] }],
+                    "tool_calls": [{
+                        "id": "call_1",
+                        "call_id": null,
+                        "function": { "name": "weather", "arguments": { "city": "Oslo" } }
+                    }]
+                },
+                { "role": "Tool", "tool_call_id": "call_1", "content": "Rain" }
+            ]
+        })
+        .to_string();
+
+        let chats = imported_chats(&json).unwrap();
+        assert_eq!(chats.len(), 3);
+        assert_eq!(chats[0].role, ChatRole::User);
+        assert_eq!(chats[0].tool_calls, None);
+        let state = chats[1].tool_calls.as_deref();
+        assert_eq!(
+            tool_runtime::parse_tool_calls(state)[0].function.name,
+            "weather"
+        );
+        assert_eq!(tool_runtime::parse_reasoning(state).len(), 1);
+        assert_eq!(chats[2].tool_call_id.as_deref(), Some("call_1"));
+    }
+
+    #[test]
+    fn only_conversation_exports_are_imported() {
+        assert!(imported_chats("{}").is_err());
+        assert!(imported_chats(r#"{"format": "other", "version": 1, "messages": []}"#).is_err());
+        assert!(imported_chats(
+            r#"{"format": "bionic-conversation", "version": 1, "messages": [{"role": "System", "content": "x"}]}"#
+        )
+        .is_err());
+        assert!(imported_chats(
+            r#"{"format": "bionic-conversation", "version": 2, "messages": [{"role": "User"}]}"#
+        )
+        .is_err());
+    }
+}
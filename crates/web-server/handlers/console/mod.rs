@@ -1,9 +1,14 @@
 mod conversation;
+mod decide_tool_call;
 mod delete;
+mod export;
 mod generated_output_canvas;
+mod generated_output_file;
+mod import;
 mod index;
 mod send_message;
 mod set_default_prompt;
+mod share;
 mod update_response;
 mod utils;
 
@@ -15,10 +20,18 @@ pub fn routes() -> Router {
     Router::new()
         .typed_get(conversation::conversation)
         .typed_get(generated_output_canvas::generated_output_canvas)
+        .typed_get(generated_output_file::generated_output_file)
         .typed_get(index::index)
+        .typed_get(export::export)
+        .typed_get(share::team_share)
+        .typed_get(share::public_share)
         .typed_post(send_message::send_message)
         .typed_post(update_response::update_response)
+        .typed_post(decide_tool_call::decide_tool_call)
         .typed_post(delete::delete)
         .typed_post(set_default_prompt::set_default_prompt)
+        .typed_post(import::import)
+        .typed_post(share::create_share)
+        .typed_post(share::delete_share)
         .layer(DefaultBodyLimit::max(50000000)) // 50MB limit for file uploads
 }
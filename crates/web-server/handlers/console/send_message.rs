@@ -98,6 +98,10 @@ pub async fn send_message(
 
     // Validate the message
     if message.validate().is_ok() {
+        // Audio is transcribed before the transaction is opened, so a slow model doesn't
+        // keep it open.
+        let transcripts = transcribe_attachments(&pool, &current_user, &files_info).await?;
+
         let mut client = pool.get().await?;
         let transaction = client.transaction().await?;
 
@@ -136,6 +140,7 @@ pub async fn send_message(
             team_id_num,
             rbac.user_id,
             &files_info,
+            &transcripts,
         )
         .await?;
 
@@ -179,6 +184,9 @@ pub async fn send_message(
 /// * `team_id` - The ID of the team the attachments belong to.
 /// * `user_id` - The ID of the user uploading the attachments.
 /// * `files_info` - A vector of tuples containing file information (name, content_type, data, size).
+/// * `transcripts` - The transcript of each file that is audio, from `transcribe_attachments`.
+///
+/// Transcripts are stored as an extra `.txt` attachment so the text is readable by tools.
 ///
 /// # Returns
 ///
@@ -190,8 +198,11 @@ async fn handle_attachments(
     team_id: i32,
     user_id: i32,
     files_info: &[(String, String, Vec<u8>, usize)],
+    transcripts: &[Option<String>],
 ) -> Result<(), CustomError> {
-    for (file_name, _content_type, file_data, _size) in files_info {
+    for ((file_name, _content_type, file_data, _size), transcript) in
+        files_info.iter().zip(transcripts)
+    {
         // Upload the file to object storage
         match object_storage::upload(storage_config, user_id, team_id, file_name, file_data).await {
             Ok(object_id) => {
@@ -210,6 +221,19 @@ async fn handle_attachments(
                     object_id,
                     chat_id
                 );
+
+                if let Some(transcript) = transcript {
+                    attach_transcript(
+                        transaction,
+                        storage_config,
+                        chat_id,
+                        team_id,
+                        user_id,
+                        file_name,
+                        transcript,
+                    )
+                    .await?;
+                }
             }
             Err(e) => {
                 tracing::error!("Failed to upload attachment: {}", e);
@@ -223,3 +247,82 @@ async fn handle_attachments(
 
     Ok(())
 }
+
+/// Transcribes the audio attachments with the speech to text model, when one is
+/// configured. A missing model or a failed transcription is logged and does not block the
+/// message.
+async fn transcribe_attachments(
+    pool: &Pool,
+    current_user: &Jwt,
+    files_info: &[(String, String, Vec<u8>, usize)],
+) -> Result<Vec<Option<String>>, CustomError> {
+    let mut transcripts = vec![None; files_info.len()];
+    let is_audio = |(file_name, content_type, ..): &(String, String, Vec<u8>, usize)| {
+        agent_runtime::transcribe::is_audio(file_name, content_type)
+    };
+    if !files_info.iter().any(is_audio) {
+        return Ok(transcripts);
+    }
+
+    let model = {
+        let mut client = pool.get().await?;
+        let transaction = client.transaction().await?;
+        authz::set_row_level_security_user_id(&transaction, current_user.sub.to_string()).await?;
+        let model = agent_runtime::transcribe::speech_to_text_model(&transaction).await?;
+        transaction.commit().await?;
+        model
+    };
+    let Some(model) = model else {
+        tracing::info!("No speech to text model configured, skipping audio attachments");
+        return Ok(transcripts);
+    };
+
+    for (file, transcript) in files_info.iter().zip(transcripts.iter_mut()) {
+        if !is_audio(file) {
+            continue;
+        }
+        let (file_name, content_type, file_data, _size) = file;
+        match speech_to_text::transcribe(&model, file_name, content_type, file_data.clone()).await {
+            Ok(text) => *transcript = Some(text),
+            Err(e) => tracing::error!("Failed to transcribe attachment {}: {}", file_name, e),
+        }
+    }
+
+    Ok(transcripts)
+}
+
+/// Stores the transcript of an audio attachment and links it to the same chat.
+async fn attach_transcript(
+    transaction: &db::Transaction<'_>,
+    storage_config: &object_storage::StorageConfig,
+    chat_id: &i32,
+    team_id: i32,
+    user_id: i32,
+    file_name: &str,
+    transcript: &str,
+) -> Result<(), CustomError> {
+    let transcript_name = format!("{file_name}.transcript.txt");
+    let object_id = object_storage::upload(
+        storage_config,
+        user_id,
+        team_id,
+        &transcript_name,
+        transcript.as_bytes(),
+    )
+    .await
+    .map_err(|e| CustomError::ExternalApi(format!("Failed to upload transcript: {}", e)))?;
+
+    attachments::insert()
+        .bind(transaction, chat_id, &object_id)
+        .await
+        .map_err(|e| CustomError::Database(e.to_string()))?;
+
+    tracing::info!(
+        "Transcript stored: file={}, object_id={}, chat_id={}",
+        transcript_name,
+        object_id,
+        chat_id
+    );
+
+    Ok(())
+}
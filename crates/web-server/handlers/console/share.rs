@@ -0,0 +1,249 @@
+use super::export::{outputs, with_chunks, TRANSCRIPT_CSP};
+use crate::{CustomError, Jwt};
+use axum::extract::Extension;
+use axum::http::header::{CACHE_CONTROL, CONTENT_SECURITY_POLICY, REFERRER_POLICY};
+use axum::response::{Html, IntoResponse};
+use axum_extra::extract::Form;
+use db::{authz, queries, Pool, ShareVisibility, SharedConversation, Transaction};
+use rand::{distr::Alphanumeric, rng, RngExt};
+use serde::Deserialize;
+use web_pages::console::transcript::{self, Transcript};
+use web_pages::routes::console::{Conversation, CreateShare, DeleteShare, PublicShare, TeamShare};
+
+#[derive(Deserialize, Default, Debug)]
+pub struct ShareForm {
+    pub visibility: String,
+    #[serde(default)]
+    pub expires_in_days: String,
+}
+
+fn share_token() -> String {
+    let token: String = rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+    format!("share_{token}")
+}
+
+fn parse_share(form: &ShareForm) -> Result<(ShareVisibility, Option<i32>), &'static str> {
+    let visibility = match form.visibility.as_str() {
+        "Team" => ShareVisibility::Team,
+        "Public" => ShareVisibility::Public,
+        _ => return Err("Choose who can open the link"),
+    };
+    let expires_in_days = match form.expires_in_days.trim() {
+        "" => None,
+        days => Some(
+            days.parse::<i32>()
+                .ok()
+                .filter(|days| (1..=3650).contains(days))
+                .ok_or("Links expire after 1 to 3650 days, or never")?,
+        ),
+    };
+    Ok((visibility, expires_in_days))
+}
+
+pub async fn create_share(
+    CreateShare {
+        team_id,
+        conversation_id,
+    }: CreateShare,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(form): Form<ShareForm>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_view_chats() {
+        return Err(CustomError::Authorization);
+    }
+
+    let conversation = Conversation {
+        team_id,
+        conversation_id,
+    }
+    .to_string();
+
+    let (visibility, expires_in_days) = match parse_share(&form) {
+        Ok(share) => share,
+        Err(message) => return crate::layout::redirect_and_snackbar(&conversation, message),
+    };
+
+    // Nothing is inserted unless the conversation is the user's.
+    let created = queries::conversation_shares::insert_share()
+        .bind(
+            &transaction,
+            &share_token(),
+            &visibility,
+            &expires_in_days,
+            &conversation_id,
+        )
+        .await?;
+
+    if created == 0 {
+        return Err(CustomError::Authorization);
+    }
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&conversation, "Share Link Created")
+}
+
+pub async fn delete_share(
+    DeleteShare {
+        team_id,
+        conversation_id,
+        id,
+    }: DeleteShare,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (_rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    queries::conversation_shares::delete_share()
+        .bind(&transaction, &id, &conversation_id)
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(
+        &Conversation {
+            team_id,
+            conversation_id,
+        }
+        .to_string(),
+        "Share Link Revoked",
+    )
+}
+
+async fn shared_conversation(
+    transaction: &Transaction<'_>,
+    token: &str,
+) -> Result<SharedConversation, CustomError> {
+    queries::conversation_shares::shared_conversation()
+        .bind(transaction, &token)
+        .opt()
+        .await?
+        .ok_or_else(|| {
+            CustomError::FaultySetup(
+                "This link doesn't exist, has expired or was revoked".to_string(),
+            )
+        })
+}
+
+/// The read-only page for a share link. Links can reach other sites, so the token isn't
+/// sent on as a referrer.
+async fn transcript_page(
+    transaction: &Transaction<'_>,
+    storage_config: &object_storage::StorageConfig,
+    shared: SharedConversation,
+    token: &str,
+) -> Result<impl IntoResponse, CustomError> {
+    let chats = queries::chats::shared_chats()
+        .bind(transaction, &token)
+        .all()
+        .await?;
+    let generated_outputs = queries::generated_outputs::shared_outputs()
+        .bind(transaction, &token)
+        .all()
+        .await?;
+
+    let transcript = Transcript::new(
+        shared.team_name,
+        None,
+        Some(shared.created_at.to_rfc3339()),
+        with_chunks(transaction, chats).await?,
+        outputs(storage_config, generated_outputs, true).await?,
+    );
+
+    Ok((
+        [
+            (CONTENT_SECURITY_POLICY, TRANSCRIPT_CSP),
+            (REFERRER_POLICY, "no-referrer"),
+            (CACHE_CONTROL, "private, no-store"),
+        ],
+        Html(transcript::page(transcript)),
+    ))
+}
+
+/// Team and public links both open here for members who can view the team's chats.
+pub async fn team_share(
+    TeamShare { team_id, token }: TeamShare,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Extension(storage_config): Extension<object_storage::StorageConfig>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    let shared = shared_conversation(&transaction, &token).await?;
+
+    if !rbac.can_view_chats() || shared.team_id != team_id_num {
+        return Err(CustomError::Authorization);
+    }
+
+    transcript_page(&transaction, &storage_config, shared, &token).await
+}
+
+/// Public links work without signing in, so there is no user to set.
+pub async fn public_share(
+    PublicShare { token }: PublicShare,
+    Extension(pool): Extension<Pool>,
+    Extension(storage_config): Extension<object_storage::StorageConfig>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    db::customer_keys::set_local_keys(&transaction).await?;
+
+    let shared = shared_conversation(&transaction, &token).await?;
+
+    if shared.visibility != ShareVisibility::Public {
+        return Err(CustomError::Authorization);
+    }
+
+    transcript_page(&transaction, &storage_config, shared, &token).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_need_a_visibility_and_a_sensible_expiry() {
+        let form = |visibility: &str, days: &str| ShareForm {
+            visibility: visibility.to_string(),
+            expires_in_days: days.to_string(),
+        };
+
+        assert_eq!(
+            parse_share(&form("Public", "7")),
+            Ok((ShareVisibility::Public, Some(7)))
+        );
+        assert_eq!(
+            parse_share(&form("Team", " ")),
+            Ok((ShareVisibility::Team, None))
+        );
+        assert!(parse_share(&form("Everyone", "")).is_err());
+        assert!(parse_share(&form("Team", "0")).is_err());
+        assert!(parse_share(&form("Team", "soon")).is_err());
+    }
+
+    #[test]
+    fn tokens_are_long_and_random() {
+        let token = share_token();
+        assert_eq!(token.len(), "share_".len() + 32);
+        assert_ne!(token, share_token());
+    }
+}
@@ -19,8 +19,8 @@ pub struct Chat {
 /// it will submit a form that directs to here. The response has already
 /// been saved in the database so here we can redirect to the conversation.
 ///
-/// Embeddings - At this point we have the complete response so we can generate
-/// embeddings for the response that are used by the search feature.
+/// Embeddings - The turns are queued for embedding by a trigger once they succeed,
+/// and rag-engine embeds them for history search.
 pub async fn update_response(
     UpdateResponse { team_id }: UpdateResponse,
     current_user: Jwt,
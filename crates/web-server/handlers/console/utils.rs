@@ -1,5 +1,5 @@
 use crate::CustomError;
-use db::queries::{chats::Chat, chats_chunks};
+use db::queries::{chats::Chat, chats_chunks, tool_call_approvals};
 use db::{ChatRole, ChatStatus, Transaction};
 use tool_runtime::parse_tool_calls;
 use web_pages::console::{ChatWithChunks, PendingChat, PendingChatState};
@@ -72,6 +72,27 @@ pub async fn process_chats(
     // Determine pending state and get non-pending chats
     let (non_pending_chats, pending_chat_state) = determine_pending_chat_state(chats);
 
+    // Don't call the model while any integration call is waiting for the user.
+    let pending_chat_state = match pending_chat_state {
+        PendingChatState::PendingToolChats(tool_chats, last_chat_id) => {
+            let approvals = match tool_chats.first() {
+                Some(chat) => {
+                    tool_call_approvals::pending()
+                        .bind(transaction, &chat.conversation_id)
+                        .all()
+                        .await?
+                }
+                None => Vec::new(),
+            };
+            if approvals.is_empty() {
+                PendingChatState::PendingToolChats(tool_chats, last_chat_id)
+            } else {
+                PendingChatState::AwaitingApproval(tool_chats, approvals)
+            }
+        }
+        state => state,
+    };
+
     // Process non-pending chats for chat_history
     for chat in non_pending_chats.iter() {
         // Get all chunks for each chat
@@ -0,0 +1,313 @@
+use crate::audit::csv_field;
+use crate::layout::{empty_string_is_none, empty_string_is_none_i32};
+use crate::{CustomError, Jwt};
+use axum::body::Body;
+use axum::extract::{Extension, Query};
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::response::{Html, IntoResponse, Response};
+use axum::Router;
+use axum_extra::extract::Form;
+use axum_extra::routing::RouterExt;
+use chrono::NaiveDate;
+use db::{authz, queries, Pool, TeamCost};
+use serde::Deserialize;
+use web_pages::costs::{api_key_label, assistant_label, micros_to_string, string_to_micros};
+use web_pages::routes::costs::{DeleteBudget, Export, Index, UpsertBudget};
+
+pub fn routes() -> Router {
+    Router::new()
+        .typed_get(loader)
+        .typed_get(export_action)
+        .typed_post(upsert_budget_action)
+        .typed_post(delete_budget_action)
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct MonthQuery {
+    pub month: Option<String>,
+}
+
+/// Months are YYYY-MM, as a month input sends them.
+fn parse_month(month: &str) -> Option<String> {
+    NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+        .ok()
+        .map(|date| date.format("%Y-%m").to_string())
+}
+
+fn current_month() -> String {
+    chrono::Utc::now().format("%Y-%m").to_string()
+}
+
+pub async fn loader(
+    Index { team_id }: Index,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Query(query): Query<MonthQuery>,
+) -> Result<Html<String>, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_budgets() {
+        return Err(CustomError::Authorization);
+    }
+
+    let month = query
+        .month
+        .as_deref()
+        .and_then(parse_month)
+        .unwrap_or_else(current_month);
+
+    let costs = queries::budgets::team_costs()
+        .bind(&transaction, &team_id_num, &month)
+        .all()
+        .await?;
+    let budgets = queries::budgets::budgets()
+        .bind(&transaction, &team_id_num)
+        .all()
+        .await?;
+    let members = queries::teams::get_users()
+        .bind(&transaction, &team_id_num)
+        .all()
+        .await?;
+
+    let html = web_pages::costs::page::page(team_id, rbac, month, costs, budgets, members);
+
+    Ok(Html(html))
+}
+
+const CSV_HEADER: &str = "month,user,assistant,api_key,model,prompt_tokens,completion_tokens,cost";
+
+/// One row for each user, assistant, API key and model combination, for chargeback.
+fn to_csv(month: &str, costs: &[TeamCost]) -> String {
+    let mut csv = format!("{CSV_HEADER}\n");
+    for cost in costs {
+        let fields = [
+            month.to_string(),
+            cost.email.clone().unwrap_or_default(),
+            assistant_label(cost),
+            api_key_label(cost),
+            cost.model.clone().unwrap_or_default(),
+            cost.prompt_tokens.to_string(),
+            cost.completion_tokens.to_string(),
+            micros_to_string(cost.cost_micros),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+pub async fn export_action(
+    Export { team_id, month }: Export,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<Response, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_budgets() {
+        return Err(CustomError::Authorization);
+    }
+
+    let Some(month) = parse_month(&month) else {
+        return Err(CustomError::FaultySetup(format!("Unknown month {month}")));
+    };
+
+    let costs = queries::budgets::team_costs()
+        .bind(&transaction, &team_id_num, &month)
+        .all()
+        .await?;
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "text/csv; charset=utf-8")
+        .header(
+            CONTENT_DISPOSITION,
+            format!("attachment; filename=\"costs-{month}.csv\""),
+        )
+        .body(Body::from(to_csv(&month, &costs)))
+        .unwrap())
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct BudgetForm {
+    #[serde(default, deserialize_with = "empty_string_is_none_i32")]
+    pub user_id: Option<i32>,
+    #[serde(default)]
+    pub monthly_limit: String,
+    #[serde(default)]
+    pub warn_percent: String,
+    #[serde(default)]
+    pub stop_percent: String,
+    #[serde(default, deserialize_with = "empty_string_is_none")]
+    pub alert_email: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Limits {
+    monthly_limit_micros: i64,
+    warn_percent: i32,
+    stop_percent: Option<i32>,
+}
+
+fn parse_limits(form: &BudgetForm) -> Result<Limits, &'static str> {
+    let monthly_limit_micros = string_to_micros(&form.monthly_limit)
+        .filter(|micros| *micros > 0)
+        .ok_or("The monthly limit must be an amount such as 500.00")?;
+    let warn_percent = form
+        .warn_percent
+        .trim()
+        .parse::<i32>()
+        .ok()
+        .filter(|percent| (1..=100).contains(percent))
+        .ok_or("Warn at must be a percentage from 1 to 100")?;
+    let stop_percent = match form.stop_percent.trim() {
+        "" => None,
+        percent => Some(
+            percent
+                .parse::<i32>()
+                .ok()
+                .filter(|percent| *percent >= warn_percent)
+                .ok_or("Stop at must be a percentage no lower than warn at, or blank")?,
+        ),
+    };
+    Ok(Limits {
+        monthly_limit_micros,
+        warn_percent,
+        stop_percent,
+    })
+}
+
+pub async fn upsert_budget_action(
+    UpsertBudget { team_id }: UpsertBudget,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(form): Form<BudgetForm>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_budgets() {
+        return Err(CustomError::Authorization);
+    }
+
+    let index = Index { team_id }.to_string();
+
+    let limits = match parse_limits(&form) {
+        Ok(limits) => limits,
+        Err(message) => return crate::layout::redirect_and_snackbar(&index, message),
+    };
+
+    let saved = queries::budgets::upsert_budget()
+        .bind(
+            &transaction,
+            &team_id_num,
+            &form.user_id,
+            &limits.monthly_limit_micros,
+            &limits.warn_percent,
+            &limits.stop_percent,
+            &form.alert_email,
+        )
+        .await?;
+
+    if saved == 0 {
+        return crate::layout::redirect_and_snackbar(&index, "Choose a member of this team");
+    }
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&index, "Budget Saved")
+}
+
+pub async fn delete_budget_action(
+    DeleteBudget { team_id, id }: DeleteBudget,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_budgets() {
+        return Err(CustomError::Authorization);
+    }
+
+    queries::budgets::delete_budget()
+        .bind(&transaction, &id, &team_id_num)
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&Index { team_id }.to_string(), "Budget Deleted")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn months_are_year_and_month() {
+        assert_eq!(parse_month("2026-10"), Some("2026-10".to_string()));
+        assert_eq!(parse_month("2026-1"), Some("2026-01".to_string()));
+        assert_eq!(parse_month("2026-13"), None);
+        assert_eq!(parse_month("2026-10'; --"), None);
+    }
+
+    #[test]
+    fn budgets_need_a_limit_and_ordered_thresholds() {
+        let form = |limit: &str, warn: &str, stop: &str| BudgetForm {
+            monthly_limit: limit.to_string(),
+            warn_percent: warn.to_string(),
+            stop_percent: stop.to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            parse_limits(&form("500", "80", "100")),
+            Ok(Limits {
+                monthly_limit_micros: 500_000_000,
+                warn_percent: 80,
+                stop_percent: Some(100),
+            })
+        );
+        assert_eq!(
+            parse_limits(&form("12.5", "50", "")).map(|limits| limits.stop_percent),
+            Ok(None)
+        );
+        assert!(parse_limits(&form("0", "80", "100")).is_err());
+        assert!(parse_limits(&form("500", "0", "100")).is_err());
+        assert!(parse_limits(&form("500", "90", "80")).is_err());
+    }
+
+    #[test]
+    fn csv_has_a_row_per_combination() {
+        let costs = vec![TeamCost {
+            email: Some("a@example.com".to_string()),
+            assistant: Some("Helper, v2".to_string()),
+            api_key_id: None,
+            api_key: None,
+            model: Some("gpt-4o".to_string()),
+            prompt_tokens: 1200,
+            completion_tokens: 300,
+            cost_micros: 4_500,
+        }];
+
+        assert_eq!(
+            to_csv("2026-10", &costs),
+            format!(
+                "{CSV_HEADER}\n2026-10,a@example.com,\"Helper, v2\",Console,gpt-4o,1200,300,0.0045\n"
+            )
+        );
+    }
+}
@@ -6,13 +6,12 @@ use axum::{
 };
 use db::authz;
 use db::queries;
-use db::types::ChunkingStrategy;
 use db::{Pool, Visibility};
 use serde::Deserialize;
 use validator::Validate;
 use web_pages::{
     routes::datasets::{Delete, Upsert},
-    string_to_visibility,
+    string_to_chunking_strategy, string_to_visibility,
 };
 
 // Delete function
@@ -23,9 +22,13 @@ pub async fn action_delete(
 ) -> Result<impl IntoResponse, CustomError> {
     let mut client = pool.get().await?;
     let transaction = client.transaction().await?;
-    let (_permissions, _team_id_num) =
+    let (permissions, _team_id_num) =
         authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
 
+    if !permissions.can_manage_datasets() {
+        return Err(CustomError::Authorization);
+    }
+
     queries::datasets::delete().bind(&transaction, &id).await?;
 
     transaction.commit().await?;
@@ -36,19 +39,24 @@ pub async fn action_delete(
     )
 }
 
+/// The highest processing priority a team member can give a dataset. Only system
+/// administrators can go above it, so one team can't jump the queue of every other.
+const MAX_TEAM_PRIORITY: i32 = 10;
+
 // Upsert function
 #[derive(Deserialize, Validate, Default, Debug)]
 pub struct NewDataset {
     pub id: Option<i32>,
     #[validate(length(min = 1, message = "The name is mandatory"))]
     pub name: String,
-    #[allow(dead_code)]
     pub chunking_strategy: String,
     pub combine_under_n_chars: i32,
     pub new_after_n_chars: i32,
     pub embeddings_model_id: i32,
     pub visibility: String,
     pub multipage_sections: bool,
+    #[serde(default)]
+    pub priority: i32,
 }
 
 pub async fn action_upsert(
@@ -63,7 +71,11 @@ pub async fn action_upsert(
     let (permissions, team_id_num) =
         authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
 
-    let chunking_strategy = ChunkingStrategy::ByTitle;
+    if !permissions.can_manage_datasets() {
+        return Err(CustomError::Authorization);
+    }
+
+    let chunking_strategy = string_to_chunking_strategy(&new_dataset.chunking_strategy);
 
     let mut visibility = string_to_visibility(&new_dataset.visibility);
 
@@ -74,6 +86,8 @@ pub async fn action_upsert(
         visibility = Visibility::Team;
     }
 
+    let priority = allowed_priority(new_dataset.priority, permissions.is_sys_admin);
+
     match (new_dataset.validate(), new_dataset.id) {
         (Ok(_), Some(id)) => {
             queries::datasets::update()
@@ -86,6 +100,7 @@ pub async fn action_upsert(
                     &new_dataset.combine_under_n_chars,
                     &new_dataset.new_after_n_chars,
                     &new_dataset.multipage_sections,
+                    &priority,
                     &id,
                 )
                 .await?;
@@ -108,6 +123,7 @@ pub async fn action_upsert(
                     &new_dataset.combine_under_n_chars,
                     &new_dataset.new_after_n_chars,
                     &new_dataset.multipage_sections,
+                    &priority,
                     &visibility,
                 )
                 .one()
@@ -130,3 +146,24 @@ pub async fn action_upsert(
         ),
     }
 }
+
+fn allowed_priority(priority: i32, is_sys_admin: bool) -> i32 {
+    if is_sys_admin {
+        priority
+    } else {
+        priority.clamp(0, MAX_TEAM_PRIORITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_system_admins_set_a_priority_outside_the_team_range() {
+        assert_eq!(allowed_priority(1_000, false), MAX_TEAM_PRIORITY);
+        assert_eq!(allowed_priority(-5, false), 0);
+        assert_eq!(allowed_priority(3, false), 3);
+        assert_eq!(allowed_priority(1_000, true), 1_000);
+    }
+}
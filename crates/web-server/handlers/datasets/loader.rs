@@ -18,6 +18,10 @@ pub async fn loader(
     let (rbac, _team_id_num) =
         authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
 
+    if !rbac.can_view_datasets() {
+        return Err(CustomError::Authorization);
+    }
+
     let datasets = datasets::datasets().bind(&transaction).all().await?;
 
     let models = models::models()
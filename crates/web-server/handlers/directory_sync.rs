@@ -0,0 +1,158 @@
+use axum::extract::Extension;
+use axum::response::{Html, IntoResponse};
+use axum::Router;
+use axum_extra::extract::Form;
+use axum_extra::routing::RouterExt;
+use db::{authz, queries, types, Pool};
+use serde::Deserialize;
+use web_pages::routes::directory_sync::{Index, Map};
+
+use crate::config::Config;
+use crate::handlers::team::create_invite::invite_roles;
+use crate::{CustomError, Jwt};
+
+pub fn routes() -> Router {
+    Router::new().typed_get(loader).typed_post(map_action)
+}
+
+pub async fn loader(
+    Index { team_id }: Index,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Extension(config): Extension<Config>,
+) -> Result<Html<String>, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.is_sys_admin {
+        return Err(CustomError::Authorization);
+    }
+
+    let groups = queries::scim::scim_groups()
+        .bind(&transaction, &None::<&str>, &0, &i64::MAX)
+        .all()
+        .await?;
+    let group_ids: Vec<i32> = groups.iter().map(|group| group.id).collect();
+    let members = queries::scim::scim_group_members()
+        .bind(&transaction, &group_ids)
+        .all()
+        .await?;
+    let teams = queries::scim::mappable_teams()
+        .bind(&transaction)
+        .all()
+        .await?;
+    let custom_roles = queries::scim::mappable_custom_roles()
+        .bind(&transaction)
+        .all()
+        .await?;
+
+    let endpoint = config
+        .scim_token
+        .as_ref()
+        .map(|_| format!("{}/scim/v2", config.base_url.trim_end_matches('/')));
+
+    let html = web_pages::directory_sync::page::page(
+        team_id,
+        rbac,
+        endpoint,
+        groups,
+        members,
+        teams,
+        custom_roles,
+    );
+
+    Ok(Html(html))
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct MapForm {
+    #[serde(default)]
+    pub mapping: String,
+}
+
+/// The team, built in roles and custom role a group gives its members.
+type Mapping = (i32, Vec<types::Role>, Option<i32>);
+
+/// None if the group isn't mapped. See `web_pages::directory_sync::page::mapping_value`.
+fn parse_mapping(mapping: &str) -> Result<Option<Mapping>, ()> {
+    if mapping.is_empty() {
+        return Ok(None);
+    }
+    let (team_id, role) = mapping.split_once(':').ok_or(())?;
+    let team_id = team_id.parse::<i32>().map_err(|_| ())?;
+    let (roles, custom_role_id) = invite_roles(role);
+    Ok(Some((team_id, roles, custom_role_id)))
+}
+
+pub async fn map_action(
+    Map { team_id, id }: Map,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(form): Form<MapForm>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.is_sys_admin {
+        return Err(CustomError::Authorization);
+    }
+
+    let index = Index { team_id }.to_string();
+
+    let Ok(mapping) = parse_mapping(&form.mapping) else {
+        return crate::layout::redirect_and_snackbar(&index, "Choose a team and role");
+    };
+    let (mapped_team_id, roles, custom_role_id) = match mapping {
+        Some((team_id, roles, custom_role_id)) => (Some(team_id), roles, custom_role_id),
+        None => (None, vec![types::Role::Collaborator], None),
+    };
+
+    queries::scim::map_scim_group()
+        .bind(&transaction, &mapped_team_id, &roles, &custom_role_id, &id)
+        .await?;
+
+    // Everyone in the group moves to the team it now grants.
+    let members: Vec<i32> = queries::scim::scim_group_members()
+        .bind(&transaction, &vec![id])
+        .all()
+        .await?
+        .iter()
+        .map(|member| member.user_id)
+        .collect();
+    crate::handlers::scim::sync_users(&transaction, &members).await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&index, "Group Mapping Saved")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mappings_name_a_team_and_role() {
+        assert_eq!(parse_mapping(""), Ok(None));
+        assert_eq!(
+            parse_mapping("12:TeamManager"),
+            Ok(Some((
+                12,
+                vec![types::Role::TeamManager, types::Role::Collaborator],
+                None
+            )))
+        );
+        assert_eq!(
+            parse_mapping("12:Collaborator"),
+            Ok(Some((12, vec![types::Role::Collaborator], None)))
+        );
+        assert_eq!(parse_mapping("12:5"), Ok(Some((12, vec![], Some(5)))));
+        assert_eq!(parse_mapping("Sales"), Err(()));
+        assert_eq!(parse_mapping("x:Collaborator"), Err(()));
+    }
+}
@@ -41,6 +41,10 @@ pub async fn loader(
     let (rbac, _team_id_num) =
         authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
 
+    if !rbac.can_view_datasets() {
+        return Err(CustomError::Authorization);
+    }
+
     let documents = documents::documents()
         .bind(&transaction, &dataset_id)
         .all()
@@ -96,9 +100,13 @@ pub async fn delete_action(
 ) -> Result<impl IntoResponse, CustomError> {
     let mut client = pool.get().await?;
     let transaction = client.transaction().await?;
-    let (_permissions, _team_id_num) =
+    let (permissions, _team_id_num) =
         authz::get_permisisons(&transaction, &current_user.into(), &delete_doc.team_id).await?;
 
+    if !permissions.can_manage_datasets() {
+        return Err(CustomError::Authorization);
+    }
+
     queries::documents::delete()
         .bind(&transaction, &delete_doc.document_id)
         .await?;
@@ -127,9 +135,13 @@ pub async fn row(
     let mut client = pool.get().await?;
     let transaction = client.transaction().await?;
 
-    let (_rbac, _team_id_num) =
+    let (rbac, _team_id_num) =
         authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
 
+    if !rbac.can_view_datasets() {
+        return Err(CustomError::Authorization);
+    }
+
     let document = documents::document()
         .bind(&transaction, &document_id)
         .one()
@@ -156,6 +168,10 @@ pub async fn upload_action(
     let (rbac, team_id_num) =
         authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
 
+    if !rbac.can_manage_datasets() {
+        return Err(CustomError::Authorization);
+    }
+
     while let Some(file) = files.next_field().await.unwrap() {
         let name = file.file_name().unwrap().to_string();
         let data = file.bytes().await.unwrap().to_vec();
@@ -0,0 +1,121 @@
+use crate::{CustomError, Jwt};
+use axum::response::Html;
+use axum::Router;
+use axum::{extract::Extension, response::IntoResponse};
+use axum_extra::extract::Form;
+use axum_extra::routing::RouterExt;
+use db::{authz, queries, Pool};
+use serde::Deserialize;
+use web_pages::guardrails::page::CATEGORIES;
+use web_pages::routes::guardrails::{Index, Update};
+use web_pages::{string_to_moderation_action, string_to_output_moderation};
+
+/// How many recent flags the page lists.
+const RECENT_FLAGS: i64 = 50;
+
+pub fn routes() -> Router {
+    Router::new().typed_get(loader).typed_post(update_action)
+}
+
+pub async fn loader(
+    Index { team_id }: Index,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<Html<String>, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_setup_models() {
+        return Err(CustomError::Authorization);
+    }
+
+    let output_moderation = queries::moderation_policies::output_moderation()
+        .bind(&transaction, &team_id_num)
+        .one()
+        .await?;
+
+    let policies = queries::moderation_policies::moderation_policies()
+        .bind(&transaction, &team_id_num)
+        .all()
+        .await?;
+
+    let flags = queries::prompt_flags::team_prompt_flags()
+        .bind(&transaction, &team_id_num, &RECENT_FLAGS)
+        .all()
+        .await?;
+
+    let has_guard_model = queries::models::guard_model()
+        .bind(&transaction)
+        .opt()
+        .await?
+        .is_some();
+
+    let html = web_pages::guardrails::page::page(
+        team_id,
+        rbac,
+        output_moderation,
+        policies,
+        flags,
+        has_guard_model,
+    );
+
+    Ok(Html(html))
+}
+
+/// Each category row posts a `flag_type` and an `action`, in the same order.
+#[derive(Deserialize, Default, Debug)]
+pub struct GuardrailsForm {
+    pub output_moderation: String,
+    #[serde(default)]
+    pub flag_type: Vec<String>,
+    #[serde(default)]
+    pub action: Vec<String>,
+}
+
+pub async fn update_action(
+    Update { team_id }: Update,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(form): Form<GuardrailsForm>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_setup_models() {
+        return Err(CustomError::Authorization);
+    }
+
+    queries::moderation_policies::set_output_moderation()
+        .bind(
+            &transaction,
+            &string_to_output_moderation(&form.output_moderation),
+            &team_id_num,
+        )
+        .await?;
+
+    for (code, action) in form.flag_type.iter().zip(form.action.iter()) {
+        let Some((flag_type, _, _)) = CATEGORIES.iter().find(|(_, c, _)| c == code) else {
+            continue;
+        };
+        queries::moderation_policies::upsert()
+            .bind(
+                &transaction,
+                &team_id_num,
+                flag_type,
+                &string_to_moderation_action(action),
+            )
+            .await?;
+    }
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(
+        &web_pages::routes::guardrails::Index { team_id }.to_string(),
+        "Guardrails Updated",
+    )
+}
@@ -24,6 +24,10 @@ pub async fn loader(
     let (rbac, _team_id_num) =
         authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
 
+    if !rbac.can_view_chat_history() {
+        return Err(CustomError::Authorization);
+    }
+
     let i18n = db::i18n::global();
     i18n.ensure_locale("en").await;
     if locale.as_str() != "en" {
@@ -41,6 +45,7 @@ pub async fn loader(
 }
 
 use axum::Form;
+use db::history_search::{search_history, QueryEmbedding};
 use serde::Deserialize;
 use validator::Validate;
 use web_pages::routes::history::Search;
@@ -61,24 +66,59 @@ pub async fn search_action(
     let mut client = pool.get().await?;
     let transaction = client.transaction().await?;
 
-    let (rbac, _team_id_num) =
+    let (rbac, team_id_num) =
         authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
 
+    if !rbac.can_view_chat_history() {
+        return Err(CustomError::Authorization);
+    }
+
     let i18n = db::i18n::global();
     i18n.ensure_locale("en").await;
     if locale.as_str() != "en" {
         i18n.ensure_locale(locale.as_str()).await;
     }
 
-    // Use SQL-based search instead of embeddings
-    let history = db::queries::history::search_history()
-        .bind(&transaction, &rbac.user_id, &search.search, &10)
-        .all()
-        .await?;
-
-    tracing::info!("Retrieved {} search results", history.len());
-
-    let html = history::results::page(rbac, team_id, history, locale.as_str());
+    // Without an embeddings model, or if it can't be reached, we match on keywords only.
+    let query = match db::queries::history::history_embeddings_model()
+        .bind(&transaction, &team_id_num)
+        .opt()
+        .await?
+    {
+        Some(model) => match embeddings::get_embeddings_via_rig(
+            &search.search,
+            &model.base_url,
+            &model.model,
+            model.context_size,
+            model.api_key.as_deref(),
+        )
+        .await
+        {
+            Ok(embeddings) => Some(QueryEmbedding {
+                model_id: model.id,
+                embeddings,
+            }),
+            Err(error) => {
+                tracing::warn!("Searching history by keyword only: {}", error);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let results = search_history(
+        &transaction,
+        rbac.user_id,
+        team_id_num,
+        &search.search,
+        query.as_ref(),
+        20,
+    )
+    .await?;
+
+    tracing::info!("Retrieved {} search results", results.len());
+
+    let html = history::results::page(rbac, team_id, results, search.search, locale.as_str());
 
     Ok(Html(html))
 }
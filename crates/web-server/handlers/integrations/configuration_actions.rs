@@ -2,11 +2,11 @@ use crate::{CustomError, Jwt};
 use axum::extract::Extension;
 use axum::response::IntoResponse;
 use axum::Form;
-use db::{authz, queries, Pool, Visibility};
+use db::{authz, queries, Pool, ToolCallPolicy, Visibility};
 use serde::Deserialize;
 use validator::Validate;
 use web_pages::routes::integrations::{
-    ConfigureApiKey, DeleteApiKeyConnection, DeleteOauth2Connection,
+    ConfigureApiKey, DeleteApiKeyConnection, DeleteOauth2Connection, SetToolCallPolicy,
 };
 
 #[derive(Deserialize, Validate, Debug)]
@@ -16,6 +16,84 @@ pub struct ApiKeyForm {
     pub visibility: String,
 }
 
+#[derive(Deserialize, Validate, Debug)]
+pub struct ToolCallPolicyForm {
+    #[validate(length(min = 1, message = "Operation is required"))]
+    pub operation_id: String,
+    pub policy: String,
+}
+
+pub async fn set_tool_call_policy_action(
+    SetToolCallPolicy {
+        team_id,
+        integration_id,
+    }: SetToolCallPolicy,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(policy_form): Form<ToolCallPolicyForm>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let (permissions, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !permissions.can_manage_integrations() {
+        return Err(CustomError::Authorization);
+    }
+
+    let view = web_pages::routes::integrations::View {
+        team_id,
+        id: integration_id,
+    }
+    .to_string();
+
+    if policy_form.validate().is_err() {
+        return Ok(crate::layout::redirect_and_snackbar(
+            &view,
+            "Invalid approval policy",
+        ));
+    }
+
+    // "Default" removes the override so the HTTP method decides.
+    let policy = match policy_form.policy.as_str() {
+        "Allow" => Some(ToolCallPolicy::Allow),
+        "Confirm" => Some(ToolCallPolicy::Confirm),
+        "Deny" => Some(ToolCallPolicy::Deny),
+        _ => None,
+    };
+
+    match policy {
+        Some(policy) => {
+            queries::tool_call_approvals::set_policy()
+                .bind(
+                    &transaction,
+                    &policy_form.operation_id,
+                    &policy,
+                    &integration_id,
+                    &team_id_num,
+                )
+                .await?;
+        }
+        None => {
+            queries::tool_call_approvals::clear_policy()
+                .bind(
+                    &transaction,
+                    &integration_id,
+                    &policy_form.operation_id,
+                    &team_id_num,
+                )
+                .await?;
+        }
+    }
+
+    transaction.commit().await?;
+
+    Ok(crate::layout::redirect_and_snackbar(
+        &view,
+        "Approval policy updated",
+    ))
+}
+
 pub async fn configure_api_key_action(
     ConfigureApiKey {
         team_id,
@@ -4,6 +4,7 @@ use axum::response::Html;
 use axum::response::IntoResponse;
 use db::{authz, queries, Pool};
 use tool_runtime::BionicOpenAPI;
+use web_pages::integrations::approval_policy::ActionMethod;
 use web_pages::integrations::integration_card::IntegrationSummary;
 use web_pages::integrations::select::PrebuiltSpec;
 use web_pages::integrations::upsert::IntegrationForm;
@@ -185,6 +186,23 @@ pub async fn view_loader(
         )
     };
 
+    let action_methods = openapi
+        .create_openapi_tools(None)
+        .unwrap_or_default()
+        .iter()
+        .map(|tool| ActionMethod {
+            operation_id: tool.operation_id().to_string(),
+            method: tool.http_method().unwrap_or_default(),
+        })
+        .collect();
+    let tool_call_policies = queries::tool_call_approvals::policies()
+        .bind(&transaction, &team_id_num)
+        .all()
+        .await?
+        .into_iter()
+        .filter(|row| row.integration_id == integration.id)
+        .collect();
+
     let i18n = db::i18n::global();
     i18n.ensure_locale("en").await;
     if locale.as_str() != "en" {
@@ -200,6 +218,8 @@ pub async fn view_loader(
         api_key_connections,
         oauth2_connections,
         oauth_client_configured,
+        action_methods,
+        tool_call_policies,
         locale.as_str(),
     );
 
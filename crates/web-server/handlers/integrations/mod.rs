@@ -11,7 +11,7 @@ pub mod loaders;
 pub use actions::{delete_action, edit_action, new_action};
 pub use configuration_actions::{
     configure_api_key_action, delete_api_key_connection_action, delete_oauth2_connection_action,
-    ApiKeyForm,
+    set_tool_call_policy_action, ApiKeyForm, ToolCallPolicyForm,
 };
 pub use helpers::parse_openapi_spec;
 pub use loaders::{edit_loader, loader, new_loader, select_loader, view_loader};
@@ -31,4 +31,5 @@ pub fn routes() -> Router {
         .typed_post(configure_api_key_action)
         .typed_post(delete_api_key_connection_action)
         .typed_post(delete_oauth2_connection_action)
+        .typed_post(set_tool_call_policy_action)
 }
@@ -14,9 +14,6 @@ use axum_extra::routing::{RouterExt, TypedPath};
 use chrono::{DateTime, FixedOffset, SecondsFormat};
 use db::Pool;
 use pgvector::Vector;
-use rig::client::EmbeddingsClient;
-use rig::embeddings::EmbeddingModel;
-use rig::providers::{ollama, openai};
 use serde::Deserialize;
 use serde_json::{json, Value};
 use std::cmp;
@@ -497,6 +494,10 @@ fn dataset_tools(context: &DatasetContext) -> Vec<McpTool> {
                         "minimum": 1,
                         "maximum": 25,
                         "description": "Maximum number of chunks to return (default 5)"
+                    },
+                    "filter": {
+                        "type": "object",
+                        "description": "Only return chunks whose metadata contains this object, for example {\"is_table\": true} or {\"heading_path\": [\"Installation\"]}"
                     }
                 },
                 "required": ["query"]
@@ -564,6 +565,8 @@ struct SearchContextParams {
     query: String,
     #[serde(default)]
     limit: Option<i64>,
+    #[serde(default)]
+    filter: Option<Value>,
 }
 
 fn parse_optional_arguments<T>(value: Value) -> Result<T, String>
@@ -732,6 +735,7 @@ async fn get_document(
                     "id": row.id,
                     "page_number": row.page_number,
                     "text": row.text,
+                    "metadata": row.metadata,
                 })
             })
             .collect();
@@ -762,6 +766,15 @@ async fn search_context(
         ));
     }
     let limit = cmp::min(limit, 25);
+    if params
+        .filter
+        .as_ref()
+        .is_some_and(|filter| !filter.is_object())
+    {
+        return Err(DatasetToolError::InvalidParams(
+            "filter must be an object".to_string(),
+        ));
+    }
 
     let mut client = pool
         .get()
@@ -794,14 +807,15 @@ async fn search_context(
         ));
     }
 
-    let embeddings = get_embeddings_via_rig(
+    let embeddings = embeddings::get_embeddings_via_rig(
         &params.query,
         &base_url,
         &embeddings_model,
         context_size,
         api_key.as_deref(),
     )
-    .await?;
+    .await
+    .map_err(DatasetToolError::Internal)?;
 
     let embedding_vector = Vector::from(embeddings);
 
@@ -814,14 +828,21 @@ async fn search_context(
                 c.page_number,
                 d.id,
                 d.file_name,
-                (c.embeddings <-> $2) AS distance
+                (c.embeddings <-> $2) AS distance,
+                c.metadata
             FROM rag.chunks c
             INNER JOIN rag.documents d ON c.document_id = d.id
             WHERE d.dataset_id = $1 AND c.embeddings IS NOT NULL
+              AND ($4::jsonb IS NULL OR c.metadata @> $4)
             ORDER BY c.embeddings <-> $2
             LIMIT $3
             ",
-            &[&context.dataset_id, &embedding_vector, &limit],
+            &[
+                &context.dataset_id,
+                &embedding_vector,
+                &limit,
+                &params.filter,
+            ],
         )
         .await
         .map_err(|err| DatasetToolError::Internal(err.to_string()))?;
@@ -841,6 +862,7 @@ async fn search_context(
                 "document_id": row.get::<_, i32>(3),
                 "document_name": row.get::<_, String>(4),
                 "distance": row.get::<_, f32>(5),
+                "metadata": row.get::<_, Value>(6),
             })
         })
         .collect();
@@ -848,68 +870,6 @@ async fn search_context(
     Ok(json!({ "chunks": chunks }))
 }
 
-fn trim_to_context_length(input: &str, context_length: i32) -> String {
-    if input.is_empty() {
-        return String::new();
-    }
-    let effective_context_length = if context_length <= 0 {
-        256
-    } else {
-        context_length
-    };
-    let char_count = input.chars().count() as i32;
-    if char_count <= effective_context_length {
-        return input.to_string();
-    }
-    input
-        .chars()
-        .take(effective_context_length as usize)
-        .collect()
-}
-
-async fn get_embeddings_via_rig(
-    input: &str,
-    api_end_point: &str,
-    model: &str,
-    context_length: i32,
-    api_key: Option<&str>,
-) -> Result<Vec<f32>, DatasetToolError> {
-    let text = String::from_utf8_lossy(input.as_bytes()).to_string();
-    let trimmed_text = trim_to_context_length(&text, context_length);
-
-    let normalized_base_url = api_end_point
-        .strip_suffix("/embeddings")
-        .or_else(|| api_end_point.strip_suffix("/v1/embeddings"))
-        .map(|s| s.trim_end_matches('/').to_string())
-        .unwrap_or_else(|| api_end_point.trim_end_matches('/').to_string());
-
-    let embedding = if let Some(key) = api_key.filter(|k| !k.trim().is_empty()) {
-        let client = openai::Client::builder()
-            .api_key(key)
-            .base_url(&normalized_base_url)
-            .build()
-            .map_err(|err| DatasetToolError::Internal(err.to_string()))?;
-        client
-            .embedding_model(model)
-            .embed_text(&trimmed_text)
-            .await
-            .map_err(|err| DatasetToolError::Internal(err.to_string()))?
-    } else {
-        let client = ollama::Client::builder()
-            .api_key("")
-            .base_url(&normalized_base_url)
-            .build()
-            .map_err(|err| DatasetToolError::Internal(err.to_string()))?;
-        client
-            .embedding_model(model)
-            .embed_text(&trimmed_text)
-            .await
-            .map_err(|err| DatasetToolError::Internal(err.to_string()))?
-    };
-
-    Ok(embedding.vec.into_iter().map(|v| v as f32).collect())
-}
-
 async fn apply_customer_key(transaction: &db::Transaction<'_>) -> Result<(), DatasetToolError> {
     if let Some(key) = db::customer_keys::get_customer_key() {
         let escaped = key.replace('\'', "''");
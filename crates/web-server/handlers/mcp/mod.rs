@@ -732,15 +732,7 @@ fn resolve_integration_context<'a>(
             )
             .await?;
 
-        if let Some(key) = db::customer_keys::get_customer_key() {
-            let escaped = key.replace('\'', "''");
-            transaction
-                .query(
-                    &format!("SET LOCAL encryption.root_key = '{}'", escaped),
-                    &[],
-                )
-                .await?;
-        }
+        db::customer_keys::set_local_keys(&transaction).await?;
 
         let (team_id, connection) = match base_context.connection_type.as_str() {
             "api_key" => {
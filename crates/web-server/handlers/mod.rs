@@ -2,10 +2,14 @@ pub mod api_keys;
 pub mod api_pipeline;
 pub mod assistants;
 pub mod audit_trail;
+pub mod automations;
 pub mod categories;
 pub mod console;
+pub mod costs;
 pub mod datasets;
+pub mod directory_sync;
 pub mod documents;
+pub mod guardrails;
 pub mod history;
 pub mod integrations;
 pub mod mcp;
@@ -22,9 +26,14 @@ pub mod profile;
 pub mod projects;
 pub mod providers;
 pub mod rate_limits;
+pub mod retention;
+pub mod roles;
+pub mod scim;
 pub mod skills;
 pub mod static_files;
 pub mod system_prompt;
 pub mod team;
 pub mod teams;
+pub mod translations;
 pub mod web_search;
+pub mod webhooks;
@@ -10,17 +10,25 @@ use axum_extra::routing::RouterExt;
 use db::authz;
 use db::queries;
 use db::queries::models;
+use db::GuardFormat;
 use db::ModelCapability;
 use db::ModelType;
 use db::Pool;
+use db::ProviderKind;
 use db::Visibility;
 // Add capabilities module
 use db::queries::capabilities;
 use serde::Deserialize;
 use validator::Validate;
+use web_pages::costs::{micros_to_string, string_to_micros};
 use web_pages::models::upsert as model_page;
-use web_pages::routes::models::{Delete, Edit, Index, New, SelectProvider, Upsert};
-use web_pages::{string_to_visibility, visibility_to_string};
+use web_pages::routes::models::{
+    Delete, DeleteEndpoint, Edit, Index, New, NewEndpoint, SelectProvider, Upsert,
+};
+use web_pages::{
+    guard_format_to_string, provider_kind_to_string, string_to_guard_format,
+    string_to_provider_kind, string_to_visibility, visibility_to_string,
+};
 
 const DEFAULT_TPM_LIMIT: i32 = 1_000_000;
 const DEFAULT_RPM_LIMIT: i32 = 10_000;
@@ -38,6 +46,8 @@ pub fn routes() -> Router {
         .typed_get(edit_loader)
         .typed_post(upsert_action)
         .typed_post(delete_action)
+        .typed_post(new_endpoint_action)
+        .typed_post(delete_endpoint_action)
 }
 
 pub async fn loader(
@@ -96,8 +106,18 @@ pub async fn loader(
         ));
     }
 
-    let html =
-        web_pages::models::page::page(team_id, rbac, setup_required, models_with_capabilities);
+    let endpoint_health = queries::model_endpoints::endpoint_health()
+        .bind(&transaction)
+        .all()
+        .await?;
+
+    let html = web_pages::models::page::page(
+        team_id,
+        rbac,
+        setup_required,
+        models_with_capabilities,
+        endpoint_health,
+    );
 
     Ok(Html(html).into_response())
 }
@@ -151,11 +171,15 @@ pub async fn new_loader(
         name: "".to_string(),
         display_name: "".to_string(),
         model_type: "LLM".to_string(),
+        provider_kind: provider_kind_to_string(ProviderKind::OpenAI),
+        guard_format: guard_format_to_string(GuardFormat::LlamaGuard),
         base_url: "".to_string(),
         api_key: "".to_string(),
         tpm_limit: DEFAULT_TPM_LIMIT,
         rpm_limit: DEFAULT_RPM_LIMIT,
         context_size_bytes: 2048,
+        input_price: "".to_string(),
+        output_price: "".to_string(),
         visibility: visibility_to_string(if rbac.is_sys_admin {
             Visibility::Company
         } else {
@@ -189,13 +213,14 @@ pub async fn new_loader(
             form.name = provider.default_model_name.unwrap_or_default();
             form.display_name = default_display;
             form.base_url = provider.base_url;
+            form.provider_kind = provider_kind_to_string(provider.provider_kind);
             form.context_size_bytes = provider.default_model_context_size;
             form.description = provider.default_model_description;
             form.has_capability_tool_use = true;
         }
     }
 
-    let html = model_page::page(team_id, rbac, setup_required, form);
+    let html = model_page::page(team_id, rbac, setup_required, form, vec![]);
 
     Ok(Html(html))
 }
@@ -254,9 +279,15 @@ pub async fn edit_loader(
         ModelType::Image => "Image".to_string(),
         ModelType::Embeddings => "Embeddings".to_string(),
         ModelType::TextToSpeech => "TextToSpeech".to_string(),
+        ModelType::SpeechToText => "SpeechToText".to_string(),
         ModelType::Guard => "Guard".to_string(),
     };
 
+    let endpoints = queries::model_endpoints::model_endpoints()
+        .bind(&transaction, &model.id)
+        .all()
+        .await?;
+
     let form = model_page::ModelForm {
         id: Some(model.id),
         prompt_id: model.prompt_id,
@@ -264,11 +295,15 @@ pub async fn edit_loader(
         // Preserve existing form values when editing
         display_name: model.display_name.clone(),
         model_type,
+        provider_kind: provider_kind_to_string(model.provider_kind),
+        guard_format: guard_format_to_string(model.guard_format),
         base_url: model.base_url,
         api_key: model.api_key.unwrap_or_default(),
         tpm_limit: model.tpm_limit,
         rpm_limit: model.rpm_limit,
         context_size_bytes: model.context_size,
+        input_price: micros_to_string(model.input_price_micros),
+        output_price: micros_to_string(model.output_price_micros),
         visibility,
         description: model.description.clone(),
         disclaimer: model.disclaimer,
@@ -283,7 +318,7 @@ pub async fn edit_loader(
         error: None,
     };
 
-    let html = model_page::page(team_id, rbac, setup_required, form);
+    let html = model_page::page(team_id, rbac, setup_required, form, endpoints);
 
     Ok(Html(html))
 }
@@ -339,11 +374,19 @@ pub struct ModelForm {
     #[validate(length(min = 1, message = "The prompt is mandatory"))]
     pub base_url: String,
     pub model_type: String,
+    #[serde(default)]
+    pub provider_kind: String,
+    #[serde(default)]
+    pub guard_format: String,
     #[serde(deserialize_with = "empty_string_is_none")]
     pub api_key: Option<String>,
     pub tpm_limit: i32,
     pub rpm_limit: i32,
     pub context_size: i32,
+    #[serde(default)]
+    pub input_price: String,
+    #[serde(default)]
+    pub output_price: String,
     pub visibility: String,
     pub disclaimer: String,
     pub description: String,
@@ -378,10 +421,30 @@ pub async fn upsert_action(
         "LLM" => ModelType::LLM,
         "Image" => ModelType::Image,
         "TextToSpeech" => ModelType::TextToSpeech,
+        "SpeechToText" => ModelType::SpeechToText,
         "Guard" => ModelType::Guard,
         _ => ModelType::Embeddings,
     };
 
+    let provider_kind = string_to_provider_kind(&model_form.provider_kind);
+    let guard_format = string_to_guard_format(&model_form.guard_format);
+
+    // Blank prices are free, as for self hosted models.
+    let price = |amount: &str| match amount.trim() {
+        "" => Some(0),
+        amount => string_to_micros(amount),
+    };
+    let (Some(input_price), Some(output_price)) = (
+        price(&model_form.input_price),
+        price(&model_form.output_price),
+    ) else {
+        return Ok(crate::layout::redirect_and_snackbar(
+            &web_pages::routes::models::Index { team_id }.to_string(),
+            "Prices must be amounts such as 2.50",
+        )
+        .into_response());
+    };
+
     let mut visibility = string_to_visibility(&model_form.visibility);
     if visibility == Visibility::Company && !rbac.is_sys_admin {
         visibility = Visibility::Team;
@@ -395,6 +458,7 @@ pub async fn upsert_action(
                     &transaction,
                     &model_form.name,
                     &model_type,
+                    &provider_kind,
                     &model_form.base_url,
                     &model_form.api_key,
                     &model_form.tpm_limit,
@@ -404,6 +468,16 @@ pub async fn upsert_action(
                 )
                 .await?;
 
+            if model_type == ModelType::Guard {
+                queries::models::set_guard_format()
+                    .bind(&transaction, &guard_format, &model_id)
+                    .await?;
+            }
+
+            queries::models::set_prices()
+                .bind(&transaction, &input_price, &output_price, &model_id)
+                .await?;
+
             let system_prompt: Option<&String> = None;
 
             if let Some(prompt_id) = model_form.prompt_id {
@@ -481,6 +555,7 @@ pub async fn upsert_action(
                     &transaction,
                     &model_form.name,
                     &model_type,
+                    &provider_kind,
                     &model_form.base_url,
                     &model_form.api_key,
                     &model_form.tpm_limit,
@@ -490,6 +565,16 @@ pub async fn upsert_action(
                 .one()
                 .await?;
 
+            if model_type == ModelType::Guard {
+                queries::models::set_guard_format()
+                    .bind(&transaction, &guard_format, &model_id)
+                    .await?;
+            }
+
+            queries::models::set_prices()
+                .bind(&transaction, &input_price, &output_price, &model_id)
+                .await?;
+
             let system_prompt: Option<String> = None;
             let image_icon: Option<i32> = None;
             let max_completion_tokens: Option<i32> = None;
@@ -574,6 +659,7 @@ pub async fn upsert_action(
                                         &transaction,
                                         &embeddings_name,
                                         &ModelType::Embeddings,
+                                        &provider.provider_kind,
                                         &provider.base_url,
                                         &model_form.api_key,
                                         &model_form.tpm_limit,
@@ -603,3 +689,93 @@ pub async fn upsert_action(
         .into_response()),
     }
 }
+
+#[derive(Deserialize, Validate, Default, Debug)]
+pub struct EndpointForm {
+    #[validate(length(min = 1, message = "The base URL is mandatory"))]
+    pub base_url: String,
+    #[serde(deserialize_with = "empty_string_is_none")]
+    pub api_key: Option<String>,
+    pub provider_kind: String,
+    #[validate(range(min = 0))]
+    pub priority: i32,
+    #[validate(range(min = 1))]
+    pub weight: i32,
+}
+
+pub async fn new_endpoint_action(
+    NewEndpoint { team_id, model_id }: NewEndpoint,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(endpoint_form): Form<EndpointForm>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let (rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_setup_models() {
+        return Err(CustomError::Authorization);
+    }
+
+    let edit_url = Edit {
+        team_id,
+        id: model_id,
+    }
+    .to_string();
+
+    if endpoint_form.validate().is_err() {
+        return crate::layout::redirect_and_snackbar(&edit_url, "Problem with Endpoint Validation");
+    }
+
+    queries::model_endpoints::insert()
+        .bind(
+            &transaction,
+            &model_id,
+            &string_to_provider_kind(&endpoint_form.provider_kind),
+            &endpoint_form.base_url,
+            &endpoint_form.api_key,
+            &endpoint_form.priority,
+            &endpoint_form.weight,
+        )
+        .one()
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&edit_url, "Endpoint Added")
+}
+
+pub async fn delete_endpoint_action(
+    DeleteEndpoint {
+        team_id,
+        model_id,
+        id,
+    }: DeleteEndpoint,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let (rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_setup_models() {
+        return Err(CustomError::Authorization);
+    }
+
+    queries::model_endpoints::delete()
+        .bind(&transaction, &id, &model_id)
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(
+        &Edit {
+            team_id,
+            id: model_id,
+        }
+        .to_string(),
+        "Endpoint Removed",
+    )
+}
@@ -27,6 +27,7 @@ pub struct NewPromptTemplate {
     pub example2: Option<String>,
     pub example3: Option<String>,
     pub example4: Option<String>,
+    pub persistent_workspace: Option<String>,
 
     // The image upload
     pub image_icon: Option<FieldData<axum::body::Bytes>>,
@@ -85,7 +86,7 @@ pub async fn upsert(
     };
 
     if new_prompt_template.validate().is_ok() {
-        if let Some(id) = new_prompt_template.id {
+        let prompt_id = if let Some(id) = new_prompt_template.id {
             update_prompt(
                 &transaction,
                 &new_prompt_template,
@@ -100,8 +101,9 @@ pub async fn upsert(
                     .bind(&transaction, &image_object_id, &id)
                     .await?;
             }
+            id
         } else {
-            let _prompt_id = insert_prompt(
+            insert_prompt(
                 &transaction,
                 &new_prompt_template,
                 image_object_id,
@@ -109,8 +111,16 @@ pub async fn upsert(
                 system_prompt,
                 team_id_num,
             )
+            .await?
+        };
+
+        queries::prompts::set_persistent_workspace()
+            .bind(
+                &transaction,
+                &new_prompt_template.persistent_workspace.is_some(),
+                &prompt_id,
+            )
             .await?;
-        }
 
         transaction.commit().await?;
 
@@ -1,22 +1,32 @@
 use crate::{CustomError, Jwt};
 use axum::{
+    body::Body,
     extract::Extension,
-    response::{Html, IntoResponse},
+    http::header::{CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_TYPE},
+    response::{Html, IntoResponse, Response},
 };
 use axum_extra::{extract::Form, routing::RouterExt};
 use db::authz;
 use db::queries;
 use db::Pool;
 use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{Cursor, Write};
 use validator::Validate;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
 
 use axum::Router;
-use web_pages::routes::profile::{Profile, SetDetails as SetDetailsRoute};
+use web_pages::routes::profile::{
+    Export as ExportRoute, Profile, SetDetails as SetDetailsRoute, SetLocale as SetLocaleRoute,
+};
 
 pub fn routes() -> Router {
     Router::new()
         .typed_get(loader)
         .typed_post(set_details_action)
+        .typed_post(set_locale_action)
+        .typed_post(export_action)
 }
 
 fn index_route(team_slug: &str) -> String {
@@ -34,6 +44,12 @@ pub struct SetDetails {
     pub last_name: String,
 }
 
+#[derive(Deserialize, Default, Debug)]
+pub struct SetLocale {
+    // Empty to follow the browser's language
+    pub locale: String,
+}
+
 pub async fn loader(
     Profile { team_id: team_slug }: Profile,
     current_user: Jwt,
@@ -50,7 +66,11 @@ pub async fn loader(
         .one()
         .await?;
 
-    Ok(Html(web_pages::profile::profile(user, team_slug, rbac)))
+    let locales = queries::i18n::locales().bind(&transaction).all().await?;
+
+    Ok(Html(web_pages::profile::profile(
+        user, team_slug, rbac, locales,
+    )))
 }
 
 pub async fn set_details_action(
@@ -78,3 +98,166 @@ pub async fn set_details_action(
 
     crate::layout::redirect_and_snackbar(&index_route(&team_slug), "Details Updated")
 }
+
+pub async fn set_locale_action(
+    SetLocaleRoute { team_id: team_slug }: SetLocaleRoute,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(form): Form<SetLocale>,
+) -> Result<impl IntoResponse, CustomError> {
+    let openid_sub = current_user.sub.clone();
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let (rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_slug).await?;
+
+    let locale = Some(form.locale.trim().to_string()).filter(|locale| !locale.is_empty());
+    if locale
+        .as_deref()
+        .is_some_and(|locale| !db::i18n::is_valid_locale(locale))
+    {
+        return crate::layout::redirect_and_snackbar(
+            &index_route(&team_slug),
+            "Language Validation Error",
+        );
+    }
+
+    queries::users::set_locale()
+        .bind(&transaction, &locale, &rbac.user_id)
+        .await?;
+
+    transaction.commit().await?;
+    db::i18n::global().forget_user_locale(&openid_sub).await;
+
+    crate::layout::redirect_and_snackbar(&index_route(&team_slug), "Language Updated")
+}
+
+/// Downloads everything the user has put into or had generated in a conversation, in
+/// every team they belong to, as a zip.
+pub async fn export_action(
+    ExportRoute { team_id: team_slug }: ExportRoute,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Extension(storage_config): Extension<object_storage::StorageConfig>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_slug).await?;
+
+    let user = queries::users::user()
+        .bind(&transaction, &rbac.user_id)
+        .one()
+        .await?;
+    let conversations = queries::user_data::export_conversations()
+        .bind(&transaction)
+        .all()
+        .await?;
+    let chats = queries::user_data::export_chats()
+        .bind(&transaction)
+        .all()
+        .await?;
+    let files = queries::user_data::export_files()
+        .bind(&transaction)
+        .all()
+        .await?;
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let zip_error = |err: zip::result::ZipError| CustomError::FaultySetup(err.to_string());
+    let io_error = |err: std::io::Error| CustomError::FaultySetup(err.to_string());
+
+    let profile = json!({
+        "id": user.id,
+        "email": user.email,
+        "first_name": user.first_name,
+        "last_name": user.last_name,
+        "locale": user.locale,
+    });
+    zip.start_file("profile.json", options).map_err(zip_error)?;
+    zip.write_all(&serde_json::to_vec_pretty(&profile)?)
+        .map_err(io_error)?;
+
+    for conversation in &conversations {
+        let messages: Vec<Value> = chats
+            .iter()
+            .filter(|chat| chat.conversation_id == conversation.id)
+            .map(|chat| {
+                json!({
+                    "id": chat.id,
+                    "role": format!("{:?}", chat.role),
+                    "status": format!("{:?}", chat.status),
+                    "content": chat.content,
+                    "tool_calls": chat.tool_calls.as_deref().map(|calls| {
+                        serde_json::from_str::<Value>(calls)
+                            .unwrap_or_else(|_| Value::String(calls.to_string()))
+                    }),
+                    "created_at": chat.created_at.to_rfc3339(),
+                })
+            })
+            .collect();
+        let export = json!({
+            "id": conversation.id,
+            "team": conversation.team_name,
+            "project": conversation.project_name,
+            "created_at": conversation.created_at.to_rfc3339(),
+            "messages": messages,
+        });
+        zip.start_file(format!("conversations/{}.json", conversation.id), options)
+            .map_err(zip_error)?;
+        zip.write_all(&serde_json::to_vec_pretty(&export)?)
+            .map_err(io_error)?;
+    }
+
+    for file in &files {
+        let object = object_storage::get(&storage_config, file.object_id).await?;
+        let Some(bytes) = object.object_data else {
+            continue;
+        };
+        zip.start_file(export_file_path(file), options)
+            .map_err(zip_error)?;
+        zip.write_all(&bytes).map_err(io_error)?;
+    }
+
+    let bytes = zip.finish().map_err(zip_error)?.into_inner();
+
+    queries::user_data::record_export()
+        .bind(&transaction, &team_id_num)
+        .await?;
+    transaction.commit().await?;
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/zip")
+        .header(CONTENT_DISPOSITION, "attachment; filename=\"my-data.zip\"")
+        .header(CACHE_CONTROL, "no-store")
+        .body(Body::from(bytes))
+        .unwrap())
+}
+
+/// Where a file goes in the export. The object id keeps files with the same name apart,
+/// and separators in the name can't move it out of its folder.
+fn export_file_path(file: &db::ExportFile) -> String {
+    format!(
+        "{}/{}/{}-{}",
+        file.folder,
+        file.conversation_id,
+        file.object_id,
+        file.file_name.replace(['/', '\\'], "_")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::export_file_path;
+
+    #[test]
+    fn export_paths_stay_in_their_folder() {
+        let file = db::ExportFile {
+            folder: "attachments".to_string(),
+            conversation_id: 7,
+            object_id: 42,
+            file_name: "../../etc/passwd".to_string(),
+        };
+        assert_eq!(export_file_path(&file), "attachments/7/42-.._.._etc_passwd");
+    }
+}
@@ -13,6 +13,7 @@ use serde::{Deserialize, Deserializer};
 use validator::Validate;
 use web_pages::providers::upsert as provider_page;
 use web_pages::routes::providers::{Delete, Edit, Index, New, Upsert};
+use web_pages::{provider_kind_to_string, string_to_provider_kind};
 
 pub fn routes() -> Router {
     Router::new()
@@ -72,6 +73,7 @@ pub async fn new_loader(
         default_model_context_size: 0,
         default_model_description: "".to_string(),
         base_url: "".to_string(),
+        provider_kind: provider_kind_to_string(db::ProviderKind::OpenAI),
         api_key_optional: false,
         default_embeddings_model_name: "".to_string(),
         default_embeddings_model_display_name: "".to_string(),
@@ -114,6 +116,7 @@ pub async fn edit_loader(
         default_model_context_size: provider.default_model_context_size,
         default_model_description: provider.default_model_description,
         base_url: provider.base_url,
+        provider_kind: provider_kind_to_string(provider.provider_kind),
         api_key_optional: provider.api_key_optional,
         default_embeddings_model_name: provider.default_embeddings_model_name.unwrap_or_default(),
         default_embeddings_model_display_name: provider
@@ -149,6 +152,8 @@ pub struct ProviderForm {
     pub default_model_description: String,
     #[validate(length(min = 1, message = "The base URL is mandatory"))]
     pub base_url: String,
+    #[serde(default)]
+    pub provider_kind: String,
     #[serde(default, deserialize_with = "checkbox_bool")]
     pub api_key_optional: bool,
     #[serde(deserialize_with = "empty_string_is_none")]
@@ -187,6 +192,8 @@ pub async fn upsert_action(
         return Err(CustomError::Authorization);
     }
 
+    let provider_kind = string_to_provider_kind(&form.provider_kind);
+
     match (form.validate(), form.id) {
         (Ok(_), Some(id)) => {
             queries::providers::update()
@@ -199,6 +206,7 @@ pub async fn upsert_action(
                     &form.default_model_context_size,
                     &form.default_model_description,
                     &form.base_url,
+                    &provider_kind,
                     &form.api_key_optional,
                     &form.default_embeddings_model_name,
                     &form.default_embeddings_model_display_name,
@@ -227,6 +235,7 @@ pub async fn upsert_action(
                     &form.default_model_context_size,
                     &form.default_model_description,
                     &form.base_url,
+                    &provider_kind,
                     &form.api_key_optional,
                     &form.default_embeddings_model_name,
                     &form.default_embeddings_model_display_name,
@@ -0,0 +1,134 @@
+use axum::extract::Extension;
+use axum::response::{Html, IntoResponse};
+use axum::Router;
+use axum_extra::extract::Form;
+use axum_extra::routing::RouterExt;
+use db::{authz, queries, Pool};
+use serde::Deserialize;
+use web_pages::routes::retention::{Index, Update};
+
+use crate::{CustomError, Jwt};
+
+pub fn routes() -> Router {
+    Router::new().typed_get(loader).typed_post(update_action)
+}
+
+pub async fn loader(
+    Index { team_id }: Index,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<Html<String>, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_retention() {
+        return Err(CustomError::Authorization);
+    }
+
+    let policy = queries::retention::retention_policy()
+        .bind(&transaction, &team_id_num)
+        .opt()
+        .await?;
+
+    let html = web_pages::retention::page::page(team_id, rbac, policy);
+
+    Ok(Html(html))
+}
+
+/// Each field is a number of days, or blank to keep that data forever.
+#[derive(Deserialize, Default, Debug)]
+pub struct RetentionForm {
+    #[serde(default)]
+    pub conversation_days: String,
+    #[serde(default)]
+    pub attachment_days: String,
+    #[serde(default)]
+    pub generated_output_days: String,
+    #[serde(default)]
+    pub token_usage_days: String,
+}
+
+fn parse_days(value: &str) -> Result<Option<i32>, ()> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    match value.parse::<i32>() {
+        Ok(days) if days > 0 => Ok(Some(days)),
+        _ => Err(()),
+    }
+}
+
+pub async fn update_action(
+    Update { team_id }: Update,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(form): Form<RetentionForm>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_retention() {
+        return Err(CustomError::Authorization);
+    }
+
+    let index = Index { team_id }.to_string();
+
+    let (
+        Ok(conversation_days),
+        Ok(attachment_days),
+        Ok(generated_output_days),
+        Ok(token_usage_days),
+    ) = (
+        parse_days(&form.conversation_days),
+        parse_days(&form.attachment_days),
+        parse_days(&form.generated_output_days),
+        parse_days(&form.token_usage_days),
+    )
+    else {
+        return crate::layout::redirect_and_snackbar(
+            &index,
+            "Retention periods must be a whole number of days, or blank",
+        );
+    };
+
+    queries::retention::upsert_retention_policy()
+        .bind(
+            &transaction,
+            &team_id_num,
+            &conversation_days,
+            &attachment_days,
+            &generated_output_days,
+            &token_usage_days,
+        )
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&index, "Retention Policy Saved")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_days;
+
+    #[test]
+    fn blank_days_keep_forever() {
+        assert_eq!(parse_days(""), Ok(None));
+        assert_eq!(parse_days("  "), Ok(None));
+    }
+
+    #[test]
+    fn days_must_be_positive_whole_numbers() {
+        assert_eq!(parse_days(" 30 "), Ok(Some(30)));
+        assert_eq!(parse_days("0"), Err(()));
+        assert_eq!(parse_days("-5"), Err(()));
+        assert_eq!(parse_days("1.5"), Err(()));
+    }
+}
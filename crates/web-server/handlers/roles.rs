@@ -0,0 +1,233 @@
+use axum::extract::Extension;
+use axum::response::{Html, IntoResponse};
+use axum::Router;
+use axum_extra::extract::Form;
+use axum_extra::routing::RouterExt;
+use db::custom_roles::grantable;
+use db::{authz, queries, Permission, Pool, Role, Transaction};
+use serde::Deserialize;
+use web_pages::routes::roles::{Delete, Edit, Index, New};
+
+use crate::{CustomError, Jwt};
+
+pub fn routes() -> Router {
+    Router::new()
+        .typed_get(loader)
+        .typed_post(new_action)
+        .typed_post(edit_action)
+        .typed_post(delete_action)
+}
+
+pub async fn loader(
+    Index { team_id }: Index,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<Html<String>, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_roles() {
+        return Err(CustomError::Authorization);
+    }
+
+    let roles = queries::custom_roles::custom_roles()
+        .bind(&transaction, &team_id_num)
+        .all()
+        .await?;
+
+    let built_in = queries::custom_roles::built_in_permissions()
+        .bind(&transaction)
+        .all()
+        .await?;
+    let permissions_of = |role: Role| -> Vec<Permission> {
+        built_in
+            .iter()
+            .filter(|row| row.role == role)
+            .map(|row| row.permission)
+            .collect()
+    };
+
+    let html = web_pages::roles::page::page(
+        team_id,
+        rbac,
+        roles,
+        permissions_of(Role::TeamManager),
+        permissions_of(Role::Collaborator),
+    );
+
+    Ok(Html(html))
+}
+
+/// Each permission ticked is posted as `permissions`.
+#[derive(Deserialize, Default, Debug)]
+pub struct RoleForm {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+impl RoleForm {
+    /// The permissions to grant, or None if the form names one a role can't have.
+    fn permissions(&self) -> Option<Vec<Permission>> {
+        self.permissions
+            .iter()
+            .map(|name| grantable(name))
+            .collect()
+    }
+}
+
+/// Checks the form, or says what is wrong with it. Names are unique in a team.
+async fn validate(
+    transaction: &Transaction<'_>,
+    team_id: i32,
+    id: Option<i32>,
+    form: &RoleForm,
+) -> Result<Result<Vec<Permission>, &'static str>, CustomError> {
+    let name = form.name.trim();
+    if name.is_empty() {
+        return Ok(Err("Roles need a name"));
+    }
+    let Some(permissions) = form.permissions() else {
+        return Ok(Err("That permission can't be given to a custom role"));
+    };
+    let taken = queries::custom_roles::custom_roles()
+        .bind(transaction, &team_id)
+        .all()
+        .await?
+        .iter()
+        .any(|role| Some(role.id) != id && role.name.eq_ignore_ascii_case(name));
+    if taken {
+        return Ok(Err("The team already has a role with that name"));
+    }
+    Ok(Ok(permissions))
+}
+
+pub async fn new_action(
+    New { team_id }: New,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(form): Form<RoleForm>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_roles() {
+        return Err(CustomError::Authorization);
+    }
+
+    let index = Index { team_id }.to_string();
+
+    let permissions = match validate(&transaction, team_id_num, None, &form).await? {
+        Ok(permissions) => permissions,
+        Err(message) => return crate::layout::redirect_and_snackbar(&index, message),
+    };
+
+    queries::custom_roles::insert_custom_role()
+        .bind(
+            &transaction,
+            &team_id_num,
+            &form.name.trim(),
+            &form.description.trim(),
+            &permissions,
+        )
+        .one()
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&index, "Role Created")
+}
+
+pub async fn edit_action(
+    Edit { team_id, id }: Edit,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(form): Form<RoleForm>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_roles() {
+        return Err(CustomError::Authorization);
+    }
+
+    let index = Index { team_id }.to_string();
+
+    let permissions = match validate(&transaction, team_id_num, Some(id), &form).await? {
+        Ok(permissions) => permissions,
+        Err(message) => return crate::layout::redirect_and_snackbar(&index, message),
+    };
+
+    queries::custom_roles::update_custom_role()
+        .bind(
+            &transaction,
+            &form.name.trim(),
+            &form.description.trim(),
+            &permissions,
+            &id,
+            &team_id_num,
+        )
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&index, "Role Updated")
+}
+
+pub async fn delete_action(
+    Delete { team_id, id }: Delete,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_roles() {
+        return Err(CustomError::Authorization);
+    }
+
+    queries::custom_roles::delete_custom_role()
+        .bind(&transaction, &id, &team_id_num)
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&Index { team_id }.to_string(), "Role Deleted")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn form(permissions: &[&str]) -> RoleForm {
+        RoleForm {
+            name: "Curator".to_string(),
+            description: String::new(),
+            permissions: permissions.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn only_grantable_permissions_are_accepted() {
+        assert_eq!(
+            form(&["ViewDatasets", "ManageDatasets"]).permissions(),
+            Some(vec![Permission::ViewDatasets, Permission::ManageDatasets])
+        );
+        assert_eq!(form(&[]).permissions(), Some(vec![]));
+        assert_eq!(form(&["ViewDatasets", "SetupModels"]).permissions(), None);
+    }
+}
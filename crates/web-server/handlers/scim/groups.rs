@@ -0,0 +1,525 @@
+use super::{
+    location, parse_filter, parse_id, scim_response, sync_users, ListQuery, PatchOperation,
+    PatchRequest, ScimAuth, ScimError,
+};
+use crate::config::Config;
+use axum::{
+    extract::{Extension, Query},
+    http::StatusCode,
+    response::Response,
+    Json, Router,
+};
+use axum_extra::routing::{RouterExt, TypedPath};
+use db::{queries, Pool, ScimGroup, Transaction};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const GROUP_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/scim/v2/Groups")]
+pub struct Groups {}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/scim/v2/Groups/{id}")]
+pub struct Group {
+    pub id: String,
+}
+
+pub fn routes() -> Router {
+    Router::new()
+        .typed_get(list)
+        .typed_post(create)
+        .typed_get(get)
+        .typed_put(replace)
+        .typed_patch(patch)
+        .typed_delete(delete)
+}
+
+#[derive(Deserialize, Debug)]
+struct Member {
+    value: String,
+}
+
+/// A group as the identity provider sends it.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupResource {
+    #[serde(default)]
+    display_name: String,
+    external_id: Option<String>,
+    #[serde(default)]
+    members: Vec<Member>,
+}
+
+fn member_ids(value: &Value) -> Result<Vec<i32>, ScimError> {
+    let members: Vec<Member> = serde_json::from_value(value.clone())
+        .map_err(|e| ScimError::invalid_value(e.to_string()))?;
+    members
+        .iter()
+        .map(|member| parse_id(&member.value))
+        .collect()
+}
+
+fn as_string(value: &Value) -> Result<String, ScimError> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| ScimError::invalid_value(format!("{value} is not a string")))
+}
+
+/// What a PATCH changes.
+#[derive(Debug, Default, PartialEq)]
+struct GroupPatch {
+    display_name: Option<String>,
+    external_id: Option<Option<String>>,
+    // Set when the members are replaced rather than added to or removed from.
+    members: Option<Vec<i32>>,
+    add: Vec<i32>,
+    remove: Vec<i32>,
+}
+
+impl GroupPatch {
+    fn replace_members(&mut self, members: Vec<i32>) {
+        self.members = Some(members);
+        self.add.clear();
+        self.remove.clear();
+    }
+
+    fn set(&mut self, path: &str, value: &Value, op: &str) -> Result<(), ScimError> {
+        match path.to_ascii_lowercase().as_str() {
+            "displayname" => self.display_name = Some(as_string(value)?),
+            "externalid" => self.external_id = Some(Some(as_string(value)?)),
+            "members" if op == "add" => self.add.extend(member_ids(value)?),
+            "members" => self.replace_members(member_ids(value)?),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The members once the patch is applied to the current ones.
+    fn apply_members(&self, current: &[i32]) -> Vec<i32> {
+        let mut members = self.members.clone().unwrap_or_else(|| current.to_vec());
+        for id in &self.add {
+            if !members.contains(id) {
+                members.push(*id);
+            }
+        }
+        members.retain(|id| !self.remove.contains(id));
+        members
+    }
+}
+
+fn parse_patch(operations: &[PatchOperation]) -> Result<GroupPatch, ScimError> {
+    let mut patch = GroupPatch::default();
+    for operation in operations {
+        let op = operation.op();
+        match (op.as_str(), operation.path.as_deref()) {
+            ("add" | "replace", Some(path)) => patch.set(path, &operation.value, &op)?,
+            ("add" | "replace", None) => {
+                let Value::Object(attributes) = &operation.value else {
+                    return Err(ScimError::invalid_value(
+                        "Operations without a path need an object value",
+                    ));
+                };
+                for (path, value) in attributes {
+                    patch.set(path, value, &op)?;
+                }
+            }
+            ("remove", Some(path)) if path.eq_ignore_ascii_case("members") => {
+                if operation.value.is_null() {
+                    patch.replace_members(vec![]);
+                } else {
+                    patch.remove.extend(member_ids(&operation.value)?);
+                }
+            }
+            // members[value eq "12"]
+            ("remove", Some(path)) if path.to_ascii_lowercase().starts_with("members[") => {
+                let filter = path["members[".len()..].trim_end_matches(']');
+                let (attribute, value) = parse_filter(filter)?;
+                if !attribute.eq_ignore_ascii_case("value") {
+                    return Err(ScimError::invalid_value(format!(
+                        "Members can't be removed by {attribute}"
+                    )));
+                }
+                patch.remove.push(parse_id(&value)?);
+            }
+            ("remove", Some(path)) if path.eq_ignore_ascii_case("externalId") => {
+                patch.external_id = Some(None)
+            }
+            ("remove", _) => {}
+            (op, _) => {
+                return Err(ScimError::invalid_value(format!(
+                    "Unsupported operation: {op}"
+                )))
+            }
+        }
+    }
+    Ok(patch)
+}
+
+async fn resources(
+    transaction: &Transaction<'_>,
+    config: &Config,
+    groups: &[ScimGroup],
+) -> Result<Vec<Value>, ScimError> {
+    let group_ids: Vec<i32> = groups.iter().map(|group| group.id).collect();
+    let members = queries::scim::scim_group_members()
+        .bind(transaction, &group_ids)
+        .all()
+        .await?;
+
+    Ok(groups
+        .iter()
+        .map(|group| {
+            let members: Vec<Value> = members
+                .iter()
+                .filter(|member| member.group_id == group.id)
+                .map(|member| {
+                    json!({
+                        "value": member.user_id.to_string(),
+                        "display": member.email,
+                        "$ref": location(config, "Users", member.user_id),
+                    })
+                })
+                .collect();
+            json!({
+                "schemas": [GROUP_SCHEMA],
+                "id": group.id.to_string(),
+                "externalId": group.external_id,
+                "displayName": group.display_name,
+                "members": members,
+                "meta": {
+                    "resourceType": "Group",
+                    "created": group.created_at.to_rfc3339(),
+                    "lastModified": group.updated_at.to_rfc3339(),
+                    "location": location(config, "Groups", group.id),
+                },
+            })
+        })
+        .collect())
+}
+
+async fn resource(
+    transaction: &Transaction<'_>,
+    config: &Config,
+    group: ScimGroup,
+) -> Result<Value, ScimError> {
+    Ok(resources(transaction, config, &[group])
+        .await?
+        .pop()
+        .unwrap_or_default())
+}
+
+async fn find_group(transaction: &Transaction<'_>, id: &str) -> Result<ScimGroup, ScimError> {
+    let id = parse_id(id)?;
+    queries::scim::scim_group()
+        .bind(transaction, &id)
+        .opt()
+        .await?
+        .ok_or_else(|| ScimError::not_found(format!("No group has the id {id}")))
+}
+
+async fn current_members(
+    transaction: &Transaction<'_>,
+    group_id: i32,
+) -> Result<Vec<i32>, ScimError> {
+    Ok(queries::scim::scim_group_members()
+        .bind(transaction, &vec![group_id])
+        .all()
+        .await?
+        .iter()
+        .map(|member| member.user_id)
+        .collect())
+}
+
+/// Display names are unique, whatever their case.
+async fn check_name_free(
+    transaction: &Transaction<'_>,
+    display_name: &str,
+    group_id: Option<i32>,
+) -> Result<(), ScimError> {
+    if display_name.trim().is_empty() {
+        return Err(ScimError::invalid_value("Groups need a displayName"));
+    }
+    let existing = queries::scim::scim_groups()
+        .bind(transaction, &Some(display_name), &0, &1)
+        .opt()
+        .await?;
+    match existing {
+        Some(existing) if Some(existing.id) != group_id => Err(ScimError::uniqueness(format!(
+            "A group already has the name {display_name}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Sets the members, then syncs everyone who joined or left.
+async fn set_members(
+    transaction: &Transaction<'_>,
+    group_id: i32,
+    current: &[i32],
+    members: &[i32],
+) -> Result<(), ScimError> {
+    let added: Vec<i32> = members
+        .iter()
+        .filter(|id| !current.contains(id))
+        .copied()
+        .collect();
+    let removed: Vec<i32> = current
+        .iter()
+        .filter(|id| !members.contains(id))
+        .copied()
+        .collect();
+    queries::scim::add_scim_group_members()
+        .bind(transaction, &group_id, &added)
+        .await?;
+    queries::scim::remove_scim_group_members()
+        .bind(transaction, &group_id, &removed)
+        .await?;
+    sync_users(transaction, &[added, removed].concat()).await?;
+    Ok(())
+}
+
+pub async fn list(
+    Groups {}: Groups,
+    _auth: ScimAuth,
+    Extension(pool): Extension<Pool>,
+    Extension(config): Extension<Config>,
+    Query(query): Query<ListQuery>,
+) -> Result<Response, ScimError> {
+    let mut display_name = None;
+    if let Some(filter) = &query.filter {
+        let (attribute, value) = parse_filter(filter)?;
+        if !attribute.eq_ignore_ascii_case("displayName") {
+            return Err(ScimError::new(
+                StatusCode::BAD_REQUEST,
+                Some("invalidFilter"),
+                format!("Groups can't be filtered on {attribute}"),
+            ));
+        }
+        display_name = Some(value);
+    }
+
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (offset, limit) = query.page();
+    let total = queries::scim::count_scim_groups()
+        .bind(&transaction, &display_name)
+        .one()
+        .await?;
+    let groups = queries::scim::scim_groups()
+        .bind(&transaction, &display_name, &offset, &limit)
+        .all()
+        .await?;
+
+    let resources = resources(&transaction, &config, &groups).await?;
+    Ok(query.list_response(total, resources))
+}
+
+pub async fn create(
+    Groups {}: Groups,
+    _auth: ScimAuth,
+    Extension(pool): Extension<Pool>,
+    Extension(config): Extension<Config>,
+    Json(body): Json<GroupResource>,
+) -> Result<Response, ScimError> {
+    let members = body
+        .members
+        .iter()
+        .map(|member| parse_id(&member.value))
+        .collect::<Result<Vec<i32>, _>>()?;
+
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    check_name_free(&transaction, &body.display_name, None).await?;
+    let id = queries::scim::insert_scim_group()
+        .bind(&transaction, &body.display_name.trim(), &body.external_id)
+        .one()
+        .await?;
+    // Not mapped to a team yet, so this only records who is in it.
+    set_members(&transaction, id, &[], &members).await?;
+    let group = find_group(&transaction, &id.to_string()).await?;
+    let resource = resource(&transaction, &config, group).await?;
+
+    transaction.commit().await?;
+
+    Ok(scim_response(StatusCode::CREATED, resource))
+}
+
+pub async fn get(
+    Group { id }: Group,
+    _auth: ScimAuth,
+    Extension(pool): Extension<Pool>,
+    Extension(config): Extension<Config>,
+) -> Result<Response, ScimError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let group = find_group(&transaction, &id).await?;
+    let resource = resource(&transaction, &config, group).await?;
+
+    Ok(scim_response(StatusCode::OK, resource))
+}
+
+pub async fn replace(
+    Group { id }: Group,
+    _auth: ScimAuth,
+    Extension(pool): Extension<Pool>,
+    Extension(config): Extension<Config>,
+    Json(body): Json<GroupResource>,
+) -> Result<Response, ScimError> {
+    let members = body
+        .members
+        .iter()
+        .map(|member| parse_id(&member.value))
+        .collect::<Result<Vec<i32>, _>>()?;
+
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let group = find_group(&transaction, &id).await?;
+    check_name_free(&transaction, &body.display_name, Some(group.id)).await?;
+    queries::scim::update_scim_group()
+        .bind(
+            &transaction,
+            &body.display_name.trim(),
+            &body.external_id,
+            &group.id,
+        )
+        .await?;
+    let current = current_members(&transaction, group.id).await?;
+    set_members(&transaction, group.id, &current, &members).await?;
+    let group = find_group(&transaction, &id).await?;
+    let resource = resource(&transaction, &config, group).await?;
+
+    transaction.commit().await?;
+
+    Ok(scim_response(StatusCode::OK, resource))
+}
+
+pub async fn patch(
+    Group { id }: Group,
+    _auth: ScimAuth,
+    Extension(pool): Extension<Pool>,
+    Extension(config): Extension<Config>,
+    Json(body): Json<PatchRequest>,
+) -> Result<Response, ScimError> {
+    let patch = parse_patch(&body.operations)?;
+
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let group = find_group(&transaction, &id).await?;
+    if patch.display_name.is_some() || patch.external_id.is_some() {
+        let display_name = patch
+            .display_name
+            .clone()
+            .unwrap_or(group.display_name.clone());
+        let external_id = patch
+            .external_id
+            .clone()
+            .unwrap_or(group.external_id.clone());
+        check_name_free(&transaction, &display_name, Some(group.id)).await?;
+        queries::scim::update_scim_group()
+            .bind(&transaction, &display_name.trim(), &external_id, &group.id)
+            .await?;
+    }
+    let current = current_members(&transaction, group.id).await?;
+    let members = patch.apply_members(&current);
+    set_members(&transaction, group.id, &current, &members).await?;
+    let group = find_group(&transaction, &id).await?;
+    let resource = resource(&transaction, &config, group).await?;
+
+    transaction.commit().await?;
+
+    Ok(scim_response(StatusCode::OK, resource))
+}
+
+pub async fn delete(
+    Group { id }: Group,
+    _auth: ScimAuth,
+    Extension(pool): Extension<Pool>,
+) -> Result<StatusCode, ScimError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let group = find_group(&transaction, &id).await?;
+    let members = current_members(&transaction, group.id).await?;
+    queries::scim::delete_scim_group()
+        .bind(&transaction, &group.id)
+        .await?;
+    sync_users(&transaction, &members).await?;
+
+    transaction.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(value: Value) -> GroupPatch {
+        let request: PatchRequest = serde_json::from_value(json!({ "Operations": value })).unwrap();
+        parse_patch(&request.operations).unwrap()
+    }
+
+    #[test]
+    fn members_are_added_and_removed() {
+        let patch = parse(json!([
+            { "op": "add", "path": "members", "value": [{ "value": "3" }, { "value": "4" }] },
+            { "op": "remove", "path": "members[value eq \"1\"]" }
+        ]));
+        assert_eq!(patch.add, vec![3, 4]);
+        assert_eq!(patch.remove, vec![1]);
+        assert_eq!(patch.apply_members(&[1, 2, 3]), vec![2, 3, 4]);
+
+        let patch = parse(json!([
+            { "op": "Remove", "path": "members", "value": [{ "value": "2" }] }
+        ]));
+        assert_eq!(patch.apply_members(&[1, 2]), vec![1]);
+    }
+
+    #[test]
+    fn members_can_be_replaced_or_cleared() {
+        let patch = parse(json!([
+            { "op": "replace", "path": "members", "value": [{ "value": "7" }] }
+        ]));
+        assert_eq!(patch.apply_members(&[1, 2]), vec![7]);
+
+        let patch = parse(json!([{ "op": "remove", "path": "members" }]));
+        assert_eq!(patch.apply_members(&[1, 2]), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn groups_are_renamed_without_a_path() {
+        let patch = parse(json!([
+            { "op": "Replace", "value": { "id": "5", "displayName": "Sales EMEA" } }
+        ]));
+        assert_eq!(
+            patch,
+            GroupPatch {
+                display_name: Some("Sales EMEA".to_string()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_operations_and_bad_ids_are_rejected() {
+        let request: PatchRequest = serde_json::from_value(json!({
+            "Operations": [{ "op": "copy", "path": "members" }]
+        }))
+        .unwrap();
+        assert!(parse_patch(&request.operations).is_err());
+
+        let request: PatchRequest = serde_json::from_value(json!({
+            "Operations": [{ "op": "add", "path": "members", "value": [{ "value": "jo" }] }]
+        }))
+        .unwrap();
+        assert!(parse_patch(&request.operations).is_err());
+    }
+}
@@ -0,0 +1,367 @@
+//! A SCIM 2.0 server so an identity provider can provision users and groups.
+//!
+//! Enabled by setting `SCIM_TOKEN`, which the identity provider sends as a bearer
+//! token. Users are matched to their sign in by email, groups are mapped to a team
+//! and roles on the Directory Sync page, and deactivating a user takes them out of
+//! other teams and revokes their API and MCP keys.
+
+mod groups;
+mod users;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use axum_extra::routing::{RouterExt, TypedPath};
+use db::{queries, Transaction};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::config::Config;
+
+const CONTENT_TYPE: &str = "application/scim+json";
+const LIST_RESPONSE: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+const ERROR: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+const MAX_RESULTS: i64 = 100;
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/scim/v2/ServiceProviderConfig")]
+pub struct ServiceProviderConfig {}
+
+pub fn routes() -> Router {
+    Router::new()
+        .typed_get(service_provider_config)
+        .merge(users::routes())
+        .merge(groups::routes())
+}
+
+/// Only lets the identity provider in.
+pub struct ScimAuth;
+
+impl<S> FromRequestParts<S> for ScimAuth
+where
+    S: Send + Sync,
+{
+    type Rejection = ScimError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(expected) = parts
+            .extensions
+            .get::<Config>()
+            .and_then(|config| config.scim_token.clone())
+        else {
+            return Err(ScimError::not_found("SCIM is not enabled"));
+        };
+        let presented = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        if tokens_match(presented.trim(), &expected) {
+            Ok(ScimAuth)
+        } else {
+            Err(ScimError::new(
+                StatusCode::UNAUTHORIZED,
+                None,
+                "Invalid SCIM token",
+            ))
+        }
+    }
+}
+
+/// Compares in constant time, so the token can't be guessed from response times.
+fn tokens_match(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// The error response SCIM clients expect.
+#[derive(Debug)]
+pub struct ScimError {
+    status: StatusCode,
+    scim_type: Option<&'static str>,
+    detail: String,
+}
+
+impl ScimError {
+    fn new(status: StatusCode, scim_type: Option<&'static str>, detail: impl Into<String>) -> Self {
+        ScimError {
+            status,
+            scim_type,
+            detail: detail.into(),
+        }
+    }
+
+    fn not_found(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, None, detail)
+    }
+
+    fn invalid_value(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, Some("invalidValue"), detail)
+    }
+
+    fn uniqueness(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, Some("uniqueness"), detail)
+    }
+}
+
+impl IntoResponse for ScimError {
+    fn into_response(self) -> Response {
+        let mut body = json!({
+            "schemas": [ERROR],
+            "status": self.status.as_u16().to_string(),
+            "detail": self.detail,
+        });
+        if let Some(scim_type) = self.scim_type {
+            body["scimType"] = json!(scim_type);
+        }
+        scim_response(self.status, body)
+    }
+}
+
+impl From<db::TokioPostgresError> for ScimError {
+    fn from(err: db::TokioPostgresError) -> ScimError {
+        tracing::error!(error = ?err, "SCIM database error");
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, None, err.to_string())
+    }
+}
+
+impl From<db::PoolError> for ScimError {
+    fn from(err: db::PoolError) -> ScimError {
+        tracing::error!(error = ?err, "SCIM database pool error");
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, None, err.to_string())
+    }
+}
+
+fn scim_response(status: StatusCode, body: Value) -> Response {
+    (status, [(header::CONTENT_TYPE, CONTENT_TYPE)], Json(body)).into_response()
+}
+
+/// The paging and filter of a list request.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ListQuery {
+    filter: Option<String>,
+    start_index: Option<i64>,
+    count: Option<i64>,
+}
+
+impl ListQuery {
+    /// The offset and limit, from a one based index and a count.
+    fn page(&self) -> (i64, i64) {
+        let start_index = self.start_index.unwrap_or(1).max(1);
+        let count = self.count.unwrap_or(MAX_RESULTS).clamp(0, MAX_RESULTS);
+        (start_index - 1, count)
+    }
+
+    fn list_response(&self, total: i64, resources: Vec<Value>) -> Response {
+        let (offset, _) = self.page();
+        scim_response(
+            StatusCode::OK,
+            json!({
+                "schemas": [LIST_RESPONSE],
+                "totalResults": total,
+                "startIndex": offset + 1,
+                "itemsPerPage": resources.len(),
+                "Resources": resources,
+            }),
+        )
+    }
+}
+
+/// Parses the `attribute eq "value"` filters identity providers use to look up a
+/// resource before creating it. Returns the attribute and value.
+fn parse_filter(filter: &str) -> Result<(String, String), ScimError> {
+    let invalid = || {
+        ScimError::new(
+            StatusCode::BAD_REQUEST,
+            Some("invalidFilter"),
+            format!("Only attribute eq \"value\" filters are supported: {filter}"),
+        )
+    };
+    let mut parts = filter.trim().splitn(3, ' ');
+    let (Some(attribute), Some(operator), Some(value)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid());
+    };
+    if !operator.eq_ignore_ascii_case("eq") {
+        return Err(invalid());
+    }
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or_else(invalid)?;
+    Ok((attribute.to_string(), value.replace("\\\"", "\"")))
+}
+
+/// The location of a resource, for its `meta`.
+fn location(config: &Config, resource_type: &str, id: i32) -> String {
+    format!(
+        "{}/scim/v2/{}/{}",
+        config.base_url.trim_end_matches('/'),
+        resource_type,
+        id
+    )
+}
+
+/// Ids in SCIM are strings, and ours are the database ids.
+fn parse_id(id: &str) -> Result<i32, ScimError> {
+    id.trim()
+        .parse()
+        .map_err(|_| ScimError::invalid_value(format!("{id} is not a valid id")))
+}
+
+/// Brings a user's memberships in line with their groups. A deactivated user leaves
+/// every team but their own and loses their API and MCP keys.
+async fn sync_user(
+    transaction: &Transaction<'_>,
+    user_id: i32,
+    active: bool,
+) -> Result<(), db::TokioPostgresError> {
+    // The audit triggers record who made each change. The identity provider isn't a
+    // user, so as with erasure the changes are recorded against the user themselves.
+    transaction
+        .execute(
+            "SELECT set_config('row_level_security.user_id', $1, true)",
+            &[&user_id.to_string()],
+        )
+        .await?;
+
+    if !active {
+        queries::scim::revoke_api_keys()
+            .bind(transaction, &user_id)
+            .await?;
+        queries::scim::leave_teams()
+            .bind(transaction, &user_id)
+            .await?;
+        return Ok(());
+    }
+    queries::scim::sync_remove_memberships()
+        .bind(transaction, &user_id)
+        .await?;
+    queries::scim::sync_grant_memberships()
+        .bind(transaction, &user_id)
+        .await?;
+    queries::scim::sync_remove_custom_roles()
+        .bind(transaction, &user_id)
+        .await?;
+    queries::scim::sync_grant_custom_roles()
+        .bind(transaction, &user_id)
+        .await?;
+    Ok(())
+}
+
+/// Syncs each of the users, who may have joined or left a group.
+pub async fn sync_users(
+    transaction: &Transaction<'_>,
+    user_ids: &[i32],
+) -> Result<(), db::TokioPostgresError> {
+    for user_id in user_ids {
+        if let Some(user) = queries::scim::scim_user()
+            .bind(transaction, user_id)
+            .opt()
+            .await?
+        {
+            sync_user(transaction, user.id, user.active).await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn service_provider_config(
+    ServiceProviderConfig {}: ServiceProviderConfig,
+    _auth: ScimAuth,
+) -> Response {
+    scim_response(
+        StatusCode::OK,
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:ServiceProviderConfig"],
+            "patch": { "supported": true },
+            "bulk": { "supported": false, "maxOperations": 0, "maxPayloadSize": 0 },
+            "filter": { "supported": true, "maxResults": MAX_RESULTS },
+            "changePassword": { "supported": false },
+            "sort": { "supported": false },
+            "etag": { "supported": false },
+            "authenticationSchemes": [{
+                "type": "oauthbearertoken",
+                "name": "Bearer Token",
+                "description": "The SCIM_TOKEN the server was started with",
+            }],
+        }),
+    )
+}
+
+/// The operations of a PATCH request.
+#[derive(Deserialize, Debug)]
+pub struct PatchRequest {
+    #[serde(rename = "Operations")]
+    operations: Vec<PatchOperation>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PatchOperation {
+    op: String,
+    path: Option<String>,
+    #[serde(default)]
+    value: Value,
+}
+
+impl PatchOperation {
+    fn op(&self) -> String {
+        self.op.to_ascii_lowercase()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_must_match_exactly() {
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secreT", "secret"));
+        assert!(!tokens_match("secret2", "secret"));
+        assert!(!tokens_match("", "secret"));
+    }
+
+    #[test]
+    fn parses_equality_filters() {
+        assert_eq!(
+            parse_filter("userName eq \"jo@example.com\"").unwrap(),
+            ("userName".to_string(), "jo@example.com".to_string())
+        );
+        assert_eq!(
+            parse_filter("displayName EQ \"Sales \\\"EMEA\\\"\"").unwrap(),
+            ("displayName".to_string(), "Sales \"EMEA\"".to_string())
+        );
+        assert!(parse_filter("userName sw \"jo\"").is_err());
+        assert!(parse_filter("userName eq jo").is_err());
+        assert!(parse_filter("userName").is_err());
+    }
+
+    #[test]
+    fn pages_are_one_based_and_capped() {
+        let query = ListQuery::default();
+        assert_eq!(query.page(), (0, MAX_RESULTS));
+        let query = ListQuery {
+            start_index: Some(11),
+            count: Some(1000),
+            ..Default::default()
+        };
+        assert_eq!(query.page(), (10, MAX_RESULTS));
+        let query = ListQuery {
+            start_index: Some(0),
+            count: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(query.page(), (0, 5));
+    }
+}
@@ -0,0 +1,513 @@
+use super::{
+    location, parse_filter, parse_id, scim_response, sync_user, ListQuery, PatchOperation,
+    PatchRequest, ScimAuth, ScimError,
+};
+use crate::config::Config;
+use axum::{
+    extract::{Extension, Query},
+    http::StatusCode,
+    response::Response,
+    Json, Router,
+};
+use axum_extra::routing::{RouterExt, TypedPath};
+use db::{queries, Pool, ScimUser, Transaction};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/scim/v2/Users")]
+pub struct Users {}
+
+#[derive(TypedPath, Deserialize)]
+#[typed_path("/scim/v2/Users/{id}")]
+pub struct User {
+    pub id: String,
+}
+
+pub fn routes() -> Router {
+    Router::new()
+        .typed_get(list)
+        .typed_post(create)
+        .typed_get(get)
+        .typed_put(replace)
+        .typed_patch(patch)
+        .typed_delete(delete)
+}
+
+/// The parts of a SCIM user we keep.
+#[derive(Debug, Clone, PartialEq)]
+struct UserFields {
+    email: String,
+    first_name: Option<String>,
+    last_name: Option<String>,
+    active: bool,
+    external_id: Option<String>,
+}
+
+impl From<&ScimUser> for UserFields {
+    fn from(user: &ScimUser) -> Self {
+        UserFields {
+            email: user.email.clone(),
+            first_name: user.first_name.clone(),
+            last_name: user.last_name.clone(),
+            active: user.active,
+            external_id: user.scim_external_id.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct Name {
+    given_name: Option<String>,
+    family_name: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Email {
+    value: String,
+    #[serde(default)]
+    primary: bool,
+}
+
+/// A user as the identity provider sends it.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UserResource {
+    #[serde(default)]
+    user_name: String,
+    name: Option<Name>,
+    #[serde(default)]
+    emails: Vec<Email>,
+    external_id: Option<String>,
+    active: Option<Value>,
+}
+
+impl UserResource {
+    /// Their email is the primary one, or their user name if they have none.
+    fn fields(self) -> Result<UserFields, ScimError> {
+        let email = primary_email(&self.emails).unwrap_or(self.user_name);
+        let email = email.trim().to_string();
+        if email.is_empty() {
+            return Err(ScimError::invalid_value("Users need a userName or email"));
+        }
+        let name = self.name.unwrap_or_default();
+        let active = match self.active {
+            Some(active) => as_bool(&active)?,
+            None => true,
+        };
+        Ok(UserFields {
+            email,
+            first_name: name.given_name,
+            last_name: name.family_name,
+            active,
+            external_id: self.external_id,
+        })
+    }
+}
+
+fn primary_email(emails: &[Email]) -> Option<String> {
+    emails
+        .iter()
+        .find(|email| email.primary)
+        .or_else(|| emails.first())
+        .map(|email| email.value.clone())
+}
+
+/// Some identity providers send booleans as "True" and "False".
+fn as_bool(value: &Value) -> Result<bool, ScimError> {
+    match value {
+        Value::Bool(value) => Ok(*value),
+        Value::String(value) if value.eq_ignore_ascii_case("true") => Ok(true),
+        Value::String(value) if value.eq_ignore_ascii_case("false") => Ok(false),
+        _ => Err(ScimError::invalid_value(format!(
+            "{value} is not a boolean"
+        ))),
+    }
+}
+
+fn as_string(value: &Value) -> Result<String, ScimError> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| ScimError::invalid_value(format!("{value} is not a string")))
+}
+
+/// Sets an attribute from a PATCH. Attributes we don't keep are ignored.
+fn set_attribute(fields: &mut UserFields, path: &str, value: &Value) -> Result<(), ScimError> {
+    let path = path.to_ascii_lowercase();
+    match path.as_str() {
+        "active" => fields.active = as_bool(value)?,
+        "username" => fields.email = as_string(value)?,
+        "externalid" => fields.external_id = Some(as_string(value)?),
+        "name.givenname" => fields.first_name = Some(as_string(value)?),
+        "name.familyname" => fields.last_name = Some(as_string(value)?),
+        "name" => {
+            let name: Name = serde_json::from_value(value.clone())
+                .map_err(|e| ScimError::invalid_value(e.to_string()))?;
+            fields.first_name = name.given_name.or(fields.first_name.take());
+            fields.last_name = name.family_name.or(fields.last_name.take());
+        }
+        // Either the list, or the value of one of them such as emails[type eq "work"].value
+        path if path.starts_with("emails") => {
+            fields.email = match value {
+                Value::Array(_) => {
+                    let emails: Vec<Email> = serde_json::from_value(value.clone())
+                        .map_err(|e| ScimError::invalid_value(e.to_string()))?;
+                    primary_email(&emails).unwrap_or(fields.email.clone())
+                }
+                value => as_string(value)?,
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn apply_patch(fields: &mut UserFields, operations: &[PatchOperation]) -> Result<(), ScimError> {
+    for operation in operations {
+        match (operation.op().as_str(), &operation.path) {
+            ("add" | "replace", Some(path)) => set_attribute(fields, path, &operation.value)?,
+            ("add" | "replace", None) => {
+                let Value::Object(attributes) = &operation.value else {
+                    return Err(ScimError::invalid_value(
+                        "Operations without a path need an object value",
+                    ));
+                };
+                for (path, value) in attributes {
+                    set_attribute(fields, path, value)?;
+                }
+            }
+            ("remove", Some(path)) => match path.to_ascii_lowercase().as_str() {
+                "externalid" => fields.external_id = None,
+                "name.givenname" => fields.first_name = None,
+                "name.familyname" => fields.last_name = None,
+                _ => {}
+            },
+            (op, _) => {
+                return Err(ScimError::invalid_value(format!(
+                    "Unsupported operation: {op}"
+                )))
+            }
+        }
+    }
+    if fields.email.trim().is_empty() {
+        return Err(ScimError::invalid_value("Users need a userName or email"));
+    }
+    Ok(())
+}
+
+fn resource(config: &Config, user: &ScimUser) -> Value {
+    json!({
+        "schemas": [USER_SCHEMA],
+        "id": user.id.to_string(),
+        "externalId": user.scim_external_id,
+        "userName": user.email,
+        "name": {
+            "givenName": user.first_name,
+            "familyName": user.last_name,
+        },
+        "emails": [{ "value": user.email, "primary": true }],
+        "active": user.active,
+        "meta": {
+            "resourceType": "User",
+            "created": user.created_at.to_rfc3339(),
+            "lastModified": user.updated_at.to_rfc3339(),
+            "location": location(config, "Users", user.id),
+        },
+    })
+}
+
+async fn find_user(transaction: &Transaction<'_>, id: &str) -> Result<ScimUser, ScimError> {
+    let id = parse_id(id)?;
+    queries::scim::scim_user()
+        .bind(transaction, &id)
+        .opt()
+        .await?
+        .ok_or_else(|| ScimError::not_found(format!("No user has the id {id}")))
+}
+
+/// Emails are unique, whatever their case.
+async fn check_email_free(
+    transaction: &Transaction<'_>,
+    email: &str,
+    user_id: Option<i32>,
+) -> Result<(), ScimError> {
+    let existing = queries::scim::scim_users()
+        .bind(transaction, &Some(email), &None::<&str>, &0, &1)
+        .opt()
+        .await?;
+    match existing {
+        Some(existing) if Some(existing.id) != user_id => Err(ScimError::uniqueness(format!(
+            "A user already has the email {email}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+async fn save(
+    transaction: &Transaction<'_>,
+    user: &ScimUser,
+    fields: &UserFields,
+) -> Result<(), ScimError> {
+    check_email_free(transaction, &fields.email, Some(user.id)).await?;
+    queries::scim::update_scim_user()
+        .bind(
+            transaction,
+            &fields.email,
+            &fields.first_name,
+            &fields.last_name,
+            &fields.active,
+            &fields.external_id,
+            &user.id,
+        )
+        .await?;
+    sync_user(transaction, user.id, fields.active).await?;
+    Ok(())
+}
+
+pub async fn list(
+    Users {}: Users,
+    _auth: ScimAuth,
+    Extension(pool): Extension<Pool>,
+    Extension(config): Extension<Config>,
+    Query(query): Query<ListQuery>,
+) -> Result<Response, ScimError> {
+    let (mut user_name, mut external_id) = (None, None);
+    if let Some(filter) = &query.filter {
+        let (attribute, value) = parse_filter(filter)?;
+        match attribute.to_ascii_lowercase().as_str() {
+            "username" | "emails" | "emails.value" => user_name = Some(value),
+            "externalid" => external_id = Some(value),
+            _ => {
+                return Err(ScimError::new(
+                    StatusCode::BAD_REQUEST,
+                    Some("invalidFilter"),
+                    format!("Users can't be filtered on {attribute}"),
+                ))
+            }
+        }
+    }
+
+    let client = pool.get().await?;
+    let (offset, limit) = query.page();
+    let total = queries::scim::count_scim_users()
+        .bind(&client, &user_name, &external_id)
+        .one()
+        .await?;
+    let users = queries::scim::scim_users()
+        .bind(&client, &user_name, &external_id, &offset, &limit)
+        .all()
+        .await?;
+
+    let resources = users.iter().map(|user| resource(&config, user)).collect();
+    Ok(query.list_response(total, resources))
+}
+
+pub async fn create(
+    Users {}: Users,
+    _auth: ScimAuth,
+    Extension(pool): Extension<Pool>,
+    Extension(config): Extension<Config>,
+    Json(body): Json<UserResource>,
+) -> Result<Response, ScimError> {
+    let fields = body.fields()?;
+
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    check_email_free(&transaction, &fields.email, None).await?;
+    let id = queries::scim::insert_scim_user()
+        .bind(
+            &transaction,
+            &fields.email,
+            &fields.first_name,
+            &fields.last_name,
+            &fields.active,
+            &fields.external_id,
+        )
+        .one()
+        .await?;
+    let user = find_user(&transaction, &id.to_string()).await?;
+
+    transaction.commit().await?;
+
+    Ok(scim_response(StatusCode::CREATED, resource(&config, &user)))
+}
+
+pub async fn get(
+    User { id }: User,
+    _auth: ScimAuth,
+    Extension(pool): Extension<Pool>,
+    Extension(config): Extension<Config>,
+) -> Result<Response, ScimError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let user = find_user(&transaction, &id).await?;
+
+    Ok(scim_response(StatusCode::OK, resource(&config, &user)))
+}
+
+pub async fn replace(
+    User { id }: User,
+    _auth: ScimAuth,
+    Extension(pool): Extension<Pool>,
+    Extension(config): Extension<Config>,
+    Json(body): Json<UserResource>,
+) -> Result<Response, ScimError> {
+    let fields = body.fields()?;
+
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let user = find_user(&transaction, &id).await?;
+    save(&transaction, &user, &fields).await?;
+    let user = find_user(&transaction, &id).await?;
+
+    transaction.commit().await?;
+
+    Ok(scim_response(StatusCode::OK, resource(&config, &user)))
+}
+
+pub async fn patch(
+    User { id }: User,
+    _auth: ScimAuth,
+    Extension(pool): Extension<Pool>,
+    Extension(config): Extension<Config>,
+    Json(body): Json<PatchRequest>,
+) -> Result<Response, ScimError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let user = find_user(&transaction, &id).await?;
+    let mut fields = UserFields::from(&user);
+    apply_patch(&mut fields, &body.operations)?;
+    save(&transaction, &user, &fields).await?;
+    let user = find_user(&transaction, &id).await?;
+
+    transaction.commit().await?;
+
+    Ok(scim_response(StatusCode::OK, resource(&config, &user)))
+}
+
+/// Users are deactivated rather than deleted, as their data belongs to their teams
+/// until it is erased with `web-server erase-user`.
+pub async fn delete(
+    User { id }: User,
+    _auth: ScimAuth,
+    Extension(pool): Extension<Pool>,
+) -> Result<StatusCode, ScimError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let user = find_user(&transaction, &id).await?;
+    let fields = UserFields {
+        active: false,
+        ..UserFields::from(&user)
+    };
+    queries::scim::leave_scim_groups()
+        .bind(&transaction, &user.id)
+        .await?;
+    save(&transaction, &user, &fields).await?;
+
+    transaction.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields() -> UserFields {
+        UserFields {
+            email: "jo@example.com".to_string(),
+            first_name: Some("Jo".to_string()),
+            last_name: Some("Bloggs".to_string()),
+            active: true,
+            external_id: None,
+        }
+    }
+
+    fn operations(value: Value) -> Vec<PatchOperation> {
+        serde_json::from_value::<PatchRequest>(json!({ "Operations": value }))
+            .unwrap()
+            .operations
+    }
+
+    #[test]
+    fn the_primary_email_is_used() {
+        let body: UserResource = serde_json::from_value(json!({
+            "userName": "jbloggs",
+            "name": { "givenName": "Jo", "familyName": "Bloggs" },
+            "emails": [
+                { "value": "jo@home.example.com" },
+                { "value": "jo@example.com", "primary": true }
+            ],
+            "active": "True"
+        }))
+        .unwrap();
+        assert_eq!(body.fields().unwrap(), fields());
+
+        let body: UserResource =
+            serde_json::from_value(json!({ "userName": "jo@example.com" })).unwrap();
+        assert_eq!(body.fields().unwrap().email, "jo@example.com");
+
+        let body: UserResource = serde_json::from_value(json!({})).unwrap();
+        assert!(body.fields().is_err());
+    }
+
+    #[test]
+    fn patches_deactivate_with_or_without_a_path() {
+        let mut user = fields();
+        apply_patch(
+            &mut user,
+            &operations(json!([{ "op": "replace", "path": "active", "value": "False" }])),
+        )
+        .unwrap();
+        assert!(!user.active);
+
+        let mut user = fields();
+        apply_patch(
+            &mut user,
+            &operations(json!([{ "op": "Replace", "value": { "active": false } }])),
+        )
+        .unwrap();
+        assert!(!user.active);
+    }
+
+    #[test]
+    fn patches_set_names_and_emails() {
+        let mut user = fields();
+        apply_patch(
+            &mut user,
+            &operations(json!([
+                { "op": "replace", "path": "name.givenName", "value": "Joanna" },
+                { "op": "replace", "path": "emails[type eq \"work\"].value", "value": "joanna@example.com" },
+                { "op": "add", "path": "externalId", "value": "00u1" },
+                { "op": "remove", "path": "name.familyName" },
+                { "op": "replace", "path": "title", "value": "Engineer" }
+            ])),
+        )
+        .unwrap();
+        assert_eq!(
+            user,
+            UserFields {
+                email: "joanna@example.com".to_string(),
+                first_name: Some("Joanna".to_string()),
+                last_name: None,
+                active: true,
+                external_id: Some("00u1".to_string()),
+            }
+        );
+
+        assert!(apply_patch(
+            &mut user,
+            &operations(json!([{ "op": "move", "path": "active", "value": true }]))
+        )
+        .is_err());
+    }
+}
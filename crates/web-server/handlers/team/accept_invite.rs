@@ -71,6 +71,17 @@ pub async fn accept_invitation(
                 )
                 .await?;
 
+            if let Some(custom_role_id) = invitation.custom_role_id {
+                queries::custom_roles::add_member_custom_roles()
+                    .bind(
+                        &transaction,
+                        &user.id,
+                        &invitation.team_id,
+                        &vec![custom_role_id],
+                    )
+                    .await?;
+            }
+
             // I the user has not set their name yet, we do it for them based on the invitation.
             if (None, None) == (user.first_name, user.last_name) {
                 queries::users::set_name()
@@ -24,7 +24,9 @@ pub struct NewInvite {
     pub first_name: String,
     #[validate(length(min = 1, message = "The last name is mandatory"))]
     pub last_name: String,
-    pub admin: Option<String>,
+    // Collaborator, TeamManager or the id of one of the team's custom roles
+    #[serde(default)]
+    pub role: String,
 }
 
 pub async fn create_invite(
@@ -91,9 +93,13 @@ pub async fn create(
     // Create a transaction and setup RLS
     let mut client = pool.get().await?;
     let transaction = client.transaction().await?;
-    let (_permissions, team_id_num) =
+    let (permissions, team_id_num) =
         authz::get_permisisons(&transaction, &current_user.into(), team_slug).await?;
 
+    if !permissions.can_make_invitations() {
+        return Err(CustomError::Authorization);
+    }
+
     let invitation_selector = rand::rng().random::<[u8; 6]>();
     let invitation_selector_base64 = URL_SAFE_NO_PAD.encode(invitation_selector);
     let invitation_verifier = rand::rng().random::<[u8; 8]>();
@@ -101,12 +107,22 @@ pub async fn create(
     let invitation_verifier_hash_base64 = URL_SAFE_NO_PAD.encode(invitation_verifier_hash);
     let invitation_verifier_base64 = URL_SAFE_NO_PAD.encode(invitation_verifier);
 
-    let roles = if new_invite.admin.is_some() {
-        vec![types::Role::TeamManager, types::Role::Collaborator]
-    } else {
-        vec![types::Role::Collaborator]
+    let (roles, custom_role_id) = invite_roles(&new_invite.role);
+
+    let custom_role = match custom_role_id {
+        Some(custom_role_id) => queries::custom_roles::custom_roles()
+            .bind(&transaction, &team_id_num)
+            .all()
+            .await?
+            .into_iter()
+            .find(|role| role.id == custom_role_id),
+        None => None,
     };
 
+    if !can_invite_as(&permissions, &roles, custom_role.as_ref()) {
+        return Err(CustomError::Authorization);
+    }
+
     queries::invitations::insert_invitation()
         .bind(
             &transaction,
@@ -117,6 +133,7 @@ pub async fn create(
             &invitation_selector_base64,
             &invitation_verifier_hash_base64,
             &roles,
+            &custom_role_id,
         )
         .await?;
 
@@ -124,3 +141,109 @@ pub async fn create(
 
     Ok((invitation_verifier_base64, invitation_selector_base64))
 }
+
+/// The built in roles and custom role an invitation gives. Someone invited with a
+/// custom role only has its permissions.
+pub(crate) fn invite_roles(role: &str) -> (Vec<types::Role>, Option<i32>) {
+    match role {
+        "TeamManager" => (
+            vec![types::Role::TeamManager, types::Role::Collaborator],
+            None,
+        ),
+        role => match role.parse::<i32>() {
+            Ok(custom_role_id) => (vec![], Some(custom_role_id)),
+            Err(_) => (vec![types::Role::Collaborator], None),
+        },
+    }
+}
+
+/// Inviting a Team Manager hands out everything a manager can do, so it takes ManageRoles,
+/// as does a custom role with permissions the inviter doesn't have.
+pub(crate) fn can_invite_as(
+    rbac: &authz::Rbac,
+    roles: &[types::Role],
+    custom_role: Option<&db::CustomRole>,
+) -> bool {
+    if roles.contains(&types::Role::TeamManager) {
+        return rbac.can_manage_roles();
+    }
+    custom_role.is_none_or(|role| db::custom_roles::can_grant(rbac, &role.permissions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use db::Permission;
+
+    fn inviter(permissions: Vec<Permission>) -> authz::Rbac {
+        authz::Rbac {
+            permissions,
+            user_id: 1,
+            email: "inviter@example.com".to_string(),
+            is_sys_admin: false,
+            first_name: None,
+            last_name: None,
+            has_multiple_teams: false,
+            current_team_name: None,
+        }
+    }
+
+    fn custom_role(permissions: Vec<Permission>) -> db::CustomRole {
+        db::CustomRole {
+            id: 12,
+            team_id: 1,
+            name: "Reviewers".to_string(),
+            description: String::new(),
+            permissions,
+            members: 0,
+        }
+    }
+
+    #[test]
+    fn only_role_managers_can_invite_with_more_than_they_have() {
+        let inviter = inviter(vec![Permission::InvitePeopleToTeam, Permission::ViewChats]);
+        let (manager, _) = invite_roles("TeamManager");
+        let (collaborator, _) = invite_roles("Collaborator");
+
+        assert!(!can_invite_as(&inviter, &manager, None));
+        assert!(can_invite_as(&inviter, &collaborator, None));
+        assert!(can_invite_as(
+            &inviter,
+            &[],
+            Some(&custom_role(vec![Permission::ViewChats]))
+        ));
+        assert!(!can_invite_as(
+            &inviter,
+            &[],
+            Some(&custom_role(vec![Permission::ManageWebhooks]))
+        ));
+
+        let manager_of_roles = authz::Rbac {
+            permissions: vec![Permission::InvitePeopleToTeam, Permission::ManageRoles],
+            ..inviter
+        };
+        assert!(can_invite_as(&manager_of_roles, &manager, None));
+        assert!(can_invite_as(
+            &manager_of_roles,
+            &[],
+            Some(&custom_role(vec![Permission::ManageWebhooks]))
+        ));
+    }
+
+    #[test]
+    fn invitations_give_the_chosen_role() {
+        assert_eq!(
+            invite_roles("TeamManager"),
+            (
+                vec![types::Role::TeamManager, types::Role::Collaborator],
+                None
+            )
+        );
+        assert_eq!(
+            invite_roles("Collaborator"),
+            (vec![types::Role::Collaborator], None)
+        );
+        assert_eq!(invite_roles(""), (vec![types::Role::Collaborator], None));
+        assert_eq!(invite_roles("12"), (vec![], Some(12)));
+    }
+}
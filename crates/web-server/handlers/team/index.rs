@@ -36,13 +36,32 @@ pub async fn index(
         .all()
         .await?;
 
+    let custom_roles = queries::custom_roles::custom_roles()
+        .bind(&transaction, &team_id_num)
+        .all()
+        .await?;
+
+    let member_roles = queries::custom_roles::member_custom_roles()
+        .bind(&transaction, &team_id_num)
+        .all()
+        .await?;
+
     let team_name = if let Some(team) = &team.name {
         format!("Team : {}", team)
     } else {
         "Team : No Name ".to_string()
     };
 
-    let html = team::members::page(rbac, members, invites, team, user, team_name);
+    let html = team::members::page(
+        rbac,
+        members,
+        invites,
+        team,
+        user,
+        team_name,
+        custom_roles,
+        member_roles,
+    );
 
     Ok(Html(html))
 }
@@ -1,9 +1,10 @@
 mod accept_invite;
-mod create_invite;
+pub(crate) mod create_invite;
 mod delete_invite;
 mod delete_member;
 mod index;
 mod set_name;
+mod set_roles;
 use axum::Router;
 use axum_extra::routing::RouterExt;
 
@@ -15,4 +16,5 @@ pub fn routes() -> Router {
         .typed_post(delete_member::delete)
         .typed_post(delete_invite::delete)
         .typed_post(set_name::set_name)
+        .typed_post(set_roles::set_roles)
 }
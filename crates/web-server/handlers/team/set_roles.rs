@@ -0,0 +1,71 @@
+use crate::{CustomError, Jwt};
+use axum::{extract::Extension, response::IntoResponse};
+use axum_extra::extract::Form;
+use db::authz;
+use db::queries;
+use db::{Pool, Role};
+use serde::Deserialize;
+use web_pages::routes::team::{Index, SetRoles};
+
+/// Each role ticked is posted as `roles` or `custom_role_ids`.
+#[derive(Deserialize, Default, Debug)]
+pub struct MemberRoles {
+    pub user_id: i32,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub custom_role_ids: Vec<i32>,
+}
+
+pub async fn set_roles(
+    SetRoles { team_id }: SetRoles,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(member_roles): Form<MemberRoles>,
+) -> Result<impl IntoResponse, CustomError> {
+    // Create a transaction and setup RLS
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_roles() {
+        return Err(CustomError::Authorization);
+    }
+
+    let index = Index { team_id }.to_string();
+
+    // Otherwise a manager could lock themselves, and perhaps everyone, out of the team.
+    if member_roles.user_id == rbac.user_id {
+        return crate::layout::redirect_and_snackbar(&index, "You can't change your own roles");
+    }
+
+    let roles: Vec<Role> = member_roles
+        .roles
+        .iter()
+        .filter_map(|role| match role.as_str() {
+            "TeamManager" => Some(Role::TeamManager),
+            "Collaborator" => Some(Role::Collaborator),
+            _ => None,
+        })
+        .collect();
+
+    queries::custom_roles::set_member_roles()
+        .bind(&transaction, &roles, &member_roles.user_id, &team_id_num)
+        .await?;
+    queries::custom_roles::clear_member_custom_roles()
+        .bind(&transaction, &member_roles.user_id, &team_id_num)
+        .await?;
+    queries::custom_roles::add_member_custom_roles()
+        .bind(
+            &transaction,
+            &member_roles.user_id,
+            &team_id_num,
+            &member_roles.custom_role_ids,
+        )
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&index, "Roles Updated")
+}
@@ -0,0 +1,189 @@
+use super::formats::{self, Format};
+use crate::{CustomError, Jwt};
+use axum::{
+    extract::{Extension, Form, Multipart},
+    response::IntoResponse,
+};
+use db::authz;
+use db::i18n::is_valid_locale;
+use db::{queries, Pool};
+use serde::Deserialize;
+use validator::Validate;
+use web_pages::routes::translations::{Delete, Import, Locale, Reload, Upsert};
+
+#[derive(Deserialize, Validate, Default, Debug)]
+pub struct TranslationForm {
+    pub locale: String,
+    #[validate(length(min = 1, message = "The key is mandatory"))]
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct DeleteForm {
+    pub locale: String,
+    pub key: String,
+}
+
+fn locale_route(team_id: String, locale: String) -> String {
+    Locale { team_id, locale }.to_string()
+}
+
+async fn reload_translations() {
+    if !db::i18n::global().reload().await {
+        tracing::warn!("Translations were saved but the cache could not be reloaded");
+    }
+}
+
+pub async fn action_upsert(
+    Upsert { team_id }: Upsert,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(form): Form<TranslationForm>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let (rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.is_sys_admin {
+        return Err(CustomError::Authorization);
+    }
+
+    let locale = form.locale.trim().to_string();
+    if form.validate().is_err() || !is_valid_locale(&locale) {
+        return crate::layout::redirect_and_snackbar(
+            &web_pages::routes::translations::Index { team_id }.to_string(),
+            "Translation Validation Error",
+        );
+    }
+
+    queries::i18n::upsert_translation()
+        .bind(&transaction, &locale, &form.key.trim(), &form.value)
+        .await?;
+    transaction.commit().await?;
+    reload_translations().await;
+
+    crate::layout::redirect_and_snackbar(&locale_route(team_id, locale), "Translation Saved")
+}
+
+pub async fn action_delete(
+    Delete { team_id }: Delete,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(form): Form<DeleteForm>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let (rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.is_sys_admin {
+        return Err(CustomError::Authorization);
+    }
+
+    queries::i18n::delete_translation()
+        .bind(&transaction, &form.locale, &form.key)
+        .await?;
+    transaction.commit().await?;
+    reload_translations().await;
+
+    crate::layout::redirect_and_snackbar(&locale_route(team_id, form.locale), "Translation Deleted")
+}
+
+pub async fn action_import(
+    Import { team_id }: Import,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let (rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.is_sys_admin {
+        return Err(CustomError::Authorization);
+    }
+
+    let mut locale = String::new();
+    let mut file: Option<(String, String)> = None;
+    while let Some(field) = multipart.next_field().await? {
+        match field.name() {
+            Some("locale") => locale = field.text().await?.trim().to_string(),
+            Some("file") => {
+                let file_name = field.file_name().unwrap_or_default().to_string();
+                file = Some((file_name, field.text().await?));
+            }
+            _ => {}
+        }
+    }
+
+    let index = web_pages::routes::translations::Index {
+        team_id: team_id.clone(),
+    }
+    .to_string();
+    let Some((file_name, text)) = file else {
+        return crate::layout::redirect_and_snackbar(&index, "No file was uploaded".to_string());
+    };
+    if !is_valid_locale(&locale) {
+        return crate::layout::redirect_and_snackbar(
+            &index,
+            "Translation Validation Error".to_string(),
+        );
+    }
+    let Some(format) = Format::from_file_name(&file_name) else {
+        return crate::layout::redirect_and_snackbar(
+            &index,
+            "Unsupported file type, use .json, .po or .ftl".to_string(),
+        );
+    };
+    let translations = match formats::import(format, &text) {
+        Ok(translations) => translations,
+        Err(err) => {
+            tracing::warn!(file_name, err, "Failed to import translations");
+            return crate::layout::redirect_and_snackbar(
+                &index,
+                "The translations file could not be read".to_string(),
+            );
+        }
+    };
+
+    for (key, value) in &translations {
+        queries::i18n::upsert_translation()
+            .bind(&transaction, &locale, key, value)
+            .await?;
+    }
+    transaction.commit().await?;
+    reload_translations().await;
+
+    crate::layout::redirect_and_snackbar(
+        &locale_route(team_id, locale),
+        format!("Imported {} Translations", translations.len()),
+    )
+}
+
+pub async fn action_reload(
+    Reload { team_id }: Reload,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+    let (rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.is_sys_admin {
+        return Err(CustomError::Authorization);
+    }
+
+    let message = if db::i18n::global().reload().await {
+        "Translations Reloaded"
+    } else {
+        "Failed to Reload Translations"
+    };
+    crate::layout::redirect_and_snackbar(
+        &web_pages::routes::translations::Index { team_id }.to_string(),
+        message,
+    )
+}
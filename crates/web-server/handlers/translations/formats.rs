@@ -0,0 +1,366 @@
+//! Import and export of the translations of one locale.
+//!
+//! - JSON: an object of key to text. Nested objects are flattened with dots, so files
+//!   in the i18next style import as well.
+//! - gettext PO: the key is the `msgid` and the translation the `msgstr`.
+//! - Fluent: message identifiers can't contain dots, so `i18n.history` is written as
+//!   `i18n-history` and read back the same way.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Po,
+    Fluent,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "po" | "pot" => Some(Self::Po),
+            "ftl" | "fluent" => Some(Self::Fluent),
+            _ => None,
+        }
+    }
+
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        file_name
+            .rsplit_once('.')
+            .and_then(|(_, extension)| Self::from_name(extension))
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Po => "po",
+            Self::Fluent => "ftl",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Po => "text/x-gettext-translation; charset=utf-8",
+            Self::Fluent => "text/plain; charset=utf-8",
+        }
+    }
+}
+
+/// Writes the translations, given as key and text, in the format.
+pub fn export(format: Format, locale: &str, translations: &[(String, String)]) -> String {
+    match format {
+        Format::Json => {
+            let map: BTreeMap<&str, &str> = translations
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect();
+            serde_json::to_string_pretty(&map).unwrap_or_default()
+        }
+        Format::Po => {
+            let mut out = format!(
+                "msgid \"\"\nmsgstr \"\"\n\"Language: {}\\n\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n",
+                po_escape(locale)
+            );
+            for (key, value) in translations {
+                out.push_str(&format!(
+                    "\nmsgid \"{}\"\nmsgstr \"{}\"\n",
+                    po_escape(key),
+                    po_escape(value)
+                ));
+            }
+            out
+        }
+        Format::Fluent => {
+            let mut out = format!("# Translations for {locale}\n");
+            for (key, value) in translations {
+                let id = key.replace('.', "-");
+                let value = fluent_escape(value);
+                if value.contains('\n') {
+                    out.push_str(&format!("\n{id} =\n"));
+                    for line in value.lines() {
+                        out.push_str(&format!("    {line}\n"));
+                    }
+                } else {
+                    out.push_str(&format!("\n{id} = {value}\n"));
+                }
+            }
+            out
+        }
+    }
+}
+
+/// Reads key and text pairs from a file in the format.
+pub fn import(format: Format, text: &str) -> Result<Vec<(String, String)>, String> {
+    match format {
+        Format::Json => {
+            let value: Value =
+                serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {e}"))?;
+            let mut translations = Vec::new();
+            flatten_json("", &value, &mut translations)?;
+            Ok(translations)
+        }
+        Format::Po => import_po(text),
+        Format::Fluent => import_fluent(text),
+    }
+}
+
+fn flatten_json(
+    prefix: &str,
+    value: &Value,
+    translations: &mut Vec<(String, String)>,
+) -> Result<(), String> {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten_json(&key, value, translations)?;
+            }
+            Ok(())
+        }
+        Value::String(text) if !prefix.is_empty() => {
+            translations.push((prefix.to_string(), text.clone()));
+            Ok(())
+        }
+        _ => Err(format!(
+            "Expected an object of texts, found {value} at '{prefix}'"
+        )),
+    }
+}
+
+fn po_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+}
+
+fn po_unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+#[derive(PartialEq)]
+enum PoField {
+    None,
+    Id,
+    Str,
+    Other,
+}
+
+fn import_po(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut translations = Vec::new();
+    let mut field = PoField::None;
+    let (mut msgid, mut msgstr) = (None::<String>, String::new());
+
+    let mut finish = |msgid: &mut Option<String>, msgstr: &mut String| {
+        // The entry with an empty id is the header and empty strings aren't translated.
+        if let Some(id) = msgid.take() {
+            if !id.is_empty() && !msgstr.is_empty() {
+                translations.push((id, std::mem::take(msgstr)));
+            }
+        }
+        msgstr.clear();
+    };
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (keyword, rest) = match line.split_once(char::is_whitespace) {
+            Some((keyword, rest)) if !line.starts_with('"') => (keyword, rest.trim()),
+            _ => ("", line),
+        };
+        let quoted = rest
+            .strip_prefix('"')
+            .and_then(|rest| rest.strip_suffix('"'))
+            .ok_or_else(|| format!("Line {}: expected a quoted string", number + 1))?;
+        let value = po_unescape(quoted);
+
+        match keyword {
+            "msgid" => {
+                finish(&mut msgid, &mut msgstr);
+                msgid = Some(value);
+                field = PoField::Id;
+            }
+            "msgstr" | "msgstr[0]" => {
+                msgstr = value;
+                field = PoField::Str;
+            }
+            "" => match field {
+                PoField::Id => msgid.get_or_insert_with(String::new).push_str(&value),
+                PoField::Str => msgstr.push_str(&value),
+                PoField::Other => {}
+                PoField::None => {
+                    return Err(format!("Line {}: unexpected string", number + 1));
+                }
+            },
+            // msgctxt, msgid_plural and further plural forms
+            _ => field = PoField::Other,
+        }
+    }
+    finish(&mut msgid, &mut msgstr);
+
+    Ok(translations)
+}
+
+// Braces start placeables in Fluent, so literal ones are written as string literals.
+fn fluent_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '{' => out.push_str("{\"{\"}"),
+            '}' => out.push_str("{\"}\"}"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn fluent_unescape(text: &str) -> String {
+    text.replace("{\"{\"}", "{").replace("{\"}\"}", "}")
+}
+
+fn import_fluent(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut translations: Vec<(String, String)> = Vec::new();
+    let mut current: Option<(String, Vec<String>)> = None;
+
+    let finish = |current: &mut Option<(String, Vec<String>)>,
+                  translations: &mut Vec<(String, String)>| {
+        if let Some((id, lines)) = current.take() {
+            let value = fluent_unescape(lines.join("\n").trim_end());
+            if !value.is_empty() {
+                translations.push((id.replace('-', "."), value));
+            }
+        }
+    };
+
+    for (number, line) in text.lines().enumerate() {
+        if line.starts_with(' ') {
+            // A continuation line; attributes (`.name = value`) aren't supported.
+            if let Some((_, lines)) = current.as_mut() {
+                let line = line.trim();
+                if !line.starts_with('.') {
+                    lines.push(line.to_string());
+                }
+            }
+            continue;
+        }
+        finish(&mut current, &mut translations);
+
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (id, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("Line {}: expected 'identifier = value'", number + 1))?;
+        let id = id.trim();
+        if id.is_empty() || id.starts_with('-') {
+            // Terms (`-brand = ...`) are only used inside other messages.
+            continue;
+        }
+        let value = value.trim();
+        let lines = if value.is_empty() {
+            Vec::new()
+        } else {
+            vec![value.to_string()]
+        };
+        current = Some((id.to_string(), lines));
+    }
+    finish(&mut current, &mut translations);
+
+    Ok(translations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<(String, String)> {
+        vec![
+            ("i18n.history".to_string(), "Chat \"History\"".to_string()),
+            (
+                "i18n.welcome".to_string(),
+                "Hello {name}\nsecond line".to_string(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn every_format_round_trips() {
+        for format in [Format::Json, Format::Po, Format::Fluent] {
+            let exported = export(format, "de", &sample());
+            assert_eq!(import(format, &exported).unwrap(), sample(), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn nested_json_is_flattened() {
+        let imported = import(
+            Format::Json,
+            r#"{"i18n": {"history": "Verlauf", "dataset": "Datensatz"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            imported,
+            vec![
+                ("i18n.dataset".to_string(), "Datensatz".to_string()),
+                ("i18n.history".to_string(), "Verlauf".to_string()),
+            ]
+        );
+        assert!(import(Format::Json, r#"{"i18n.history": 3}"#).is_err());
+    }
+
+    #[test]
+    fn po_skips_the_header_and_untranslated_entries() {
+        let po = r#"
+# A comment
+msgid ""
+msgstr ""
+"Language: de\n"
+
+msgctxt "menu"
+msgid "i18n.history"
+msgstr ""
+"Chat-"
+"Verlauf"
+
+msgid "i18n.dataset"
+msgstr ""
+"#;
+
+        assert_eq!(
+            import(Format::Po, po).unwrap(),
+            vec![("i18n.history".to_string(), "Chat-Verlauf".to_string())]
+        );
+    }
+
+    #[test]
+    fn formats_are_chosen_by_name_or_extension() {
+        assert_eq!(Format::from_name("PO"), Some(Format::Po));
+        assert_eq!(Format::from_file_name("de.ftl"), Some(Format::Fluent));
+        assert_eq!(Format::from_file_name("de.yaml"), None);
+    }
+}
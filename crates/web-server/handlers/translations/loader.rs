@@ -0,0 +1,132 @@
+use super::formats::{self, Format};
+use crate::{CustomError, Jwt};
+use axum::{
+    body::Body,
+    extract::Extension,
+    http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    response::{Html, Response},
+};
+use db::authz;
+use db::{queries, Pool};
+use web_pages::routes::translations::{Export, Index, Locale};
+use web_pages::translations::page::TranslationRow;
+
+pub async fn loader(
+    Index { team_id }: Index,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<Html<String>, CustomError> {
+    render(team_id, "en".to_string(), current_user, pool).await
+}
+
+pub async fn locale_loader(
+    Locale { team_id, locale }: Locale,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<Html<String>, CustomError> {
+    render(team_id, locale, current_user, pool).await
+}
+
+async fn render(
+    team_id: String,
+    locale: String,
+    current_user: Jwt,
+    pool: Pool,
+) -> Result<Html<String>, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.is_sys_admin {
+        return Err(CustomError::Authorization);
+    }
+
+    let translations = queries::i18n::all_translations()
+        .bind(&transaction)
+        .all()
+        .await?;
+
+    let coverage = db::i18n::coverage(&translations);
+    let text = |row_locale: &str, key: &str| {
+        translations
+            .iter()
+            .find(|translation| translation.locale == row_locale && translation.key == key)
+            .map(|translation| translation.value.clone())
+    };
+    // Every known key, so the missing ones can be filled in.
+    let rows = coverage
+        .iter()
+        .find(|locale_coverage| locale_coverage.locale == locale)
+        .map(|locale_coverage| {
+            let mut keys: Vec<&String> = translations
+                .iter()
+                .filter(|translation| translation.locale == locale)
+                .map(|translation| &translation.key)
+                .chain(locale_coverage.missing.iter())
+                .collect();
+            keys.sort();
+            keys.into_iter()
+                .map(|key| TranslationRow {
+                    key: key.clone(),
+                    value: text(&locale, key),
+                    reference: text("en", key),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let html = web_pages::translations::page::page(team_id, rbac, locale, coverage, rows);
+
+    Ok(Html(html))
+}
+
+pub async fn export(
+    Export {
+        team_id,
+        locale,
+        format,
+    }: Export,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<Response, CustomError> {
+    let format = Format::from_name(&format)
+        .ok_or_else(|| CustomError::FaultySetup(format!("Unknown format {format}")))?;
+
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, _team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.is_sys_admin {
+        return Err(CustomError::Authorization);
+    }
+
+    let translations: Vec<(String, String)> = queries::i18n::translations_by_locale()
+        .bind(&transaction, &locale)
+        .all()
+        .await?
+        .into_iter()
+        .map(|translation| (translation.key, translation.value))
+        .collect();
+
+    let file_name = format!(
+        "{}.{}",
+        locale.replace(
+            |c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '_',
+            "_"
+        ),
+        format.extension()
+    );
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, format.content_type())
+        .header(
+            CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{file_name}\""),
+        )
+        .body(Body::from(formats::export(format, &locale, &translations)))
+        .unwrap())
+}
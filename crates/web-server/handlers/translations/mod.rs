@@ -0,0 +1,20 @@
+mod actions;
+pub mod formats;
+mod loader;
+
+pub use actions::*;
+pub use loader::*;
+
+use axum::Router;
+use axum_extra::routing::RouterExt;
+
+pub fn routes() -> Router {
+    Router::new()
+        .typed_get(loader::loader)
+        .typed_get(loader::locale_loader)
+        .typed_get(loader::export)
+        .typed_post(actions::action_upsert)
+        .typed_post(actions::action_delete)
+        .typed_post(actions::action_import)
+        .typed_post(actions::action_reload)
+}
@@ -0,0 +1,276 @@
+use axum::extract::Extension;
+use axum::response::{Html, IntoResponse};
+use axum::Router;
+use axum_extra::extract::Form;
+use axum_extra::routing::RouterExt;
+use db::webhooks::{is_event, TEST_EVENT};
+use db::{authz, queries, Pool};
+use rand::{distr::Alphanumeric, rng, RngExt};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+use web_pages::routes::webhooks::{Delete, Edit, Index, New, Replay, Test, View};
+
+use crate::{CustomError, Jwt};
+
+/// How many deliveries the webhook's page lists.
+const RECENT_DELIVERIES: i64 = 100;
+
+pub fn routes() -> Router {
+    Router::new()
+        .typed_get(loader)
+        .typed_get(view_loader)
+        .typed_post(new_action)
+        .typed_post(edit_action)
+        .typed_post(delete_action)
+        .typed_post(test_action)
+        .typed_post(replay_action)
+}
+
+pub async fn loader(
+    Index { team_id }: Index,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<Html<String>, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_webhooks() {
+        return Err(CustomError::Authorization);
+    }
+
+    let webhooks = queries::webhooks::webhooks()
+        .bind(&transaction, &team_id_num)
+        .all()
+        .await?;
+
+    let html = web_pages::webhooks::page::page(team_id, rbac, webhooks);
+
+    Ok(Html(html))
+}
+
+pub async fn view_loader(
+    View { team_id, id }: View,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<Html<String>, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_webhooks() {
+        return Err(CustomError::Authorization);
+    }
+
+    let webhook = queries::webhooks::webhook()
+        .bind(&transaction, &id, &team_id_num)
+        .one()
+        .await?;
+
+    let deliveries = queries::webhooks::deliveries()
+        .bind(&transaction, &id, &team_id_num, &RECENT_DELIVERIES)
+        .all()
+        .await?;
+
+    let html = web_pages::webhooks::view::page(team_id, rbac, webhook, deliveries);
+
+    Ok(Html(html))
+}
+
+/// The events are checkboxes, so each one selected is posted as `events`.
+#[derive(Deserialize, Validate, Default, Debug)]
+pub struct WebhookForm {
+    #[validate(url(message = "The URL must be valid"))]
+    pub url: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub events: Vec<String>,
+    pub active: Option<String>,
+}
+
+impl WebhookForm {
+    fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+            && outbound::check_url(self.url.trim()).is_ok()
+            && !self.events.is_empty()
+            && self.events.iter().all(|event| is_event(event))
+    }
+}
+
+pub async fn new_action(
+    New { team_id }: New,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(form): Form<WebhookForm>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_webhooks() {
+        return Err(CustomError::Authorization);
+    }
+
+    let index = Index { team_id }.to_string();
+
+    if !form.is_valid() {
+        return crate::layout::redirect_and_snackbar(
+            &index,
+            "Webhooks need a public http(s) URL and at least one event",
+        );
+    }
+
+    let secret: String = rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+
+    queries::webhooks::insert()
+        .bind(
+            &transaction,
+            &team_id_num,
+            &form.url.trim(),
+            &form.description.trim(),
+            &format!("whsec_{secret}"),
+            &form.events,
+        )
+        .one()
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&index, "Webhook Created")
+}
+
+pub async fn edit_action(
+    Edit { team_id, id }: Edit,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+    Form(form): Form<WebhookForm>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_webhooks() {
+        return Err(CustomError::Authorization);
+    }
+
+    let index = Index { team_id }.to_string();
+
+    if !form.is_valid() {
+        return crate::layout::redirect_and_snackbar(
+            &index,
+            "Webhooks need a public http(s) URL and at least one event",
+        );
+    }
+
+    queries::webhooks::update()
+        .bind(
+            &transaction,
+            &form.url.trim(),
+            &form.description.trim(),
+            &form.events,
+            &form.active.is_some(),
+            &id,
+            &team_id_num,
+        )
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&index, "Webhook Updated")
+}
+
+pub async fn delete_action(
+    Delete { team_id, id }: Delete,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_webhooks() {
+        return Err(CustomError::Authorization);
+    }
+
+    queries::webhooks::delete()
+        .bind(&transaction, &id, &team_id_num)
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&Index { team_id }.to_string(), "Webhook Deleted")
+}
+
+pub async fn test_action(
+    Test { team_id, id }: Test,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_webhooks() {
+        return Err(CustomError::Authorization);
+    }
+
+    let payload = json!({
+        "webhook_id": id,
+        "sent_by": rbac.email,
+        "message": format!("A {TEST_EVENT} event from Bionic"),
+    });
+
+    queries::webhooks::enqueue_test()
+        .bind(&transaction, &payload, &id, &team_id_num)
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&View { team_id, id }.to_string(), "Test Event Queued")
+}
+
+pub async fn replay_action(
+    Replay {
+        team_id,
+        id,
+        delivery_id,
+    }: Replay,
+    current_user: Jwt,
+    Extension(pool): Extension<Pool>,
+) -> Result<impl IntoResponse, CustomError> {
+    let mut client = pool.get().await?;
+    let transaction = client.transaction().await?;
+
+    let (rbac, team_id_num) =
+        authz::get_permisisons(&transaction, &current_user.into(), &team_id).await?;
+
+    if !rbac.can_manage_webhooks() {
+        return Err(CustomError::Authorization);
+    }
+
+    queries::webhooks::replay()
+        .bind(&transaction, &delivery_id, &id, &team_id_num)
+        .await?;
+
+    transaction.commit().await?;
+
+    crate::layout::redirect_and_snackbar(&View { team_id, id }.to_string(), "Delivery Queued")
+}
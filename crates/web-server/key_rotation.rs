@@ -0,0 +1,111 @@
+//! `web-server rotate-keys [--batch-size N] [--rewrap-only]`
+//!
+//! To change the root key, set `CUSTOMER_KEY` to the new key and `CUSTOMER_KEY_PREVIOUS`
+//! to the old one, run this, then remove `CUSTOMER_KEY_PREVIOUS`. Without a root key
+//! change it creates a new data key and re-encrypts everything with it.
+//!
+//! It connects with `KEY_ROTATION_DATABASE_URL` rather than the application's URL, as
+//! only the `key_rotation` role can run the rotation functions.
+
+use db::encryption::{rotate_keys, RotationOptions};
+
+/// Runs the rotation and returns the process exit code.
+pub async fn run(args: impl Iterator<Item = String>) -> i32 {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}\nusage: web-server rotate-keys [--batch-size N] [--rewrap-only]");
+            return 2;
+        }
+    };
+
+    if db::customer_keys::get_customer_key().is_none() {
+        eprintln!("CUSTOMER_KEY is not set, there is nothing to rotate");
+        return 1;
+    }
+
+    let Ok(database_url) = std::env::var("KEY_ROTATION_DATABASE_URL") else {
+        eprintln!(
+            "KEY_ROTATION_DATABASE_URL is not set, it should connect as the key_rotation role"
+        );
+        return 1;
+    };
+    let pool = db::create_pool(&database_url);
+    let client = match pool.get().await {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("Unable to connect to the database: {err}");
+            return 1;
+        }
+    };
+
+    match rotate_keys(&client, &options).await {
+        Ok(report) => {
+            println!("Re-wrapped data keys: {}", report.rewrapped_keys);
+            if let Some(version) = report.data_key_version {
+                println!("Active data key: version {version}");
+            }
+            for (column, count) in &report.reencrypted {
+                println!("Re-encrypted {column}: {count}");
+            }
+            println!("Deleted retired data keys: {}", report.deleted_keys);
+            if report.remaining.is_empty() {
+                0
+            } else {
+                for (column, count) in &report.remaining {
+                    eprintln!("Not readable with the configured keys, {column}: {count}");
+                }
+                1
+            }
+        }
+        Err(err) => {
+            eprintln!("Key rotation failed: {err}");
+            1
+        }
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<RotationOptions, String> {
+    let mut options = RotationOptions::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--batch-size" => {
+                options.batch_size = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .filter(|size: &i32| *size > 0)
+                    .ok_or("--batch-size needs a positive number")?;
+            }
+            "--rewrap-only" => options.new_data_key = false,
+            other => return Err(format!("Unknown argument: {other}")),
+        }
+    }
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> impl Iterator<Item = String> {
+        values
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn parses_rotation_arguments() {
+        assert_eq!(parse_args(args(&[])), Ok(RotationOptions::default()));
+        assert_eq!(
+            parse_args(args(&["--batch-size", "50", "--rewrap-only"])),
+            Ok(RotationOptions {
+                batch_size: 50,
+                new_data_key: false
+            })
+        );
+        assert!(parse_args(args(&["--batch-size", "0"])).is_err());
+        assert!(parse_args(args(&["--force"])).is_err());
+    }
+}
@@ -1,10 +1,11 @@
 use std::convert::Infallible;
-use std::future::ready;
 
 use axum::extract::FromRequestParts;
 use axum::http::header::ACCEPT_LANGUAGE;
 use axum::http::request::Parts;
 
+use crate::Jwt;
+
 #[derive(Clone, Debug)]
 pub struct Locale(pub String);
 
@@ -24,10 +25,11 @@ where
 {
     type Rejection = Infallible;
 
-    fn from_request_parts(
-        parts: &mut Parts,
-        _state: &S,
-    ) -> impl std::future::Future<Output = Result<Self, Self::Rejection>> + Send {
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(locale) = user_locale(parts, state).await {
+            return Ok(Locale(locale));
+        }
+
         let locale = parts
             .headers
             .get(ACCEPT_LANGUAGE)
@@ -35,10 +37,16 @@ where
             .and_then(parse_accept_language)
             .unwrap_or_else(Locale::default_locale);
 
-        ready(Ok(Locale(locale)))
+        Ok(Locale(locale))
     }
 }
 
+// The locale the signed in user picked on their profile, which is cached.
+async fn user_locale<S: Send + Sync>(parts: &mut Parts, state: &S) -> Option<String> {
+    let jwt = Jwt::from_request_parts(parts, state).await.ok()?;
+    db::i18n::global().user_locale(&jwt.sub).await
+}
+
 fn parse_accept_language(header: &str) -> Option<String> {
     header.split(',').find_map(|entry| {
         let tag = entry.split(';').next()?.trim();
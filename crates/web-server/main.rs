@@ -1,11 +1,20 @@
+pub mod audit;
+pub mod audit_sink;
+pub mod automations;
+pub mod budget_alerts;
 pub mod config;
+pub mod cron;
 pub mod email;
+pub mod erasure;
 pub mod errors;
 pub mod handlers;
 pub mod jwt;
+pub mod key_rotation;
 pub mod layout;
 pub mod locale;
+pub mod retention;
 pub mod telemetry;
+pub mod webhook_delivery;
 
 use axum_extra::routing::RouterExt;
 pub use errors::CustomError;
@@ -41,10 +50,11 @@ async fn main() {
         .add_directive(format!("db={}", log_level).parse().unwrap())
         .add_directive(format!("agent_runtime={}", log_level).parse().unwrap())
         .add_directive(format!("tool_runtime={}", log_level).parse().unwrap())
+        .add_directive(format!("observability={}", log_level).parse().unwrap())
         // Add more of your crates as needed
         ;
 
-    tracing_subscriber::fmt().with_env_filter(filter).init();
+    let traces = observability::init("web-server", filter);
 
     // Set up panic hook with logging
     std::panic::set_hook(Box::new(|panic_info| {
@@ -75,10 +85,24 @@ async fn main() {
 
     let config = config::Config::new();
     let pool = db::create_pool(&config.app_database_url);
+
+    if std::env::args().nth(1).as_deref() == Some("rotate-keys") {
+        std::process::exit(key_rotation::run(std::env::args().skip(2)).await);
+    }
+
+    if std::env::args().nth(1).as_deref() == Some("erase-user") {
+        std::process::exit(erasure::run(&pool, std::env::args().skip(2)).await);
+    }
+
     let storage_config = object_storage::StorageConfig::database(pool.clone());
     let i18n = db::I18n::new(pool.clone());
     i18n.warm_cache().await;
     db::i18n::set_global(i18n.clone());
+    audit_sink::spawn(pool.clone());
+    webhook_delivery::spawn(pool.clone());
+    automations::spawn(pool.clone(), config.clone());
+    retention::spawn(pool.clone());
+    budget_alerts::spawn(pool.clone(), config.clone());
     let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
 
     // build our application with a route
@@ -90,7 +114,9 @@ async fn main() {
         .merge(handlers::api_keys::routes())
         .merge(handlers::audit_trail::routes())
         .merge(handlers::console::routes())
+        .merge(handlers::costs::routes())
         .merge(handlers::datasets::routes())
+        .merge(handlers::directory_sync::routes())
         .merge(handlers::documents::routes())
         .merge(handlers::history::routes())
         .merge(handlers::integrations::routes())
@@ -102,6 +128,7 @@ async fn main() {
         .merge(handlers::mcp::routes())
         .merge(handlers::models::routes())
         .merge(handlers::providers::routes())
+        .merge(handlers::guardrails::routes())
         .merge(handlers::categories::routes())
         .merge(handlers::pipelines::routes())
         .merge(handlers::profile::routes())
@@ -109,19 +136,33 @@ async fn main() {
         .merge(handlers::assistants::routes())
         .merge(handlers::my_assistants::routes())
         .merge(handlers::rate_limits::routes())
+        .merge(handlers::retention::routes())
+        .merge(handlers::roles::routes())
+        .merge(handlers::scim::routes())
         .merge(handlers::skills::routes())
         .merge(handlers::system_prompt::routes())
         .merge(handlers::team::routes())
         .merge(handlers::web_search::routes())
         .merge(handlers::teams::routes())
+        .merge(handlers::translations::routes())
+        .merge(handlers::webhooks::routes())
+        .merge(handlers::automations::routes())
         .layer(middleware::from_fn(telemetry::annotate_render_time))
+        .layer(middleware::from_fn(telemetry::trace_request))
+        .layer(middleware::from_fn(audit::client_ip))
         .layer(Extension(config.clone()))
         .layer(Extension(pool.clone()))
         .layer(Extension(storage_config.clone()));
 
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
     tracing::info!("listening on http://{}", &addr);
-    axum::serve(listener, app.into_make_service())
-        .await
-        .unwrap();
+    if traces.exporting() {
+        tracing::info!("exporting traces over OTLP");
+    }
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
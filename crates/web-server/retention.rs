@@ -0,0 +1,119 @@
+//! Enforces each team's retention policy.
+//!
+//! Every few minutes the policies that haven't run in the last hour are claimed, and
+//! anything older than they allow is deleted: stale conversations with their files,
+//! then attachments, generated files and token usage on their own. Each purge that
+//! deletes something is recorded in the team's audit trail. Expired run_bash workspace
+//! snapshots are deleted on the same schedule, whatever the policy.
+
+use std::time::Duration;
+
+use db::{queries, DuePolicy, Pool, Transaction};
+use serde_json::{Map, Value};
+
+const BATCH_SIZE: i64 = 10;
+const POLL_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Starts enforcing retention policies in the background.
+pub fn spawn(pool: Pool) {
+    tokio::spawn(run(pool));
+}
+
+async fn run(pool: Pool) {
+    loop {
+        if let Err(err) = delete_expired_workspaces(&pool).await {
+            tracing::warn!("Failed to delete expired workspaces: {err}");
+        }
+        match enforce_due(&pool).await {
+            // A full batch means there is probably more waiting.
+            Ok(enforced) if enforced as i64 == BATCH_SIZE => continue,
+            Ok(_) => {}
+            Err(err) => tracing::warn!("Failed to enforce retention policies: {err}"),
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn delete_expired_workspaces(pool: &Pool) -> Result<u64, String> {
+    let client = pool.get().await.map_err(|e| e.to_string())?;
+    queries::conversation_workspaces::delete_expired()
+        .bind(&client)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Enforces the policies that are due. Returns how many were enforced.
+async fn enforce_due(pool: &Pool) -> Result<usize, String> {
+    let mut client = pool.get().await.map_err(|e| e.to_string())?;
+
+    // Claiming commits straight away so other replicas skip these policies.
+    let transaction = client.transaction().await.map_err(|e| e.to_string())?;
+    let policies = queries::retention::claim_due_policies()
+        .bind(&transaction, &BATCH_SIZE)
+        .all()
+        .await
+        .map_err(|e| e.to_string())?;
+    transaction.commit().await.map_err(|e| e.to_string())?;
+
+    for policy in &policies {
+        let transaction = client.transaction().await.map_err(|e| e.to_string())?;
+        let deleted = purge(&transaction, policy)
+            .await
+            .map_err(|e| e.to_string())?;
+        if deleted.values().any(|count| count.as_u64() != Some(0)) {
+            queries::retention::record_purge()
+                .bind(
+                    &transaction,
+                    &policy.audit_user_id,
+                    &policy.team_id,
+                    &Value::Object(deleted),
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        transaction.commit().await.map_err(|e| e.to_string())?;
+    }
+
+    Ok(policies.len())
+}
+
+/// Deletes what the policy no longer keeps, and counts it by kind.
+async fn purge(
+    transaction: &Transaction<'_>,
+    policy: &DuePolicy,
+) -> Result<Map<String, Value>, db::TokioPostgresError> {
+    let team_id = policy.team_id;
+    let mut deleted = Map::new();
+
+    if let Some(days) = policy.conversation_days {
+        // The files first, as nothing removes them once their conversation is gone.
+        let files = queries::retention::purge_conversation_objects()
+            .bind(transaction, &team_id, &days)
+            .await?;
+        let conversations = queries::retention::purge_conversations()
+            .bind(transaction, &team_id, &days)
+            .await?;
+        deleted.insert("conversations".into(), conversations.into());
+        deleted.insert("conversation_files".into(), files.into());
+    }
+    if let Some(days) = policy.attachment_days {
+        let attachments = queries::retention::purge_attachments()
+            .bind(transaction, &team_id, &days)
+            .await?;
+        deleted.insert("attachments".into(), attachments.into());
+    }
+    if let Some(days) = policy.generated_output_days {
+        let outputs = queries::retention::purge_generated_outputs()
+            .bind(transaction, &team_id, &days)
+            .await?;
+        deleted.insert("generated_outputs".into(), outputs.into());
+    }
+    if let Some(days) = policy.token_usage_days {
+        let token_usage = queries::retention::purge_token_usage()
+            .bind(transaction, &days, &team_id)
+            .await?;
+        deleted.insert("token_usage".into(), token_usage.into());
+    }
+
+    Ok(deleted)
+}
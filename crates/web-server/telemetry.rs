@@ -1,12 +1,13 @@
 use axum::{
     body::Body,
-    extract::Request,
-    http::{header, HeaderValue},
+    extract::{MatchedPath, Request},
+    http::{header, HeaderValue, Method},
     middleware::Next,
     response::Response,
 };
 use http_body_util::BodyExt;
 use std::{convert::Infallible, time::Instant};
+use tracing::Instrument;
 
 const RENDER_TIME_HEADER: &str = "X-Render-Time";
 const HTML_CACHE_CONTROL: HeaderValue =
@@ -58,3 +59,53 @@ fn is_html_response(response: &Response) -> bool {
         .map(|value| value.starts_with("text/html"))
         .unwrap_or(false)
 }
+
+/// Runs each request in a span named after its route, continuing the caller's trace when
+/// the request has a `traceparent` header.
+pub async fn trace_request(req: Request, next: Next) -> Response {
+    let method = req.method().clone();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let span = tracing::info_span!(
+        "http.request",
+        otel.name = span_name(&method, route.as_deref()),
+        otel.kind = "server",
+        otel.status_code = tracing::field::Empty,
+        http.request.method = %method,
+        http.route = route,
+        http.response.status_code = tracing::field::Empty,
+    );
+    observability::set_parent_from(&span, req.headers());
+
+    let response = next.run(req).instrument(span.clone()).await;
+
+    span.record("http.response.status_code", response.status().as_u16());
+    if response.status().is_server_error() {
+        span.record("otel.status_code", "ERROR");
+    }
+    response
+}
+
+/// The route rather than the path, so share links and ids don't end up in span names.
+fn span_name(method: &Method, route: Option<&str>) -> String {
+    match route {
+        Some(route) => format!("{method} {route}"),
+        None => method.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_are_named_after_the_route() {
+        assert_eq!(
+            span_name(&Method::GET, Some("/shared/{token}")),
+            "GET /shared/{token}"
+        );
+        assert_eq!(span_name(&Method::POST, None), "POST");
+    }
+}
@@ -0,0 +1,212 @@
+//! Sends the queued webhook events.
+//!
+//! Every request is signed with the webhook's secret. `X-Bionic-Signature` is
+//! `t=<unix time>,v1=<hex HMAC-SHA256 of "<t>.<body>">`, so receivers can check who sent
+//! it and reject old requests. A delivery that fails is retried with exponential backoff
+//! and marked as failed after `MAX_ATTEMPTS`; it can then be replayed from the webhook's
+//! page.
+//!
+//! Only public addresses are called, see `outbound`, and the response body isn't kept as
+//! it could hold anything the URL returns.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use db::{queries, ClaimedDelivery, Pool, WebhookDeliveryStatus};
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::Sha256;
+
+const BATCH_SIZE: i64 = 10;
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+pub const MAX_ATTEMPTS: i32 = 10;
+const FIRST_RETRY_SECS: i32 = 30;
+const MAX_RETRY_SECS: i32 = 6 * 60 * 60;
+
+/// Starts sending webhook events in the background.
+pub fn spawn(pool: Pool) {
+    tokio::spawn(run(pool));
+}
+
+async fn run(pool: Pool) {
+    let http = outbound::client(REQUEST_TIMEOUT);
+
+    loop {
+        match deliver_due(&pool, &http).await {
+            // A full batch means there is probably more waiting.
+            Ok(sent) if sent as i64 == BATCH_SIZE => continue,
+            Ok(_) => {}
+            Err(err) => tracing::warn!("Failed to send webhooks: {err}"),
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Sends the deliveries that are due and records the outcome. Returns how many were sent.
+async fn deliver_due(pool: &Pool, http: &reqwest::Client) -> Result<usize, String> {
+    let mut client = pool.get().await.map_err(|e| e.to_string())?;
+
+    // Claiming commits straight away so other replicas skip these deliveries while
+    // they're being sent.
+    let transaction = client.transaction().await.map_err(|e| e.to_string())?;
+    db::customer_keys::set_local_keys(&transaction)
+        .await
+        .map_err(|e| e.to_string())?;
+    let deliveries = queries::webhooks::claim_deliveries()
+        .bind(&transaction, &BATCH_SIZE)
+        .all()
+        .await
+        .map_err(|e| e.to_string())?;
+    transaction.commit().await.map_err(|e| e.to_string())?;
+
+    for delivery in &deliveries {
+        let attempt = send(http, delivery).await;
+        let transaction = client.transaction().await.map_err(|e| e.to_string())?;
+        record(&transaction, delivery, attempt)
+            .await
+            .map_err(|e| e.to_string())?;
+        transaction.commit().await.map_err(|e| e.to_string())?;
+    }
+
+    Ok(deliveries.len())
+}
+
+#[derive(Debug, Default)]
+struct Attempt {
+    response_status: Option<i32>,
+    error: Option<String>,
+}
+
+async fn send(http: &reqwest::Client, delivery: &ClaimedDelivery) -> Attempt {
+    if let Err(error) = outbound::check_url(&delivery.url) {
+        return Attempt {
+            error: Some(error),
+            ..Attempt::default()
+        };
+    }
+
+    let body = payload(delivery);
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs() as i64)
+        .unwrap_or_default();
+
+    let response = http
+        .post(&delivery.url)
+        .header("Content-Type", "application/json")
+        .header("User-Agent", "Bionic-Webhooks")
+        .header("X-Bionic-Event", &delivery.event_type)
+        .header("X-Bionic-Delivery", delivery.event_id.to_string())
+        .header(
+            "X-Bionic-Signature",
+            signature(&delivery.secret, timestamp, &body),
+        )
+        .body(body)
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            let status = response.status();
+            Attempt {
+                response_status: Some(status.as_u16() as i32),
+                error: (!status.is_success())
+                    .then(|| format!("The endpoint responded with {status}")),
+            }
+        }
+        Err(err) => Attempt {
+            error: Some(err.to_string()),
+            ..Attempt::default()
+        },
+    }
+}
+
+async fn record(
+    transaction: &db::Transaction<'_>,
+    delivery: &ClaimedDelivery,
+    attempt: Attempt,
+) -> Result<(), db::TokioPostgresError> {
+    let Some(error) = attempt.error else {
+        queries::webhooks::delivered()
+            .bind(
+                transaction,
+                &attempt.response_status.unwrap_or_default(),
+                &delivery.id,
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let (status, retry_in_secs) = if delivery.attempts >= MAX_ATTEMPTS {
+        (WebhookDeliveryStatus::Failed, 0)
+    } else {
+        (
+            WebhookDeliveryStatus::Pending,
+            retry_delay(delivery.attempts),
+        )
+    };
+    queries::webhooks::attempt_failed()
+        .bind(
+            transaction,
+            &status,
+            &attempt.response_status,
+            &error,
+            &retry_in_secs,
+            &delivery.id,
+        )
+        .await?;
+    Ok(())
+}
+
+/// The request body: the event with the team it happened in.
+pub fn payload(delivery: &ClaimedDelivery) -> String {
+    json!({
+        "id": delivery.event_id,
+        "type": delivery.event_type,
+        "created_at": delivery.created_at,
+        "team_id": db::team_public_id::encode(delivery.team_id),
+        "data": delivery.payload,
+    })
+    .to_string()
+}
+
+pub fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(format!("{timestamp}.").as_bytes());
+    mac.update(body.as_bytes());
+    format!(
+        "t={timestamp},v1={}",
+        hex::encode(mac.finalize().into_bytes())
+    )
+}
+
+/// Seconds until the next attempt after `attempts` failed ones.
+fn retry_delay(attempts: i32) -> i32 {
+    let doublings = (attempts - 1).clamp(0, 30) as u32;
+    FIRST_RETRY_SECS
+        .saturating_mul(2i32.saturating_pow(doublings))
+        .min(MAX_RETRY_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_is_an_hmac_of_the_timestamp_and_body() {
+        // echo -n '1700000000.{"a":1}' | openssl dgst -sha256 -hmac whsec_test
+        assert_eq!(
+            signature("whsec_test", 1_700_000_000, r#"{"a":1}"#),
+            "t=1700000000,v1=38877139021993b830af32feea6e18a8da83eb2f6e49ee50bd9e4cf4ca4d3789"
+        );
+    }
+
+    #[test]
+    fn retries_back_off_exponentially() {
+        let delays: Vec<i32> = (1..=MAX_ATTEMPTS).map(retry_delay).collect();
+        assert_eq!(&delays[..4], &[30, 60, 120, 240]);
+        assert_eq!(delays.last(), Some(&(30 * 512)));
+        assert_eq!(retry_delay(100), MAX_RETRY_SECS);
+    }
+}